@@ -1,10 +1,14 @@
 //! ring-agent — in-guest companion to Ring's micro-VM runtimes.
 //!
 //! Listens on AF_VSOCK port 2375 (well-known to the host-side client) and
-//! services length-prefixed JSON requests. Today the only supported request
-//! is `Exec`, used to back `health_checks: [{ type: command, ... }]` on
-//! Cloud Hypervisor and Firecracker deployments, where Ring has no
-//! `docker exec` equivalent.
+//! services length-prefixed JSON requests, on Cloud Hypervisor and
+//! Firecracker deployments, where Ring has no `docker exec` equivalent:
+//!
+//! - `Exec` runs a command to completion and returns its output. It backs
+//!   `health_checks: [{ type: command, ... }]`.
+//! - `Session` starts an interactive process, optionally on a terminal, for
+//!   `ring deployment exec`. The connection then carries its stdio as frames
+//!   (see below) until the process exits.
 //!
 //! The same binary serves both: the guest side is plain AF_VSOCK either way.
 //! Only the host differs — Cloud Hypervisor connects over kernel AF_VSOCK,
@@ -12,18 +16,27 @@
 //! handshake before speaking this protocol.
 //!
 //! Wire format:
-//!   request:  [u32 BE length][JSON ExecRequest]
-//!   response: [u32 BE length][JSON ExecResponse]
+//!   request:  [u32 BE length][JSON Request]
+//!   response: [u32 BE length][JSON Response]            (Exec)
+//!   frames:   [u8 kind][u32 BE length][payload], both ways (Session)
+//!
+//! Session frames: the host sends `STDIN` (an empty payload closes stdin) and
+//! `RESIZE` (u16 BE cols, u16 BE rows); the agent sends `STDOUT`, `STDERR`,
+//! then one `EXIT` (i32 BE exit code) or `ERROR` (UTF-8 message) and closes.
+//! The host closing the connection kills the process.
 //!
-//! One connection per request — no multiplexing, no streaming. Health checks
-//! are short-lived and idempotent; complexity isn't worth it yet.
+//! One connection per request — no multiplexing.
 
 use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
 use std::process::Stdio;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::process::Command;
-use tokio_vsock::{VMADDR_CID_ANY, VsockAddr, VsockListener};
+use tokio::sync::mpsc;
+use tokio_vsock::{VMADDR_CID_ANY, VsockAddr, VsockListener, VsockStream};
 
 const VSOCK_PORT: u32 = 2375;
 const MAX_REQUEST_BYTES: u32 = 1 << 20; // 1 MiB cap so a malformed length can't OOM us.
@@ -31,11 +44,23 @@ const MAX_REQUEST_BYTES: u32 = 1 << 20; // 1 MiB cap so a malformed length can't
 // frames larger than 1 MiB; truncating each stream to 256 KiB keeps the
 // JSON envelope (plus exit_code etc.) under that limit with margin.
 const MAX_OUTPUT_BYTES: usize = 256 * 1024;
+// Same bound on one session frame: stdin is forwarded in small chunks, so a
+// bigger length is a broken peer.
+const MAX_FRAME_BYTES: u32 = 1 << 20;
+
+// Session frame kinds. Must match `hypervisor::vsock_client` on the host.
+const FRAME_STDIN: u8 = 0;
+const FRAME_STDOUT: u8 = 1;
+const FRAME_STDERR: u8 = 2;
+const FRAME_EXIT: u8 = 3;
+const FRAME_RESIZE: u8 = 4;
+const FRAME_ERROR: u8 = 5;
 
 #[derive(Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum Request {
     Exec(ExecRequest),
+    Session(SessionRequest),
 }
 
 #[derive(Deserialize)]
@@ -47,6 +72,19 @@ struct ExecRequest {
     timeout_ms: Option<u64>,
 }
 
+#[derive(Deserialize)]
+struct SessionRequest {
+    argv: Vec<String>,
+    #[serde(default)]
+    env: Vec<(String, String)>,
+    #[serde(default)]
+    tty: bool,
+    #[serde(default)]
+    cols: u16,
+    #[serde(default)]
+    rows: u16,
+}
+
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum Response {
@@ -103,6 +141,7 @@ async fn handle(mut stream: tokio_vsock::VsockStream) -> std::io::Result<()> {
     stream.read_exact(&mut body).await?;
 
     let response = match serde_json::from_slice::<Request>(&body) {
+        Ok(Request::Session(req)) => return run_session(stream, req).await,
         Ok(Request::Exec(req)) => match run_exec(req).await {
             Ok(r) => Response::Exec(r),
             Err(e) => Response::Error { message: e },
@@ -156,6 +195,256 @@ async fn run_exec(req: ExecRequest) -> Result<ExecResponse, String> {
     }
 }
 
+/// Serve an interactive session until the process exits or the host goes
+/// away. Output reaches the host through one writer task, so frames from
+/// stdout, stderr and the exit code never interleave mid-frame.
+async fn run_session(stream: VsockStream, req: SessionRequest) -> std::io::Result<()> {
+    let (mut reader, writer) = tokio::io::split(stream);
+    let (frames, queue) = mpsc::channel::<(u8, Vec<u8>)>(64);
+    let writer = tokio::spawn(write_frames(writer, queue));
+
+    let outcome = if req.tty {
+        session_on_pty(&mut reader, req, frames.clone()).await
+    } else {
+        session_on_pipes(&mut reader, req, frames.clone()).await
+    };
+    let last = match outcome {
+        Ok(code) => (FRAME_EXIT, code.to_be_bytes().to_vec()),
+        Err(message) => (FRAME_ERROR, message.into_bytes()),
+    };
+    let _ = frames.send(last).await;
+    drop(frames);
+    let _ = writer.await;
+    Ok(())
+}
+
+async fn write_frames<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    mut queue: mpsc::Receiver<(u8, Vec<u8>)>,
+) -> std::io::Result<()> {
+    while let Some((kind, payload)) = queue.recv().await {
+        writer.write_u8(kind).await?;
+        writer.write_u32(payload.len() as u32).await?;
+        writer.write_all(&payload).await?;
+        writer.flush().await?;
+    }
+    writer.shutdown().await
+}
+
+/// One host-to-agent frame; `None` once the host is gone.
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Option<(u8, Vec<u8>)> {
+    let kind = reader.read_u8().await.ok()?;
+    let len = reader.read_u32().await.ok()?;
+    if len > MAX_FRAME_BYTES {
+        return None;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await.ok()?;
+    Some((kind, payload))
+}
+
+fn session_command(argv: &[String], env: &[(String, String)]) -> Result<Command, String> {
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| "argv must have at least one element".to_string())?;
+    let mut cmd = Command::new(program);
+    cmd.args(args).env_clear().kill_on_drop(true);
+    for (k, v) in env {
+        cmd.env(k, v);
+    }
+    Ok(cmd)
+}
+
+async fn session_on_pipes<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    req: SessionRequest,
+    frames: mpsc::Sender<(u8, Vec<u8>)>,
+) -> Result<i32, String> {
+    let mut cmd = session_command(&req.argv, &req.env)?;
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("spawn '{}' failed: {}", req.argv[0], e))?;
+
+    let mut stdin = child.stdin.take();
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| tokio::spawn(pump(out, FRAME_STDOUT, frames.clone())));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| tokio::spawn(pump(err, FRAME_STDERR, frames.clone())));
+
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status,
+            frame = read_frame(reader) => match frame {
+                Some((FRAME_STDIN, data)) if data.is_empty() => stdin = None,
+                Some((FRAME_STDIN, data)) => {
+                    if let Some(input) = stdin.as_mut()
+                        && input.write_all(&data).await.is_err()
+                    {
+                        stdin = None;
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    let _ = child.kill().await;
+                    return Err("host closed the session".to_string());
+                }
+            },
+        }
+    };
+    for pump in [stdout, stderr].into_iter().flatten() {
+        let _ = pump.await;
+    }
+    let status = status.map_err(|e| format!("wait failed: {}", e))?;
+    Ok(status.code().unwrap_or(-1))
+}
+
+async fn pump<R: AsyncRead + Unpin>(mut out: R, kind: u8, frames: mpsc::Sender<(u8, Vec<u8>)>) {
+    let mut buf = vec![0u8; 8192];
+    while let Ok(read) = out.read(&mut buf).await {
+        if read == 0 || frames.send((kind, buf[..read].to_vec())).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn session_on_pty<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    req: SessionRequest,
+    frames: mpsc::Sender<(u8, Vec<u8>)>,
+) -> Result<i32, String> {
+    let (master, slave) = open_pty(req.cols, req.rows)?;
+    let master = Arc::new(master);
+
+    let mut cmd = session_command(&req.argv, &req.env)?;
+    let stdio = |fd: &OwnedFd| {
+        fd.try_clone()
+            .map(Stdio::from)
+            .map_err(|e| format!("dup pty failed: {}", e))
+    };
+    cmd.stdin(stdio(&slave)?)
+        .stdout(stdio(&slave)?)
+        .stderr(stdio(&slave)?);
+    // SAFETY: only async-signal-safe calls between fork and exec. The new
+    // session makes the pty the process' controlling terminal, so ^C and
+    // job control reach it.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY, 0) < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("spawn '{}' failed: {}", req.argv[0], e))?;
+    // The child holds the only slave ends now: reads on the master end with
+    // EIO once it and its descendants are gone.
+    drop(slave);
+
+    let mut output = File::from(
+        master
+            .try_clone()
+            .map_err(|e| format!("dup pty failed: {}", e))?,
+    );
+    let output = tokio::task::spawn_blocking(move || {
+        let mut buf = vec![0u8; 8192];
+        while let Ok(read) = std::io::Read::read(&mut output, &mut buf) {
+            if read == 0
+                || frames
+                    .blocking_send((FRAME_STDOUT, buf[..read].to_vec()))
+                    .is_err()
+            {
+                break;
+            }
+        }
+    });
+    let mut input = File::from(
+        master
+            .try_clone()
+            .map_err(|e| format!("dup pty failed: {}", e))?,
+    );
+
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status,
+            frame = read_frame(reader) => match frame {
+                // A terminal has no separate end of input: EOF is ^D.
+                Some((FRAME_STDIN, data)) if data.is_empty() => {
+                    let _ = std::io::Write::write_all(&mut input, &[4]);
+                }
+                Some((FRAME_STDIN, data)) => {
+                    let _ = std::io::Write::write_all(&mut input, &data);
+                }
+                Some((FRAME_RESIZE, data)) if data.len() == 4 => {
+                    let cols = u16::from_be_bytes([data[0], data[1]]);
+                    let rows = u16::from_be_bytes([data[2], data[3]]);
+                    set_window_size(&master, cols, rows);
+                }
+                Some(_) => {}
+                None => {
+                    let _ = child.kill().await;
+                    return Err("host closed the session".to_string());
+                }
+            },
+        }
+    };
+    // A background job can keep the terminal open past the process; don't
+    // wait for it forever.
+    let _ = tokio::time::timeout(Duration::from_secs(1), output).await;
+    let status = status.map_err(|e| format!("wait failed: {}", e))?;
+    Ok(status.code().unwrap_or(-1))
+}
+
+fn open_pty(cols: u16, rows: u16) -> Result<(OwnedFd, OwnedFd), String> {
+    let mut master: libc::c_int = -1;
+    let mut slave: libc::c_int = -1;
+    let size = window_size(cols, rows);
+    // SAFETY: the out-pointers are valid for the call; a null name and
+    // termios ask for the defaults.
+    let rc = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            &size,
+        )
+    };
+    if rc != 0 {
+        return Err(format!(
+            "openpty failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    // SAFETY: openpty succeeded, so both are open descriptors we now own.
+    Ok(unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) })
+}
+
+fn window_size(cols: u16, rows: u16) -> libc::winsize {
+    libc::winsize {
+        ws_row: if rows == 0 { 24 } else { rows },
+        ws_col: if cols == 0 { 80 } else { cols },
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+fn set_window_size(master: &OwnedFd, cols: u16, rows: u16) {
+    let size = window_size(cols, rows);
+    // SAFETY: `master` is an open pty and `size` outlives the call.
+    unsafe {
+        libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size);
+    }
+}
+
 /// Lossy-decode then truncate. We always trim to `MAX_OUTPUT_BYTES` *after*
 /// turning bytes into a `String` so the cut never lands inside a multi-byte
 /// UTF-8 sequence (lossy_into_owned has already replaced any invalid bytes
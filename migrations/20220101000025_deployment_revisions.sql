@@ -0,0 +1,34 @@
+-- Revision history of deployment specs, so a bad release can be undone from
+-- the server instead of digging the previous manifest out of git.
+--
+-- A redeploy creates a new `deployment` row and the previous one is marked
+-- `deleted`, then removed by the scheduler's cleanup. The spec it carried goes
+-- with it. This table keeps every accepted spec as a numbered revision.
+--
+-- Keyed by (namespace, name), not by deployment id: a revision outlives the
+-- row that was created from it, which is the whole point. No FK for the same
+-- reason. `deployment_id` only records which row the revision produced.
+--
+-- `spec` is the request body as accepted by `POST /deployments` (JSON), so a
+-- rollback replays it through the exact same create path.
+--
+-- `rollback_of` is set when the revision was produced by a rollback, and
+-- names the revision that was restored.
+--
+-- Retention is namespace-bound, like `audit_log`: the history goes away when
+-- the namespace is deleted, and each deployment keeps its most recent
+-- revisions only (pruned in application code).
+CREATE TABLE IF NOT EXISTS deployment_revision (
+    id VARCHAR(255) PRIMARY KEY,
+    namespace VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    revision INTEGER NOT NULL,
+    deployment_id VARCHAR(255) NOT NULL,
+    spec JSON NOT NULL,
+    rollback_of INTEGER DEFAULT NULL,
+    created_at DATETIME NOT NULL,
+    UNIQUE (namespace, name, revision)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deployment_revision_namespace_name
+    ON deployment_revision(namespace, name);
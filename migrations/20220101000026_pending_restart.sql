@@ -0,0 +1,13 @@
+-- Rolling restart: recreate every instance of a deployment without changing
+-- its spec (`POST /deployments/{id}/restart`), e.g. to pick up a rotated
+-- secret or config, or to shake off a leak.
+--
+-- `pending_restart` holds the ids of the instances that were running when the
+-- restart was requested (JSON array). The scheduler replaces them one at a
+-- time, only once the rest of the deployment is ready, and drops each id from
+-- the list as it goes. NULL or an empty array means no restart in progress.
+--
+-- Snapshotting the ids instead of a "restart requested at" timestamp keeps the
+-- scheduler runtime-agnostic: an instance is done as soon as its id is gone,
+-- whatever the runtime reports as its start time.
+ALTER TABLE deployment ADD COLUMN pending_restart JSON DEFAULT NULL;
@@ -0,0 +1,14 @@
+-- Pause reconciliation of a single deployment (`POST /deployments/{id}/pause`).
+--
+-- A paused deployment is skipped by the scheduler: no restart of a crashed
+-- instance, no scaling towards `replicas`, no autoscaling decision, no health
+-- check action. Its instances are left exactly as they are, which is the point:
+-- debugging a container in place, or holding a half-finished rollout, without
+-- Ring "fixing" things under the operator's feet.
+--
+-- Deletion is not held: deleting a paused deployment still tears it down.
+--
+-- A flag rather than a status on purpose: the status keeps describing what the
+-- workload is doing (`running`, `creating`, ...), and resuming picks up from
+-- there instead of having to remember what the status was before the pause.
+ALTER TABLE deployment ADD COLUMN paused BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- Rollout strategy of a deployment, from the manifest's `strategy:` block.
+--
+--   {"type": "rolling", "max_surge": 1, "max_unavailable": 0}
+--   {"type": "recreate"}
+--
+-- Stored on the incoming (child) deployment of a rollout: it is the new spec
+-- that decides how it replaces the old one. NULL means no block was declared,
+-- which behaves as `rolling` with the defaults (one extra instance at a time,
+-- never below the target count) -- the pace Ring always had.
+ALTER TABLE deployment ADD COLUMN strategy JSON DEFAULT NULL;
@@ -0,0 +1,17 @@
+-- Scheduled jobs (`kind: cronjob`).
+--
+-- `cron` holds the manifest's `cron:` block on the cronjob itself:
+--
+--   {"schedule": "0 3 * * *", "concurrency_policy": "forbid", "history_limit": 3}
+--
+-- The cronjob never runs a container. Each trigger creates a regular
+-- `kind: job` deployment, its run, whose `cronjob_id` points back at the
+-- cronjob: the runs are reconciled, logged and cleaned up like any job.
+--
+-- `last_scheduled_at` (RFC 3339) is the last trigger the scheduler acted on,
+-- persisted so a server restart neither fires a trigger twice nor replays
+-- every trigger missed while it was down.
+ALTER TABLE deployment ADD COLUMN cron JSON DEFAULT NULL;
+ALTER TABLE deployment ADD COLUMN cronjob_id TEXT DEFAULT NULL;
+ALTER TABLE deployment ADD COLUMN last_scheduled_at TEXT DEFAULT NULL;
+CREATE INDEX IF NOT EXISTS idx_deployment_cronjob_id ON deployment(cronjob_id);
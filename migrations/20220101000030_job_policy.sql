@@ -0,0 +1,12 @@
+-- Job retries, deadline, parallelism and cleanup.
+--
+-- `job` holds the manifest's `job:` block of a `kind: job` (or of a cronjob,
+-- which hands it to its runs):
+--
+--   {"backoff_limit": 3, "active_deadline": "600s", "parallelism": 2,
+--    "completions": 4, "ttl_after_finished": "3600s"}
+--
+-- `exit_code` is the exit code of the job's last finished attempt, so a CI
+-- pipeline can gate on it through `ring deployment inspect`.
+ALTER TABLE deployment ADD COLUMN job JSON DEFAULT NULL;
+ALTER TABLE deployment ADD COLUMN exit_code INTEGER DEFAULT NULL;
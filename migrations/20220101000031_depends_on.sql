@@ -0,0 +1,11 @@
+-- Deployment dependencies.
+--
+-- `depends_on` holds the manifest's `depends_on:` list, the deployments of
+-- the same namespace (by name) that must reach a condition before this one is
+-- started:
+--
+--   [{"name": "postgres", "condition": "ready"}]
+--
+-- A deployment created with dependencies starts `pending`; the scheduler
+-- holds it there until every dependency is satisfied.
+ALTER TABLE deployment ADD COLUMN depends_on JSON DEFAULT NULL;
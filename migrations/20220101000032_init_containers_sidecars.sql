@@ -0,0 +1,11 @@
+-- Init containers and sidecars.
+--
+-- Both hold a JSON list of extra containers run for every instance of the
+-- deployment (container runtimes only):
+--
+--   [{"name": "migrate", "image": "app:1.4", "command": ["./migrate"]}]
+--
+-- `init_containers` run to completion, in order, before the main container;
+-- `sidecars` run next to it in its network namespace.
+ALTER TABLE deployment ADD COLUMN init_containers JSON DEFAULT NULL;
+ALTER TABLE deployment ADD COLUMN sidecars JSON DEFAULT NULL;
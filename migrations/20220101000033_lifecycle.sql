@@ -0,0 +1,10 @@
+-- Graceful termination.
+--
+-- JSON stop policy and hooks of a deployment (the manifest's `lifecycle:`
+-- block):
+--
+--   {"stop_signal": "SIGQUIT", "stop_grace_period": "60s",
+--    "pre_stop": {"command": ["./drain"]}}
+--
+-- NULL keeps the runtime defaults: SIGTERM, then SIGKILL after 10s.
+ALTER TABLE deployment ADD COLUMN lifecycle JSON DEFAULT NULL;
@@ -0,0 +1,9 @@
+-- Per-deployment restart policy.
+--
+-- JSON retry policy of a worker (the manifest's `restart_policy:` block):
+--
+--   {"mode": "on_failure", "max_restarts": 10,
+--    "backoff": {"initial": "5s", "max": "600s"}}
+--
+-- NULL keeps the defaults: crashes restarted up to 4 times, 1s to 60s apart.
+ALTER TABLE deployment ADD COLUMN restart_policy JSON DEFAULT NULL;
@@ -0,0 +1,8 @@
+-- Scale-to-zero for rarely used workers.
+--
+-- JSON idle policy of a worker (the manifest's `idle:` block):
+--
+--   {"after": "900s", "wake_timeout": "60s"}
+--
+-- A deployment put to sleep has `desired_replicas = 0`; NULL keeps it always on.
+ALTER TABLE deployment ADD COLUMN idle JSON DEFAULT NULL;
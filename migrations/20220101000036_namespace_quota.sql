@@ -0,0 +1,11 @@
+-- Per-namespace resource quotas.
+--
+-- JSON quota of a namespace; every key is optional and an absent key is
+-- unbounded:
+--
+--   {"max_deployments": 10, "max_replicas": 20, "max_volumes": 5,
+--    "max_secrets": 50, "limits": {"cpu": "8", "memory": "16Gi"},
+--    "requests": {"cpu": "4", "memory": "8Gi"}}
+--
+-- NULL leaves the namespace unbounded, as before.
+ALTER TABLE namespace ADD COLUMN quota JSON DEFAULT NULL;
@@ -0,0 +1,13 @@
+-- Namespace-level manifest defaults.
+--
+-- JSON defaults of a namespace, merged into each deployment applied to it:
+--
+--   {"runtime": "podman", "labels": {"team": "a"},
+--    "image_pull_secret": "regcred",
+--    "resources": {"limits": {"cpu": "500m", "memory": "256Mi"}},
+--    "min": {"memory": "64Mi"}, "max": {"cpu": "2", "memory": "2Gi"}}
+--
+-- On a deployment: the fields that came from those defaults, e.g.
+-- ["runtime", "labels.team", "resources.limits.cpu"].
+ALTER TABLE namespace ADD COLUMN defaults JSON DEFAULT NULL;
+ALTER TABLE deployment ADD COLUMN defaulted JSON DEFAULT NULL;
@@ -0,0 +1,8 @@
+-- Labels on secrets, like deployments and configs carry.
+--
+-- JSON object of string labels, e.g. `ring apply` stamping its owner:
+--
+--   {"ring.owner": "payments"}
+--
+-- NULL for a secret without labels.
+ALTER TABLE secret ADD COLUMN labels JSON DEFAULT NULL;
@@ -0,0 +1,8 @@
+-- Structured log parsing and multi-line merging.
+--
+-- JSON logging options of a deployment (the manifest's `logging:` block):
+--
+--   {"format": "json", "multiline_pattern": "^\\s+at "}
+--
+-- NULL detects JSON lines and reads every other line on its own.
+ALTER TABLE deployment ADD COLUMN logging JSON DEFAULT NULL;
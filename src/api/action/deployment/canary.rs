@@ -0,0 +1,263 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::deployment::DeploymentOutput;
+use crate::api::server::Db;
+use crate::api::validation::problem_response;
+use crate::models::audit_log;
+use crate::models::deployments::{self, Deployment, DeploymentStatus, Strategy};
+use crate::scheduler::canary;
+
+/// `POST /deployments/{id}/promote` — end a canary's analysis window early and
+/// let the rollout replace the rest of the old version.
+///
+/// `{id}` is the canary, i.e. the deployment created by the apply. Answers
+/// `202 Accepted`: the drain itself happens on the next scheduler ticks.
+pub(crate) async fn promote(
+    Path(id): Path<String>,
+    auth: Auth,
+    State(pool): State<Db>,
+) -> Response {
+    let (deployment, parent_id) = match load_canary(&pool, &auth, &id).await {
+        Ok(found) => found,
+        Err(resp) => return resp,
+    };
+
+    if canary::is_promoted(&pool, &deployment).await {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "the canary was already promoted",
+        );
+    }
+
+    canary::promote(&pool, &deployment, &parent_id, "manual", "api").await;
+    record(&pool, &auth, "promote", &deployment).await;
+
+    (
+        StatusCode::ACCEPTED,
+        Json(DeploymentOutput::from_to_model(deployment)),
+    )
+        .into_response()
+}
+
+/// `POST /deployments/{id}/abort` — give up on a canary: its instances are torn
+/// down and the previous version grows back to its full count.
+///
+/// Also accepted after a promotion, as long as the rollout has not completed.
+pub(crate) async fn abort(Path(id): Path<String>, auth: Auth, State(pool): State<Db>) -> Response {
+    let (mut deployment, parent_id) = match load_canary(&pool, &auth, &id).await {
+        Ok(found) => found,
+        Err(resp) => return resp,
+    };
+
+    canary::abort(&pool, &mut deployment, &parent_id, "manual", "api").await;
+    if let Err(e) = deployments::update(&pool, &deployment).await {
+        error!("Failed to abort canary {}: {}", deployment.id, e);
+        return problem_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error",
+            "failed to abort the canary",
+        );
+    }
+    record(&pool, &auth, "abort", &deployment).await;
+
+    (
+        StatusCode::ACCEPTED,
+        Json(DeploymentOutput::from_to_model(deployment)),
+    )
+        .into_response()
+}
+
+/// Load `id` and check it is a canary still in progress. Returns the
+/// deployment and the id of the deployment it replaces.
+async fn load_canary(pool: &Db, auth: &Auth, id: &str) -> Result<(Deployment, String), Response> {
+    // Scope (`deployments:write`) is enforced centrally; the namespace
+    // boundary is checked here against the loaded deployment.
+    let deployment = match deployments::find(pool, id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return Err(problem_response(
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                "deployment not found",
+            ));
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return Err(problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to look up deployment",
+            ));
+        }
+    };
+    require_namespace(&auth.source, &deployment.namespace)?;
+
+    if !matches!(deployment.strategy, Some(Strategy::Canary { .. })) {
+        return Err(problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "deployment was not rolled out with the canary strategy",
+        ));
+    }
+
+    let Some(parent_id) = deployment.parent_id.clone() else {
+        return Err(problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "no canary rollout in progress: the deployment has no previous version to replace",
+        ));
+    };
+
+    if matches!(
+        deployment.status,
+        DeploymentStatus::Deleted | DeploymentStatus::Failed
+    ) {
+        return Err(problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            format!("the canary is already {}", deployment.status),
+        ));
+    }
+
+    Ok((deployment, parent_id))
+}
+
+async fn record(pool: &Db, auth: &Auth, action: &str, deployment: &Deployment) {
+    let _ = audit_log::record(
+        pool,
+        Some(&auth.user.id),
+        action,
+        "deployment",
+        &deployment.name,
+        Some(&deployment.namespace),
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::server::tests::{login, new_test_app_with_pool};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    fn manifest(image: &str) -> Value {
+        json!({
+            "runtime": "docker", "name": "web", "namespace": "canary", "image": image,
+            "replicas": 3,
+            "strategy": {"type": "canary", "instances": 1, "analysis_window": "600s"}
+        })
+    }
+
+    /// Apply twice, the first deployment marked running in between, so the
+    /// second one is a canary of the first. Returns (parent, canary).
+    async fn start_canary(
+        server: &TestServer,
+        pool: &sqlx::SqlitePool,
+        token: &str,
+    ) -> (String, String) {
+        let mut ids = Vec::new();
+        for image in ["nginx:1", "nginx:2"] {
+            let response = server
+                .post("/deployments")
+                .add_header("Authorization", format!("Bearer {}", token))
+                .json(&manifest(image))
+                .await;
+            assert_eq!(response.status_code(), StatusCode::CREATED);
+            let id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+            sqlx::query("UPDATE deployment SET status = 'running' WHERE id = ?")
+                .bind(&id)
+                .execute(pool)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        (ids[0].clone(), ids[1].clone())
+    }
+
+    async fn reasons(pool: &sqlx::SqlitePool, id: &str) -> Vec<String> {
+        sqlx::query_scalar(
+            "SELECT reason FROM deployment_event WHERE deployment_id = ? AND reason IS NOT NULL",
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn promote_is_recorded_once() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let (_, canary) = start_canary(&server, &pool, &token).await;
+
+        let response = server
+            .post(&format!("/deployments/{}/promote", canary))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+        assert!(
+            reasons(&pool, &canary)
+                .await
+                .contains(&"canary_promoted".to_string())
+        );
+
+        let response = server
+            .post(&format!("/deployments/{}/promote", canary))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn abort_tears_the_canary_down_and_keeps_the_parent() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let (parent, canary) = start_canary(&server, &pool, &token).await;
+
+        let response = server
+            .post(&format!("/deployments/{}/abort", canary))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::ACCEPTED);
+        assert_eq!(response.json::<Value>()["status"], "deleted");
+
+        let parent: Value = server
+            .get(&format!("/deployments/{}", parent))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await
+            .json();
+        assert_eq!(parent["status"], "running");
+
+        // Nothing left to abort.
+        let response = server
+            .post(&format!("/deployments/{}/abort", canary))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn promote_is_refused_outside_a_canary() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let (parent, _) = start_canary(&server, &pool, &token).await;
+
+        // The parent carries the strategy but replaces nothing.
+        let response = server
+            .post(&format!("/deployments/{}/promote", parent))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
+}
@@ -6,31 +6,36 @@ use axum::{
     Json,
     extract::{Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use validator::{Validate, ValidationError};
 
+use crate::api::action::deployment::diff::spec_changes;
+use crate::api::action::namespace::quota::violation as quota_violation;
 use crate::api::auth::{Auth, require_namespace, require_scope};
-use crate::api::dto::deployment::DeploymentOutput;
+use crate::api::dto::deployment::{DeploymentOutput, DeploymentPlan};
 use crate::api::server::Db;
 use crate::api::validation::{Violation, ViolationList};
 use crate::models::audit_log;
 use crate::models::deployment_event;
+use crate::models::deployment_revision;
 use crate::models::deployments;
 use crate::models::deployments::{
-    Deployment, DeploymentConfig, DeploymentPort, DeploymentStatus, EnvValue, NetworkConfig,
-    NetworkMode, Resource, default_image_pull_policy,
+    ContainerSpec, Cron, Dependency, Deployment, DeploymentConfig, DeploymentPort,
+    DeploymentStatus, EnvValue, Idle, JobSpec, Lifecycle, Logging, NetworkConfig, NetworkMode,
+    PortProtocol, Resource, ResourceSpec, RestartMode, RestartPolicy, Strategy,
+    default_image_pull_policy,
 };
-use crate::models::namespace;
+use crate::models::namespace::{self, Defaults};
 
 fn default_replicas() -> u32 {
     1
 }
 
-fn validate_runtime(runtime: &str) -> Result<(), ValidationError> {
+pub(crate) fn validate_runtime(runtime: &str) -> Result<(), ValidationError> {
     match runtime {
         "docker" | "podman" | "containerd" | "cloud-hypervisor" | "firecracker" => Ok(()),
         _ => Err(
@@ -125,7 +130,8 @@ fn validate_environment(input: &DeploymentInput, errors: &mut ViolationList) {
 /// `"2"`; `parse_memory_string` handles binary (`Ki`, `Mi`, …) and decimal
 /// (`K`, `M`, …) suffixes. Anything else used to be a silent runtime crash.
 /// Reject an autoscaling policy that cannot be satisfied (min below 1, max
-/// below min, a target outside 0-100). Rejecting at the API boundary keeps the
+/// below min, no target, a CPU target outside 0-100, a memory target without a
+/// memory request). Rejecting at the API boundary keeps the
 /// scheduler free of "what does this even mean" cases.
 fn validate_autoscale(input: &DeploymentInput, errors: &mut ViolationList) {
     let Some(policy) = &input.autoscale else {
@@ -140,13 +146,28 @@ fn validate_autoscale(input: &DeploymentInput, errors: &mut ViolationList) {
         ));
     }
 
+    // Memory is a percentage of the request: without one there is nothing to
+    // divide by (the limit may be unset too, reported as 0).
+    let requested_memory = input
+        .resources
+        .as_ref()
+        .and_then(|r| r.requests.as_ref())
+        .and_then(|r| r.memory.as_ref());
+    if policy.target_memory.is_some() && requested_memory.is_none() {
+        errors.push(Violation::new(
+            "autoscale",
+            "autoscale.target_memory is a percentage of resources.requests.memory, which is unset",
+            "deployment.autoscale.invalid",
+        ));
+    }
+
     // containerd reports `cpu_usage_percent` as a hard-coded 0 (see
     // src/runtime/containerd/stats.rs — a percentage needs two samples, and the
     // sampling loop does not exist yet). A CPU-driven controller fed a constant
     // zero reads "idle" forever and walks the deployment down to `min` whatever
     // the real load. Refusing is the honest answer; silently scaling on a fake
-    // measurement is not.
-    if input.runtime == "containerd" {
+    // measurement is not. Memory and scraped metrics are real there.
+    if input.runtime == "containerd" && policy.target_cpu.is_some() {
         errors.push(Violation::new(
             "autoscale",
             "the containerd runtime does not report CPU usage yet, so it cannot be autoscaled on a CPU target",
@@ -155,6 +176,609 @@ fn validate_autoscale(input: &DeploymentInput, errors: &mut ViolationList) {
     }
 }
 
+/// Reject a rollout strategy that cannot be carried out: a rolling update that
+/// may neither add nor remove an instance, a canary without instances or with
+/// an unparseable window, a strategy on a job (it never rolls out, it runs
+/// once), and a surge or canary on a deployment publishing a host port (the
+/// extra instance would collide with the old one on the bind).
+fn validate_strategy(input: &DeploymentInput, errors: &mut ViolationList) {
+    let Some(strategy) = &input.strategy else {
+        return;
+    };
+
+    if let Err(message) = strategy.validate() {
+        errors.push(Violation::new(
+            "strategy",
+            message,
+            "deployment.strategy.invalid",
+        ));
+    }
+
+    if let Some(kind) = one_shot_kind(&input.kind) {
+        errors.push(Violation::new(
+            "strategy",
+            format!(
+                "{}; it is never rolled out, remove the strategy block",
+                kind
+            ),
+            "deployment.strategy.job_unsupported",
+        ));
+    }
+
+    if strategy.surges() && input.ports.iter().any(|p| p.published > 0) {
+        errors.push(Violation::new(
+            "strategy",
+            "a deployment publishing a host port cannot run two versions side by side: the new instance would collide with the old one on the port. Use type: rolling with max_surge: 0, or type: recreate",
+            "deployment.strategy.host_port_conflict",
+        ));
+    }
+}
+
+/// `kind: cronjob` and the `cron:` block go together: a cronjob without a
+/// schedule would never run, a schedule on any other kind would be silently
+/// ignored. The schedule itself must parse.
+fn validate_cron(input: &DeploymentInput, errors: &mut ViolationList) {
+    match (&input.kind, &input.cron) {
+        (DeploymentKind::Cronjob, None) => errors.push(Violation::new(
+            "cron",
+            "kind=cronjob requires a cron block with a schedule",
+            "deployment.cron.required",
+        )),
+        (DeploymentKind::Cronjob, Some(cron)) => {
+            if let Err(message) = cron.validate() {
+                errors.push(Violation::new(
+                    "cron.schedule",
+                    message,
+                    "deployment.cron.schedule_invalid",
+                ));
+            }
+        }
+        (_, Some(_)) => errors.push(Violation::new(
+            "cron",
+            "the cron block only applies to kind=cronjob",
+            "deployment.cron.kind_mismatch",
+        )),
+        (_, None) => {}
+    }
+}
+
+/// The `job:` block only means something to a job (or a cronjob, which hands
+/// it to its runs), and only the container runtimes honour it: the microVM
+/// runtimes still run a job as a single attempt.
+fn validate_job(input: &DeploymentInput, errors: &mut ViolationList) {
+    let Some(job) = &input.job else {
+        return;
+    };
+
+    if one_shot_kind(&input.kind).is_none() {
+        errors.push(Violation::new(
+            "job",
+            "the job block only applies to kind=job and kind=cronjob",
+            "deployment.job.kind_mismatch",
+        ));
+    }
+
+    if let Err(message) = job.validate() {
+        errors.push(Violation::new("job", message, "deployment.job.invalid"));
+    }
+
+    if !matches!(input.runtime.as_str(), "docker" | "podman" | "containerd") {
+        errors.push(Violation::new(
+            "job",
+            format!(
+                "the job block is not supported on the {} runtime; use docker, podman or containerd",
+                input.runtime
+            ),
+            "deployment.job.runtime_unsupported",
+        ));
+    }
+}
+
+/// `init_containers` and `sidecars` are only run by the container runtimes,
+/// and their names tag container names and log lines, so they must be unique
+/// across both lists. A sidecar would keep a job's instance alive after its
+/// main container exited, so only workers take them.
+fn validate_extra_containers(input: &DeploymentInput, errors: &mut ViolationList) {
+    let mut seen = std::collections::HashSet::new();
+    for (field, containers) in [
+        ("init_containers", &input.init_containers),
+        ("sidecars", &input.sidecars),
+    ] {
+        if containers.is_empty() {
+            continue;
+        }
+        if !matches!(input.runtime.as_str(), "docker" | "podman" | "containerd") {
+            errors.push(Violation::new(
+                field,
+                format!(
+                    "{} are not supported on the {} runtime; use docker, podman or containerd",
+                    field, input.runtime
+                ),
+                format!("deployment.{}.runtime_unsupported", field),
+            ));
+        }
+        for (i, container) in containers.iter().enumerate() {
+            let path = format!("{}[{}]", field, i);
+            if let Err(message) = container.validate() {
+                errors.push(Violation::new(
+                    path,
+                    message,
+                    format!("deployment.{}.invalid", field),
+                ));
+            } else if !seen.insert(container.name.as_str()) {
+                errors.push(Violation::new(
+                    path,
+                    format!("container name '{}' is used more than once", container.name),
+                    format!("deployment.{}.invalid", field),
+                ));
+            }
+        }
+    }
+
+    if !input.sidecars.is_empty()
+        && let Some(kind) = one_shot_kind(&input.kind)
+    {
+        errors.push(Violation::new(
+            "sidecars",
+            format!("{}; sidecars only run next to a worker", kind),
+            "deployment.sidecars.kind_unsupported",
+        ));
+    }
+}
+
+/// The container runtimes honour the whole `lifecycle:` block. The microVM
+/// runtimes stop a guest through an ACPI shutdown, so only its grace period
+/// applies there: there is no signal to choose and nothing to run a hook in.
+fn validate_lifecycle(input: &DeploymentInput, errors: &mut ViolationList) {
+    let Some(lifecycle) = &input.lifecycle else {
+        return;
+    };
+
+    if let Err(message) = lifecycle.validate() {
+        errors.push(Violation::new(
+            "lifecycle",
+            message,
+            "deployment.lifecycle.invalid",
+        ));
+    }
+
+    if !matches!(input.runtime.as_str(), "docker" | "podman" | "containerd") {
+        let unsupported: Vec<&str> = [
+            ("stop_signal", lifecycle.stop_signal.is_some()),
+            ("pre_stop", lifecycle.pre_stop.is_some()),
+            ("post_start", lifecycle.post_start.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, set)| set.then_some(field))
+        .collect();
+        if !unsupported.is_empty() {
+            errors.push(Violation::new(
+                "lifecycle",
+                format!(
+                    "lifecycle.{} is not supported on the {} runtime, which only honours stop_grace_period",
+                    unsupported.join(", lifecycle."),
+                    input.runtime
+                ),
+                "deployment.lifecycle.runtime_unsupported",
+            ));
+        }
+    }
+}
+
+/// `restart_policy` is a worker's: a job retries through `job.backoff_limit`.
+/// Every runtime retries a failed start within its budget and backoff, but
+/// only Docker and Podman see an instance exit with a code, so `always` and
+/// `never` are theirs.
+fn validate_restart_policy(input: &DeploymentInput, errors: &mut ViolationList) {
+    let Some(policy) = &input.restart_policy else {
+        return;
+    };
+
+    if one_shot_kind(&input.kind).is_some() {
+        errors.push(Violation::new(
+            "restart_policy",
+            "the restart_policy block only applies to workers; a job retries through job.backoff_limit",
+            "deployment.restart_policy.kind_mismatch",
+        ));
+    }
+
+    if let Err(message) = policy.validate() {
+        errors.push(Violation::new(
+            "restart_policy",
+            message,
+            "deployment.restart_policy.invalid",
+        ));
+    }
+
+    if policy.mode != RestartMode::OnFailure
+        && !matches!(input.runtime.as_str(), "docker" | "podman")
+    {
+        errors.push(Violation::new(
+            "restart_policy",
+            format!(
+                "restart_policy.mode {} is not supported on the {} runtime; use docker or podman",
+                policy.mode, input.runtime
+            ),
+            "deployment.restart_policy.runtime_unsupported",
+        ));
+    }
+}
+
+/// `idle` puts a worker to sleep when its published ports go quiet, so it needs
+/// some, and TCP ones: Ring proxies TCP connections, and a connection is what
+/// wakes it. The autoscaler would fight it over the instance count.
+fn validate_idle(input: &DeploymentInput, errors: &mut ViolationList) {
+    let Some(idle) = &input.idle else {
+        return;
+    };
+
+    if one_shot_kind(&input.kind).is_some() {
+        errors.push(Violation::new(
+            "idle",
+            "the idle block only applies to workers; a job already stops when it is done",
+            "deployment.idle.kind_mismatch",
+        ));
+    }
+
+    if let Err(message) = idle.validate() {
+        errors.push(Violation::new("idle", message, "deployment.idle.invalid"));
+    }
+
+    if input.ports.is_empty() {
+        errors.push(Violation::new(
+            "idle",
+            "an idle deployment is woken by a connection on its published ports; declare at least one",
+            "deployment.idle.no_ports",
+        ));
+    }
+    for (idx, port) in input.ports.iter().enumerate() {
+        if port.protocol == PortProtocol::Udp {
+            errors.push(Violation::new(
+                format!("ports[{}].protocol", idx),
+                "an idle deployment only publishes tcp ports: Ring proxies them to wake it",
+                "deployment.idle.udp_unsupported",
+            ));
+        }
+    }
+
+    if input.autoscale.is_some() {
+        errors.push(Violation::new(
+            "idle",
+            "idle cannot be combined with autoscale: both would set the instance count",
+            "deployment.idle.autoscale_conflict",
+        ));
+    }
+}
+
+/// `logging` only says how the output is read: any kind and runtime can have
+/// it, as long as the format is known and the pattern compiles.
+fn validate_logging(input: &DeploymentInput, errors: &mut ViolationList) {
+    if let Some(logging) = &input.logging
+        && let Err(message) = logging.validate()
+    {
+        errors.push(Violation::new(
+            "logging",
+            message,
+            "deployment.logging.invalid",
+        ));
+    }
+}
+
+/// `depends_on` names other deployments of the namespace: each at most once,
+/// and never the deployment itself. Cycles through other deployments need the
+/// stored ones and are checked by `dependency_cycle_violation`.
+fn validate_depends_on(input: &DeploymentInput, errors: &mut ViolationList) {
+    let mut seen = std::collections::HashSet::new();
+    for (i, dependency) in input.depends_on.iter().enumerate() {
+        let path = format!("depends_on[{}].name", i);
+        if dependency.name.trim().is_empty() {
+            errors.push(Violation::new(
+                path,
+                "a dependency needs the name of a deployment",
+                "deployment.depends_on.invalid",
+            ));
+        } else if dependency.name == input.name {
+            errors.push(Violation::new(
+                path,
+                "a deployment cannot depend on itself",
+                "deployment.depends_on.invalid",
+            ));
+        } else if !seen.insert(dependency.name.as_str()) {
+            errors.push(Violation::new(
+                path,
+                format!("'{}' is listed more than once", dependency.name),
+                "deployment.depends_on.invalid",
+            ));
+        }
+    }
+}
+
+/// Refuse a `depends_on` that closes a cycle with the namespace's current
+/// deployments: none of them would ever leave `pending`.
+async fn dependency_cycle_violation(
+    pool: &Db,
+    input: &DeploymentInput,
+) -> Result<Option<Violation>, sqlx::Error> {
+    if input.depends_on.is_empty() {
+        return Ok(None);
+    }
+
+    let filters = HashMap::from([("namespace".to_string(), vec![input.namespace.clone()])]);
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    for deployment in deployments::find_all(pool, filters).await? {
+        if deployment.status == DeploymentStatus::Deleted {
+            continue;
+        }
+        edges.entry(deployment.name).or_default().extend(
+            deployment
+                .depends_on
+                .into_iter()
+                .map(|dependency| dependency.name),
+        );
+    }
+    // The applied spec replaces whatever the current version depends on.
+    edges.insert(
+        input.name.clone(),
+        input.depends_on.iter().map(|d| d.name.clone()).collect(),
+    );
+
+    Ok(
+        deployments::dependency_cycle(&input.name, &edges).map(|cycle| {
+            Violation::new(
+                "depends_on",
+                format!("dependency cycle: {}", cycle.join(" -> ")),
+                "deployment.depends_on.cycle",
+            )
+        }),
+    )
+}
+
+/// How an apply supersedes the active deployments of the same name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rollout {
+    /// Nothing of that name is running yet.
+    Create,
+    /// The new deployment rolls in while the current one drains.
+    Rolling,
+    /// The active deployments are dropped at once, for the given reason.
+    Replace(&'static str),
+}
+
+impl Rollout {
+    fn action(&self) -> &'static str {
+        match self {
+            Rollout::Create => "create",
+            Rollout::Rolling => "rolling_update",
+            Rollout::Replace(_) => "replace",
+        }
+    }
+}
+
+/// Decide between a rolling update and an immediate replace of `active`:
+/// rolling only when there is exactly one active deployment (the current
+/// one), it can roll (health checks or an explicit strategy) and `force` is
+/// not set.
+fn plan_rollout(input: &DeploymentInput, active: &[Deployment], force: bool) -> Rollout {
+    if active.is_empty() {
+        return Rollout::Create;
+    }
+
+    let has_health_checks = input
+        .health_checks
+        .as_ref()
+        .map(|hc| !hc.is_empty())
+        .unwrap_or(false);
+
+    // A published host port can be bound by only one container at a
+    // time. Rolling update creates the new container *before*
+    // draining the old one, so the new bind collides with the old
+    // ("port is already allocated") and the deployment loops in
+    // instance_creation_failed. For these deployments we must
+    // recreate (drop old, then create new) instead — a brief
+    // downtime, but deterministic and loop-free.
+    let publishes_host_port = input.ports.iter().any(|p| p.published > 0);
+
+    // An explicit strategy is the operator saying how the two
+    // versions may overlap: validation has already refused the
+    // combinations that would collide (a surge on a host port), and
+    // `recreate` / `max_surge: 0` never run both at once, so the
+    // rollout goes ahead even without health checks — the drain then
+    // waits on the runtime's "running" alone.
+    //
+    // A cronjob has no instances of its own to roll: its runs are
+    // separate jobs, so it is always replaced.
+    let is_cronjob = matches!(input.kind, DeploymentKind::Cronjob);
+    let rollout_allowed =
+        !is_cronjob && (input.strategy.is_some() || (has_health_checks && !publishes_host_port));
+
+    if !force && active.len() == 1 && rollout_allowed {
+        return Rollout::Rolling;
+    }
+
+    // Pick the most specific reason so operators can fix the root cause:
+    // `force=true` is a deliberate caller choice, the others are config gaps,
+    // and `host_port_published` is the rolling-incompatible case.
+    Rollout::Replace(if force {
+        "force"
+    } else if is_cronjob {
+        "cronjob"
+    } else if active.len() > 1 {
+        "multiple_active_deployments"
+    } else if !has_health_checks {
+        "no_health_checks"
+    } else {
+        "host_port_published"
+    })
+}
+
+/// Fill in what the manifest leaves out from the namespace's `defaults`, and
+/// return the paths filled, for `inspect`. Defaults never override a value the
+/// manifest sets. Without a runtime, from the manifest or the defaults, the
+/// deployment runs on docker.
+fn merge_defaults(input: &mut DeploymentInput, defaults: Option<&Defaults>) -> Vec<String> {
+    let mut defaulted = Vec::new();
+    if let Some(defaults) = defaults {
+        if input.runtime.is_empty()
+            && let Some(runtime) = &defaults.runtime
+        {
+            input.runtime = runtime.clone();
+            defaulted.push("runtime".to_string());
+        }
+
+        let mut keys: Vec<&String> = defaults.labels.keys().collect();
+        keys.sort();
+        for key in keys {
+            if !input.labels.contains_key(key) {
+                input
+                    .labels
+                    .insert(key.clone(), defaults.labels[key].clone());
+                defaulted.push(format!("labels.{}", key));
+            }
+        }
+
+        // Registry credentials only mean something to the container runtimes,
+        // and a deployment that brings its own keeps them.
+        let pulls_images = matches!(
+            input.runtime.as_str(),
+            "" | "docker" | "podman" | "containerd"
+        );
+        if let Some(secret) = &defaults.image_pull_secret
+            && pulls_images
+        {
+            let config = input.config.get_or_insert_with(|| DeploymentConfig {
+                image_pull_policy: default_image_pull_policy(),
+                server: None,
+                username: None,
+                password: None,
+                user: None,
+                use_host_auth: false,
+                image_pull_secret: None,
+            });
+            let has_credentials = config.image_pull_secret.is_some()
+                || config.use_host_auth
+                || config.server.is_some()
+                || config.username.is_some()
+                || config.password.is_some();
+            if !has_credentials {
+                config.image_pull_secret = Some(secret.clone());
+                defaulted.push("config.image_pull_secret".to_string());
+            }
+        }
+
+        if let Some(resources) = &defaults.resources {
+            let target = input.resources.get_or_insert_with(Resource::default);
+            for (section, from, into) in [
+                ("limits", &resources.limits, &mut target.limits),
+                ("requests", &resources.requests, &mut target.requests),
+            ] {
+                let Some(from) = from else { continue };
+                let into = into.get_or_insert_with(ResourceSpec::default);
+                if into.cpu.is_none() && from.cpu.is_some() {
+                    into.cpu = from.cpu.clone();
+                    defaulted.push(format!("resources.{}.cpu", section));
+                }
+                if into.memory.is_none() && from.memory.is_some() {
+                    into.memory = from.memory.clone();
+                    defaulted.push(format!("resources.{}.memory", section));
+                }
+            }
+        }
+    }
+    if input.runtime.is_empty() {
+        input.runtime = "docker".to_string();
+    }
+    defaulted
+}
+
+/// The namespace's `min`/`max` per-instance bounds, once its defaults are in.
+fn validate_namespace_bounds(
+    input: &DeploymentInput,
+    defaults: &Defaults,
+    errors: &mut ViolationList,
+) {
+    for out in defaults.check_bounds(input.resources.as_ref()) {
+        errors.push(Violation::new(
+            out.path,
+            format!("namespace '{}': {}", input.namespace, out.message),
+            format!("namespace.bounds.{}", out.kind),
+        ));
+    }
+}
+
+/// The caps of `quota` this deployment would break. The deployment it
+/// replaces, if any, is left out of the namespace's usage: the new one takes
+/// its place.
+///
+/// It weighs what it settles on, as the autoscaler would have it on its first
+/// tick: `inherited`, the count a rolling update carries over from its
+/// parent, within `autoscale`. The autoscaler checks every scale-up against
+/// the quota after that. Named volumes it mounts that the namespace does not hold yet count as
+/// new volumes, the scheduler registering them on the first apply.
+async fn quota_violations(
+    pool: &Db,
+    quota: &namespace::Quota,
+    input: &DeploymentInput,
+    inherited: Option<u32>,
+) -> Result<ViolationList, sqlx::Error> {
+    let usage = namespace::usage(pool, &input.namespace, Some(&input.name)).await?;
+    let replicas = match (&input.autoscale, inherited) {
+        (Some(policy), Some(desired)) => policy.clamp(desired),
+        (Some(policy), None) => policy.clamp(input.replicas),
+        (None, _) => input.replicas,
+    };
+    let footprint = namespace::Footprint::new(replicas, input.resources.as_ref());
+
+    let mut violations = ViolationList::new();
+    for breach in quota.check(&usage, &footprint) {
+        let path = match breach.field {
+            "deployments" => "namespace".to_string(),
+            "replicas" => "replicas".to_string(),
+            resource => format!("resources.{}", resource),
+        };
+        violations.push(quota_violation(&input.namespace, path, breach));
+    }
+
+    if quota.max_volumes.is_some() {
+        let mut names: Vec<&str> = input
+            .volumes
+            .iter()
+            .filter(|v| matches!(v.r#type, VolumeType::Volume))
+            .filter_map(|v| v.source.as_deref())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let mut fresh = 0;
+        for name in names {
+            let filters = HashMap::from([
+                ("namespace".to_string(), vec![input.namespace.clone()]),
+                ("name".to_string(), vec![name.to_string()]),
+            ]);
+            if crate::models::volumes::find_all(pool, filters)
+                .await?
+                .is_empty()
+            {
+                fresh += 1;
+            }
+        }
+        if fresh > 0
+            && let Some(breach) = quota.check_volumes(&usage, fresh)
+        {
+            violations.push(quota_violation(&input.namespace, "volumes", breach));
+        }
+    }
+    Ok(violations)
+}
+
+/// The kinds whose instances run to completion instead of being kept alive,
+/// described for the violations that follow from it. `None` for a worker.
+fn one_shot_kind(kind: &DeploymentKind) -> Option<&'static str> {
+    match kind {
+        DeploymentKind::Worker => None,
+        DeploymentKind::Job => Some("kind=job runs once and exits"),
+        DeploymentKind::Cronjob => Some("kind=cronjob runs once per trigger and exits"),
+    }
+}
+
 fn validate_resources(input: &DeploymentInput, errors: &mut ViolationList) {
     let Some(resources) = &input.resources else {
         return;
@@ -352,15 +976,19 @@ fn validate_cross_field_constraints(input: &DeploymentInput, errors: &mut Violat
         }
     }
 
-    // `kind: job + replicas > 1`: a job is one-shot. Multiple replicas
-    // would mean N parallel runs of the same task which is not what the
-    // job kind models.
-    if matches!(input.kind, DeploymentKind::Job) && input.replicas > 1 {
+    // `kind: job + replicas > 1`: a job is one-shot. Parallel attempts are
+    // asked for with `job.parallelism`/`job.completions`, which say how many
+    // must succeed; `replicas` cannot. The same goes for every run of a
+    // cronjob.
+    let Some(one_shot) = one_shot_kind(&input.kind) else {
+        return;
+    };
+    if input.replicas > 1 {
         errors.push(Violation::new(
             "replicas",
             format!(
-                "kind=job runs once and exits; replicas must be 1, got {}",
-                input.replicas
+                "{}; replicas must be 1, got {} (run containers side by side with job.parallelism)",
+                one_shot, input.replicas
             ),
             "deployment.replicas.job_must_be_one",
         ));
@@ -369,10 +997,10 @@ fn validate_cross_field_constraints(input: &DeploymentInput, errors: &mut Violat
     // `kind: job + autoscale`: same reasoning as the replicas guard above, and
     // there is nothing to measure anyway — a job has no steady-state CPU, it
     // runs and exits, so a controller aiming at a CPU setpoint is meaningless.
-    if matches!(input.kind, DeploymentKind::Job) && input.autoscale.is_some() {
+    if input.autoscale.is_some() {
         errors.push(Violation::new(
             "autoscale",
-            "kind=job runs once and exits; it cannot be autoscaled",
+            format!("{}; it cannot be autoscaled", one_shot),
             "deployment.autoscale.job_unsupported",
         ));
     }
@@ -380,13 +1008,15 @@ fn validate_cross_field_constraints(input: &DeploymentInput, errors: &mut Violat
     // `kind: job + readiness check`: readiness gates a rolling update.
     // Jobs don't roll — they run once. A readiness flag here is a config
     // gap that would never trigger anything useful.
-    if matches!(input.kind, DeploymentKind::Job)
-        && let Some(hcs) = input.health_checks.as_ref()
+    if let Some(hcs) = input.health_checks.as_ref()
         && hcs.iter().any(|hc| hc.is_readiness())
     {
         errors.push(Violation::new(
             "health_checks",
-            "kind=job is incompatible with readiness health checks (readiness gates rolling updates, which don't apply to one-shot jobs)",
+            format!(
+                "{}; readiness health checks gate rolling updates, which don't apply to it",
+                one_shot
+            ),
             "deployment.health_checks.job_readiness_unsupported",
         ));
     }
@@ -641,6 +1271,7 @@ pub enum DeploymentKind {
     #[default]
     Worker,
     Job,
+    Cronjob,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Validate)]
@@ -648,6 +1279,9 @@ pub(crate) struct DeploymentInput {
     #[serde(default)]
     kind: DeploymentKind,
     name: String,
+    /// Filled from the namespace's default runtime, else `docker`, when left
+    /// out.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     #[validate(custom(function = "validate_runtime"))]
     runtime: String,
     namespace: String,
@@ -674,6 +1308,26 @@ pub(crate) struct DeploymentInput {
     ports: Vec<DeploymentPort>,
     #[serde(default)]
     network: Option<NetworkConfig>,
+    #[serde(default)]
+    strategy: Option<Strategy>,
+    #[serde(default)]
+    cron: Option<Cron>,
+    #[serde(default)]
+    job: Option<JobSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<Dependency>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    init_containers: Vec<ContainerSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sidecars: Vec<ContainerSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lifecycle: Option<Lifecycle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    restart_policy: Option<RestartPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idle: Option<Idle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    logging: Option<Logging>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -685,6 +1339,9 @@ struct Message {
 pub(crate) struct CreateQueryParams {
     #[serde(default)]
     force: bool,
+    /// Validate and plan the apply, and report it, without storing anything.
+    #[serde(default)]
+    dry_run: bool,
 }
 
 pub(crate) async fn create(
@@ -693,6 +1350,58 @@ pub(crate) async fn create(
     Query(params): Query<CreateQueryParams>,
     Json(input): Json<DeploymentInput>,
 ) -> impl IntoResponse {
+    submit(&pool, &auth, input, params.force, params.dry_run, None).await
+}
+
+/// Validate `input` and turn it into a deployment: a rolling update when the
+/// current deployment allows it, an immediate replace otherwise. Every
+/// accepted spec is stored as a new revision.
+///
+/// Shared by `POST /deployments` and the rollback endpoint, so a rollback goes
+/// through exactly the same checks and rollout as a regular apply.
+/// `rollback_of` names the revision being restored, if any. A `dry_run` goes
+/// through the same checks and rollout decision, then answers with a
+/// [`DeploymentPlan`] instead of applying it.
+pub(crate) async fn submit(
+    pool: &Db,
+    auth: &Auth,
+    mut input: DeploymentInput,
+    force: bool,
+    dry_run: bool,
+    rollback_of: Option<i64>,
+) -> Response {
+    // Kept verbatim for the revision history: replaying this exact body is
+    // what a rollback does. Taken before the namespace defaults are merged
+    // in, so a rollback picks up the defaults of its day.
+    let spec = match serde_json::to_string(&input) {
+        Ok(spec) => spec,
+        Err(e) => {
+            error!("Deployment spec serialization error: {}", e);
+            let message = Message {
+                message: "Internal server error".to_string(),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
+        }
+    };
+
+    // Only read for a namespace the caller may deploy to: the bounds would
+    // otherwise leak through the violations. The 403 comes further down.
+    let defaults = if require_namespace(&auth.source, &input.namespace).is_ok() {
+        match namespace::find_by_name(pool, &input.namespace).await {
+            Ok(ns) => ns.and_then(|ns| ns.defaults),
+            Err(e) => {
+                error!("Failed to load namespace '{}': {}", input.namespace, e);
+                let message = Message {
+                    message: "Internal server error".to_string(),
+                };
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
+            }
+        }
+    } else {
+        None
+    };
+    let defaulted = merge_defaults(&mut input, defaults.as_ref());
+
     // Accumulate every validation error in one pass: a manifest that
     // violates several rules surfaces the full list in one response so
     // the user can fix everything in one apply cycle. Order:
@@ -710,8 +1419,20 @@ pub(crate) async fn create(
     validate_environment(&input, &mut violations);
     validate_resources(&input, &mut violations);
     validate_autoscale(&input, &mut violations);
+    validate_strategy(&input, &mut violations);
+    validate_cron(&input, &mut violations);
+    validate_job(&input, &mut violations);
+    validate_depends_on(&input, &mut violations);
+    validate_extra_containers(&input, &mut violations);
+    validate_lifecycle(&input, &mut violations);
+    validate_restart_policy(&input, &mut violations);
+    validate_idle(&input, &mut violations);
+    validate_logging(&input, &mut violations);
     validate_config(&input, &mut violations);
     validate_cross_field_constraints(&input, &mut violations);
+    if let Some(defaults) = &defaults {
+        validate_namespace_bounds(&input, defaults, &mut violations);
+    }
     if !violations.is_empty() {
         return violations.into_response();
     }
@@ -725,8 +1446,24 @@ pub(crate) async fn create(
         return resp;
     }
 
+    match dependency_cycle_violation(pool, &input).await {
+        Ok(None) => {}
+        Ok(Some(violation)) => {
+            violations.push(violation);
+            return violations.into_response();
+        }
+        Err(e) => {
+            error!("Failed to load the dependencies of '{}': {}", input.name, e);
+            let message = Message {
+                message: "Internal server error".to_string(),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
+        }
+    }
+
     // Auto-create namespace if it doesn't exist
-    match namespace::find_by_name(&pool, &input.namespace).await {
+    let mut creates_namespace = false;
+    let quota = match namespace::find_by_name(pool, &input.namespace).await {
         Ok(None) => {
             // Creating a namespace is an administrative act gated by
             // `namespaces:write`. Deploying into a namespace that does not exist
@@ -736,25 +1473,31 @@ pub(crate) async fn create(
             if let Err(response) = require_scope(&auth.source, "namespaces:write") {
                 return response;
             }
-
-            let new_namespace = namespace::Namespace {
-                id: Uuid::new_v4().to_string(),
-                created_at: Utc::now().to_string(),
-                updated_at: None,
-                name: input.namespace.clone(),
-            };
-            if let Err(e) = namespace::create(&pool, new_namespace).await
-                && !e.to_string().contains("UNIQUE constraint failed")
-            {
-                error!("Failed to create namespace '{}': {}", input.namespace, e);
-                let message = Message {
-                    message: "Failed to create namespace".to_string(),
+            if dry_run {
+                creates_namespace = true;
+            } else {
+                let new_namespace = namespace::Namespace {
+                    id: Uuid::new_v4().to_string(),
+                    created_at: Utc::now().to_string(),
+                    updated_at: None,
+                    name: input.namespace.clone(),
+                    quota: None,
+                    defaults: None,
                 };
-                return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
+                if let Err(e) = namespace::create(pool, new_namespace).await
+                    && !e.to_string().contains("UNIQUE constraint failed")
+                {
+                    error!("Failed to create namespace '{}': {}", input.namespace, e);
+                    let message = Message {
+                        message: "Failed to create namespace".to_string(),
+                    };
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
+                }
+                info!("Namespace '{}' created automatically", input.namespace);
             }
-            info!("Namespace '{}' created automatically", input.namespace);
+            None
         }
-        Ok(Some(_)) => {}
+        Ok(Some(ns)) => ns.quota,
         Err(e) => {
             error!("Failed to check namespace '{}': {}", input.namespace, e);
             let message = Message {
@@ -762,15 +1505,56 @@ pub(crate) async fn create(
             };
             return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
         }
-    }
+    };
 
     let active_deployments =
-        deployments::find_active_by_namespace_name(&pool, &input.namespace, &input.name).await;
+        match deployments::find_active_by_namespace_name(pool, &input.namespace, &input.name).await
+        {
+            Ok(deployments_list) => deployments_list,
+            Err(e) => {
+                error!("Database error while checking active deployments: {}", e);
+                let message = Message {
+                    message: "Internal server error".to_string(),
+                };
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
+            }
+        };
+    info!(
+        "Checking for existing deployments: namespace='{}', name='{}' - found: {}",
+        input.namespace,
+        input.name,
+        active_deployments.len()
+    );
+
+    let rollout = plan_rollout(&input, &active_deployments, force);
+    // Before anything is replaced: a refused apply leaves the current
+    // deployment running.
+    // A rolling update carries the parent's autoscaled count over.
+    let inherited = match rollout {
+        Rollout::Rolling => active_deployments[0].desired_replicas,
+        _ => None,
+    };
+    if let Some(quota) = &quota {
+        match quota_violations(pool, quota, &input, inherited).await {
+            Ok(found) if found.is_empty() => {}
+            Ok(found) => return found.into_response(),
+            Err(e) => {
+                error!(
+                    "Failed to compute the usage of namespace '{}': {}",
+                    input.namespace, e
+                );
+                let message = Message {
+                    message: "Internal server error".to_string(),
+                };
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
+            }
+        }
+    }
+
+    // Newest first: the one a dry run diffs against.
+    let current = active_deployments.first().cloned();
+    let superseded: Vec<String> = active_deployments.iter().map(|d| d.id.clone()).collect();
 
-    // Determine whether rolling update is possible:
-    // - only when there is exactly one active deployment (the current one)
-    // - it has health checks configured
-    // - --force flag is not set
     let mut rolling_parent_id: Option<String> = None;
     let mut inherited_desired_replicas: Option<u32> = None;
     // Captured to log a `ForceReplace` event on the new deployment once
@@ -779,92 +1563,62 @@ pub(crate) async fn create(
     // of having to compare timestamps across two deployments.
     let mut replaced_deployment_ids: Vec<String> = Vec::new();
     let mut replace_reason: Option<&'static str> = None;
-
-    match active_deployments {
-        Ok(deployments_list) => {
+    // Known before the replaced deployments are marked deleted, so the runs of
+    // a replaced cronjob can be handed over to the new one first.
+    let deployment_id = Uuid::new_v4().to_string();
+    let mut carried_last_scheduled_at: Option<String> = None;
+
+    match rollout {
+        Rollout::Create => {}
+        Rollout::Rolling => {
+            // Rolling update: keep old deployment running
+            let existing = &active_deployments[0];
             info!(
-                "Checking for existing deployments: namespace='{}', name='{}' - found: {}",
-                input.namespace,
-                input.name,
-                deployments_list.len()
+                "Rolling update: keeping deployment {} running as parent",
+                existing.id
             );
-
-            if !deployments_list.is_empty() {
-                info!(
-                    "Found {} active deployments with the same namespace and name",
-                    deployments_list.len()
-                );
-
-                let has_health_checks = input
-                    .health_checks
-                    .as_ref()
-                    .map(|hc| !hc.is_empty())
-                    .unwrap_or(false);
-
-                // A published host port can be bound by only one container at a
-                // time. Rolling update creates the new container *before*
-                // draining the old one, so the new bind collides with the old
-                // ("port is already allocated") and the deployment loops in
-                // instance_creation_failed. For these deployments we must
-                // recreate (drop old, then create new) instead — a brief
-                // downtime, but deterministic and loop-free.
-                let publishes_host_port = input.ports.iter().any(|p| p.published > 0);
-
-                // Rolling update: keep old deployment running if conditions are met
-                if !params.force
-                    && has_health_checks
-                    && deployments_list.len() == 1
-                    && !publishes_host_port
-                {
-                    let existing = &deployments_list[0];
-                    info!(
-                        "Rolling update: keeping deployment {} running as parent",
-                        existing.id
-                    );
-                    rolling_parent_id = Some(existing.id.clone());
-                    // Carry the parent's scaled-up capacity into the child.
-                    // Without this a redeploy silently drops an autoscaled
-                    // deployment back to the manifest count — a service running
-                    // 8 instances under load would restart at 2 and have to
-                    // climb again, one cooldown at a time, exactly when it is
-                    // least able to afford it.
-                    inherited_desired_replicas = existing.desired_replicas;
-                } else {
-                    // Immediate replace. Pick the most specific reason so
-                    // operators can fix the root cause: `force=true` is a
-                    // deliberate caller choice, the others are config gaps,
-                    // and `host_port_published` is the rolling-incompatible case.
-                    replace_reason = Some(if params.force {
-                        "force"
-                    } else if !has_health_checks {
-                        "no_health_checks"
-                    } else if deployments_list.len() > 1 {
-                        "multiple_active_deployments"
-                    } else {
-                        "host_port_published"
-                    });
-                    for mut deployment in deployments_list {
-                        info!("Marking deployment {} as deleted", deployment.id);
-                        replaced_deployment_ids.push(deployment.id.clone());
-                        deployment.status = DeploymentStatus::Deleted;
-                        deployment.updated_at = Some(Utc::now().to_string());
-                        if let Err(e) = deployments::update(&pool, &deployment).await {
-                            error!(
-                                "Failed to mark deployment {} as deleted: {}",
-                                deployment.id, e
-                            );
-                        }
+            rolling_parent_id = Some(existing.id.clone());
+            // Carry the parent's scaled-up capacity into the child.
+            // Without this a redeploy silently drops an autoscaled
+            // deployment back to the manifest count — a service running
+            // 8 instances under load would restart at 2 and have to
+            // climb again, one cooldown at a time, exactly when it is
+            // least able to afford it.
+            inherited_desired_replicas = existing.desired_replicas;
+        }
+        Rollout::Replace(reason) => {
+            replace_reason = Some(reason);
+            for mut deployment in active_deployments {
+                if dry_run {
+                    break;
+                }
+                // Re-applying a cronjob must neither lose its run history
+                // nor fire again for a trigger it already acted on.
+                if deployment.kind == "cronjob" {
+                    if let Err(e) =
+                        deployments::adopt_runs(pool, &deployment.id, &deployment_id).await
+                    {
+                        error!(
+                            "Failed to move the runs of cronjob {} over: {}",
+                            deployment.id, e
+                        );
+                    }
+                    if carried_last_scheduled_at < deployment.last_scheduled_at {
+                        carried_last_scheduled_at = deployment.last_scheduled_at.clone();
                     }
                 }
+                info!("Marking deployment {} as deleted", deployment.id);
+                replaced_deployment_ids.push(deployment.id.clone());
+                deployment.status = DeploymentStatus::Deleted;
+                deployment.updated_at = Some(Utc::now().to_string());
+                if let Err(e) = deployments::update(pool, &deployment).await {
+                    error!(
+                        "Failed to mark deployment {} as deleted: {}",
+                        deployment.id, e
+                    );
+                }
             }
         }
-        Err(e) => {
-            error!("Database error while checking active deployments: {}", e);
-            let message = Message {
-                message: "Internal server error".to_string(),
-            };
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(message)).into_response();
-        }
     }
 
     let utc: DateTime<Utc> = Utc::now();
@@ -881,17 +1635,23 @@ pub(crate) async fn create(
     };
 
     let deployment = deployments::Deployment {
-        id: Uuid::new_v4().to_string(),
+        id: deployment_id,
         name: input.name.clone(),
         runtime: input.runtime.clone(),
         namespace: input.namespace.clone(),
         kind: match input.kind {
             DeploymentKind::Worker => "worker".to_string(),
             DeploymentKind::Job => "job".to_string(),
+            DeploymentKind::Cronjob => "cronjob".to_string(),
         },
         image: input.image.clone(),
         config: input.config.clone(),
-        status: DeploymentStatus::Creating,
+        // Held back by the scheduler until its dependencies are satisfied.
+        status: if input.depends_on.is_empty() {
+            DeploymentStatus::Creating
+        } else {
+            DeploymentStatus::Pending
+        },
         created_at: utc.to_string(),
         updated_at: None,
         labels: input.labels,
@@ -915,12 +1675,43 @@ pub(crate) async fn create(
         pending_events: vec![],
         parent_id: rolling_parent_id,
         network: input.network.clone(),
+        defaulted: defaulted.clone(),
+        idle: input.idle.clone(),
+        logging: input.logging.clone(),
+        restart_policy: input.restart_policy.clone(),
+        lifecycle: input.lifecycle.clone(),
+        init_containers: input.init_containers.clone(),
+        sidecars: input.sidecars.clone(),
+        depends_on: input.depends_on.clone(),
+        job: input.job.clone(),
+        exit_code: None,
+        cron: input.cron.clone(),
+        cronjob_id: None,
+        last_scheduled_at: carried_last_scheduled_at,
+        strategy: input.strategy.clone(),
+        rollout_limit: None,
+        restart_surge: false,
+        paused: false,
+        pending_restart: vec![],
     };
 
-    match deployments::create(&pool, &deployment).await {
+    if dry_run {
+        let plan = DeploymentPlan {
+            namespace: deployment.namespace.clone(),
+            name: deployment.name.clone(),
+            action: rollout.action().to_string(),
+            reason: replace_reason.map(str::to_string),
+            replaces: superseded,
+            creates_namespace,
+            changes: spec_changes(current.as_ref(), &deployment),
+        };
+        return (StatusCode::OK, Json(plan)).into_response();
+    }
+
+    match deployments::create(pool, &deployment).await {
         Ok(deployment) => {
             let _ = deployment_event::log_event(
-                &pool,
+                pool,
                 deployment.id.clone(),
                 "info",
                 format!("Deployment '{}' created successfully", deployment.name),
@@ -938,7 +1729,7 @@ pub(crate) async fn create(
                 let ignored = cloud_hypervisor_ignored_fields(&deployment);
                 if !ignored.is_empty() {
                     let _ = deployment_event::log_event(
-                        &pool,
+                        pool,
                         deployment.id.clone(),
                         "warning",
                         format!(
@@ -985,10 +1776,14 @@ pub(crate) async fn create(
                         "Replaced {} immediately because it publishes a host port — rolling update would collide on the port (it creates the new container before stopping the old), so Ring recreated it instead (brief downtime)",
                         replaced
                     ),
+                    "cronjob" => format!(
+                        "Replaced {} immediately: a cronjob has no instances to roll, its runs and last trigger carry over",
+                        replaced
+                    ),
                     other => format!("Replaced {} immediately ({})", replaced, other),
                 };
                 let _ = deployment_event::log_event(
-                    &pool,
+                    pool,
                     deployment.id.clone(),
                     "warning",
                     message,
@@ -998,10 +1793,54 @@ pub(crate) async fn create(
                 .await;
             }
 
+            // A failed write loses one history entry, not the deployment: the
+            // spec is already live, so report it and carry on.
+            match deployment_revision::record(
+                pool,
+                &deployment.namespace,
+                &deployment.name,
+                &deployment.id,
+                &spec,
+                rollback_of,
+            )
+            .await
+            {
+                Ok(revision) => {
+                    let message = match rollback_of {
+                        Some(restored) => format!(
+                            "Rolled back to revision {} (recorded as revision {})",
+                            restored, revision.revision
+                        ),
+                        None => format!("Recorded as revision {}", revision.revision),
+                    };
+                    let _ = deployment_event::log_event(
+                        pool,
+                        deployment.id.clone(),
+                        "info",
+                        message,
+                        "api",
+                        Some(if rollback_of.is_some() {
+                            "rollback"
+                        } else {
+                            "revision_recorded"
+                        }),
+                    )
+                    .await;
+                }
+                Err(e) => error!(
+                    "Failed to record a revision for deployment {}: {}",
+                    deployment.id, e
+                ),
+            }
+
             let _ = audit_log::record(
-                &pool,
+                pool,
                 Some(&auth.user.id),
-                "create",
+                if rollback_of.is_some() {
+                    "rollback"
+                } else {
+                    "create"
+                },
                 "deployment",
                 &input.name,
                 Some(&input.namespace),
@@ -1027,6 +1866,7 @@ pub(crate) async fn create(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::dto::deployment::FieldChange;
     use crate::api::dto::namespace::NamespaceOutput;
     use crate::api::server::tests::{login, new_test_app, new_test_app_with_pool};
     use axum_test::{TestResponse, TestServer};
@@ -3659,4 +4499,990 @@ mod tests {
             codes
         );
     }
+
+    async fn violation_codes(
+        server: &TestServer,
+        token: &str,
+        body: serde_json::Value,
+    ) -> Vec<String> {
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = response.json();
+        body["violations"]
+            .as_array()
+            .unwrap_or(&vec![])
+            .iter()
+            .map(|v| v["code"].as_str().unwrap_or("").to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn create_rejects_an_unworkable_strategy() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            json!({
+                "runtime": "docker", "name": "web", "namespace": "strategy", "image": "nginx",
+                "strategy": {"type": "rolling", "max_surge": 0, "max_unavailable": 0}
+            }),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.strategy.invalid"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            json!({
+                "runtime": "docker", "name": "migrate", "namespace": "strategy", "image": "busybox",
+                "kind": "job", "strategy": {"type": "recreate"}
+            }),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.strategy.job_unsupported"]);
+
+        // The default surge of 1 would collide on the host port.
+        let codes = violation_codes(
+            &server,
+            &token,
+            json!({
+                "runtime": "docker", "name": "edge", "namespace": "strategy", "image": "nginx",
+                "ports": [{"published": 8080, "target": 80}],
+                "strategy": {"type": "rolling"}
+            }),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.strategy.host_port_conflict"]);
+    }
+
+    #[tokio::test]
+    async fn explicit_strategy_rolls_out_without_health_checks() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        // A host port with recreate: no health check and a published port,
+        // both of which used to force an immediate replace.
+        let manifest = |image: &str| {
+            json!({
+                "runtime": "docker", "name": "lock-holder", "namespace": "strategy", "image": image,
+                "ports": [{"published": 8080, "target": 80}],
+                "strategy": {"type": "recreate"}
+            })
+        };
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&manifest("nginx:1.0"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let first_id = response.json::<serde_json::Value>()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        sqlx::query("UPDATE deployment SET status = 'running' WHERE id = ?")
+            .bind(&first_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&manifest("nginx:2.0"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let child: serde_json::Value = response.json();
+        assert_eq!(child["parent_id"], first_id.as_str());
+        assert_eq!(child["strategy"]["type"], "recreate");
+
+        let parent: serde_json::Value = server
+            .get(&format!("/deployments/{}", first_id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await
+            .json();
+        assert_eq!(parent["status"], "running");
+    }
+
+    #[tokio::test]
+    async fn create_checks_the_cron_block_against_the_kind() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let cronjob = |extra: serde_json::Value| {
+            let mut body = json!({
+                "runtime": "docker", "name": "nightly", "namespace": "cron", "image": "busybox",
+                "kind": "cronjob"
+            });
+            body.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            body
+        };
+
+        let codes = violation_codes(&server, &token, cronjob(json!({}))).await;
+        assert_eq!(codes, vec!["deployment.cron.required"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            cronjob(json!({"cron": {"schedule": "every night"}})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.cron.schedule_invalid"]);
+
+        // The job rules hold for every run.
+        let codes = violation_codes(
+            &server,
+            &token,
+            cronjob(json!({"cron": {"schedule": "0 3 * * *"}, "replicas": 2})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.replicas.job_must_be_one"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            json!({
+                "runtime": "docker", "name": "web", "namespace": "cron", "image": "nginx",
+                "cron": {"schedule": "0 3 * * *"}
+            }),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.cron.kind_mismatch"]);
+    }
+
+    #[tokio::test]
+    async fn create_checks_the_job_block() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let job = |runtime: &str, kind: &str, block: serde_json::Value| {
+            json!({
+                "runtime": runtime, "name": "reindex", "namespace": "batch", "image": "busybox",
+                "kind": kind, "job": block
+            })
+        };
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            job("docker", "job", json!({"parallelism": 0})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.job.invalid"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            job("docker", "job", json!({"active_deadline": "10 minutes"})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.job.invalid"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            job("docker", "worker", json!({"backoff_limit": 3})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.job.kind_mismatch"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            job("firecracker", "job", json!({"backoff_limit": 3})),
+        )
+        .await;
+        assert!(codes.contains(&"deployment.job.runtime_unsupported".to_string()));
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&job(
+                "containerd",
+                "job",
+                json!({"backoff_limit": 3, "completions": 4, "parallelism": 2, "ttl_after_finished": "3600s"}),
+            ))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        assert_eq!(
+            body["job"],
+            json!({"backoff_limit": 3, "parallelism": 2, "completions": 4, "ttl_after_finished": "3600s"})
+        );
+        assert!(body.get("exit_code").is_none());
+    }
+
+    #[tokio::test]
+    async fn create_checks_init_containers_and_sidecars() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let body =
+            |runtime: &str, kind: &str, init: serde_json::Value, sidecars: serde_json::Value| {
+                json!({
+                    "runtime": runtime, "name": "api", "namespace": "prod", "image": "api:1.4",
+                    "kind": kind, "init_containers": init, "sidecars": sidecars
+                })
+            };
+        let proxy = json!([{"name": "proxy", "image": "cloud-sql-proxy:2"}]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            body(
+                "docker",
+                "worker",
+                json!([{"name": "Migrate", "image": "api:1.4"}]),
+                json!([]),
+            ),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.init_containers.invalid"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            body(
+                "docker",
+                "worker",
+                json!([{"name": "proxy", "image": "busybox"}]),
+                proxy.clone(),
+            ),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.sidecars.invalid"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            body("docker", "job", json!([]), proxy.clone()),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.sidecars.kind_unsupported"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            body("firecracker", "worker", json!([]), proxy.clone()),
+        )
+        .await;
+        assert!(codes.contains(&"deployment.sidecars.runtime_unsupported".to_string()));
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body(
+                "containerd",
+                "worker",
+                json!([{"name": "migrate", "image": "api:1.4", "command": ["./migrate"]}]),
+                proxy.clone(),
+            ))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(
+            created["init_containers"][0]["command"],
+            json!(["./migrate"])
+        );
+        assert_eq!(created["sidecars"], proxy);
+    }
+
+    #[tokio::test]
+    async fn create_checks_the_lifecycle_block() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let body = |runtime: &str, lifecycle: serde_json::Value| {
+            json!({
+                "runtime": runtime, "name": "worker", "namespace": "prod",
+                "image": "worker:2.0", "lifecycle": lifecycle
+            })
+        };
+
+        for lifecycle in [
+            json!({"stop_signal": "SIGNOPE"}),
+            json!({"stop_grace_period": "1m"}),
+            json!({"pre_stop": {"command": []}}),
+            json!({"pre_stop": {"http": {"port": 8080, "path": "drain"}}}),
+            json!({"post_start": {"http": {"port": 8080}}}),
+        ] {
+            let codes = violation_codes(&server, &token, body("docker", lifecycle.clone())).await;
+            assert_eq!(codes, vec!["deployment.lifecycle.invalid"], "{}", lifecycle);
+        }
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            body(
+                "firecracker",
+                json!({"stop_signal": "SIGQUIT", "stop_grace_period": "30s"}),
+            ),
+        )
+        .await;
+        assert!(codes.contains(&"deployment.lifecycle.runtime_unsupported".to_string()));
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("firecracker", json!({"stop_grace_period": "30s"})))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let lifecycle = json!({
+            "stop_signal": "SIGQUIT",
+            "stop_grace_period": "60s",
+            "pre_stop": {"http": {"port": 8080, "path": "/drain"}},
+            "post_start": {"command": ["./warm-cache"]}
+        });
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("docker", lifecycle.clone()))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(created["lifecycle"], lifecycle);
+    }
+
+    #[tokio::test]
+    async fn create_checks_the_autoscale_targets() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let body = |runtime: &str, autoscale: serde_json::Value| {
+            json!({
+                "runtime": runtime, "name": "consumer", "namespace": "prod",
+                "image": "consumer:2.0", "autoscale": autoscale
+            })
+        };
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            body("docker", json!({"min": 1, "max": 4, "target_memory": 80})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.autoscale.invalid"]);
+        let codes = violation_codes(
+            &server,
+            &token,
+            body("containerd", json!({"min": 1, "max": 4, "target_cpu": 70})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.autoscale.runtime_unsupported"]);
+
+        let autoscale = json!({
+            "min": 1, "max": 8,
+            "metrics": [{
+                "name": "queue_backlog", "labels": {"queue": "orders"},
+                "port": 9100, "path": "/metrics", "target": 50.0, "scope": "deployment"
+            }],
+            "cooldown_up": "30s", "cooldown_down": "600s", "tolerance": 0.2
+        });
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("containerd", autoscale.clone()))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(created["autoscale"], autoscale);
+    }
+
+    #[tokio::test]
+    async fn create_checks_the_restart_policy() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let body = |runtime: &str, kind: &str, policy: serde_json::Value| {
+            json!({
+                "runtime": runtime, "kind": kind, "name": "worker", "namespace": "prod",
+                "image": "worker:2.0", "restart_policy": policy
+            })
+        };
+
+        for policy in [
+            json!({"mode": "never", "max_restarts": 3}),
+            json!({"backoff": {"initial": "1m"}}),
+            json!({"backoff": {"initial": "0s"}}),
+            json!({"backoff": {"initial": "600s", "max": "60s"}}),
+        ] {
+            let codes =
+                violation_codes(&server, &token, body("docker", "worker", policy.clone())).await;
+            assert_eq!(
+                codes,
+                vec!["deployment.restart_policy.invalid"],
+                "{}",
+                policy
+            );
+        }
+
+        let codes = violation_codes(&server, &token, body("docker", "job", json!({}))).await;
+        assert_eq!(codes, vec!["deployment.restart_policy.kind_mismatch"]);
+        let codes = violation_codes(
+            &server,
+            &token,
+            body("containerd", "worker", json!({"mode": "always"})),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.restart_policy.runtime_unsupported"]);
+
+        let policy = json!({"mode": "always", "backoff": {"initial": "5s", "max": "600s"}});
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("docker", "worker", policy.clone()))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(created["restart_policy"], policy);
+    }
+
+    #[tokio::test]
+    async fn create_checks_the_idle_block() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let body = |ports: serde_json::Value, idle: serde_json::Value| {
+            json!({
+                "runtime": "docker", "name": "wiki", "namespace": "tools",
+                "image": "wiki:1.0", "ports": ports, "idle": idle
+            })
+        };
+        let tcp = json!([{"published": 8080, "target": 80}]);
+
+        let codes =
+            violation_codes(&server, &token, body(json!([]), json!({"after": "900s"}))).await;
+        assert_eq!(codes, vec!["deployment.idle.no_ports"]);
+        let codes =
+            violation_codes(&server, &token, body(tcp.clone(), json!({"after": "15m"}))).await;
+        assert_eq!(codes, vec!["deployment.idle.invalid"]);
+        let codes = violation_codes(
+            &server,
+            &token,
+            body(
+                json!([{"published": 5353, "target": 53, "protocol": "udp"}]),
+                json!({"after": "900s"}),
+            ),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.idle.udp_unsupported"]);
+        let mut autoscaled = body(tcp.clone(), json!({"after": "900s"}));
+        autoscaled["autoscale"] = json!({"min": 1, "max": 1, "target_cpu": 70});
+        let codes = violation_codes(&server, &token, autoscaled).await;
+        assert_eq!(codes, vec!["deployment.idle.autoscale_conflict"]);
+
+        let idle = json!({"after": "900s", "wake_timeout": "30s"});
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body(tcp, idle.clone()))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(created["idle"], idle);
+        assert_eq!(created["asleep"], false);
+    }
+
+    #[tokio::test]
+    async fn create_checks_the_logging_block() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let body = |logging: serde_json::Value| {
+            json!({
+                "runtime": "docker", "name": "api", "namespace": "tools",
+                "image": "api:1.0", "logging": logging
+            })
+        };
+
+        for logging in [
+            json!({"format": "xml"}),
+            json!({"multiline_pattern": "^(\\s+at "}),
+        ] {
+            let codes = violation_codes(&server, &token, body(logging.clone())).await;
+            assert_eq!(codes, vec!["deployment.logging.invalid"], "{}", logging);
+        }
+
+        let logging = json!({"format": "json", "multiline_pattern": "^\\s+at |^Caused by:"});
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body(logging.clone()))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(created["logging"], logging);
+    }
+
+    #[tokio::test]
+    async fn create_orders_deployments_with_depends_on() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let worker = |name: &str, depends_on: serde_json::Value| {
+            json!({
+                "runtime": "docker", "name": name, "namespace": "stack", "image": "busybox",
+                "depends_on": depends_on
+            })
+        };
+
+        let codes = violation_codes(&server, &token, worker("api", json!([{"name": "api"}]))).await;
+        assert_eq!(codes, vec!["deployment.depends_on.invalid"]);
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            worker(
+                "api",
+                json!([{"name": "db"}, {"name": "db", "condition": "ready"}]),
+            ),
+        )
+        .await;
+        assert_eq!(codes, vec!["deployment.depends_on.invalid"]);
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&worker(
+                "api",
+                json!([{"name": "db", "condition": "ready"}]),
+            ))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["status"], "pending");
+        assert_eq!(
+            body["depends_on"],
+            json!([{"name": "db", "condition": "ready"}])
+        );
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&worker("db", json!([{"name": "api"}])))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = response.json();
+        assert!(body.to_string().contains("deployment.depends_on.cycle"));
+        assert!(body.to_string().contains("db -> api -> db"));
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&worker("db", json!([])))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["status"], "creating");
+    }
+
+    #[tokio::test]
+    async fn reapplying_a_cronjob_keeps_its_runs_and_last_trigger() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let manifest = |image: &str| {
+            json!({
+                "runtime": "docker", "name": "nightly", "namespace": "cron", "image": image,
+                "kind": "cronjob", "cron": {"schedule": "0 3 * * *"},
+                // Would roll a worker out; a cronjob is replaced regardless.
+                "health_checks": [{"type": "tcp", "port": 80, "interval": "5s", "timeout": "1s", "on_failure": "restart"}]
+            })
+        };
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&manifest("reports:1"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let first_id = response.json::<serde_json::Value>()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let mut first = deployments::find(&pool, &first_id).await.unwrap().unwrap();
+        let tomorrow = Utc::now() + chrono::Duration::days(1);
+        crate::scheduler::cronjob::reconcile(&pool, &mut first, tomorrow).await;
+        let last_trigger = first.last_scheduled_at.clone();
+        assert!(last_trigger.is_some());
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&manifest("reports:2"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let second: serde_json::Value = response.json();
+        assert!(second["parent_id"].is_null());
+        assert_eq!(
+            second["last_scheduled_at"].as_str(),
+            last_trigger.as_deref()
+        );
+
+        let second_id = second["id"].as_str().unwrap();
+        assert_eq!(
+            deployments::find_runs(&pool, second_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let first = deployments::find(&pool, &first_id).await.unwrap().unwrap();
+        assert_eq!(first.status, DeploymentStatus::Deleted);
+    }
+
+    #[tokio::test]
+    async fn create_enforces_the_namespace_quota() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "name": "team-a",
+                "quota": {
+                    "max_deployments": 1,
+                    "max_replicas": 3,
+                    "max_volumes": 0,
+                    "limits": {"memory": "1Gi"}
+                }
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let body = |name: &str, replicas: u32| {
+            json!({
+                "runtime": "docker",
+                "namespace": "team-a",
+                "name": name,
+                "image": "nginx:latest",
+                "replicas": replicas,
+                "resources": {"limits": {"memory": "256Mi"}}
+            })
+        };
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("api", 2))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        // Re-applying the same deployment replaces it: its own share does
+        // not count twice.
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("api", 3))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let codes = violation_codes(&server, &token, body("api", 5)).await;
+        assert_eq!(
+            codes,
+            vec!["namespace.quota.replicas", "namespace.quota.limits.memory"]
+        );
+        let codes = violation_codes(&server, &token, body("worker", 0)).await;
+        assert_eq!(codes, vec!["namespace.quota.deployments"]);
+
+        let mut undeclared = body("api", 1);
+        undeclared["resources"] = json!({"limits": {"cpu": "1"}});
+        let codes = violation_codes(&server, &token, undeclared).await;
+        assert_eq!(codes, vec!["namespace.quota.limits.memory"]);
+
+        let mut with_volume = body("api", 1);
+        with_volume["volumes"] = json!([{
+            "type": "volume", "source": "data", "destination": "/data",
+            "driver": "local", "permission": "ro"
+        }]);
+        let codes = violation_codes(&server, &token, with_volume).await;
+        assert_eq!(codes, vec!["namespace.quota.volumes"]);
+    }
+
+    /// A rolling update starts the child at the parent's autoscaled count,
+    /// not at the manifest's `replicas`: that is what the quota weighs.
+    #[tokio::test]
+    async fn a_rolling_update_is_weighed_at_the_count_it_inherits() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"name": "team-a", "quota": {"max_replicas": 3}}))
+            .await;
+        let body = |image: &str| {
+            json!({
+                "runtime": "docker",
+                "namespace": "team-a",
+                "name": "api",
+                "image": image,
+                "autoscale": {"min": 1, "max": 6, "target_cpu": 70},
+                "health_checks": [{"type": "tcp", "port": 80, "interval": "10s", "timeout": "5s", "on_failure": "restart"}]
+            })
+        };
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("nginx:1.0"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        let id = created["id"].as_str().unwrap();
+        sqlx::query("UPDATE deployment SET status = 'running' WHERE id = ?")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        deployments::set_desired_replicas(&pool, id, 4)
+            .await
+            .unwrap();
+
+        let codes = violation_codes(&server, &token, body("nginx:2.0")).await;
+        assert_eq!(codes, vec!["namespace.quota.replicas"]);
+    }
+
+    /// A sleeping deployment is woken by a connection, without asking the
+    /// quota: it keeps the share it runs at once awake.
+    #[tokio::test]
+    async fn an_idle_deployment_keeps_its_share_of_the_quota_while_asleep() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"name": "team-a", "quota": {"max_replicas": 1}}))
+            .await;
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "runtime": "docker",
+                "namespace": "team-a",
+                "name": "api",
+                "image": "nginx:latest",
+                "ports": [{"published": 8080, "target": 80}],
+                "idle": {"after": "900s"}
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        deployments::set_desired_replicas(&pool, created["id"].as_str().unwrap(), 0)
+            .await
+            .unwrap();
+
+        let codes = violation_codes(
+            &server,
+            &token,
+            json!({
+                "runtime": "docker",
+                "namespace": "team-a",
+                "name": "worker",
+                "image": "nginx:latest"
+            }),
+        )
+        .await;
+        assert_eq!(codes, vec!["namespace.quota.replicas"]);
+    }
+
+    #[tokio::test]
+    async fn create_fills_in_and_bounds_by_the_namespace_defaults() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "name": "team-a",
+                "defaults": {
+                    "runtime": "podman",
+                    "labels": {"team": "a", "tier": "web"},
+                    "image_pull_secret": "registry",
+                    "resources": {"limits": {"cpu": "500m", "memory": "256Mi"}},
+                    "max": {"cpu": "1", "memory": "1Gi"}
+                }
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "namespace": "team-a",
+                "name": "api",
+                "image": "nginx:latest",
+                "labels": {"tier": "api"},
+                "resources": {"limits": {"memory": "512Mi"}}
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(created["runtime"], "podman");
+        assert_eq!(created["labels"]["team"], "a");
+        assert_eq!(created["labels"]["tier"], "api");
+        assert_eq!(created["config"]["image_pull_secret"], "registry");
+        assert_eq!(created["resources"]["limits"]["cpu"], "500m");
+        assert_eq!(created["resources"]["limits"]["memory"], "512Mi");
+        assert_eq!(
+            created["defaulted"],
+            json!([
+                "runtime",
+                "labels.team",
+                "config.image_pull_secret",
+                "resources.limits.cpu"
+            ])
+        );
+
+        // Above the namespace maximum, whether asked for or defaulted.
+        let codes = violation_codes(
+            &server,
+            &token,
+            json!({
+                "namespace": "team-a",
+                "name": "api",
+                "image": "nginx:latest",
+                "resources": {"limits": {"cpu": "2"}}
+            }),
+        )
+        .await;
+        assert_eq!(codes, vec!["namespace.bounds.max"]);
+
+        // Outside any namespace defaults, the runtime is still docker.
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "namespace": "default",
+                "name": "plain",
+                "image": "nginx:latest"
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let created: serde_json::Value = response.json();
+        assert_eq!(created["runtime"], "docker");
+        assert!(created.get("defaulted").is_none());
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_the_plan_without_applying_it() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let body = |image: &str, health_checks: bool| {
+            let mut body = json!({
+                "name": "web",
+                "namespace": "preview",
+                "image": image,
+            });
+            if health_checks {
+                body["health_checks"] = json!([{
+                    "type": "tcp", "port": 80, "interval": "10s",
+                    "timeout": "5s", "on_failure": "restart"
+                }]);
+            }
+            body
+        };
+
+        // Nothing exists yet: the namespace and the deployment would be created.
+        let response = server
+            .post("/deployments?dry_run=true")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("nginx:1.25", true))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let plan: DeploymentPlan = response.json();
+        assert_eq!(plan.action, "create");
+        assert!(plan.creates_namespace);
+        assert!(plan.changes.iter().any(|c| c.path == "image"));
+        let stored = deployments::find_active_by_namespace_name(&pool, "preview", "web")
+            .await
+            .unwrap();
+        assert!(stored.is_empty());
+        assert!(
+            namespace::find_by_name(&pool, "preview")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("nginx:1.25", true))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let current: serde_json::Value = response.json();
+        let current_id = current["id"].as_str().unwrap().to_string();
+
+        let response = server
+            .post("/deployments?dry_run=true")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("nginx:1.27", true))
+            .await;
+        let plan: DeploymentPlan = response.json();
+        assert_eq!(plan.action, "rolling_update");
+        assert_eq!(plan.replaces, vec![current_id.clone()]);
+        assert_eq!(
+            plan.changes,
+            vec![FieldChange {
+                path: "image".to_string(),
+                from: Some(json!("nginx:1.25")),
+                to: Some(json!("nginx:1.27")),
+            }]
+        );
+
+        // Without health checks the current one is replaced, and says why.
+        let response = server
+            .post("/deployments?dry_run=true&force=false")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body("nginx:1.25", false))
+            .await;
+        let plan: DeploymentPlan = response.json();
+        assert_eq!(plan.action, "replace");
+        assert_eq!(plan.reason.as_deref(), Some("no_health_checks"));
+        assert_eq!(plan.changes[0].path, "health_checks");
+        assert_eq!(plan.changes[0].to, None);
+
+        // Still validated.
+        let response = server
+            .post("/deployments?dry_run=true")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"name": "web", "namespace": "preview", "image": "nginx", "runtime": "nope"}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let stored = deployments::find_active_by_namespace_name(&pool, "preview", "web")
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, current_id);
+        assert_eq!(stored[0].image, "nginx:1.25");
+    }
 }
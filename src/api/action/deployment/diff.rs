@@ -0,0 +1,153 @@
+use serde_json::{Map, Value};
+
+use crate::api::dto::deployment::FieldChange;
+use crate::models::deployments::{Deployment, DeploymentConfig};
+
+/// Bookkeeping the server owns, not the manifest: never part of a diff.
+const STATE_FIELDS: &[&str] = &[
+    "id",
+    "created_at",
+    "updated_at",
+    "status",
+    "restart_count",
+    "instances",
+    "desired_replicas",
+    "image_digest",
+    "pending_events",
+    "parent_id",
+    "pending_restart",
+    "paused",
+    "cronjob_id",
+    "last_scheduled_at",
+    "exit_code",
+    "defaulted",
+];
+
+/// The fields `next` changes from `current`, as dotted paths into the spec
+/// (`image`, `labels.team`, `resources.limits.cpu`). Objects are walked key by
+/// key; a list is one value, compared as a whole. Without a `current`, every
+/// field of `next` is an addition.
+pub(crate) fn spec_changes(current: Option<&Deployment>, next: &Deployment) -> Vec<FieldChange> {
+    let mut before = Map::new();
+    if let Some(current) = current {
+        flatten("", manifest_spec(current), &mut before);
+    }
+    let mut after = Map::new();
+    flatten("", manifest_spec(next), &mut after);
+
+    let mut paths: Vec<&String> = before.keys().chain(after.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let from = before.get(path);
+            let to = after.get(path);
+            (from != to).then(|| FieldChange {
+                path: path.clone(),
+                from: from.cloned(),
+                to: to.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// The manifest side of a deployment, with its volumes read back from the
+/// stored JSON so they diff like any other list. Also what a namespace export
+/// writes out. An absent `config` is stored
+/// as `{}` and read back with its defaults, so it is compared that way.
+pub(crate) fn manifest_spec(deployment: &Deployment) -> Value {
+    let mut value = serde_json::to_value(deployment).unwrap_or(Value::Null);
+    if let Value::Object(fields) = &mut value {
+        for field in STATE_FIELDS {
+            fields.remove(*field);
+        }
+        if deployment.config.is_none() {
+            let stored: Option<DeploymentConfig> = serde_json::from_str("{}").ok();
+            fields.insert(
+                "config".to_string(),
+                serde_json::to_value(stored).unwrap_or(Value::Null),
+            );
+        }
+        let volumes = serde_json::from_str(&deployment.volumes).unwrap_or(Value::Null);
+        fields.insert("volumes".to_string(), volumes);
+    }
+    value
+}
+
+/// Leaves of `value` keyed by their dotted path. Empty objects and lists, and
+/// nulls, are dropped: the API treats them as absent.
+fn flatten(prefix: &str, value: Value, into: &mut Map<String, Value>) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let path = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&path, value, into);
+            }
+        }
+        Value::Null => {}
+        Value::Array(items) if items.is_empty() => {}
+        leaf => {
+            into.insert(prefix.to_string(), leaf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_deployment() -> Deployment {
+        serde_json::from_value(json!({
+            "id": "d1",
+            "created_at": "2026-10-18 10:00:00 UTC",
+            "status": "running",
+            "restart_count": 0,
+            "namespace": "default",
+            "name": "api",
+            "image": "nginx:1.25",
+            "runtime": "docker",
+            "kind": "worker",
+            "replicas": 2,
+            "command": [],
+            "labels": {},
+            "environment": {},
+            "volumes": "[]"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reports_changed_added_and_removed_fields() {
+        let mut current = sample_deployment();
+        current.labels.insert("tier".to_string(), "web".to_string());
+        let mut next = current.clone();
+        next.id = "another".to_string();
+        next.image = "nginx:1.27".to_string();
+        next.labels.remove("tier");
+        next.labels.insert("team".to_string(), "a".to_string());
+
+        let changes = spec_changes(Some(&current), &next);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["image", "labels.team", "labels.tier"]);
+        assert_eq!(changes[0].from, Some(json!(current.image)));
+        assert_eq!(changes[0].to, Some(json!("nginx:1.27")));
+        assert_eq!(changes[1].from, None);
+        assert_eq!(changes[2].to, None);
+    }
+
+    #[test]
+    fn an_identical_spec_has_no_changes() {
+        let current = sample_deployment();
+        let mut next = current.clone();
+        next.id = "another".to_string();
+        next.restart_count = 4;
+        assert!(spec_changes(Some(&current), &next).is_empty());
+    }
+}
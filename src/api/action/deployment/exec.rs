@@ -0,0 +1,334 @@
+use axum::extract::ws::{
+    Message, WebSocket, WebSocketUpgrade, rejection::WebSocketUpgradeRejection,
+};
+use axum::extract::{FromRequestParts, Path, State};
+use axum::http::StatusCode;
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use url::form_urlencoded::parse;
+
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::exec::{self as wire, ExecStatus};
+use crate::api::server::{Db, RuntimeMap};
+use crate::api::validation::problem_response;
+use crate::hypervisor::exec::{ExecOutput, ExecRequest, ExecSession, TerminalSize};
+use crate::models::audit_log;
+use crate::models::deployments;
+
+/// Program run when the query names none.
+const DEFAULT_COMMAND: &str = "sh";
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExecQuery {
+    /// Program and arguments, one `command=` per word, in order.
+    command: Vec<String>,
+    tty: bool,
+    /// Instance to run in; the first running one when absent.
+    instance: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+}
+
+impl<S> FromRequestParts<S> for ExecQuery
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or("");
+        let mut params = ExecQuery::default();
+
+        for (key, value) in parse(query.as_bytes()).into_owned() {
+            match key.as_str() {
+                "command" | "command[]" => params.command.push(value),
+                "tty" => params.tty = matches!(value.as_str(), "true" | "1"),
+                "instance" => params.instance = Some(value),
+                "cols" | "rows" => {
+                    let Ok(n) = value.parse::<u16>() else {
+                        return Err(problem_response(
+                            StatusCode::BAD_REQUEST,
+                            "Bad Request",
+                            format!("{} must be a number between 0 and 65535", key),
+                        ));
+                    };
+                    if key == "cols" {
+                        params.cols = Some(n);
+                    } else {
+                        params.rows = Some(n);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(params)
+    }
+}
+
+/// `GET /deployments/{id}/exec` — run a command in one of the deployment's
+/// running instances, its stdio attached to a WebSocket.
+///
+/// Everything that can fail is checked before the upgrade, so a client gets a
+/// plain problem response for an unknown deployment, an instance that is not
+/// running, or a runtime that cannot start the process. Once upgraded, the
+/// messages follow [`crate::api::dto::exec`]. Closing the socket kills the
+/// process; on Docker and Podman, only when the daemon runs on this host.
+pub(crate) async fn exec(
+    Path(id): Path<String>,
+    params: ExecQuery,
+    auth: Auth,
+    State(pool): State<Db>,
+    State(runtimes): State<RuntimeMap>,
+    ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Response {
+    // Scope (`deployments:exec`) is enforced centrally; the namespace
+    // boundary is checked here against the loaded deployment.
+    let deployment = match deployments::find(&pool, &id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to look up deployment",
+            );
+        }
+    };
+    if let Err(resp) = require_namespace(&auth.source, &deployment.namespace) {
+        return resp;
+    }
+
+    let runtime = runtimes.get(&deployment.runtime);
+    let instances = match runtime {
+        Some(rt) => rt.list_instances(deployment.id.clone(), "running").await,
+        None => Vec::new(),
+    };
+    let instance = match params.instance {
+        Some(wanted) if instances.contains(&wanted) => wanted,
+        Some(wanted) => {
+            return problem_response(
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                format!(
+                    "instance {} is not a running instance of this deployment",
+                    wanted
+                ),
+            );
+        }
+        None => match instances.into_iter().next() {
+            Some(first) => first,
+            None => {
+                return problem_response(
+                    StatusCode::CONFLICT,
+                    "Conflict",
+                    "deployment has no running instance to exec into",
+                );
+            }
+        },
+    };
+    let Some(runtime) = runtime else {
+        // Unreachable: without a runtime there is no running instance.
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    // Only now: a session opened for a request that cannot be upgraded would
+    // start a process nobody can reach.
+    let ws = match ws {
+        Ok(ws) => ws,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let command = if params.command.is_empty() {
+        vec![DEFAULT_COMMAND.to_string()]
+    } else {
+        params.command
+    };
+    let size = match (params.cols, params.rows) {
+        (Some(cols), Some(rows)) if params.tty => Some(TerminalSize { cols, rows }),
+        _ => None,
+    };
+    let request = ExecRequest {
+        command: command.clone(),
+        tty: params.tty,
+        size,
+    };
+    let session = match runtime.exec(&instance, request).await {
+        Ok(session) => session,
+        Err(e) => {
+            return problem_response(
+                StatusCode::BAD_GATEWAY,
+                "Bad Gateway",
+                format!(
+                    "failed to start the command in instance {}: {}",
+                    instance, e
+                ),
+            );
+        }
+    };
+
+    info!(
+        "User {} opened an exec session in instance {} of deployment {}: {:?}",
+        auth.user.username, instance, deployment.id, command
+    );
+    let _ = audit_log::record(
+        &pool,
+        Some(&auth.user.id),
+        "exec",
+        "deployment",
+        &deployment.name,
+        Some(&deployment.namespace),
+    )
+    .await;
+
+    ws.on_upgrade(move |socket| bridge(socket, session))
+}
+
+/// Pumps the session's output to the socket and the socket's stdin and
+/// resizes to the session, until the process exits or the client leaves.
+async fn bridge(mut socket: WebSocket, session: ExecSession) {
+    let ExecSession {
+        stdin,
+        resize,
+        mut output,
+    } = session;
+    let mut stdin = Some(stdin);
+
+    loop {
+        tokio::select! {
+            message = output.recv() => {
+                let (channel, payload) = match message {
+                    Some(ExecOutput::Stdout(bytes)) => (wire::CHANNEL_STDOUT, bytes),
+                    Some(ExecOutput::Stderr(bytes)) => (wire::CHANNEL_STDERR, bytes),
+                    Some(ExecOutput::Exit(exit_code)) => {
+                        finish(&mut socket, exit_code).await;
+                        return;
+                    }
+                    None => {
+                        finish(&mut socket, None).await;
+                        return;
+                    }
+                };
+                let message = Message::Binary(wire::encode(channel, &payload).into());
+                if socket.send(message).await.is_err() {
+                    return;
+                }
+            }
+            incoming = socket.recv() => {
+                let data = match incoming {
+                    Some(Ok(Message::Binary(data))) => data,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                };
+                match wire::decode(&data) {
+                    Some((wire::CHANNEL_STDIN, [])) => stdin = None,
+                    Some((wire::CHANNEL_STDIN, bytes)) => {
+                        if let Some(tx) = &stdin {
+                            let _ = tx.send(bytes.to_vec()).await;
+                        }
+                    }
+                    Some((wire::CHANNEL_RESIZE, json)) => {
+                        if let Ok(size) = serde_json::from_slice::<TerminalSize>(json) {
+                            let _ = resize.send(size).await;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+async fn finish(socket: &mut WebSocket, exit_code: Option<i64>) {
+    let status = serde_json::to_vec(&ExecStatus { exit_code }).unwrap_or_default();
+    let _ = socket
+        .send(Message::Binary(
+            wire::encode(wire::CHANNEL_STATUS, &status).into(),
+        ))
+        .await;
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    async fn create(server: &TestServer, token: &str) -> String {
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(
+                &json!({"runtime": "docker", "name": "web", "namespace": "exec", "image": "nginx"}),
+            )
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        response.json::<Value>()["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn exec_unknown_deployment_is_not_found() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .get("/deployments/unknown/exec")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn exec_without_a_running_instance_is_a_conflict() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let id = create(&server, &token).await;
+
+        let response = server
+            .get(&format!("/deployments/{}/exec", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+        let response = server
+            .get(&format!("/deployments/{}/exec?instance=abc", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn exec_needs_its_own_scope() {
+        let app = new_test_app().await;
+        let admin = login(app.clone(), "admin", "changeme").await;
+        let viewer = login(app.clone(), "john.doe", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let id = create(&server, &admin).await;
+
+        let response = server
+            .get(&format!("/deployments/{}/exec", id))
+            .add_header("Authorization", format!("Bearer {}", viewer))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+        // `deployments:write` alone does not open a shell either.
+        let mint = server
+            .post("/tokens")
+            .add_header("Authorization", format!("Bearer {}", admin))
+            .json(&json!({ "name": "deployer", "scopes": ["deployments:write"], "namespaces": [] }))
+            .await;
+        let pat = mint.json::<Value>()["token"].as_str().unwrap().to_string();
+        let response = server
+            .get(&format!("/deployments/{}/exec", id))
+            .add_header("Authorization", format!("Bearer {}", pat))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    }
+}
@@ -1,21 +1,28 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
-    http::{HeaderValue, StatusCode, header},
+    extract::{FromRequestParts, Path, State},
+    http::{HeaderValue, StatusCode, header, request::Parts},
     response::{
-        IntoResponse,
-        sse::{KeepAlive, Sse},
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
     },
 };
 use chrono::Utc;
+use futures::StreamExt;
 use regex::Regex;
-use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::LazyLock;
+use url::form_urlencoded::parse;
 
 use crate::api::auth::{Auth, AuthSource, require_namespace};
 use crate::api::server::{Db, RuntimeMap};
+use crate::api::validation::problem_response;
+use crate::config::config::Config;
 use crate::hypervisor::lifecycle_trait::Log;
-use crate::models::deployments;
+use crate::hypervisor::log_format::{LogParser, normalize_level};
+use crate::models::deployments::{self, Deployment};
+use crate::scheduler::log_archive::{ArchiveQuery, LogArchive};
 
 /// Scope string used to bind a stream ticket to a specific deployment's log
 /// endpoint. Keep this in sync with the client that mints the ticket.
@@ -23,24 +30,144 @@ pub(crate) fn logs_scope(deployment_id: &str) -> String {
     format!("deployment:logs:{}", deployment_id)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 pub struct LogsQuery {
-    #[serde(default = "default_tail")]
     tail: Option<u64>,
-    #[serde(default)]
     since: Option<String>,
-    #[serde(default)]
-    container: Option<String>,
-    #[serde(default)]
+    /// Unix timestamp: only the lines written at or before it. The runtimes
+    /// and the archive apply it, as most lines carry no date of their own.
+    until: Option<i32>,
+    /// Instance names (or prefixes) to read, from `instance` (repeatable, or
+    /// comma-separated) and the older single-valued `container`. Empty for
+    /// all of them.
+    instances: Vec<String>,
     follow: bool,
+    /// Only the lines of instances that are gone, read from the log archive.
+    previous: bool,
+    filter: LogFilter,
     // NOTE: the dashboard still appends `?ticket=<t>` here (EventSource can't
-    // set headers). It's consumed upstream by `api::auth::auth_middleware`;
-    // `serde_urlencoded` ignores the unknown key, so it's intentionally absent
-    // from this struct.
+    // set headers). It's consumed upstream by `api::auth::auth_middleware`
+    // and ignored by the parser below.
 }
 
-fn default_tail() -> Option<u64> {
-    Some(100)
+impl<S> FromRequestParts<S> for LogsQuery
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or("");
+        let mut params = LogsQuery {
+            tail: Some(DEFAULT_TAIL),
+            since: None,
+            until: None,
+            instances: Vec::new(),
+            follow: false,
+            previous: false,
+            filter: LogFilter::default(),
+        };
+
+        for (key, value) in parse(query.as_bytes()).into_owned() {
+            match key.as_str() {
+                "tail" => match value.parse::<u64>() {
+                    Ok(n) => params.tail = Some(n),
+                    Err(_) => return Err(bad_request("tail must be a number of lines")),
+                },
+                "since" => params.since = Some(value),
+                "until" => match parse_since(&value) {
+                    Some(until) => params.until = Some(until),
+                    None => {
+                        return Err(bad_request(
+                            "until must be a duration (30s, 10m, 2h) or an RFC 3339 timestamp",
+                        ));
+                    }
+                },
+                "container" | "instance" | "instance[]" => params.instances.extend(list(&value)),
+                "level" | "level[]" => params
+                    .filter
+                    .levels
+                    .extend(list(&value).map(|level| normalize_level(&level))),
+                "grep" => match Regex::new(&value) {
+                    Ok(re) => params.filter.grep = Some(re),
+                    Err(e) => return Err(bad_request(format!("grep is not a valid regex: {}", e))),
+                },
+                "follow" => params.follow = matches!(value.as_str(), "true" | "1"),
+                "previous" => params.previous = matches!(value.as_str(), "true" | "1"),
+                _ => {}
+            }
+        }
+
+        Ok(params)
+    }
+}
+
+const DEFAULT_TAIL: u64 = 100;
+
+fn bad_request(detail: impl Into<String>) -> Response {
+    problem_response(StatusCode::BAD_REQUEST, "Bad Request", detail)
+}
+
+/// The non-empty items of a comma-separated value.
+fn list(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+}
+
+/// Line filters applied on the server, so a client only receives what it
+/// asked for, in one-shot and follow mode alike.
+#[derive(Debug, Default)]
+pub(crate) struct LogFilter {
+    /// Levels as `classify_log` reports them. Empty for any.
+    pub(crate) levels: Vec<String>,
+    /// Matched against the message.
+    pub(crate) grep: Option<Regex>,
+}
+
+impl LogFilter {
+    /// Whether anything is filtered on the line's content, so a `tail` has to
+    /// count the lines that pass rather than the raw ones.
+    fn is_active(&self) -> bool {
+        !self.levels.is_empty() || self.grep.is_some()
+    }
+
+    pub(crate) fn matches(&self, log: &Log) -> bool {
+        if !self.levels.is_empty() && !self.levels.contains(&log.level) {
+            return false;
+        }
+        if let Some(re) = &self.grep
+            && !re.is_match(&log.message)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Keep the last `tail` entries of each instance and container, the way the
+/// runtimes count their own `tail`.
+fn keep_last(logs: Vec<Log>, tail: Option<u64>) -> Vec<Log> {
+    let Some(n) = tail.map(|n| n as usize) else {
+        return logs;
+    };
+    let mut counts: HashMap<(String, Option<String>), usize> = HashMap::new();
+    let mut keep = vec![false; logs.len()];
+    for (index, log) in logs.iter().enumerate().rev() {
+        let count = counts
+            .entry((log.instance.clone(), log.container.clone()))
+            .or_default();
+        if *count < n {
+            *count += 1;
+            keep[index] = true;
+        }
+    }
+    logs.into_iter()
+        .zip(keep)
+        .filter_map(|(log, keep)| keep.then_some(log))
+        .collect()
 }
 
 static SINCE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d+)(s|m|h)$").unwrap());
@@ -68,10 +195,11 @@ fn parse_since(since: &str) -> Option<i32> {
 
 pub(crate) async fn logs(
     Path(id): Path<String>,
-    Query(params): Query<LogsQuery>,
+    params: LogsQuery,
     auth: Auth,
     State(pool): State<Db>,
     State(runtimes): State<RuntimeMap>,
+    State(configuration): State<Config>,
 ) -> impl IntoResponse {
     // Auth is enforced upstream by `api::auth::auth_middleware`: the scope
     // (`deployments:read`) is checked centrally, and a stream ticket is bound to
@@ -87,23 +215,66 @@ pub(crate) async fn logs(
             {
                 return resp.into_response();
             }
+            let since = params.since.as_deref().and_then(parse_since);
+
+            // With the archive enabled it answers everything but `follow`: it
+            // still has the lines of the instances the runtime removed.
+            let archive = LogArchive::from_config(&configuration.server.logs.archive);
+            if params.previous || (archive.is_some() && since.is_some() && !params.follow) {
+                if params.follow {
+                    return problem_response(
+                        StatusCode::BAD_REQUEST,
+                        "Bad Request",
+                        "previous cannot be combined with follow",
+                    );
+                }
+                let Some(archive) = archive else {
+                    return problem_response(
+                        StatusCode::CONFLICT,
+                        "Conflict",
+                        "the log archive is not enabled on this server ([server.logs.archive])",
+                    );
+                };
+                return Json(read_archive(&archive, &deployment, &params, since, &runtimes).await)
+                    .into_response();
+            }
+
             let runtime = match runtimes.get(&deployment.runtime) {
                 Some(rt) => rt,
                 None => return StatusCode::NOT_FOUND.into_response(),
             };
-
-            let tail = params.tail.map(|t| t.to_string());
-            let since = params.since.as_deref().and_then(parse_since);
+            // One runtime filter per requested instance; `None` reads them all.
+            let sources: Vec<Option<&str>> = if params.instances.is_empty() {
+                vec![None]
+            } else {
+                params.instances.iter().map(|i| Some(i.as_str())).collect()
+            };
 
             if params.follow {
-                let stream = runtime
-                    .stream_logs(
-                        &deployment.id,
-                        tail.as_deref(),
-                        since,
-                        params.container.as_deref(),
-                    )
-                    .await;
+                // `tail` is the replay of each source before filtering: a
+                // stream has no end to count matching lines back from.
+                let tail = params.tail.map(|t| t.to_string());
+                let mut streams = Vec::with_capacity(sources.len());
+                for source in sources {
+                    streams.push(
+                        runtime
+                            .stream_logs(&deployment.id, tail.as_deref(), since, source)
+                            .await,
+                    );
+                }
+                let deadline = params.follow_deadline();
+                let filter = params.filter;
+                let stream = LogParser::for_deployment(&deployment)
+                    .stream(futures::stream::select_all(streams).boxed())
+                    .filter(move |log| std::future::ready(filter.matches(log)))
+                    .map(|log| {
+                        let json = serde_json::to_string(&log).unwrap_or_default();
+                        Ok::<_, Infallible>(Event::default().data(json))
+                    });
+                let stream = match deadline {
+                    Some(deadline) => stream.take_until(tokio::time::sleep(deadline)).boxed(),
+                    None => stream.boxed(),
+                };
 
                 // Prevent the ticket from leaking via Referer if the page
                 // ever links externally. Defense in depth on top of the
@@ -117,15 +288,33 @@ pub(crate) async fn logs(
                 );
                 response
             } else {
-                let logs = runtime
-                    .get_logs(
-                        &deployment.id,
-                        tail.as_deref(),
-                        since,
-                        params.container.as_deref(),
-                    )
-                    .await;
-                Json(logs).into_response()
+                // The runtimes apply `tail` to each container. With a content
+                // filter, read everything and keep the last `tail` lines of
+                // each that pass, not the ones among its last `tail`.
+                let filtered = params.filter.is_active();
+                let tail = if filtered {
+                    None
+                } else {
+                    params.tail.map(|t| t.to_string())
+                };
+                let mut lines = Vec::new();
+                for source in sources {
+                    lines.extend(
+                        runtime
+                            .get_logs(&deployment.id, tail.as_deref(), since, params.until, source)
+                            .await,
+                    );
+                }
+                let logs = LogParser::for_deployment(&deployment)
+                    .fold(lines)
+                    .into_iter()
+                    .filter(|log| params.filter.matches(log))
+                    .collect();
+                if filtered {
+                    Json(keep_last(logs, params.tail)).into_response()
+                } else {
+                    Json(logs).into_response()
+                }
             }
         }
         Ok(None) => Json(Vec::<Log>::new()).into_response(),
@@ -133,9 +322,167 @@ pub(crate) async fn logs(
     }
 }
 
+impl LogsQuery {
+    /// How long a follow stream stays open when `until` is set: it ends once
+    /// no later line can match.
+    fn follow_deadline(&self) -> Option<std::time::Duration> {
+        let until = i64::from(self.until?);
+        let left = until - Utc::now().timestamp();
+        Some(std::time::Duration::from_secs(left.max(0) as u64))
+    }
+}
+
+/// The deployment's series in the archive. `previous` leaves out the
+/// instances that are still running: their lines are the runtime's to serve.
+async fn read_archive(
+    archive: &LogArchive,
+    deployment: &Deployment,
+    params: &LogsQuery,
+    since: Option<i32>,
+    runtimes: &RuntimeMap,
+) -> Vec<Log> {
+    let running: Vec<String> = match runtimes.get(&deployment.runtime) {
+        Some(runtime) if params.previous => runtime
+            .list_instances_with_names(deployment.id.clone(), "running")
+            .await
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let query = ArchiveQuery {
+        since: since.map(i64::from),
+        until: params.until.map(i64::from),
+        instances: &params.instances,
+        exclude: &running,
+    };
+    let lines = archive
+        .read(&deployment.namespace, &deployment.name, &query)
+        .await;
+    let logs = LogParser::for_deployment(deployment)
+        .fold(lines)
+        .into_iter()
+        .filter(|log| params.filter.matches(log))
+        .collect();
+    keep_last(logs, params.tail)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::server::tests::{login, new_test_app};
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn previous_needs_the_archive() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(
+                &json!({"runtime": "docker", "name": "web", "namespace": "logs", "image": "nginx"}),
+            )
+            .await;
+        let id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+        let response = server
+            .get(&format!("/deployments/{}/logs?previous=true", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+        let response = server
+            .get(&format!(
+                "/deployments/{}/logs?previous=true&follow=true",
+                id
+            ))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn malformed_filters_are_rejected() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        for query in ["grep=(unclosed", "until=yesterday", "tail=many"] {
+            let response = server
+                .get(&format!("/deployments/any/logs?{}", query))
+                .add_header("Authorization", format!("Bearer {}", token))
+                .await;
+            assert_eq!(response.status_code(), StatusCode::BAD_REQUEST, "{}", query);
+        }
+    }
+
+    fn log(level: &str, message: &str, timestamp: Option<&str>) -> Log {
+        Log {
+            instance: "web-1".to_string(),
+            container: None,
+            message: message.to_string(),
+            level: level.to_string(),
+            timestamp: timestamp.map(str::to_string),
+            fields: Default::default(),
+        }
+    }
+
+    #[test]
+    fn filter_matches_level_and_grep() {
+        let filter = LogFilter {
+            levels: vec!["error".to_string(), "warning".to_string()],
+            grep: Some(Regex::new("time(d )?out").unwrap()),
+        };
+        assert!(filter.matches(&log("error", "upstream timed out", None)));
+        assert!(filter.matches(&log("warning", "timeout", Some("2024/01/15 10:29:59"))));
+        assert!(!filter.matches(&log("info", "upstream timed out", None)));
+        assert!(!filter.matches(&log("error", "connection refused", None)));
+        assert!(LogFilter::default().matches(&log("unknown", "anything", None)));
+    }
+
+    #[test]
+    fn tail_is_kept_per_instance() {
+        let mut logs = Vec::new();
+        for n in 1..=3 {
+            for instance in ["web-1", "web-2"] {
+                logs.push(Log {
+                    instance: instance.to_string(),
+                    ..log("info", &format!("{} line {}", instance, n), None)
+                });
+            }
+        }
+        // A sidecar counts on its own too.
+        logs.push(Log {
+            container: Some("proxy".to_string()),
+            ..log("info", "web-1 proxy line", None)
+        });
+
+        let messages: Vec<String> = keep_last(logs, Some(2))
+            .into_iter()
+            .map(|log| log.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "web-1 line 2",
+                "web-2 line 2",
+                "web-1 line 3",
+                "web-2 line 3",
+                "web-1 proxy line"
+            ]
+        );
+    }
+
+    #[test]
+    fn instance_lists_are_comma_separated() {
+        assert_eq!(
+            list("web-1, web-2,,").collect::<Vec<_>>(),
+            vec!["web-1", "web-2"]
+        );
+    }
 
     #[test]
     fn test_parse_since_relative_seconds() {
@@ -1,16 +1,33 @@
+pub(crate) mod canary;
 pub(crate) mod create;
 pub(crate) mod delete;
+pub(crate) mod diff;
 pub(crate) mod events;
+pub(crate) mod exec;
 pub(crate) mod get;
 pub(crate) mod health_checks;
 pub(crate) mod list;
 pub(crate) mod logs;
 pub(crate) mod metrics;
+pub(crate) mod pause;
+pub(crate) mod restart;
+pub(crate) mod revisions;
+pub(crate) mod rollback;
+pub(crate) mod runs;
+pub(crate) mod scale;
+pub(crate) use canary::{abort, promote};
 pub(crate) use create::create;
 pub(crate) use delete::delete;
 pub(crate) use events::get_deployment_events;
+pub(crate) use exec::exec;
 pub(crate) use get::get;
 pub(crate) use health_checks::get_health_checks;
 pub(crate) use list::list;
 pub(crate) use logs::logs;
 pub(crate) use metrics::metrics as get_deployment_metrics;
+pub(crate) use pause::{pause, resume};
+pub(crate) use restart::restart;
+pub(crate) use revisions::revisions;
+pub(crate) use rollback::rollback;
+pub(crate) use runs::runs;
+pub(crate) use scale::scale;
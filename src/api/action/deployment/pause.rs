@@ -0,0 +1,210 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::deployment::DeploymentOutput;
+use crate::api::server::Db;
+use crate::api::validation::problem_response;
+use crate::models::audit_log;
+use crate::models::deployment_event;
+use crate::models::deployments::{self, DeploymentStatus};
+
+/// `POST /deployments/{id}/pause` — stop reconciling a deployment.
+///
+/// The scheduler skips it from the next tick on: a crashed instance is not
+/// restarted, the count is not converged, the autoscaler holds, and health
+/// checks take no action. The running instances are left untouched, so one can
+/// be debugged in place or a rollout held half-way.
+pub(crate) async fn pause(Path(id): Path<String>, auth: Auth, State(pool): State<Db>) -> Response {
+    set_paused(id, auth, pool, true).await
+}
+
+/// `POST /deployments/{id}/resume` — hand a paused deployment back to the
+/// scheduler, which picks up reconciliation on its next tick.
+pub(crate) async fn resume(Path(id): Path<String>, auth: Auth, State(pool): State<Db>) -> Response {
+    set_paused(id, auth, pool, false).await
+}
+
+async fn set_paused(id: String, auth: Auth, pool: Db, paused: bool) -> Response {
+    let action = if paused { "pause" } else { "resume" };
+
+    // Scope (`deployments:write`) is enforced centrally; the namespace
+    // boundary is checked here against the loaded deployment.
+    let mut deployment = match deployments::find(&pool, &id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to look up deployment",
+            );
+        }
+    };
+    if let Err(resp) = require_namespace(&auth.source, &deployment.namespace) {
+        return resp;
+    }
+
+    if matches!(
+        deployment.status,
+        DeploymentStatus::Deleted | DeploymentStatus::Completed | DeploymentStatus::Failed
+    ) {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            format!(
+                "deployment is {} and is no longer reconciled",
+                deployment.status
+            ),
+        );
+    }
+
+    if deployment.paused == paused {
+        return Json(DeploymentOutput::from_to_model(deployment)).into_response();
+    }
+
+    match deployments::set_paused(&pool, &deployment.id, paused).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!("Failed to {} deployment {}: {}", action, deployment.id, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                format!("failed to {} deployment", action),
+            );
+        }
+    }
+    deployment.paused = paused;
+
+    info!(
+        "Deployment {} {}",
+        deployment.id,
+        if paused { "paused" } else { "resumed" }
+    );
+
+    let (message, reason) = if paused {
+        (
+            "Reconciliation paused: instances are left as they are until resumed",
+            "paused",
+        )
+    } else {
+        ("Reconciliation resumed", "resumed")
+    };
+    let _ = deployment_event::log_event(
+        &pool,
+        deployment.id.clone(),
+        "info",
+        message.to_string(),
+        "api",
+        Some(reason),
+    )
+    .await;
+
+    let _ = audit_log::record(
+        &pool,
+        Some(&auth.user.id),
+        action,
+        "deployment",
+        &deployment.name,
+        Some(&deployment.namespace),
+    )
+    .await;
+
+    Json(DeploymentOutput::from_to_model(deployment)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    async fn create(server: &TestServer, token: &str) -> String {
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"runtime": "docker", "name": "web", "namespace": "pause", "image": "nginx"}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        response.json::<Value>()["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn pause_and_resume_toggle_the_flag() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let id = create(&server, &token).await;
+
+        let response = server
+            .post(&format!("/deployments/{}/pause", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<Value>()["paused"], true);
+
+        let fetched: Value = server
+            .get(&format!("/deployments/{}", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await
+            .json();
+        assert_eq!(fetched["paused"], true);
+
+        let response = server
+            .post(&format!("/deployments/{}/resume", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert_eq!(response.json::<Value>()["paused"], false);
+    }
+
+    #[tokio::test]
+    async fn pause_is_recorded_in_the_audit_log() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+        let id = create(&server, &token).await;
+
+        server
+            .post(&format!("/deployments/{}/pause", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        // Pausing twice is a no-op, not a second audit entry.
+        server
+            .post(&format!("/deployments/{}/pause", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+
+        let entries: Vec<Value> = server
+            .get("/namespaces/pause/audit")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await
+            .json();
+        let pauses = entries.iter().filter(|e| e["action"] == "pause").count();
+        assert_eq!(pauses, 1);
+    }
+
+    #[tokio::test]
+    async fn pause_unknown_deployment_is_not_found() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/deployments/unknown/pause")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+}
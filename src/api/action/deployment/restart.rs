@@ -0,0 +1,235 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::deployment::DeploymentOutput;
+use crate::api::server::{Db, RuntimeMap};
+use crate::api::validation::problem_response;
+use crate::models::audit_log;
+use crate::models::deployment_event;
+use crate::models::deployments::{self, DeploymentStatus};
+
+/// `POST /deployments/{id}/restart` — recreate every instance, one at a time.
+///
+/// The spec is left untouched: the instances running now are recorded and the
+/// scheduler replaces them one per cycle, starting each replacement and waiting
+/// for it to be ready before removing an old instance (see
+/// `handle_rolling_restart`). Answers `202 Accepted` as soon as the restart is
+/// recorded; progress is reported as `deployment.rolling_update` events with
+/// `reason: restart`.
+pub(crate) async fn restart(
+    Path(id): Path<String>,
+    auth: Auth,
+    State(pool): State<Db>,
+    State(runtimes): State<RuntimeMap>,
+) -> Response {
+    // Scope (`deployments:write`) is enforced centrally; the namespace
+    // boundary is checked here against the loaded deployment.
+    let mut deployment = match deployments::find(&pool, &id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to look up deployment",
+            );
+        }
+    };
+    if let Err(resp) = require_namespace(&auth.source, &deployment.namespace) {
+        return resp;
+    }
+
+    if deployment.kind == "job" {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "kind=job runs once and exits; it cannot be restarted",
+        );
+    }
+
+    if deployment.kind == "cronjob" {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "kind=cronjob has no instance of its own to restart; its runs are started by the schedule",
+        );
+    }
+
+    if deployment.status != DeploymentStatus::Running {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            format!(
+                "deployment is {}; only a running deployment can be restarted",
+                deployment.status
+            ),
+        );
+    }
+
+    if deployment.parent_id.is_some() {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "a rolling update is still in progress; restart once it has completed",
+        );
+    }
+
+    if !deployment.pending_restart.is_empty() {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            format!(
+                "a restart is already in progress ({} instance(s) left to replace)",
+                deployment.pending_restart.len()
+            ),
+        );
+    }
+
+    let instances = match runtimes.get(&deployment.runtime) {
+        Some(rt) => rt.list_instances(deployment.id.clone(), "running").await,
+        None => Vec::new(),
+    };
+    if instances.is_empty() {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "deployment has no running instance to restart",
+        );
+    }
+
+    match deployments::set_pending_restart(&pool, &deployment.id, &instances).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!(
+                "Failed to record restart of deployment {}: {}",
+                deployment.id, e
+            );
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to restart deployment",
+            );
+        }
+    }
+
+    info!(
+        "Rolling restart of deployment {} requested ({} instances)",
+        deployment.id,
+        instances.len()
+    );
+
+    let _ = deployment_event::log_event(
+        &pool,
+        deployment.id.clone(),
+        "info",
+        format!(
+            "Rolling restart requested: {} instance(s) will be replaced one at a time",
+            instances.len()
+        ),
+        "api",
+        Some("restart_requested"),
+    )
+    .await;
+
+    let _ = audit_log::record(
+        &pool,
+        Some(&auth.user.id),
+        "restart",
+        "deployment",
+        &deployment.name,
+        Some(&deployment.namespace),
+    )
+    .await;
+
+    deployment.pending_restart = instances;
+    (
+        StatusCode::ACCEPTED,
+        Json(DeploymentOutput::from_to_model(deployment)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    async fn create(server: &TestServer, token: &str, body: Value) -> String {
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        response.json::<Value>()["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn restart_unknown_deployment_is_not_found() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/deployments/unknown/restart")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn restart_is_refused_until_the_deployment_runs() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let id = create(
+            &server,
+            &token,
+            json!({"runtime": "docker", "name": "web", "namespace": "restart", "image": "nginx"}),
+        )
+        .await;
+
+        // Freshly created, never picked up by the scheduler: nothing to restart.
+        let response = server
+            .post(&format!("/deployments/{}/restart", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn restart_is_refused_for_a_job() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let id = create(
+            &server,
+            &token,
+            json!({"runtime": "docker", "name": "migrate", "namespace": "restart", "image": "busybox", "kind": "job"}),
+        )
+        .await;
+
+        let response = server
+            .post(&format!("/deployments/{}/restart", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+        let body: Value = response.json();
+        assert!(body["detail"].as_str().unwrap().contains("job"));
+    }
+}
@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::revision::RevisionOutput;
+use crate::api::server::Db;
+use crate::models::deployment_revision;
+use crate::models::deployments;
+
+/// `GET /deployments/{id}/revisions` — the specs accepted for this deployment,
+/// most recent first.
+///
+/// History is keyed by namespace and name, so any row of the deployment —
+/// including one already replaced by a redeploy — lists the same revisions.
+pub(crate) async fn revisions(
+    Path(id): Path<String>,
+    auth: Auth,
+    State(pool): State<Db>,
+) -> Response {
+    // Scope (`deployments:read`) is enforced centrally; the namespace boundary
+    // is checked here against the loaded deployment.
+    let deployment = match deployments::find(&pool, &id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Deployment not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if let Err(resp) = require_namespace(&auth.source, &deployment.namespace) {
+        return resp;
+    }
+
+    match deployment_revision::find_by_namespace_name(
+        &pool,
+        &deployment.namespace,
+        &deployment.name,
+    )
+    .await
+    {
+        Ok(revisions) => Json(
+            revisions
+                .into_iter()
+                .map(RevisionOutput::from_to_model)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => {
+            error!("Failed to read revisions of deployment {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::dto::revision::RevisionOutput;
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn each_apply_is_recorded_as_a_revision() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let mut id = String::new();
+        for image in ["nginx:1.25", "nginx:1.26"] {
+            let response = server
+                .post("/deployments")
+                .add_header("Authorization", format!("Bearer {}", token))
+                .json(&json!({
+                    "runtime": "docker",
+                    "name": "web",
+                    "namespace": "revisions",
+                    "image": image
+                }))
+                .await;
+            assert_eq!(response.status_code(), StatusCode::CREATED);
+            id = response.json::<serde_json::Value>()["id"]
+                .as_str()
+                .unwrap()
+                .to_string();
+        }
+
+        let response = server
+            .get(&format!("/deployments/{}/revisions", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+
+        let revisions: Vec<RevisionOutput> = response.json();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].revision, 2);
+        assert_eq!(revisions[0].deployment_id, id);
+        assert_eq!(revisions[0].spec["image"], "nginx:1.26");
+        assert_eq!(revisions[1].revision, 1);
+        assert_eq!(revisions[1].spec["image"], "nginx:1.25");
+    }
+
+    #[tokio::test]
+    async fn revisions_of_unknown_deployment_is_not_found() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .get("/deployments/unknown/revisions")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,208 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::api::action::deployment::create::{DeploymentInput, submit};
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::server::Db;
+use crate::models::deployment_revision;
+use crate::models::deployments;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RollbackQuery {
+    /// Revision to restore. Defaults to the one before the latest, i.e. "undo
+    /// the last apply".
+    #[serde(default)]
+    revision: Option<i64>,
+}
+
+/// `POST /deployments/{id}/rollback?revision=N` — re-apply a stored spec.
+///
+/// The spec is replayed through the same path as `POST /deployments` (without
+/// `force`), so validation, the rolling update and its readiness gate all
+/// apply exactly as they did the first time. The rollback is itself recorded
+/// as a new revision pointing at the one it restored, so history only ever
+/// moves forward.
+pub(crate) async fn rollback(
+    Path(id): Path<String>,
+    Query(params): Query<RollbackQuery>,
+    auth: Auth,
+    State(pool): State<Db>,
+) -> Response {
+    // Scope (`deployments:write`) is enforced centrally; the namespace
+    // boundary is checked here against the loaded deployment.
+    let deployment = match deployments::find(&pool, &id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Deployment not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if let Err(resp) = require_namespace(&auth.source, &deployment.namespace) {
+        return resp;
+    }
+
+    let target = match params.revision {
+        Some(revision) => {
+            deployment_revision::find_revision(
+                &pool,
+                &deployment.namespace,
+                &deployment.name,
+                revision,
+            )
+            .await
+        }
+        None => deployment_revision::find_by_namespace_name(
+            &pool,
+            &deployment.namespace,
+            &deployment.name,
+        )
+        .await
+        .map(|revisions| revisions.into_iter().nth(1)),
+    };
+
+    let target = match target {
+        Ok(Some(target)) => target,
+        Ok(None) => {
+            let error = match params.revision {
+                Some(revision) => format!("Revision {} not found", revision),
+                None => "No previous revision to roll back to".to_string(),
+            };
+            return (StatusCode::NOT_FOUND, Json(json!({ "error": error }))).into_response();
+        }
+        Err(e) => {
+            error!("Failed to read revisions of deployment {}: {}", id, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let input: DeploymentInput = match serde_json::from_str(&target.spec) {
+        Ok(input) => input,
+        Err(e) => {
+            // Only possible if the stored spec predates an incompatible
+            // change to the manifest format.
+            error!(
+                "Revision {} of {}/{} no longer parses: {}",
+                target.revision, target.namespace, target.name, e
+            );
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "error": format!("Revision {} can no longer be applied: {}", target.revision, e)
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    info!(
+        "Rolling back {}/{} to revision {}",
+        target.namespace, target.name, target.revision
+    );
+
+    submit(&pool, &auth, input, false, false, Some(target.revision)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::dto::revision::RevisionOutput;
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    async fn apply(server: &TestServer, token: &str, image: &str) -> String {
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "runtime": "docker",
+                "name": "web",
+                "namespace": "rollback",
+                "image": image
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        response.json::<Value>()["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn rollback_restores_the_requested_revision() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        apply(&server, &token, "nginx:1.25").await;
+        apply(&server, &token, "nginx:1.26").await;
+        let id = apply(&server, &token, "nginx:1.27").await;
+
+        let response = server
+            .post(&format!("/deployments/{}/rollback?revision=1", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let body: Value = response.json();
+        assert_eq!(body["image"], "nginx:1.25");
+        assert_ne!(body["id"], id.as_str());
+
+        let revisions: Vec<RevisionOutput> = server
+            .get(&format!("/deployments/{}/revisions", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await
+            .json();
+        assert_eq!(revisions.len(), 4);
+        assert_eq!(revisions[0].revision, 4);
+        assert_eq!(revisions[0].rollback_of, Some(1));
+        assert_eq!(revisions[0].spec["image"], "nginx:1.25");
+    }
+
+    #[tokio::test]
+    async fn rollback_defaults_to_the_previous_revision() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        apply(&server, &token, "nginx:1.25").await;
+        let id = apply(&server, &token, "nginx:1.26").await;
+
+        let response = server
+            .post(&format!("/deployments/{}/rollback", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        assert_eq!(response.json::<Value>()["image"], "nginx:1.25");
+    }
+
+    #[tokio::test]
+    async fn rollback_to_unknown_revision_is_not_found() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let id = apply(&server, &token, "nginx:1.25").await;
+
+        let response = server
+            .post(&format!("/deployments/{}/rollback?revision=7", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+        // A single revision has nothing before it to go back to.
+        let response = server
+            .post(&format!("/deployments/{}/rollback", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::run::RunOutput;
+use crate::api::server::Db;
+use crate::api::validation::problem_response;
+use crate::models::deployments;
+
+/// `GET /deployments/{id}/runs` — the runs a cronjob spawned and still keeps
+/// (its active runs plus the last `history_limit` finished ones), most recent
+/// first.
+///
+/// Each run is a `kind: job` deployment: its logs and events are read through
+/// the regular deployment endpoints with the run's id.
+pub(crate) async fn runs(Path(id): Path<String>, auth: Auth, State(pool): State<Db>) -> Response {
+    // Scope (`deployments:read`) is enforced centrally; the namespace boundary
+    // is checked here against the loaded deployment.
+    let deployment = match deployments::find(&pool, &id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to look up deployment",
+            );
+        }
+    };
+    if let Err(resp) = require_namespace(&auth.source, &deployment.namespace) {
+        return resp;
+    }
+
+    if deployment.kind != "cronjob" {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            format!(
+                "deployment is kind={}; only a cronjob has runs",
+                deployment.kind
+            ),
+        );
+    }
+
+    match deployments::find_runs(&pool, &deployment.id).await {
+        Ok(runs) => Json(
+            runs.into_iter()
+                .map(RunOutput::from_to_model)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+        Err(e) => {
+            error!("Failed to read the runs of cronjob {}: {}", id, e);
+            problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to read the runs",
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::dto::run::RunOutput;
+    use crate::api::server::tests::{login, new_test_app_with_pool};
+    use crate::models::deployments;
+    use crate::scheduler::cronjob;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn runs_lists_what_the_schedule_spawned() {
+        let (pool, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "runtime": "docker", "name": "nightly", "namespace": "reports",
+                "image": "busybox", "kind": "cronjob",
+                "cron": {"schedule": "*/5 * * * *"}
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let body: Value = response.json();
+        assert_eq!(body["kind"], "cronjob");
+        assert_eq!(body["cron"]["concurrency_policy"], "forbid");
+        assert_eq!(body["cron"]["history_limit"], 3);
+        assert!(body["next_scheduled_at"].is_string());
+        let id = body["id"].as_str().unwrap().to_string();
+
+        let mut cron = deployments::find(&pool, &id).await.unwrap().unwrap();
+        let later = chrono::Utc::now() + chrono::Duration::minutes(10);
+        cronjob::reconcile(&pool, &mut cron, later).await;
+
+        let response = server
+            .get(&format!("/deployments/{}/runs", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let runs: Vec<RunOutput> = response.json();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].name.starts_with("nightly-"));
+        assert!(runs[0].finished_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn runs_is_refused_outside_a_cronjob() {
+        let (_, app) = new_test_app_with_pool().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"runtime": "docker", "name": "web", "namespace": "reports", "image": "nginx"}))
+            .await;
+        let id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+
+        let response = server
+            .get(&format!("/deployments/{}/runs", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
+}
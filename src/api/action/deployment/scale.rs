@@ -0,0 +1,423 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::api::action::namespace::quota::violation as quota_violation;
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::deployment::{DeploymentOutput, DeploymentVolume};
+use crate::api::server::{Db, RuntimeMap};
+use crate::api::validation::{Violation, ViolationList, problem_response};
+use crate::events::{self, Event};
+use crate::models::audit_log;
+use crate::models::deployment_event;
+use crate::models::deployments::{self, Deployment, DeploymentStatus, NetworkMode};
+use crate::models::namespace::{self, Footprint};
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct ScaleInput {
+    replicas: u32,
+}
+
+/// The replica rules `POST /deployments` enforces, re-checked against the
+/// stored spec: scaling must not reach a count the same manifest would have
+/// been rejected for.
+fn validate_replicas(deployment: &Deployment, replicas: u32, errors: &mut ViolationList) {
+    if replicas <= 1 {
+        return;
+    }
+
+    if !deployment.ports.is_empty() {
+        errors.push(Violation::new(
+            "replicas",
+            format!(
+                "replicas > 1 ({}) is incompatible with published `ports`: every replica would race for the same host port",
+                replicas
+            ),
+            "deployment.replicas.ports_conflict",
+        ));
+    }
+
+    if deployment
+        .network
+        .as_ref()
+        .is_some_and(|network| matches!(network.mode, NetworkMode::Host))
+    {
+        errors.push(Violation::new(
+            "replicas",
+            format!(
+                "host networking is incompatible with replicas > 1 (got {}): all replicas would compete for the same host ports",
+                replicas
+            ),
+            "deployment.replicas.host_network_conflict",
+        ));
+    }
+
+    let volumes: Vec<DeploymentVolume> =
+        serde_json::from_str(&deployment.volumes).unwrap_or_default();
+    for volume in volumes
+        .iter()
+        .filter(|v| v.r#type == "volume" && v.permission == "rw")
+    {
+        errors.push(Violation::new(
+            "replicas",
+            format!(
+                "named volume '{}' is mounted read-write; replicas > 1 ({}) would share it with no write coordination",
+                volume.source.as_deref().unwrap_or("<unnamed>"),
+                replicas
+            ),
+            "deployment.volumes.shared_rw_replicas",
+        ));
+    }
+}
+
+/// The caps of its namespace's quota `deployment` would break at `replicas`.
+async fn quota_violations(
+    pool: &Db,
+    deployment: &Deployment,
+    replicas: u32,
+) -> Result<ViolationList, sqlx::Error> {
+    let mut violations = ViolationList::new();
+    let Some(quota) = namespace::find_by_name(pool, &deployment.namespace)
+        .await?
+        .and_then(|ns| ns.quota)
+    else {
+        return Ok(violations);
+    };
+    let usage = namespace::usage(pool, &deployment.namespace, Some(&deployment.name)).await?;
+    let footprint = Footprint::of(deployment).with_replicas(replicas);
+    // Already counted among the namespace's deployments: only its size grows.
+    for breach in quota
+        .check(&usage, &footprint)
+        .into_iter()
+        .filter(|breach| breach.field != "deployments")
+    {
+        let path = match breach.field {
+            "replicas" => "replicas".to_string(),
+            resource => format!("resources.{}", resource),
+        };
+        violations.push(quota_violation(&deployment.namespace, path, breach));
+    }
+    Ok(violations)
+}
+
+/// `POST /deployments/{id}/scale` — change the instance count in place.
+///
+/// No new deployment row is created, so unlike a re-apply this never starts a
+/// rolling update: the runtime adds or removes instances on its next tick.
+/// Refused for an autoscaled deployment — the autoscaler owns the count there
+/// and would undo the change — and for jobs, which run once.
+pub(crate) async fn scale(
+    Path(id): Path<String>,
+    auth: Auth,
+    State(pool): State<Db>,
+    State(runtimes): State<RuntimeMap>,
+    Json(input): Json<ScaleInput>,
+) -> Response {
+    // Scope (`deployments:write`) is enforced centrally; the namespace
+    // boundary is checked here against the loaded deployment.
+    let mut deployment = match deployments::find(&pool, &id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!("Failed to look up deployment {}: {}", id, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to look up deployment",
+            );
+        }
+    };
+    if let Err(resp) = require_namespace(&auth.source, &deployment.namespace) {
+        return resp;
+    }
+
+    if matches!(
+        deployment.status,
+        DeploymentStatus::Deleted | DeploymentStatus::Completed | DeploymentStatus::Failed
+    ) {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            format!(
+                "deployment is {} and can no longer be scaled",
+                deployment.status
+            ),
+        );
+    }
+
+    if deployment.kind == "job" {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "kind=job runs once and exits; it cannot be scaled",
+        );
+    }
+
+    if deployment.kind == "cronjob" {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            "kind=cronjob starts one run per trigger; it cannot be scaled",
+        );
+    }
+
+    if let Some(policy) = &deployment.autoscale {
+        return problem_response(
+            StatusCode::CONFLICT,
+            "Conflict",
+            format!(
+                "deployment is autoscaled between {} and {} instances; change `autoscale.min`/`autoscale.max` instead",
+                policy.min, policy.max
+            ),
+        );
+    }
+
+    let mut violations = ViolationList::new();
+    validate_replicas(&deployment, input.replicas, &mut violations);
+    if !violations.is_empty() {
+        return violations.into_response();
+    }
+
+    let previous = deployment.replicas;
+    if input.replicas == previous {
+        return Json(DeploymentOutput::from_to_model(deployment)).into_response();
+    }
+
+    if input.replicas > previous {
+        match quota_violations(&pool, &deployment, input.replicas).await {
+            Ok(found) if found.is_empty() => {}
+            Ok(found) => return found.into_response(),
+            Err(e) => {
+                error!(
+                    "Failed to compute the usage of namespace '{}': {}",
+                    deployment.namespace, e
+                );
+                return problem_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal Server Error",
+                    "failed to compute namespace usage",
+                );
+            }
+        }
+    }
+
+    match deployments::set_replicas(&pool, &deployment.id, input.replicas).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return problem_response(StatusCode::NOT_FOUND, "Not Found", "deployment not found");
+        }
+        Err(e) => {
+            error!("Failed to scale deployment {}: {}", deployment.id, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to scale deployment",
+            );
+        }
+    }
+    deployment.replicas = input.replicas;
+
+    info!(
+        "Deployment {} scaled from {} to {} replicas",
+        deployment.id, previous, input.replicas
+    );
+
+    let _ = deployment_event::log_event(
+        &pool,
+        deployment.id.clone(),
+        "info",
+        format!("Scaled from {} to {} replicas", previous, input.replicas),
+        "api",
+        Some("scaled"),
+    )
+    .await;
+
+    let instance_count = match runtimes.get(&deployment.runtime) {
+        Some(rt) => rt
+            .list_instances(deployment.id.clone(), "running")
+            .await
+            .len(),
+        None => 0,
+    };
+    let direction = if input.replicas > previous {
+        "up"
+    } else {
+        "down"
+    };
+    events::publish(
+        &pool,
+        Event::deployment_scaled(&deployment, direction, instance_count),
+    )
+    .await;
+
+    let _ = audit_log::record(
+        &pool,
+        Some(&auth.user.id),
+        "scale",
+        "deployment",
+        &deployment.name,
+        Some(&deployment.namespace),
+    )
+    .await;
+
+    Json(DeploymentOutput::from_to_model(deployment)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::{Value, json};
+
+    async fn create(server: &TestServer, token: &str, body: Value) -> String {
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        response.json::<Value>()["id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn scale_updates_replicas_in_place() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let id = create(
+            &server,
+            &token,
+            json!({"runtime": "docker", "name": "web", "namespace": "scale", "image": "nginx"}),
+        )
+        .await;
+
+        let response = server
+            .post(&format!("/deployments/{}/scale", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"replicas": 4}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let body: Value = response.json();
+        // Same row, new count: no child deployment was created.
+        assert_eq!(body["id"], id.as_str());
+        assert_eq!(body["replicas"], 4);
+
+        let list: Vec<Value> = server
+            .get("/deployments?namespace[]=scale")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await
+            .json();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0]["replicas"], 4);
+    }
+
+    #[tokio::test]
+    async fn scale_is_refused_for_an_autoscaled_deployment() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let id = create(
+            &server,
+            &token,
+            json!({
+                "runtime": "docker", "name": "web", "namespace": "scale", "image": "nginx",
+                "autoscale": {"min": 1, "max": 4, "target_cpu": 70}
+            }),
+        )
+        .await;
+
+        let response = server
+            .post(&format!("/deployments/{}/scale", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"replicas": 3}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn scale_enforces_the_replica_rules_of_create() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let id = create(
+            &server,
+            &token,
+            json!({
+                "runtime": "docker", "name": "web", "namespace": "scale", "image": "nginx",
+                "ports": [{"published": 8080, "target": 80}]
+            }),
+        )
+        .await;
+
+        let response = server
+            .post(&format!("/deployments/{}/scale", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"replicas": 2}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: Value = response.json();
+        assert_eq!(
+            body["violations"][0]["code"],
+            "deployment.replicas.ports_conflict"
+        );
+    }
+
+    #[tokio::test]
+    async fn scale_unknown_deployment_is_not_found() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/deployments/unknown/scale")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"replicas": 2}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn scale_up_is_bounded_by_the_namespace_quota() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"name": "scale", "quota": {"max_replicas": 3}}))
+            .await;
+        let id = create(
+            &server,
+            &token,
+            json!({"runtime": "docker", "name": "web", "namespace": "scale", "image": "nginx"}),
+        )
+        .await;
+
+        let response = server
+            .post(&format!("/deployments/{}/scale", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"replicas": 4}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: Value = response.json();
+        assert_eq!(body["violations"][0]["code"], "namespace.quota.replicas");
+
+        let response = server
+            .post(&format!("/deployments/{}/scale", id))
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"replicas": 3}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+}
@@ -1,10 +1,11 @@
+use crate::api::action::namespace::defaults;
 use crate::api::action::namespace::validation::{
     NAMESPACE_NAME_MAX, NAMESPACE_NAME_MIN, NAMESPACE_NAME_PATTERN,
 };
-use crate::api::auth::Auth;
+use crate::api::auth::{Auth, require_scope};
 use crate::api::dto::namespace::NamespaceOutput;
 use crate::api::server::Db;
-use crate::api::validation::{ViolationList, problem_response};
+use crate::api::validation::{Violation, ViolationList, problem_response};
 use crate::models::audit_log;
 use crate::models::namespace;
 use axum::Json;
@@ -32,6 +33,10 @@ pub(crate) struct NamespaceInput {
         )
     )]
     name: String,
+    #[serde(default)]
+    quota: Option<namespace::Quota>,
+    #[serde(default)]
+    defaults: Option<namespace::Defaults>,
 }
 
 pub(crate) async fn create(
@@ -39,18 +44,37 @@ pub(crate) async fn create(
     auth: Auth,
     Json(input): Json<NamespaceInput>,
 ) -> Response {
+    let mut violations = ViolationList::new();
     if let Err(errs) = input.validate() {
-        let violations: ViolationList = errs.into();
+        violations.extend_from_validator(errs);
+    }
+    if let Some(Err(message)) = input.quota.as_ref().map(namespace::Quota::validate) {
+        violations.push(Violation::new("quota", message, "namespace.quota.invalid"));
+    }
+    if let Some(violation) = input.defaults.as_ref().and_then(defaults::violation) {
+        violations.push(violation);
+    }
+    if !violations.is_empty() {
         return violations.into_response();
     }
 
     // Scope (`namespaces:write`) is enforced centrally by the auth middleware.
+    // A quota is what keeps one team off another's capacity, so setting one
+    // takes the same `admin` scope as `PUT /namespaces/{name}/quota`.
+    if input.quota.is_some()
+        && let Err(response) = require_scope(&auth.source, "admin")
+    {
+        return response;
+    }
+
     let utc: DateTime<Utc> = Utc::now();
     let new_namespace = namespace::Namespace {
         id: Uuid::new_v4().to_string(),
         created_at: utc.to_string(),
         updated_at: None,
         name: input.name,
+        quota: input.quota,
+        defaults: input.defaults,
     };
 
     match namespace::create(&pool, new_namespace.clone()).await {
@@ -0,0 +1,141 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+
+use crate::api::action::deployment::create::validate_runtime;
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::namespace::NamespaceOutput;
+use crate::api::server::Db;
+use crate::api::validation::{Violation, ViolationList, problem_response};
+use crate::models::audit_log;
+use crate::models::namespace::{self, Defaults};
+
+/// `PUT /namespaces/{name}/defaults` — replace the manifest defaults of a
+/// namespace; `{}` drops them. Deployments already applied keep what they
+/// were merged with until their next apply.
+// Scope (`namespaces:write`) is enforced centrally by the auth middleware.
+pub(crate) async fn defaults(
+    Path(name): Path<String>,
+    State(pool): State<Db>,
+    auth: Auth,
+    Json(input): Json<Defaults>,
+) -> Response {
+    if let Some(violation) = violation(&input) {
+        let mut violations = ViolationList::new();
+        violations.push(violation);
+        return violations.into_response();
+    }
+    if let Err(response) = require_namespace(&auth.source, &name) {
+        return response;
+    }
+
+    let defaults = (input != Defaults::default()).then_some(&input);
+    match namespace::set_defaults(&pool, &name, defaults, &Utc::now().to_string()).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return problem_response(
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                format!("namespace '{}' does not exist", name),
+            );
+        }
+        Err(e) => {
+            error!("Failed to set the defaults of namespace '{}': {}", name, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to set namespace defaults",
+            );
+        }
+    }
+
+    let _ = audit_log::record(
+        &pool,
+        Some(&auth.user.id),
+        "update",
+        "namespace_defaults",
+        &name,
+        Some(&name),
+    )
+    .await;
+
+    match namespace::find_by_name(&pool, &name).await {
+        Ok(Some(ns)) => Json(NamespaceOutput::from_to_model(ns)).into_response(),
+        _ => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// What is wrong with `defaults`, as a `namespace.defaults.invalid` violation.
+pub(crate) fn violation(defaults: &Defaults) -> Option<Violation> {
+    let message = match &defaults.runtime {
+        Some(runtime) => validate_runtime(runtime)
+            .err()
+            .map(|e| format!("defaults.runtime: {}", e.message.unwrap_or_default())),
+        None => None,
+    }
+    .or_else(|| defaults.validate().err())?;
+    Some(Violation::new(
+        "defaults",
+        message,
+        "namespace.defaults.invalid",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::dto::namespace::NamespaceOutput;
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn defaults_are_set_validated_and_dropped() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({ "name": "team-a" }))
+            .await;
+
+        let response = server
+            .put("/namespaces/team-a/defaults")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({ "runtime": "podman", "labels": { "team": "a" } }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let defaults = response.json::<NamespaceOutput>().defaults.unwrap();
+        assert_eq!(defaults.runtime.as_deref(), Some("podman"));
+
+        for invalid in [
+            json!({ "runtime": "lxc" }),
+            json!({ "min": { "cpu": "2" }, "max": { "cpu": "1" } }),
+            json!({
+                "resources": { "limits": { "memory": "4Gi" } },
+                "max": { "memory": "1Gi" }
+            }),
+        ] {
+            let response = server
+                .put("/namespaces/team-a/defaults")
+                .add_header("Authorization", format!("Bearer {}", token))
+                .json(&invalid)
+                .await;
+            assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+            let body = response.json::<serde_json::Value>();
+            assert_eq!(body["violations"][0]["code"], "namespace.defaults.invalid");
+        }
+
+        let response = server
+            .put("/namespaces/team-a/defaults")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert!(response.json::<NamespaceOutput>().defaults.is_none());
+    }
+}
@@ -7,10 +7,12 @@ use serde_json::json;
 use crate::api::auth::Auth;
 use crate::api::server::Db;
 use crate::models::audit_log;
+use crate::models::deployment_revision;
 use crate::models::namespace;
 
 /// `DELETE /namespaces/{name}` — remove an empty namespace and purge its
-/// audit trail (retention is namespace-bound by design).
+/// audit trail and deployment revision history (retention is namespace-bound
+/// by design).
 ///
 /// Refuses with 409 if the namespace still holds live deployments, secrets
 /// or configs: we never cascade-delete an operator's resources.
@@ -70,6 +72,12 @@ pub(crate) async fn delete(
             name, e
         );
     }
+    if let Err(e) = deployment_revision::delete_by_namespace(&pool, &name).await {
+        warn!(
+            "Failed to purge the revision history of namespace '{}': {}",
+            name, e
+        );
+    }
     let _ = audit_log::record(
         &pool,
         Some(&auth.user.id),
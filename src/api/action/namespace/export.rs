@@ -0,0 +1,272 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde_json::{Map, Value};
+
+use crate::api::action::deployment::diff::manifest_spec;
+use crate::api::auth::{Auth, require_namespace, require_scope};
+use crate::api::dto::namespace::{ExportedConfig, ExportedNamespace, ManifestExport};
+use crate::api::server::Db;
+use crate::api::validation::problem_response;
+use crate::models::config;
+use crate::models::deployments::{self, Deployment, DeploymentConfig, DeploymentStatus};
+use crate::models::namespace;
+
+/// `GET /namespaces/{name}/export` — the namespace as a manifest `ring apply`
+/// takes back: the namespace and its defaults, its configs with their data,
+/// and its deployments as declared.
+///
+/// What the server owns (`id`, `status`, `instances`, `desired_replicas`, …)
+/// is left out, and so is what came from the namespace defaults, so the
+/// manifest picks up the defaults of wherever it is applied. Secrets are never
+/// exported: deployments keep their `secretRef`s, the secrets themselves must
+/// exist where the manifest lands.
+// Scope (`deployments:read`) is enforced centrally by the auth middleware.
+pub(crate) async fn export(
+    Path(name): Path<String>,
+    State(pool): State<Db>,
+    auth: Auth,
+) -> Response {
+    if let Err(response) = require_namespace(&auth.source, &name) {
+        return response;
+    }
+    // Config data is exported verbatim.
+    if let Err(response) = require_scope(&auth.source, "configs:read") {
+        return response;
+    }
+
+    let ns = match namespace::find_by_name(&pool, &name).await {
+        Ok(Some(ns)) => ns,
+        Ok(None) => {
+            return problem_response(
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                format!("namespace '{}' does not exist", name),
+            );
+        }
+        Err(e) => {
+            error!("Failed to look up namespace '{}': {}", name, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to load namespace",
+            );
+        }
+    };
+
+    let filters = HashMap::from([("namespace".to_string(), vec![name.clone()])]);
+    let (list, configs) = match tokio::try_join!(
+        deployments::find_all(&pool, filters),
+        config::find_by_namespace(&pool, &name)
+    ) {
+        Ok(found) => found,
+        Err(e) => {
+            error!("Failed to export namespace '{}': {}", name, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to export namespace",
+            );
+        }
+    };
+
+    let mut manifest = ManifestExport::default();
+    manifest.namespaces.insert(
+        ns.name.clone(),
+        ExportedNamespace {
+            name: ns.name,
+            defaults: ns.defaults,
+        },
+    );
+    for config in configs {
+        manifest.configs.insert(
+            config.name.clone(),
+            ExportedConfig {
+                namespace: config.namespace,
+                name: config.name,
+                data: config.data,
+                labels: (!config.labels.is_empty()).then_some(config.labels),
+            },
+        );
+    }
+    for deployment in declared(&list) {
+        manifest
+            .deployments
+            .insert(deployment.name.clone(), exported(deployment));
+    }
+
+    Json(manifest).into_response()
+}
+
+/// The deployments a manifest declares: the newest of each name, without the
+/// runs of a cronjob (they come from the cronjob) or what was deleted.
+fn declared(deployments: &[Deployment]) -> Vec<&Deployment> {
+    let mut newest: BTreeMap<&str, &Deployment> = BTreeMap::new();
+    for deployment in deployments
+        .iter()
+        .filter(|d| d.cronjob_id.is_none() && d.status != DeploymentStatus::Deleted)
+    {
+        let entry = newest.entry(&deployment.name).or_insert(deployment);
+        if deployment.created_at > entry.created_at {
+            *entry = deployment;
+        }
+    }
+    newest.into_values().collect()
+}
+
+/// `deployment` as a manifest entry, less what it took from the defaults,
+/// and less what it leaves at the API default: nulls, empty lists and maps,
+/// and a `config` no manifest set.
+fn exported(deployment: &Deployment) -> Value {
+    let mut spec = manifest_spec(deployment);
+    if let Value::Object(fields) = &mut spec {
+        for path in &deployment.defaulted {
+            remove_path(fields, path);
+        }
+        let unset: Option<DeploymentConfig> = serde_json::from_str("{}").ok();
+        if fields.get("config") == serde_json::to_value(unset).ok().as_ref() {
+            fields.remove("config");
+        }
+    }
+    drop_empty(&mut spec);
+    spec
+}
+
+/// Strip nulls, and the lists and maps that end up empty, all the way down.
+fn drop_empty(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.values_mut().for_each(drop_empty);
+            fields.retain(|_, v| !is_empty(v));
+        }
+        Value::Array(items) => items.iter_mut().for_each(drop_empty),
+        _ => {}
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+/// Remove the dotted `path` from `fields`, and the objects it leaves empty.
+/// A key may itself hold dots (a `labels.app.kubernetes.io/name` path), so
+/// the whole rest of the path is tried as a key before descending.
+fn remove_path(fields: &mut Map<String, Value>, path: &str) {
+    if fields.remove(path).is_some() {
+        return;
+    }
+    let Some((head, rest)) = path.split_once('.') else {
+        return;
+    };
+    if let Some(Value::Object(inner)) = fields.get_mut(head) {
+        remove_path(inner, rest);
+        if inner.is_empty() {
+            fields.remove(head);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::server::tests::{login, new_test_app};
+    use axum_test::TestServer;
+    use serde_json::json;
+
+    #[test]
+    fn removes_a_path_and_what_it_leaves_empty() {
+        let mut fields = json!({
+            "labels": {"app.kubernetes.io/name": "web", "team": "a"},
+            "resources": {"limits": {"cpu": "1"}},
+            "runtime": "docker"
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        remove_path(&mut fields, "labels.app.kubernetes.io/name");
+        remove_path(&mut fields, "resources.limits.cpu");
+        remove_path(&mut fields, "runtime");
+        assert_eq!(Value::Object(fields), json!({"labels": {"team": "a"}}));
+    }
+
+    #[tokio::test]
+    async fn export_writes_the_namespace_back_as_a_manifest() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"name": "staging", "defaults": {"labels": {"team": "a"}}}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        let response = server
+            .post("/configs")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({"namespace": "staging", "name": "nginx", "data": "{\"a\":\"b\"}"}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        for image in ["nginx:1.25", "nginx:1.27"] {
+            let response = server
+                .post("/deployments")
+                .add_header("Authorization", format!("Bearer {}", token))
+                .json(&json!({
+                    "namespace": "staging",
+                    "name": "web",
+                    "image": image,
+                    "replicas": 2,
+                    "labels": {"tier": "web"},
+                    "environment": {"DB_PASSWORD": {"secretRef": "db-password"}}
+                }))
+                .await;
+            assert_eq!(response.status_code(), StatusCode::CREATED);
+        }
+
+        let response = server
+            .get("/namespaces/staging/export")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let manifest: Value = response.json();
+        assert_eq!(
+            manifest["namespaces"]["staging"]["defaults"]["labels"]["team"],
+            "a"
+        );
+        assert_eq!(manifest["configs"]["nginx"]["data"], "{\"a\":\"b\"}");
+
+        let web = &manifest["deployments"]["web"];
+        assert_eq!(web["image"], "nginx:1.27");
+        assert_eq!(web["replicas"], 2);
+        assert_eq!(web["labels"], json!({"tier": "web"}));
+        assert_eq!(
+            web["environment"]["DB_PASSWORD"],
+            json!({"secretRef": "db-password"})
+        );
+        for stripped in [
+            "id",
+            "status",
+            "instances",
+            "desired_replicas",
+            "defaulted",
+            "config",
+            "command",
+        ] {
+            assert!(web.get(stripped).is_none(), "{} must be stripped", stripped);
+        }
+
+        let response = server
+            .get("/namespaces/missing/export")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,12 +1,20 @@
 pub(crate) mod audit;
 pub(crate) mod create;
+pub(crate) mod defaults;
 pub(crate) mod delete;
+pub(crate) mod export;
 pub(crate) mod get;
 pub(crate) mod list;
+pub(crate) mod quota;
+pub(crate) mod usage;
 pub(crate) mod validation;
 
 pub(crate) use audit::audit;
 pub(crate) use create::create;
+pub(crate) use defaults::defaults;
 pub(crate) use delete::delete;
+pub(crate) use export::export;
 pub(crate) use get::get;
 pub(crate) use list::list;
+pub(crate) use quota::quota;
+pub(crate) use usage::usage;
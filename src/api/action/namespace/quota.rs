@@ -0,0 +1,165 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+
+use crate::api::auth::Auth;
+use crate::api::dto::namespace::NamespaceOutput;
+use crate::api::server::Db;
+use crate::api::validation::{Violation, ViolationList, problem_response};
+use crate::models::audit_log;
+use crate::models::namespace::{self, Breach, Namespace, Quota, Usage};
+
+/// `PUT /namespaces/{name}/quota` — replace the quota of a namespace. Every
+/// cap is optional; `{}` lifts them all.
+///
+/// Lowering a cap below what the namespace already holds is accepted: nothing
+/// running is stopped, but the namespace cannot grow again until it is back
+/// under it.
+// Scope (`admin`) is enforced centrally by the auth middleware.
+pub(crate) async fn quota(
+    Path(name): Path<String>,
+    State(pool): State<Db>,
+    auth: Auth,
+    Json(input): Json<Quota>,
+) -> Response {
+    if let Err(message) = input.validate() {
+        let mut violations = ViolationList::new();
+        violations.push(Violation::new("quota", message, "namespace.quota.invalid"));
+        return violations.into_response();
+    }
+
+    let quota = (input != Quota::default()).then_some(&input);
+    match namespace::set_quota(&pool, &name, quota, &Utc::now().to_string()).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return problem_response(
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                format!("namespace '{}' does not exist", name),
+            );
+        }
+        Err(e) => {
+            error!("Failed to set the quota of namespace '{}': {}", name, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to set namespace quota",
+            );
+        }
+    }
+
+    let _ = audit_log::record(
+        &pool,
+        Some(&auth.user.id),
+        "update",
+        "namespace_quota",
+        &name,
+        Some(&name),
+    )
+    .await;
+
+    match namespace::find_by_name(&pool, &name).await {
+        Ok(Some(ns)) => Json(NamespaceOutput::from_to_model(ns)).into_response(),
+        _ => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// A cap a write would break, as a violation coded `namespace.quota.<cap>`.
+pub(crate) fn violation(
+    namespace: &str,
+    path: impl Into<String>,
+    breach: namespace::Breach,
+) -> Violation {
+    Violation::new(
+        path,
+        format!("quota of namespace '{}': {}", namespace, breach.message),
+        format!("namespace.quota.{}", breach.field),
+    )
+}
+
+/// Refuse a write to `ns` that `check` says its quota has no room for: the
+/// problem+json response to return, if any. Used by the secret and volume
+/// endpoints, which add one of a kind.
+pub(crate) async fn refuse(
+    pool: &Db,
+    ns: &Namespace,
+    check: impl Fn(&Quota, &Usage) -> Option<Breach>,
+) -> Option<Response> {
+    let quota = ns.quota.as_ref()?;
+    let usage = match namespace::usage(pool, &ns.name, None).await {
+        Ok(usage) => usage,
+        Err(e) => {
+            error!(
+                "Failed to compute the usage of namespace '{}': {}",
+                ns.name, e
+            );
+            return Some(problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to compute namespace usage",
+            ));
+        }
+    };
+    let breach = check(quota, &usage)?;
+    let mut violations = ViolationList::new();
+    violations.push(violation(&ns.name, "namespace", breach));
+    Some(violations.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::dto::namespace::NamespaceOutput;
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn quota_is_set_validated_and_lifted() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({ "name": "team-a" }))
+            .await;
+
+        let response = server
+            .put("/namespaces/team-a/quota")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({ "max_deployments": 2, "requests": { "memory": "1Gi" } }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let ns = response.json::<NamespaceOutput>();
+        let quota = ns.quota.expect("the quota is returned");
+        assert_eq!(quota.max_deployments, Some(2));
+
+        let response = server
+            .put("/namespaces/team-a/quota")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({ "limits": { "cpu": "lots" } }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = response.json::<serde_json::Value>();
+        assert_eq!(body["violations"][0]["code"], "namespace.quota.invalid");
+
+        let response = server
+            .put("/namespaces/team-a/quota")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        assert!(response.json::<NamespaceOutput>().quota.is_none());
+
+        let response = server
+            .put("/namespaces/nowhere/quota")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({}))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,122 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use crate::api::auth::{Auth, require_namespace};
+use crate::api::dto::namespace::UsageOutput;
+use crate::api::server::Db;
+use crate::api::validation::problem_response;
+use crate::models::namespace;
+
+/// `GET /namespaces/{name}/usage` — what the namespace holds next to its
+/// quota: deployments, replicas, volumes, secrets and the summed `limits` and
+/// `requests` of its instances.
+// Scope (`namespaces:read`) is enforced centrally by the auth middleware.
+pub(crate) async fn usage(
+    Path(name): Path<String>,
+    State(pool): State<Db>,
+    auth: Auth,
+) -> Response {
+    if let Err(response) = require_namespace(&auth.source, &name) {
+        return response;
+    }
+
+    let ns = match namespace::find_by_name(&pool, &name).await {
+        Ok(Some(ns)) => ns,
+        Ok(None) => {
+            return problem_response(
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                format!("namespace '{}' does not exist", name),
+            );
+        }
+        Err(e) => {
+            error!("Failed to look up namespace '{}': {}", name, e);
+            return problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to load namespace",
+            );
+        }
+    };
+
+    match namespace::usage(&pool, &name, None).await {
+        Ok(usage) => Json(UsageOutput::from_to_model(ns, usage)).into_response(),
+        Err(e) => {
+            error!("Failed to compute the usage of namespace '{}': {}", name, e);
+            problem_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                "failed to compute namespace usage",
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::dto::namespace::UsageOutput;
+    use crate::api::server::tests::{login, new_test_app};
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn usage_sums_the_namespace_against_its_quota() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "name": "team-a",
+                "quota": { "max_replicas": 10, "limits": { "cpu": "4" } }
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let response = server
+            .post("/deployments")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&json!({
+                "runtime": "docker",
+                "namespace": "team-a",
+                "name": "api",
+                "image": "nginx:latest",
+                "replicas": 3,
+                "resources": { "limits": { "cpu": "500m", "memory": "256Mi" } }
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let response = server
+            .get("/namespaces/team-a/usage")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+        let usage = response.json::<UsageOutput>();
+        assert_eq!(usage.quota.max_replicas, Some(10));
+        assert_eq!(usage.used.deployments, 1);
+        assert_eq!(usage.used.replicas, 3);
+        assert_eq!(usage.used.limits.cpu.as_deref(), Some("1500m"));
+        assert_eq!(usage.used.limits.memory.as_deref(), Some("768Mi"));
+        // An undeclared request counts as its limit.
+        assert_eq!(usage.used.requests.cpu.as_deref(), Some("1500m"));
+    }
+
+    #[tokio::test]
+    async fn usage_of_an_unknown_namespace_is_not_found() {
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        let response = server
+            .get("/namespaces/nowhere/usage")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    }
+}
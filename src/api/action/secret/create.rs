@@ -1,3 +1,4 @@
+use crate::api::action::namespace::quota;
 use crate::api::action::namespace::validation::{
     NAMESPACE_NAME_MAX, NAMESPACE_NAME_MIN, NAMESPACE_NAME_PATTERN,
 };
@@ -16,6 +17,7 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -56,6 +58,8 @@ pub(crate) struct SecretInput {
         message = "must be 1 to 1048576 bytes (1 MiB)"
     ))]
     value: String,
+    #[serde(default)]
+    labels: HashMap<String, String>,
 }
 
 #[derive(Serialize)]
@@ -64,6 +68,8 @@ struct SecretOutput {
     created_at: String,
     namespace: String,
     name: String,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    labels: HashMap<String, String>,
 }
 
 pub(crate) async fn create(
@@ -99,7 +105,13 @@ pub(crate) async fn create(
                 "failed to verify namespace",
             );
         }
-        Ok(Some(_)) => {}
+        Ok(Some(ns)) => {
+            if let Some(response) =
+                quota::refuse(&pool, &ns, |quota, usage| quota.check_secrets(usage)).await
+            {
+                return response;
+            }
+        }
     }
 
     let encrypted_value = secret::encrypt_value(&input.value);
@@ -111,6 +123,7 @@ pub(crate) async fn create(
         namespace: input.namespace,
         name: input.name,
         value: encrypted_value,
+        labels: input.labels,
     };
 
     match secret::create(&pool, &new_secret).await {
@@ -129,6 +142,7 @@ pub(crate) async fn create(
                 created_at: new_secret.created_at,
                 namespace: new_secret.namespace,
                 name: new_secret.name,
+                labels: new_secret.labels,
             };
             (StatusCode::CREATED, Json(output)).into_response()
         }
@@ -173,6 +187,66 @@ mod tests {
             .await;
     }
 
+    #[tokio::test]
+    async fn create_secret_respects_the_namespace_quota() {
+        set_test_key();
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        server
+            .post("/namespaces")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({ "name": "team-a", "quota": { "max_secrets": 1 } }))
+            .await;
+
+        let secret = |name: &str| serde_json::json!({ "namespace": "team-a", "name": name, "value": "s3cr3t" });
+        let response = server
+            .post("/secrets")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&secret("db-password"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let response = server
+            .post("/secrets")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&secret("api-key"))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["violations"][0]["code"], "namespace.quota.secrets");
+    }
+
+    #[tokio::test]
+    async fn create_secret_with_labels_lists_them() {
+        set_test_key();
+        let app = new_test_app().await;
+        let token = login(app.clone(), "admin", "changeme").await;
+        let server = TestServer::new(app).unwrap();
+
+        create_namespace(&server, &token, "production").await;
+
+        let response = server
+            .post("/secrets")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({
+                "namespace": "production",
+                "name": "db-password",
+                "value": "super-secret",
+                "labels": {"ring.owner": "payments"}
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+
+        let response = server
+            .get("/secrets")
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
+        let secrets: Vec<serde_json::Value> = response.json();
+        assert_eq!(secrets[0]["labels"]["ring.owner"], "payments");
+    }
+
     #[tokio::test]
     async fn create_secret() {
         set_test_key();
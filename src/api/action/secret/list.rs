@@ -48,6 +48,8 @@ struct SecretOutput {
     updated_at: Option<String>,
     namespace: String,
     name: String,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    labels: HashMap<String, String>,
 }
 
 pub(crate) async fn list(
@@ -83,6 +85,7 @@ pub(crate) async fn list(
             updated_at: s.updated_at,
             namespace: s.namespace,
             name: s.name,
+            labels: s.labels,
         })
         .collect();
 
@@ -1,3 +1,4 @@
+use crate::api::action::namespace::quota;
 use crate::api::action::namespace::validation::{
     NAMESPACE_NAME_MAX, NAMESPACE_NAME_MIN, NAMESPACE_NAME_PATTERN,
 };
@@ -117,7 +118,13 @@ pub(crate) async fn create(
                 "failed to verify namespace",
             );
         }
-        Ok(Some(_)) => {}
+        Ok(Some(ns)) => {
+            if let Some(response) =
+                quota::refuse(&pool, &ns, |quota, usage| quota.check_volumes(usage, 1)).await
+            {
+                return response;
+            }
+        }
     }
 
     // host_path is the Docker volume name / directory name once provisioned;
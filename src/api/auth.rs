@@ -115,7 +115,19 @@ fn scope_for_route(method: &Method, matched_path: &str) -> Option<&'static str>
         "/deployments/{id}/events"
         | "/deployments/{id}/health-checks"
         | "/deployments/{id}/metrics"
-        | "/deployments/{id}/logs" => Some("deployments:read"),
+        | "/deployments/{id}/logs"
+        | "/deployments/{id}/revisions"
+        | "/deployments/{id}/runs" => Some("deployments:read"),
+        // Imperative actions on an existing deployment are writes.
+        "/deployments/{id}/rollback"
+        | "/deployments/{id}/scale"
+        | "/deployments/{id}/restart"
+        | "/deployments/{id}/pause"
+        | "/deployments/{id}/resume"
+        | "/deployments/{id}/promote"
+        | "/deployments/{id}/abort" => Some("deployments:write"),
+        // A shell inside an instance: neither a read nor a plain write.
+        "/deployments/{id}/exec" => Some("deployments:exec"),
         // Node info is host-level; gate it behind the same read scope as
         // deployments (there is no dedicated node scope).
         "/node/get" => Some("deployments:read"),
@@ -124,7 +136,14 @@ fn scope_for_route(method: &Method, matched_path: &str) -> Option<&'static str>
         "/namespaces" => Some("namespaces:write"),
         "/namespaces/{id}" if is_read => Some("namespaces:read"),
         "/namespaces/{id}" => Some("namespaces:write"),
-        "/namespaces/{id}/audit" => Some("namespaces:read"),
+        "/namespaces/{id}/audit" | "/namespaces/{id}/usage" => Some("namespaces:read"),
+        // Raising its own quota would defeat the point of one: only an admin
+        // sets it.
+        "/namespaces/{id}/quota" => Some("admin"),
+        "/namespaces/{id}/defaults" => Some("namespaces:write"),
+        // An export is the namespace's deployments as a manifest; the handler
+        // also asks for `configs:read`, their data being part of it.
+        "/namespaces/{id}/export" => Some("deployments:read"),
         // Volumes. These were previously unmapped, which deny-by-default turned
         // into admin-only — so an `operator` could not manage the volumes of the
         // workloads it is meant to administer.
@@ -600,6 +619,47 @@ mod tests {
             scope_for_route(&Method::GET, "/deployments/{id}/logs"),
             Some("deployments:read")
         );
+        assert_eq!(
+            scope_for_route(&Method::GET, "/deployments/{id}/revisions"),
+            Some("deployments:read")
+        );
+        assert_eq!(
+            scope_for_route(&Method::GET, "/deployments/{id}/runs"),
+            Some("deployments:read")
+        );
+        assert_eq!(
+            scope_for_route(&Method::POST, "/deployments/{id}/rollback"),
+            Some("deployments:write")
+        );
+        assert_eq!(
+            scope_for_route(&Method::POST, "/deployments/{id}/scale"),
+            Some("deployments:write")
+        );
+        assert_eq!(
+            scope_for_route(&Method::POST, "/deployments/{id}/restart"),
+            Some("deployments:write")
+        );
+        assert_eq!(
+            scope_for_route(&Method::POST, "/deployments/{id}/pause"),
+            Some("deployments:write")
+        );
+        assert_eq!(
+            scope_for_route(&Method::POST, "/deployments/{id}/resume"),
+            Some("deployments:write")
+        );
+        assert_eq!(
+            scope_for_route(&Method::POST, "/deployments/{id}/promote"),
+            Some("deployments:write")
+        );
+        assert_eq!(
+            scope_for_route(&Method::POST, "/deployments/{id}/abort"),
+            Some("deployments:write")
+        );
+        // Exec is a GET (the WebSocket upgrade) yet never a read.
+        assert_eq!(
+            scope_for_route(&Method::GET, "/deployments/{id}/exec"),
+            Some("deployments:exec")
+        );
         // Token lifecycle and ticket minting require admin (no escalation via
         // rotate/revoke with a lesser scope).
         assert_eq!(scope_for_route(&Method::POST, "/tokens"), Some("admin"));
@@ -615,6 +675,19 @@ mod tests {
             scope_for_route(&Method::POST, "/auth/stream-ticket"),
             Some("admin")
         );
+        // A namespace reads its usage but only an admin sets its quota.
+        assert_eq!(
+            scope_for_route(&Method::GET, "/namespaces/{id}/usage"),
+            Some("namespaces:read")
+        );
+        assert_eq!(
+            scope_for_route(&Method::PUT, "/namespaces/{id}/quota"),
+            Some("admin")
+        );
+        assert_eq!(
+            scope_for_route(&Method::GET, "/namespaces/{id}/export"),
+            Some("deployments:read")
+        );
         // Unmapped routes return None → middleware denies PATs by default.
         assert_eq!(scope_for_route(&Method::GET, "/some/new/route"), None);
     }
@@ -44,8 +44,8 @@ pub(crate) struct DeploymentOutput {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) autoscale: Option<crate::models::deployments::Autoscale>,
     /// The count Ring is actually reconciling towards, i.e. what the runtimes
-    /// use. Absent when the deployment is not autoscaled, in which case
-    /// `replicas` is the target.
+    /// use. Absent when the deployment is neither autoscaled nor asleep, in
+    /// which case `replicas` is the target.
     ///
     /// This is the *effective* value, not the raw stored decision: a policy
     /// edited to a narrower range applies immediately, so reporting the stored
@@ -76,6 +76,62 @@ pub(crate) struct DeploymentOutput {
     pub(crate) parent_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) network: Option<NetworkConfig>,
+    /// Set during a rolling restart: the instances still to be replaced.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) pending_restart: Vec<String>,
+    /// Reconciliation is suspended: the scheduler leaves the deployment alone.
+    #[serde(default)]
+    pub(crate) paused: bool,
+    /// Rollout strategy, absent when the default (rolling, max_surge 1,
+    /// max_unavailable 0) applies.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) strategy: Option<crate::models::deployments::Strategy>,
+    /// Schedule of a `kind: cronjob`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) cron: Option<crate::models::deployments::Cron>,
+    /// Last trigger a cronjob acted on, absent until it first fires.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) last_scheduled_at: Option<String>,
+    /// Next trigger of a cronjob (RFC 3339), computed from its schedule.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) next_scheduled_at: Option<String>,
+    /// On a cronjob run: the cronjob that spawned it.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) cronjob_id: Option<String>,
+    /// Retry, deadline and completion policy of a job.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) job: Option<crate::models::deployments::JobSpec>,
+    /// Exit code of a job's last finished attempt, absent until one exits.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) exit_code: Option<i64>,
+    /// Deployments of the namespace this one waits for before it starts.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) depends_on: Vec<crate::models::deployments::Dependency>,
+    /// Containers run to completion before each instance's main container.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) init_containers: Vec<crate::models::deployments::ContainerSpec>,
+    /// Containers running next to each instance's main container.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) sidecars: Vec<crate::models::deployments::ContainerSpec>,
+    /// Stop signal, grace period and hooks, absent for the runtime defaults.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) lifecycle: Option<crate::models::deployments::Lifecycle>,
+    /// How exits and failed starts are retried, absent for the defaults.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) restart_policy: Option<crate::models::deployments::RestartPolicy>,
+    /// Scale-to-zero policy, absent for a deployment that always runs.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) idle: Option<crate::models::deployments::Idle>,
+    /// How the output is parsed, absent to detect JSON lines.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) logging: Option<crate::models::deployments::Logging>,
+    /// Scaled to zero by its `idle` policy until the next connection.
+    #[serde(default)]
+    pub(crate) asleep: bool,
+    /// Fields filled from the namespace defaults, e.g. `runtime` or
+    /// `labels.team`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) defaulted: Vec<String>,
 }
 
 impl DeploymentOutput {
@@ -83,10 +139,14 @@ impl DeploymentOutput {
         // Computed before the struct is taken apart below: `target_replicas()`
         // needs the whole deployment, and reports the effective count rather
         // than the raw stored decision.
-        let effective_target = deployment
-            .autoscale
-            .as_ref()
-            .map(|_| deployment.target_replicas());
+        let effective_target = (deployment.autoscale.is_some() || deployment.asleep())
+            .then(|| deployment.target_replicas());
+        let asleep = deployment.asleep();
+
+        let next_scheduled_at = deployment.cron.as_ref().and_then(|cron| {
+            let anchor = deployment.schedule_anchor()?;
+            cron.next_after(&anchor).map(|next| next.to_rfc3339())
+        });
 
         let labels: HashMap<String, String> = deployment.labels;
         let environment: HashMap<String, EnvValue> = deployment.environment;
@@ -126,6 +186,24 @@ impl DeploymentOutput {
             image_digest: deployment.image_digest,
             parent_id: deployment.parent_id,
             network: deployment.network,
+            pending_restart: deployment.pending_restart,
+            paused: deployment.paused,
+            strategy: deployment.strategy,
+            cron: deployment.cron,
+            last_scheduled_at: deployment.last_scheduled_at,
+            next_scheduled_at,
+            cronjob_id: deployment.cronjob_id,
+            job: deployment.job,
+            exit_code: deployment.exit_code,
+            depends_on: deployment.depends_on,
+            init_containers: deployment.init_containers,
+            sidecars: deployment.sidecars,
+            lifecycle: deployment.lifecycle,
+            restart_policy: deployment.restart_policy,
+            idle: deployment.idle,
+            logging: deployment.logging,
+            asleep,
+            defaulted: deployment.defaulted,
         }
     }
 }
@@ -150,3 +228,37 @@ pub(crate) struct DeploymentVolume {
     pub(crate) driver: String,
     pub(crate) permission: String,
 }
+
+/// What `POST /deployments?dry_run=true` found: the action an apply of the
+/// same body would take, and the fields it would change. Nothing is stored.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct DeploymentPlan {
+    pub(crate) namespace: String,
+    pub(crate) name: String,
+    /// `create`, `rolling_update` or `replace`.
+    pub(crate) action: String,
+    /// Why a `replace` does not roll: `force`, `cronjob`, `no_health_checks`,
+    /// `host_port_published` or `multiple_active_deployments`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) reason: Option<String>,
+    /// The active deployments the apply would supersede.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) replaces: Vec<String>,
+    /// The namespace does not exist yet and would be created.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub(crate) creates_namespace: bool,
+    /// Changes against the newest active deployment, empty when the spec is
+    /// the same. Every field, for a `create`.
+    pub(crate) changes: Vec<FieldChange>,
+}
+
+/// One field of a [`DeploymentPlan`], as a dotted path into the spec.
+/// `from` is absent for an addition, `to` for a removal.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct FieldChange {
+    pub(crate) path: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) from: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) to: Option<serde_json::Value>,
+}
@@ -0,0 +1,58 @@
+//! Wire format of `GET /deployments/{id}/exec` once upgraded to a WebSocket.
+//!
+//! Every message is binary: one channel byte, then the payload. Shared by the
+//! API handler and `ring deployment exec` so both ends agree by construction.
+
+use serde::{Deserialize, Serialize};
+
+/// Client to server: bytes for the process' stdin. An empty payload closes it.
+pub(crate) const CHANNEL_STDIN: u8 = 0;
+/// Server to client: the process' stdout (the terminal's output with a TTY).
+pub(crate) const CHANNEL_STDOUT: u8 = 1;
+/// Server to client: the process' stderr. Never used with a TTY.
+pub(crate) const CHANNEL_STDERR: u8 = 2;
+/// Server to client: an [`ExecStatus`] as JSON, the last message before close.
+pub(crate) const CHANNEL_STATUS: u8 = 3;
+/// Client to server: a `{"cols": …, "rows": …}` terminal resize as JSON.
+pub(crate) const CHANNEL_RESIZE: u8 = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExecStatus {
+    /// `null` when the runtime could not tell how the process ended.
+    pub(crate) exit_code: Option<i64>,
+}
+
+pub(crate) fn encode(channel: u8, payload: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(payload.len() + 1);
+    message.push(channel);
+    message.extend_from_slice(payload);
+    message
+}
+
+/// Splits a message into its channel and payload; `None` for an empty one.
+pub(crate) fn decode(message: &[u8]) -> Option<(u8, &[u8])> {
+    message
+        .split_first()
+        .map(|(channel, payload)| (*channel, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_message_round_trips() {
+        let message = encode(CHANNEL_STDOUT, b"hello");
+        assert_eq!(message, b"\x01hello");
+        assert_eq!(
+            decode(&message),
+            Some((CHANNEL_STDOUT, b"hello".as_slice()))
+        );
+        // A bare channel byte is an empty payload: end of stdin.
+        assert_eq!(
+            decode(&[CHANNEL_STDIN]),
+            Some((CHANNEL_STDIN, [].as_slice()))
+        );
+        assert_eq!(decode(&[]), None);
+    }
+}
@@ -1,7 +1,10 @@
 pub(crate) mod audit;
 pub(crate) mod config;
 pub(crate) mod deployment;
+pub(crate) mod exec;
 pub(crate) mod namespace;
 pub(crate) mod node;
+pub(crate) mod revision;
+pub(crate) mod run;
 pub(crate) mod stats;
 pub(crate) mod user;
@@ -1,5 +1,7 @@
-use crate::models::namespace::Namespace;
+use crate::models::deployments::ResourceSpec;
+use crate::models::namespace::{Defaults, Namespace, Quota, Usage};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub(crate) struct NamespaceOutput {
@@ -7,6 +9,10 @@ pub(crate) struct NamespaceOutput {
     pub created_at: String,
     pub updated_at: Option<String>,
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<Quota>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<Defaults>,
 }
 
 impl NamespaceOutput {
@@ -16,6 +22,70 @@ impl NamespaceOutput {
             created_at: namespace.created_at,
             updated_at: Option::from(namespace.updated_at.unwrap_or_default()),
             name: namespace.name,
+            quota: namespace.quota,
+            defaults: namespace.defaults,
         }
     }
 }
+
+/// `GET /namespaces/{name}/usage`: what the namespace holds next to its quota,
+/// amounts in the manifest notation.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct UsageOutput {
+    pub namespace: String,
+    pub quota: Quota,
+    pub used: UsedOutput,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct UsedOutput {
+    pub deployments: u32,
+    pub replicas: u32,
+    pub volumes: u32,
+    pub secrets: u32,
+    pub limits: ResourceSpec,
+    pub requests: ResourceSpec,
+}
+
+impl UsageOutput {
+    pub fn from_to_model(namespace: Namespace, usage: Usage) -> Self {
+        UsageOutput {
+            namespace: namespace.name,
+            quota: namespace.quota.unwrap_or_default(),
+            used: UsedOutput {
+                deployments: usage.deployments,
+                replicas: usage.replicas,
+                volumes: usage.volumes,
+                secrets: usage.secrets,
+                limits: usage.limits.to_spec(),
+                requests: usage.requests.to_spec(),
+            },
+        }
+    }
+}
+
+/// `GET /namespaces/{name}/export`: the namespace as a manifest `ring apply`
+/// takes back, keyed by name.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub(crate) struct ManifestExport {
+    pub namespaces: BTreeMap<String, ExportedNamespace>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub configs: BTreeMap<String, ExportedConfig>,
+    pub deployments: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct ExportedNamespace {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<Defaults>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct ExportedConfig {
+    pub namespace: String,
+    pub name: String,
+    pub data: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<String>,
+}
@@ -0,0 +1,32 @@
+use crate::models::deployment_revision::DeploymentRevision;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct RevisionOutput {
+    pub revision: i64,
+    pub deployment_id: String,
+    pub created_at: String,
+    /// The revision this one restored, when it was produced by a rollback.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rollback_of: Option<i64>,
+    /// The spec as accepted by `POST /deployments`.
+    pub spec: serde_json::Value,
+}
+
+impl RevisionOutput {
+    pub fn from_to_model(revision: DeploymentRevision) -> Self {
+        RevisionOutput {
+            revision: revision.revision,
+            deployment_id: revision.deployment_id,
+            created_at: revision.created_at,
+            rollback_of: revision.rollback_of,
+            spec: serde_json::from_str(&revision.spec).unwrap_or_else(|e| {
+                warn!(
+                    "Failed to parse the spec of revision {} of {}/{}: {}",
+                    revision.revision, revision.namespace, revision.name, e
+                );
+                serde_json::Value::Null
+            }),
+        }
+    }
+}
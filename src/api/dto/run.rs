@@ -0,0 +1,35 @@
+use crate::models::deployments::Deployment;
+use crate::scheduler::cronjob;
+use serde::{Deserialize, Serialize};
+
+/// One run of a cronjob: the `kind: job` deployment a trigger spawned.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct RunOutput {
+    /// Deployment id of the run, for `ring deployment logs|events|inspect`.
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub restart_count: u32,
+    pub created_at: String,
+    /// When the run reached its final status, absent while it is active.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub finished_at: Option<String>,
+}
+
+impl RunOutput {
+    pub fn from_to_model(run: Deployment) -> Self {
+        let finished_at = if cronjob::is_active(&run) {
+            None
+        } else {
+            run.updated_at.clone()
+        };
+        RunOutput {
+            id: run.id,
+            name: run.name,
+            status: run.status.to_string(),
+            restart_count: run.restart_count,
+            created_at: run.created_at,
+            finished_at,
+        }
+    }
+}
@@ -17,14 +17,24 @@ use crate::api::action::logout::logout;
 use crate::api::action::stream_ticket::stream_ticket;
 use crate::config::config::Config;
 
+use crate::api::action::deployment::abort as deployment_abort;
 use crate::api::action::deployment::create as deployment_create;
 use crate::api::action::deployment::delete as deployment_delete;
+use crate::api::action::deployment::exec as deployment_exec;
 use crate::api::action::deployment::get as deployment_get;
 use crate::api::action::deployment::get_deployment_events;
 use crate::api::action::deployment::get_deployment_metrics;
 use crate::api::action::deployment::get_health_checks;
 use crate::api::action::deployment::list as deployment_list;
 use crate::api::action::deployment::logs as deployment_logs;
+use crate::api::action::deployment::pause as deployment_pause;
+use crate::api::action::deployment::promote as deployment_promote;
+use crate::api::action::deployment::restart as deployment_restart;
+use crate::api::action::deployment::resume as deployment_resume;
+use crate::api::action::deployment::revisions as deployment_revisions;
+use crate::api::action::deployment::rollback as deployment_rollback;
+use crate::api::action::deployment::runs as deployment_runs;
+use crate::api::action::deployment::scale as deployment_scale;
 
 use crate::api::action::config::create as config_create;
 use crate::api::action::config::delete as config_delete;
@@ -35,9 +45,13 @@ use crate::api::action::node::get as node_get;
 
 use crate::api::action::namespace::audit as namespace_audit;
 use crate::api::action::namespace::create as namespace_create;
+use crate::api::action::namespace::defaults as namespace_defaults;
 use crate::api::action::namespace::delete as namespace_delete;
+use crate::api::action::namespace::export as namespace_export;
 use crate::api::action::namespace::get as namespace_get;
 use crate::api::action::namespace::list as namespace_list;
+use crate::api::action::namespace::quota as namespace_quota;
+use crate::api::action::namespace::usage as namespace_usage;
 
 use crate::api::action::user::create::create as user_create;
 use crate::api::action::user::delete::delete as user_delete;
@@ -106,11 +120,12 @@ pub(crate) fn router(state: AppState) -> Router {
         .route("/healthz", get(healthz))
         .route("/metrics", get(metrics));
 
-    // SSE: protected, but NO timeout layer — a tower Timeout would kill the
-    // long-lived stream. The auth middleware only wraps the request→response
-    // head, so it doesn't interfere with the streaming body.
+    // SSE and WebSocket: protected, but NO timeout layer — a tower Timeout
+    // would kill the long-lived stream. The auth middleware only wraps the
+    // request→response head, so it doesn't interfere with the streaming body.
     let streaming_routes = Router::new()
         .route("/deployments/{id}/logs", get(deployment_logs))
+        .route("/deployments/{id}/exec", get(deployment_exec))
         .route_layer(from_fn_with_state(state.clone(), auth_middleware));
 
     // All other routes: protected + 10s timeout.
@@ -125,6 +140,15 @@ pub(crate) fn router(state: AppState) -> Router {
         .route("/deployments/{id}/events", get(get_deployment_events))
         .route("/deployments/{id}/health-checks", get(get_health_checks))
         .route("/deployments/{id}/metrics", get(get_deployment_metrics))
+        .route("/deployments/{id}/revisions", get(deployment_revisions))
+        .route("/deployments/{id}/runs", get(deployment_runs))
+        .route("/deployments/{id}/rollback", post(deployment_rollback))
+        .route("/deployments/{id}/scale", post(deployment_scale))
+        .route("/deployments/{id}/restart", post(deployment_restart))
+        .route("/deployments/{id}/pause", post(deployment_pause))
+        .route("/deployments/{id}/resume", post(deployment_resume))
+        .route("/deployments/{id}/promote", post(deployment_promote))
+        .route("/deployments/{id}/abort", post(deployment_abort))
         .route("/node/get", get(node_get))
         .route("/namespaces", get(namespace_list).post(namespace_create))
         .route(
@@ -132,6 +156,10 @@ pub(crate) fn router(state: AppState) -> Router {
             get(namespace_get).delete(namespace_delete),
         )
         .route("/namespaces/{id}/audit", get(namespace_audit))
+        .route("/namespaces/{id}/usage", get(namespace_usage))
+        .route("/namespaces/{id}/export", get(namespace_export))
+        .route("/namespaces/{id}/quota", put(namespace_quota))
+        .route("/namespaces/{id}/defaults", put(namespace_defaults))
         .route("/configs", get(config_list).post(config_create))
         .route(
             "/configs/{id}",
@@ -47,20 +47,34 @@ pub(crate) struct ProblemDetails {
 /// to an exit code with `exit_code::from_http_status`.
 pub(crate) async fn render_response_error(context: &str, response: Response) -> u16 {
     let status = response.status();
-    let status_u16 = status.as_u16();
-    let is_problem = response
+    let content_type = response
         .headers()
         .get(reqwest::header::CONTENT_TYPE)
         .and_then(|v| v.to_str().ok())
-        .map(|ct| ct.starts_with("application/problem+json"))
-        .unwrap_or(false);
+        .map(str::to_string);
 
     // Consume the body — once. We get the bytes first because we need both
     // the structured parse (when problem+json) and a fallback raw-text
     // print (when the structured parse fails).
     let body = response.bytes().await.unwrap_or_default();
 
-    if is_problem && let Ok(problem) = serde_json::from_slice::<ProblemDetails>(&body) {
+    render_error_body(context, status, content_type.as_deref(), &body)
+}
+
+/// [`render_response_error`] for a response already read, such as the one a
+/// refused WebSocket upgrade leaves behind.
+pub(crate) fn render_error_body(
+    context: &str,
+    status: http::StatusCode,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> u16 {
+    let status_u16 = status.as_u16();
+    let is_problem = content_type
+        .map(|ct| ct.starts_with("application/problem+json"))
+        .unwrap_or(false);
+
+    if is_problem && let Ok(problem) = serde_json::from_slice::<ProblemDetails>(body) {
         // Title line: `<context>: <title> (<status>)`. e.g.
         // `Unable to create user: Validation failed (422)`.
         let title = if problem.title.is_empty() {
@@ -88,7 +102,7 @@ pub(crate) async fn render_response_error(context: &str, response: Response) ->
     // it's plausibly text. Avoids drowning the terminal in binary on a
     // 502 from a misconfigured proxy.
     eprintln!("{}: {}", context, status);
-    let text = String::from_utf8_lossy(&body);
+    let text = String::from_utf8_lossy(body);
     let trimmed = text.trim();
     if !trimmed.is_empty() && trimmed.len() <= 2_000 {
         eprintln!("{}", trimmed);
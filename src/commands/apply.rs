@@ -1,3 +1,5 @@
+use crate::api::dto::config::ConfigOutput;
+use crate::api::dto::deployment::{DeploymentOutput, DeploymentPlan};
 use crate::cli::problem_json::render_response_error;
 use crate::config::auth::load_auth_config;
 use crate::config::config::{Config, get_config_dir};
@@ -7,7 +9,7 @@ use clap::{Arg, ArgAction, ArgMatches, Command};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, to_string_pretty};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::error::Error;
 use std::fmt;
@@ -47,7 +49,8 @@ struct Deployment {
     #[serde(default)]
     namespace: String,
 
-    #[serde(default = "default_runtime")]
+    // Left empty to take the namespace's default runtime, or docker.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     runtime: String,
 
     #[serde(default = "default_kind")]
@@ -82,6 +85,47 @@ struct Deployment {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     autoscale: Option<Autoscale>,
 
+    /// How a new version replaces the running one. Absent means the server's
+    /// default (rolling, one extra instance at a time).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strategy: Option<Strategy>,
+
+    /// Schedule of a `kind: cronjob`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cron: Option<Cron>,
+
+    /// Retries, deadline, parallelism and cleanup of a job or of a cronjob's
+    /// runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    job: Option<Job>,
+
+    /// Deployments of the namespace to wait for before starting this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<Dependency>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    init_containers: Vec<ExtraContainer>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sidecars: Vec<ExtraContainer>,
+
+    /// Stop signal, grace period and hooks. Absent means the runtime defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lifecycle: Option<Lifecycle>,
+
+    /// Which exits are restarted, how often and how far apart. Absent means
+    /// the server defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    restart_policy: Option<RestartPolicy>,
+
+    /// Scale to zero after a quiet period, wake on the next connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idle: Option<Idle>,
+
+    /// How the output is parsed: JSON/logfmt fields, multi-line entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    logging: Option<Logging>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     health_checks: Vec<HealthCheck>,
 
@@ -170,7 +214,167 @@ struct Port {
 struct Autoscale {
     min: u32,
     max: u32,
-    target_cpu: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target_cpu: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target_memory: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    metrics: Vec<AutoscaleMetric>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cooldown_up: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cooldown_down: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tolerance: Option<f64>,
+}
+
+/// One `autoscale.metrics` entry: a gauge scraped from the instances.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct AutoscaleMetric {
+    name: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    labels: HashMap<String, String>,
+    port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    target: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+}
+
+/// Rollout strategy as written in a manifest. Unset numbers are left out of
+/// the payload so the server's defaults apply.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Strategy {
+    Rolling {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_surge: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_unavailable: Option<u32>,
+    },
+    Recreate,
+    Canary {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        instances: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        analysis_window: Option<String>,
+    },
+}
+
+/// Cronjob schedule as written in a manifest. Unset fields are left out of the
+/// payload so the server's defaults apply; the server also parses the schedule.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct Cron {
+    schedule: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    concurrency_policy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    history_limit: Option<u32>,
+}
+
+/// One `init_containers:` or `sidecars:` entry.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct ExtraContainer {
+    name: String,
+    image: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    command: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    environment: HashMap<String, EnvValue>,
+}
+
+/// The `lifecycle:` block. Durations stay strings so the server reports a
+/// malformed one.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct Lifecycle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stop_signal: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stop_grace_period: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pre_stop: Option<LifecycleHook>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    post_start: Option<LifecycleHook>,
+}
+
+/// A `pre_stop:` or `post_start:` hook: `command` or `http`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct LifecycleHook {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    command: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    http: Option<HttpHook>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct HttpHook {
+    port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+/// The `restart_policy:` block. The mode and durations stay strings so the
+/// server reports a malformed one.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct RestartPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_restarts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backoff: Option<RestartBackoff>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct RestartBackoff {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    initial: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max: Option<String>,
+}
+
+/// The `idle:` block. Durations stay strings so the server reports a
+/// malformed one.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct Idle {
+    after: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wake_timeout: Option<String>,
+}
+
+/// The `logging:` block, checked by the server.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct Logging {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    multiline_pattern: Option<String>,
+}
+
+/// One `depends_on:` entry. The condition is left out when unset so the
+/// server's default (`started`) applies.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct Dependency {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    condition: Option<String>,
+}
+
+/// Job policy as written in a manifest. Unset fields are left out of the
+/// payload so the server's defaults apply.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct Job {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backoff_limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_deadline: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parallelism: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completions: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl_after_finished: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -272,6 +476,11 @@ fn default_permission() -> String {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct NamespaceDefinition {
     name: String,
+    // What deployments in the namespace inherit when they leave it out, and
+    // the resource bounds they must stay within. Passed through as is to
+    // `PUT /namespaces/{name}/defaults`, which validates it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    defaults: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -298,18 +507,126 @@ struct ConfigDefinition {
     labels: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct SecretDefinition {
+    namespace: String,
+    name: String,
+    // `$VAR` is interpolated, so the value can come from `--env-file` or the
+    // environment instead of sitting in the manifest.
+    value: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    labels: HashMap<String, String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ConfigFile {
     #[serde(default)]
     namespaces: HashMap<String, NamespaceDefinition>,
     #[serde(default)]
     configs: HashMap<String, ConfigDefinition>,
+    #[serde(default)]
+    secrets: HashMap<String, SecretDefinition>,
     deployments: HashMap<String, Deployment>,
 }
 
-fn default_runtime() -> String {
-    "docker".to_string()
+/// Label `ring apply --owner` stamps on what it creates, so `--prune` can
+/// tell what a manifest owns.
+const OWNER_LABEL: &str = "ring.owner";
+
+/// Config labels are one free-form string: the owner is added as a
+/// `ring.owner=<owner>` pair, comma separated from what is already there.
+fn config_labels_with_owner(labels: Option<&str>, owner: &str) -> String {
+    let pair = format!("{}={}", OWNER_LABEL, owner);
+    match labels.map(str::trim) {
+        Some(labels) if !labels.is_empty() => format!("{},{}", labels, pair),
+        _ => pair,
+    }
+}
+
+fn config_owner(labels: &str) -> Option<&str> {
+    labels.split(',').find_map(|pair| {
+        pair.trim()
+            .strip_prefix(OWNER_LABEL)
+            .and_then(|rest| rest.strip_prefix('='))
+    })
+}
+
+/// A secret as `GET /secrets` lists it.
+#[derive(Debug, Deserialize)]
+struct ListedSecret {
+    id: String,
+    namespace: String,
+    name: String,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+}
+
+/// A resource carrying the owner that the manifest no longer declares.
+#[derive(Debug, Clone, PartialEq)]
+struct Orphan {
+    /// `deployment`, `config` or `secret`, also the order they are pruned in:
+    /// deployments first, so the configs and secrets are no longer in use.
+    kind: &'static str,
+    id: String,
+    namespace: String,
+    name: String,
 }
+
+/// What `owner` holds on the server that is not in `kept`, keyed by kind,
+/// namespace and name. The runs of a cronjob go with their cronjob.
+fn find_orphans(
+    owner: &str,
+    kept: &HashSet<(&'static str, String, String)>,
+    deployments: Vec<DeploymentOutput>,
+    configs: Vec<ConfigOutput>,
+    secrets: Vec<ListedSecret>,
+) -> Vec<Orphan> {
+    let is_kept = |kind: &'static str, namespace: &str, name: &str| {
+        kept.contains(&(kind, namespace.to_string(), name.to_string()))
+    };
+
+    let mut orphans: Vec<Orphan> = Vec::new();
+    for deployment in deployments {
+        if deployment.labels.get(OWNER_LABEL).map(String::as_str) == Some(owner)
+            && deployment.status != "deleted"
+            && deployment.cronjob_id.is_none()
+            && !is_kept("deployment", &deployment.namespace, &deployment.name)
+        {
+            orphans.push(Orphan {
+                kind: "deployment",
+                id: deployment.id,
+                namespace: deployment.namespace,
+                name: deployment.name,
+            });
+        }
+    }
+    for config in configs {
+        if config_owner(&config.labels) == Some(owner)
+            && !is_kept("config", &config.namespace, &config.name)
+        {
+            orphans.push(Orphan {
+                kind: "config",
+                id: config.id,
+                namespace: config.namespace,
+                name: config.name,
+            });
+        }
+    }
+    for secret in secrets {
+        if secret.labels.get(OWNER_LABEL).map(String::as_str) == Some(owner)
+            && !is_kept("secret", &secret.namespace, &secret.name)
+        {
+            orphans.push(Orphan {
+                kind: "secret",
+                id: secret.id,
+                namespace: secret.namespace,
+                name: secret.name,
+            });
+        }
+    }
+    orphans
+}
+
 fn default_kind() -> String {
     "worker".to_string()
 }
@@ -328,7 +645,8 @@ impl Deployment {
             ));
         }
 
-        if self.runtime != "docker"
+        if !self.runtime.is_empty()
+            && self.runtime != "docker"
             && self.runtime != "podman"
             && self.runtime != "containerd"
             && self.runtime != "cloud-hypervisor"
@@ -372,6 +690,22 @@ impl Deployment {
             *arg = env_resolver(arg, env_vars);
         }
 
+        for container in self
+            .init_containers
+            .iter_mut()
+            .chain(self.sidecars.iter_mut())
+        {
+            container.image = env_resolver(&container.image, env_vars);
+            for arg in container.command.iter_mut() {
+                *arg = env_resolver(arg, env_vars);
+            }
+            for value in container.environment.values_mut() {
+                if let EnvValue::Plain(s) = value {
+                    *s = env_resolver(s, env_vars);
+                }
+            }
+        }
+
         // The API rejects a `config` or `secret` volume whose permission is
         // not `ro`. The CLI default is `rw`, so force `ro` here — a manifest
         // carrying one of these volume types must apply without the user
@@ -395,6 +729,13 @@ pub(crate) fn command_config() -> Command {
                 .value_name("FILE")
                 .help("Sets a custom config file")
         )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["ring", "compose"])
+                .default_value("ring")
+                .help("Format of the file: a Ring manifest, or a Docker Compose file translated as ring convert compose does")
+        )
         .arg(
             Arg::new("env-file")
                 .required(false)
@@ -410,6 +751,24 @@ pub(crate) fn command_config() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("previews the object that would be sent to your cluster, without actually sending it.")
         )
+        .arg(
+            Arg::new("diff")
+                .long("diff")
+                .action(ArgAction::SetTrue)
+                .help("asks the server what each deployment would change and how it would roll out, without applying anything.")
+        )
+        .arg(
+            Arg::new("owner")
+                .long("owner")
+                .value_name("OWNER")
+                .help("labels every deployment, config and secret with this owner (ring.owner). Defaults to the manifest path with --prune.")
+        )
+        .arg(
+            Arg::new("prune")
+                .long("prune")
+                .action(ArgAction::SetTrue)
+                .help("deletes the deployments, configs and secrets of the owner that are no longer in the manifest.")
+        )
         .arg(
             Arg::new("force")
                 .long("force")
@@ -442,6 +801,17 @@ fn load_config_file(
     Ok(config)
 }
 
+/// A Compose file, translated into the manifest it stands for. What the
+/// translation drops is reported, not silently lost.
+fn load_compose_file(file_path: &str) -> Result<ConfigFile, ApplyError> {
+    let conversion = crate::commands::convert::compose::convert_file(file_path, None)
+        .map_err(ApplyError::Validation)?;
+    for warning in &conversion.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    serde_yaml::from_value(conversion.manifest).map_err(ApplyError::YamlParse)
+}
+
 /// Turn a config's `data` (inline JSON) and `files` (filename -> path) into a
 /// single JSON object string stored back into `data`. Rules:
 ///   - `data` alone: kept as-is (backwards compatible).
@@ -595,14 +965,35 @@ async fn create_namespace_on_server(
     if status.is_success() {
         info!("Namespace '{}' created successfully", namespace.name);
         println!("Namespace '{}' created", namespace.name);
-        Ok(())
     } else if status == reqwest::StatusCode::CONFLICT {
         info!("Namespace '{}' already exists, skipping", namespace.name);
         println!("Namespace '{}' already exists, skipping", namespace.name);
-        Ok(())
     } else {
         let context = format!("Failed to create namespace '{}'", namespace.name);
         let code = render_response_error(&context, response).await;
+        return Err(ApplyError::Reported(code));
+    }
+
+    // Applied on every run, so the manifest stays the source of truth for the
+    // defaults of a namespace that already existed.
+    let Some(defaults) = &namespace.defaults else {
+        return Ok(());
+    };
+    let url = format!("{}/namespaces/{}/defaults", api_url, namespace.name);
+    let response = client
+        .put(&url)
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(defaults)
+        .send()
+        .await
+        .map_err(ApplyError::Http)?;
+
+    if response.status().is_success() {
+        info!("Namespace '{}' defaults applied", namespace.name);
+        Ok(())
+    } else {
+        let context = format!("Failed to apply defaults of namespace '{}'", namespace.name);
+        let code = render_response_error(&context, response).await;
         Err(ApplyError::Reported(code))
     }
 }
@@ -756,6 +1147,190 @@ async fn deploy_to_server(
     }
 }
 
+/// Create a secret. One that already exists is left as is: its value cannot
+/// be read back to compare, and rotating it is a `ring secret` operation.
+async fn create_secret_on_server(
+    secret: &SecretDefinition,
+    api_url: &str,
+    auth_token: &str,
+    client: &reqwest::Client,
+) -> Result<(), ApplyError> {
+    let url = format!("{}/secrets", api_url);
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&json!(secret))
+        .send()
+        .await
+        .map_err(ApplyError::Http)?;
+
+    let status = response.status();
+
+    if status.is_success() {
+        info!(
+            "Secret '{}' created in namespace '{}'",
+            secret.name, secret.namespace
+        );
+        println!(
+            "Secret '{}' created in namespace '{}'",
+            secret.name, secret.namespace
+        );
+        Ok(())
+    } else if status == reqwest::StatusCode::CONFLICT {
+        println!(
+            "Secret '{}' already exists in namespace '{}', skipping",
+            secret.name, secret.namespace
+        );
+        Ok(())
+    } else {
+        let context = format!("Failed to create secret '{}'", secret.name);
+        let code = render_response_error(&context, response).await;
+        Err(ApplyError::Reported(code))
+    }
+}
+
+/// `GET` a list endpoint of the API.
+async fn list_from_server<T: serde::de::DeserializeOwned>(
+    path: &str,
+    api_url: &str,
+    auth_token: &str,
+    client: &reqwest::Client,
+) -> Result<Vec<T>, ApplyError> {
+    let response = client
+        .get(format!("{}/{}", api_url, path))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send()
+        .await
+        .map_err(ApplyError::Http)?;
+
+    if response.status().is_success() {
+        response.json().await.map_err(ApplyError::Http)
+    } else {
+        let context = format!("Failed to list {}", path);
+        let code = render_response_error(&context, response).await;
+        Err(ApplyError::Reported(code))
+    }
+}
+
+/// Delete what `owner` holds that the manifest no longer declares, or only
+/// print it on a dry run.
+async fn prune(
+    owner: &str,
+    kept: &HashSet<(&'static str, String, String)>,
+    dry_run: bool,
+    api_url: &str,
+    auth_token: &str,
+    client: &reqwest::Client,
+) -> Result<(), ApplyError> {
+    let deployments = list_from_server("deployments", api_url, auth_token, client).await?;
+    let configs = list_from_server("configs", api_url, auth_token, client).await?;
+    let secrets = list_from_server("secrets", api_url, auth_token, client).await?;
+    let orphans = find_orphans(owner, kept, deployments, configs, secrets);
+
+    if orphans.is_empty() {
+        println!("Nothing to prune for owner '{}'", owner);
+        return Ok(());
+    }
+
+    let mut first_error: Option<ApplyError> = None;
+    for orphan in orphans {
+        if dry_run {
+            println!(
+                "DRY RUN - Would prune {} '{}' in namespace '{}'",
+                orphan.kind, orphan.name, orphan.namespace
+            );
+            continue;
+        }
+
+        let response = client
+            .delete(format!("{}/{}s/{}", api_url, orphan.kind, orphan.id))
+            .header("Authorization", format!("Bearer {}", auth_token))
+            .send()
+            .await
+            .map_err(ApplyError::Http)?;
+
+        if response.status().is_success() {
+            info!(
+                "Pruned {} '{}' in namespace '{}'",
+                orphan.kind, orphan.name, orphan.namespace
+            );
+            println!(
+                "Pruned {} '{}' in namespace '{}'",
+                orphan.kind, orphan.name, orphan.namespace
+            );
+        } else {
+            let context = format!("Failed to prune {} '{}'", orphan.kind, orphan.name);
+            let code = render_response_error(&context, response).await;
+            if first_error.is_none() {
+                first_error = Some(ApplyError::Reported(code));
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Ask the server to validate and plan `deployment` without applying it.
+async fn plan_on_server(
+    deployment: &Deployment,
+    api_url: &str,
+    auth_token: &str,
+    force: bool,
+    client: &reqwest::Client,
+) -> Result<DeploymentPlan, ApplyError> {
+    let mut url = format!("{}/deployments?dry_run=true", api_url);
+
+    if force {
+        url.push_str("&force=true");
+    }
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .json(&json!(deployment))
+        .send()
+        .await
+        .map_err(ApplyError::Http)?;
+
+    if response.status().is_success() {
+        response.json().await.map_err(ApplyError::Http)
+    } else {
+        let context = format!("Deployment '{}' would be refused", deployment.name);
+        let code = render_response_error(&context, response).await;
+        Err(ApplyError::Reported(code))
+    }
+}
+
+/// A plan the way `ring apply --diff` prints it: the action on one line, then
+/// one line per field, `+` added, `-` removed, `~` changed.
+fn format_plan(plan: &DeploymentPlan) -> String {
+    let action = match (plan.action.as_str(), plan.reason.as_deref()) {
+        ("create", _) if plan.creates_namespace => "create (and its namespace)".to_string(),
+        ("rolling_update", _) => "rolling update".to_string(),
+        ("replace", Some(reason)) => format!("replace ({})", reason.replace('_', " ")),
+        (action, _) => action.to_string(),
+    };
+    let mut out = format!("deployment {}/{}: {}", plan.namespace, plan.name, action);
+    if plan.changes.is_empty() {
+        out.push_str(", no changes");
+    }
+    for change in &plan.changes {
+        let line = match (&change.from, &change.to) {
+            (None, Some(to)) => format!("  + {}: {}", change.path, to),
+            (Some(from), None) => format!("  - {}: {}", change.path, from),
+            (Some(from), Some(to)) => format!("  ~ {}: {} -> {}", change.path, from, to),
+            (None, None) => continue,
+        };
+        out.push('\n');
+        out.push_str(&line);
+    }
+    out
+}
+
 pub(crate) async fn apply(args: &ArgMatches, configuration: Config, client: &reqwest::Client) {
     if let Err(e) = apply_internal(args, configuration, client).await {
         // `Reported` means render_response_error has already written a
@@ -788,9 +1363,19 @@ async fn apply_internal(
     let env_file = args.get_one::<String>("env-file").unwrap_or(&env_binding);
     let env_vars = parse_env_file(env_file);
 
-    let binding = "ring.yaml".to_string();
+    let is_compose = args.get_one::<String>("format").map(String::as_str) == Some("compose");
+    let binding = if is_compose {
+        "compose.yaml"
+    } else {
+        "ring.yaml"
+    }
+    .to_string();
     let file = args.get_one::<String>("file").unwrap_or(&binding);
-    let config_file = load_config_file(file, &env_vars)?;
+    let config_file = if is_compose {
+        load_compose_file(file)?
+    } else {
+        load_config_file(file, &env_vars)?
+    };
 
     check_auth(&get_config_dir())?;
 
@@ -800,12 +1385,29 @@ async fn apply_internal(
     let is_dry_run = args.get_flag("dry-run");
     let is_verbose = args.get_flag("verbose");
     let is_force = args.get_flag("force");
+    // A diff applies nothing either, it only asks the server about deployments.
+    let is_diff = args.get_flag("diff");
+    let is_prune = args.get_flag("prune");
+    // Without `--owner`, a prune owns what this manifest file declares. The
+    // absolute path keeps two `ring.yaml` of different projects apart.
+    let owner = match args.get_one::<String>("owner") {
+        Some(owner) => Some(owner.clone()),
+        None if is_prune => Some(
+            fs::canonicalize(file)
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|_| file.clone()),
+        ),
+        None => None,
+    };
+    // Everything the manifest declares, by kind, namespace and name: what a
+    // prune leaves alone.
+    let mut kept: HashSet<(&'static str, String, String)> = HashSet::new();
 
     let mut first_error: Option<ApplyError> = None;
 
     // Create namespaces first
     for (key, namespace) in &config_file.namespaces {
-        if is_dry_run {
+        if is_dry_run || is_diff {
             println!("DRY RUN - Would create namespace '{}'", namespace.name);
         } else if let Err(e) =
             create_namespace_on_server(namespace, &api_url, &auth_config.token, client).await
@@ -824,11 +1426,15 @@ async fn apply_internal(
     for (key, mut config) in config_file.configs {
         config.namespace = env_resolver(&config.namespace, &env_vars);
         config.name = env_resolver(&config.name, &env_vars);
+        kept.insert(("config", config.namespace.clone(), config.name.clone()));
+        if let Some(owner) = &owner {
+            config.labels = Some(config_labels_with_owner(config.labels.as_deref(), owner));
+        }
         // `data` is already resolved and interpolated by `resolve_config_data`
         // at load time (which is where the inline-vs-files interpolation policy
         // lives), so the payload is sent as-is here.
 
-        if is_dry_run {
+        if is_dry_run || is_diff {
             println!(
                 "DRY RUN - Would create config '{}' in namespace '{}'",
                 config.name, config.namespace
@@ -845,6 +1451,33 @@ async fn apply_internal(
         }
     }
 
+    // Secrets after namespaces too, before the deployments that reference them.
+    for (key, mut secret) in config_file.secrets {
+        secret.namespace = env_resolver(&secret.namespace, &env_vars);
+        secret.name = env_resolver(&secret.name, &env_vars);
+        secret.value = env_resolver(&secret.value, &env_vars);
+        kept.insert(("secret", secret.namespace.clone(), secret.name.clone()));
+        if let Some(owner) = &owner {
+            secret.labels.insert(OWNER_LABEL.to_string(), owner.clone());
+        }
+
+        if is_dry_run || is_diff {
+            println!(
+                "DRY RUN - Would create secret '{}' in namespace '{}'",
+                secret.name, secret.namespace
+            );
+        } else if let Err(e) =
+            create_secret_on_server(&secret, &api_url, &auth_config.token, client).await
+        {
+            if !matches!(e, ApplyError::Reported(_)) {
+                eprintln!("Failed to create secret '{}': {}", key, e);
+            }
+            if first_error.is_none() {
+                first_error = Some(e);
+            }
+        }
+    }
+
     let mut success_count = 0;
     let mut error_count = 0;
 
@@ -861,6 +1494,16 @@ async fn apply_internal(
         }
 
         deployment.resolve_env_vars(&env_vars);
+        kept.insert((
+            "deployment",
+            deployment.namespace.clone(),
+            deployment.name.clone(),
+        ));
+        if let Some(owner) = &owner {
+            deployment
+                .labels
+                .insert(OWNER_LABEL.to_string(), owner.clone());
+        }
 
         if is_verbose {
             let json = json!(deployment);
@@ -874,6 +1517,23 @@ async fn apply_internal(
         if is_dry_run {
             preview_deployment(&deployment, &api_url, is_force, is_verbose);
             success_count += 1;
+        } else if is_diff {
+            match plan_on_server(&deployment, &api_url, &auth_config.token, is_force, client).await
+            {
+                Ok(plan) => {
+                    println!("{}", format_plan(&plan));
+                    success_count += 1;
+                }
+                Err(e) => {
+                    if !matches!(e, ApplyError::Reported(_)) {
+                        eprintln!("Failed to plan '{}': {}", deployment_name, e);
+                    }
+                    error_count += 1;
+                    if first_error.is_none() {
+                        first_error = Some(e);
+                    }
+                }
+            }
         } else {
             match deploy_to_server(&deployment, &api_url, &auth_config.token, is_force, client)
                 .await
@@ -895,6 +1555,28 @@ async fn apply_internal(
         }
     }
 
+    // Only once everything applied: a manifest that half failed is not a
+    // trustworthy list of what to keep.
+    if is_prune && let Some(owner) = &owner {
+        if first_error.is_some() {
+            eprintln!("Skipping prune: part of the manifest failed to apply");
+        } else if let Err(e) = prune(
+            owner,
+            &kept,
+            is_dry_run || is_diff,
+            &api_url,
+            &auth_config.token,
+            client,
+        )
+        .await
+        {
+            if !matches!(e, ApplyError::Reported(_)) {
+                eprintln!("Failed to prune: {}", e);
+            }
+            first_error = Some(e);
+        }
+    }
+
     println!("\nSummary:");
     println!("  Successful: {}", success_count);
     if error_count > 0 {
@@ -905,6 +1587,9 @@ async fn apply_internal(
         println!("\nDRY RUN COMPLETE - No actual changes were made");
         println!("To deploy for real, remove the --dry-run flag");
     }
+    if is_diff && !is_dry_run {
+        println!("\nDIFF COMPLETE - No actual changes were made");
+    }
 
     if let Some(e) = first_error {
         return Err(e);
@@ -913,7 +1598,7 @@ async fn apply_internal(
     Ok(())
 }
 
-fn parse_env_file(env_file: &str) -> HashMap<String, String> {
+pub(crate) fn parse_env_file(env_file: &str) -> HashMap<String, String> {
     let mut env_vars = HashMap::new();
 
     if env_file.is_empty() {
@@ -1012,6 +1697,16 @@ mod tests {
             command: Vec::new(),
             resources: None,
             autoscale: None,
+            strategy: None,
+            cron: None,
+            job: None,
+            depends_on: Vec::new(),
+            init_containers: Vec::new(),
+            sidecars: Vec::new(),
+            lifecycle: None,
+            restart_policy: None,
+            idle: None,
+            logging: None,
             health_checks: Vec::new(),
             ports: Vec::new(),
             network: None,
@@ -1176,7 +1871,7 @@ deployments:
             .expect("autoscale should parse");
         assert_eq!(policy.min, 2);
         assert_eq!(policy.max, 10);
-        assert_eq!(policy.target_cpu, 70.0);
+        assert_eq!(policy.target_cpu, Some(70.0));
 
         // Opt-in: a deployment that says nothing about autoscaling must send no
         // policy at all, so Ring leaves its replica count alone.
@@ -1206,6 +1901,263 @@ deployments:
         );
     }
 
+    #[test]
+    fn strategy_block_passes_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  api:
+    name: api
+    image: myapp:latest
+    strategy:
+      type: rolling
+      max_unavailable: 1
+  lock:
+    name: lock
+    image: myapp:latest
+    strategy:
+      type: recreate
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let api = serde_json::to_value(&config.deployments["api"]).unwrap();
+        assert_eq!(
+            api["strategy"],
+            serde_json::json!({"type": "rolling", "max_unavailable": 1})
+        );
+        let lock = serde_json::to_value(&config.deployments["lock"]).unwrap();
+        assert_eq!(lock["strategy"], serde_json::json!({"type": "recreate"}));
+    }
+
+    #[test]
+    fn cron_block_passes_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  nightly:
+    name: nightly
+    kind: cronjob
+    image: reports:latest
+    cron:
+      schedule: "0 3 * * *"
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let nightly = serde_json::to_value(&config.deployments["nightly"]).unwrap();
+        assert_eq!(nightly["kind"], "cronjob");
+        assert_eq!(
+            nightly["cron"],
+            serde_json::json!({"schedule": "0 3 * * *"})
+        );
+    }
+
+    #[test]
+    fn job_block_passes_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  reindex:
+    name: reindex
+    kind: job
+    image: search:latest
+    job:
+      backoff_limit: 3
+      completions: 4
+      parallelism: 2
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let reindex = serde_json::to_value(&config.deployments["reindex"]).unwrap();
+        assert_eq!(
+            reindex["job"],
+            serde_json::json!({"backoff_limit": 3, "parallelism": 2, "completions": 4})
+        );
+    }
+
+    #[test]
+    fn depends_on_passes_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  api:
+    name: api
+    image: api:latest
+    depends_on:
+      - name: postgres
+        condition: ready
+      - name: cache
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let api = serde_json::to_value(&config.deployments["api"]).unwrap();
+        assert_eq!(
+            api["depends_on"],
+            serde_json::json!([{"name": "postgres", "condition": "ready"}, {"name": "cache"}])
+        );
+    }
+
+    #[test]
+    fn lifecycle_passes_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  worker:
+    name: worker
+    image: worker:latest
+    lifecycle:
+      stop_signal: SIGQUIT
+      stop_grace_period: 120s
+      pre_stop:
+        http:
+          port: 8080
+          path: /drain
+      post_start:
+        command: ["./warm-cache"]
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let worker = serde_json::to_value(&config.deployments["worker"]).unwrap();
+        assert_eq!(
+            worker["lifecycle"],
+            serde_json::json!({
+                "stop_signal": "SIGQUIT",
+                "stop_grace_period": "120s",
+                "pre_stop": {"http": {"port": 8080, "path": "/drain"}},
+                "post_start": {"command": ["./warm-cache"]}
+            })
+        );
+    }
+
+    #[test]
+    fn autoscale_metrics_pass_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  consumer:
+    name: consumer
+    image: consumer:latest
+    autoscale:
+      min: 1
+      max: 20
+      metrics:
+        - name: queue_backlog
+          labels: { queue: orders }
+          port: 9100
+          target: 50
+      cooldown_down: 600s
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let consumer = serde_json::to_value(&config.deployments["consumer"]).unwrap();
+        assert_eq!(
+            consumer["autoscale"],
+            serde_json::json!({
+                "min": 1, "max": 20,
+                "metrics": [{
+                    "name": "queue_backlog", "labels": {"queue": "orders"},
+                    "port": 9100, "target": 50.0
+                }],
+                "cooldown_down": "600s"
+            })
+        );
+    }
+
+    #[test]
+    fn restart_policy_passes_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  worker:
+    name: worker
+    image: worker:latest
+    restart_policy:
+      max_restarts: 10
+      backoff:
+        max: 600s
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let worker = serde_json::to_value(&config.deployments["worker"]).unwrap();
+        assert_eq!(
+            worker["restart_policy"],
+            serde_json::json!({"max_restarts": 10, "backoff": {"max": "600s"}})
+        );
+    }
+
+    #[test]
+    fn idle_passes_through_with_server_defaults() {
+        let yaml_content = r#"
+deployments:
+  wiki:
+    name: wiki
+    image: wiki:latest
+    ports:
+      - published: 8080
+        target: 80
+    idle:
+      after: 900s
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let wiki = serde_json::to_value(&config.deployments["wiki"]).unwrap();
+        assert_eq!(wiki["idle"], serde_json::json!({"after": "900s"}));
+    }
+
+    #[test]
+    fn logging_passes_through() {
+        let yaml_content = r#"
+deployments:
+  api:
+    name: api
+    image: api:latest
+    logging:
+      format: json
+      multiline_pattern: '^\s+at '
+"#;
+        let config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+
+        let api = serde_json::to_value(&config.deployments["api"]).unwrap();
+        assert_eq!(
+            api["logging"],
+            serde_json::json!({"format": "json", "multiline_pattern": "^\\s+at "})
+        );
+    }
+
+    #[test]
+    fn extra_containers_resolve_env_vars() {
+        let yaml_content = r#"
+deployments:
+  api:
+    name: api
+    image: api:latest
+    init_containers:
+      - name: migrate
+        image: "api:$TAG"
+        command: ["./migrate", "--to", "$TAG"]
+    sidecars:
+      - name: proxy
+        image: cloud-sql-proxy:2
+        environment:
+          INSTANCE: "$DB_INSTANCE"
+          TOKEN:
+            secretRef: proxy-token
+"#;
+        let mut config: ConfigFile = serde_yaml::from_str(yaml_content).unwrap();
+        let env_vars = HashMap::from([
+            ("TAG".to_string(), "1.4".to_string()),
+            ("DB_INSTANCE".to_string(), "prod:eu:main".to_string()),
+        ]);
+        let api = config.deployments.get_mut("api").unwrap();
+        api.resolve_env_vars(&env_vars);
+
+        let api = serde_json::to_value(&*api).unwrap();
+        assert_eq!(
+            api["init_containers"],
+            serde_json::json!([{"name": "migrate", "image": "api:1.4", "command": ["./migrate", "--to", "1.4"]}])
+        );
+        assert_eq!(
+            api["sidecars"][0]["environment"]["INSTANCE"],
+            "prod:eu:main"
+        );
+        assert_eq!(
+            api["sidecars"][0]["environment"]["TOKEN"],
+            serde_json::json!({"secretRef": "proxy-token"})
+        );
+    }
+
     #[test]
     fn test_config_file_with_command_resources_health_checks() {
         let yaml_content = r#"
@@ -1290,6 +2242,16 @@ deployments:
             ],
             resources: None,
             autoscale: None,
+            strategy: None,
+            cron: None,
+            job: None,
+            depends_on: Vec::new(),
+            init_containers: Vec::new(),
+            sidecars: Vec::new(),
+            lifecycle: None,
+            restart_policy: None,
+            idle: None,
+            logging: None,
             health_checks: Vec::new(),
             ports: Vec::new(),
             network: None,
@@ -1602,6 +2564,38 @@ deployments:
         assert!(entry.files.is_empty());
     }
 
+    #[test]
+    fn test_load_compose_file_yields_its_deployments() {
+        let dir = std::env::temp_dir().join("ring_apply_compose");
+        let _ = fs::create_dir_all(&dir);
+        let compose = dir.join("compose.yaml");
+        fs::write(
+            &compose,
+            r#"
+name: shop
+services:
+  api:
+    image: myapp:latest
+    environment:
+      TOKEN:
+    ports:
+      - "8080:80"
+"#,
+        )
+        .unwrap();
+
+        let config = load_compose_file(compose.to_str().unwrap()).unwrap();
+        let api = &config.deployments["api"];
+        assert_eq!(api.namespace, "shop");
+        assert_eq!(api.replicas, 1);
+        assert_eq!(api.kind, "worker");
+        assert_eq!(api.ports[0].published, 8080);
+        assert!(matches!(
+            &api.environment["TOKEN"],
+            EnvValue::Plain(value) if value == "$TOKEN"
+        ));
+    }
+
     #[test]
     fn test_config_file_without_configs() {
         let yaml_content = r#"
@@ -1653,4 +2647,107 @@ deployments:
         let non_string_id = vec![serde_json::json!({"id": 42})];
         assert_eq!(first_config_id(&non_string_id), None);
     }
+
+    #[test]
+    fn a_plan_prints_its_action_and_one_line_per_change() {
+        let plan: DeploymentPlan = serde_json::from_value(json!({
+            "namespace": "preview",
+            "name": "web",
+            "action": "replace",
+            "reason": "no_health_checks",
+            "replaces": ["d1"],
+            "changes": [
+                {"path": "image", "from": "nginx:1.25", "to": "nginx:1.27"},
+                {"path": "labels.team", "to": "a"},
+                {"path": "replicas", "from": 3}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            format_plan(&plan),
+            "deployment preview/web: replace (no health checks)\n  \
+             ~ image: \"nginx:1.25\" -> \"nginx:1.27\"\n  \
+             + labels.team: \"a\"\n  \
+             - replicas: 3"
+        );
+
+        let unchanged: DeploymentPlan = serde_json::from_value(json!({
+            "namespace": "preview",
+            "name": "web",
+            "action": "rolling_update",
+            "changes": []
+        }))
+        .unwrap();
+        assert_eq!(
+            format_plan(&unchanged),
+            "deployment preview/web: rolling update, no changes"
+        );
+    }
+
+    #[test]
+    fn the_owner_rides_along_config_labels() {
+        assert_eq!(
+            config_labels_with_owner(None, "payments"),
+            "ring.owner=payments"
+        );
+        let labels = config_labels_with_owner(Some("tier=frontend"), "payments");
+        assert_eq!(labels, "tier=frontend,ring.owner=payments");
+        assert_eq!(config_owner(&labels), Some("payments"));
+        assert_eq!(config_owner("tier=frontend"), None);
+        assert_eq!(config_owner("ring.owners=x"), None);
+    }
+
+    #[test]
+    fn orphans_are_what_the_owner_holds_outside_the_manifest() {
+        let deployment = |id: &str, name: &str, owner: Option<&str>, extra: serde_json::Value| {
+            let mut value = json!({
+                "id": id, "created_at": "", "updated_at": "", "status": "running",
+                "restart_count": 0, "name": name, "namespace": "shop",
+                "runtime": "docker", "kind": "worker", "image": "nginx",
+                "command": [], "replicas": 1, "ports": [], "labels": {},
+                "environment": {}, "volumes": [], "instances": [],
+                "health_checks": []
+            });
+            if let Some(owner) = owner {
+                value["labels"] = json!({ OWNER_LABEL: owner });
+            }
+            for (key, field) in extra.as_object().unwrap() {
+                value[key] = field.clone();
+            }
+            serde_json::from_value::<DeploymentOutput>(value).unwrap()
+        };
+        let deployments = vec![
+            deployment("d1", "web", Some("ci"), json!({})),
+            deployment("d2", "old-worker", Some("ci"), json!({})),
+            deployment("d3", "old-worker", Some("ci"), json!({"status": "deleted"})),
+            deployment("d4", "report-1", Some("ci"), json!({"cronjob_id": "c1"})),
+            deployment("d5", "hand-made", None, json!({})),
+            deployment("d6", "theirs", Some("other"), json!({})),
+        ];
+        let configs: Vec<ConfigOutput> = serde_json::from_value(json!([
+            {"id": "c1", "created_at": "", "updated_at": null, "namespace": "shop",
+             "name": "nginx", "data": "{}", "labels": "ring.owner=ci"},
+            {"id": "c2", "created_at": "", "updated_at": null, "namespace": "shop",
+             "name": "legacy", "data": "{}", "labels": "tier=web,ring.owner=ci"}
+        ]))
+        .unwrap();
+        let secrets: Vec<ListedSecret> = serde_json::from_value(json!([
+            {"id": "s1", "namespace": "shop", "name": "db", "labels": {"ring.owner": "ci"}},
+            {"id": "s2", "namespace": "shop", "name": "manual"}
+        ]))
+        .unwrap();
+        let kept: HashSet<(&'static str, String, String)> = [
+            ("deployment", "shop".to_string(), "web".to_string()),
+            ("config", "shop".to_string(), "nginx".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        let orphans = find_orphans("ci", &kept, deployments, configs, secrets);
+        let found: Vec<(&str, &str)> = orphans.iter().map(|o| (o.kind, o.id.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![("deployment", "d2"), ("config", "c2"), ("secret", "s1")]
+        );
+    }
 }
@@ -0,0 +1,903 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use clap::{Arg, ArgMatches, Command};
+use serde_yaml::{Mapping, Value};
+
+use crate::commands::apply::parse_env_file;
+use crate::exit_code;
+
+/// Service keys the conversion understands. Anything else is reported.
+const SERVICE_KEYS: &[&str] = &[
+    "image",
+    "command",
+    "environment",
+    "env_file",
+    "ports",
+    "volumes",
+    "deploy",
+    "healthcheck",
+    "network_mode",
+    "labels",
+];
+
+/// A Compose file turned into a Ring manifest, with what could not be carried
+/// over. The manifest keeps the services' order.
+pub(crate) struct Conversion {
+    pub(crate) manifest: Value,
+    pub(crate) warnings: Vec<String>,
+}
+
+pub(crate) fn command_config() -> Command {
+    Command::new("compose")
+        .about("Translate a Docker Compose file into a Ring manifest")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_name("FILE")
+                .help("Compose file to convert"),
+        )
+        .arg(Arg::new("namespace").short('n').long("namespace").help(
+            "Namespace of the deployments. Defaults to the Compose project name, else default",
+        ))
+}
+
+pub(crate) fn execute(args: &ArgMatches) {
+    let file = args.get_one::<String>("file").expect("file is required");
+    let namespace = args.get_one::<String>("namespace").map(String::as_str);
+
+    let conversion = match convert_file(file, namespace) {
+        Ok(conversion) => conversion,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    };
+    for warning in &conversion.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    match serde_yaml::to_string(&conversion.manifest) {
+        Ok(rendered) => println!("{}", rendered.trim_end()),
+        Err(e) => {
+            eprintln!("Failed to render the manifest: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    }
+}
+
+/// Reads a Compose file from disk. `env_file` and relative bind mounts are
+/// resolved against the file's directory, as Compose does.
+pub(crate) fn convert_file(path: &str, namespace: Option<&str>) -> Result<Conversion, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("failed to read '{}': {}", path, e))?;
+    let base_dir = Path::new(path).parent().unwrap_or(Path::new("."));
+    convert(&contents, base_dir, namespace)
+}
+
+pub(crate) fn convert(
+    contents: &str,
+    base_dir: &Path,
+    namespace: Option<&str>,
+) -> Result<Conversion, String> {
+    let compose: Value =
+        serde_yaml::from_str(contents).map_err(|e| format!("invalid compose file: {}", e))?;
+    let top = compose
+        .as_mapping()
+        .ok_or_else(|| "invalid compose file: expected a mapping".to_string())?;
+    let mut warnings = Vec::new();
+
+    for key in top.keys().filter_map(Value::as_str) {
+        if !matches!(key, "services" | "volumes" | "name" | "version") && !key.starts_with("x-") {
+            warnings.push(format!("top-level '{}' is not supported, ignored", key));
+        }
+    }
+    if contents.contains("${") {
+        warnings.push(
+            "'${VAR}' interpolation is left as is: Ring interpolates '$VAR' at apply time"
+                .to_string(),
+        );
+    }
+
+    let namespace = namespace
+        .map(str::to_string)
+        .or_else(|| top.get("name").and_then(scalar))
+        .unwrap_or_else(|| "default".to_string());
+    let drivers = volume_drivers(top.get("volumes"), &mut warnings);
+
+    let services = top
+        .get("services")
+        .and_then(Value::as_mapping)
+        .ok_or_else(|| "the compose file has no services".to_string())?;
+
+    let mut deployments = Mapping::new();
+    for (name, service) in services {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let Some(service) = service.as_mapping() else {
+            warnings.push(format!("service '{}' is not a mapping, skipped", name));
+            continue;
+        };
+        let mut service_warnings = Vec::new();
+        let deployment = convert_service(
+            name,
+            service,
+            &namespace,
+            base_dir,
+            &drivers,
+            &mut service_warnings,
+        )?;
+        warnings.extend(
+            service_warnings
+                .into_iter()
+                .map(|warning| format!("service '{}': {}", name, warning)),
+        );
+        if let Some(deployment) = deployment {
+            deployments.insert(name.into(), Value::Mapping(deployment));
+        }
+    }
+
+    let mut manifest = Mapping::new();
+    manifest.insert("deployments".into(), Value::Mapping(deployments));
+    Ok(Conversion {
+        manifest: Value::Mapping(manifest),
+        warnings,
+    })
+}
+
+/// The driver of each top-level named volume that sets one.
+fn volume_drivers(volumes: Option<&Value>, warnings: &mut Vec<String>) -> HashMap<String, String> {
+    let mut drivers = HashMap::new();
+    let Some(volumes) = volumes.and_then(Value::as_mapping) else {
+        return drivers;
+    };
+    for (name, definition) in volumes {
+        let (Some(name), Some(definition)) = (name.as_str(), definition.as_mapping()) else {
+            continue;
+        };
+        for key in definition.keys().filter_map(Value::as_str) {
+            match key {
+                "driver" => {
+                    if let Some(driver) = definition.get("driver").and_then(scalar) {
+                        drivers.insert(name.to_string(), driver);
+                    }
+                }
+                "external" | "name" => {}
+                other => warnings.push(format!(
+                    "volume '{}': '{}' is not supported, ignored",
+                    name, other
+                )),
+            }
+        }
+    }
+    drivers
+}
+
+fn convert_service(
+    name: &str,
+    service: &Mapping,
+    namespace: &str,
+    base_dir: &Path,
+    drivers: &HashMap<String, String>,
+    warnings: &mut Vec<String>,
+) -> Result<Option<Mapping>, String> {
+    let Some(image) = service.get("image").and_then(scalar) else {
+        warnings.push("no image (building one is not supported), skipped".to_string());
+        return Ok(None);
+    };
+    for key in service.keys().filter_map(Value::as_str) {
+        if !SERVICE_KEYS.contains(&key) && !key.starts_with("x-") {
+            warnings.push(format!("'{}' is not supported, ignored", key));
+        }
+    }
+
+    let mut deployment = Mapping::new();
+    deployment.insert("name".into(), name.into());
+    deployment.insert("namespace".into(), namespace.into());
+    deployment.insert("image".into(), image.into());
+
+    let deploy = service.get("deploy").and_then(Value::as_mapping);
+    let replicas = deploy
+        .and_then(|deploy| deploy.get("replicas"))
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    deployment.insert("replicas".into(), replicas.into());
+
+    if let Some(command) = service.get("command") {
+        let command = match command {
+            Value::String(line) => shell_words::split(line)
+                .map_err(|e| format!("service '{}': invalid command: {}", name, e))?,
+            Value::Sequence(args) => args.iter().filter_map(scalar).collect(),
+            _ => Vec::new(),
+        };
+        if !command.is_empty() {
+            deployment.insert("command".into(), strings(command));
+        }
+    }
+
+    let environment =
+        environment(service, base_dir).map_err(|e| format!("service '{}': {}", name, e))?;
+    if !environment.is_empty() {
+        deployment.insert("environment".into(), Value::Mapping(environment));
+    }
+
+    if let Some(labels) = service.get("labels") {
+        let mut labels = pairs(labels);
+        for value in labels.values_mut().filter(|value| value.is_null()) {
+            *value = Value::String(String::new());
+        }
+        if !labels.is_empty() {
+            deployment.insert("labels".into(), Value::Mapping(labels));
+        }
+    }
+
+    let ports: Vec<Value> = service
+        .get("ports")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(|port| convert_port(port, warnings))
+        .map(Value::Mapping)
+        .collect();
+    if !ports.is_empty() {
+        deployment.insert("ports".into(), Value::Sequence(ports));
+    }
+
+    let volumes: Vec<Value> = service
+        .get("volumes")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(|volume| convert_volume(volume, base_dir, drivers, warnings))
+        .map(Value::Mapping)
+        .collect();
+    if !volumes.is_empty() {
+        deployment.insert("volumes".into(), Value::Sequence(volumes));
+    }
+
+    if let Some(deploy) = deploy {
+        for key in deploy.keys().filter_map(Value::as_str) {
+            if !matches!(key, "replicas" | "resources") {
+                warnings.push(format!("'deploy.{}' is not supported, ignored", key));
+            }
+        }
+        if let Some(resources) = deploy.get("resources").and_then(Value::as_mapping) {
+            let resources = convert_resources(resources, warnings);
+            if !resources.is_empty() {
+                deployment.insert("resources".into(), Value::Mapping(resources));
+            }
+        }
+    }
+
+    if let Some(healthcheck) = service.get("healthcheck").and_then(Value::as_mapping)
+        && let Some(check) = convert_healthcheck(healthcheck, warnings)
+    {
+        deployment.insert(
+            "health_checks".into(),
+            Value::Sequence(vec![Value::Mapping(check)]),
+        );
+    }
+
+    match service.get("network_mode").and_then(Value::as_str) {
+        Some("host") => {
+            let mut network = Mapping::new();
+            network.insert("mode".into(), "host".into());
+            deployment.insert("network".into(), Value::Mapping(network));
+        }
+        Some("bridge") | None => {}
+        Some(other) => warnings.push(format!(
+            "network_mode '{}' is not supported, ignored",
+            other
+        )),
+    }
+
+    Ok(Some(deployment))
+}
+
+/// `env_file` entries in order, then `environment` on top, as Compose merges
+/// them. A variable listed without a value is passed through from the
+/// environment `ring apply` runs in, as `$NAME`.
+fn environment(service: &Mapping, base_dir: &Path) -> Result<Mapping, String> {
+    let mut environment = Mapping::new();
+
+    let env_files: Vec<&Value> = match service.get("env_file") {
+        Some(Value::Sequence(files)) => files.iter().collect(),
+        Some(file) => vec![file],
+        None => Vec::new(),
+    };
+    for entry in env_files {
+        let (path, required) = match entry {
+            Value::Mapping(entry) => (
+                entry.get("path").and_then(scalar).unwrap_or_default(),
+                entry
+                    .get("required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+            ),
+            other => (scalar(other).unwrap_or_default(), true),
+        };
+        let full_path = base_dir.join(&path);
+        if !full_path.is_file() {
+            if required {
+                return Err(format!("env_file '{}' not found", full_path.display()));
+            }
+            continue;
+        }
+        let mut variables: Vec<(String, String)> = parse_env_file(&full_path.to_string_lossy())
+            .into_iter()
+            .collect();
+        variables.sort();
+        for (key, value) in variables {
+            environment.insert(key.into(), value.into());
+        }
+    }
+
+    if let Some(variables) = service.get("environment") {
+        for (key, value) in pairs(variables) {
+            let value = match value {
+                Value::Null => Value::String(format!("${}", key.as_str().unwrap_or_default())),
+                value => value,
+            };
+            environment.insert(key, value);
+        }
+    }
+
+    Ok(environment)
+}
+
+/// A `key: value` mapping or a `["key=value"]` list, values as strings. A
+/// key without a value, in either form, maps to null.
+fn pairs(value: &Value) -> Mapping {
+    let mut pairs = Mapping::new();
+    match value {
+        Value::Mapping(entries) => {
+            for (key, value) in entries {
+                let Some(key) = scalar(key) else { continue };
+                let value = scalar(value).map(Value::String).unwrap_or(Value::Null);
+                pairs.insert(key.into(), value);
+            }
+        }
+        Value::Sequence(entries) => {
+            for entry in entries.iter().filter_map(Value::as_str) {
+                match entry.split_once('=') {
+                    Some((key, value)) => pairs.insert(key.into(), value.into()),
+                    None => pairs.insert(entry.into(), Value::Null),
+                };
+            }
+        }
+        _ => {}
+    }
+    pairs
+}
+
+fn convert_port(port: &Value, warnings: &mut Vec<String>) -> Option<Mapping> {
+    let (host_ip, published, target, protocol) = match port {
+        Value::Mapping(port) => {
+            for key in port.keys().filter_map(Value::as_str) {
+                if !matches!(
+                    key,
+                    "target" | "published" | "host_ip" | "protocol" | "mode"
+                ) {
+                    warnings.push(format!("'ports.{}' is not supported, ignored", key));
+                }
+            }
+            (
+                port.get("host_ip").and_then(scalar),
+                port.get("published").and_then(scalar),
+                port.get("target").and_then(scalar).unwrap_or_default(),
+                port.get("protocol").and_then(scalar),
+            )
+        }
+        other => {
+            let spec = scalar(other).unwrap_or_default();
+            let (spec, protocol) = match spec.split_once('/') {
+                Some((spec, protocol)) => (spec.to_string(), Some(protocol.to_string())),
+                None => (spec, None),
+            };
+            // `[::1]:8080:80`: the brackets keep an IPv6 address in one piece.
+            let (host_ip, rest) = match spec.strip_prefix('[').and_then(|s| s.split_once("]:")) {
+                Some((ip, rest)) => (Some(ip.to_string()), rest.to_string()),
+                None => (None, spec.clone()),
+            };
+            let parts: Vec<&str> = rest.split(':').collect();
+            match (host_ip, parts.as_slice()) {
+                (None, [target]) => (None, None, target.to_string(), protocol),
+                (host_ip, [published, target]) => (
+                    host_ip,
+                    Some(published.to_string()),
+                    target.to_string(),
+                    protocol,
+                ),
+                (None, [host_ip, published, target]) => (
+                    Some(host_ip.to_string()),
+                    Some(published.to_string()),
+                    target.to_string(),
+                    protocol,
+                ),
+                _ => {
+                    warnings.push(format!("port '{}' is not understood, ignored", spec));
+                    return None;
+                }
+            }
+        }
+    };
+
+    let Some(published) = published.filter(|published| !published.is_empty()) else {
+        warnings.push(format!(
+            "port {} has no published port (Ring publishes fixed host ports only), ignored",
+            target
+        ));
+        return None;
+    };
+    let (Ok(published), Ok(target)) = (published.parse::<u16>(), target.parse::<u16>()) else {
+        warnings.push(format!(
+            "port {}:{} is a range or not a number, ignored",
+            published, target
+        ));
+        return None;
+    };
+    let protocol = protocol.map(|protocol| protocol.to_lowercase());
+    if let Some(protocol) = protocol.as_deref()
+        && protocol != "tcp"
+        && protocol != "udp"
+    {
+        warnings.push(format!(
+            "port {}:{} uses {}, only tcp and udp are supported, ignored",
+            published, target, protocol
+        ));
+        return None;
+    }
+
+    let mut mapping = Mapping::new();
+    mapping.insert("published".into(), published.into());
+    mapping.insert("target".into(), target.into());
+    if let Some(host_ip) = host_ip.filter(|ip| !ip.is_empty()) {
+        mapping.insert("host_ip".into(), host_ip.into());
+    }
+    if protocol.as_deref() == Some("udp") {
+        mapping.insert("protocol".into(), "udp".into());
+    }
+    Some(mapping)
+}
+
+fn convert_volume(
+    volume: &Value,
+    base_dir: &Path,
+    drivers: &HashMap<String, String>,
+    warnings: &mut Vec<String>,
+) -> Option<Mapping> {
+    let (kind, source, target, read_only) = match volume {
+        Value::Mapping(volume) => {
+            for key in volume.keys().filter_map(Value::as_str) {
+                if !matches!(key, "type" | "source" | "target" | "read_only") {
+                    warnings.push(format!("'volumes.{}' is not supported, ignored", key));
+                }
+            }
+            let kind = volume
+                .get("type")
+                .and_then(scalar)
+                .unwrap_or_else(|| "volume".to_string());
+            (
+                Some(kind),
+                volume.get("source").and_then(scalar).unwrap_or_default(),
+                volume.get("target").and_then(scalar).unwrap_or_default(),
+                volume
+                    .get("read_only")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            )
+        }
+        other => {
+            let spec = scalar(other).unwrap_or_default();
+            let parts: Vec<&str> = spec.split(':').collect();
+            match parts.as_slice() {
+                [source, target] => (None, source.to_string(), target.to_string(), false),
+                [source, target, options] => (
+                    None,
+                    source.to_string(),
+                    target.to_string(),
+                    options.split(',').any(|option| option == "ro"),
+                ),
+                _ => (None, String::new(), spec.clone(), false),
+            }
+        }
+    };
+
+    // The short syntax tells a bind mount from a named volume by its source.
+    let kind = kind.unwrap_or_else(|| {
+        if source.starts_with(['.', '/', '~']) {
+            "bind".to_string()
+        } else {
+            "volume".to_string()
+        }
+    });
+    if kind != "bind" && kind != "volume" {
+        warnings.push(format!(
+            "{} mount of {} is not supported, ignored",
+            kind, target
+        ));
+        return None;
+    }
+    if source.is_empty() {
+        warnings.push(format!(
+            "anonymous volume {} is not supported, ignored",
+            target
+        ));
+        return None;
+    }
+
+    let (source, driver) = if kind == "bind" {
+        (host_path(&source, base_dir), "local".to_string())
+    } else {
+        let driver = drivers
+            .get(&source)
+            .cloned()
+            .unwrap_or_else(|| "local".to_string());
+        (source, driver)
+    };
+
+    let mut mapping = Mapping::new();
+    mapping.insert("type".into(), kind.into());
+    mapping.insert("source".into(), source.into());
+    mapping.insert("destination".into(), target.into());
+    mapping.insert("driver".into(), driver.into());
+    mapping.insert(
+        "permission".into(),
+        if read_only { "ro" } else { "rw" }.into(),
+    );
+    Some(mapping)
+}
+
+/// A bind source as an absolute path: `~` is the user's home, a relative path
+/// is relative to the Compose file.
+fn host_path(source: &str, base_dir: &Path) -> String {
+    let path = match source.strip_prefix('~') {
+        Some(rest) => {
+            let home = std::env::var("HOME").unwrap_or_default();
+            Path::new(&home).join(rest.trim_start_matches('/'))
+        }
+        None => base_dir.join(source),
+    };
+    std::path::absolute(&path)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// `limits` become Ring limits and `reservations` its requests.
+fn convert_resources(resources: &Mapping, warnings: &mut Vec<String>) -> Mapping {
+    let mut converted = Mapping::new();
+    for (key, spec) in resources {
+        let Some(key) = key.as_str() else { continue };
+        let ring_key = match key {
+            "limits" => "limits",
+            "reservations" => "requests",
+            other => {
+                warnings.push(format!(
+                    "'deploy.resources.{}' is not supported, ignored",
+                    other
+                ));
+                continue;
+            }
+        };
+        let Some(spec) = spec.as_mapping() else {
+            continue;
+        };
+        let mut ring_spec = Mapping::new();
+        for (field, value) in spec {
+            match field.as_str() {
+                Some("cpus") => {
+                    if let Some(cpus) = scalar(value) {
+                        ring_spec.insert("cpu".into(), cpus.into());
+                    }
+                }
+                Some("memory") => match scalar(value).as_deref().and_then(memory) {
+                    Some(memory) => {
+                        ring_spec.insert("memory".into(), memory.into());
+                    }
+                    None => warnings.push(format!(
+                        "'deploy.resources.{}.memory' is not a size, ignored",
+                        key
+                    )),
+                },
+                Some(other) => warnings.push(format!(
+                    "'deploy.resources.{}.{}' is not supported, ignored",
+                    key, other
+                )),
+                None => {}
+            }
+        }
+        if !ring_spec.is_empty() {
+            converted.insert(ring_key.into(), Value::Mapping(ring_spec));
+        }
+    }
+    converted
+}
+
+/// A Compose byte size (`512m`, `1gb`, `2G`, bytes) in Ring's notation.
+/// Compose units are binary, so `512m` is `512Mi`.
+fn memory(size: &str) -> Option<String> {
+    let size = size.trim().to_lowercase();
+    if size.chars().all(|c| c.is_ascii_digit()) && !size.is_empty() {
+        return Some(size);
+    }
+    let size = size.strip_suffix('b').unwrap_or(&size);
+    let split = size.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = size.split_at(split);
+    number.parse::<f64>().ok()?;
+    let unit = match unit {
+        "" => return Some(number.to_string()),
+        "k" => "Ki",
+        "m" => "Mi",
+        "g" => "Gi",
+        "t" => "Ti",
+        _ => return None,
+    };
+    Some(format!("{}{}", number, unit))
+}
+
+/// The healthcheck as a `command` check. Compose's probe is Docker's native
+/// `HEALTHCHECK`, which is what a Ring readiness `command` check becomes too.
+fn convert_healthcheck(healthcheck: &Mapping, warnings: &mut Vec<String>) -> Option<Mapping> {
+    if healthcheck.get("disable").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    let command = match healthcheck.get("test")? {
+        Value::String(line) => line.clone(),
+        Value::Sequence(test) => {
+            let test: Vec<String> = test.iter().filter_map(scalar).collect();
+            match test.split_first() {
+                Some((form, args)) if form == "CMD" => shell_words::join(args),
+                Some((form, args)) if form == "CMD-SHELL" => args.join(" "),
+                Some((form, _)) if form == "NONE" => return None,
+                _ => test.join(" "),
+            }
+        }
+        _ => return None,
+    };
+
+    let mut duration = |key: &str, default: &str| -> Option<String> {
+        let value = healthcheck.get(key).and_then(scalar)?;
+        match compose_duration(&value) {
+            Some(duration) => Some(duration),
+            None => {
+                warnings.push(format!(
+                    "'healthcheck.{}' '{}' is not a duration, {} used",
+                    key, value, default
+                ));
+                Some(default.to_string())
+            }
+        }
+    };
+    let interval = duration("interval", "30s").unwrap_or_else(|| "30s".to_string());
+    let timeout = duration("timeout", "30s").unwrap_or_else(|| "30s".to_string());
+    let start_period = duration("start_period", "0s");
+
+    for key in healthcheck.keys().filter_map(Value::as_str) {
+        if !matches!(
+            key,
+            "test" | "interval" | "timeout" | "retries" | "start_period" | "disable"
+        ) {
+            warnings.push(format!("'healthcheck.{}' is not supported, ignored", key));
+        }
+    }
+
+    let mut check = Mapping::new();
+    check.insert("type".into(), "command".into());
+    check.insert("command".into(), command.into());
+    check.insert("interval".into(), interval.into());
+    check.insert("timeout".into(), timeout.into());
+    let threshold = healthcheck
+        .get("retries")
+        .and_then(Value::as_u64)
+        .unwrap_or(3);
+    check.insert("threshold".into(), threshold.into());
+    check.insert("on_failure".into(), "restart".into());
+    check.insert("readiness".into(), true.into());
+    if let Some(start_period) = start_period {
+        check.insert("start_period".into(), start_period.into());
+    }
+    Some(check)
+}
+
+/// A Compose duration (`1m30s`, `500ms`, `2h`) as Ring writes one: whole
+/// seconds, else milliseconds.
+fn compose_duration(value: &str) -> Option<String> {
+    let mut millis: u64 = 0;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let (number, tail) = rest.split_at(digits);
+        let number: u64 = number.parse().ok()?;
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let factor = match unit {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            _ => return None,
+        };
+        millis += number * factor;
+        rest = tail;
+    }
+    if millis.is_multiple_of(1_000) {
+        Some(format!("{}s", millis / 1_000))
+    } else {
+        Some(format!("{}ms", millis))
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn strings(values: Vec<String>) -> Value {
+    Value::Sequence(values.into_iter().map(Value::String).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment<'a>(conversion: &'a Conversion, name: &str) -> &'a Value {
+        &conversion.manifest["deployments"][name]
+    }
+
+    #[test]
+    fn maps_a_service_onto_a_deployment() {
+        let dir = std::env::temp_dir().join("ring_compose_convert");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("web.env"), "LOG_LEVEL=debug\nPORT=80\n").unwrap();
+
+        let compose = r#"
+name: shop
+services:
+  web:
+    image: nginx:1.27
+    command: nginx -g "daemon off;"
+    env_file: web.env
+    environment:
+      - PORT=8080
+      - API_TOKEN
+    labels:
+      team: core
+    ports:
+      - "127.0.0.1:8080:80"
+      - target: 53
+        published: "5353"
+        protocol: udp
+    volumes:
+      - ./html:/usr/share/nginx/html:ro
+      - cache:/var/cache/nginx
+    deploy:
+      replicas: 2
+      resources:
+        limits:
+          cpus: "0.5"
+          memory: 512M
+        reservations:
+          memory: 128m
+    healthcheck:
+      test: ["CMD", "curl", "-f", "http://localhost/"]
+      interval: 1m30s
+      timeout: 5s
+      retries: 5
+    network_mode: host
+volumes:
+  cache:
+    driver: nfs
+"#;
+        let conversion = convert(compose, &dir, None).unwrap();
+        assert!(conversion.warnings.is_empty(), "{:?}", conversion.warnings);
+        let web = deployment(&conversion, "web");
+
+        assert_eq!(web["namespace"], "shop");
+        assert_eq!(web["replicas"], 2);
+        assert_eq!(web["command"][2], "daemon off;");
+        assert_eq!(web["environment"]["LOG_LEVEL"], "debug");
+        assert_eq!(web["environment"]["PORT"], "8080");
+        assert_eq!(web["environment"]["API_TOKEN"], "$API_TOKEN");
+        assert_eq!(web["labels"]["team"], "core");
+
+        assert_eq!(web["ports"][0]["host_ip"], "127.0.0.1");
+        assert_eq!(web["ports"][0]["published"], 8080);
+        assert_eq!(web["ports"][1]["protocol"], "udp");
+
+        assert_eq!(web["volumes"][0]["type"], "bind");
+        assert_eq!(
+            web["volumes"][0]["source"],
+            dir.join("html").display().to_string()
+        );
+        assert_eq!(web["volumes"][0]["permission"], "ro");
+        assert_eq!(web["volumes"][1]["type"], "volume");
+        assert_eq!(web["volumes"][1]["driver"], "nfs");
+
+        assert_eq!(web["resources"]["limits"]["cpu"], "0.5");
+        assert_eq!(web["resources"]["limits"]["memory"], "512Mi");
+        assert_eq!(web["resources"]["requests"]["memory"], "128Mi");
+
+        let check = &web["health_checks"][0];
+        assert_eq!(check["type"], "command");
+        assert_eq!(check["command"], "curl -f http://localhost/");
+        assert_eq!(check["interval"], "90s");
+        assert_eq!(check["threshold"], 5);
+        assert_eq!(web["network"]["mode"], "host");
+    }
+
+    #[test]
+    fn reports_what_it_cannot_carry_over() {
+        let compose = r#"
+services:
+  app:
+    build: .
+  db:
+    image: postgres:16
+    restart: always
+    depends_on: [cache]
+    ports:
+      - "5432"
+      - "9000-9001:9000-9001"
+    volumes:
+      - /var/lib/postgresql/data
+      - type: tmpfs
+        target: /tmp
+    deploy:
+      placement:
+        constraints: [node.role == manager]
+networks:
+  back: {}
+"#;
+        let conversion = convert(compose, Path::new("/srv"), Some("data")).unwrap();
+        let db = deployment(&conversion, "db");
+        assert_eq!(db["namespace"], "data");
+        assert_eq!(db["replicas"], 1);
+        assert!(db.get("ports").is_none());
+        assert!(db.get("volumes").is_none());
+        assert!(conversion.manifest["deployments"].get("app").is_none());
+
+        let warnings = conversion.warnings.join("\n");
+        for expected in [
+            "top-level 'networks' is not supported",
+            "service 'app': no image",
+            "service 'db': 'restart' is not supported",
+            "service 'db': 'depends_on' is not supported",
+            "service 'db': port 5432 has no published port",
+            "service 'db': port 9000-9001:9000-9001 is a range",
+            "service 'db': anonymous volume /var/lib/postgresql/data",
+            "service 'db': tmpfs mount of /tmp",
+            "service 'db': 'deploy.placement' is not supported",
+        ] {
+            assert!(
+                warnings.contains(expected),
+                "missing {:?} in\n{}",
+                expected,
+                warnings
+            );
+        }
+    }
+
+    #[test]
+    fn converts_sizes_and_durations() {
+        assert_eq!(memory("512M").as_deref(), Some("512Mi"));
+        assert_eq!(memory("1gb").as_deref(), Some("1Gi"));
+        assert_eq!(memory("1048576").as_deref(), Some("1048576"));
+        assert_eq!(memory("lots"), None);
+
+        assert_eq!(compose_duration("1m30s").as_deref(), Some("90s"));
+        assert_eq!(compose_duration("1500ms").as_deref(), Some("1500ms"));
+        assert_eq!(compose_duration("1h").as_deref(), Some("3600s"));
+        assert_eq!(compose_duration("soon"), None);
+    }
+}
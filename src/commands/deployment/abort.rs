@@ -0,0 +1,52 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::cli::problem_json::render_response_error;
+use crate::cli::style;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("abort")
+        .about("Abort a canary: tear the new version down and keep the previous one")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .help("The canary deployment ID to abort"),
+        )
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let id = args.get_one::<String>("id").unwrap();
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .post(format!("{}/deployments/{}/abort", api_url, id))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Cannot abort canary {}: {}", id, error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+
+    if !response.status().is_success() {
+        let context = format!("Unable to abort canary '{}'", id);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+
+    style::print_success(&format!(
+        "Canary {} aborted: the previous version keeps serving",
+        id
+    ));
+}
@@ -0,0 +1,421 @@
+//! `ring deployment exec`: a command in a running instance, its stdio attached
+//! to this terminal through the API's `GET /deployments/{id}/exec` WebSocket.
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use futures::{SinkExt, StreamExt};
+use nix::sys::termios::{SetArg, Termios, cfmakeraw, tcgetattr, tcsetattr};
+use serde::Deserialize;
+use std::io::IsTerminal;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::signal::unix::{Signal, SignalKind, signal};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::{self, Message};
+use url::Url;
+
+use crate::api::dto::exec::{self as wire, ExecStatus};
+use crate::cli::problem_json::{http_error, render_error_body};
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code::{self, ExitCode};
+use crate::hypervisor::exec::TerminalSize;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("exec")
+        .about("Run a command in a running instance of a deployment")
+        .arg(
+            Arg::new("deployment")
+                .required(true)
+                .help("The deployment ID or name"),
+        )
+        .arg(
+            Arg::new("namespace").long("namespace").short('n').help(
+                "Namespace of the deployment, to tell apart two deployments of the same name",
+            ),
+        )
+        .arg(
+            Arg::new("instance")
+                .long("instance")
+                .help("Instance to run in (default: the first running one)"),
+        )
+        .arg(
+            Arg::new("no-tty")
+                .long("no-tty")
+                .short('T')
+                .help("Do not allocate a terminal, even when stdin is one")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("command")
+                .num_args(1..)
+                .last(true)
+                .help("Command to run, after `--` (default: sh)"),
+        )
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let target = args.get_one::<String>("deployment").unwrap();
+    let namespace = args.get_one::<String>("namespace");
+    let instance = args.get_one::<String>("instance");
+    let command: Vec<String> = args
+        .get_many::<String>("command")
+        .map(|words| words.cloned().collect())
+        .unwrap_or_default();
+
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+    let id = resolve(client, &api_url, &auth_config.token, target, namespace).await;
+
+    // A terminal only makes sense when both ends of this one are a terminal:
+    // `ring deployment exec web -- cat file > out` must get the raw bytes.
+    let tty = !args.get_flag("no-tty")
+        && std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal();
+    let size = if tty { terminal_size() } else { None };
+
+    let url = match exec_url(&api_url, &id, &command, tty, instance, size) {
+        Ok(url) => url,
+        Err(e) => {
+            eprintln!("Invalid API URL {}: {}", api_url, e);
+            ExitCode::General.exit();
+        }
+    };
+    let mut request = match url.as_str().into_client_request() {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("Invalid API URL {}: {}", api_url, e);
+            ExitCode::General.exit();
+        }
+    };
+    if let Ok(value) = HeaderValue::from_str(&format!("Bearer {}", auth_config.token)) {
+        request.headers_mut().insert("Authorization", value);
+    }
+
+    let socket = match tokio_tungstenite::connect_async(request).await {
+        Ok((socket, _)) => socket,
+        Err(tungstenite::Error::Http(response)) => {
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .and_then(|v| v.to_str().ok());
+            let body = response.body().as_deref().unwrap_or_default();
+            let context = format!("Unable to exec into deployment '{}'", target);
+            let code = render_error_body(&context, response.status(), content_type, body);
+            exit_code::from_http_status(code).exit();
+        }
+        Err(e) => {
+            eprintln!("Cannot exec into deployment {}: {}", target, e);
+            ExitCode::Connection.exit();
+        }
+    };
+
+    let raw_mode = if tty { RawMode::enable() } else { None };
+    let outcome = run(socket, tty).await;
+    // `process::exit` runs no destructor: give the terminal back first.
+    drop(raw_mode);
+
+    match outcome {
+        Ok(code) => std::process::exit(code),
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::Connection.exit();
+        }
+    }
+}
+
+/// The deployment id `target` names: an id as is, otherwise the one
+/// deployment of that name (in `namespace`, when given).
+async fn resolve(
+    client: &reqwest::Client,
+    api_url: &str,
+    token: &str,
+    target: &str,
+    namespace: Option<&String>,
+) -> String {
+    let mut request = client
+        .get(format!("{}/deployments", api_url))
+        .header("Authorization", format!("Bearer {}", token));
+    if let Some(namespace) = namespace {
+        request = request.query(&[("namespace", namespace)]);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Cannot exec into deployment {}: {}", target, error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        eprintln!("{}", http_error(status, "deployment", target));
+        exit_code::from_http_status(status).exit();
+    }
+    let deployments = match response.json::<Vec<Listed>>().await {
+        Ok(deployments) => deployments,
+        Err(e) => {
+            eprintln!("Unable to parse the deployment list: {}", e);
+            ExitCode::General.exit();
+        }
+    };
+
+    match pick(&deployments, target) {
+        Ok(id) => id,
+        Err(Ambiguous(namespaces)) if !namespaces.is_empty() => {
+            eprintln!(
+                "error: deployment '{}' exists in several namespaces ({}); pick one with --namespace",
+                target,
+                namespaces.join(", ")
+            );
+            ExitCode::General.exit();
+        }
+        Err(_) => {
+            eprintln!("{}", http_error(404, "deployment", target));
+            ExitCode::NotFound.exit();
+        }
+    }
+}
+
+/// What name resolution needs of a listed deployment.
+#[derive(Deserialize, Debug)]
+struct Listed {
+    id: String,
+    name: String,
+    namespace: String,
+}
+
+/// Namespaces holding a deployment of the name looked up; empty when none does.
+#[derive(Debug, PartialEq)]
+struct Ambiguous(Vec<String>);
+
+fn pick(deployments: &[Listed], target: &str) -> Result<String, Ambiguous> {
+    if let Some(deployment) = deployments.iter().find(|d| d.id == target) {
+        return Ok(deployment.id.clone());
+    }
+    let named: Vec<&Listed> = deployments.iter().filter(|d| d.name == target).collect();
+    match named.as_slice() {
+        [deployment] => Ok(deployment.id.clone()),
+        named => Err(Ambiguous(
+            named.iter().map(|d| d.namespace.clone()).collect(),
+        )),
+    }
+}
+
+fn exec_url(
+    api_url: &str,
+    id: &str,
+    command: &[String],
+    tty: bool,
+    instance: Option<&String>,
+    size: Option<TerminalSize>,
+) -> Result<Url, String> {
+    let mut url = Url::parse(&format!(
+        "{}/deployments/{}/exec",
+        api_url.trim_end_matches('/'),
+        id
+    ))
+    .map_err(|e| e.to_string())?;
+    let scheme = match url.scheme() {
+        "https" => "wss",
+        _ => "ws",
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| format!("cannot use {} over a WebSocket", url.scheme()))?;
+
+    {
+        let mut query = url.query_pairs_mut();
+        for word in command {
+            query.append_pair("command", word);
+        }
+        if tty {
+            query.append_pair("tty", "true");
+        }
+        if let Some(instance) = instance {
+            query.append_pair("instance", instance);
+        }
+        if let Some(size) = size {
+            query.append_pair("cols", &size.cols.to_string());
+            query.append_pair("rows", &size.rows.to_string());
+        }
+    }
+    Ok(url)
+}
+
+/// Pumps this terminal to the remote process and back until it exits, then
+/// answers its exit code. `Err` when the connection drops before that.
+async fn run<S>(socket: tokio_tungstenite::WebSocketStream<S>, tty: bool) -> Result<i32, String>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut sink, mut stream) = socket.split();
+    let mut stdin = tokio::io::stdin();
+    let mut stdout = tokio::io::stdout();
+    let mut stderr = tokio::io::stderr();
+    let mut window_changes = if tty {
+        signal(SignalKind::window_change()).ok()
+    } else {
+        None
+    };
+    let mut buf = vec![0u8; 8192];
+    let mut stdin_open = true;
+
+    loop {
+        tokio::select! {
+            read = stdin.read(&mut buf), if stdin_open => {
+                let payload = match read {
+                    Ok(n) if n > 0 => &buf[..n],
+                    // End of input: an empty message tells the server.
+                    _ => {
+                        stdin_open = false;
+                        &[][..]
+                    }
+                };
+                let message = Message::Binary(wire::encode(wire::CHANNEL_STDIN, payload).into());
+                if let Err(e) = sink.send(message).await {
+                    return Err(format!("Connection to the instance lost: {}", e));
+                }
+            }
+            Some(()) = window_changed(&mut window_changes) => {
+                if let Some(size) = terminal_size() {
+                    let json = serde_json::to_vec(&size).unwrap_or_default();
+                    let message = Message::Binary(wire::encode(wire::CHANNEL_RESIZE, &json).into());
+                    let _ = sink.send(message).await;
+                }
+            }
+            message = stream.next() => {
+                let data = match message {
+                    Some(Ok(Message::Binary(data))) => data,
+                    Some(Ok(Message::Close(_))) | None => {
+                        return Err("Connection to the instance closed before the command exited".to_string());
+                    }
+                    Some(Err(e)) => return Err(format!("Connection to the instance lost: {}", e)),
+                    Some(Ok(_)) => continue,
+                };
+                match wire::decode(&data) {
+                    Some((wire::CHANNEL_STDOUT, bytes)) => {
+                        let _ = stdout.write_all(bytes).await;
+                        let _ = stdout.flush().await;
+                    }
+                    Some((wire::CHANNEL_STDERR, bytes)) => {
+                        let _ = stderr.write_all(bytes).await;
+                        let _ = stderr.flush().await;
+                    }
+                    Some((wire::CHANNEL_STATUS, json)) => {
+                        let status = serde_json::from_slice::<ExecStatus>(json).ok();
+                        // An exit the runtime could not read is still a failure.
+                        let code = status.and_then(|s| s.exit_code).unwrap_or(1);
+                        return Ok(code as i32);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+async fn window_changed(signal: &mut Option<Signal>) -> Option<()> {
+    match signal {
+        Some(signal) => signal.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn terminal_size() -> Option<TerminalSize> {
+    // SAFETY: `winsize` is plain old data; TIOCGWINSZ only fills it in.
+    let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut ws) } != 0 || ws.ws_col == 0
+    {
+        return None;
+    }
+    Some(TerminalSize {
+        cols: ws.ws_col,
+        rows: ws.ws_row,
+    })
+}
+
+/// This terminal in raw mode, so keys (`^C` included) reach the remote
+/// process as typed. Restored on drop.
+struct RawMode {
+    original: Termios,
+}
+
+impl RawMode {
+    fn enable() -> Option<Self> {
+        let stdin = std::io::stdin();
+        let original = tcgetattr(&stdin).ok()?;
+        let mut raw = original.clone();
+        cfmakeraw(&mut raw);
+        tcsetattr(&stdin, SetArg::TCSANOW, &raw).ok()?;
+        Some(Self { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = tcsetattr(std::io::stdin(), SetArg::TCSANOW, &self.original);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment(id: &str, name: &str, namespace: &str) -> Listed {
+        Listed {
+            id: id.to_string(),
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+        }
+    }
+
+    #[test]
+    fn a_deployment_is_picked_by_id_or_unique_name() {
+        let deployments = [
+            deployment("d1", "web", "prod"),
+            deployment("d2", "web", "staging"),
+            deployment("d3", "worker", "prod"),
+        ];
+        assert_eq!(pick(&deployments, "d2"), Ok("d2".to_string()));
+        assert_eq!(pick(&deployments, "worker"), Ok("d3".to_string()));
+        assert_eq!(
+            pick(&deployments, "web"),
+            Err(Ambiguous(vec!["prod".to_string(), "staging".to_string()]))
+        );
+        assert_eq!(pick(&deployments, "api"), Err(Ambiguous(Vec::new())));
+    }
+
+    #[test]
+    fn the_exec_url_carries_the_command_word_by_word() {
+        let url = exec_url(
+            "https://ring.example.com/",
+            "d1",
+            &["sh".to_string(), "-c".to_string(), "echo a b".to_string()],
+            true,
+            None,
+            Some(TerminalSize { cols: 80, rows: 24 }),
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "wss://ring.example.com/deployments/d1/exec?command=sh&command=-c&command=echo+a+b&tty=true&cols=80&rows=24"
+        );
+
+        let url = exec_url(
+            "http://localhost:3030",
+            "d1",
+            &[],
+            false,
+            Some(&"i1".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "ws://localhost:3030/deployments/d1/exec?instance=i1"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use crate::api::dto::revision::RevisionOutput;
+use crate::cli::output::{output_arg, output_format};
+use crate::cli::problem_json::http_error;
+use crate::cli::style;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+use clap::{Arg, ArgMatches, Command};
+use cli_table::{Table, WithTitle, format::Justify};
+
+pub(crate) fn command_config() -> Command {
+    Command::new("history")
+        .about("List the revisions of a deployment")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .help("The deployment ID to list revisions for"),
+        )
+        .arg(output_arg())
+}
+
+#[derive(Table)]
+struct RevisionTableItem {
+    #[table(title = "Revision", justify = "Justify::Right")]
+    revision: i64,
+    #[table(title = "Created at (UTC)")]
+    created_at: String,
+    #[table(title = "Image")]
+    image: String,
+    #[table(title = "Replicas")]
+    replicas: String,
+    #[table(title = "Deployment ID")]
+    deployment_id: String,
+    #[table(title = "Note")]
+    note: String,
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let id = args.get_one::<String>("id").unwrap();
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .get(format!("{}/deployments/{}/revisions", api_url, id))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Error fetching revisions: {}", error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+
+    let status = response.status();
+    if status != 200 {
+        style::print_error(&http_error(status.as_u16(), "deployment", id));
+        exit_code::from_http_status(status.as_u16()).exit();
+    }
+
+    let body = match response.text().await {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("Failed to read revisions response: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    };
+
+    if output_format(args).is_json() {
+        println!("{}", body);
+        return;
+    }
+
+    let revisions: Vec<RevisionOutput> = match serde_json::from_str(&body) {
+        Ok(list) => list,
+        Err(e) => {
+            eprintln!("Failed to parse revisions: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    };
+
+    if revisions.is_empty() {
+        println!("No revisions recorded for deployment {}", id);
+        return;
+    }
+
+    let rows: Vec<RevisionTableItem> = revisions
+        .into_iter()
+        .map(|revision| RevisionTableItem {
+            revision: revision.revision,
+            created_at: style::format_date(&revision.created_at),
+            image: revision.spec["image"].as_str().unwrap_or("-").to_string(),
+            // The API applies the same default when `replicas` is omitted.
+            replicas: revision.spec["replicas"].as_u64().unwrap_or(1).to_string(),
+            deployment_id: revision.deployment_id,
+            note: revision
+                .rollback_of
+                .map(|restored| format!("rollback to {}", restored))
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    style::print_table(rows.with_title());
+}
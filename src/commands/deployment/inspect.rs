@@ -5,6 +5,7 @@ use crate::cli::style;
 use crate::config::auth::load_auth_config;
 use crate::config::config::Config;
 use crate::exit_code;
+use crate::models::deployments::{LifecycleHook, RestartMode, Strategy};
 use clap::Arg;
 use clap::ArgMatches;
 use clap::Command;
@@ -96,14 +97,167 @@ pub(crate) async fn execute(
                 // `desired_replicas` is the effective target the API already
                 // clamped; `replicas` is what the manifest declared.
                 (Some(policy), Some(target)) => {
+                    let mut targets = Vec::new();
+                    if let Some(cpu) = policy.target_cpu {
+                        targets.push(format!("{:.0}% CPU", cpu));
+                    }
+                    if let Some(memory) = policy.target_memory {
+                        targets.push(format!("{:.0}% of requested memory", memory));
+                    }
+                    for metric in &policy.metrics {
+                        targets.push(format!("{} {} per instance", metric.name, metric.target));
+                    }
                     println!(
-                        "Replicas      : {} (declared {}, autoscale {}-{} targeting {:.0}% CPU)",
-                        target, deployment.replicas, policy.min, policy.max, policy.target_cpu
+                        "Replicas      : {} (declared {}, autoscale {}-{} targeting {})",
+                        target,
+                        deployment.replicas,
+                        policy.min,
+                        policy.max,
+                        targets.join(", ")
                     );
                 }
                 _ => println!("Replicas      : {}", deployment.replicas),
             }
             println!("Restart count : {}", deployment.restart_count);
+            match &deployment.strategy {
+                Some(Strategy::Rolling {
+                    max_surge,
+                    max_unavailable,
+                }) => println!(
+                    "Strategy      : rolling (max surge {}, max unavailable {})",
+                    max_surge, max_unavailable
+                ),
+                Some(Strategy::Recreate) => println!("Strategy      : recreate"),
+                Some(Strategy::Canary {
+                    instances,
+                    analysis_window,
+                }) => println!(
+                    "Strategy      : canary ({} instance(s), analysis window {})",
+                    instances, analysis_window
+                ),
+                None => {}
+            }
+            if let Some(cron) = &deployment.cron {
+                println!(
+                    "Schedule      : {} (UTC, concurrency {}, keeps {} finished run(s))",
+                    cron.schedule, cron.concurrency_policy, cron.history_limit
+                );
+                if let Some(last) = &deployment.last_scheduled_at {
+                    println!("Last trigger  : {}", last);
+                }
+                if let Some(next) = &deployment.next_scheduled_at {
+                    println!("Next trigger  : {}", next);
+                }
+            }
+            if let Some(cronjob_id) = &deployment.cronjob_id {
+                println!("Started by    : cronjob {}", cronjob_id);
+            }
+            if let Some(job) = &deployment.job {
+                let mut policy = vec![format!(
+                    "{} completion(s), {} at a time",
+                    job.completions, job.parallelism
+                )];
+                if let Some(limit) = job.backoff_limit {
+                    policy.push(format!("{} retries", limit));
+                }
+                if let Some(deadline) = &job.active_deadline {
+                    policy.push(format!("deadline {}", deadline));
+                }
+                if let Some(ttl) = &job.ttl_after_finished {
+                    policy.push(format!("deleted {} after it finishes", ttl));
+                }
+                println!("Job policy    : {}", policy.join(", "));
+            }
+            if let Some(code) = deployment.exit_code {
+                println!("Exit code     : {}", code);
+            }
+            if !deployment.depends_on.is_empty() {
+                let dependencies: Vec<String> = deployment
+                    .depends_on
+                    .iter()
+                    .map(|d| format!("{} ({})", d.name, d.condition))
+                    .collect();
+                println!("Depends on    : {}", dependencies.join(", "));
+            }
+            for (label, containers) in [
+                ("Init          ", &deployment.init_containers),
+                ("Sidecars      ", &deployment.sidecars),
+            ] {
+                if !containers.is_empty() {
+                    let containers: Vec<String> = containers
+                        .iter()
+                        .map(|c| format!("{} ({})", c.name, c.image))
+                        .collect();
+                    println!("{}: {}", label, containers.join(", "));
+                }
+            }
+            if let Some(lifecycle) = &deployment.lifecycle {
+                let mut stop = vec![format!(
+                    "{}, {} grace",
+                    lifecycle.stop_signal.as_deref().unwrap_or("SIGTERM"),
+                    lifecycle.stop_grace_period.as_deref().unwrap_or("10s")
+                )];
+                if let Some(hook) = &lifecycle.pre_stop {
+                    stop.push(format!("pre-stop {}", describe_hook(hook)));
+                }
+                println!("Stop          : {}", stop.join(", "));
+                if let Some(hook) = &lifecycle.post_start {
+                    println!("Post-start    : {}", describe_hook(hook));
+                }
+            }
+            if let Some(policy) = &deployment.restart_policy {
+                let (initial, max) = policy.backoff_bounds();
+                let limit = match (policy.mode, policy.max_restarts) {
+                    (RestartMode::Never, _) => "no restarts".to_string(),
+                    (_, Some(max_restarts)) => format!("up to {} restarts", max_restarts),
+                    (_, None) => "no restart limit".to_string(),
+                };
+                println!(
+                    "Restart       : {}, {}, backoff {}s to {}s",
+                    policy.mode,
+                    limit,
+                    initial.as_secs(),
+                    max.as_secs()
+                );
+            }
+            if let Some(idle) = &deployment.idle {
+                let state = if deployment.asleep {
+                    "asleep, woken by the next connection"
+                } else {
+                    "awake"
+                };
+                println!(
+                    "Idle          : scales to zero after {} without a connection ({}, wake timeout {}s)",
+                    idle.after,
+                    state,
+                    idle.wake_timeout().as_secs()
+                );
+            }
+            if let Some(logging) = &deployment.logging {
+                let format = logging.format.as_deref().unwrap_or("detect JSON lines");
+                match &logging.multiline_pattern {
+                    Some(pattern) => println!(
+                        "Logging       : {}, continuation lines match {}",
+                        format, pattern
+                    ),
+                    None => println!("Logging       : {}", format),
+                }
+            }
+            if deployment.paused {
+                println!("Paused        : yes (the scheduler leaves this deployment alone)");
+            }
+            if !deployment.pending_restart.is_empty() {
+                println!(
+                    "Restarting    : {} instance(s) left to replace",
+                    deployment.pending_restart.len()
+                );
+            }
+            if !deployment.defaulted.is_empty() {
+                println!(
+                    "Defaulted     : {} (namespace defaults)",
+                    deployment.defaulted.join(", ")
+                );
+            }
             println!("Created at    : {}", deployment.created_at);
             println!("Updated at    : {}", deployment.updated_at);
             println!();
@@ -152,3 +306,11 @@ pub(crate) async fn execute(
         }
     }
 }
+
+/// `./drain --wait`, or `GET :8080/drain` for an HTTP hook.
+fn describe_hook(hook: &LifecycleHook) -> String {
+    match hook {
+        LifecycleHook::Command(command) => command.join(" "),
+        LifecycleHook::Http(http) => format!("GET :{}{}", http.port, http.path),
+    }
+}
@@ -29,8 +29,8 @@ pub(crate) fn command_config() -> Command {
         .arg(
             Arg::new("type")
                 .long("type")
-                .help("Filter by type (worker or job)")
-                .value_parser(["worker", "job"]),
+                .help("Filter by type (worker, job or cronjob)")
+                .value_parser(["worker", "job", "cronjob"]),
         )
         .arg(
             Arg::new("label")
@@ -180,7 +180,11 @@ pub(crate) async fn execute(
                         };
                         format!("{}/{}{}", deployment.instances.len(), target, marker)
                     },
-                    status: style::status(&deployment.status),
+                    status: if deployment.paused {
+                        format!("{} (paused)", style::status(&deployment.status))
+                    } else {
+                        style::status(&deployment.status)
+                    },
                 })
             }
 
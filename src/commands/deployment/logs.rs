@@ -1,3 +1,4 @@
+use crate::cli::problem_json::ProblemDetails;
 use crate::config::auth::load_auth_config;
 use crate::config::config::Config;
 use crate::exit_code;
@@ -57,6 +58,53 @@ pub(crate) fn command_config() -> Command {
                 .short('c')
                 .help("Filter logs by container/instance name"),
         )
+        .arg(
+            Arg::new("instance")
+                .long("instance")
+                .help("Only these instances (name or prefix), repeatable")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("level")
+                .long("level")
+                .short('l')
+                .help("Only lines of this level (error, warning, info, debug, unknown), repeatable")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("grep")
+                .long("grep")
+                .short('g')
+                .help("Only lines whose message matches this regex"),
+        )
+        .arg(
+            Arg::new("until")
+                .long("until")
+                .help("Only lines up to a relative duration ago (e.g. 5m) or an RFC3339 timestamp"),
+        )
+        .arg(
+            Arg::new("previous")
+                .long("previous")
+                .short('p')
+                .help(
+                    "Show the logs of instances that are gone (crashed, replaced, scaled \
+                     down), from the server's log archive",
+                )
+                .action(ArgAction::SetTrue)
+                .conflicts_with("follow"),
+        )
+}
+
+/// What to ask `GET /deployments/{id}/logs` for.
+struct LogsQuery {
+    tail: Option<u64>,
+    since: Option<String>,
+    until: Option<String>,
+    container: Option<String>,
+    instances: Vec<String>,
+    levels: Vec<String>,
+    grep: Option<String>,
+    previous: bool,
 }
 
 pub(crate) async fn execute(
@@ -66,36 +114,30 @@ pub(crate) async fn execute(
 ) {
     let id = args.get_one::<String>("id").unwrap();
     let follow = args.get_flag("follow");
-    let tail = args.get_one::<u64>("tail").copied();
-    let since = args.get_one::<String>("since").cloned();
-    let container = args.get_one::<String>("container").cloned();
+    let query = LogsQuery {
+        tail: args.get_one::<u64>("tail").copied(),
+        since: args.get_one::<String>("since").cloned(),
+        until: args.get_one::<String>("until").cloned(),
+        container: args.get_one::<String>("container").cloned(),
+        instances: args
+            .get_many::<String>("instance")
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default(),
+        levels: args
+            .get_many::<String>("level")
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default(),
+        grep: args.get_one::<String>("grep").cloned(),
+        previous: args.get_flag("previous"),
+    };
 
     let api_url = configuration.get_api_url();
     let auth_config = load_auth_config(configuration.name.clone());
 
     if follow {
-        follow_logs(
-            id,
-            tail,
-            since,
-            container,
-            &api_url,
-            &auth_config.token,
-            client,
-        )
-        .await;
+        follow_logs(id, &query, &api_url, &auth_config.token, client).await;
     } else {
-        match fetch_logs(
-            id,
-            tail,
-            since.as_deref(),
-            container.as_deref(),
-            &api_url,
-            &auth_config.token,
-            client,
-        )
-        .await
-        {
+        match fetch_logs(id, &query, &api_url, &auth_config.token, client).await {
             Ok(logs) => {
                 if logs.is_empty() {
                     println!("No logs available for this deployment");
@@ -113,24 +155,33 @@ pub(crate) async fn execute(
     }
 }
 
-fn build_url(
-    api_url: &str,
-    id: &str,
-    tail: Option<u64>,
-    since: Option<&str>,
-    container: Option<&str>,
-) -> String {
+fn build_url(api_url: &str, id: &str, query: &LogsQuery) -> String {
     let mut url = format!("{}/deployments/{}/logs", api_url, id);
     let mut params: Vec<String> = Vec::new();
-    if let Some(t) = tail {
+    if let Some(t) = query.tail {
         params.push(format!("tail={}", t));
     }
-    if let Some(s) = since {
+    if let Some(s) = &query.since {
         params.push(format!("since={}", encode_query(s)));
     }
-    if let Some(c) = container {
+    if let Some(u) = &query.until {
+        params.push(format!("until={}", encode_query(u)));
+    }
+    if let Some(c) = &query.container {
         params.push(format!("container={}", encode_query(c)));
     }
+    for instance in &query.instances {
+        params.push(format!("instance={}", encode_query(instance)));
+    }
+    for level in &query.levels {
+        params.push(format!("level={}", encode_query(level)));
+    }
+    if let Some(g) = &query.grep {
+        params.push(format!("grep={}", encode_query(g)));
+    }
+    if query.previous {
+        params.push("previous=true".to_string());
+    }
     if !params.is_empty() {
         url.push('?');
         url.push_str(&params.join("&"));
@@ -140,14 +191,12 @@ fn build_url(
 
 async fn fetch_logs(
     id: &str,
-    tail: Option<u64>,
-    since: Option<&str>,
-    container: Option<&str>,
+    query: &LogsQuery,
     api_url: &str,
     token: &str,
     client: &reqwest::Client,
 ) -> Result<Vec<Log>, FetchLogsError> {
-    let url = build_url(api_url, id, tail, since, container);
+    let url = build_url(api_url, id, query);
 
     let response = client
         .get(&url)
@@ -158,9 +207,17 @@ async fn fetch_logs(
 
     let status = response.status();
     if status != 200 {
+        // A problem's detail says why, e.g. that the archive is disabled.
+        let reason = response
+            .json::<ProblemDetails>()
+            .await
+            .ok()
+            .map(|problem| problem.detail)
+            .filter(|detail| !detail.is_empty())
+            .unwrap_or_else(|| status.to_string());
         return Err(FetchLogsError::Status(
             status.as_u16(),
-            format!("Unable to fetch deployment logs: {}", status),
+            format!("Unable to fetch deployment logs: {}", reason),
         ));
     }
 
@@ -172,23 +229,12 @@ async fn fetch_logs(
 
 async fn follow_logs(
     id: &str,
-    tail: Option<u64>,
-    since: Option<String>,
-    container: Option<String>,
+    query: &LogsQuery,
     api_url: &str,
     token: &str,
     client: &reqwest::Client,
 ) {
-    let initial = fetch_logs(
-        id,
-        tail,
-        since.as_deref(),
-        container.as_deref(),
-        api_url,
-        token,
-        client,
-    )
-    .await;
+    let initial = fetch_logs(id, query, api_url, token, client).await;
 
     let mut seen: HashSet<String> = HashSet::new();
 
@@ -208,17 +254,7 @@ async fn follow_logs(
     loop {
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
 
-        match fetch_logs(
-            id,
-            tail,
-            since.as_deref(),
-            container.as_deref(),
-            api_url,
-            token,
-            client,
-        )
-        .await
-        {
+        match fetch_logs(id, query, api_url, token, client).await {
             Ok(logs) => {
                 for log in logs {
                     let key = log_key(&log);
@@ -240,9 +276,10 @@ async fn follow_logs(
 
 fn log_key(log: &Log) -> String {
     format!(
-        "{}|{}|{}",
+        "{}|{}|{}|{}",
         log.timestamp.clone().unwrap_or_default(),
         log.instance,
+        log.container.clone().unwrap_or_default(),
         log.message
     )
 }
@@ -260,9 +297,54 @@ fn encode_query(value: &str) -> String {
     out
 }
 
+/// `instance` for the main container, `instance/container` for an init
+/// container or a sidecar.
+fn source(log: &Log) -> String {
+    match &log.container {
+        Some(container) => format!("{}/{}", log.instance, container),
+        None => log.instance.clone(),
+    }
+}
+
 fn print_log(log: &Log) {
+    let line = format!("{}{}", log.message, fields(log));
     match &log.timestamp {
-        Some(ts) => println!("[{}] {} {}", ts, log.instance, log.message),
-        None => println!("{} {}", log.instance, log.message),
+        Some(ts) => println!("[{}] {} {}", ts, source(log), line),
+        None => println!("{} {}", source(log), line),
+    }
+}
+
+/// The extra fields of a structured line, as ` key=value` pairs.
+fn fields(log: &Log) -> String {
+    log.fields
+        .iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(s) => format!(" {}={}", key, s),
+            other => format!(" {}={}", key, other),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_become_query_parameters() {
+        let query = LogsQuery {
+            tail: Some(20),
+            since: None,
+            until: Some("5m".to_string()),
+            container: None,
+            instances: vec!["web-1".to_string(), "web-2".to_string()],
+            levels: vec!["error".to_string()],
+            grep: Some("time out|refused".to_string()),
+            previous: false,
+        };
+        assert_eq!(
+            build_url("http://ring:3030", "abc", &query),
+            "http://ring:3030/deployments/abc/logs?tail=20&until=5m&instance=web-1\
+             &instance=web-2&level=error&grep=time%20out%7Crefused"
+        );
     }
 }
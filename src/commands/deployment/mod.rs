@@ -1,7 +1,17 @@
+pub(crate) mod abort;
 pub(crate) mod delete;
 pub(crate) mod events;
+pub(crate) mod exec;
 pub(crate) mod health_checks;
+pub(crate) mod history;
 pub(crate) mod inspect;
 pub(crate) mod list;
 pub(crate) mod logs;
 pub(crate) mod metrics;
+pub(crate) mod pause;
+pub(crate) mod promote;
+pub(crate) mod restart;
+pub(crate) mod resume;
+pub(crate) mod rollback;
+pub(crate) mod runs;
+pub(crate) mod scale;
@@ -0,0 +1,58 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::api::dto::deployment::DeploymentOutput;
+use crate::cli::problem_json::render_response_error;
+use crate::cli::style;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("restart")
+        .about("Recreate every instance of a deployment, one at a time")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .help("The deployment ID to restart"),
+        )
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let id = args.get_one::<String>("id").unwrap();
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .post(format!("{}/deployments/{}/restart", api_url, id))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Cannot restart deployment {}: {}", id, error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+
+    if !response.status().is_success() {
+        let context = format!("Unable to restart deployment '{}'", id);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+
+    match response.json::<DeploymentOutput>().await {
+        Ok(deployment) => style::print_success(&format!(
+            "Rolling restart of {}/{} started ({} instance(s) to replace)",
+            deployment.namespace,
+            deployment.name,
+            deployment.pending_restart.len()
+        )),
+        Err(_) => style::print_success(&format!("Rolling restart of {} started", id)),
+    }
+}
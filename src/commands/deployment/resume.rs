@@ -0,0 +1,49 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::cli::problem_json::render_response_error;
+use crate::cli::style;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("resume")
+        .about("Resume reconciliation of a paused deployment")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .help("The deployment ID to resume"),
+        )
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let id = args.get_one::<String>("id").unwrap();
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .post(format!("{}/deployments/{}/resume", api_url, id))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Cannot resume deployment {}: {}", id, error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+
+    if !response.status().is_success() {
+        let context = format!("Unable to resume deployment '{}'", id);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+
+    style::print_success(&format!("Deployment {} resumed", id));
+}
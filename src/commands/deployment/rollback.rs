@@ -0,0 +1,68 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::api::dto::deployment::DeploymentOutput;
+use crate::cli::problem_json::render_response_error;
+use crate::cli::style;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("rollback")
+        .about("Roll a deployment back to a previous revision")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .help("The deployment ID to roll back"),
+        )
+        .arg(
+            Arg::new("revision")
+                .long("revision")
+                .short('r')
+                .value_parser(clap::value_parser!(i64))
+                .help("Revision to restore (see `ring deployment history`); defaults to the previous one"),
+        )
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let id = args.get_one::<String>("id").unwrap();
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let mut url = format!("{}/deployments/{}/rollback", api_url, id);
+    if let Some(revision) = args.get_one::<i64>("revision") {
+        url.push_str(&format!("?revision={}", revision));
+    }
+
+    let request = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Cannot roll back deployment {}: {}", id, error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+
+    if !response.status().is_success() {
+        let context = format!("Unable to roll back deployment '{}'", id);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+
+    match response.json::<DeploymentOutput>().await {
+        Ok(deployment) => style::print_success(&format!(
+            "Deployment {}/{} rolled back (new deployment {}, image {})",
+            deployment.namespace, deployment.name, deployment.id, deployment.image
+        )),
+        Err(_) => style::print_success(&format!("Deployment {} rolled back", id)),
+    }
+}
@@ -0,0 +1,110 @@
+use crate::api::dto::run::RunOutput;
+use crate::cli::output::{output_arg, output_format};
+use crate::cli::problem_json::render_response_error;
+use crate::cli::style;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+use clap::{Arg, ArgMatches, Command};
+use cli_table::{Table, WithTitle};
+
+pub(crate) fn command_config() -> Command {
+    Command::new("runs")
+        .about("List the runs of a cronjob")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .help("The cronjob deployment ID to list runs for"),
+        )
+        .arg(output_arg())
+}
+
+#[derive(Table)]
+struct RunTableItem {
+    #[table(title = "Run ID")]
+    id: String,
+    #[table(title = "Name")]
+    name: String,
+    #[table(title = "Status")]
+    status: String,
+    #[table(title = "Restarts")]
+    restart_count: u32,
+    #[table(title = "Started at (UTC)")]
+    created_at: String,
+    #[table(title = "Finished at (UTC)")]
+    finished_at: String,
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let id = args.get_one::<String>("id").unwrap();
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .get(format!("{}/deployments/{}/runs", api_url, id))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Error fetching runs: {}", error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+
+    if !response.status().is_success() {
+        let context = format!("Unable to list the runs of '{}'", id);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+
+    let body = match response.text().await {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("Failed to read runs response: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    };
+
+    if output_format(args).is_json() {
+        println!("{}", body);
+        return;
+    }
+
+    let runs: Vec<RunOutput> = match serde_json::from_str(&body) {
+        Ok(list) => list,
+        Err(e) => {
+            eprintln!("Failed to parse runs: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    };
+
+    if runs.is_empty() {
+        println!("Cronjob {} has not started any run yet", id);
+        return;
+    }
+
+    let rows: Vec<RunTableItem> = runs
+        .into_iter()
+        .map(|run| RunTableItem {
+            id: run.id,
+            name: run.name,
+            status: style::status(&run.status),
+            restart_count: run.restart_count,
+            created_at: style::format_date(&run.created_at),
+            finished_at: run
+                .finished_at
+                .as_deref()
+                .map(style::format_date)
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+
+    style::print_table(rows.with_title());
+}
@@ -0,0 +1,69 @@
+use clap::{Arg, ArgMatches, Command};
+use serde_json::json;
+
+use crate::api::dto::deployment::DeploymentOutput;
+use crate::cli::problem_json::render_response_error;
+use crate::cli::style;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("scale")
+        .about("Change the number of replicas of a deployment in place")
+        .arg(
+            Arg::new("id")
+                .required(true)
+                .help("The deployment ID to scale"),
+        )
+        .arg(
+            Arg::new("replicas")
+                .long("replicas")
+                .required(true)
+                .value_parser(clap::value_parser!(u32))
+                .help("The new number of replicas"),
+        )
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let id = args.get_one::<String>("id").unwrap();
+    let replicas = *args.get_one::<u32>("replicas").unwrap();
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .post(format!("{}/deployments/{}/scale", api_url, id))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .json(&json!({ "replicas": replicas }))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Cannot scale deployment {}: {}", id, error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+
+    if !response.status().is_success() {
+        let context = format!("Unable to scale deployment '{}'", id);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+
+    match response.json::<DeploymentOutput>().await {
+        Ok(deployment) => style::print_success(&format!(
+            "Deployment {}/{} scaled to {} replicas",
+            deployment.namespace, deployment.name, deployment.replicas
+        )),
+        Err(_) => style::print_success(&format!(
+            "Deployment {} scaled to {} replicas",
+            id, replicas
+        )),
+    }
+}
@@ -1,6 +1,7 @@
 pub(crate) mod apply;
 pub(crate) mod completions;
 pub(crate) mod context;
+pub(crate) mod convert;
 pub(crate) mod dashboard;
 pub(crate) mod deployment;
 pub(crate) mod doctor;
@@ -0,0 +1,73 @@
+use crate::api::dto::namespace::ManifestExport;
+use crate::cli::problem_json::render_response_error;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("export")
+        .about("Print a namespace as a manifest ring apply takes back")
+        .arg(Arg::new("namespace").required(true).help("Namespace name"))
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_parser(["yaml", "json"])
+                .default_value("yaml")
+                .help("Manifest format"),
+        )
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let namespace = args
+        .get_one::<String>("namespace")
+        .expect("namespace is required");
+    let output = args.get_one::<String>("output").map(String::as_str);
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .get(format!("{}/namespaces/{}/export", api_url, namespace))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Failed to export namespace: {}", error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+    if !response.status().is_success() {
+        let context = format!("Unable to export namespace '{}'", namespace);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+    let manifest: ManifestExport = match response.json().await {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("Failed to parse the exported manifest: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    };
+
+    let rendered = match output {
+        Some("json") => serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string()),
+        _ => serde_yaml::to_string(&manifest).map_err(|e| e.to_string()),
+    };
+    match rendered {
+        Ok(rendered) => println!("{}", rendered.trim_end()),
+        Err(e) => {
+            eprintln!("Failed to render the manifest: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    }
+}
@@ -1,5 +1,8 @@
 pub(crate) mod audit;
 pub(crate) mod create;
 pub(crate) mod delete;
+pub(crate) mod export;
 pub(crate) mod list;
 pub(crate) mod prune;
+pub(crate) mod quota;
+pub(crate) mod usage;
@@ -0,0 +1,131 @@
+use crate::cli::problem_json::render_response_error;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
+use serde_json::{Map, Value, json};
+
+/// Count caps, as `(flag, quota key, help)`.
+const COUNTS: &[(&str, &str, &str)] = &[
+    (
+        "max-deployments",
+        "max_deployments",
+        "Deployments the namespace may hold",
+    ),
+    (
+        "max-replicas",
+        "max_replicas",
+        "Instances summed over its deployments",
+    ),
+    (
+        "max-volumes",
+        "max_volumes",
+        "Volumes the namespace may hold",
+    ),
+    (
+        "max-secrets",
+        "max_secrets",
+        "Secrets the namespace may hold",
+    ),
+];
+
+/// Resource caps, as `(flag, quota section, key)`.
+const AMOUNTS: &[(&str, &str, &str)] = &[
+    ("limits-cpu", "limits", "cpu"),
+    ("limits-memory", "limits", "memory"),
+    ("requests-cpu", "requests", "cpu"),
+    ("requests-memory", "requests", "memory"),
+];
+
+pub(crate) fn command_config() -> Command {
+    let mut command = Command::new("quota")
+        .about("Set the quota of a namespace (replaces the current one)")
+        .arg(Arg::new("namespace").required(true).help("Namespace name"))
+        .arg(
+            Arg::new("clear")
+                .long("clear")
+                .action(ArgAction::SetTrue)
+                .help("Lift every cap"),
+        );
+    for (flag, _, help) in COUNTS {
+        command = command.arg(
+            Arg::new(*flag)
+                .long(*flag)
+                .value_parser(clap::value_parser!(u32))
+                .conflicts_with("clear")
+                .help(*help),
+        );
+    }
+    for (flag, _, key) in AMOUNTS {
+        command = command.arg(Arg::new(*flag).long(*flag).conflicts_with("clear").help(
+            if *key == "cpu" {
+                "Summed CPU over every instance, e.g. 4 or 1500m"
+            } else {
+                "Summed memory over every instance, e.g. 8Gi"
+            },
+        ));
+    }
+    command
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let namespace = args
+        .get_one::<String>("namespace")
+        .expect("namespace is required");
+
+    let mut quota = Map::new();
+    for (flag, key, _) in COUNTS {
+        if let Some(value) = args.get_one::<u32>(flag) {
+            quota.insert(key.to_string(), json!(value));
+        }
+    }
+    for (flag, section, key) in AMOUNTS {
+        if let Some(value) = args.get_one::<String>(flag) {
+            quota
+                .entry(section.to_string())
+                .or_insert_with(|| json!({}))
+                .as_object_mut()
+                .expect("quota sections are objects")
+                .insert(key.to_string(), json!(value));
+        }
+    }
+    if quota.is_empty() && !args.get_flag("clear") {
+        eprintln!("Nothing to set: pass at least one cap, or --clear to lift them all");
+        exit_code::ExitCode::General.exit();
+    }
+
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+    let request = client
+        .put(format!("{}/namespaces/{}/quota", api_url, namespace))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .json(&Value::Object(quota))
+        .send()
+        .await;
+
+    match request {
+        Ok(response) if response.status().is_success() => {
+            if args.get_flag("clear") {
+                println!("Quota of namespace '{}' lifted", namespace);
+            } else {
+                println!("Quota of namespace '{}' updated", namespace);
+            }
+        }
+        Ok(response) => {
+            let context = format!("Unable to set the quota of namespace '{}'", namespace);
+            let code = render_response_error(&context, response).await;
+            exit_code::from_http_status(code).exit();
+        }
+        Err(error) => {
+            eprintln!("Failed to set namespace quota: {}", error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    }
+}
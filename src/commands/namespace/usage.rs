@@ -0,0 +1,152 @@
+use crate::cli::problem_json::render_response_error;
+use crate::config::auth::load_auth_config;
+use crate::config::config::Config;
+use crate::exit_code;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command;
+use cli_table::{Table, WithTitle, print_stdout};
+use serde::Deserialize;
+
+pub(crate) fn command_config() -> Command {
+    Command::new("usage")
+        .about("Show what a namespace holds next to its quota")
+        .arg(Arg::new("namespace").required(true).help("Namespace name"))
+}
+
+#[derive(Table)]
+struct UsageTableItem {
+    #[table(title = "Resource")]
+    resource: &'static str,
+    #[table(title = "Used")]
+    used: String,
+    #[table(title = "Quota")]
+    quota: String,
+}
+
+#[derive(Deserialize, Default)]
+struct Amounts {
+    cpu: Option<String>,
+    memory: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Quota {
+    max_deployments: Option<u32>,
+    max_replicas: Option<u32>,
+    max_volumes: Option<u32>,
+    max_secrets: Option<u32>,
+    #[serde(default)]
+    limits: Option<Amounts>,
+    #[serde(default)]
+    requests: Option<Amounts>,
+}
+
+#[derive(Deserialize)]
+struct Used {
+    deployments: u32,
+    replicas: u32,
+    volumes: u32,
+    secrets: u32,
+    limits: Amounts,
+    requests: Amounts,
+}
+
+#[derive(Deserialize)]
+struct UsageOutput {
+    quota: Quota,
+    used: Used,
+}
+
+fn cap<T: ToString>(value: Option<T>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub(crate) async fn execute(
+    args: &ArgMatches,
+    mut configuration: Config,
+    client: &reqwest::Client,
+) {
+    let namespace = args
+        .get_one::<String>("namespace")
+        .expect("namespace is required");
+    let api_url = configuration.get_api_url();
+    let auth_config = load_auth_config(configuration.name.clone());
+
+    let request = client
+        .get(format!("{}/namespaces/{}/usage", api_url, namespace))
+        .header("Authorization", format!("Bearer {}", auth_config.token))
+        .send()
+        .await;
+
+    let response = match request {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("Failed to fetch namespace usage: {}", error);
+            exit_code::from_reqwest_error(&error).exit();
+        }
+    };
+    if !response.status().is_success() {
+        let context = format!("Unable to fetch the usage of namespace '{}'", namespace);
+        let code = render_response_error(&context, response).await;
+        exit_code::from_http_status(code).exit();
+    }
+    let usage: UsageOutput = match response.json().await {
+        Ok(usage) => usage,
+        Err(e) => {
+            eprintln!("Failed to parse namespace usage: {}", e);
+            exit_code::ExitCode::General.exit();
+        }
+    };
+
+    let quota = usage.quota;
+    let used = usage.used;
+    let limits = quota.limits.unwrap_or_default();
+    let requests = quota.requests.unwrap_or_default();
+    let rows = vec![
+        UsageTableItem {
+            resource: "deployments",
+            used: used.deployments.to_string(),
+            quota: cap(quota.max_deployments),
+        },
+        UsageTableItem {
+            resource: "replicas",
+            used: used.replicas.to_string(),
+            quota: cap(quota.max_replicas),
+        },
+        UsageTableItem {
+            resource: "limits.cpu",
+            used: used.limits.cpu.unwrap_or_default(),
+            quota: cap(limits.cpu),
+        },
+        UsageTableItem {
+            resource: "limits.memory",
+            used: used.limits.memory.unwrap_or_default(),
+            quota: cap(limits.memory),
+        },
+        UsageTableItem {
+            resource: "requests.cpu",
+            used: used.requests.cpu.unwrap_or_default(),
+            quota: cap(requests.cpu),
+        },
+        UsageTableItem {
+            resource: "requests.memory",
+            used: used.requests.memory.unwrap_or_default(),
+            quota: cap(requests.memory),
+        },
+        UsageTableItem {
+            resource: "volumes",
+            used: used.volumes.to_string(),
+            quota: cap(quota.max_volumes),
+        },
+        UsageTableItem {
+            resource: "secrets",
+            used: used.secrets.to_string(),
+            quota: cap(quota.max_secrets),
+        },
+    ];
+
+    print_stdout(rows.with_title()).expect("");
+}
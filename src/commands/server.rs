@@ -353,6 +353,64 @@ pub(crate) async fn execute(
         });
     }
 
+    // Ports of the deployments with an `idle:` block: Ring holds them so it
+    // can scale those deployments to zero and wake them on the next
+    // connection. See `scheduler::idle`.
+    {
+        let idle_pool = pool.clone();
+        let idle_runtimes = runtimes.clone();
+        let idle_interval = configuration.server.scheduler.interval;
+        task::spawn(async move {
+            crate::scheduler::idle::run(idle_pool, idle_runtimes, idle_interval).await;
+        });
+    }
+
+    // Server-side copy of every instance's output (opt-in), so logs outlive
+    // the containers that wrote them. See `scheduler::log_archive`.
+    if let Some(archive) =
+        crate::scheduler::log_archive::LogArchive::from_config(&configuration.server.logs.archive)
+    {
+        info!(
+            "Log archive enabled in {}",
+            configuration.server.logs.archive.resolved_directory()
+        );
+        let archive_pool = pool.clone();
+        let archive_runtimes = runtimes.clone();
+        let archive_interval = configuration.server.scheduler.interval;
+        task::spawn(async move {
+            crate::scheduler::log_archive::run(
+                Arc::new(archive),
+                archive_pool,
+                archive_runtimes,
+                archive_interval,
+            )
+            .await;
+        });
+    }
+
+    // Workload lines shipped to syslog, files or an OTLP collector, by
+    // namespace. See `scheduler::log_forwarder`.
+    if let Some(forwarder) =
+        crate::scheduler::log_forwarder::LogForwarder::from_config(&configuration.server.logs)
+    {
+        info!(
+            "Forwarding workload logs to {}",
+            forwarder.sink_names().join(", ")
+        );
+        let forwarder_pool = pool.clone();
+        let forwarder_runtimes = runtimes.clone();
+        let forwarder_interval = configuration.server.scheduler.interval;
+        task::spawn(async move {
+            crate::scheduler::log_forwarder::run(
+                Arc::new(forwarder),
+                forwarder_pool,
+                forwarder_runtimes,
+                forwarder_interval,
+            )
+            .await;
+        });
+    }
+
     // The scheduler reads the same snapshot the API serves, so autoscaling
     // decisions use the numbers already being collected rather than issuing a
     // second round of runtime stats calls.
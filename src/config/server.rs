@@ -9,6 +9,7 @@
 //! `client {}` / `server {}` stanzas: one tool, both roles, one file.
 
 use serde::Deserialize;
+use std::collections::BTreeMap;
 
 /// Top-level `[server]` table. Shared by the whole file (a host runs one daemon,
 /// whatever client contexts point at it).
@@ -22,6 +23,8 @@ pub(crate) struct ServerConfig {
     pub(crate) dashboard: DashboardConfig,
     #[serde(default)]
     pub(crate) telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub(crate) logs: WorkloadLogsConfig,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -480,6 +483,241 @@ impl LogsConfig {
     }
 }
 
+/// `[server.logs]` — what Ring does with the output of the workloads it runs.
+/// Not to be confused with `[server.telemetry.logs]`, which exports the
+/// server's own log.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub(crate) struct WorkloadLogsConfig {
+    #[serde(default)]
+    pub(crate) archive: LogArchiveConfig,
+    /// Destinations workload lines can be forwarded to, by name
+    /// (`[server.logs.sinks.<name>]`). Nothing is sent to a sink no route
+    /// names.
+    #[serde(default)]
+    pub(crate) sinks: BTreeMap<String, LogSinkConfig>,
+    /// Which lines go to which sinks (`[[server.logs.routes]]`). A line goes
+    /// to the sinks of every route it matches. No route: forwarding is off.
+    #[serde(default)]
+    pub(crate) routes: Vec<LogRouteConfig>,
+}
+
+/// One `[server.logs.sinks.<name>]`, told apart by its `type`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum LogSinkConfig {
+    /// RFC 5424 syslog.
+    Syslog {
+        /// `udp://host:port`, `tcp://host:port` (octet-counted framing, RFC
+        /// 6587) or `unix:///dev/log`. The port defaults to 514.
+        address: String,
+        /// Syslog facility name, e.g. `local0`. Defaults to `user`.
+        #[serde(default = "default_syslog_facility")]
+        facility: String,
+    },
+    /// One JSON object per line in a local file, rotated by size.
+    File {
+        path: String,
+        /// Size (bytes) at which the file is rotated. Defaults to 10 MiB.
+        #[serde(default = "default_sink_file_max_bytes")]
+        max_bytes: u64,
+        /// Rotated files kept next to the live one. Defaults to 5.
+        #[serde(default = "default_sink_file_max_files")]
+        max_files: u32,
+    },
+    /// OTLP/gRPC logs, through the same exporter as `[server.telemetry.logs]`.
+    Otlp {
+        /// Collector endpoint. Defaults to `http://127.0.0.1:4317`.
+        #[serde(default = "default_logs_endpoint")]
+        endpoint: String,
+        /// `service.name` resource attribute. Defaults to `ring-workloads`.
+        #[serde(default = "default_sink_service_name")]
+        service_name: String,
+    },
+}
+
+fn default_syslog_facility() -> String {
+    "user".to_string()
+}
+
+fn default_sink_file_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_sink_file_max_files() -> u32 {
+    5
+}
+
+fn default_sink_service_name() -> String {
+    "ring-workloads".to_string()
+}
+
+/// One `[[server.logs.routes]]` rule.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct LogRouteConfig {
+    /// Namespaces whose lines the rule takes. Empty or absent for all.
+    #[serde(default)]
+    pub(crate) namespaces: Vec<String>,
+    /// Levels it takes, as `classify_log` reports them. Empty or absent for
+    /// all.
+    #[serde(default)]
+    pub(crate) levels: Vec<String>,
+    /// Names of the `[server.logs.sinks]` the lines go to.
+    pub(crate) sinks: Vec<String>,
+}
+
+/// `[server.logs.archive]` — keep a copy of every instance's output on the
+/// server, so `ring deployment logs --previous` still has something to show
+/// once the runtime has removed a crashed or replaced container. Opt-in: with
+/// `enabled` false (the default) nothing is collected and logs are read from
+/// the runtime only, as before.
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct LogArchiveConfig {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    /// Where the segments are written, one sub-directory per namespace and
+    /// deployment name. Defaults to `<config dir>/logs`.
+    pub(crate) directory: Option<String>,
+    /// Size (bytes) at which a deployment's live segment is rotated. Defaults
+    /// to 10 MiB.
+    #[serde(default = "default_archive_max_segment_bytes")]
+    pub(crate) max_segment_bytes: u64,
+    /// Rotated segments kept per deployment, on top of the live one. Defaults
+    /// to 5.
+    #[serde(default = "default_archive_max_segments")]
+    pub(crate) max_segments: u32,
+    /// Segments last written longer ago than this are deleted. Defaults to a
+    /// week (168 hours).
+    #[serde(default = "default_archive_max_age_hours")]
+    pub(crate) max_age_hours: u64,
+}
+
+fn default_archive_max_segment_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_archive_max_segments() -> u32 {
+    5
+}
+
+fn default_archive_max_age_hours() -> u64 {
+    168
+}
+
+impl Default for LogArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: None,
+            max_segment_bytes: default_archive_max_segment_bytes(),
+            max_segments: default_archive_max_segments(),
+            max_age_hours: default_archive_max_age_hours(),
+        }
+    }
+}
+
+impl LogArchiveConfig {
+    pub(crate) fn resolved_directory(&self) -> String {
+        self.directory
+            .clone()
+            .unwrap_or_else(|| format!("{}/logs", crate::config::config::get_config_dir()))
+    }
+}
+
+#[cfg(test)]
+mod logs_tests {
+    use super::*;
+
+    #[test]
+    fn archive_is_off_by_default() {
+        let cfg: ServerConfig = toml::from_str("").unwrap();
+        assert!(!cfg.logs.archive.enabled);
+        assert_eq!(cfg.logs.archive.max_segment_bytes, 10 * 1024 * 1024);
+        assert_eq!(cfg.logs.archive.max_segments, 5);
+        assert_eq!(cfg.logs.archive.max_age_hours, 168);
+    }
+
+    #[test]
+    fn archive_block_parses_from_toml() {
+        let cfg: ServerConfig = toml::from_str(
+            r#"
+            [logs.archive]
+            enabled = true
+            directory = "/var/lib/ring/logs"
+            max_segment_bytes = 1048576
+            max_age_hours = 24
+            "#,
+        )
+        .unwrap();
+        assert!(cfg.logs.archive.enabled);
+        assert_eq!(cfg.logs.archive.resolved_directory(), "/var/lib/ring/logs");
+        assert_eq!(cfg.logs.archive.max_segment_bytes, 1_048_576);
+        assert_eq!(cfg.logs.archive.max_segments, 5);
+        assert_eq!(cfg.logs.archive.max_age_hours, 24);
+    }
+
+    #[test]
+    fn sinks_and_routes_parse_from_toml() {
+        let cfg: ServerConfig = toml::from_str(
+            r#"
+            [logs.sinks.central]
+            type = "syslog"
+            address = "udp://logs.internal:514"
+            facility = "local0"
+
+            [logs.sinks.audit]
+            type = "file"
+            path = "/var/log/ring/workloads.log"
+
+            [logs.sinks.otel]
+            type = "otlp"
+            endpoint = "http://collector:4317"
+
+            [[logs.routes]]
+            sinks = ["central"]
+
+            [[logs.routes]]
+            namespaces = ["payments"]
+            levels = ["error", "warning"]
+            sinks = ["audit", "otel"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.logs.sinks["central"],
+            LogSinkConfig::Syslog {
+                address: "udp://logs.internal:514".to_string(),
+                facility: "local0".to_string(),
+            }
+        );
+        assert_eq!(
+            cfg.logs.sinks["audit"],
+            LogSinkConfig::File {
+                path: "/var/log/ring/workloads.log".to_string(),
+                max_bytes: 10 * 1024 * 1024,
+                max_files: 5,
+            }
+        );
+        assert_eq!(
+            cfg.logs.sinks["otel"],
+            LogSinkConfig::Otlp {
+                endpoint: "http://collector:4317".to_string(),
+                service_name: "ring-workloads".to_string(),
+            }
+        );
+        assert!(cfg.logs.routes[0].namespaces.is_empty());
+        assert_eq!(cfg.logs.routes[1].namespaces, vec!["payments"]);
+        assert_eq!(cfg.logs.routes[1].sinks, vec!["audit", "otel"]);
+
+        let unknown = toml::from_str::<ServerConfig>(
+            r#"
+            [logs.sinks.kafka]
+            type = "kafka"
+            "#,
+        );
+        assert!(unknown.is_err());
+    }
+}
+
 #[cfg(test)]
 mod telemetry_tests {
     use super::*;
@@ -29,7 +29,8 @@ pub(crate) const KIND_DEPLOYMENT_HEALTH_CHECK_FAILED: &str = "deployment.health_
 
 /// Emitted as a rolling update progresses: a parent instance drained (`step`),
 /// the rollout finished (`complete`), or it was abandoned because the child
-/// never became healthy (`failed`).
+/// never became healthy (`failed`). `reason` tells a new release (`update`)
+/// from an in-place rolling restart (`restart`).
 pub(crate) const KIND_DEPLOYMENT_ROLLING_UPDATE: &str = "deployment.rolling_update";
 
 /// Emitted when the reconciler adds or removes one instance to converge on the
@@ -194,7 +195,10 @@ impl Event {
     /// Build a `deployment.rolling_update` event for the *child* deployment
     /// being rolled out. `phase` is `step` (a parent instance was drained),
     /// `complete` (parent fully replaced), or `failed` (child never became
-    /// healthy; parent left running). `drained_instance_id` is set on `step`.
+    /// healthy; parent left running). A canary adds `canary_started`,
+    /// `canary_promoted` and `canary_aborted`. `drained_instance_id` is set on `step`.
+    /// Carries `reason: update`; see [`Event::deployment_restart`] for the
+    /// in-place variant.
     pub(crate) fn deployment_rolling_update(
         child: &Deployment,
         parent_id: &str,
@@ -212,6 +216,33 @@ impl Event {
                 "parent_id": parent_id,
                 "phase": phase,
                 "drained_instance_id": drained_instance_id,
+                "reason": "update",
+            }),
+        }
+    }
+
+    /// Build a `deployment.rolling_update` event for a rolling restart
+    /// (`POST /deployments/{id}/restart`). Same payload as
+    /// [`Event::deployment_rolling_update`], with `reason: restart` and no
+    /// parent: the deployment replaces its own instances. `phase` is `step`
+    /// (one instance replaced, `drained_instance_id` set) or `complete`.
+    pub(crate) fn deployment_restart(
+        deployment: &Deployment,
+        phase: &str,
+        drained_instance_id: Option<&str>,
+    ) -> Self {
+        Event {
+            kind: KIND_DEPLOYMENT_ROLLING_UPDATE.to_string(),
+            payload: json!({
+                "schema_version": SCHEMA_VERSION,
+                "deployment_id": deployment.id,
+                "namespace": deployment.namespace,
+                "name": deployment.name,
+                "kind": deployment.kind,
+                "parent_id": null,
+                "phase": phase,
+                "drained_instance_id": drained_instance_id,
+                "reason": "restart",
             }),
         }
     }
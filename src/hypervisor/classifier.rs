@@ -193,8 +193,6 @@ pub(crate) fn apply_vm_start_failure(
     runtime: &str,
     bound_status: DeploymentStatus,
 ) {
-    use crate::models::deployments::MAX_RESTART_COUNT;
-
     let (status, reason) = classify_vm_start_error(err);
     deployment.emit_event("error", format!("{}", err), runtime, Some(reason));
 
@@ -211,13 +209,13 @@ pub(crate) fn apply_vm_start_failure(
             // report 5 restarts having never started a single process, sending
             // whoever read it looking for an instability that never existed.
             if !scheduler_skips_by_status(&terminal) {
-                deployment.restart_count = MAX_RESTART_COUNT;
+                deployment.exhaust_restarts();
             }
             deployment.status = terminal;
         }
         None => {
             deployment.restart_count += 1;
-            if deployment.restart_count >= MAX_RESTART_COUNT {
+            if deployment.restarts_exhausted() {
                 deployment.status = bound_status;
             }
         }
@@ -481,6 +479,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
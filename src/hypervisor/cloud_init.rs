@@ -515,6 +515,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
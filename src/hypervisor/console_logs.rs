@@ -7,9 +7,10 @@
 //! process' redirected stdout. This module turns that file into the
 //! line-oriented stream the `RuntimeLifecycle` trait expects:
 //!
-//! - [`read_lines`] — synchronous one-shot read with `tail` (last N lines)
-//!   and `since` (drop lines older than N seconds, best-effort: the console
-//!   has no native timestamps so we fall back to file mtime windowing).
+//! - [`read_lines`] — synchronous one-shot read with `tail` (last N lines),
+//!   `since` (drop lines older than N seconds, best-effort: the console
+//!   has no native timestamps so we fall back to file mtime windowing) and
+//!   `until` (see [`written_until`]).
 //! - [`stream_lines`] — async streaming reader that follows the file as the
 //!   VMM appends to it, equivalent to `tail -f`.
 //!
@@ -17,6 +18,7 @@
 //! runtime's `lifecycle.rs`) wrap them in a `Log` with `classify_log` /
 //! `extract_date`.
 
+use crate::hypervisor::lifecycle_trait::{date_timestamp, extract_date};
 use futures::stream::{self, Stream, StreamExt};
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
@@ -27,7 +29,7 @@ use tracing::{debug, warn};
 /// rotated backup first (`<path>.N`), then `.N-1`, ..., `.1`, then the live
 /// `<path>`. Missing files are skipped silently — rotations may be sparse if
 /// the VM has not produced enough output to fill every slot yet.
-fn rotated_files_in_read_order(path: &Path) -> Vec<PathBuf> {
+pub(crate) fn rotated_files_in_read_order(path: &Path) -> Vec<PathBuf> {
     let mut files = Vec::new();
     // Discover up to a generous upper bound; in practice the configured
     // `max_console_log_backups` caps this at 3-10.
@@ -64,9 +66,17 @@ fn backup_path(path: &Path, idx: u32) -> PathBuf {
 /// same moment for now) is older. Approximate by design — the serial
 /// console has no native timestamps.
 ///
+/// `until`: unix timestamp, applied to each file with [`written_until`]
+/// before `tail` is counted.
+///
 /// Reads through any rotated backups (`<path>.1`, `.2`, ...) so a `--tail N`
 /// that spans a rotation boundary still returns the requested history.
-pub(crate) async fn read_lines(path: &Path, tail: Option<&str>, since: Option<i32>) -> Vec<String> {
+pub(crate) async fn read_lines(
+    path: &Path,
+    tail: Option<&str>,
+    since: Option<i32>,
+    until: Option<i32>,
+) -> Vec<String> {
     let mut lines: Vec<String> = Vec::new();
     for file in rotated_files_in_read_order(path) {
         let bytes = match tokio::fs::read(&file).await {
@@ -76,7 +86,11 @@ pub(crate) async fn read_lines(path: &Path, tail: Option<&str>, since: Option<i3
         // The serial console is not guaranteed to be UTF-8 (early kernel output
         // may carry stray bytes). Lossy conversion keeps us robust.
         let text = String::from_utf8_lossy(&bytes);
-        lines.extend(text.lines().map(|s| s.to_string()));
+        let file_lines = text.lines().map(|s| s.to_string()).collect();
+        match until {
+            Some(until) => lines.extend(written_until(&file, file_lines, until).await),
+            None => lines.extend(file_lines),
+        }
     }
     if lines.is_empty() && !path.exists() {
         return Vec::new();
@@ -108,6 +122,29 @@ pub(crate) async fn read_lines(path: &Path, tail: Option<&str>, since: Option<i3
     lines
 }
 
+/// The lines of `file` written at or before `until` (unix timestamp). A file
+/// last modified by then is kept whole. Otherwise only the lines carrying a
+/// date of their own (`extract_date`) can be placed; the undated ones are
+/// dropped, as nothing says they are old enough.
+pub(crate) async fn written_until(file: &Path, lines: Vec<String>, until: i32) -> Vec<String> {
+    let modified = tokio::fs::metadata(file)
+        .await
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok());
+    if modified.is_some_and(|mtime| mtime.as_secs() as i64 <= i64::from(until)) {
+        return lines;
+    }
+    lines
+        .into_iter()
+        .filter(|line| {
+            extract_date(line)
+                .and_then(|date| date_timestamp(&date))
+                .is_some_and(|at| at <= i64::from(until))
+        })
+        .collect()
+}
+
 /// Stream new lines as Cloud Hypervisor appends them to the console log.
 ///
 /// The implementation polls the file on a short interval rather than using
@@ -125,7 +162,7 @@ pub(crate) async fn stream_lines(
     since: Option<i32>,
 ) -> Pin<Box<dyn Stream<Item = String> + Send>> {
     // Replay tail synchronously, then poll for growth.
-    let initial = read_lines(&path, tail.as_deref(), since).await;
+    let initial = read_lines(&path, tail.as_deref(), since, None).await;
     let initial_stream = stream::iter(initial);
 
     // State carried by the `unfold` follower:
@@ -443,7 +480,7 @@ mod tests {
         tokio::fs::write(&path, b"line-1\nline-2\nline-3\n")
             .await
             .unwrap();
-        let lines = read_lines(&path, None, None).await;
+        let lines = read_lines(&path, None, None, None).await;
         assert_eq!(lines, vec!["line-1", "line-2", "line-3"]);
         tokio::fs::remove_file(&path).await.ok();
     }
@@ -452,7 +489,7 @@ mod tests {
     async fn read_lines_respects_tail_n() {
         let path = scratch_file("tail-n");
         tokio::fs::write(&path, b"a\nb\nc\nd\ne\n").await.unwrap();
-        let lines = read_lines(&path, Some("2"), None).await;
+        let lines = read_lines(&path, Some("2"), None, None).await;
         assert_eq!(lines, vec!["d", "e"]);
         tokio::fs::remove_file(&path).await.ok();
     }
@@ -461,15 +498,38 @@ mod tests {
     async fn read_lines_tail_all_returns_everything() {
         let path = scratch_file("tail-all");
         tokio::fs::write(&path, b"a\nb\nc\n").await.unwrap();
-        let lines = read_lines(&path, Some("all"), None).await;
+        let lines = read_lines(&path, Some("all"), None, None).await;
         assert_eq!(lines, vec!["a", "b", "c"]);
         tokio::fs::remove_file(&path).await.ok();
     }
 
+    #[tokio::test]
+    async fn read_lines_until_drops_what_it_cannot_date() {
+        let path = scratch_file("until");
+        tokio::fs::write(
+            &path,
+            b"2024-01-15T10:29:59Z started\nundated\n2024/01/15 10:30:01 late\nlast\n",
+        )
+        .await
+        .unwrap();
+        let until = chrono::DateTime::parse_from_rfc3339("2024-01-15T10:30:00Z")
+            .unwrap()
+            .timestamp() as i32;
+        // Written just now, after `until`: only the dated line before it.
+        let lines = read_lines(&path, Some("2"), None, Some(until)).await;
+        assert_eq!(lines, vec!["2024-01-15T10:29:59Z started"]);
+
+        // Not written since: every line is old enough, `tail` counts them.
+        let now = chrono::Utc::now().timestamp() as i32;
+        let lines = read_lines(&path, Some("2"), None, Some(now + 60)).await;
+        assert_eq!(lines, vec!["2024/01/15 10:30:01 late", "last"]);
+        tokio::fs::remove_file(&path).await.ok();
+    }
+
     #[tokio::test]
     async fn read_lines_handles_missing_file() {
         let path = scratch_file("missing");
-        let lines = read_lines(&path, None, None).await;
+        let lines = read_lines(&path, None, None, None).await;
         assert!(lines.is_empty());
     }
 
@@ -480,7 +540,7 @@ mod tests {
         tokio::fs::write(&path, b"hello\n\xffworld\n")
             .await
             .unwrap();
-        let lines = read_lines(&path, None, None).await;
+        let lines = read_lines(&path, None, None, None).await;
         assert_eq!(lines.len(), 2);
         assert_eq!(lines[0], "hello");
         assert!(lines[1].contains("world"));
@@ -603,7 +663,7 @@ mod tests {
             .await
             .unwrap();
         // Don't create `path` itself — simulate the gap before CH writes.
-        let lines = read_lines(&path, Some("all"), None).await;
+        let lines = read_lines(&path, Some("all"), None, None).await;
         assert_eq!(lines, vec!["a", "b", "c", "d", "e"]);
         tokio::fs::remove_file(backup_path(&path, 1)).await.ok();
 
@@ -613,7 +673,7 @@ mod tests {
             .await
             .unwrap();
         tokio::fs::write(&path2, b"").await.unwrap();
-        let lines = read_lines(&path2, Some("all"), None).await;
+        let lines = read_lines(&path2, Some("all"), None, None).await;
         assert_eq!(lines, vec!["a", "b", "c", "d", "e"]);
         tokio::fs::remove_file(&path2).await.ok();
         tokio::fs::remove_file(backup_path(&path2, 1)).await.ok();
@@ -631,14 +691,14 @@ mod tests {
             .unwrap();
         tokio::fs::write(&path, b"new-a\nnew-b\n").await.unwrap();
 
-        let lines = read_lines(&path, None, None).await;
+        let lines = read_lines(&path, None, None, None).await;
         assert_eq!(
             lines,
             vec!["old-a", "old-b", "mid-a", "mid-b", "new-a", "new-b"]
         );
 
         // tail=3 must pick the last three across the boundary.
-        let tail = read_lines(&path, Some("3"), None).await;
+        let tail = read_lines(&path, Some("3"), None, None).await;
         assert_eq!(tail, vec!["mid-b", "new-a", "new-b"]);
 
         tokio::fs::remove_file(&path).await.ok();
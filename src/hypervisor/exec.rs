@@ -0,0 +1,75 @@
+//! Interactive exec: a process started inside a running instance with its
+//! stdio attached, behind `GET /deployments/{id}/exec`.
+//!
+//! Every runtime hands back the same [`ExecSession`], three channels the API
+//! bridges to the WebSocket: bytes for the process' stdin, terminal resizes,
+//! and its output ending with the exit code. How the bytes get in and out is
+//! the runtime's business — a hijacked `docker exec`, FIFOs handed to a
+//! containerd shim, or a `ring-agent` session over vsock.
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+/// Buffered messages per direction. Small on purpose: a client that stops
+/// reading pushes back on the process instead of growing the server's memory.
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone)]
+pub(crate) struct ExecRequest {
+    /// Program and arguments, run as is (no shell in between).
+    pub(crate) command: Vec<String>,
+    /// Allocate a terminal: stdout and stderr are then one stream, and the
+    /// session accepts resizes.
+    pub(crate) tty: bool,
+    /// Initial terminal size, when `tty` is set.
+    pub(crate) size: Option<TerminalSize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct TerminalSize {
+    pub(crate) cols: u16,
+    pub(crate) rows: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ExecOutput {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+    /// The process is gone; always the last message. `None` when the runtime
+    /// could not tell how it ended.
+    Exit(Option<i64>),
+}
+
+/// The API's side of a running exec. Dropping `stdin` closes the process'
+/// stdin; dropping the whole session lets the runtime tear the exec down.
+pub(crate) struct ExecSession {
+    pub(crate) stdin: mpsc::Sender<Vec<u8>>,
+    pub(crate) resize: mpsc::Sender<TerminalSize>,
+    pub(crate) output: mpsc::Receiver<ExecOutput>,
+}
+
+/// The runtime's side of the same channels.
+pub(crate) struct ExecIo {
+    pub(crate) stdin: mpsc::Receiver<Vec<u8>>,
+    pub(crate) resize: mpsc::Receiver<TerminalSize>,
+    pub(crate) output: mpsc::Sender<ExecOutput>,
+}
+
+/// A connected session and runtime pair of channel ends.
+pub(crate) fn channels() -> (ExecSession, ExecIo) {
+    let (stdin_tx, stdin_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (resize_tx, resize_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (output_tx, output_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    (
+        ExecSession {
+            stdin: stdin_tx,
+            resize: resize_tx,
+            output: output_rx,
+        },
+        ExecIo {
+            stdin: stdin_rx,
+            resize: resize_rx,
+            output: output_tx,
+        },
+    )
+}
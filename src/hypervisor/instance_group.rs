@@ -0,0 +1,172 @@
+//! Init containers and sidecars on the container runtimes (Docker, Podman,
+//! containerd).
+//!
+//! An instance with extra containers is a group: its init containers run to
+//! completion first, then the main container starts, then its sidecars join
+//! the main container's network namespace. Only the main container carries
+//! the `ring_deployment` label, so instance counting, crash detection and
+//! health checks never see the others. The members are found through the
+//! labels below instead, and are removed with their main container.
+//!
+//! The naming and labelling live here so both runtimes tag their members the
+//! same way and the log readers can tell them apart.
+
+use crate::models::deployments::{ContainerSpec, Deployment};
+use std::collections::HashMap;
+
+/// On every container of a group, main included: the main container's name.
+pub(crate) const GROUP_LABEL: &str = "ring_group";
+/// On init containers and sidecars: the deployment they belong to.
+pub(crate) const MEMBER_OF_LABEL: &str = "ring_member_of";
+/// On init containers and sidecars: their name in the manifest.
+pub(crate) const CONTAINER_LABEL: &str = "ring_container";
+/// On init containers and sidecars: `init` or `sidecar`.
+pub(crate) const ROLE_LABEL: &str = "ring_container_role";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Role {
+    Init,
+    Sidecar,
+}
+
+impl Role {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Role::Init => "init",
+            Role::Sidecar => "sidecar",
+        }
+    }
+}
+
+/// Container name of the member `container` of the group `group`, e.g.
+/// `prod_api_x1y2z3_proxy`. Filtering logs by the instance name therefore
+/// matches its members too.
+pub(crate) fn member_name(group: &str, container: &str) -> String {
+    format!("{}_{}", group, container)
+}
+
+/// Labels of a member: the user's labels plus the group bookkeeping. Never
+/// `ring_deployment`, which would count it as an instance.
+pub(crate) fn member_labels(
+    deployment: &Deployment,
+    group: &str,
+    container: &ContainerSpec,
+    role: Role,
+) -> HashMap<String, String> {
+    let mut labels = deployment.labels.clone();
+    labels.insert(GROUP_LABEL.to_string(), group.to_string());
+    labels.insert(MEMBER_OF_LABEL.to_string(), deployment.id.clone());
+    labels.insert(CONTAINER_LABEL.to_string(), container.name.clone());
+    labels.insert(ROLE_LABEL.to_string(), role.as_str().to_string());
+    labels
+}
+
+/// The deployment as seen by one of its members: its image, command and
+/// environment, the deployment's volumes and config, and none of what only
+/// makes sense for the main container (health checks, ports, resources).
+pub(crate) fn member_deployment(deployment: &Deployment, container: &ContainerSpec) -> Deployment {
+    Deployment {
+        image: container.image.clone(),
+        command: container.command.clone(),
+        environment: container.environment.clone(),
+        health_checks: vec![],
+        ports: vec![],
+        resources: None,
+        init_containers: vec![],
+        sidecars: vec![],
+        ..deployment.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::deployments::{DeploymentStatus, EnvValue};
+
+    fn api() -> Deployment {
+        Deployment {
+            id: "d-1".to_string(),
+            created_at: "2026-01-01 00:00:00 UTC".to_string(),
+            updated_at: None,
+            status: DeploymentStatus::Running,
+            restart_count: 0,
+            namespace: "prod".to_string(),
+            name: "api".to_string(),
+            image: "api:1.0".to_string(),
+            config: None,
+            runtime: "docker".to_string(),
+            kind: "worker".to_string(),
+            replicas: 1,
+            command: vec!["serve".to_string()],
+            instances: vec![],
+            labels: HashMap::from([("team".to_string(), "core".to_string())]),
+            environment: HashMap::from([("PORT".to_string(), EnvValue::Plain("80".to_string()))]),
+            volumes: "[]".to_string(),
+            health_checks: vec![],
+            resources: None,
+            autoscale: None,
+            desired_replicas: None,
+            image_digest: None,
+            ports: vec![],
+            pending_events: vec![],
+            parent_id: None,
+            network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            depends_on: vec![],
+            init_containers: vec![],
+            sidecars: vec![],
+            pending_restart: vec![],
+            paused: false,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            job: None,
+            exit_code: None,
+        }
+    }
+
+    fn proxy() -> ContainerSpec {
+        ContainerSpec {
+            name: "proxy".to_string(),
+            image: "cloud-sql-proxy:2".to_string(),
+            command: vec![],
+            environment: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn members_are_labelled_without_counting_as_instances() {
+        let labels = member_labels(&api(), "prod_api_abc", &proxy(), Role::Sidecar);
+
+        assert_eq!(labels[GROUP_LABEL], "prod_api_abc");
+        assert_eq!(labels[MEMBER_OF_LABEL], "d-1");
+        assert_eq!(labels[CONTAINER_LABEL], "proxy");
+        assert_eq!(labels[ROLE_LABEL], "sidecar");
+        assert_eq!(labels["team"], "core");
+        assert!(!labels.contains_key("ring_deployment"));
+        assert_eq!(member_name("prod_api_abc", "proxy"), "prod_api_abc_proxy");
+    }
+
+    #[test]
+    fn a_member_runs_its_own_image_with_the_deployment_volumes() {
+        let mut deployment = api();
+        deployment.volumes = r#"[{"type":"bind","source":"/data","destination":"/data"}]"#.into();
+        deployment.sidecars = vec![proxy()];
+
+        let member = member_deployment(&deployment, &proxy());
+
+        assert_eq!(member.image, "cloud-sql-proxy:2");
+        assert!(member.command.is_empty());
+        assert!(member.environment.is_empty());
+        assert!(member.sidecars.is_empty());
+        assert_eq!(member.volumes, deployment.volumes);
+        assert_eq!(member.namespace, "prod");
+    }
+}
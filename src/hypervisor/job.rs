@@ -0,0 +1,406 @@
+//! Progress of a `kind: job` on the container runtimes (Docker, Podman,
+//! containerd).
+//!
+//! A job runs attempts — one container each — until `job.completions` of them
+//! exited 0, at most `job.parallelism` at a time. A failed attempt is retried
+//! within `job.backoff_limit`, and the whole job is given up once
+//! `job.active_deadline` has elapsed since its creation.
+//!
+//! The runtimes only observe their attempts and carry out the decision; the
+//! decision itself lives here so Docker and containerd cannot drift apart. Like
+//! the [`classifier`](super::classifier), it reads nothing but the deployment
+//! and what the runtime observed.
+
+use crate::hypervisor::classifier::{Disposition, classify_exit_code};
+use crate::models::deployments::{Deployment, DeploymentStatus, MAX_RESTART_COUNT};
+use chrono::{DateTime, Utc};
+
+/// What a runtime observed of one attempt.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Attempt {
+    Running,
+    Succeeded,
+    /// Exited with anything but 0. The exit code is `None` when the runtime
+    /// could not read it.
+    Failed(Option<i64>),
+}
+
+/// What the runtime must do with its attempts this tick. The deployment
+/// itself (status, `restart_count`, `exit_code`, events) is updated by
+/// [`step`].
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct JobStep {
+    /// Failed attempts to remove: they were counted and are retried.
+    pub(crate) remove: Vec<String>,
+    /// Running attempts to stop because the job is over. Stopped, not
+    /// removed, so their logs stay readable.
+    pub(crate) stop: Vec<String>,
+    /// How many new attempts to start.
+    pub(crate) start: u32,
+}
+
+/// `restart_count` at which the job has used up its retries: one past
+/// `job.backoff_limit`, or `MAX_RESTART_COUNT` when the job sets none.
+pub(crate) fn retry_bound(deployment: &Deployment) -> u32 {
+    match deployment.job.as_ref().and_then(|job| job.backoff_limit) {
+        Some(limit) => limit.saturating_add(1),
+        None => MAX_RESTART_COUNT,
+    }
+}
+
+/// Spend the rest of the job's retries at once, for a creation error that
+/// cannot fix itself on a retry.
+pub(crate) fn exhaust_retries(deployment: &mut Deployment) {
+    deployment.restart_count = deployment.restart_count.max(retry_bound(deployment));
+}
+
+/// Decide the next move of `deployment` from its `attempts` (instance id and
+/// what was observed of it).
+///
+/// A finished job (`completed` or `failed`) is left as is. The job completes
+/// as soon as enough attempts succeeded, and fails once it is past its
+/// deadline, out of retries, or when an attempt fails without a
+/// `backoff_limit` to retry it.
+pub(crate) fn step(
+    deployment: &mut Deployment,
+    attempts: &[(String, Attempt)],
+    now: DateTime<Utc>,
+    component: &str,
+) -> JobStep {
+    let mut step = JobStep::default();
+    if matches!(
+        deployment.status,
+        DeploymentStatus::Completed | DeploymentStatus::Failed
+    ) {
+        return step;
+    }
+
+    let spec = deployment.job.clone().unwrap_or_default();
+    let running: Vec<String> = attempts
+        .iter()
+        .filter(|(_, attempt)| *attempt == Attempt::Running)
+        .map(|(id, _)| id.clone())
+        .collect();
+    let succeeded = attempts
+        .iter()
+        .filter(|(_, attempt)| *attempt == Attempt::Succeeded)
+        .count() as u32;
+    let failed: Vec<(&String, Option<i64>)> = attempts
+        .iter()
+        .filter_map(|(id, attempt)| match attempt {
+            Attempt::Failed(code) => Some((id, *code)),
+            _ => None,
+        })
+        .collect();
+
+    if let Some((_, code)) = failed.last() {
+        deployment.exit_code = *code;
+    } else if succeeded > 0 {
+        deployment.exit_code = Some(0);
+    }
+
+    if succeeded >= spec.completions {
+        deployment.status = DeploymentStatus::Completed;
+        step.stop = running;
+        return step;
+    }
+
+    if let (Some(deadline), Some(created_at)) = (spec.deadline(), deployment.created_at_utc())
+        && (now - created_at)
+            .to_std()
+            .is_ok_and(|elapsed| elapsed >= deadline)
+    {
+        deployment.emit_event(
+            "error",
+            format!(
+                "Job exceeded its active deadline of {}: {} running attempt(s) stopped",
+                spec.active_deadline.as_deref().unwrap_or_default(),
+                running.len()
+            ),
+            component,
+            Some("deadline_exceeded"),
+        );
+        deployment.status = DeploymentStatus::Failed;
+        step.stop = running;
+        return step;
+    }
+
+    for (id, code) in &failed {
+        let exit = code
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        // 126/127: the command cannot run at all, a retry would fail the same
+        // way. Without a backoff limit, any failure is final.
+        let retryable =
+            spec.backoff_limit.is_some() && classify_exit_code(*code) == Disposition::Retry;
+        if !retryable {
+            deployment.emit_event(
+                "error",
+                format!("Attempt {} failed (exit_code={})", id, exit),
+                component,
+                Some("job_failed"),
+            );
+            deployment.status = DeploymentStatus::Failed;
+            step.stop = running;
+            return step;
+        }
+        deployment.restart_count += 1;
+        deployment.emit_event(
+            "warning",
+            format!(
+                "Attempt {} failed (exit_code={}), retry {} of {}",
+                id,
+                exit,
+                deployment.restart_count,
+                retry_bound(deployment) - 1
+            ),
+            component,
+            Some("job_attempt_failed"),
+        );
+    }
+
+    if deployment.restart_count >= retry_bound(deployment) {
+        if !failed.is_empty() {
+            deployment.emit_event(
+                "error",
+                format!(
+                    "Job failed: backoff limit of {} retries reached",
+                    retry_bound(deployment) - 1
+                ),
+                component,
+                Some("backoff_limit_exceeded"),
+            );
+        }
+        // The failed attempts are kept: the last one's logs say why.
+        deployment.status = DeploymentStatus::Failed;
+        step.stop = running;
+        return step;
+    }
+
+    step.remove = failed.iter().map(|(id, _)| (*id).clone()).collect();
+    // A retry waits for the next tick, so the scheduler's retry backoff (armed
+    // by the `restart_count` bump) spaces the attempts out.
+    if failed.is_empty() {
+        let wanted = spec.parallelism.min(spec.completions - succeeded);
+        step.start = wanted.saturating_sub(running.len() as u32);
+    }
+    if !running.is_empty() {
+        deployment.status = DeploymentStatus::Running;
+    }
+    step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::deployments::JobSpec;
+    use std::collections::HashMap;
+
+    fn job(spec: Option<JobSpec>) -> Deployment {
+        Deployment {
+            id: "job".to_string(),
+            created_at: "2026-05-30 20:00:00.000 UTC".to_string(),
+            updated_at: None,
+            status: DeploymentStatus::Creating,
+            restart_count: 0,
+            namespace: "test".to_string(),
+            name: "migrate".to_string(),
+            image: "busybox".to_string(),
+            config: None,
+            runtime: "docker".to_string(),
+            kind: "job".to_string(),
+            replicas: 1,
+            command: vec![],
+            instances: vec![],
+            labels: HashMap::new(),
+            environment: HashMap::new(),
+            volumes: "[]".to_string(),
+            health_checks: vec![],
+            resources: None,
+            autoscale: None,
+            desired_replicas: None,
+            image_digest: None,
+            ports: vec![],
+            pending_events: vec![],
+            parent_id: None,
+            network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            pending_restart: vec![],
+            paused: false,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            job: spec,
+            exit_code: None,
+        }
+    }
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        "2026-05-30T20:00:00Z".parse::<DateTime<Utc>>().unwrap()
+            + chrono::Duration::minutes(minutes)
+    }
+
+    fn attempt(id: &str, attempt: Attempt) -> (String, Attempt) {
+        (id.to_string(), attempt)
+    }
+
+    #[test]
+    fn a_new_job_starts_as_many_attempts_as_it_may_run() {
+        let mut d = job(Some(JobSpec {
+            parallelism: 3,
+            completions: 2,
+            ..JobSpec::default()
+        }));
+        assert_eq!(step(&mut d, &[], at(0), "docker").start, 2);
+
+        let mut d = job(None);
+        assert_eq!(step(&mut d, &[], at(0), "docker").start, 1);
+    }
+
+    #[test]
+    fn completes_once_enough_attempts_succeeded() {
+        let mut d = job(Some(JobSpec {
+            parallelism: 2,
+            completions: 3,
+            ..JobSpec::default()
+        }));
+        let plan = step(
+            &mut d,
+            &[
+                attempt("a", Attempt::Succeeded),
+                attempt("b", Attempt::Running),
+            ],
+            at(1),
+            "docker",
+        );
+        // Two left to succeed, one already running.
+        assert_eq!(plan.start, 1);
+        assert_eq!(d.status, DeploymentStatus::Running);
+
+        let plan = step(
+            &mut d,
+            &[
+                attempt("a", Attempt::Succeeded),
+                attempt("b", Attempt::Succeeded),
+                attempt("c", Attempt::Succeeded),
+            ],
+            at(2),
+            "docker",
+        );
+        assert_eq!(d.status, DeploymentStatus::Completed);
+        assert_eq!(d.exit_code, Some(0));
+        assert_eq!(plan.start, 0);
+    }
+
+    #[test]
+    fn without_a_backoff_limit_a_failed_attempt_fails_the_job() {
+        let mut d = job(None);
+        step(
+            &mut d,
+            &[attempt("a", Attempt::Failed(Some(2)))],
+            at(1),
+            "docker",
+        );
+        assert_eq!(d.status, DeploymentStatus::Failed);
+        assert_eq!(d.exit_code, Some(2));
+        assert_eq!(d.restart_count, 0);
+    }
+
+    #[test]
+    fn failed_attempts_are_retried_within_the_backoff_limit() {
+        let mut d = job(Some(JobSpec {
+            backoff_limit: Some(2),
+            ..JobSpec::default()
+        }));
+        for retry in 1..=2 {
+            let plan = step(
+                &mut d,
+                &[attempt("a", Attempt::Failed(Some(1)))],
+                at(1),
+                "docker",
+            );
+            assert_eq!(d.restart_count, retry);
+            assert_eq!(plan.remove, vec!["a".to_string()]);
+            assert_eq!(plan.start, 0);
+            // Next tick, once the attempt is gone.
+            assert_eq!(step(&mut d, &[], at(1), "docker").start, 1);
+        }
+
+        let plan = step(
+            &mut d,
+            &[attempt("a", Attempt::Failed(Some(1)))],
+            at(1),
+            "docker",
+        );
+        assert_eq!(d.status, DeploymentStatus::Failed);
+        // The last attempt is kept for its logs.
+        assert!(plan.remove.is_empty());
+        assert_eq!(plan.start, 0);
+        assert!(
+            d.pending_events
+                .iter()
+                .any(|e| e.reason.as_deref() == Some("backoff_limit_exceeded"))
+        );
+    }
+
+    #[test]
+    fn a_command_that_cannot_run_is_not_retried() {
+        let mut d = job(Some(JobSpec {
+            backoff_limit: Some(5),
+            ..JobSpec::default()
+        }));
+        step(
+            &mut d,
+            &[attempt("a", Attempt::Failed(Some(127)))],
+            at(1),
+            "docker",
+        );
+        assert_eq!(d.status, DeploymentStatus::Failed);
+        assert_eq!(d.restart_count, 0);
+    }
+
+    #[test]
+    fn the_deadline_stops_what_still_runs() {
+        let mut d = job(Some(JobSpec {
+            active_deadline: Some("600s".to_string()),
+            ..JobSpec::default()
+        }));
+        let plan = step(&mut d, &[attempt("a", Attempt::Running)], at(5), "docker");
+        assert_eq!(d.status, DeploymentStatus::Running);
+        assert!(plan.stop.is_empty());
+
+        let plan = step(&mut d, &[attempt("a", Attempt::Running)], at(10), "docker");
+        assert_eq!(d.status, DeploymentStatus::Failed);
+        assert_eq!(plan.stop, vec!["a".to_string()]);
+        assert_eq!(
+            d.pending_events.last().unwrap().reason.as_deref(),
+            Some("deadline_exceeded")
+        );
+    }
+
+    #[test]
+    fn creation_errors_share_the_retry_budget() {
+        let mut d = job(None);
+        assert_eq!(retry_bound(&d), MAX_RESTART_COUNT);
+        d.restart_count = MAX_RESTART_COUNT;
+        step(&mut d, &[], at(1), "docker");
+        assert_eq!(d.status, DeploymentStatus::Failed);
+
+        let mut d = job(Some(JobSpec {
+            backoff_limit: Some(10),
+            ..JobSpec::default()
+        }));
+        exhaust_retries(&mut d);
+        assert_eq!(d.restart_count, 11);
+    }
+}
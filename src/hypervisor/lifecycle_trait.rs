@@ -1,14 +1,13 @@
 use crate::api::dto::stats::InstanceStatsOutput;
+use crate::hypervisor::exec::{ExecRequest, ExecSession};
 use crate::models::deployments::Deployment;
 use crate::models::health_check::{HealthCheck, HealthCheckStatus};
 use crate::models::volume::ResolvedMount;
 use async_trait::async_trait;
-use axum::response::sse::Event;
 use futures::stream;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::convert::Infallible;
 use std::net::IpAddr;
 use std::pin::Pin;
 use std::sync::LazyLock;
@@ -16,11 +15,25 @@ use std::sync::LazyLock;
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub(crate) struct Log {
     pub(crate) instance: String,
+    /// The init container or sidecar of `instance` that wrote the line,
+    /// absent for the main container.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) container: Option<String>,
     pub(crate) message: String,
     pub(crate) level: String,
     pub(crate) timestamp: Option<String>,
+    /// What a structured line carried besides its level, time and message
+    /// (see `log_format`), empty for plain text.
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty", default)]
+    pub(crate) fields: serde_json::Map<String, serde_json::Value>,
 }
 
+/// Lines of one or more instances, in the order they were written, as
+/// returned by [`RuntimeLifecycle::stream_logs`]. Ends when every followed
+/// source is gone (a Docker container that stopped); file-backed runtimes
+/// follow until dropped.
+pub(crate) type LogStream = Pin<Box<dyn futures::Stream<Item = Log> + Send>>;
+
 /// Best-effort log level classification. Recognises three families of
 /// conventions that show up in a Ring stream:
 ///
@@ -133,8 +146,13 @@ fn parse_syslog_priority(log: &str) -> Option<&'static str> {
     }
 }
 
-static DATE_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2}").unwrap());
+/// nginx's `YYYY/MM/DD HH:MM:SS`, or an RFC 3339 timestamp with its offset.
+static DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2}|\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:\d{2})",
+    )
+    .unwrap()
+});
 
 pub(crate) fn extract_date(log: &str) -> Option<String> {
     let date = DATE_REGEX.find(log).map(|d| d.as_str()).unwrap_or("");
@@ -144,6 +162,17 @@ pub(crate) fn extract_date(log: &str) -> Option<String> {
     Some(date.to_string())
 }
 
+/// Unix timestamp of a date `extract_date` found; the nginx one is read as
+/// UTC.
+pub(crate) fn date_timestamp(date: &str) -> Option<i64> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(date) {
+        return Some(dt.timestamp());
+    }
+    chrono::NaiveDateTime::parse_from_str(date, "%Y/%m/%d %H:%M:%S")
+        .ok()
+        .map(|dt| dt.and_utc().timestamp())
+}
+
 #[async_trait]
 pub(crate) trait RuntimeLifecycle: Send + Sync {
     async fn apply(
@@ -197,11 +226,14 @@ pub(crate) trait RuntimeLifecycle: Send + Sync {
 
     async fn remove_instance(&self, instance_id: String) -> bool;
 
+    /// `until` is a unix timestamp: only the lines written at or before it,
+    /// with `tail` counted among those.
     async fn get_logs(
         &self,
         _deployment_id: &str,
         _tail: Option<&str>,
         _since: Option<i32>,
+        _until: Option<i32>,
         _instance_filter: Option<&str>,
     ) -> Vec<Log> {
         Vec::new()
@@ -213,7 +245,7 @@ pub(crate) trait RuntimeLifecycle: Send + Sync {
         _tail: Option<&str>,
         _since: Option<i32>,
         _instance_filter: Option<&str>,
-    ) -> Pin<Box<dyn futures::Stream<Item = Result<Event, Infallible>> + Send>> {
+    ) -> LogStream {
         Box::pin(stream::empty())
     }
 
@@ -245,6 +277,13 @@ pub(crate) trait RuntimeLifecycle: Send + Sync {
         )
     }
 
+    /// Start `request.command` inside the instance with its stdio attached,
+    /// for `GET /deployments/{id}/exec`. The session lives until the process
+    /// exits or the caller drops it.
+    async fn exec(&self, _instance_id: &str, _request: ExecRequest) -> Result<ExecSession, String> {
+        Err("interactive exec is not supported on this runtime".to_string())
+    }
+
     /// Execute one health-check definition for one instance.
     ///
     /// The default impl orchestrates the three probe types via shared
@@ -419,6 +458,10 @@ mod tests {
             extract_date("2021/08/10 12:00:00 [info] This is an info log"),
             Some("2021/08/10 12:00:00".to_string())
         );
+        assert_eq!(
+            extract_date("2021-08-10T12:00:00.123Z INFO listening"),
+            Some("2021-08-10T12:00:00.123Z".to_string())
+        );
         assert_eq!(extract_date("[info] This is an info log"), None);
     }
 }
@@ -0,0 +1,481 @@
+//! Structured log lines and multi-line entries.
+//!
+//! Runtimes hand over each line as text, with the level `classify_log` guessed
+//! and the date `extract_date` found. A [`LogParser`], built from the
+//! deployment's `logging:` block, reads them again where the logs are served:
+//!
+//! - a JSON object (detected by default, or with `format: json`) or a logfmt
+//!   line (`format: logfmt`) gives its level, time and message to the `Log`,
+//!   and the rest of its keys to `Log::fields`;
+//! - with a `multiline_pattern`, a line matching it continues the previous
+//!   entry of the same instance and container, e.g. a Java stack trace.
+//!
+//! What cannot be read as asked stays the text line it was.
+
+use crate::hypervisor::lifecycle_trait::{Log, LogStream};
+use crate::models::deployments::{Deployment, LogFormat, Logging};
+use chrono::{DateTime, SecondsFormat};
+use futures::StreamExt;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Keys holding the level, the time and the message, first match wins.
+/// `log.level` is the Elastic Common Schema's.
+const LEVEL_KEYS: &[&str] = &["level", "lvl", "severity", "log.level"];
+const TIME_KEYS: &[&str] = &["ts", "time", "timestamp", "@timestamp"];
+const MESSAGE_KEYS: &[&str] = &["msg", "message"];
+
+/// How long a followed entry waits for more continuation lines before it is
+/// sent: the last line of a stack trace is only known by what comes next.
+const FLUSH_AFTER: Duration = Duration::from_millis(500);
+
+pub(crate) struct LogParser {
+    format: LogFormat,
+    /// Continuation lines, `None` when every line is an entry of its own.
+    multiline: Option<Regex>,
+}
+
+impl LogParser {
+    pub(crate) fn for_deployment(deployment: &Deployment) -> Self {
+        Self::new(deployment.logging.as_ref())
+    }
+
+    fn new(logging: Option<&Logging>) -> Self {
+        let Some(logging) = logging else {
+            return Self {
+                format: LogFormat::Auto,
+                multiline: None,
+            };
+        };
+        // Checked when the deployment was created; a stored pattern that no
+        // longer compiles folds nothing.
+        let multiline = logging
+            .multiline_pattern
+            .as_deref()
+            .and_then(|pattern| Regex::new(pattern).ok());
+        Self {
+            format: logging.format(),
+            multiline,
+        }
+    }
+
+    /// Read one line's structure, if it has the one the deployment declared.
+    pub(crate) fn parse(&self, mut log: Log) -> Log {
+        let fields = match self.format {
+            LogFormat::Text => None,
+            LogFormat::Auto => json_object(&log.message),
+            // Also past a prefix, e.g. the date a wrapper script writes first.
+            LogFormat::Json => log
+                .message
+                .find('{')
+                .and_then(|start| json_object(&log.message[start..])),
+            LogFormat::Logfmt => logfmt(&log.message),
+        };
+        let Some(mut fields) = fields else {
+            return log;
+        };
+
+        if let Some(level) = take(&mut fields, LEVEL_KEYS, level_of) {
+            log.level = level;
+        }
+        if let Some(timestamp) = take(&mut fields, TIME_KEYS, timestamp_of) {
+            log.timestamp = Some(timestamp);
+        }
+        if let Some(message) = take(&mut fields, MESSAGE_KEYS, |value| match value {
+            Value::String(message) => Some(message.clone()),
+            _ => None,
+        }) {
+            log.message = message;
+        }
+        log.fields = fields;
+        log
+    }
+
+    fn continues(&self, log: &Log) -> bool {
+        self.multiline
+            .as_ref()
+            .is_some_and(|re| re.is_match(&log.message))
+    }
+
+    /// Parse a batch of lines and fold the continuation lines into the entry
+    /// they follow, keeping the entries in the order they started.
+    pub(crate) fn fold(&self, logs: Vec<Log>) -> Vec<Log> {
+        let mut entries: Vec<Log> = Vec::with_capacity(logs.len());
+        // Index of the entry each instance and container is writing.
+        let mut open: HashMap<(String, Option<String>), usize> = HashMap::new();
+        for log in logs {
+            let source = (log.instance.clone(), log.container.clone());
+            if self.continues(&log)
+                && let Some(&index) = open.get(&source)
+            {
+                append(&mut entries[index], &log.message);
+                continue;
+            }
+            open.insert(source, entries.len());
+            entries.push(self.parse(log));
+        }
+        entries
+    }
+
+    /// [`Self::fold`] for a followed stream: an entry is sent once the next
+    /// one from its source starts, after [`FLUSH_AFTER`] without a
+    /// continuation, or when the stream ends.
+    pub(crate) fn stream(self, logs: LogStream) -> LogStream {
+        if self.multiline.is_none() {
+            return logs.map(move |log| self.parse(log)).boxed();
+        }
+
+        struct State {
+            parser: LogParser,
+            logs: LogStream,
+            /// At most one entry per source, with when it last grew.
+            open: Vec<(Instant, Log)>,
+            ready: VecDeque<Log>,
+            done: bool,
+        }
+
+        impl State {
+            fn push(&mut self, log: Log) {
+                let now = Instant::now();
+                let position = self.open.iter().position(|(_, entry)| {
+                    entry.instance == log.instance && entry.container == log.container
+                });
+                if self.parser.continues(&log)
+                    && let Some(index) = position
+                {
+                    let (grew, entry) = &mut self.open[index];
+                    append(entry, &log.message);
+                    *grew = now;
+                    return;
+                }
+                if let Some(index) = position {
+                    self.ready.push_back(self.open.remove(index).1);
+                }
+                self.open.push((now, self.parser.parse(log)));
+            }
+
+            /// Send the entries that stopped growing, all of them when
+            /// `everything`.
+            fn flush(&mut self, everything: bool) {
+                let now = Instant::now();
+                let (expired, open): (Vec<_>, Vec<_>) = std::mem::take(&mut self.open)
+                    .into_iter()
+                    .partition(|(grew, _)| everything || *grew + FLUSH_AFTER <= now);
+                self.open = open;
+                self.ready
+                    .extend(expired.into_iter().map(|(_, entry)| entry));
+            }
+        }
+
+        let state = State {
+            parser: self,
+            logs,
+            open: Vec::new(),
+            ready: VecDeque::new(),
+            done: false,
+        };
+        futures::stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(entry) = state.ready.pop_front() {
+                    return Some((entry, state));
+                }
+                if state.done {
+                    return None;
+                }
+                let oldest = state.open.iter().map(|(grew, _)| *grew).min();
+                let next = match oldest {
+                    Some(grew) => {
+                        match tokio::time::timeout_at(grew + FLUSH_AFTER, state.logs.next()).await {
+                            Ok(next) => next,
+                            Err(_) => {
+                                state.flush(false);
+                                continue;
+                            }
+                        }
+                    }
+                    None => state.logs.next().await,
+                };
+                match next {
+                    Some(log) => state.push(log),
+                    None => {
+                        state.done = true;
+                        state.flush(true);
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+fn append(entry: &mut Log, line: &str) {
+    entry.message.push('\n');
+    entry.message.push_str(line);
+}
+
+fn json_object(line: &str) -> Option<Map<String, Value>> {
+    let line = line.trim();
+    if !line.starts_with('{') {
+        return None;
+    }
+    match serde_json::from_str(line) {
+        Ok(Value::Object(fields)) => Some(fields),
+        _ => None,
+    }
+}
+
+/// `key=value key="quoted value" flag` pairs. `None` for a line without a
+/// single `key=value`: plain text, not logfmt.
+fn logfmt(line: &str) -> Option<Map<String, Value>> {
+    let mut fields = Map::new();
+    let mut pairs = 0;
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            key.push(c);
+        }
+        if chars.next_if_eq(&'=').is_none() {
+            fields.insert(key, Value::Bool(true));
+            continue;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some(escaped) => value.push(escaped),
+                        None => {}
+                    },
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+        if !key.is_empty() {
+            fields.insert(key, Value::String(value));
+            pairs += 1;
+        }
+    }
+    (pairs > 0).then_some(fields)
+}
+
+/// Remove the first of `keys` that `read` understands and return what it
+/// read. A key it does not understand stays among the fields.
+fn take(
+    fields: &mut Map<String, Value>,
+    keys: &[&str],
+    read: impl Fn(&Value) -> Option<String>,
+) -> Option<String> {
+    let (key, value) = keys
+        .iter()
+        .find_map(|key| fields.get(*key).and_then(&read).map(|value| (*key, value)))?;
+    fields.remove(key);
+    Some(value)
+}
+
+/// `classify_log` says `warning` and `error`; accept the short spellings too.
+pub(crate) fn normalize_level(level: &str) -> String {
+    match level.to_ascii_lowercase().as_str() {
+        "warn" => "warning".to_string(),
+        "err" => "error".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// A level in `classify_log`'s words. Numbers are pino's (10 to 60), or a
+/// syslog severity (0 to 7).
+fn level_of(value: &Value) -> Option<String> {
+    let level = match value {
+        Value::String(name) => match name.to_ascii_lowercase().as_str() {
+            "fatal" | "panic" | "critical" | "crit" | "alert" | "emerg" | "emergency" | "error"
+            | "err" => "error",
+            "warning" | "warn" => "warning",
+            "info" | "information" | "notice" => "info",
+            "debug" | "trace" => "debug",
+            _ => return None,
+        },
+        Value::Number(number) => match number.as_u64()? {
+            0..=3 => "error",
+            4 => "warning",
+            5 | 6 => "info",
+            7 => "debug",
+            10..=29 => "debug",
+            30..=39 => "info",
+            40..=49 => "warning",
+            50.. => "error",
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(level.to_string())
+}
+
+/// A string is kept as written. A number is a Unix time in seconds, or in
+/// milliseconds when too large for seconds, and becomes RFC 3339.
+fn timestamp_of(value: &Value) -> Option<String> {
+    match value {
+        Value::String(timestamp) => Some(timestamp.clone()),
+        Value::Number(number) => {
+            let at = number.as_f64()?;
+            let millis = if at > 1e12 { at } else { at * 1000.0 };
+            DateTime::from_timestamp_millis(millis as i64)
+                .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, true))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(instance: &str, message: &str) -> Log {
+        Log {
+            instance: instance.to_string(),
+            container: None,
+            message: message.to_string(),
+            level: crate::hypervisor::lifecycle_trait::classify_log(message),
+            timestamp: None,
+            fields: Default::default(),
+        }
+    }
+
+    fn parser(format: Option<&str>, multiline_pattern: Option<&str>) -> LogParser {
+        LogParser::new(Some(&Logging {
+            format: format.map(str::to_string),
+            multiline_pattern: multiline_pattern.map(str::to_string),
+        }))
+    }
+
+    #[test]
+    fn json_lines_are_detected() {
+        let parser = LogParser::new(None);
+        let log = parser.parse(line(
+            "api-1",
+            r#"{"level":"warn","ts":1705314600.5,"msg":"slow query","table":"users","ms":812}"#,
+        ));
+        assert_eq!(log.level, "warning");
+        assert_eq!(log.timestamp.as_deref(), Some("2024-01-15T10:30:00.500Z"));
+        assert_eq!(log.message, "slow query");
+        assert_eq!(log.fields["table"], "users");
+        assert_eq!(log.fields["ms"], 812);
+        assert_eq!(log.fields.len(), 2);
+
+        // pino's numeric levels, and a level it cannot read stays a field.
+        let log = parser.parse(line("api-1", r#"{"level":50,"time":1705314600123}"#));
+        assert_eq!(log.level, "error");
+        assert_eq!(log.timestamp.as_deref(), Some("2024-01-15T10:30:00.123Z"));
+        let log = parser.parse(line("api-1", r#"{"level":"loud","msg":"hi"}"#));
+        assert_eq!(log.level, "unknown");
+        assert_eq!(log.fields["level"], "loud");
+
+        // Text, and JSON that is not an object, are left alone.
+        for text in ["[error] upstream timed out", "[1, 2]", "{not json"] {
+            let log = parser.parse(line("api-1", text));
+            assert_eq!(log.message, text);
+            assert!(log.fields.is_empty());
+        }
+    }
+
+    #[test]
+    fn the_declared_format_wins() {
+        let json = r#"2024-01-15 {"severity":"ERROR","message":"boom"}"#;
+        assert_eq!(
+            parser(Some("json"), None).parse(line("a", json)).message,
+            "boom"
+        );
+        assert_eq!(parser(None, None).parse(line("a", json)).message, json);
+
+        let object = r#"{"msg":"as is"}"#;
+        assert_eq!(
+            parser(Some("text"), None).parse(line("a", object)).message,
+            object
+        );
+
+        let log = parser(Some("logfmt"), None).parse(line(
+            "a",
+            r#"ts=2024-01-15T10:30:00Z level=debug msg="cache \"miss\"" key=user:42 cold"#,
+        ));
+        assert_eq!(log.level, "debug");
+        assert_eq!(log.timestamp.as_deref(), Some("2024-01-15T10:30:00Z"));
+        assert_eq!(log.message, r#"cache "miss""#);
+        assert_eq!(log.fields["key"], "user:42");
+        assert_eq!(log.fields["cold"], true);
+        let text = "no pairs in here";
+        assert_eq!(
+            parser(Some("logfmt"), None).parse(line("a", text)).message,
+            text
+        );
+    }
+
+    #[test]
+    fn continuation_lines_fold_into_their_entry() {
+        let parser = parser(None, Some(r"^\s+at |^Caused by:"));
+        let logs = parser.fold(vec![
+            line("api-1", "java.lang.IllegalStateException: closed"),
+            line("api-2", "GET /health 200"),
+            line("api-1", "\tat com.example.Pool.get(Pool.java:42)"),
+            line("api-1", "Caused by: java.io.IOException: reset"),
+            line("api-1", "GET /users 500"),
+            line("api-3", "    at orphan (no entry to continue)"),
+        ]);
+        let messages: Vec<&str> = logs.iter().map(|log| log.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "java.lang.IllegalStateException: closed\n\tat com.example.Pool.get(Pool.java:42)\nCaused by: java.io.IOException: reset",
+                "GET /health 200",
+                "GET /users 500",
+                "    at orphan (no entry to continue)",
+            ]
+        );
+    }
+
+    #[test]
+    fn short_level_names_are_accepted() {
+        assert_eq!(normalize_level("WARN"), "warning");
+        assert_eq!(normalize_level("err"), "error");
+        assert_eq!(normalize_level("debug"), "debug");
+    }
+
+    #[tokio::test]
+    async fn a_followed_entry_is_sent_once_complete() {
+        let parser = parser(None, Some(r"^\s+at "));
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let mut entries = parser.stream(rx.boxed());
+
+        tx.unbounded_send(line("api-1", "Exception: boom")).unwrap();
+        tx.unbounded_send(line("api-2", "started")).unwrap();
+        tx.unbounded_send(line("api-1", "  at main")).unwrap();
+        tx.unbounded_send(line("api-1", "retrying")).unwrap();
+        // The next entry of api-1 closes the previous one.
+        let first = entries.next().await.unwrap();
+        assert_eq!(first.message, "Exception: boom\n  at main");
+
+        // Nothing more for a while: the open entries go out as they are.
+        tx.unbounded_send(line("api-2", "  at nothing")).unwrap();
+        let mut rest = vec![
+            entries.next().await.unwrap().message,
+            entries.next().await.unwrap().message,
+        ];
+        rest.sort();
+        assert_eq!(rest, vec!["retrying", "started\n  at nothing"]);
+
+        drop(tx);
+        assert!(entries.next().await.is_none());
+    }
+}
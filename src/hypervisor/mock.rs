@@ -1,16 +1,19 @@
 use crate::api::dto::stats::InstanceStatsOutput;
-use crate::hypervisor::lifecycle_trait::{Log, RuntimeLifecycle};
+use crate::hypervisor::lifecycle_trait::{Log, LogStream, RuntimeLifecycle};
 use crate::models::deployments::Deployment;
 use crate::models::health_check::{HealthCheck, HealthCheckStatus};
 use crate::models::volume::ResolvedMount;
 use async_trait::async_trait;
-use axum::response::sse::Event;
-use std::convert::Infallible;
-use std::pin::Pin;
+use std::collections::HashMap;
+use std::sync::Mutex;
 
 pub(crate) struct MockRuntime {
     health_check_result: (HealthCheckStatus, Option<String>),
     instance_stats: Vec<InstanceStatsOutput>,
+    instances: Mutex<Vec<String>>,
+    owned: Mutex<HashMap<String, Vec<String>>>,
+    removed: Mutex<Vec<String>>,
+    address: Option<std::net::IpAddr>,
 }
 
 impl MockRuntime {
@@ -18,6 +21,10 @@ impl MockRuntime {
         Self {
             health_check_result: (HealthCheckStatus::Success, None),
             instance_stats: Vec::new(),
+            instances: Mutex::new(Vec::new()),
+            owned: Mutex::new(HashMap::new()),
+            removed: Mutex::new(Vec::new()),
+            address: None,
         }
     }
 
@@ -25,6 +32,10 @@ impl MockRuntime {
         Self {
             health_check_result: (HealthCheckStatus::Failed, Some(message.to_string())),
             instance_stats: Vec::new(),
+            instances: Mutex::new(Vec::new()),
+            owned: Mutex::new(HashMap::new()),
+            removed: Mutex::new(Vec::new()),
+            address: None,
         }
     }
 
@@ -34,6 +45,39 @@ impl MockRuntime {
         self.instance_stats = stats;
         self
     }
+
+    /// Seed the instances `list_instances` reports. `remove_instance` drops
+    /// them from this list and records them, see [`MockRuntime::removed`].
+    pub(crate) fn with_instances(self, instances: &[&str]) -> Self {
+        *self.instances.lock().unwrap() = instances.iter().map(|i| i.to_string()).collect();
+        self
+    }
+
+    /// Seed the instances of one deployment. `list_instances` answers with
+    /// these for that id, and with the shared list for any other.
+    pub(crate) fn with_deployment_instances(self, deployment_id: &str, instances: &[&str]) -> Self {
+        self.owned.lock().unwrap().insert(
+            deployment_id.to_string(),
+            instances.iter().map(|i| i.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Answer `instance_address` with `address` for every instance.
+    pub(crate) fn with_address(mut self, address: std::net::IpAddr) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Stand in for the runtime's reconcile: bring up a new instance.
+    pub(crate) fn add_instance(&self, instance_id: &str) {
+        self.instances.lock().unwrap().push(instance_id.to_string());
+    }
+
+    /// Instances removed through `remove_instance`, in call order.
+    pub(crate) fn removed(&self) -> Vec<String> {
+        self.removed.lock().unwrap().clone()
+    }
 }
 
 #[async_trait]
@@ -46,14 +90,26 @@ impl RuntimeLifecycle for MockRuntime {
         deployment
     }
 
-    async fn list_instances(&self, _deployment_id: String, _status: &str) -> Vec<String> {
-        Vec::new()
+    async fn list_instances(&self, deployment_id: String, _status: &str) -> Vec<String> {
+        if let Some(owned) = self.owned.lock().unwrap().get(&deployment_id) {
+            return owned.clone();
+        }
+        self.instances.lock().unwrap().clone()
     }
 
-    async fn remove_instance(&self, _instance_id: String) -> bool {
+    async fn remove_instance(&self, instance_id: String) -> bool {
+        self.instances.lock().unwrap().retain(|i| *i != instance_id);
+        for owned in self.owned.lock().unwrap().values_mut() {
+            owned.retain(|i| *i != instance_id);
+        }
+        self.removed.lock().unwrap().push(instance_id);
         true
     }
 
+    async fn instance_address(&self, _instance_id: &str) -> Option<std::net::IpAddr> {
+        self.address
+    }
+
     async fn execute_health_check(
         &self,
         _instance_id: &str,
@@ -67,6 +123,7 @@ impl RuntimeLifecycle for MockRuntime {
         _deployment_id: &str,
         _tail: Option<&str>,
         _since: Option<i32>,
+        _until: Option<i32>,
         _container: Option<&str>,
     ) -> Vec<Log> {
         Vec::new()
@@ -78,7 +135,7 @@ impl RuntimeLifecycle for MockRuntime {
         _tail: Option<&str>,
         _since: Option<i32>,
         _container: Option<&str>,
-    ) -> Pin<Box<dyn futures::Stream<Item = Result<Event, Infallible>> + Send>> {
+    ) -> LogStream {
         Box::pin(futures::stream::empty())
     }
 
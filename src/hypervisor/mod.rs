@@ -7,16 +7,21 @@ pub(crate) mod classifier;
 pub(crate) mod cloud_init;
 pub(crate) mod console_logs;
 pub(crate) mod error;
+pub(crate) mod exec;
 pub(crate) mod health_probes;
 pub(crate) mod host_nat;
 pub(crate) mod host_net;
+pub(crate) mod instance_group;
+pub(crate) mod job;
 pub(crate) mod lifecycle_trait;
+pub(crate) mod log_format;
 #[cfg(test)]
 pub(crate) mod mock;
 pub(crate) mod port_forwarder;
 pub(crate) mod resources;
 pub(crate) mod stats;
 pub(crate) mod tap;
+pub(crate) mod termination;
 pub(crate) mod types;
 pub(crate) mod virtiofs;
 pub(crate) mod volume_image;
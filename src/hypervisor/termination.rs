@@ -0,0 +1,114 @@
+//! Graceful termination and post-start hooks on the container runtimes
+//! (Docker, Podman, containerd): the manifest's `lifecycle:` block.
+//!
+//! The block travels with each instance as a label, so every path that stops
+//! one (scale-down, rolling update, restart, deletion, a job's deadline, or a
+//! scheduler call that only knows the instance id) honours it without looking
+//! the deployment up. Stopping an instance that still runs goes:
+//!
+//! 1. `pre_stop`, bounded by the grace period;
+//! 2. the stop signal;
+//! 3. SIGKILL once what is left of the grace period is spent.
+//!
+//! The microVM runtimes only take the grace period ([`acpi_grace_period`]):
+//! a guest is asked to power off through ACPI and is torn down once it did,
+//! or once the grace period is spent.
+
+use crate::hypervisor::health_probes;
+use crate::models::deployments::{Deployment, HttpHook, Lifecycle};
+use crate::models::health_check::HealthCheckStatus;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// On a main container whose deployment has a `lifecycle:` block: the block,
+/// as JSON.
+pub(crate) const LIFECYCLE_LABEL: &str = "ring_lifecycle";
+
+/// Upper bound of a `post_start` hook. The instance is not counted as started
+/// before its hook returns, so a hook that hangs must not hold it forever.
+pub(crate) const POST_START_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Add the lifecycle label of `deployment` to `labels`, if it has a block.
+pub(crate) fn insert_label(deployment: &Deployment, labels: &mut HashMap<String, String>) {
+    if let Some(lifecycle) = &deployment.lifecycle
+        && let Ok(json) = serde_json::to_string(lifecycle)
+    {
+        labels.insert(LIFECYCLE_LABEL.to_string(), json);
+    }
+}
+
+/// The lifecycle an instance was created with, the defaults when it carries
+/// none (created before the block existed, or without one).
+pub(crate) fn from_labels(labels: &HashMap<String, String>) -> Lifecycle {
+    labels
+        .get(LIFECYCLE_LABEL)
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default()
+}
+
+/// How long a microVM of `deployment` gets to power off after an ACPI
+/// shutdown request. `None` without a `stop_grace_period`: the VM runtimes
+/// then keep tearing guests down right away.
+pub(crate) fn acpi_grace_period(deployment: &Deployment) -> Option<Duration> {
+    deployment
+        .lifecycle
+        .as_ref()
+        .filter(|l| l.stop_grace_period.is_some())
+        .map(|l| l.grace_period())
+}
+
+/// Run an HTTP `pre_stop` hook: a GET that must answer 2xx within `timeout`.
+pub(crate) async fn http_hook(
+    address: Option<IpAddr>,
+    hook: &HttpHook,
+    timeout: Duration,
+) -> Result<(), String> {
+    let Some(ip) = address else {
+        return Err("could not resolve the instance address".to_string());
+    };
+    let url = format!("http://localhost:{}{}", hook.port, hook.path);
+    match health_probes::http_probe(ip, &url, timeout).await {
+        (HealthCheckStatus::Success, _) => Ok(()),
+        (_, message) => Err(message.unwrap_or_default()),
+    }
+}
+
+/// Turn the outcome of a command hook run through a runtime's exec into a
+/// result.
+pub(crate) fn command_outcome(outcome: (HealthCheckStatus, Option<String>)) -> Result<(), String> {
+    match outcome {
+        (HealthCheckStatus::Success, _) => Ok(()),
+        (_, message) => Err(message.unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::deployments::LifecycleHook;
+
+    #[test]
+    fn the_label_round_trips_and_defaults_when_missing() {
+        let lifecycle = Lifecycle {
+            stop_signal: Some("SIGQUIT".to_string()),
+            stop_grace_period: Some("60s".to_string()),
+            pre_stop: Some(LifecycleHook::Command(vec!["./drain".to_string()])),
+            post_start: None,
+        };
+        let labels = HashMap::from([(
+            LIFECYCLE_LABEL.to_string(),
+            serde_json::to_string(&lifecycle).unwrap(),
+        )]);
+
+        let read = from_labels(&labels);
+        assert_eq!(read, lifecycle);
+        assert_eq!(read.signal_number(), 3);
+        assert_eq!(read.grace_period(), Duration::from_secs(60));
+
+        let defaults = from_labels(&HashMap::new());
+        assert_eq!(defaults.signal_number(), 15);
+        assert_eq!(defaults.grace_period(), Duration::from_secs(10));
+        assert!(defaults.pre_stop.is_none());
+    }
+}
@@ -6,11 +6,16 @@
 //!
 //! One TCP-style connection per request. The agent does not multiplex.
 //!
+//! A `Session` request (interactive exec) keeps the connection open and
+//! switches it to `[u8 kind][u32 BE length][payload]` frames both ways; see
+//! [`open_session`].
+//!
 //! Two transports reach the same agent protocol:
 //!   - Cloud Hypervisor: kernel AF_VSOCK on the host (`exec`).
 //!   - Firecracker: vsock multiplexed over a host Unix socket (`exec_uds`),
 //!     which first performs Firecracker's `CONNECT <port>` handshake.
 
+use crate::hypervisor::exec::{self, ExecOutput, ExecRequest, ExecSession};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -23,6 +28,17 @@ const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
 const READ_TIMEOUT: Duration = Duration::from_secs(60);
 const MAX_RESPONSE_BYTES: u32 = 1 << 20;
 
+// Session frame kinds. Must match `crates/ring-agent`.
+const FRAME_STDIN: u8 = 0;
+const FRAME_STDOUT: u8 = 1;
+const FRAME_STDERR: u8 = 2;
+const FRAME_EXIT: u8 = 3;
+const FRAME_RESIZE: u8 = 4;
+const FRAME_ERROR: u8 = 5;
+
+/// What an interactive session starts with: the agent clears the environment.
+const SESSION_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum Request<'a> {
@@ -31,6 +47,13 @@ enum Request<'a> {
         env: &'a [(String, String)],
         timeout_ms: Option<u64>,
     },
+    Session {
+        argv: &'a [String],
+        env: &'a [(String, String)],
+        tty: bool,
+        cols: u16,
+        rows: u16,
+    },
 }
 
 #[derive(Deserialize)]
@@ -85,17 +108,38 @@ pub(crate) async fn exec(
         READ_TIMEOUT
     );
 
+    let stream = connect(cid).await?;
+    exchange(stream, argv, env, timeout).await
+}
+
+/// Open an interactive session with the agent in the guest `cid` (Cloud
+/// Hypervisor). Lives until the process exits or the session is dropped.
+pub(crate) async fn session(cid: u32, request: &ExecRequest) -> Result<ExecSession, VsockError> {
+    let stream = connect(cid).await?;
+    open_session(stream, request).await
+}
+
+/// Firecracker variant of [`session`], through the multiplexing Unix socket
+/// as [`exec_uds`] does.
+pub(crate) async fn session_uds(
+    cid: u32,
+    uds_path: &str,
+    request: &ExecRequest,
+) -> Result<ExecSession, VsockError> {
+    let stream = connect_uds(cid, uds_path).await?;
+    open_session(stream, request).await
+}
+
+async fn connect(cid: u32) -> Result<VsockStream, VsockError> {
     let addr = VsockAddr::new(cid, VSOCK_PORT);
 
-    let stream = tokio::time::timeout(CONNECT_TIMEOUT, VsockStream::connect(addr))
+    tokio::time::timeout(CONNECT_TIMEOUT, VsockStream::connect(addr))
         .await
         .map_err(|_| VsockError::Connect {
             cid,
             source: std::io::Error::new(std::io::ErrorKind::TimedOut, "connect timed out"),
         })?
-        .map_err(|e| VsockError::Connect { cid, source: e })?;
-
-    exchange(stream, argv, env, timeout).await
+        .map_err(|e| VsockError::Connect { cid, source: e })
 }
 
 /// Firecracker variant: connect to the host-side multiplexing Unix socket
@@ -120,6 +164,12 @@ pub(crate) async fn exec_uds(
         READ_TIMEOUT
     );
 
+    let stream = connect_uds(cid, uds_path).await?;
+    exchange(stream, argv, env, timeout).await
+}
+
+/// Connect to the agent through Firecracker's multiplexing Unix socket.
+async fn connect_uds(cid: u32, uds_path: &str) -> Result<UnixStream, VsockError> {
     // Host-to-guest goes through the device's BASE multiplexing socket, then the
     // `CONNECT <port>` handshake below selects the guest listener. The
     // `<uds_path>_<port>` form is the *guest-to-host* socket (created only when
@@ -148,7 +198,7 @@ pub(crate) async fn exec_uds(
         })??;
     read_ok_line(&mut stream, cid).await?;
 
-    exchange(stream, argv, env, timeout).await
+    Ok(stream)
 }
 
 /// Read Firecracker's `OK <port>\n` acknowledgement line, byte by byte (the
@@ -246,17 +296,141 @@ async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
     }
 }
 
-/// Explain a vsock connect failure for a `command` health check.
+/// Send a `Session` request over an already connected stream, then bridge
+/// its frames to a session's channels. Transport-agnostic, like [`exchange`].
+/// No read timeout: an interactive shell may sit idle for as long as its user
+/// does. Dropping the session closes the connection, which kills the process.
+async fn open_session<S>(mut stream: S, request: &ExecRequest) -> Result<ExecSession, VsockError>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let mut env = vec![("PATH".to_string(), SESSION_PATH.to_string())];
+    if request.tty {
+        env.push(("TERM".to_string(), "xterm".to_string()));
+    }
+    let header = Request::Session {
+        argv: &request.command,
+        env: &env,
+        tty: request.tty,
+        cols: request.size.map(|size| size.cols).unwrap_or(0),
+        rows: request.size.map(|size| size.rows).unwrap_or(0),
+    };
+    let body = serde_json::to_vec(&header)?;
+    let len = (body.len() as u32).to_be_bytes();
+    tokio::time::timeout(WRITE_TIMEOUT, async {
+        stream.write_all(&len).await?;
+        stream.write_all(&body).await?;
+        stream.flush().await
+    })
+    .await
+    .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "vsock write timed out"))??;
+
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let (session, io) = exec::channels();
+    let exec::ExecIo {
+        mut stdin,
+        mut resize,
+        output,
+    } = io;
+
+    tokio::spawn(async move {
+        let mut stdin_open = true;
+        loop {
+            let frame = tokio::select! {
+                bytes = stdin.recv(), if stdin_open => match bytes {
+                    // An empty STDIN frame means end of input to the agent.
+                    Some(bytes) if bytes.is_empty() => None,
+                    Some(bytes) => Some((FRAME_STDIN, bytes)),
+                    None => {
+                        stdin_open = false;
+                        Some((FRAME_STDIN, Vec::new()))
+                    }
+                },
+                size = resize.recv() => match size {
+                    Some(size) => Some((
+                        FRAME_RESIZE,
+                        [size.cols.to_be_bytes(), size.rows.to_be_bytes()].concat(),
+                    )),
+                    None => break,
+                },
+            };
+            let Some((kind, payload)) = frame else {
+                continue;
+            };
+            if write_frame(&mut writer, kind, &payload).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            let frame = tokio::select! {
+                frame = read_frame(&mut reader) => frame,
+                _ = output.closed() => return,
+            };
+            let message = match frame {
+                Some((FRAME_STDOUT, payload)) => ExecOutput::Stdout(payload),
+                Some((FRAME_STDERR, payload)) => ExecOutput::Stderr(payload),
+                Some((FRAME_EXIT, payload)) if payload.len() == 4 => {
+                    let code = i32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]);
+                    let _ = output.send(ExecOutput::Exit(Some(i64::from(code)))).await;
+                    return;
+                }
+                Some((FRAME_ERROR, mut payload)) => {
+                    payload.push(b'\n');
+                    let _ = output.send(ExecOutput::Stderr(payload)).await;
+                    let _ = output.send(ExecOutput::Exit(None)).await;
+                    return;
+                }
+                Some(_) => continue,
+                None => {
+                    let _ = output.send(ExecOutput::Exit(None)).await;
+                    return;
+                }
+            };
+            if output.send(message).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(session)
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    kind: u8,
+    payload: &[u8],
+) -> std::io::Result<()> {
+    writer.write_u8(kind).await?;
+    writer.write_u32(payload.len() as u32).await?;
+    writer.write_all(payload).await?;
+    writer.flush().await
+}
+
+/// One agent-to-host frame; `None` once the connection is gone or broken.
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Option<(u8, Vec<u8>)> {
+    let kind = reader.read_u8().await.ok()?;
+    let len = reader.read_u32().await.ok()?;
+    if len > MAX_RESPONSE_BYTES {
+        return None;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await.ok()?;
+    Some((kind, payload))
+}
+
+/// Explain a vsock connect failure for a `command` health check or an exec.
 ///
 /// A bare io error leaves the operator with nothing to act on, and there are two
 /// quite different causes:
 ///
 /// * the guest side is at fault — `ring-agent` isn't installed, isn't running,
 ///   or isn't listening on [`VSOCK_PORT`] yet;
-/// * the VM has no vsock device at all. It is attached at boot, and only when
-///   the deployment already declares a `command` check, so one added to a
-///   running deployment cannot work until that VM restarts — neither hypervisor
-///   can hot-plug it.
+/// * the VM has no vsock device at all. It is attached at boot to every VM,
+///   but one booted by an older Ring, which only attached it for `command`
+///   checks, has none until it restarts — neither hypervisor can hot-plug it.
 ///
 /// `host_socket_present` reports whether the host-side vsock socket is on disk.
 /// It is a *hint*, not proof: a crashed VMM can leave the socket behind until
@@ -290,9 +464,9 @@ pub(crate) fn connect_failure_message(
 
     format!(
         "cannot reach ring-agent in the guest (CID {cid}): {source}. {first}, {second}. \
-         The device is attached at boot only when the deployment already declares a \
-         `command` check, so one added to a running deployment takes effect on the next \
-         VM restart — neither {runtime} nor Ring can hot-plug it"
+         The device is attached at boot, so a VM booted by an older Ring, which only \
+         attached it for `command` checks, gets it on the next VM restart — neither \
+         {runtime} nor Ring can hot-plug it"
     )
 }
 
@@ -364,11 +538,63 @@ mod tests {
     fn the_boot_time_constraint_is_always_explained() {
         for present in [true, false] {
             let msg = connect_failure_message("cloud-hypervisor", 7, "no such file", present);
-            assert!(msg.contains("attached at boot only"), "{msg}");
+            assert!(msg.contains("attached at boot"), "{msg}");
             assert!(msg.contains("next VM restart"), "gives the remedy: {msg}");
         }
     }
 
+    #[tokio::test]
+    async fn a_session_bridges_frames_to_the_channels() {
+        use crate::hypervisor::exec::TerminalSize;
+
+        let (host, mut agent) = tokio::io::duplex(4096);
+        let request = ExecRequest {
+            command: vec!["sh".to_string()],
+            tty: true,
+            size: Some(TerminalSize {
+                cols: 120,
+                rows: 40,
+            }),
+        };
+        let mut session = open_session(host, &request).await.unwrap();
+
+        let len = agent.read_u32().await.unwrap();
+        let mut body = vec![0u8; len as usize];
+        agent.read_exact(&mut body).await.unwrap();
+        let header: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(header["type"], "session");
+        assert_eq!(header["argv"], serde_json::json!(["sh"]));
+        assert_eq!(header["tty"], true);
+        assert_eq!(header["cols"], 120);
+
+        session.stdin.send(b"ls\n".to_vec()).await.unwrap();
+        assert_eq!(
+            read_frame(&mut agent).await,
+            Some((FRAME_STDIN, b"ls\n".to_vec()))
+        );
+        session
+            .resize
+            .send(TerminalSize { cols: 80, rows: 24 })
+            .await
+            .unwrap();
+        assert_eq!(
+            read_frame(&mut agent).await,
+            Some((FRAME_RESIZE, vec![0, 80, 0, 24]))
+        );
+
+        write_frame(&mut agent, FRAME_STDOUT, b"a.txt\n")
+            .await
+            .unwrap();
+        write_frame(&mut agent, FRAME_EXIT, &3i32.to_be_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            session.output.recv().await,
+            Some(ExecOutput::Stdout(b"a.txt\n".to_vec()))
+        );
+        assert_eq!(session.output.recv().await, Some(ExecOutput::Exit(Some(3))));
+    }
+
     /// The runtime name is interpolated so the message points at the right docs.
     #[test]
     fn message_names_the_runtime() {
@@ -188,6 +188,12 @@ fn build_cli() -> Command {
                 .subcommand(commands::server::command_config()),
         )
         .subcommand(commands::apply::command_config())
+        .subcommand(
+            Command::new("convert")
+                .about("Translate other formats into a Ring manifest")
+                .subcommand_required(true)
+                .subcommand(commands::convert::compose::command_config()),
+        )
         .subcommand(commands::dashboard::command_config())
         .subcommand(commands::doctor::command_config())
         .subcommand(commands::login::command_config())
@@ -201,9 +207,19 @@ fn build_cli() -> Command {
                 .subcommand(commands::deployment::inspect::command_config())
                 .subcommand(commands::deployment::delete::command_config())
                 .subcommand(commands::deployment::logs::command_config())
+                .subcommand(commands::deployment::exec::command_config())
                 .subcommand(commands::deployment::events::command_config())
                 .subcommand(commands::deployment::metrics::command_config())
-                .subcommand(commands::deployment::health_checks::command_config()),
+                .subcommand(commands::deployment::health_checks::command_config())
+                .subcommand(commands::deployment::history::command_config())
+                .subcommand(commands::deployment::rollback::command_config())
+                .subcommand(commands::deployment::scale::command_config())
+                .subcommand(commands::deployment::restart::command_config())
+                .subcommand(commands::deployment::pause::command_config())
+                .subcommand(commands::deployment::resume::command_config())
+                .subcommand(commands::deployment::promote::command_config())
+                .subcommand(commands::deployment::abort::command_config())
+                .subcommand(commands::deployment::runs::command_config()),
         )
         .subcommand(
             Command::new("namespace")
@@ -213,6 +229,9 @@ fn build_cli() -> Command {
                 .subcommand(commands::namespace::list::command_config())
                 .subcommand(commands::namespace::prune::command_config())
                 .subcommand(commands::namespace::audit::command_config())
+                .subcommand(commands::namespace::quota::command_config())
+                .subcommand(commands::namespace::usage::command_config())
+                .subcommand(commands::namespace::export::command_config())
                 .subcommand(commands::namespace::delete::command_config()),
         )
         .subcommand(
@@ -327,6 +346,11 @@ async fn main() {
         Some(("apply", sub_matches)) => {
             commands::apply::apply(sub_matches, config, &client).await;
         }
+        Some(("convert", sub_matches)) => {
+            if let Some(("compose", sub_matches)) = sub_matches.subcommand() {
+                commands::convert::compose::execute(sub_matches);
+            }
+        }
         Some(("dashboard", sub_matches)) => {
             commands::dashboard::execute(sub_matches, config, context.to_string()).await;
         }
@@ -346,6 +370,9 @@ async fn main() {
                     commands::deployment::delete::execute(sub_matches, config, &client).await;
                 }
 
+                ("exec", sub_matches) => {
+                    commands::deployment::exec::execute(sub_matches, config, &client).await;
+                }
                 ("logs", sub_matches) => {
                     commands::deployment::logs::execute(sub_matches, config, &client).await;
                 }
@@ -359,6 +386,33 @@ async fn main() {
                     commands::deployment::health_checks::execute(sub_matches, config, &client)
                         .await;
                 }
+                ("history", sub_matches) => {
+                    commands::deployment::history::execute(sub_matches, config, &client).await;
+                }
+                ("rollback", sub_matches) => {
+                    commands::deployment::rollback::execute(sub_matches, config, &client).await;
+                }
+                ("scale", sub_matches) => {
+                    commands::deployment::scale::execute(sub_matches, config, &client).await;
+                }
+                ("restart", sub_matches) => {
+                    commands::deployment::restart::execute(sub_matches, config, &client).await;
+                }
+                ("pause", sub_matches) => {
+                    commands::deployment::pause::execute(sub_matches, config, &client).await;
+                }
+                ("resume", sub_matches) => {
+                    commands::deployment::resume::execute(sub_matches, config, &client).await;
+                }
+                ("promote", sub_matches) => {
+                    commands::deployment::promote::execute(sub_matches, config, &client).await;
+                }
+                ("abort", sub_matches) => {
+                    commands::deployment::abort::execute(sub_matches, config, &client).await;
+                }
+                ("runs", sub_matches) => {
+                    commands::deployment::runs::execute(sub_matches, config, &client).await;
+                }
                 _ => {}
             }
         }
@@ -377,6 +431,15 @@ async fn main() {
                 ("audit", sub_matches) => {
                     commands::namespace::audit::execute(sub_matches, config, &client).await;
                 }
+                ("quota", sub_matches) => {
+                    commands::namespace::quota::execute(sub_matches, config, &client).await;
+                }
+                ("usage", sub_matches) => {
+                    commands::namespace::usage::execute(sub_matches, config, &client).await;
+                }
+                ("export", sub_matches) => {
+                    commands::namespace::export::execute(sub_matches, config, &client).await;
+                }
                 ("delete", sub_matches) => {
                     commands::namespace::delete::execute(sub_matches, config, &client).await;
                 }
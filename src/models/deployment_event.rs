@@ -115,6 +115,40 @@ pub(crate) async fn find_events_by_deployment_and_level(
     .await
 }
 
+/// The most recent event of `deployment_id` carrying `reason`, if any. Lets a
+/// multi-tick process (a rolling restart) pace itself on its own last step
+/// without holding state in the scheduler.
+pub(crate) async fn find_latest_by_reason(
+    pool: &SqlitePool,
+    deployment_id: &str,
+    reason: &str,
+) -> Result<Option<DeploymentEvent>, sqlx::Error> {
+    sqlx::query_as::<_, DeploymentEvent>(
+        "SELECT id, deployment_id, timestamp, level, message, component, reason
+         FROM deployment_event WHERE deployment_id = ? AND reason = ? ORDER BY timestamp DESC LIMIT 1",
+    )
+    .bind(deployment_id)
+    .bind(reason)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Events of `deployment_id` recorded after `since` (RFC 3339), oldest first.
+pub(crate) async fn find_events_since(
+    pool: &SqlitePool,
+    deployment_id: &str,
+    since: &str,
+) -> Result<Vec<DeploymentEvent>, sqlx::Error> {
+    sqlx::query_as::<_, DeploymentEvent>(
+        "SELECT id, deployment_id, timestamp, level, message, component, reason
+         FROM deployment_event WHERE deployment_id = ? AND timestamp > ? ORDER BY timestamp ASC",
+    )
+    .bind(deployment_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
 /// Record a deployment event. Call at the point the thing actually happened.
 ///
 /// Callers discard the result on purpose: failing to record an event must never
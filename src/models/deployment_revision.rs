@@ -0,0 +1,220 @@
+//! Numbered history of the specs accepted for a deployment.
+//!
+//! A redeploy replaces the deployment row, and the scheduler's cleanup then
+//! removes the old one — taking its spec with it. Each spec accepted by
+//! `POST /deployments` is therefore also stored here, keyed by namespace and
+//! name so it outlives the row it produced. A rollback replays one of these
+//! specs through the regular create path.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Revisions kept per deployment. Older ones are pruned when a new revision is
+/// recorded: a history is only useful for the last few releases, and an
+/// unbounded table would grow with every `ring apply` in a CI loop.
+pub(crate) const REVISION_HISTORY_LIMIT: i64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub(crate) struct DeploymentRevision {
+    pub(crate) id: String,
+    pub(crate) namespace: String,
+    pub(crate) name: String,
+    pub(crate) revision: i64,
+    pub(crate) deployment_id: String,
+    pub(crate) spec: String,
+    pub(crate) rollback_of: Option<i64>,
+    pub(crate) created_at: String,
+}
+
+/// Store `spec` as the next revision of `namespace/name` and return it.
+///
+/// The revision number is computed inside the INSERT, so two concurrent
+/// applies of the same deployment cannot be handed the same number: the
+/// loser hits the UNIQUE constraint instead of silently overwriting history.
+pub(crate) async fn record(
+    pool: &SqlitePool,
+    namespace: &str,
+    name: &str,
+    deployment_id: &str,
+    spec: &str,
+    rollback_of: Option<i64>,
+) -> Result<DeploymentRevision, sqlx::Error> {
+    let id = Uuid::new_v4().to_string();
+
+    sqlx::query(
+        "INSERT INTO deployment_revision (id, namespace, name, revision, deployment_id, spec, rollback_of, created_at)
+         SELECT ?, ?, ?, COALESCE(MAX(revision), 0) + 1, ?, ?, ?, ?
+         FROM deployment_revision WHERE namespace = ? AND name = ?",
+    )
+    .bind(&id)
+    .bind(namespace)
+    .bind(name)
+    .bind(deployment_id)
+    .bind(spec)
+    .bind(rollback_of)
+    .bind(Utc::now().to_rfc3339())
+    .bind(namespace)
+    .bind(name)
+    .execute(pool)
+    .await?;
+
+    let revision = sqlx::query_as::<_, DeploymentRevision>(
+        "SELECT id, namespace, name, revision, deployment_id, spec, rollback_of, created_at
+         FROM deployment_revision WHERE id = ?",
+    )
+    .bind(&id)
+    .fetch_one(pool)
+    .await?;
+
+    // Pruning is housekeeping: a failure leaves a few extra rows behind, which
+    // is harmless, so it must not fail the revision that was just written.
+    if let Err(e) = sqlx::query(
+        "DELETE FROM deployment_revision WHERE namespace = ? AND name = ? AND revision <= ?",
+    )
+    .bind(namespace)
+    .bind(name)
+    .bind(revision.revision - REVISION_HISTORY_LIMIT)
+    .execute(pool)
+    .await
+    {
+        warn!(
+            "Failed to prune revision history of {}/{}: {}",
+            namespace, name, e
+        );
+    }
+
+    Ok(revision)
+}
+
+/// Every stored revision of `namespace/name`, most recent first.
+pub(crate) async fn find_by_namespace_name(
+    pool: &SqlitePool,
+    namespace: &str,
+    name: &str,
+) -> Result<Vec<DeploymentRevision>, sqlx::Error> {
+    sqlx::query_as::<_, DeploymentRevision>(
+        "SELECT id, namespace, name, revision, deployment_id, spec, rollback_of, created_at
+         FROM deployment_revision WHERE namespace = ? AND name = ? ORDER BY revision DESC",
+    )
+    .bind(namespace)
+    .bind(name)
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn find_revision(
+    pool: &SqlitePool,
+    namespace: &str,
+    name: &str,
+    revision: i64,
+) -> Result<Option<DeploymentRevision>, sqlx::Error> {
+    sqlx::query_as::<_, DeploymentRevision>(
+        "SELECT id, namespace, name, revision, deployment_id, spec, rollback_of, created_at
+         FROM deployment_revision WHERE namespace = ? AND name = ? AND revision = ?",
+    )
+    .bind(namespace)
+    .bind(name)
+    .bind(revision)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Drop a namespace's whole revision history. Called when the namespace
+/// itself is deleted — retention is namespace-bound, like the audit log.
+pub(crate) async fn delete_by_namespace(
+    pool: &SqlitePool,
+    namespace: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM deployment_revision WHERE namespace = ?")
+        .bind(namespace)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn revisions_are_numbered_per_deployment() {
+        let pool = test_pool().await;
+
+        let first = record(&pool, "ns", "api", "d1", "{}", None).await.unwrap();
+        let second = record(&pool, "ns", "api", "d2", "{}", None).await.unwrap();
+        let other = record(&pool, "ns", "web", "d3", "{}", None).await.unwrap();
+
+        assert_eq!(first.revision, 1);
+        assert_eq!(second.revision, 2);
+        // Numbering is per namespace/name, not global.
+        assert_eq!(other.revision, 1);
+
+        let history = find_by_namespace_name(&pool, "ns", "api").await.unwrap();
+        assert_eq!(
+            history.iter().map(|r| r.revision).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(
+            find_revision(&pool, "ns", "api", 1)
+                .await
+                .unwrap()
+                .unwrap()
+                .deployment_id,
+            "d1"
+        );
+        assert!(
+            find_revision(&pool, "ns", "api", 3)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn history_is_pruned_to_the_limit() {
+        let pool = test_pool().await;
+
+        for i in 0..(REVISION_HISTORY_LIMIT + 3) {
+            record(&pool, "ns", "api", &format!("d{}", i), "{}", None)
+                .await
+                .unwrap();
+        }
+
+        let history = find_by_namespace_name(&pool, "ns", "api").await.unwrap();
+        assert_eq!(history.len() as i64, REVISION_HISTORY_LIMIT);
+        // Numbers keep counting up after a prune; the oldest ones are gone.
+        assert_eq!(history[0].revision, REVISION_HISTORY_LIMIT + 3);
+        assert_eq!(history.last().unwrap().revision, 4);
+    }
+
+    #[tokio::test]
+    async fn namespace_delete_clears_history() {
+        let pool = test_pool().await;
+        record(&pool, "ns1", "api", "d1", "{}", None).await.unwrap();
+        record(&pool, "ns2", "api", "d2", "{}", None).await.unwrap();
+
+        assert_eq!(delete_by_namespace(&pool, "ns1").await.unwrap(), 1);
+        assert!(
+            find_by_namespace_name(&pool, "ns1", "api")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            find_by_namespace_name(&pool, "ns2", "api")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -5,8 +5,17 @@ use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
 
+/// Failures a worker without a `restart_policy` absorbs before it lands on
+/// `CrashLoopBackOff`: the fifth one stops it, so it is restarted four times.
 pub(crate) const MAX_RESTART_COUNT: u32 = 5;
 
+/// Delay before the first retry of a failing deployment without
+/// `restart_policy.backoff.initial`. It doubles on every further failure.
+pub(crate) const DEFAULT_BACKOFF_INITIAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Cap of the retry delay without `restart_policy.backoff.max`.
+pub(crate) const DEFAULT_BACKOFF_MAX: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// Statuses the scheduler keeps reconciling on every tick.
 ///
 /// Single source of truth: `scheduler::schedule` builds its DB filter from this
@@ -270,7 +279,7 @@ pub(crate) enum EnvValue {
     },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub(crate) struct ResourceSpec {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) cpu: Option<String>,
@@ -278,7 +287,7 @@ pub(crate) struct ResourceSpec {
     pub(crate) memory: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub(crate) struct Resource {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) limits: Option<ResourceSpec>,
@@ -286,24 +295,808 @@ pub(crate) struct Resource {
     pub(crate) requests: Option<ResourceSpec>,
 }
 
-/// Horizontal autoscaling policy for a deployment.
+fn default_max_surge() -> u32 {
+    1
+}
+
+fn default_canary_instances() -> u32 {
+    1
+}
+
+fn default_analysis_window() -> String {
+    "300s".to_string()
+}
+
+/// How a new version of a deployment replaces the running one (the manifest's
+/// `strategy:` block).
 ///
-/// Only CPU for now. Memory is deliberately left out: long-running runtimes
-/// (JVM, Node) commonly hold on to memory they no longer use, so a memory-based
-/// rule scales up and never back down, and `memory_limit_bytes` is 0 when no
-/// limit is configured, which makes a percentage meaningless. Adding
-/// `target_memory` later is a backward-compatible addition to this struct.
+/// Only consulted for a rollout, i.e. when the deployment was created with a
+/// `parent_id`. A deployment without a block behaves as
+/// `Strategy::default()`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum Strategy {
+    /// Replace instances progressively. `max_surge` is how many instances the
+    /// rollout may run above the target count, `max_unavailable` how far below
+    /// it the ready count may drop. Raising either speeds up a large rollout;
+    /// `max_surge: 0` means the new version only starts once an old instance
+    /// is gone.
+    Rolling {
+        #[serde(default = "default_max_surge")]
+        max_surge: u32,
+        #[serde(default)]
+        max_unavailable: u32,
+    },
+    /// Stop every old instance before starting any new one. For singletons
+    /// (holding a lock, a port, a single writer) where the two versions must
+    /// never overlap. Implies downtime for the length of the start.
+    Recreate,
+    /// Bring up `instances` of the new version next to the old ones and hold
+    /// for `analysis_window`. Promoted automatically if its health checks stay
+    /// green and it reports no runtime error meanwhile, aborted (and torn down)
+    /// otherwise; `ring deployment promote|abort` decides early. Once promoted
+    /// it proceeds as the default rolling update.
+    Canary {
+        #[serde(default = "default_canary_instances")]
+        instances: u32,
+        #[serde(default = "default_analysis_window")]
+        analysis_window: String,
+    },
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Strategy::Rolling {
+            max_surge: default_max_surge(),
+            max_unavailable: 0,
+        }
+    }
+}
+
+impl Strategy {
+    /// Reject a strategy that could never make progress. Returns the reason so
+    /// the API can report it verbatim.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self {
+            Strategy::Rolling {
+                max_surge: 0,
+                max_unavailable: 0,
+            } => Err(
+                "strategy.max_surge and strategy.max_unavailable cannot both be 0: the rollout could neither add a new instance nor remove an old one"
+                    .to_string(),
+            ),
+            Strategy::Canary { instances: 0, .. } => {
+                Err("strategy.instances must be at least 1".to_string())
+            }
+            Strategy::Canary {
+                analysis_window, ..
+            } => match crate::models::health_check::HealthCheck::parse_duration(analysis_window)
+            {
+                Ok(window) if !window.is_zero() => Ok(()),
+                Ok(_) => Err("strategy.analysis_window must be longer than 0s".to_string()),
+                Err(e) => Err(format!("strategy.analysis_window: {}", e)),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Surge and unavailability budget of the rolling phase: the `rolling`
+    /// numbers themselves, the defaults for a promoted canary. `None` for
+    /// `recreate`.
+    /// Whether a rollout with this strategy runs instances of both versions
+    /// at once.
+    pub(crate) fn surges(&self) -> bool {
+        match self {
+            Strategy::Rolling { max_surge, .. } => *max_surge > 0,
+            Strategy::Canary { .. } => true,
+            Strategy::Recreate => false,
+        }
+    }
+
+    pub(crate) fn rolling_budget(&self) -> Option<(u32, u32)> {
+        match self {
+            Strategy::Rolling {
+                max_surge,
+                max_unavailable,
+            } => Some((*max_surge, *max_unavailable)),
+            Strategy::Canary { .. } => Some((default_max_surge(), 0)),
+            Strategy::Recreate => None,
+        }
+    }
+}
+
+/// What a cronjob does when a trigger fires while a previous run is still
+/// active.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ConcurrencyPolicy {
+    /// Start the new run next to the active one(s).
+    Allow,
+    /// Skip the trigger; the next one is tried as usual.
+    #[default]
+    Forbid,
+    /// Tear the active run(s) down and start the new one.
+    Replace,
+}
+
+impl fmt::Display for ConcurrencyPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcurrencyPolicy::Allow => write!(f, "allow"),
+            ConcurrencyPolicy::Forbid => write!(f, "forbid"),
+            ConcurrencyPolicy::Replace => write!(f, "replace"),
+        }
+    }
+}
+
+fn default_history_limit() -> u32 {
+    3
+}
+
+/// Schedule of a `kind: cronjob` deployment (the manifest's `cron:` block).
+///
+/// The cronjob itself never runs a container: every trigger spawns a
+/// `kind: job` deployment (a run) from the cronjob's spec.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Cron {
+    /// Standard five-field cron expression (`minute hour day month weekday`),
+    /// evaluated in UTC.
+    pub(crate) schedule: String,
+    #[serde(default)]
+    pub(crate) concurrency_policy: ConcurrencyPolicy,
+    /// How many finished runs are kept, with their status and logs, before the
+    /// oldest are deleted.
+    #[serde(default = "default_history_limit")]
+    pub(crate) history_limit: u32,
+}
+
+impl Cron {
+    /// Reject a schedule that does not parse. Returns the reason so the API can
+    /// report it verbatim.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        self.parse().map(|_| ())
+    }
+
+    /// The first trigger strictly after `after`, `None` if the schedule does
+    /// not parse or never fires again.
+    pub(crate) fn next_after(
+        &self,
+        after: &chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.parse().ok()?.find_next_occurrence(after, false).ok()
+    }
+
+    /// The latest trigger in `(after, now]`, `None` when nothing is due. Every
+    /// trigger missed in between (the server was down, the cronjob paused)
+    /// collapses into this one: a cronjob catches up with a single run, not
+    /// one per missed trigger.
+    pub(crate) fn due(
+        &self,
+        after: &chrono::DateTime<chrono::Utc>,
+        now: &chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.parse()
+            .ok()?
+            .iter_after(*after)
+            .take_while(|trigger| trigger <= now)
+            .last()
+    }
+
+    fn parse(&self) -> Result<croner::Cron, String> {
+        croner::Cron::new(&self.schedule)
+            .parse()
+            .map_err(|e| format!("cron.schedule '{}': {}", self.schedule, e))
+    }
+}
+
+fn default_job_count() -> u32 {
+    1
+}
+
+/// Retry, deadline and completion policy of a `kind: job` (the manifest's
+/// `job:` block). A cronjob carries it for its runs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct JobSpec {
+    /// How many times a failed attempt (a container exiting non-zero, or one
+    /// that could not be created) is retried before the job is `failed`.
+    /// Unset keeps the historical behaviour: a non-zero exit fails the job at
+    /// once and only creation errors are retried, up to `MAX_RESTART_COUNT`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) backoff_limit: Option<u32>,
+    /// Wall-clock budget of the whole job, counted from its creation (e.g.
+    /// `"600s"`). Past it, the running containers are killed and the job is
+    /// `failed`, whatever its retries left.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) active_deadline: Option<String>,
+    /// How many containers may run at the same time.
+    #[serde(default = "default_job_count")]
+    pub(crate) parallelism: u32,
+    /// How many containers must exit 0 for the job to be `completed`.
+    #[serde(default = "default_job_count")]
+    pub(crate) completions: u32,
+    /// Delete the job (containers, logs, events) this long after it finished,
+    /// e.g. `"3600s"`. Unset keeps it until it is deleted by hand.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) ttl_after_finished: Option<String>,
+}
+
+impl Default for JobSpec {
+    fn default() -> Self {
+        JobSpec {
+            backoff_limit: None,
+            active_deadline: None,
+            parallelism: default_job_count(),
+            completions: default_job_count(),
+            ttl_after_finished: None,
+        }
+    }
+}
+
+impl JobSpec {
+    /// Reject a policy that could never complete. Returns the reason so the
+    /// API can report it verbatim.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.parallelism < 1 {
+            return Err("job.parallelism must be at least 1".to_string());
+        }
+        if self.completions < 1 {
+            return Err("job.completions must be at least 1".to_string());
+        }
+        if let Some(deadline) = &self.active_deadline {
+            match crate::models::health_check::HealthCheck::parse_duration(deadline) {
+                Ok(duration) if !duration.is_zero() => {}
+                Ok(_) => return Err("job.active_deadline must be longer than 0s".to_string()),
+                Err(e) => return Err(format!("job.active_deadline: {}", e)),
+            }
+        }
+        if let Some(ttl) = &self.ttl_after_finished {
+            crate::models::health_check::HealthCheck::parse_duration(ttl)
+                .map_err(|e| format!("job.ttl_after_finished: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// `active_deadline` as a duration, `None` when unset or unparsable.
+    pub(crate) fn deadline(&self) -> Option<std::time::Duration> {
+        self.active_deadline
+            .as_deref()
+            .and_then(|d| crate::models::health_check::HealthCheck::parse_duration(d).ok())
+    }
+
+    /// `ttl_after_finished` as a duration, `None` when unset or unparsable.
+    pub(crate) fn ttl(&self) -> Option<std::time::Duration> {
+        self.ttl_after_finished
+            .as_deref()
+            .and_then(|d| crate::models::health_check::HealthCheck::parse_duration(d).ok())
+    }
+}
+
+/// An extra container of every instance of a deployment: an entry of the
+/// manifest's `init_containers:` (run to completion before the main container)
+/// or `sidecars:` (run next to it, in its network namespace). Both mount the
+/// deployment's volumes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct ContainerSpec {
+    pub(crate) name: String,
+    pub(crate) image: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) command: Vec<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub(crate) environment: HashMap<String, EnvValue>,
+}
+
+impl ContainerSpec {
+    /// Names end up in container names and log lines: lowercase letters,
+    /// digits and dashes, at most 63 characters.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let valid_name = !self.name.is_empty()
+            && self.name.len() <= 63
+            && !self.name.starts_with('-')
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid_name {
+            return Err(format!(
+                "'{}' is not a valid container name (lowercase letters, digits and '-', at most 63 characters)",
+                self.name
+            ));
+        }
+        if self.image.trim().is_empty() {
+            return Err(format!("container '{}' needs an image", self.name));
+        }
+        Ok(())
+    }
+}
+
+/// Grace period an instance gets between its stop signal and SIGKILL when
+/// `lifecycle.stop_grace_period` is unset. Docker's own default.
+pub(crate) const DEFAULT_STOP_GRACE_PERIOD: std::time::Duration =
+    std::time::Duration::from_secs(10);
+
+/// Linux signal numbers by name, for `lifecycle.stop_signal`.
+const SIGNALS: &[(&str, u32)] = &[
+    ("HUP", 1),
+    ("INT", 2),
+    ("QUIT", 3),
+    ("ABRT", 6),
+    ("KILL", 9),
+    ("USR1", 10),
+    ("USR2", 12),
+    ("ALRM", 14),
+    ("TERM", 15),
+    ("WINCH", 28),
+    ("PWR", 30),
+];
+
+/// Something run inside an instance around its start or stop: a command
+/// (`{ command: [...] }`) or, for `pre_stop` only, an HTTP GET
+/// (`{ http: { port, path } }`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LifecycleHook {
+    Command(Vec<String>),
+    Http(HttpHook),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct HttpHook {
+    pub(crate) port: u16,
+    #[serde(default = "default_hook_path")]
+    pub(crate) path: String,
+}
+
+fn default_hook_path() -> String {
+    "/".to_string()
+}
+
+/// How an instance is stopped, and what runs right after it starts (the
+/// manifest's `lifecycle:` block). Applies wherever an instance is stopped:
+/// scale-down, rolling update, restart, deletion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub(crate) struct Lifecycle {
+    /// Signal asking the workload to exit, by name (`SIGQUIT`, `QUIT`) or
+    /// number. Unset: SIGTERM (the image's `STOPSIGNAL` on Docker and Podman).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) stop_signal: Option<String>,
+    /// How long the instance gets to exit, `pre_stop` included, before it is
+    /// killed (e.g. `"60s"`). Unset: [`DEFAULT_STOP_GRACE_PERIOD`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) stop_grace_period: Option<String>,
+    /// Run before the stop signal, e.g. to deregister from a queue.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) pre_stop: Option<LifecycleHook>,
+    /// Run once the instance started. A failure fails the instance.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) post_start: Option<LifecycleHook>,
+}
+
+impl Lifecycle {
+    /// Reject what no runtime could honour. Returns the reason so the API can
+    /// report it verbatim.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if let Some(signal) = &self.stop_signal
+            && parse_signal(signal).is_none()
+        {
+            return Err(format!(
+                "lifecycle.stop_signal: unknown signal '{}'",
+                signal
+            ));
+        }
+        if let Some(grace) = &self.stop_grace_period {
+            crate::models::health_check::HealthCheck::parse_duration(grace)
+                .map_err(|e| format!("lifecycle.stop_grace_period: {}", e))?;
+        }
+        match &self.pre_stop {
+            Some(LifecycleHook::Command(command)) if command.is_empty() => {
+                return Err("lifecycle.pre_stop.command must not be empty".to_string());
+            }
+            Some(LifecycleHook::Http(http)) if http.port == 0 || !http.path.starts_with('/') => {
+                return Err(
+                    "lifecycle.pre_stop.http needs a port and a path starting with '/'".to_string(),
+                );
+            }
+            _ => {}
+        }
+        match &self.post_start {
+            Some(LifecycleHook::Command(command)) if command.is_empty() => {
+                Err("lifecycle.post_start.command must not be empty".to_string())
+            }
+            Some(LifecycleHook::Http(_)) => {
+                Err("lifecycle.post_start only supports a command".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `stop_signal` as a number, SIGTERM when unset or unknown.
+    pub(crate) fn signal_number(&self) -> u32 {
+        self.stop_signal
+            .as_deref()
+            .and_then(parse_signal)
+            .unwrap_or(15)
+    }
+
+    /// `stop_grace_period` as a duration, [`DEFAULT_STOP_GRACE_PERIOD`] when
+    /// unset or unparsable.
+    pub(crate) fn grace_period(&self) -> std::time::Duration {
+        self.stop_grace_period
+            .as_deref()
+            .and_then(|d| crate::models::health_check::HealthCheck::parse_duration(d).ok())
+            .unwrap_or(DEFAULT_STOP_GRACE_PERIOD)
+    }
+}
+
+/// `SIGQUIT`, `QUIT` or `3` to 3. `None` for anything else.
+fn parse_signal(signal: &str) -> Option<u32> {
+    if let Ok(number) = signal.parse::<u32>() {
+        return (1..=64).contains(&number).then_some(number);
+    }
+    let upper = signal.to_ascii_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    SIGNALS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, number)| *number)
+}
+
+/// Which exits of a worker's instance are restarted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RestartMode {
+    /// Every exit, a clean one (code 0) included.
+    Always,
+    /// Crashes only; a clean exit completes the deployment.
+    #[default]
+    OnFailure,
+    /// None: the first crash stops the deployment.
+    Never,
+}
+
+impl fmt::Display for RestartMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestartMode::Always => write!(f, "always"),
+            RestartMode::OnFailure => write!(f, "on_failure"),
+            RestartMode::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// Bounds of the delay between two retries, as durations (`"1s"`, `"300s"`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub(crate) struct RestartBackoff {
+    /// Unset: [`DEFAULT_BACKOFF_INITIAL`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) initial: Option<String>,
+    /// Unset: [`DEFAULT_BACKOFF_MAX`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) max: Option<String>,
+}
+
+/// How a worker is restarted when an instance exits or fails to start (the
+/// manifest's `restart_policy:` block).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub(crate) struct RestartPolicy {
+    #[serde(default)]
+    pub(crate) mode: RestartMode,
+    /// Restarts allowed before the deployment lands on `CrashLoopBackOff`.
+    /// Unset: no limit. The count is forgiven once the worker stayed healthy
+    /// for its anti-flap window.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) max_restarts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) backoff: Option<RestartBackoff>,
+}
+
+impl RestartPolicy {
+    /// Reject what no runtime could honour. Returns the reason so the API can
+    /// report it verbatim.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.mode == RestartMode::Never && self.max_restarts.is_some() {
+            return Err("restart_policy.max_restarts does not apply to mode never".to_string());
+        }
+        let Some(backoff) = &self.backoff else {
+            return Ok(());
+        };
+        let parse = |field: &str, value: &Option<String>| match value {
+            Some(raw) => crate::models::health_check::HealthCheck::parse_duration(raw)
+                .map(Some)
+                .map_err(|e| format!("restart_policy.backoff.{}: {}", field, e)),
+            None => Ok(None),
+        };
+        if parse("initial", &backoff.initial)?.is_some_and(|initial| initial.is_zero()) {
+            return Err("restart_policy.backoff.initial must be greater than zero".to_string());
+        }
+        parse("max", &backoff.max)?;
+        let (initial, max) = self.backoff_bounds();
+        if initial > max {
+            return Err(format!(
+                "restart_policy.backoff.initial ({}s) must not exceed backoff.max ({}s)",
+                initial.as_secs(),
+                max.as_secs()
+            ));
+        }
+        Ok(())
+    }
+
+    /// `(initial, max)` delay between two retries, the defaults for what is
+    /// unset or unparsable.
+    pub(crate) fn backoff_bounds(&self) -> (std::time::Duration, std::time::Duration) {
+        let parse = |value: Option<&String>, default| {
+            value
+                .and_then(|d| crate::models::health_check::HealthCheck::parse_duration(d).ok())
+                .unwrap_or(default)
+        };
+        let backoff = self.backoff.as_ref();
+        (
+            parse(
+                backoff.and_then(|b| b.initial.as_ref()),
+                DEFAULT_BACKOFF_INITIAL,
+            ),
+            parse(backoff.and_then(|b| b.max.as_ref()), DEFAULT_BACKOFF_MAX),
+        )
+    }
+}
+
+/// How long a connection that woke an `idle` deployment waits for it to be
+/// ready without `idle.wake_timeout`.
+pub(crate) const DEFAULT_IDLE_WAKE_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_secs(60);
+
+/// Scale-to-zero for a rarely used worker (the manifest's `idle:` block).
+///
+/// Ring binds the deployment's published ports itself and proxies them to the
+/// instance, so it sees every connection: once none was open for `after`, the
+/// deployment is scaled to zero, and the next one starts it again and is held
+/// until it is ready (see `scheduler::idle`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Idle {
+    /// Time without any connection before the deployment is scaled to zero,
+    /// e.g. `"900s"`.
+    pub(crate) after: String,
+    /// How long a connection waits for the deployment it woke to be ready
+    /// before it is dropped. Unset: [`DEFAULT_IDLE_WAKE_TIMEOUT`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) wake_timeout: Option<String>,
+}
+
+impl Idle {
+    /// Reject durations that do not parse or leave no room to run. Returns the
+    /// reason so the API can report it verbatim.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let after = crate::models::health_check::HealthCheck::parse_duration(&self.after)
+            .map_err(|e| format!("idle.after: {}", e))?;
+        if after.is_zero() {
+            return Err("idle.after must be greater than zero".to_string());
+        }
+        if let Some(raw) = &self.wake_timeout {
+            let timeout = crate::models::health_check::HealthCheck::parse_duration(raw)
+                .map_err(|e| format!("idle.wake_timeout: {}", e))?;
+            if timeout.is_zero() {
+                return Err("idle.wake_timeout must be greater than zero".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// `after` as a duration. `None` when it does not parse, in which case the
+    /// deployment is never put to sleep.
+    pub(crate) fn idle_after(&self) -> Option<std::time::Duration> {
+        crate::models::health_check::HealthCheck::parse_duration(&self.after).ok()
+    }
+
+    /// `wake_timeout` as a duration, the default when unset or unparsable.
+    pub(crate) fn wake_timeout(&self) -> std::time::Duration {
+        self.wake_timeout
+            .as_deref()
+            .and_then(|d| crate::models::health_check::HealthCheck::parse_duration(d).ok())
+            .unwrap_or(DEFAULT_IDLE_WAKE_TIMEOUT)
+    }
+}
+
+/// How the lines a deployment writes are read (the manifest's `logging:`
+/// block). Applied wherever its logs are served, by
+/// `hypervisor::log_format::LogParser`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub(crate) struct Logging {
+    /// `json`, `logfmt` or `text`. Unset: a line that is a JSON object is
+    /// read as one, any other as text.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) format: Option<String>,
+    /// Regex matching the lines that continue the previous entry, e.g.
+    /// `^\s+at |^Caused by:` for Java stack traces.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) multiline_pattern: Option<String>,
+}
+
+/// The `logging.format` values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LogFormat {
+    /// Detect JSON objects, take anything else as text.
+    Auto,
+    Json,
+    Logfmt,
+    Text,
+}
+
+impl Logging {
+    /// Reject an unknown format or a pattern that does not compile. Returns
+    /// the reason so the API can report it verbatim.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if let Some(format) = &self.format
+            && !matches!(format.as_str(), "json" | "logfmt" | "text")
+        {
+            return Err(format!(
+                "logging.format must be json, logfmt or text, not {}",
+                format
+            ));
+        }
+        if let Some(pattern) = &self.multiline_pattern {
+            regex::Regex::new(pattern).map_err(|e| format!("logging.multiline_pattern: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// `format` as a [`LogFormat`], auto-detection when unset or unknown.
+    pub(crate) fn format(&self) -> LogFormat {
+        match self.format.as_deref() {
+            Some("json") => LogFormat::Json,
+            Some("logfmt") => LogFormat::Logfmt,
+            Some("text") => LogFormat::Text,
+            _ => LogFormat::Auto,
+        }
+    }
+}
+
+/// What a dependency must have reached before its dependent is started.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DependencyCondition {
+    /// The dependency is running (or, for a job, has run).
+    #[default]
+    Started,
+    /// The dependency is running and its readiness health checks are green
+    /// for their `min_healthy_time`. Same as `started` without readiness
+    /// checks.
+    Ready,
+    /// The dependency is a job that completed successfully.
+    Completed,
+}
+
+impl fmt::Display for DependencyCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyCondition::Started => write!(f, "started"),
+            DependencyCondition::Ready => write!(f, "ready"),
+            DependencyCondition::Completed => write!(f, "completed"),
+        }
+    }
+}
+
+/// One entry of the manifest's `depends_on:` list: another deployment of the
+/// same namespace, by name.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Dependency {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) condition: DependencyCondition,
+}
+
+/// The first dependency cycle through `start`, given every deployment's
+/// dependencies by name, as the path of names from `start` back to itself.
+/// `None` when `start` is on no cycle.
+pub(crate) fn dependency_cycle(
+    start: &str,
+    edges: &HashMap<String, Vec<String>>,
+) -> Option<Vec<String>> {
+    fn visit(
+        name: &str,
+        start: &str,
+        edges: &HashMap<String, Vec<String>>,
+        path: &mut Vec<String>,
+        seen: &mut std::collections::HashSet<String>,
+    ) -> bool {
+        for next in edges.get(name).into_iter().flatten() {
+            path.push(next.clone());
+            if next == start {
+                return true;
+            }
+            if seen.insert(next.clone()) && visit(next, start, edges, path, seen) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let mut path = vec![start.to_string()];
+    let mut seen = std::collections::HashSet::new();
+    visit(start, start, edges, &mut path, &mut seen).then_some(path)
+}
+
+/// Horizontal autoscaling policy for a deployment.
+///
+/// Scales on any mix of three per-instance signals: CPU (`target_cpu`), memory
+/// (`target_memory`) and gauges the workload exposes (`metrics`). Memory is
+/// measured against `requests.memory`, not the limit: `memory_limit_bytes` is
+/// 0 when no limit is configured, which would make a percentage meaningless.
+/// Mind that long-running runtimes (JVM, Node) commonly hold on to memory they
+/// no longer use, so a memory target scales up more readily than back down.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub(crate) struct Autoscale {
-    /// Never scale below this. Must be >= 1: scaling to zero is a different
-    /// feature (it needs a wake-up path, which Ring has no way to trigger).
+    /// Never scale below this. Must be >= 1: scaling to zero is the job of the
+    /// `idle` block, which wakes the deployment on its next connection.
     pub(crate) min: u32,
     /// Never scale above this. The ceiling matters on a single node, where
     /// instances share one machine's CPU.
     pub(crate) max: u32,
     /// Average CPU percentage across the deployment's instances that the
     /// autoscaler aims to hold, e.g. 70.
-    pub(crate) target_cpu: f64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) target_cpu: Option<f64>,
+    /// Average memory usage across the instances, as a percentage of
+    /// `resources.requests.memory`, e.g. 80.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) target_memory: Option<f64>,
+    /// Gauges scraped from the instances, e.g. a queue's backlog.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) metrics: Vec<MetricTarget>,
+    /// Minimum time between a scale-up and the next decision (e.g. `"60s"`).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) cooldown_up: Option<String>,
+    /// Minimum time between a scale-down and the next decision (e.g. `"300s"`).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) cooldown_down: Option<String>,
+    /// How far from a target still counts as on target, as a fraction of the
+    /// target (0.1 is ±10%). Unset: 10 points for `target_cpu` and
+    /// `target_memory`, 10% of the target for a metric.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) tolerance: Option<f64>,
+}
+
+/// Whether a scraped gauge describes the whole deployment or one instance.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum MetricScope {
+    /// Every instance reports the same value (a queue's backlog): it is read
+    /// from one instance and split across the running instances.
+    #[default]
+    Deployment,
+    /// Each instance reports its own load (requests in flight): the values are
+    /// averaged.
+    Instance,
+}
+
+impl fmt::Display for MetricScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetricScope::Deployment => write!(f, "deployment"),
+            MetricScope::Instance => write!(f, "instance"),
+        }
+    }
+}
+
+/// One entry of `autoscale.metrics`: a Prometheus gauge the workload serves
+/// over HTTP, and the value per instance to hold it at.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct MetricTarget {
+    /// Name of the gauge in the Prometheus text format.
+    pub(crate) name: String,
+    /// Only the series carrying these labels count; their values are summed.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub(crate) labels: HashMap<String, String>,
+    pub(crate) port: u16,
+    #[serde(default = "default_metrics_path")]
+    pub(crate) path: String,
+    /// Value per instance to aim for.
+    pub(crate) target: f64,
+    #[serde(default)]
+    pub(crate) scope: MetricScope,
+}
+
+fn default_metrics_path() -> String {
+    "/metrics".to_string()
 }
 
 impl Autoscale {
@@ -311,7 +1104,10 @@ impl Autoscale {
     /// can report it verbatim.
     pub(crate) fn validate(&self) -> Result<(), String> {
         if self.min < 1 {
-            return Err("autoscale.min must be at least 1".to_string());
+            return Err(
+                "autoscale.min must be at least 1; scale to zero with an `idle` block instead"
+                    .to_string(),
+            );
         }
         if self.max < self.min {
             return Err(format!(
@@ -319,9 +1115,60 @@ impl Autoscale {
                 self.max, self.min
             ));
         }
-        if !(self.target_cpu.is_finite() && self.target_cpu > 0.0 && self.target_cpu < 100.0) {
+        if self.target_cpu.is_none() && self.target_memory.is_none() && self.metrics.is_empty() {
+            return Err(
+                "autoscale needs a target: target_cpu, target_memory or metrics".to_string(),
+            );
+        }
+        if let Some(cpu) = self.target_cpu
+            && !(cpu.is_finite() && cpu > 0.0 && cpu < 100.0)
+        {
             return Err("autoscale.target_cpu must be between 0 and 100 (exclusive)".to_string());
         }
+        if let Some(memory) = self.target_memory
+            && !(memory.is_finite() && memory > 0.0)
+        {
+            return Err("autoscale.target_memory must be a positive percentage".to_string());
+        }
+        for metric in &self.metrics {
+            if metric.name.is_empty()
+                || !metric
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+            {
+                return Err(format!(
+                    "autoscale.metrics: '{}' is not a valid metric name",
+                    metric.name
+                ));
+            }
+            if metric.port == 0 || !metric.path.starts_with('/') {
+                return Err(format!(
+                    "autoscale.metrics.{} needs a port and a path starting with '/'",
+                    metric.name
+                ));
+            }
+            if !(metric.target.is_finite() && metric.target > 0.0) {
+                return Err(format!(
+                    "autoscale.metrics.{}.target must be positive",
+                    metric.name
+                ));
+            }
+        }
+        for (field, value) in [
+            ("cooldown_up", &self.cooldown_up),
+            ("cooldown_down", &self.cooldown_down),
+        ] {
+            if let Some(raw) = value {
+                crate::models::health_check::HealthCheck::parse_duration(raw)
+                    .map_err(|e| format!("autoscale.{}: {}", field, e))?;
+            }
+        }
+        if let Some(tolerance) = self.tolerance
+            && !(tolerance.is_finite() && (0.0..1.0).contains(&tolerance))
+        {
+            return Err("autoscale.tolerance must be at least 0 and below 1".to_string());
+        }
         Ok(())
     }
 
@@ -422,6 +1269,77 @@ pub(crate) struct Deployment {
     pub(crate) parent_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub(crate) network: Option<NetworkConfig>,
+    /// Instances still to be replaced by a rolling restart, oldest request
+    /// first. Empty when no restart is in progress.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) pending_restart: Vec<String>,
+    /// Reconciliation suspended (`POST /deployments/{id}/pause`): the scheduler
+    /// leaves the deployment and its instances alone until it is resumed.
+    #[serde(default)]
+    pub(crate) paused: bool,
+    /// Rollout strategy from the manifest. `None` behaves as
+    /// `Strategy::default()`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) strategy: Option<Strategy>,
+    /// Ceiling the scheduler puts on [`Deployment::target_replicas`] for one
+    /// tick while a rollout is in progress: the incoming deployment may not
+    /// outgrow its surge budget, and the outgoing one must not respawn what is
+    /// being drained. Never persisted.
+    #[serde(skip)]
+    pub(crate) rollout_limit: Option<u32>,
+    /// One instance over [`Deployment::settled_replicas`] for one tick while
+    /// a rolling restart is in progress, so each replacement is up before the
+    /// instance it replaces is removed. Never persisted.
+    #[serde(skip)]
+    pub(crate) restart_surge: bool,
+    /// Schedule of a `kind: cronjob`, `None` for every other kind.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) cron: Option<Cron>,
+    /// The cronjob a run was spawned by, `None` outside of cronjob runs.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) cronjob_id: Option<String>,
+    /// Last trigger a cronjob acted on (RFC 3339), `None` until it first fires.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) last_scheduled_at: Option<String>,
+    /// Retry, deadline and completion policy of a job, `None` for the
+    /// defaults (see [`JobSpec`]).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) job: Option<JobSpec>,
+    /// Exit code of the job's last finished attempt, `None` until one exits.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) exit_code: Option<i64>,
+    /// Deployments of the same namespace this one waits for before it is
+    /// started. Only consulted while it is `pending`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) depends_on: Vec<Dependency>,
+    /// Containers run to completion, in order, before each instance's main
+    /// container starts.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) init_containers: Vec<ContainerSpec>,
+    /// Containers running next to each instance's main container, sharing its
+    /// network namespace and volumes.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) sidecars: Vec<ContainerSpec>,
+    /// Stop signal, grace period and hooks, `None` for the runtime defaults
+    /// (see [`Lifecycle`]).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) lifecycle: Option<Lifecycle>,
+    /// How exits and failed starts are retried, `None` for the defaults (see
+    /// [`RestartPolicy`]).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) restart_policy: Option<RestartPolicy>,
+    /// Scale-to-zero policy, `None` for a deployment that always runs (see
+    /// [`Idle`]).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) idle: Option<Idle>,
+    /// How its output is parsed, `None` to detect JSON lines (see
+    /// [`Logging`]).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) logging: Option<Logging>,
+    /// Manifest fields filled in from the namespace's defaults, e.g.
+    /// `runtime` or `labels.team`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) defaulted: Vec<String>,
 }
 
 impl Deployment {
@@ -447,16 +1365,137 @@ impl Deployment {
     /// This is what the runtimes reconcile against, NOT `replicas`. When the
     /// deployment is autoscaled, the autoscaler's decision wins; otherwise the
     /// declared count does. `replicas` therefore keeps meaning "what the
-    /// manifest asked for" and stays untouched by the scheduler.
+    /// manifest asked for" and stays untouched by the scheduler. An `idle`
+    /// deployment put to sleep targets zero.
     ///
     /// The decision is re-clamped here rather than trusted: a policy edited to
     /// a narrower range (say max lowered from 10 to 4) must take effect on the
     /// next tick, not once the autoscaler happens to write a new value.
+    ///
+    /// During a rollout the scheduler may cap it further for the current tick
+    /// (`rollout_limit`), so the two versions stay within the strategy's budget;
+    /// during a rolling restart it adds the replacement (`restart_surge`).
     pub(crate) fn target_replicas(&self) -> u32 {
+        let target = self.settled_replicas() + u32::from(self.restart_surge);
+        match self.rollout_limit {
+            Some(limit) => target.min(limit),
+            None => target,
+        }
+    }
+
+    /// Failures the deployment absorbs before it gives up: `restart_count`
+    /// at which a worker lands on `CrashLoopBackOff`. `None` when its
+    /// `restart_policy` sets no `max_restarts`, and it retries for good.
+    pub(crate) fn failure_budget(&self) -> Option<u32> {
+        match &self.restart_policy {
+            None => Some(MAX_RESTART_COUNT),
+            Some(policy) if policy.mode == RestartMode::Never => Some(1),
+            Some(policy) => policy.max_restarts.map(|n| n.saturating_add(1)),
+        }
+    }
+
+    /// Whether `restart_count` has used up the [`Deployment::failure_budget`].
+    pub(crate) fn restarts_exhausted(&self) -> bool {
+        self.failure_budget()
+            .is_some_and(|budget| self.restart_count >= budget)
+    }
+
+    /// Spend the rest of the budget at once, for a creation error that cannot
+    /// fix itself on a retry. Without a budget it only counts as one more
+    /// failure: the deployment keeps retrying, at its backoff's pace.
+    pub(crate) fn exhaust_restarts(&mut self) {
+        match self.failure_budget() {
+            Some(budget) => self.restart_count = self.restart_count.max(budget),
+            None => self.restart_count += 1,
+        }
+    }
+
+    /// Which exits of an instance are restarted.
+    pub(crate) fn restart_mode(&self) -> RestartMode {
+        self.restart_policy
+            .as_ref()
+            .map(|policy| policy.mode)
+            .unwrap_or_default()
+    }
+
+    /// `(initial, max)` delay between two retries.
+    pub(crate) fn restart_backoff(&self) -> (std::time::Duration, std::time::Duration) {
+        self.restart_policy
+            .as_ref()
+            .map(|policy| policy.backoff_bounds())
+            .unwrap_or((DEFAULT_BACKOFF_INITIAL, DEFAULT_BACKOFF_MAX))
+    }
+
+    /// Where a cronjob's schedule resumes from: its last trigger, or its
+    /// creation when it never fired. `None` when neither parses.
+    pub(crate) fn schedule_anchor(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        if let Some(at) = &self.last_scheduled_at {
+            return chrono::DateTime::parse_from_rfc3339(at)
+                .ok()
+                .map(|at| at.with_timezone(&chrono::Utc));
+        }
+        self.created_at_utc()
+    }
+
+    /// Whether an `idle` deployment was scaled to zero for lack of
+    /// connections. It stays so until the next one wakes it.
+    pub(crate) fn asleep(&self) -> bool {
+        self.idle.is_some() && self.desired_replicas == Some(0)
+    }
+
+    /// The published ports the runtime binds on the host itself. None under
+    /// `idle`: Ring holds those ports and proxies them to the instance.
+    pub(crate) fn runtime_bound_ports(&self) -> &[DeploymentPort] {
+        if self.idle.is_some() {
+            &[]
+        } else {
+            &self.ports
+        }
+    }
+
+    /// `created_at` as a timestamp, `None` when it does not parse.
+    pub(crate) fn created_at_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        // `created_at` is `Utc::now().to_string()`, e.g.
+        // "2026-05-30 20:07:20.341309196 UTC", which the RFC 3339 parser
+        // rejects.
+        let trimmed = self.created_at.trim_end_matches(" UTC").trim();
+        chrono::NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f")
+            .ok()
+            .map(|at| at.and_utc())
+    }
+
+    /// Whether an instance can run next to the current ones: not with a
+    /// published host port or host networking, where the two would collide on
+    /// the bind, nor with a strategy that rules out any overlap (`recreate`,
+    /// `max_surge: 0`).
+    pub(crate) fn can_surge(&self) -> bool {
+        self.strategy.clone().unwrap_or_default().surges()
+            && !self.ports.iter().any(|p| p.published > 0)
+            && !self
+                .network
+                .as_ref()
+                .is_some_and(|network| matches!(network.mode, NetworkMode::Host))
+    }
+
+    /// The count the deployment converges on once no rollout holds it back:
+    /// [`Deployment::target_replicas`] without the scheduler's per-tick
+    /// `rollout_limit`.
+    pub(crate) fn settled_replicas(&self) -> u32 {
+        if self.asleep() {
+            return 0;
+        }
         match (&self.autoscale, self.desired_replicas) {
             (Some(policy), Some(desired)) => policy.clamp(desired),
-            (Some(policy), None) => policy.clamp(self.replicas),
-            (None, _) => self.replicas,
+            _ => self.awake_replicas(),
+        }
+    }
+
+    /// The count an `idle` deployment wakes up at: a wake clears
+    /// `desired_replicas`, so it is the declared count, within `autoscale`.
+    pub(crate) fn awake_replicas(&self) -> u32 {
+        match &self.autoscale {
+            Some(policy) => policy.clamp(self.replicas),
+            None => self.replicas,
         }
     }
 }
@@ -487,6 +1526,22 @@ struct DeploymentRow {
     parent_id: Option<String>,
     ports: Option<String>,
     network_mode: Option<String>,
+    pending_restart: Option<String>,
+    paused: bool,
+    strategy: Option<String>,
+    cron: Option<String>,
+    cronjob_id: Option<String>,
+    last_scheduled_at: Option<String>,
+    job: Option<String>,
+    exit_code: Option<i64>,
+    depends_on: Option<String>,
+    init_containers: Option<String>,
+    sidecars: Option<String>,
+    lifecycle: Option<String>,
+    restart_policy: Option<String>,
+    idle: Option<String>,
+    logging: Option<String>,
+    defaulted: Option<String>,
 }
 
 fn parse_environment(json_str: &str, deployment_id: &str) -> HashMap<String, EnvValue> {
@@ -531,70 +1586,213 @@ impl From<DeploymentRow> for Deployment {
                 warn!("Failed to deserialize command for deployment {}: {}", id, e);
                 Vec::new()
             }),
-            instances: vec![],
-            labels: serde_json::from_str(&row.labels).unwrap_or_else(|e| {
-                warn!("Failed to deserialize labels for deployment {}: {}", id, e);
-                HashMap::new()
+            instances: vec![],
+            labels: serde_json::from_str(&row.labels).unwrap_or_else(|e| {
+                warn!("Failed to deserialize labels for deployment {}: {}", id, e);
+                HashMap::new()
+            }),
+            environment: parse_environment(&row.environment, &id),
+            volumes: row.volumes,
+            health_checks: row
+                .health_checks
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    serde_json::from_str(&s).unwrap_or_else(|e| {
+                        warn!(
+                            "Failed to deserialize health_checks for deployment {}: {}",
+                            id, e
+                        );
+                        Vec::new()
+                    })
+                })
+                .unwrap_or_default(),
+            resources: row
+                .resources
+                .filter(|s| !s.is_empty())
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            autoscale: row
+                .autoscale
+                .filter(|s| !s.is_empty())
+                .and_then(|s| {
+                    serde_json::from_str(&s)
+                        .map_err(|e| {
+                            warn!(
+                                "Failed to deserialize autoscale for deployment {}: {} — treating it as not autoscaled",
+                                id, e
+                            );
+                            e
+                        })
+                        .ok()
+                }),
+            desired_replicas: row.desired_replicas.and_then(|n| u32::try_from(n).ok()),
+            image_digest: row.image_digest,
+            ports: row
+                .ports
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    serde_json::from_str(&s).unwrap_or_else(|e| {
+                        warn!("Failed to deserialize ports for deployment {}: {}", id, e);
+                        Vec::new()
+                    })
+                })
+                .unwrap_or_default(),
+            pending_events: vec![],
+            parent_id: row.parent_id,
+            network: row.network_mode.as_deref().and_then(|s| {
+                NetworkMode::from_str(s)
+                    .map(|mode| NetworkConfig { mode })
+                    .map_err(|e| {
+                        warn!(
+                            "Failed to parse network_mode '{}' for deployment {}: {}",
+                            s, id, e
+                        );
+                        e
+                    })
+                    .ok()
+            }),
+            pending_restart: row
+                .pending_restart
+                .filter(|s| !s.is_empty())
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            paused: row.paused,
+            strategy: row
+                .strategy
+                .filter(|s| !s.is_empty())
+                .and_then(|s| {
+                    serde_json::from_str(&s)
+                        .map_err(|e| {
+                            warn!(
+                                "Failed to deserialize strategy for deployment {}: {} — using the default",
+                                id, e
+                            );
+                            e
+                        })
+                        .ok()
+                }),
+            rollout_limit: None,
+            restart_surge: false,
+            cron: row.cron.filter(|s| !s.is_empty()).and_then(|s| {
+                serde_json::from_str(&s)
+                    .map_err(|e| {
+                        warn!(
+                            "Failed to deserialize cron for deployment {}: {} — it will not be triggered",
+                            id, e
+                        );
+                        e
+                    })
+                    .ok()
+            }),
+            cronjob_id: row.cronjob_id,
+            last_scheduled_at: row.last_scheduled_at,
+            job: row.job.filter(|s| !s.is_empty()).and_then(|s| {
+                serde_json::from_str(&s)
+                    .map_err(|e| {
+                        warn!(
+                            "Failed to deserialize job for deployment {}: {} — using the defaults",
+                            id, e
+                        );
+                        e
+                    })
+                    .ok()
             }),
-            environment: parse_environment(&row.environment, &id),
-            volumes: row.volumes,
-            health_checks: row
-                .health_checks
+            exit_code: row.exit_code,
+            depends_on: row
+                .depends_on
                 .filter(|s| !s.is_empty())
                 .map(|s| {
                     serde_json::from_str(&s).unwrap_or_else(|e| {
                         warn!(
-                            "Failed to deserialize health_checks for deployment {}: {}",
+                            "Failed to deserialize depends_on for deployment {}: {}",
                             id, e
                         );
                         Vec::new()
                     })
                 })
                 .unwrap_or_default(),
-            resources: row
-                .resources
-                .filter(|s| !s.is_empty())
-                .and_then(|s| serde_json::from_str(&s).ok()),
-            autoscale: row
-                .autoscale
+            init_containers: row
+                .init_containers
                 .filter(|s| !s.is_empty())
-                .and_then(|s| {
-                    serde_json::from_str(&s)
-                        .map_err(|e| {
-                            warn!(
-                                "Failed to deserialize autoscale for deployment {}: {} — treating it as not autoscaled",
-                                id, e
-                            );
-                            e
-                        })
-                        .ok()
-                }),
-            desired_replicas: row.desired_replicas.and_then(|n| u32::try_from(n).ok()),
-            image_digest: row.image_digest,
-            ports: row
-                .ports
+                .map(|s| {
+                    serde_json::from_str(&s).unwrap_or_else(|e| {
+                        warn!(
+                            "Failed to deserialize init_containers for deployment {}: {}",
+                            id, e
+                        );
+                        Vec::new()
+                    })
+                })
+                .unwrap_or_default(),
+            sidecars: row
+                .sidecars
                 .filter(|s| !s.is_empty())
                 .map(|s| {
                     serde_json::from_str(&s).unwrap_or_else(|e| {
-                        warn!("Failed to deserialize ports for deployment {}: {}", id, e);
+                        warn!(
+                            "Failed to deserialize sidecars for deployment {}: {}",
+                            id, e
+                        );
                         Vec::new()
                     })
                 })
                 .unwrap_or_default(),
-            pending_events: vec![],
-            parent_id: row.parent_id,
-            network: row.network_mode.as_deref().and_then(|s| {
-                NetworkMode::from_str(s)
-                    .map(|mode| NetworkConfig { mode })
+            lifecycle: row.lifecycle.filter(|s| !s.is_empty()).and_then(|s| {
+                serde_json::from_str(&s)
                     .map_err(|e| {
                         warn!(
-                            "Failed to parse network_mode '{}' for deployment {}: {}",
-                            s, id, e
+                            "Failed to deserialize lifecycle for deployment {}: {} — using the defaults",
+                            id, e
+                        );
+                        e
+                    })
+                    .ok()
+            }),
+            restart_policy: row.restart_policy.filter(|s| !s.is_empty()).and_then(|s| {
+                serde_json::from_str(&s)
+                    .map_err(|e| {
+                        warn!(
+                            "Failed to deserialize restart_policy for deployment {}: {} — using the defaults",
+                            id, e
+                        );
+                        e
+                    })
+                    .ok()
+            }),
+            idle: row.idle.filter(|s| !s.is_empty()).and_then(|s| {
+                serde_json::from_str(&s)
+                    .map_err(|e| {
+                        warn!(
+                            "Failed to deserialize idle for deployment {}: {} — keeping it always on",
+                            id, e
+                        );
+                        e
+                    })
+                    .ok()
+            }),
+            logging: row.logging.filter(|s| !s.is_empty()).and_then(|s| {
+                serde_json::from_str(&s)
+                    .map_err(|e| {
+                        warn!(
+                            "Failed to deserialize logging for deployment {}: {} — detecting JSON lines",
+                            id, e
                         );
                         e
                     })
                     .ok()
             }),
+            defaulted: row
+                .defaulted
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    serde_json::from_str(&s).unwrap_or_else(|e| {
+                        warn!(
+                            "Failed to deserialize defaulted for deployment {}: {}",
+                            id, e
+                        );
+                        Vec::new()
+                    })
+                })
+                .unwrap_or_default(),
         }
     }
 }
@@ -602,7 +1800,8 @@ impl From<DeploymentRow> for Deployment {
 const SELECT_COLUMNS: &str = "
     id, created_at, updated_at, status, restart_count,
     namespace, name, image, command, config, runtime, kind,
-    replicas, labels, environment, volumes, health_checks, resources, autoscale, desired_replicas, image_digest, parent_id, ports, network_mode
+    replicas, labels, environment, volumes, health_checks, resources, autoscale, desired_replicas, image_digest, parent_id, ports, network_mode,
+    pending_restart, paused, strategy, cron, cronjob_id, last_scheduled_at, job, exit_code, depends_on, init_containers, sidecars, lifecycle, restart_policy, idle, logging, defaulted
 ";
 
 const ALLOWED_FILTER_COLUMNS: &[&str] = &["namespace", "status", "kind"];
@@ -684,12 +1883,50 @@ pub(crate) async fn create(
         .network
         .as_ref()
         .map(|n| n.mode.as_str().to_string());
+    let strategy_json = deployment
+        .strategy
+        .as_ref()
+        .map(|s| serde_json::to_string(s).unwrap_or_else(|_| "null".to_string()));
+    let cron_json = deployment
+        .cron
+        .as_ref()
+        .map(|c| serde_json::to_string(c).unwrap_or_else(|_| "null".to_string()));
+    let job_json = deployment
+        .job
+        .as_ref()
+        .map(|j| serde_json::to_string(j).unwrap_or_else(|_| "null".to_string()));
+    let depends_on_json = (!deployment.depends_on.is_empty())
+        .then(|| serde_json::to_string(&deployment.depends_on).unwrap_or_default());
+    let init_containers_json = (!deployment.init_containers.is_empty())
+        .then(|| serde_json::to_string(&deployment.init_containers).unwrap_or_default());
+    let sidecars_json = (!deployment.sidecars.is_empty())
+        .then(|| serde_json::to_string(&deployment.sidecars).unwrap_or_default());
+    let lifecycle_json = deployment
+        .lifecycle
+        .as_ref()
+        .map(|l| serde_json::to_string(l).unwrap_or_else(|_| "null".to_string()));
+    let restart_policy_json = deployment
+        .restart_policy
+        .as_ref()
+        .map(|p| serde_json::to_string(p).unwrap_or_else(|_| "null".to_string()));
+    let idle_json = deployment
+        .idle
+        .as_ref()
+        .map(|i| serde_json::to_string(i).unwrap_or_else(|_| "null".to_string()));
+    let logging_json = deployment
+        .logging
+        .as_ref()
+        .map(|l| serde_json::to_string(l).unwrap_or_else(|_| "null".to_string()));
+    let defaulted_json = (!deployment.defaulted.is_empty())
+        .then(|| serde_json::to_string(&deployment.defaulted).unwrap_or_default());
+    let pending_restart_json = (!deployment.pending_restart.is_empty())
+        .then(|| serde_json::to_string(&deployment.pending_restart).unwrap_or_default());
 
     sqlx::query(
         "INSERT INTO deployment (
             id, created_at, status, restart_count, namespace, name, image,
-            command, config, runtime, kind, replicas, labels, environment, volumes, health_checks, resources, autoscale, desired_replicas, image_digest, parent_id, ports, network_mode
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            command, config, runtime, kind, replicas, labels, environment, volumes, health_checks, resources, autoscale, desired_replicas, image_digest, parent_id, ports, network_mode, pending_restart, paused, strategy, cron, cronjob_id, last_scheduled_at, job, exit_code, depends_on, init_containers, sidecars, lifecycle, restart_policy, idle, logging, defaulted
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&deployment.id)
     .bind(&deployment.created_at)
@@ -714,6 +1951,22 @@ pub(crate) async fn create(
     .bind(&deployment.parent_id)
     .bind(&ports_json)
     .bind(&network_mode)
+    .bind(&pending_restart_json)
+    .bind(deployment.paused)
+    .bind(&strategy_json)
+    .bind(&cron_json)
+    .bind(&deployment.cronjob_id)
+    .bind(&deployment.last_scheduled_at)
+    .bind(&job_json)
+    .bind(deployment.exit_code)
+    .bind(&depends_on_json)
+    .bind(&init_containers_json)
+    .bind(&sidecars_json)
+    .bind(&lifecycle_json)
+    .bind(&restart_policy_json)
+    .bind(&idle_json)
+    .bind(&logging_json)
+    .bind(&defaulted_json)
     .execute(pool)
     .await?;
 
@@ -802,6 +2055,174 @@ pub(crate) async fn set_desired_replicas(
     Ok(affected > 0)
 }
 
+/// Wake an `idle` deployment that was scaled to zero: clear its
+/// `desired_replicas` so it runs its `replicas` again. `false` when it was not
+/// asleep, e.g. because a concurrent connection already woke it.
+pub(crate) async fn wake(pool: &SqlitePool, id: &str) -> Result<bool, sqlx::Error> {
+    let affected = sqlx::query(
+        "UPDATE deployment SET desired_replicas = NULL, updated_at = datetime('now') WHERE id = ? AND desired_replicas = 0",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(affected > 0)
+}
+
+/// Change the declared instance count in place (`POST /deployments/{id}/scale`).
+///
+/// Unlike a re-apply this creates no new deployment row, so no rolling update
+/// is triggered: the runtimes simply converge on the new count on their next
+/// tick. Returns `false` when no row matched.
+pub(crate) async fn set_replicas(
+    pool: &SqlitePool,
+    id: &str,
+    replicas: u32,
+) -> Result<bool, sqlx::Error> {
+    let affected = sqlx::query(
+        "UPDATE deployment SET replicas = ?, updated_at = datetime('now') WHERE id = ?",
+    )
+    .bind(replicas as i32)
+    .bind(id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(affected > 0)
+}
+
+/// Persist the instances a rolling restart still has to replace
+/// (`POST /deployments/{id}/restart`, then the scheduler as it progresses).
+///
+/// Targeted like [`set_replicas`]: the scheduler's full-row [`update`] does not
+/// carry this column. An empty list clears it. Returns `false` when no row
+/// matched.
+pub(crate) async fn set_pending_restart(
+    pool: &SqlitePool,
+    id: &str,
+    pending: &[String],
+) -> Result<bool, sqlx::Error> {
+    let json = (!pending.is_empty()).then(|| serde_json::to_string(pending).unwrap_or_default());
+
+    let affected = sqlx::query(
+        "UPDATE deployment SET pending_restart = ?, updated_at = datetime('now') WHERE id = ?",
+    )
+    .bind(json)
+    .bind(id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(affected > 0)
+}
+
+/// Suspend or resume reconciliation (`POST /deployments/{id}/pause|resume`).
+///
+/// Targeted like [`set_replicas`], so the scheduler's full-row [`update`] of a
+/// copy read before the call can never undo it. Returns `false` when no row
+/// matched.
+pub(crate) async fn set_paused(
+    pool: &SqlitePool,
+    id: &str,
+    paused: bool,
+) -> Result<bool, sqlx::Error> {
+    let affected =
+        sqlx::query("UPDATE deployment SET paused = ?, updated_at = datetime('now') WHERE id = ?")
+            .bind(paused)
+            .bind(id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+
+    Ok(affected > 0)
+}
+
+/// Record the trigger a cronjob last acted on (RFC 3339). Targeted like
+/// [`set_paused`]. Returns `false` when no row matched.
+pub(crate) async fn set_last_scheduled_at(
+    pool: &SqlitePool,
+    id: &str,
+    scheduled_at: &str,
+) -> Result<bool, sqlx::Error> {
+    let affected = sqlx::query(
+        "UPDATE deployment SET last_scheduled_at = ?, updated_at = datetime('now') WHERE id = ?",
+    )
+    .bind(scheduled_at)
+    .bind(id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(affected > 0)
+}
+
+/// The runs a cronjob spawned and that are not deleted yet, most recent first.
+pub(crate) async fn find_runs(
+    pool: &SqlitePool,
+    cronjob_id: &str,
+) -> Result<Vec<Deployment>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {} FROM deployment WHERE cronjob_id = ? AND status <> 'deleted' ORDER BY created_at DESC, id DESC",
+        SELECT_COLUMNS
+    );
+
+    let rows = sqlx::query_as::<_, DeploymentRow>(&sql)
+        .bind(cronjob_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(Deployment::from).collect())
+}
+
+/// Hand the runs of a replaced cronjob over to the cronjob replacing it, so a
+/// re-apply keeps the run history and its concurrency policy still sees a run
+/// in progress.
+pub(crate) async fn adopt_runs(
+    pool: &SqlitePool,
+    from_cronjob_id: &str,
+    to_cronjob_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE deployment SET cronjob_id = ? WHERE cronjob_id = ?")
+        .bind(to_cronjob_id)
+        .bind(from_cronjob_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Persist the exit code of a job's last finished attempt.
+pub(crate) async fn set_exit_code(
+    pool: &SqlitePool,
+    id: &str,
+    exit_code: Option<i64>,
+) -> Result<bool, sqlx::Error> {
+    let affected = sqlx::query("UPDATE deployment SET exit_code = ? WHERE id = ?")
+        .bind(exit_code)
+        .bind(id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    Ok(affected > 0)
+}
+
+/// Finished jobs that asked to be deleted some time after they finished
+/// (`job.ttl_after_finished`).
+pub(crate) async fn find_expirable_jobs(pool: &SqlitePool) -> Result<Vec<Deployment>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {} FROM deployment WHERE kind = 'job' AND status IN ('completed', 'failed') AND json_extract(job, '$.ttl_after_finished') IS NOT NULL",
+        SELECT_COLUMNS
+    );
+
+    let rows = sqlx::query_as::<_, DeploymentRow>(&sql)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(Deployment::from).collect())
+}
+
 pub(crate) async fn find_referencing_secret(
     pool: &SqlitePool,
     namespace: &str,
@@ -919,10 +2340,101 @@ mod tests {
         Autoscale {
             min: 2,
             max: 8,
-            target_cpu: 70.0,
+            target_cpu: Some(70.0),
+            ..Default::default()
+        }
+    }
+
+    fn nightly() -> Cron {
+        serde_json::from_value(serde_json::json!({"schedule": "0 3 * * *"})).unwrap()
+    }
+
+    fn utc(s: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    }
+
+    #[test]
+    fn lifecycle_signals_and_grace_period() {
+        let lifecycle = |signal: &str| Lifecycle {
+            stop_signal: Some(signal.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(lifecycle("SIGQUIT").signal_number(), 3);
+        assert_eq!(lifecycle("quit").signal_number(), 3);
+        assert_eq!(lifecycle("10").signal_number(), 10);
+        assert!(lifecycle("SIGNOPE").validate().is_err());
+        assert!(lifecycle("65").validate().is_err());
+
+        let defaults = Lifecycle::default();
+        assert_eq!(defaults.signal_number(), 15);
+        assert_eq!(defaults.grace_period(), DEFAULT_STOP_GRACE_PERIOD);
+        assert!(defaults.validate().is_ok());
+
+        let hooks: Lifecycle = serde_json::from_str(
+            r#"{"stop_grace_period": "90s", "pre_stop": {"http": {"port": 8080}},
+                "post_start": {"command": ["./warm-cache"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(hooks.grace_period(), std::time::Duration::from_secs(90));
+        assert_eq!(
+            hooks.pre_stop,
+            Some(LifecycleHook::Http(HttpHook {
+                port: 8080,
+                path: "/".to_string()
+            }))
+        );
+        assert!(hooks.validate().is_ok());
+    }
+
+    #[test]
+    fn cron_defaults_and_validation() {
+        let cron = nightly();
+        assert_eq!(cron.concurrency_policy, ConcurrencyPolicy::Forbid);
+        assert_eq!(cron.history_limit, 3);
+        assert!(cron.validate().is_ok());
+
+        for bad in ["", "every night", "61 * * * *", "* * * *"] {
+            let cron = Cron {
+                schedule: bad.to_string(),
+                ..nightly()
+            };
+            assert!(cron.validate().is_err(), "'{bad}' must be rejected");
         }
     }
 
+    #[test]
+    fn cron_finds_the_next_and_the_latest_due_trigger() {
+        let cron = nightly();
+        assert_eq!(
+            cron.next_after(&utc("2026-10-18T03:00:00Z")),
+            Some(utc("2026-10-19T03:00:00Z"))
+        );
+        assert_eq!(
+            cron.due(&utc("2026-10-18T02:00:00Z"), &utc("2026-10-18T02:59:59Z")),
+            None
+        );
+        // Several missed triggers: only the latest is due.
+        assert_eq!(
+            cron.due(&utc("2026-10-18T02:00:00Z"), &utc("2026-10-20T12:00:00Z")),
+            Some(utc("2026-10-20T03:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn the_schedule_resumes_from_the_last_trigger_or_the_creation() {
+        let mut d = worker("d1", 0);
+        d.created_at = "2026-10-18 02:30:00.123456789 UTC".to_string();
+        assert_eq!(
+            d.schedule_anchor(),
+            Some(utc("2026-10-18T02:30:00.123456789Z"))
+        );
+
+        d.last_scheduled_at = Some("2026-10-19T03:00:00+00:00".to_string());
+        assert_eq!(d.schedule_anchor(), Some(utc("2026-10-19T03:00:00Z")));
+    }
+
     #[test]
     fn a_deployment_without_a_policy_is_never_autoscaled() {
         // The opt-in guarantee, at the one place every runtime reads. A
@@ -969,7 +2481,8 @@ mod tests {
         d.autoscale = Some(Autoscale {
             min: 1,
             max: 4,
-            target_cpu: 70.0,
+            target_cpu: Some(70.0),
+            ..Default::default()
         });
         d.desired_replicas = Some(9);
 
@@ -1003,7 +2516,8 @@ mod tests {
         child.autoscale = Some(Autoscale {
             min: 1,
             max: 4,
-            target_cpu: 70.0,
+            target_cpu: Some(70.0),
+            ..Default::default()
         });
         child.desired_replicas = Some(8); // inherited from the parent
 
@@ -1013,12 +2527,13 @@ mod tests {
     #[test]
     fn autoscale_policies_reject_impossible_ranges() {
         assert!(autoscale_policy().validate().is_ok());
-        // Scaling to zero needs a wake-up path Ring has no way to trigger.
+        // Scaling to zero is the `idle` block's job, not the autoscaler's.
         assert!(
             Autoscale {
                 min: 0,
                 max: 5,
-                target_cpu: 70.0
+                target_cpu: Some(70.0),
+                ..Default::default()
             }
             .validate()
             .is_err()
@@ -1027,7 +2542,8 @@ mod tests {
             Autoscale {
                 min: 5,
                 max: 2,
-                target_cpu: 70.0
+                target_cpu: Some(70.0),
+                ..Default::default()
             }
             .validate()
             .is_err()
@@ -1037,7 +2553,8 @@ mod tests {
                 Autoscale {
                     min: 1,
                     max: 5,
-                    target_cpu: bad
+                    target_cpu: Some(bad),
+                    ..Default::default()
                 }
                 .validate()
                 .is_err(),
@@ -1046,6 +2563,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn autoscale_policies_check_memory_metrics_and_tuning() {
+        let policy = |json: serde_json::Value| -> Autoscale {
+            let mut value = serde_json::json!({"min": 1, "max": 5});
+            value
+                .as_object_mut()
+                .unwrap()
+                .extend(json.as_object().unwrap().clone());
+            serde_json::from_value(value).unwrap()
+        };
+
+        let backlog = policy(serde_json::json!({
+            "metrics": [{"name": "queue_backlog", "port": 9100, "target": 50}],
+            "cooldown_down": "600s",
+            "tolerance": 0.2
+        }));
+        assert!(backlog.validate().is_ok());
+        assert_eq!(backlog.metrics[0].path, "/metrics");
+        assert_eq!(backlog.metrics[0].scope, MetricScope::Deployment);
+        assert!(
+            policy(serde_json::json!({"target_memory": 80.0}))
+                .validate()
+                .is_ok()
+        );
+
+        for bad in [
+            serde_json::json!({}),
+            serde_json::json!({"target_memory": 0.0}),
+            serde_json::json!({"metrics": [{"name": "queue backlog", "port": 9100, "target": 5}]}),
+            serde_json::json!({"metrics": [{"name": "backlog", "port": 0, "target": 5}]}),
+            serde_json::json!({"metrics": [{"name": "backlog", "port": 9100, "target": 0}]}),
+            serde_json::json!({"target_cpu": 70.0, "cooldown_up": "1m"}),
+            serde_json::json!({"target_cpu": 70.0, "tolerance": 1.5}),
+        ] {
+            assert!(policy(bad.clone()).validate().is_err(), "{bad}");
+        }
+    }
+
     async fn test_pool() -> SqlitePool {
         let pool = SqlitePoolOptions::new()
             .max_connections(1)
@@ -1084,6 +2639,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
@@ -1112,6 +2685,99 @@ mod tests {
         assert_eq!(count_in_db(&pool, "d1").await, 3);
     }
 
+    #[tokio::test]
+    async fn restart_policy_sets_the_failure_budget() {
+        let pool = test_pool().await;
+        let mut d = worker("d1", MAX_RESTART_COUNT);
+        assert_eq!(d.failure_budget(), Some(MAX_RESTART_COUNT));
+        assert!(d.restarts_exhausted());
+
+        d.restart_policy = Some(RestartPolicy {
+            max_restarts: Some(10),
+            backoff: Some(RestartBackoff {
+                initial: Some("5s".to_string()),
+                max: Some("600s".to_string()),
+            }),
+            ..Default::default()
+        });
+        assert_eq!(d.failure_budget(), Some(11));
+        assert!(!d.restarts_exhausted());
+        assert_eq!(
+            d.restart_backoff(),
+            (
+                std::time::Duration::from_secs(5),
+                std::time::Duration::from_secs(600)
+            )
+        );
+        create(&pool, &d).await.unwrap();
+        let stored = find(&pool, "d1").await.unwrap().unwrap();
+        assert_eq!(stored.restart_policy, d.restart_policy);
+
+        // Without a limit, a terminal error counts as one more failure
+        // instead of ending the retries.
+        d.restart_policy = Some(RestartPolicy::default());
+        d.exhaust_restarts();
+        assert_eq!(d.restart_count, MAX_RESTART_COUNT + 1);
+        assert!(!d.restarts_exhausted());
+
+        d.restart_policy = Some(RestartPolicy {
+            mode: RestartMode::Never,
+            ..Default::default()
+        });
+        assert_eq!(d.failure_budget(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn an_idle_deployment_sleeps_at_zero_until_woken() {
+        let pool = test_pool().await;
+        let mut d = worker("d1", 0);
+        d.ports = vec![DeploymentPort {
+            published: 8080,
+            target: 80,
+            host_ip: None,
+            protocol: PortProtocol::Tcp,
+        }];
+        d.idle = Some(Idle {
+            after: "900s".to_string(),
+            wake_timeout: None,
+        });
+        assert!(d.idle.as_ref().unwrap().validate().is_ok());
+        assert_eq!(
+            d.idle.as_ref().unwrap().wake_timeout(),
+            DEFAULT_IDLE_WAKE_TIMEOUT
+        );
+        assert!(d.runtime_bound_ports().is_empty());
+        assert_eq!(d.target_replicas(), 1);
+
+        create(&pool, &d).await.unwrap();
+        assert!(set_desired_replicas(&pool, "d1", 0).await.unwrap());
+        let stored = find(&pool, "d1").await.unwrap().unwrap();
+        assert_eq!(stored.idle, d.idle);
+        assert!(stored.asleep());
+        assert_eq!(stored.target_replicas(), 0);
+
+        // Only the first of two concurrent wakes changes anything.
+        assert!(wake(&pool, "d1").await.unwrap());
+        assert!(!wake(&pool, "d1").await.unwrap());
+        let stored = find(&pool, "d1").await.unwrap().unwrap();
+        assert!(!stored.asleep());
+        assert_eq!(stored.target_replicas(), 1);
+
+        // Without the block, a zero on the row is not a sleep.
+        d.idle = None;
+        d.desired_replicas = Some(0);
+        assert!(!d.asleep());
+        assert_eq!(d.runtime_bound_ports().len(), 1);
+
+        for (after, wake_timeout) in [("15m", None), ("0s", None), ("900s", Some("0s"))] {
+            let idle = Idle {
+                after: after.to_string(),
+                wake_timeout: wake_timeout.map(str::to_string),
+            };
+            assert!(idle.validate().is_err(), "{:?}", idle);
+        }
+    }
+
     #[tokio::test]
     async fn reset_zeroes_the_counter() {
         let pool = test_pool().await;
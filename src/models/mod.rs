@@ -1,6 +1,7 @@
 pub(crate) mod audit_log;
 pub(crate) mod config;
 pub(crate) mod deployment_event;
+pub(crate) mod deployment_revision;
 pub(crate) mod deployments;
 pub(crate) mod event_queue;
 pub(crate) mod health_check;
@@ -1,53 +1,650 @@
+use crate::models::deployments::{
+    self, Deployment, DeploymentStatus, Resource, ResourceSpec, parse_cpu_string,
+    parse_memory_string,
+};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct Namespace {
     pub(crate) id: String,
     pub(crate) created_at: String,
     pub(crate) updated_at: Option<String>,
     pub(crate) name: String,
+    /// Caps on what the namespace may hold; `None` leaves it unbounded.
+    pub(crate) quota: Option<Quota>,
+    /// Merged into every deployment applied to the namespace.
+    pub(crate) defaults: Option<Defaults>,
 }
 
+/// What a deployment of the namespace gets when its manifest leaves a field
+/// out, and the per-instance `resources` it may ask for.
+///
+/// Defaults fill in, never override: a label the manifest sets keeps its
+/// value, and a deployment carrying its own registry credentials gets no
+/// `image_pull_secret`. `min` and `max` bound the per-instance `limits` and
+/// `requests` once the defaults are in; a `max` also requires the limit to be
+/// declared, or the deployment could take the whole node.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub(crate) struct Defaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) runtime: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) labels: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) image_pull_secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) resources: Option<Resource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) min: Option<ResourceSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max: Option<ResourceSpec>,
+}
+
+/// A per-instance amount outside the namespace's `min`/`max`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OutOfBounds {
+    /// Manifest path of the amount, e.g. `resources.limits.cpu`.
+    pub(crate) path: String,
+    pub(crate) message: String,
+    /// `min`, `max` or `undeclared`.
+    pub(crate) kind: &'static str,
+}
+
+impl Defaults {
+    /// Every amount must parse, `min` must not exceed `max`, and the default
+    /// `resources` must sit within them.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let parse = |key: &str, spec: &Option<ResourceSpec>| -> Result<Amounts, String> {
+            spec.as_ref()
+                .map(Amounts::parse)
+                .transpose()
+                .map(Option::unwrap_or_default)
+                .map_err(|e| format!("defaults.{}: {}", key, e))
+        };
+        if let Some(resources) = &self.resources {
+            parse("resources.limits", &resources.limits)?;
+            parse("resources.requests", &resources.requests)?;
+        }
+        let min = parse("min", &self.min)?;
+        let max = parse("max", &self.max)?;
+        let capped = |spec: &Option<ResourceSpec>, cpu: bool| {
+            spec.as_ref().is_some_and(|s| {
+                if cpu {
+                    s.cpu.is_some()
+                } else {
+                    s.memory.is_some()
+                }
+            })
+        };
+        if capped(&self.min, true) && capped(&self.max, true) && min.cpu > max.cpu {
+            return Err("defaults: min.cpu is above max.cpu".to_string());
+        }
+        if capped(&self.min, false) && capped(&self.max, false) && min.memory > max.memory {
+            return Err("defaults: min.memory is above max.memory".to_string());
+        }
+        if let Some(resources) = &self.resources
+            && let Some(out) = self
+                .check_bounds(Some(resources))
+                .into_iter()
+                .find(|out| out.kind != "undeclared")
+        {
+            return Err(format!("defaults.{}: {}", out.path, out.message));
+        }
+        Ok(())
+    }
+
+    /// The per-instance amounts of `resources` outside `min`/`max`.
+    pub(crate) fn check_bounds(&self, resources: Option<&Resource>) -> Vec<OutOfBounds> {
+        let mut found = Vec::new();
+        for (section, spec) in [
+            ("limits", resources.and_then(|r| r.limits.as_ref())),
+            ("requests", resources.and_then(|r| r.requests.as_ref())),
+        ] {
+            for (key, format) in [
+                ("cpu", format_cpu as fn(i64) -> String),
+                ("memory", format_memory),
+            ] {
+                let pick = |spec: Option<&ResourceSpec>| -> Option<i64> {
+                    let spec = spec?;
+                    if key == "cpu" {
+                        spec.cpu.as_deref().and_then(|c| parse_cpu_string(c).ok())
+                    } else {
+                        spec.memory
+                            .as_deref()
+                            .and_then(|m| parse_memory_string(m).ok())
+                    }
+                };
+                let path = format!("resources.{}.{}", section, key);
+                let value = pick(spec);
+                let max = pick(self.max.as_ref());
+                let min = pick(self.min.as_ref());
+                match (value, max) {
+                    (Some(value), Some(max)) if value > max => found.push(OutOfBounds {
+                        path: path.clone(),
+                        message: format!(
+                            "{} per instance is above the namespace maximum of {}",
+                            format(value),
+                            format(max)
+                        ),
+                        kind: "max",
+                    }),
+                    (None, Some(max)) if section == "limits" => found.push(OutOfBounds {
+                        path: path.clone(),
+                        message: format!(
+                            "the namespace allows at most {} per instance, so the limit must be declared",
+                            format(max)
+                        ),
+                        kind: "undeclared",
+                    }),
+                    _ => {}
+                }
+                if let (Some(value), Some(min)) = (value, min)
+                    && value < min
+                {
+                    found.push(OutOfBounds {
+                        path,
+                        message: format!(
+                            "{} per instance is below the namespace minimum of {}",
+                            format(value),
+                            format(min)
+                        ),
+                        kind: "min",
+                    });
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Per-namespace caps, so teams sharing a node cannot starve each other.
+/// Every cap is optional and an absent one is unbounded.
+///
+/// `limits` and `requests` bound the sums over every instance of the
+/// namespace: a deployment weighs its per-instance `resources` times its
+/// replica count. A deployment that does not declare a bounded amount is
+/// refused, or it would slip past the cap; an undeclared request counts as
+/// its limit.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub(crate) struct Quota {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_deployments: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_replicas: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_volumes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_secrets: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) limits: Option<ResourceSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) requests: Option<ResourceSpec>,
+}
+
+/// CPU in nanocores and memory in bytes, the units `parse_cpu_string` and
+/// `parse_memory_string` return.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct Amounts {
+    pub(crate) cpu: i64,
+    pub(crate) memory: i64,
+}
+
+impl Amounts {
+    fn parse(spec: &ResourceSpec) -> Result<Self, String> {
+        Ok(Self {
+            cpu: spec
+                .cpu
+                .as_deref()
+                .map(parse_cpu_string)
+                .transpose()?
+                .unwrap_or(0),
+            memory: spec
+                .memory
+                .as_deref()
+                .map(parse_memory_string)
+                .transpose()?
+                .unwrap_or(0),
+        })
+    }
+
+    /// Back into the manifest notation, e.g. `1500m` and `768Mi`.
+    pub(crate) fn to_spec(self) -> ResourceSpec {
+        ResourceSpec {
+            cpu: Some(format_cpu(self.cpu)),
+            memory: Some(format_memory(self.memory)),
+        }
+    }
+}
+
+/// What a namespace holds, counted the way its quota is written.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Usage {
+    pub(crate) deployments: u32,
+    pub(crate) replicas: u32,
+    pub(crate) volumes: u32,
+    pub(crate) secrets: u32,
+    pub(crate) limits: Amounts,
+    pub(crate) requests: Amounts,
+}
+
+/// The weight of one deployment: its replica count, and the amounts of one
+/// instance that it declares.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Footprint {
+    pub(crate) replicas: u32,
+    limits: Declared,
+    requests: Declared,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Declared {
+    cpu: Option<i64>,
+    memory: Option<i64>,
+}
+
+impl Footprint {
+    /// `replicas` instances shaped by `resources`. Amounts that do not parse
+    /// count as undeclared; create-time validation refuses them anyway.
+    pub(crate) fn new(replicas: u32, resources: Option<&Resource>) -> Self {
+        let declared = |spec: Option<&ResourceSpec>| Declared {
+            cpu: spec
+                .and_then(|s| s.cpu.as_deref())
+                .and_then(|c| parse_cpu_string(c).ok()),
+            memory: spec
+                .and_then(|s| s.memory.as_deref())
+                .and_then(|m| parse_memory_string(m).ok()),
+        };
+        let limits = declared(resources.and_then(|r| r.limits.as_ref()));
+        let requests = declared(resources.and_then(|r| r.requests.as_ref()));
+        Self {
+            replicas,
+            limits,
+            requests: Declared {
+                cpu: requests.cpu.or(limits.cpu),
+                memory: requests.memory.or(limits.memory),
+            },
+        }
+    }
+
+    /// A stored deployment, at the replica count it settles on. One asleep
+    /// weighs what it runs once awake: a connection wakes it without asking
+    /// the quota. A cronjob reserves what one of its runs takes.
+    pub(crate) fn of(deployment: &Deployment) -> Self {
+        let replicas = if deployment.asleep() {
+            deployment.awake_replicas()
+        } else {
+            deployment.settled_replicas()
+        };
+        Self::new(replicas, deployment.resources.as_ref())
+    }
+
+    /// The same deployment at another replica count.
+    pub(crate) fn with_replicas(&self, replicas: u32) -> Self {
+        Self {
+            replicas,
+            ..self.clone()
+        }
+    }
+
+    fn total(&self, declared: Declared) -> Amounts {
+        let n = i64::from(self.replicas);
+        Amounts {
+            cpu: declared.cpu.unwrap_or(0).saturating_mul(n),
+            memory: declared.memory.unwrap_or(0).saturating_mul(n),
+        }
+    }
+}
+
+impl Usage {
+    /// Usage of `deployments` (the rows of one namespace), plus its volume
+    /// and secret counts.
+    ///
+    /// Only live deployments count. The runs of a cronjob are left out, the
+    /// cronjob reserving their share itself, and so is the parent of a
+    /// rolling update: both share a name, and the newest one is what the
+    /// namespace settles on.
+    pub(crate) fn of(deployments: &[Deployment], volumes: u32, secrets: u32) -> Self {
+        let mut newest: HashMap<&str, &Deployment> = HashMap::new();
+        for deployment in deployments.iter().filter(|d| {
+            d.cronjob_id.is_none()
+                && !matches!(
+                    d.status,
+                    DeploymentStatus::Deleted
+                        | DeploymentStatus::Completed
+                        | DeploymentStatus::Failed
+                )
+        }) {
+            let slot = newest.entry(deployment.name.as_str()).or_insert(deployment);
+            if deployment.created_at_utc() > slot.created_at_utc() {
+                *slot = deployment;
+            }
+        }
+
+        let mut usage = Self {
+            volumes,
+            secrets,
+            ..Self::default()
+        };
+        for deployment in newest.values() {
+            usage.add(&Footprint::of(deployment));
+        }
+        usage
+    }
+
+    pub(crate) fn add(&mut self, footprint: &Footprint) {
+        let limits = footprint.total(footprint.limits);
+        let requests = footprint.total(footprint.requests);
+        self.deployments += 1;
+        self.replicas = self.replicas.saturating_add(footprint.replicas);
+        self.limits.cpu = self.limits.cpu.saturating_add(limits.cpu);
+        self.limits.memory = self.limits.memory.saturating_add(limits.memory);
+        self.requests.cpu = self.requests.cpu.saturating_add(requests.cpu);
+        self.requests.memory = self.requests.memory.saturating_add(requests.memory);
+    }
+}
+
+/// One cap a change would break. `field` is the quota key (`replicas`,
+/// `limits.cpu`, …).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Breach {
+    pub(crate) field: &'static str,
+    pub(crate) message: String,
+}
+
+impl Quota {
+    /// Every amount must parse and no cap may be negative.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        for (key, spec) in [("limits", &self.limits), ("requests", &self.requests)] {
+            let Some(spec) = spec else { continue };
+            let amounts = Amounts::parse(spec).map_err(|e| format!("quota.{}: {}", key, e))?;
+            if amounts.cpu < 0 || amounts.memory < 0 {
+                return Err(format!("quota.{}: amounts must not be negative", key));
+            }
+        }
+        Ok(())
+    }
+
+    /// The caps `usage` would break once `incoming` joins it, in quota order.
+    /// `usage` must not already count `incoming`.
+    pub(crate) fn check(&self, usage: &Usage, incoming: &Footprint) -> Vec<Breach> {
+        let mut breaches = Vec::new();
+        if let Some(max) = self.max_deployments
+            && usage.deployments + 1 > max
+        {
+            breaches.push(Breach {
+                field: "deployments",
+                message: format!(
+                    "the namespace allows {} deployments and already holds {}",
+                    max, usage.deployments
+                ),
+            });
+        }
+        if let Some(max) = self.max_replicas
+            && usage.replicas.saturating_add(incoming.replicas) > max
+        {
+            breaches.push(Breach {
+                field: "replicas",
+                message: format!(
+                    "{} more replicas would exceed the namespace's {} ({} in use)",
+                    incoming.replicas, max, usage.replicas
+                ),
+            });
+        }
+        for (key, cap, used, declared) in [
+            ("limits", &self.limits, usage.limits, incoming.limits),
+            (
+                "requests",
+                &self.requests,
+                usage.requests,
+                incoming.requests,
+            ),
+        ] {
+            let Some(cap) = cap else { continue };
+            let Ok(max) = Amounts::parse(cap) else {
+                continue;
+            };
+            let want = incoming.total(declared);
+            if cap.cpu.is_some() {
+                let field = if key == "limits" {
+                    "limits.cpu"
+                } else {
+                    "requests.cpu"
+                };
+                if declared.cpu.is_none() && incoming.replicas > 0 {
+                    breaches.push(Breach {
+                        field,
+                        message: format!(
+                            "the namespace caps {}, so the deployment must declare resources.{}",
+                            field, field
+                        ),
+                    });
+                } else if used.cpu.saturating_add(want.cpu) > max.cpu {
+                    breaches.push(Breach {
+                        field,
+                        message: format!(
+                            "{} more would exceed the namespace's {} ({} in use)",
+                            format_cpu(want.cpu),
+                            format_cpu(max.cpu),
+                            format_cpu(used.cpu)
+                        ),
+                    });
+                }
+            }
+            if cap.memory.is_some() {
+                let field = if key == "limits" {
+                    "limits.memory"
+                } else {
+                    "requests.memory"
+                };
+                if declared.memory.is_none() && incoming.replicas > 0 {
+                    breaches.push(Breach {
+                        field,
+                        message: format!(
+                            "the namespace caps {}, so the deployment must declare resources.{}",
+                            field, field
+                        ),
+                    });
+                } else if used.memory.saturating_add(want.memory) > max.memory {
+                    breaches.push(Breach {
+                        field,
+                        message: format!(
+                            "{} more would exceed the namespace's {} ({} in use)",
+                            format_memory(want.memory),
+                            format_memory(max.memory),
+                            format_memory(used.memory)
+                        ),
+                    });
+                }
+            }
+        }
+        breaches
+    }
+
+    /// Whether one more volume fits next to `usage`.
+    pub(crate) fn check_volumes(&self, usage: &Usage, incoming: u32) -> Option<Breach> {
+        let max = self.max_volumes?;
+        (usage.volumes.saturating_add(incoming) > max).then(|| Breach {
+            field: "volumes",
+            message: format!(
+                "the namespace allows {} volumes and already holds {}",
+                max, usage.volumes
+            ),
+        })
+    }
+
+    /// Whether one more secret fits next to `usage`.
+    pub(crate) fn check_secrets(&self, usage: &Usage) -> Option<Breach> {
+        let max = self.max_secrets?;
+        (usage.secrets + 1 > max).then(|| Breach {
+            field: "secrets",
+            message: format!(
+                "the namespace allows {} secrets and already holds {}",
+                max, usage.secrets
+            ),
+        })
+    }
+}
+
+/// Nanocores as `2` or `1500m`.
+fn format_cpu(nanos: i64) -> String {
+    if nanos % 1_000_000_000 == 0 {
+        format!("{}", nanos / 1_000_000_000)
+    } else {
+        format!("{}m", nanos / 1_000_000)
+    }
+}
+
+/// Bytes in the largest binary unit that divides them, e.g. `768Mi`.
+fn format_memory(bytes: i64) -> String {
+    for (suffix, unit) in [("Gi", 1i64 << 30), ("Mi", 1 << 20), ("Ki", 1 << 10)] {
+        if bytes != 0 && bytes % unit == 0 {
+            return format!("{}{}", bytes / unit, suffix);
+        }
+    }
+    bytes.to_string()
+}
+
+#[derive(sqlx::FromRow)]
+struct NamespaceRow {
+    id: String,
+    created_at: String,
+    updated_at: Option<String>,
+    name: String,
+    quota: Option<String>,
+    defaults: Option<String>,
+}
+
+impl From<NamespaceRow> for Namespace {
+    fn from(row: NamespaceRow) -> Self {
+        let quota = row.quota.filter(|json| !json.is_empty()).and_then(|json| {
+            match serde_json::from_str(&json) {
+                Ok(quota) => Some(quota),
+                Err(e) => {
+                    warn!(
+                        "Failed to deserialize quota for namespace {}: {}",
+                        row.name, e
+                    );
+                    None
+                }
+            }
+        });
+        let defaults = row
+            .defaults
+            .filter(|json| !json.is_empty())
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(defaults) => Some(defaults),
+                Err(e) => {
+                    warn!(
+                        "Failed to deserialize defaults for namespace {}: {}",
+                        row.name, e
+                    );
+                    None
+                }
+            });
+        Namespace {
+            id: row.id,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            name: row.name,
+            quota,
+            defaults,
+        }
+    }
+}
+
+const COLUMNS: &str = "id, created_at, updated_at, name, quota, defaults";
+
 pub(crate) async fn find(pool: &SqlitePool, id: &str) -> Result<Option<Namespace>, sqlx::Error> {
-    sqlx::query_as::<_, Namespace>(
-        "SELECT id, created_at, updated_at, name FROM namespace WHERE id = ?",
-    )
-    .bind(id)
-    .fetch_optional(pool)
-    .await
+    let row =
+        sqlx::query_as::<_, NamespaceRow>(&format!("SELECT {COLUMNS} FROM namespace WHERE id = ?"))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    Ok(row.map(Namespace::from))
 }
 
 pub(crate) async fn find_by_name(
     pool: &SqlitePool,
     name: &str,
 ) -> Result<Option<Namespace>, sqlx::Error> {
-    sqlx::query_as::<_, Namespace>(
-        "SELECT id, created_at, updated_at, name FROM namespace WHERE name = ?",
-    )
+    let row = sqlx::query_as::<_, NamespaceRow>(&format!(
+        "SELECT {COLUMNS} FROM namespace WHERE name = ?"
+    ))
     .bind(name)
     .fetch_optional(pool)
-    .await
+    .await?;
+    Ok(row.map(Namespace::from))
 }
 
 pub(crate) async fn find_all(pool: &SqlitePool) -> Result<Vec<Namespace>, sqlx::Error> {
-    sqlx::query_as::<_, Namespace>(
-        "SELECT id, created_at, updated_at, name FROM namespace ORDER BY name",
-    )
+    let rows = sqlx::query_as::<_, NamespaceRow>(&format!(
+        "SELECT {COLUMNS} FROM namespace ORDER BY name"
+    ))
     .fetch_all(pool)
-    .await
+    .await?;
+    Ok(rows.into_iter().map(Namespace::from).collect())
 }
 
 pub(crate) async fn create(pool: &SqlitePool, namespace: Namespace) -> Result<(), sqlx::Error> {
-    sqlx::query("INSERT INTO namespace (id, created_at, updated_at, name) VALUES (?, ?, ?, ?)")
-        .bind(&namespace.id)
-        .bind(&namespace.created_at)
-        .bind(&namespace.updated_at)
-        .bind(&namespace.name)
+    let quota = namespace
+        .quota
+        .as_ref()
+        .and_then(|q| serde_json::to_string(q).ok());
+    let defaults = namespace
+        .defaults
+        .as_ref()
+        .and_then(|d| serde_json::to_string(d).ok());
+    sqlx::query(
+        "INSERT INTO namespace (id, created_at, updated_at, name, quota, defaults) VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&namespace.id)
+    .bind(&namespace.created_at)
+    .bind(&namespace.updated_at)
+    .bind(&namespace.name)
+    .bind(quota)
+    .bind(defaults)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Replace the quota of the namespace `name`; `None` lifts it. Returns
+/// whether the namespace exists.
+pub(crate) async fn set_quota(
+    pool: &SqlitePool,
+    name: &str,
+    quota: Option<&Quota>,
+    updated_at: &str,
+) -> Result<bool, sqlx::Error> {
+    let quota = quota.and_then(|q| serde_json::to_string(q).ok());
+    let result = sqlx::query("UPDATE namespace SET quota = ?, updated_at = ? WHERE name = ?")
+        .bind(quota)
+        .bind(updated_at)
+        .bind(name)
         .execute(pool)
         .await?;
+    Ok(result.rows_affected() > 0)
+}
 
-    Ok(())
+/// Replace the defaults of the namespace `name`; `None` drops them. Returns
+/// whether the namespace exists.
+pub(crate) async fn set_defaults(
+    pool: &SqlitePool,
+    name: &str,
+    defaults: Option<&Defaults>,
+    updated_at: &str,
+) -> Result<bool, sqlx::Error> {
+    let defaults = defaults.and_then(|d| serde_json::to_string(d).ok());
+    let result = sqlx::query("UPDATE namespace SET defaults = ?, updated_at = ? WHERE name = ?")
+        .bind(defaults)
+        .bind(updated_at)
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
 }
 
 pub(crate) async fn delete_by_name(pool: &SqlitePool, name: &str) -> Result<u64, sqlx::Error> {
@@ -83,3 +680,145 @@ pub(crate) async fn count_resources(pool: &SqlitePool, name: &str) -> Result<i64
 
     Ok(deployments + secrets + configs)
 }
+
+/// What the namespace `name` holds, leaving out the deployment `excluding`
+/// (the one about to be replaced or resized, whose share the caller adds
+/// back at its new size).
+pub(crate) async fn usage(
+    pool: &SqlitePool,
+    name: &str,
+    excluding: Option<&str>,
+) -> Result<Usage, sqlx::Error> {
+    let filters = HashMap::from([("namespace".to_string(), vec![name.to_string()])]);
+    let mut list = deployments::find_all(pool, filters).await?;
+    if let Some(excluding) = excluding {
+        list.retain(|d| d.name != excluding);
+    }
+
+    let volumes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM volumes WHERE namespace = ?")
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+    let secrets: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM secret WHERE namespace = ?")
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Usage::of(&list, volumes as u32, secrets as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(cpu: Option<&str>, memory: Option<&str>) -> ResourceSpec {
+        ResourceSpec {
+            cpu: cpu.map(str::to_string),
+            memory: memory.map(str::to_string),
+        }
+    }
+
+    fn resources(limits: ResourceSpec, requests: Option<ResourceSpec>) -> Resource {
+        Resource {
+            limits: Some(limits),
+            requests,
+        }
+    }
+
+    #[test]
+    fn a_footprint_weighs_every_instance() {
+        let footprint = Footprint::new(
+            3,
+            Some(&resources(
+                spec(Some("500m"), Some("256Mi")),
+                Some(spec(Some("250m"), None)),
+            )),
+        );
+        let mut usage = Usage::default();
+        usage.add(&footprint);
+
+        assert_eq!(usage.deployments, 1);
+        assert_eq!(usage.replicas, 3);
+        assert_eq!(usage.limits.to_spec(), spec(Some("1500m"), Some("768Mi")));
+        // The memory request falls back to the limit.
+        assert_eq!(usage.requests.to_spec(), spec(Some("750m"), Some("768Mi")));
+    }
+
+    #[test]
+    fn check_reports_every_broken_cap() {
+        let quota = Quota {
+            max_deployments: Some(2),
+            max_replicas: Some(4),
+            limits: Some(spec(Some("2"), Some("1Gi"))),
+            ..Quota::default()
+        };
+        let mut usage = Usage::default();
+        usage.add(&Footprint::new(
+            2,
+            Some(&resources(spec(Some("500m"), Some("256Mi")), None)),
+        ));
+
+        let fits = Footprint::new(2, Some(&resources(spec(Some("500m"), Some("256Mi")), None)));
+        assert!(quota.check(&usage, &fits).is_empty());
+
+        let too_big = fits.with_replicas(3);
+        let fields: Vec<&str> = quota
+            .check(&usage, &too_big)
+            .iter()
+            .map(|b| b.field)
+            .collect();
+        assert_eq!(fields, vec!["replicas", "limits.cpu", "limits.memory"]);
+
+        usage.add(&fits);
+        let fields: Vec<&str> = quota
+            .check(&usage, &fits.with_replicas(0))
+            .iter()
+            .map(|b| b.field)
+            .collect();
+        assert_eq!(fields, vec!["deployments"]);
+    }
+
+    #[test]
+    fn a_capped_amount_must_be_declared() {
+        let quota = Quota {
+            requests: Some(spec(None, Some("1Gi"))),
+            ..Quota::default()
+        };
+        let undeclared = Footprint::new(1, None);
+        let breaches = quota.check(&Usage::default(), &undeclared);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].field, "requests.memory");
+        assert!(breaches[0].message.contains("must declare"));
+
+        // A memory limit stands in for the request; the cpu is not capped.
+        let declared = Footprint::new(1, Some(&resources(spec(None, Some("512Mi")), None)));
+        assert!(quota.check(&Usage::default(), &declared).is_empty());
+    }
+
+    #[test]
+    fn counts_are_capped_one_at_a_time() {
+        let quota = Quota {
+            max_volumes: Some(1),
+            max_secrets: Some(2),
+            ..Quota::default()
+        };
+        let usage = Usage {
+            volumes: 1,
+            secrets: 1,
+            ..Usage::default()
+        };
+        assert!(quota.check_volumes(&usage, 1).is_some());
+        assert!(quota.check_secrets(&usage).is_none());
+        assert!(Quota::default().check_secrets(&usage).is_none());
+    }
+
+    #[test]
+    fn validate_rejects_unreadable_amounts() {
+        let quota = Quota {
+            limits: Some(spec(Some("lots"), None)),
+            ..Quota::default()
+        };
+        assert!(quota.validate().unwrap_err().starts_with("quota.limits"));
+        assert!(Quota::default().validate().is_ok());
+    }
+}
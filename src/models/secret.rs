@@ -93,6 +93,8 @@ pub(crate) struct Secret {
     pub(crate) name: String,
     #[serde(skip_serializing)]
     pub(crate) value: Vec<u8>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) labels: HashMap<String, String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -103,10 +105,19 @@ struct SecretRow {
     namespace: String,
     name: String,
     value: Vec<u8>,
+    labels: Option<String>,
 }
 
 impl From<SecretRow> for Secret {
     fn from(row: SecretRow) -> Self {
+        let labels = row
+            .labels
+            .and_then(|labels| {
+                serde_json::from_str(&labels)
+                    .map_err(|e| warn!("Invalid labels on secret {}: {}", row.id, e))
+                    .ok()
+            })
+            .unwrap_or_default();
         Secret {
             id: row.id,
             created_at: row.created_at,
@@ -114,6 +125,7 @@ impl From<SecretRow> for Secret {
             namespace: row.namespace,
             name: row.name,
             value: row.value,
+            labels,
         }
     }
 }
@@ -131,7 +143,7 @@ pub(crate) async fn find_all(
     filters: HashMap<String, Vec<String>>,
 ) -> Result<Vec<Secret>, sqlx::Error> {
     let (query, values) = crate::models::query::build_filtered_query(
-        "SELECT id, created_at, updated_at, namespace, name, value FROM secret",
+        "SELECT id, created_at, updated_at, namespace, name, value, labels FROM secret",
         &filters,
         ALLOWED_FILTER_COLUMNS,
     );
@@ -147,7 +159,7 @@ pub(crate) async fn find_all(
 
 pub(crate) async fn find(pool: &SqlitePool, id: &str) -> Result<Option<Secret>, sqlx::Error> {
     let row = sqlx::query_as::<_, SecretRow>(
-        "SELECT id, created_at, updated_at, namespace, name, value FROM secret WHERE id = ?",
+        "SELECT id, created_at, updated_at, namespace, name, value, labels FROM secret WHERE id = ?",
     )
     .bind(id)
     .fetch_optional(pool)
@@ -162,7 +174,7 @@ pub(crate) async fn find_by_namespace_name(
     name: &str,
 ) -> Result<Option<Secret>, sqlx::Error> {
     let row = sqlx::query_as::<_, SecretRow>(
-        "SELECT id, created_at, updated_at, namespace, name, value FROM secret WHERE namespace = ? AND name = ?"
+        "SELECT id, created_at, updated_at, namespace, name, value, labels FROM secret WHERE namespace = ? AND name = ?"
     )
     .bind(namespace)
     .bind(name)
@@ -174,7 +186,7 @@ pub(crate) async fn find_by_namespace_name(
 
 pub(crate) async fn create(pool: &SqlitePool, secret: &Secret) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "INSERT INTO secret (id, created_at, updated_at, namespace, name, value) VALUES (?, ?, ?, ?, ?, ?)"
+        "INSERT INTO secret (id, created_at, updated_at, namespace, name, value, labels) VALUES (?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&secret.id)
     .bind(&secret.created_at)
@@ -182,6 +194,10 @@ pub(crate) async fn create(pool: &SqlitePool, secret: &Secret) -> Result<(), sql
     .bind(&secret.namespace)
     .bind(&secret.name)
     .bind(&secret.value)
+    .bind(
+        (!secret.labels.is_empty())
+            .then(|| serde_json::to_string(&secret.labels).unwrap_or_default()),
+    )
     .execute(pool)
     .await?;
 
@@ -64,6 +64,9 @@ impl Role {
             Role::Operator => &[
                 "deployments:read",
                 "deployments:write",
+                // A shell in an instance reads whatever the workload can, so
+                // it is its own scope rather than part of `deployments:write`.
+                "deployments:exec",
                 "secrets:read",
                 "secrets:write",
                 "configs:read",
@@ -418,6 +421,7 @@ mod tests {
 
         let operator = Role::Operator.scopes();
         assert!(operator.contains(&"deployments:write".to_string()));
+        assert!(operator.contains(&"deployments:exec".to_string()));
         // An operator administers its workloads' storage, so volumes are part
         // of the job: they were unreachable while /volumes had no mapped scope.
         assert!(operator.contains(&"volumes:write".to_string()));
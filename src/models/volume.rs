@@ -148,6 +148,7 @@ mod tests {
             namespace: "default".to_string(),
             name: name.to_string(),
             value: encrypt_value(plaintext),
+            labels: HashMap::new(),
         }
     }
 
@@ -223,6 +223,14 @@ impl CloudHypervisorClient {
         Ok(())
     }
 
+    /// Press the virtual power button: an ACPI shutdown request the guest
+    /// may honour by powering off cleanly.
+    pub async fn power_button(&self) -> Result<(), ClientError> {
+        self.request(Method::PUT, "/api/v1/vm.power-button", None)
+            .await?;
+        Ok(())
+    }
+
     /// Delete a VM instance.
     pub async fn delete_vm(&self) -> Result<(), ClientError> {
         self.request(Method::PUT, "/api/v1/vm.delete", None).await?;
@@ -6,11 +6,13 @@ use crate::config::config::get_config_dir;
 use crate::config::server::CloudHypervisorConfig;
 use crate::hypervisor::classifier::apply_vm_start_failure;
 use crate::hypervisor::error::RuntimeError;
+use crate::hypervisor::exec::{ExecRequest, ExecSession};
 use crate::hypervisor::host_net::{InstanceNet, cid_for_instance};
 use crate::hypervisor::lifecycle_trait::{Log, RuntimeLifecycle, classify_log, extract_date};
 use crate::hypervisor::port_forwarder::{self, PortForwarder};
+use crate::hypervisor::termination;
 use crate::hypervisor::virtiofs::{self, VirtiofsMount};
-use crate::models::deployments::{Deployment, DeploymentStatus, MAX_RESTART_COUNT};
+use crate::models::deployments::{Deployment, DeploymentStatus};
 use crate::models::health_check::HealthCheckStatus;
 use crate::models::volume::ResolvedMount;
 use crate::runtime::docker::tiny_id;
@@ -111,6 +113,12 @@ pub struct CloudHypervisorLifecycle {
     /// Removed on stop. Absence means the VM is gone (or was never tracked
     /// by this process — e.g. inherited across a ring-server restart).
     pids: Mutex<HashMap<String, InstanceProcessInfo>>,
+    /// ACPI shutdown grace period per deployment, keyed by its instance-id
+    /// prefix (see `deployment_prefix`). Refreshed from
+    /// `lifecycle.stop_grace_period` on every apply, so it is back after a
+    /// ring-server restart as soon as the deployment is reconciled. Absent:
+    /// VMs are shut down right away.
+    grace_periods: Mutex<HashMap<String, std::time::Duration>>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -126,6 +134,7 @@ impl CloudHypervisorLifecycle {
             virtiofs_mounts: Mutex::new(HashMap::new()),
             port_forwarders: Mutex::new(HashMap::new()),
             pids: Mutex::new(HashMap::new()),
+            grace_periods: Mutex::new(HashMap::new()),
         }
     }
 
@@ -420,8 +429,9 @@ impl CloudHypervisorLifecycle {
         // port now, the VM is doomed to be unreachable on it. Failing here
         // means the scheduler increments restart_count and eventually
         // surfaces a CrashLoopBackOff with a clear PortAllocationFailed
-        // event in the deployment history.
-        for p in &deployment.ports {
+        // event in the deployment history. The ports of an `idle` deployment
+        // are held by Ring's own proxy, and never bound by the VM's forwarders.
+        for p in deployment.runtime_bound_ports() {
             let host_ip = p
                 .host_ip
                 .as_deref()
@@ -581,29 +591,10 @@ impl CloudHypervisorLifecycle {
             None
         };
 
-        // Attach a vhost-vsock device whenever the deployment declares a
-        // `command` health check — that's the only consumer today. Adding a
-        // vsock for every CH VM would mean an extra device per VM for no
-        // benefit; gate it on demand instead.
-        //
-        // Known limitation: `needs_vsock` is evaluated only at boot, and CH has
-        // no hot-attach path for vhost-vsock. A `command` check added to an
-        // already-running deployment therefore cannot reach its VM until that
-        // VM restarts. Whether it ever does is up to the check's `on_failure`:
-        // `restart` heals itself after the failure threshold, but `alert` and
-        // `stop` never re-run this path, so the check stays red until the
-        // operator restarts the deployment. `execute_command_probe` detects
-        // this case (no host-side vsock socket) and says so, rather than
-        // blaming a missing `ring-agent` in the guest.
-        let needs_vsock = deployment
-            .health_checks
-            .iter()
-            .any(|hc| matches!(hc, crate::models::health_check::HealthCheck::Command { .. }));
-        let vsock_cid = if needs_vsock {
-            Some(cid_for_instance(instance_id))
-        } else {
-            None
-        };
+        // Every VM gets a vhost-vsock device: `ring-agent` behind it runs
+        // `command` health checks and `ring deployment exec` sessions, and CH
+        // has no hot-attach path for vhost-vsock.
+        let vsock_cid = Some(cid_for_instance(instance_id));
         let vsock_socket_path = vsock_cid
             .map(|_| PathBuf::from(&self.config.socket_dir).join(format!("{}.vsock", instance_id)));
         let guest_net = net_alloc
@@ -749,8 +740,8 @@ impl CloudHypervisorLifecycle {
         // running with a black-hole port. `forwarders` is a local owned
         // Vec; on early return its Drop kills any socat we already spawned.
         if let Some(net) = &net_alloc {
-            let mut forwarders = Vec::with_capacity(deployment.ports.len());
-            for p in &deployment.ports {
+            let mut forwarders = Vec::with_capacity(deployment.runtime_bound_ports().len());
+            for p in deployment.runtime_bound_ports() {
                 match port_forwarder::spawn_forwarder(
                     &net.guest_ip,
                     p.published,
@@ -782,6 +773,48 @@ impl CloudHypervisorLifecycle {
         Ok(())
     }
 
+    /// Record the ACPI grace period of `deployment`'s instances, or forget it.
+    fn remember_grace_period(&self, deployment: &Deployment) {
+        let prefix = Self::deployment_prefix(&deployment.id);
+        if let Ok(mut map) = self.grace_periods.lock() {
+            match termination::acpi_grace_period(deployment) {
+                Some(grace) => map.insert(prefix, grace),
+                None => map.remove(&prefix),
+            };
+        }
+    }
+
+    /// Ask the guest to power off through ACPI, when its deployment has a
+    /// grace period, and wait until it did or the grace period is spent.
+    async fn acpi_shutdown(&self, client: &CloudHypervisorClient, instance_id: &str) {
+        let grace = self.grace_periods.lock().ok().and_then(|map| {
+            map.iter()
+                .find(|(prefix, _)| instance_id.starts_with(prefix.as_str()))
+                .map(|(_, grace)| *grace)
+        });
+        let Some(grace) = grace else {
+            return;
+        };
+        if let Err(e) = client.power_button().await {
+            debug!("ACPI shutdown of VM {} failed: {}", instance_id, e);
+            return;
+        }
+        let deadline = tokio::time::Instant::now() + grace;
+        while tokio::time::Instant::now() < deadline {
+            match client.info().await {
+                Ok(info) if info.state != "Shutdown" => {
+                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                }
+                _ => return,
+            }
+        }
+        warn!(
+            "VM {} did not power off within {}s, shutting it down",
+            instance_id,
+            grace.as_secs()
+        );
+    }
+
     /// Stop and remove a VM instance.
     async fn stop_vm(&self, instance_id: &str) -> bool {
         let socket = self.socket_path(instance_id);
@@ -792,6 +825,7 @@ impl CloudHypervisorLifecycle {
 
         if socket.exists() {
             let client = CloudHypervisorClient::new(socket_str);
+            self.acpi_shutdown(&client, instance_id).await;
 
             if let Err(e) = client.shutdown_vm().await {
                 warn!("Failed to shutdown VM {}: {}", instance_id, e);
@@ -1072,7 +1106,7 @@ impl CloudHypervisorLifecycle {
                     self.stop_vm(&instance_id).await;
                     deployment.instances.clear();
                     deployment.restart_count += 1;
-                    if deployment.restart_count >= MAX_RESTART_COUNT {
+                    if deployment.restarts_exhausted() {
                         deployment.status = DeploymentStatus::Failed;
                         deployment.emit_event(
                             "error",
@@ -1209,6 +1243,7 @@ impl RuntimeLifecycle for CloudHypervisorLifecycle {
         mut deployment: Deployment,
         resolved_mounts: Vec<ResolvedMount>,
     ) -> Deployment {
+        self.remember_grace_period(&deployment);
         if deployment.status == DeploymentStatus::Deleted {
             let instances = self.scan_instances(&deployment.id, &[]).await;
             for instance_id in &instances {
@@ -1247,6 +1282,7 @@ impl RuntimeLifecycle for CloudHypervisorLifecycle {
         deployment_id: &str,
         tail: Option<&str>,
         since: Option<i32>,
+        until: Option<i32>,
         instance_filter: Option<&str>,
     ) -> Vec<Log> {
         // Read every state CH knows about: a crashed VM still has a console
@@ -1261,12 +1297,15 @@ impl RuntimeLifecycle for CloudHypervisorLifecycle {
                 continue;
             }
             let path = self.console_log_path(&instance_id);
-            let lines = crate::hypervisor::console_logs::read_lines(&path, tail, since).await;
+            let lines =
+                crate::hypervisor::console_logs::read_lines(&path, tail, since, until).await;
             for message in lines {
                 logs.push(Log {
                     instance: instance_id.clone(),
+                    container: None,
                     level: classify_log(&message),
                     timestamp: extract_date(&message),
+                    fields: Default::default(),
                     message,
                 });
             }
@@ -1280,12 +1319,7 @@ impl RuntimeLifecycle for CloudHypervisorLifecycle {
         tail: Option<&str>,
         since: Option<i32>,
         instance_filter: Option<&str>,
-    ) -> std::pin::Pin<
-        Box<
-            dyn futures::Stream<Item = Result<axum::response::sse::Event, std::convert::Infallible>>
-                + Send,
-        >,
-    > {
+    ) -> crate::hypervisor::lifecycle_trait::LogStream {
         let instances = self.scan_instances(deployment_id, &[]).await;
         let filtered: Vec<String> = instances
             .into_iter()
@@ -1299,15 +1333,7 @@ impl RuntimeLifecycle for CloudHypervisorLifecycle {
             return Box::pin(stream::empty());
         }
 
-        let mut streams: Vec<
-            std::pin::Pin<
-                Box<
-                    dyn futures::Stream<
-                            Item = Result<axum::response::sse::Event, std::convert::Infallible>,
-                        > + Send,
-                >,
-            >,
-        > = Vec::new();
+        let mut streams: Vec<crate::hypervisor::lifecycle_trait::LogStream> = Vec::new();
 
         for instance_id in filtered {
             let path = self.console_log_path(&instance_id);
@@ -1319,15 +1345,13 @@ impl RuntimeLifecycle for CloudHypervisorLifecycle {
             )
             .await;
 
-            let mapped = raw.map(move |line| {
-                let log = Log {
-                    instance: owned_id.clone(),
-                    level: classify_log(&line),
-                    timestamp: extract_date(&line),
-                    message: line,
-                };
-                let json = serde_json::to_string(&log).unwrap_or_default();
-                Ok(axum::response::sse::Event::default().data(json))
+            let mapped = raw.map(move |line| Log {
+                instance: owned_id.clone(),
+                container: None,
+                level: classify_log(&line),
+                timestamp: extract_date(&line),
+                fields: Default::default(),
+                message: line,
             });
 
             streams.push(Box::pin(mapped));
@@ -1399,6 +1423,26 @@ impl RuntimeLifecycle for CloudHypervisorLifecycle {
         }
     }
 
+    /// Open an interactive session with `ring-agent` in the guest, over the
+    /// same vsock transport as `execute_command_probe`.
+    async fn exec(&self, instance_id: &str, request: ExecRequest) -> Result<ExecSession, String> {
+        let cid = cid_for_instance(instance_id);
+        match crate::hypervisor::vsock_client::session(cid, &request).await {
+            Ok(session) => Ok(session),
+            Err(crate::hypervisor::vsock_client::VsockError::Connect { cid, source }) => {
+                let vsock_path =
+                    PathBuf::from(&self.config.socket_dir).join(format!("{}.vsock", instance_id));
+                Err(crate::hypervisor::vsock_client::connect_failure_message(
+                    "cloud-hypervisor",
+                    cid,
+                    &source.to_string(),
+                    vsock_path.exists(),
+                ))
+            }
+            Err(e) => Err(format!("vsock session failed: {}", e)),
+        }
+    }
+
     /// Fan out `read_instance_stats` over each running VM of the deployment.
     /// CPU% requires two samples spaced apart; we sleep a short interval
     /// between reads, so this call blocks for ~`SAMPLE_INTERVAL_MS`. Docker's
@@ -1527,6 +1571,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         };
 
         let (vcpus, memory_mb) = parse_resources(&deployment);
@@ -1569,6 +1631,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         };
 
         let (vcpus, memory_mb) = parse_resources(&deployment);
@@ -1760,6 +1840,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
@@ -0,0 +1,335 @@
+//! Interactive exec for the containerd runtime, via `Tasks.Exec`.
+//!
+//! containerd has no hijacked connection like `docker exec`: the shim wires
+//! the exec process' stdio to paths we hand it. Ring creates one FIFO per
+//! stream under [`EXEC_DIR`], keeps the other end, and pumps bytes between
+//! those and the session's channels. The exit code comes from `Tasks.Wait`.
+
+use crate::hypervisor::exec::{self, ExecOutput, ExecRequest, ExecSession, TerminalSize};
+use containerd_client::services::v1::tasks_client::TasksClient;
+use containerd_client::services::v1::{
+    CloseIoRequest, DeleteProcessRequest, ExecProcessRequest, KillRequest, ResizePtyRequest,
+    StartRequest, WaitRequest,
+};
+use containerd_client::with_namespace;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::unix::pipe;
+use tonic::Request;
+use tonic::transport::Channel;
+
+/// Host directory holding the FIFOs of the running exec sessions, one
+/// sub-directory per exec.
+const EXEC_DIR: &str = "/run/ring/containerd/exec";
+
+/// How long output keeps being read once the process has exited: what it
+/// wrote last may still sit in the FIFO.
+const DRAIN_IDLE: Duration = Duration::from_millis(100);
+
+/// SIGKILL, sent to an exec whose client went away.
+const SIGKILL: u32 = 9;
+
+pub(crate) async fn open_session(
+    client: &containerd_client::Client,
+    namespace: &str,
+    instance_id: &str,
+    request: ExecRequest,
+) -> Result<ExecSession, String> {
+    if !super::health_check::task_running(client, namespace, instance_id).await {
+        return Err("task is not running".to_string());
+    }
+
+    let exec_id = format!("ring-exec-{}", super::tiny_id());
+    let dir = PathBuf::from(EXEC_DIR).join(&exec_id);
+    let fifos = Fifos::create(&dir, request.tty).map_err(|e| {
+        let _ = std::fs::remove_dir_all(&dir);
+        format!("Failed to create the exec FIFOs: {}", e)
+    })?;
+
+    let target = Target {
+        channel: client.channel(),
+        namespace: namespace.to_string(),
+        container_id: instance_id.to_string(),
+        exec_id,
+        dir,
+    };
+    let mut tasks = TasksClient::new(target.channel.clone());
+    let exec_req = with_namespace!(
+        ExecProcessRequest {
+            container_id: target.container_id.clone(),
+            stdin: fifos.stdin_path.display().to_string(),
+            stdout: fifos.stdout_path.display().to_string(),
+            stderr: fifos
+                .stderr_path
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            terminal: request.tty,
+            spec: Some(super::oci::build_interactive_process_spec(
+                request.command,
+                request.tty,
+            )),
+            exec_id: target.exec_id.clone(),
+        },
+        target.namespace
+    );
+    if let Err(e) = tasks.exec(exec_req).await {
+        target.cleanup().await;
+        return Err(format!("Failed to create exec: {}", e));
+    }
+    let start_req = with_namespace!(
+        StartRequest {
+            container_id: target.container_id.clone(),
+            exec_id: target.exec_id.clone(),
+        },
+        target.namespace
+    );
+    if let Err(e) = tasks.start(start_req).await {
+        target.cleanup().await;
+        return Err(format!("Failed to start exec: {}", e));
+    }
+    if let Some(size) = request.size.filter(|_| request.tty) {
+        target.resize(size).await;
+    }
+
+    let (session, mut io) = exec::channels();
+    let Fifos {
+        mut stdin,
+        mut stdout,
+        mut stderr,
+        ..
+    } = fifos;
+
+    let input_target = target.clone();
+    tokio::spawn(async move {
+        while let Some(bytes) = io.stdin.recv().await {
+            if stdin.write_all(&bytes).await.is_err() {
+                break;
+            }
+        }
+        // The shim holds its own end of the FIFO open, so closing ours is not
+        // enough for the process to read EOF.
+        drop(stdin);
+        input_target.close_stdin().await;
+    });
+
+    let resize_target = target.clone();
+    tokio::spawn(async move {
+        while let Some(size) = io.resize.recv().await {
+            resize_target.resize(size).await;
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut tasks = TasksClient::new(target.channel.clone());
+        let wait = tasks.wait(with_namespace!(
+            WaitRequest {
+                container_id: target.container_id.clone(),
+                exec_id: target.exec_id.clone(),
+            },
+            target.namespace
+        ));
+        tokio::pin!(wait);
+
+        let mut out_buf = vec![0u8; 8192];
+        let mut err_buf = vec![0u8; 8192];
+        let exit_code = loop {
+            tokio::select! {
+                read = read_some(&mut stdout, &mut out_buf) => {
+                    let message = ExecOutput::Stdout(out_buf[..read].to_vec());
+                    if io.output.send(message).await.is_err() {
+                        target.kill().await;
+                        let _ = tokio::time::timeout(Duration::from_secs(5), &mut wait).await;
+                        target.cleanup().await;
+                        return;
+                    }
+                }
+                read = read_optional(stderr.as_mut(), &mut err_buf) => {
+                    let message = ExecOutput::Stderr(err_buf[..read].to_vec());
+                    if io.output.send(message).await.is_err() {
+                        target.kill().await;
+                        let _ = tokio::time::timeout(Duration::from_secs(5), &mut wait).await;
+                        target.cleanup().await;
+                        return;
+                    }
+                }
+                status = &mut wait => {
+                    break status.ok().map(|response| i64::from(response.into_inner().exit_status));
+                }
+                // Nobody is listening any more, even to a quiet process.
+                _ = io.output.closed() => {
+                    target.kill().await;
+                    let _ = tokio::time::timeout(Duration::from_secs(5), &mut wait).await;
+                    target.cleanup().await;
+                    return;
+                }
+            }
+        };
+
+        drain(&mut stdout, &mut out_buf, &io.output, ExecOutput::Stdout).await;
+        if let Some(stderr) = stderr.as_mut() {
+            drain(stderr, &mut err_buf, &io.output, ExecOutput::Stderr).await;
+        }
+        let _ = io.output.send(ExecOutput::Exit(exit_code)).await;
+        target.cleanup().await;
+    });
+
+    Ok(session)
+}
+
+/// Ring's ends of an exec's FIFOs. All are opened read-write, so no open
+/// waits for the shim and the readers never see a premature EOF before the
+/// shim has connected; the end of the output is told by `Tasks.Wait` instead.
+struct Fifos {
+    stdin_path: PathBuf,
+    stdout_path: PathBuf,
+    /// A terminal merges stderr into stdout: no FIFO then.
+    stderr_path: Option<PathBuf>,
+    stdin: pipe::Sender,
+    stdout: pipe::Receiver,
+    stderr: Option<pipe::Receiver>,
+}
+
+impl Fifos {
+    fn create(dir: &Path, tty: bool) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let stdin_path = dir.join("stdin");
+        let stdout_path = dir.join("stdout");
+        let stderr_path = (!tty).then(|| dir.join("stderr"));
+        for path in [Some(&stdin_path), Some(&stdout_path), stderr_path.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            mkfifo(path)?;
+        }
+
+        let mut options = pipe::OpenOptions::new();
+        options.read_write(true);
+        let stdin = options.open_sender(&stdin_path)?;
+        let stdout = options.open_receiver(&stdout_path)?;
+        let stderr = match &stderr_path {
+            Some(path) => Some(options.open_receiver(path)?),
+            None => None,
+        };
+        Ok(Self {
+            stdin_path,
+            stdout_path,
+            stderr_path,
+            stdin,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+fn mkfifo(path: &Path) -> std::io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `path` is a valid NUL-terminated string for the whole call.
+    if unsafe { libc::mkfifo(path.as_ptr(), 0o600) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Reads at least one byte. A stream that fails or ends is never ready again,
+/// so it stops competing with the others in a `select!`.
+async fn read_some<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> usize {
+    match reader.read(buf).await {
+        Ok(read) if read > 0 => read,
+        _ => std::future::pending().await,
+    }
+}
+
+async fn read_optional<R: AsyncRead + Unpin>(reader: Option<&mut R>, buf: &mut [u8]) -> usize {
+    match reader {
+        Some(reader) => read_some(reader, buf).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Forwards what is left in a FIFO once the process is gone, until it stays
+/// empty for [`DRAIN_IDLE`].
+async fn drain<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut [u8],
+    output: &tokio::sync::mpsc::Sender<ExecOutput>,
+    wrap: fn(Vec<u8>) -> ExecOutput,
+) {
+    while let Ok(read) = tokio::time::timeout(DRAIN_IDLE, read_some(reader, buf)).await {
+        if output.send(wrap(buf[..read].to_vec())).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// The exec process an interactive session drives.
+#[derive(Clone)]
+struct Target {
+    channel: Channel,
+    namespace: String,
+    container_id: String,
+    exec_id: String,
+    dir: PathBuf,
+}
+
+impl Target {
+    fn tasks(&self) -> TasksClient<Channel> {
+        TasksClient::new(self.channel.clone())
+    }
+
+    async fn resize(&self, size: TerminalSize) {
+        let req = with_namespace!(
+            ResizePtyRequest {
+                container_id: self.container_id.clone(),
+                exec_id: self.exec_id.clone(),
+                width: u32::from(size.cols),
+                height: u32::from(size.rows),
+            },
+            self.namespace
+        );
+        if let Err(e) = self.tasks().resize_pty(req).await {
+            debug!("Failed to resize exec {}: {}", self.exec_id, e);
+        }
+    }
+
+    async fn close_stdin(&self) {
+        let req = with_namespace!(
+            CloseIoRequest {
+                container_id: self.container_id.clone(),
+                exec_id: self.exec_id.clone(),
+                stdin: true,
+            },
+            self.namespace
+        );
+        let _ = self.tasks().close_io(req).await;
+    }
+
+    async fn kill(&self) {
+        let req = with_namespace!(
+            KillRequest {
+                container_id: self.container_id.clone(),
+                exec_id: self.exec_id.clone(),
+                signal: SIGKILL,
+                all: false,
+            },
+            self.namespace
+        );
+        let _ = self.tasks().kill(req).await;
+    }
+
+    /// Reaps the exec process and removes its FIFOs.
+    async fn cleanup(&self) {
+        let req = with_namespace!(
+            DeleteProcessRequest {
+                container_id: self.container_id.clone(),
+                exec_id: self.exec_id.clone(),
+            },
+            self.namespace
+        );
+        let _ = self.tasks().delete_process(req).await;
+        let _ = tokio::fs::remove_dir_all(&self.dir).await;
+    }
+}
@@ -3,7 +3,8 @@
 //! - [`instance_address`] resolves the CNI-assigned IP so the trait-default
 //!   `execute_health_check` can run TCP/HTTP probes.
 //! - [`execute_command_check`] runs a `command` probe inside the task via
-//!   `Tasks.Exec`, equivalent to `docker exec`.
+//!   `Tasks.Exec`, equivalent to `docker exec`; [`run_exec`] does the same
+//!   for the lifecycle hooks.
 //!
 //! `instance_address` reads the IP that `host-local` IPAM recorded for the
 //! container at CNI ADD time. host-local writes one file per allocated address
@@ -76,6 +77,19 @@ pub(crate) async fn execute_command_check(
         }
     };
 
+    run_exec(client, namespace, instance_id, args, EXEC_WAIT_TIMEOUT).await
+}
+
+/// Run `args` inside the task via `Tasks.Exec`, bounded by `timeout`, and
+/// report its exit status the way [`execute_command_check`] does. Also runs
+/// the lifecycle hooks.
+pub(crate) async fn run_exec(
+    client: &containerd_client::Client,
+    namespace: &str,
+    instance_id: &str,
+    args: Vec<String>,
+    timeout: std::time::Duration,
+) -> (HealthCheckStatus, Option<String>) {
     // Refuse to exec into a task that is not running — Exec would error opaquely.
     if !task_running(client, namespace, instance_id).await {
         return (
@@ -123,11 +137,10 @@ pub(crate) async fn execute_command_check(
         );
     }
 
-    // Wait for completion and read the exit status. Bound the wait: a probe
-    // command that hangs (e.g. `sleep 9999`) would otherwise block this call
-    // forever, since Task.Wait has no built-in deadline. The trait dispatch
-    // doesn't thread the per-probe timeout down here, so we apply a defensive
-    // upper bound — a hung probe must fail, not wedge the health loop.
+    // Wait for completion and read the exit status. Bound the wait: a command
+    // that hangs (e.g. `sleep 9999`) would otherwise block this call forever,
+    // since Task.Wait has no built-in deadline — a hung probe must fail, not
+    // wedge the health loop.
     let wait_req = with_namespace!(
         WaitRequest {
             container_id: instance_id.to_string(),
@@ -135,7 +148,7 @@ pub(crate) async fn execute_command_check(
         },
         namespace
     );
-    let outcome = match tokio::time::timeout(EXEC_WAIT_TIMEOUT, tasks.wait(wait_req)).await {
+    let outcome = match tokio::time::timeout(timeout, tasks.wait(wait_req)).await {
         Ok(Ok(resp)) => match resp.into_inner().exit_status {
             0 => (
                 HealthCheckStatus::Success,
@@ -165,10 +178,7 @@ pub(crate) async fn execute_command_check(
                 .await;
             (
                 HealthCheckStatus::Failed,
-                Some(format!(
-                    "Command probe timed out after {}s",
-                    EXEC_WAIT_TIMEOUT.as_secs()
-                )),
+                Some(format!("Command timed out after {}s", timeout.as_secs())),
             )
         }
     };
@@ -198,7 +208,7 @@ async fn cleanup_exec(
     let _ = tasks.delete_process(req).await;
 }
 
-async fn task_running(
+pub(super) async fn task_running(
     client: &containerd_client::Client,
     namespace: &str,
     instance_id: &str,
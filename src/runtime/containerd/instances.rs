@@ -9,6 +9,7 @@
 
 use super::RING_DEPLOYMENT_LABEL;
 use crate::hypervisor::error::RuntimeError;
+use crate::hypervisor::instance_group;
 use containerd_client::services::v1::ListContainersRequest;
 use containerd_client::services::v1::ListTasksRequest;
 use containerd_client::services::v1::containers_client::ContainersClient;
@@ -62,6 +63,51 @@ pub(crate) async fn list_instances_with_names(
     }
 }
 
+/// An init container or sidecar, as found in containerd.
+pub(crate) struct Member {
+    pub(crate) id: String,
+    /// Id of the instance (main container) it belongs to.
+    pub(crate) group: String,
+    /// Its name in the manifest.
+    pub(crate) container: String,
+}
+
+/// Init containers and sidecars whose `label` is `value`: an instance with
+/// `GROUP_LABEL`, or a whole deployment with `MEMBER_OF_LABEL`.
+pub(crate) async fn list_members(
+    client: &containerd_client::Client,
+    namespace: &str,
+    label: &str,
+    value: &str,
+) -> Vec<Member> {
+    let mut containers = ContainersClient::new(client.channel());
+    let filter = format!("labels.\"{}\"=={}", label, value);
+    let req = with_namespace!(
+        ListContainersRequest {
+            filters: vec![filter],
+        },
+        namespace
+    );
+    match containers.list(req).await {
+        Ok(resp) => resp
+            .into_inner()
+            .containers
+            .into_iter()
+            .filter_map(|c| {
+                Some(Member {
+                    group: c.labels.get(instance_group::GROUP_LABEL)?.clone(),
+                    container: c.labels.get(instance_group::CONTAINER_LABEL)?.clone(),
+                    id: c.id,
+                })
+            })
+            .collect(),
+        Err(e) => {
+            debug!("containerd list group members error: {}", e);
+            Vec::new()
+        }
+    }
+}
+
 async fn list_instances_inner(
     client: &containerd_client::Client,
     namespace: &str,
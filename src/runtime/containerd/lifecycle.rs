@@ -18,43 +18,46 @@
 //! CNI `DEL`.
 
 use super::client::{DEFAULT_RUNTIME, DEFAULT_SNAPSHOTTER};
-use super::image::{ContainerdImage, ensure_image};
+use super::image::{ContainerdImage, ResolvedImage, ensure_image};
 use super::{ContainerdLifecycle, RING_DEPLOYMENT_LABEL, cni, instances, oci, tiny_id};
 use crate::api::dto::stats::InstanceStatsOutput;
+use crate::hypervisor::classifier::{classify_create_error, scheduler_skips_by_status};
 use crate::hypervisor::error::RuntimeError;
-use crate::hypervisor::lifecycle_trait::{Log, RuntimeLifecycle, classify_log, extract_date};
-use crate::models::deployments::{Deployment, DeploymentStatus, MAX_RESTART_COUNT};
+use crate::hypervisor::exec::{ExecRequest, ExecSession};
+use crate::hypervisor::instance_group::{self, Role};
+use crate::hypervisor::job::{self, Attempt};
+use crate::hypervisor::lifecycle_trait::{
+    Log, LogStream, RuntimeLifecycle, classify_log, extract_date,
+};
+use crate::hypervisor::termination;
+use crate::models::deployments::{ContainerSpec, Deployment, DeploymentStatus, LifecycleHook};
 use crate::models::health_check::HealthCheckStatus;
 use crate::models::volume::ResolvedMount;
 use crate::runtime::docker::ImagePullPolicy;
 use async_trait::async_trait;
-use axum::response::sse::Event;
 use containerd_client::services::v1::container::Runtime;
 use containerd_client::services::v1::containers_client::ContainersClient;
 use containerd_client::services::v1::snapshots::snapshots_client::SnapshotsClient;
-use containerd_client::services::v1::snapshots::{PrepareSnapshotRequest, RemoveSnapshotRequest};
+use containerd_client::services::v1::snapshots::{
+    MountsRequest, PrepareSnapshotRequest, RemoveSnapshotRequest,
+};
 use containerd_client::services::v1::tasks_client::TasksClient;
 use containerd_client::services::v1::{
     Container, CreateContainerRequest, CreateTaskRequest, DeleteContainerRequest,
-    DeleteTaskRequest, GetRequest, KillRequest, StartRequest, WaitRequest,
+    DeleteTaskRequest, GetContainerRequest, GetRequest, KillRequest, StartRequest, WaitRequest,
 };
 use containerd_client::types::Mount;
 use containerd_client::types::v1::Status as TaskStatus;
 use containerd_client::with_namespace;
-use futures::stream::{self, Stream};
+use futures::stream;
 use std::cmp::Ordering;
-use std::convert::Infallible;
-use std::pin::Pin;
+use std::collections::HashMap;
 use tonic::Request;
 
-/// SIGTERM / SIGKILL signal numbers (Linux). Used for graceful then forced kill.
-const SIGTERM: u32 = 15;
+/// SIGKILL signal number (Linux), sent once an instance's grace period is
+/// spent. The graceful signal comes from its `lifecycle:` block.
 const SIGKILL: u32 = 9;
 
-/// How long an instance gets to exit after SIGTERM before we SIGKILL it. Matches
-/// Docker's default 10s stop grace period.
-const STOP_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(10);
-
 /// Host directory where Ring keeps per-instance log files written by the task's
 /// stdio. Mirrors how the shim's fifo/file stdio is consumed.
 fn log_path(instance_id: &str) -> String {
@@ -145,24 +148,20 @@ impl RuntimeLifecycle for ContainerdLifecycle {
         deployment_id: &str,
         tail: Option<&str>,
         since: Option<i32>,
+        until: Option<i32>,
         instance_filter: Option<&str>,
     ) -> Vec<Log> {
-        let instances = self
-            .list_instances_with_names(deployment_id.to_string(), "all")
-            .await;
         let mut logs = Vec::new();
-        for (instance_id, instance_name) in instances {
-            if let Some(f) = instance_filter
-                && !instance_id.contains(f)
-                && !instance_name.contains(f)
-            {
-                continue;
-            }
-            for message in super::logs::read_logs(&instance_id, tail, since).await {
+        for (container_id, instance_name, container) in
+            self.log_sources(deployment_id, instance_filter).await
+        {
+            for message in super::logs::read_logs(&container_id, tail, since, until).await {
                 logs.push(Log {
                     instance: instance_name.clone(),
+                    container: container.clone(),
                     level: classify_log(&message),
                     timestamp: extract_date(&message),
+                    fields: Default::default(),
                     message,
                 });
             }
@@ -176,29 +175,19 @@ impl RuntimeLifecycle for ContainerdLifecycle {
         tail: Option<&str>,
         since: Option<i32>,
         instance_filter: Option<&str>,
-    ) -> Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>> {
-        let instances = self
-            .list_instances_with_names(deployment_id.to_string(), "all")
-            .await;
-        let mut streams: Vec<Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>> =
-            Vec::new();
-        for (instance_id, instance_name) in instances {
-            if let Some(f) = instance_filter
-                && !instance_id.contains(f)
-                && !instance_name.contains(f)
-            {
-                continue;
-            }
-            let raw = super::logs::stream_logs(instance_id, tail, since).await;
-            let mapped = futures::StreamExt::map(raw, move |line| {
-                let log = Log {
-                    instance: instance_name.clone(),
-                    level: classify_log(&line),
-                    timestamp: extract_date(&line),
-                    message: line,
-                };
-                let json = serde_json::to_string(&log).unwrap_or_default();
-                Ok(Event::default().data(json))
+    ) -> LogStream {
+        let mut streams: Vec<LogStream> = Vec::new();
+        for (container_id, instance_name, container) in
+            self.log_sources(deployment_id, instance_filter).await
+        {
+            let raw = super::logs::stream_logs(container_id, tail, since).await;
+            let mapped = futures::StreamExt::map(raw, move |line| Log {
+                instance: instance_name.clone(),
+                container: container.clone(),
+                level: classify_log(&line),
+                timestamp: extract_date(&line),
+                fields: Default::default(),
+                message: line,
             });
             streams.push(Box::pin(mapped));
         }
@@ -231,6 +220,11 @@ impl RuntimeLifecycle for ContainerdLifecycle {
         .await
     }
 
+    async fn exec(&self, instance_id: &str, request: ExecRequest) -> Result<ExecSession, String> {
+        let client = self.connect().await.map_err(|e| e.to_string())?;
+        super::exec::open_session(&client, &self.config.namespace, instance_id, request).await
+    }
+
     async fn get_instance_stats(&self, deployment_id: &str) -> Vec<InstanceStatsOutput> {
         let Ok(client) = self.connect().await else {
             return Vec::new();
@@ -252,6 +246,48 @@ impl RuntimeLifecycle for ContainerdLifecycle {
 }
 
 impl ContainerdLifecycle {
+    /// Every container whose logs belong to the deployment, as `(container
+    /// id, instance name, container name)`: the instances, then their init
+    /// containers and sidecars, tagged with their name in the manifest.
+    async fn log_sources(
+        &self,
+        deployment_id: &str,
+        instance_filter: Option<&str>,
+    ) -> Vec<(String, String, Option<String>)> {
+        let Ok(client) = self.connect().await else {
+            return Vec::new();
+        };
+        let instances = instances::list_instances_with_names(
+            &client,
+            &self.config.namespace,
+            deployment_id,
+            "all",
+        )
+        .await;
+        let members = instances::list_members(
+            &client,
+            &self.config.namespace,
+            instance_group::MEMBER_OF_LABEL,
+            deployment_id,
+        )
+        .await;
+
+        let matches = |id: &str, name: &str| {
+            instance_filter.is_none_or(|f| id.contains(f) || name.contains(f))
+        };
+        let mut sources: Vec<(String, String, Option<String>)> = instances
+            .into_iter()
+            .filter(|(id, name)| matches(id, name))
+            .map(|(id, name)| (id, name, None))
+            .collect();
+        for member in members {
+            if matches(&member.id, &member.group) {
+                sources.push((member.id, member.group, Some(member.container)));
+            }
+        }
+        sources
+    }
+
     async fn handle_job(
         &self,
         mut deployment: Deployment,
@@ -262,43 +298,49 @@ impl ContainerdLifecycle {
             self.remove_all(&mut deployment, client, "job").await;
             return deployment;
         }
-        if matches!(
-            deployment.status,
-            DeploymentStatus::Completed | DeploymentStatus::Failed
-        ) {
-            return deployment;
-        }
-        if deployment.restart_count >= MAX_RESTART_COUNT {
-            deployment.status = DeploymentStatus::Failed;
-            return deployment;
-        }
-
         let all =
             instances::list_instances(client, &self.config.namespace, &deployment.id, "all").await;
-        if let Some(instance_id) = all.first() {
-            match self.task_status(client, instance_id).await {
-                Some(TaskStatus::Running) | Some(TaskStatus::Paused) => {
-                    deployment.status = DeploymentStatus::Running;
-                }
+        let mut attempts = Vec::with_capacity(all.len());
+        for instance_id in all {
+            let attempt = match self.task_status(client, &instance_id).await {
+                Some(TaskStatus::Running) | Some(TaskStatus::Paused) => Attempt::Running,
+                // A stopped task: the Get response carries its exit status.
                 Some(TaskStatus::Stopped) => {
-                    // A stopped task: inspect exit code to tell completed vs
-                    // failed. The Get response carries exit_status.
-                    deployment.status = match self.task_exit_status(client, instance_id).await {
-                        Some(0) => DeploymentStatus::Completed,
-                        _ => DeploymentStatus::Failed,
-                    };
-                }
-                _ => {
-                    deployment.status = DeploymentStatus::Failed;
+                    match self.task_exit_status(client, &instance_id).await {
+                        Some(0) => Attempt::Succeeded,
+                        code => Attempt::Failed(code.map(i64::from)),
+                    }
                 }
-            }
-        } else {
+                _ => Attempt::Failed(None),
+            };
+            attempts.push((instance_id, attempt));
+        }
+
+        let plan = job::step(&mut deployment, &attempts, chrono::Utc::now(), "containerd");
+        for instance_id in &plan.stop {
+            self.stop_task(client, instance_id).await;
+        }
+        for instance_id in &plan.remove {
+            self.teardown_instance(client, instance_id).await;
+        }
+
+        // Creation errors share the retry budget of failed attempts, see the
+        // Docker runtime.
+        for _ in 0..plan.start {
             match self
                 .create_instance(&mut deployment, client, resolved_mounts)
                 .await
             {
                 Ok(_) => deployment.status = DeploymentStatus::Running,
-                Err(err) => handle_create_error(&mut deployment, err, true),
+                Err(err) => {
+                    let terminal = classify_create_error(&err).is_terminal()
+                        && !matches!(err, RuntimeError::InstanceCreationFailed(_));
+                    handle_create_error(&mut deployment, err, true);
+                    if terminal && !scheduler_skips_by_status(&deployment.status) {
+                        job::exhaust_retries(&mut deployment);
+                    }
+                    break;
+                }
             }
         }
         deployment
@@ -314,13 +356,14 @@ impl ContainerdLifecycle {
             self.remove_all(&mut deployment, client, "worker").await;
             return deployment;
         }
-        if deployment.restart_count >= MAX_RESTART_COUNT {
+        if deployment.restarts_exhausted() {
             deployment.status = DeploymentStatus::CrashLoopBackOff;
             return deployment;
         }
         if deployment.status == DeploymentStatus::CrashLoopBackOff {
             return deployment;
         }
+        self.restart_exited_sidecars(&mut deployment, client).await;
 
         let current = deployment.instances.len();
         let target = match usize::try_from(deployment.target_replicas()) {
@@ -420,38 +463,24 @@ impl ContainerdLifecycle {
     ) -> Result<(), RuntimeError> {
         crate::hypervisor::resources::check_host_memory(deployment)?;
 
-        let ns = &self.config.namespace;
-
-        // 1. Image + rootfs chain id. Credentials come either from the host's
-        // Docker config (when the deployment opts into `use_host_auth` and the
-        // runtime authorizes it) or from the inline config fields.
-        let config = deployment.config.as_ref();
-        let activated = config.map(|c| c.use_host_auth).unwrap_or(false);
-        let inline = config.and_then(|c| {
-            match (
-                c.server.as_deref(),
-                c.username.as_deref(),
-                c.password.as_deref(),
-            ) {
-                (Some(s), Some(u), Some(p)) => Some((s, u, p)),
-                _ => None,
-            }
-        });
-        let auth = crate::runtime::registry_auth::resolve_deployment_auth(
-            &deployment.image,
-            activated,
-            inline,
-            &self.config.host_auth,
-        )
-        .map_err(|e| RuntimeError::ImagePullFailed(e.to_string()))?;
-        let image = ContainerdImage::from_deployment(&deployment.image, auth);
-        let policy = deployment
-            .config
-            .as_ref()
-            .map(|c| ImagePullPolicy::parse(&c.image_pull_policy))
-            .unwrap_or(ImagePullPolicy::Always);
-        let resolved = ensure_image(client, ns, &image, policy).await?;
-        deployment.image_digest = resolved.digest;
+        // 1. Image + rootfs chain id, for the main container and every init
+        // container and sidecar: a missing image fails the instance before any
+        // of its containers runs.
+        let (image_ref, resolved) = self
+            .resolve_image(client, deployment, &deployment.image)
+            .await?;
+        deployment.image_digest = resolved.digest.clone();
+        let mut member_images = Vec::new();
+        for container in deployment
+            .init_containers
+            .iter()
+            .chain(deployment.sidecars.iter())
+        {
+            member_images.push(
+                self.resolve_image(client, deployment, &container.image)
+                    .await?,
+            );
+        }
         let image_default_args = resolved.default_args;
 
         // Instance id: human-readable, unique, also the container + snapshot key.
@@ -462,7 +491,8 @@ impl ContainerdLifecycle {
             .prepare_snapshot(client, &instance_id, &resolved.chain_id)
             .await?;
 
-        // 3. Materialize content mounts to disk, then build the OCI spec.
+        // 3. Materialize content mounts to disk, run the init containers, then
+        // build the OCI spec.
         let config_files = match write_config_files(deployment, resolved_mounts).await {
             Ok(f) => f,
             Err(e) => {
@@ -470,6 +500,27 @@ impl ContainerdLifecycle {
                 return Err(e);
             }
         };
+        let (init_images, sidecar_images) =
+            member_images.split_at(deployment.init_containers.len());
+        for (init, (image_ref, image)) in deployment.init_containers.iter().zip(init_images) {
+            if let Err(e) = self
+                .run_init_container(
+                    client,
+                    deployment,
+                    &instance_id,
+                    init,
+                    image_ref,
+                    image,
+                    resolved_mounts,
+                    &config_files,
+                )
+                .await
+            {
+                self.teardown_members(client, &instance_id).await;
+                self.remove_snapshot(client, &instance_id).await;
+                return Err(e);
+            }
+        }
         let spec = oci::build_spec(
             deployment,
             resolved_mounts,
@@ -477,30 +528,72 @@ impl ContainerdLifecycle {
             &image_default_args,
         );
 
-        // 4. Register the container object, tagged with the owning deployment.
+        // 4. Register the container object, tagged with the owning deployment
+        // (for list/remove filtering) plus any user-supplied labels, matching
+        // the Docker runtime's label set.
+        let mut labels = deployment.labels.clone();
+        labels.insert(RING_DEPLOYMENT_LABEL.to_string(), deployment.id.clone());
+        termination::insert_label(deployment, &mut labels);
         if let Err(e) = self
-            .create_container_object(
-                client,
-                &instance_id,
-                &deployment.id,
-                &deployment.labels,
-                &image.reference,
-                spec,
-            )
+            .create_container_object(client, &instance_id, labels, &image_ref, spec)
             .await
         {
+            self.teardown_members(client, &instance_id).await;
             self.remove_snapshot(client, &instance_id).await;
             return Err(e);
         }
 
         // 5. Create + start the task, wiring CNI in between.
-        if let Err(e) = self
-            .create_and_start_task(client, &instance_id, mounts)
+        let pid = match self
+            .create_and_start_task(client, &instance_id, mounts, None)
             .await
         {
-            self.delete_container_object(client, &instance_id).await;
-            self.remove_snapshot(client, &instance_id).await;
-            return Err(e);
+            Ok(pid) => pid,
+            Err(e) => {
+                self.teardown_members(client, &instance_id).await;
+                self.delete_container_object(client, &instance_id).await;
+                self.remove_snapshot(client, &instance_id).await;
+                return Err(e);
+            }
+        };
+
+        // 6. Sidecars join the main task's network namespace, so they can only
+        // start once it exists. Without all of them the instance is incomplete:
+        // tear the whole group down and let the retry start over.
+        let netns = format!("/proc/{}/ns/net", pid);
+        for (sidecar, (image_ref, image)) in deployment.sidecars.iter().zip(sidecar_images) {
+            if let Err(e) = self
+                .start_sidecar(
+                    client,
+                    deployment,
+                    &instance_id,
+                    sidecar,
+                    image_ref,
+                    image,
+                    resolved_mounts,
+                    &config_files,
+                    &netns,
+                )
+                .await
+            {
+                self.teardown_instance(client, &instance_id).await;
+                return Err(e);
+            }
+        }
+
+        if let Some(hook) = deployment
+            .lifecycle
+            .as_ref()
+            .and_then(|l| l.post_start.clone())
+            && let Err(e) = self
+                .run_hook(client, &instance_id, &hook, termination::POST_START_TIMEOUT)
+                .await
+        {
+            self.teardown_instance(client, &instance_id).await;
+            return Err(RuntimeError::InstanceCreationFailed(format!(
+                "post_start hook failed: {}",
+                e
+            )));
         }
 
         deployment.instances.push(instance_id.clone());
@@ -508,6 +601,287 @@ impl ContainerdLifecycle {
         Ok(())
     }
 
+    /// Pull (per the deployment's policy and credentials) and unpack `image`.
+    /// Returns its fully-qualified reference with the resolved rootfs.
+    async fn resolve_image(
+        &self,
+        client: &containerd_client::Client,
+        deployment: &Deployment,
+        image: &str,
+    ) -> Result<(String, ResolvedImage), RuntimeError> {
+        // Credentials come either from the host's Docker config (when the
+        // deployment opts into `use_host_auth` and the runtime authorizes it)
+        // or from the inline config fields.
+        let config = deployment.config.as_ref();
+        let activated = config.map(|c| c.use_host_auth).unwrap_or(false);
+        let inline = config.and_then(|c| {
+            match (
+                c.server.as_deref(),
+                c.username.as_deref(),
+                c.password.as_deref(),
+            ) {
+                (Some(s), Some(u), Some(p)) => Some((s, u, p)),
+                _ => None,
+            }
+        });
+        let auth = crate::runtime::registry_auth::resolve_deployment_auth(
+            image,
+            activated,
+            inline,
+            &self.config.host_auth,
+        )
+        .map_err(|e| RuntimeError::ImagePullFailed(e.to_string()))?;
+        let image = ContainerdImage::from_deployment(image, auth);
+        let policy = deployment
+            .config
+            .as_ref()
+            .map(|c| ImagePullPolicy::parse(&c.image_pull_policy))
+            .unwrap_or(ImagePullPolicy::Always);
+        let resolved = ensure_image(client, &self.config.namespace, &image, policy).await?;
+        Ok((image.reference, resolved))
+    }
+
+    /// Create and start one init container or sidecar of the instance
+    /// `group`. A sidecar is given the main task's network namespace
+    /// (`netns`); an init container, which runs before it exists, gets its
+    /// own, wired through CNI. Returns the member's container id.
+    #[allow(clippy::too_many_arguments)]
+    async fn start_member(
+        &self,
+        client: &containerd_client::Client,
+        deployment: &Deployment,
+        group: &str,
+        spec: &ContainerSpec,
+        role: Role,
+        (image_ref, image): (&str, &ResolvedImage),
+        resolved_mounts: &[ResolvedMount],
+        config_files: &[(String, String)],
+        netns: Option<&str>,
+    ) -> Result<String, RuntimeError> {
+        let member = instance_group::member_deployment(deployment, spec);
+        let id = instance_group::member_name(group, &spec.name);
+
+        let mounts = self.prepare_snapshot(client, &id, &image.chain_id).await?;
+        let oci_spec = oci::build_spec_in_netns(
+            &member,
+            resolved_mounts,
+            config_files,
+            &image.default_args,
+            netns,
+        );
+        let labels = instance_group::member_labels(deployment, group, spec, role);
+        if let Err(e) = self
+            .create_container_object(client, &id, labels, image_ref, oci_spec)
+            .await
+        {
+            self.remove_snapshot(client, &id).await;
+            return Err(e);
+        }
+        if let Err(e) = self.create_and_start_task(client, &id, mounts, netns).await {
+            self.delete_container_object(client, &id).await;
+            self.remove_snapshot(client, &id).await;
+            return Err(e);
+        }
+        debug!("containerd {} container {} started", role.as_str(), id);
+        Ok(id)
+    }
+
+    /// Run one init container to completion. Anything but exit code 0 fails
+    /// the instance; the exited container is kept so its logs stay readable
+    /// until the instance is torn down.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_init_container(
+        &self,
+        client: &containerd_client::Client,
+        deployment: &Deployment,
+        group: &str,
+        spec: &ContainerSpec,
+        image_ref: &str,
+        image: &ResolvedImage,
+        resolved_mounts: &[ResolvedMount],
+        config_files: &[(String, String)],
+    ) -> Result<(), RuntimeError> {
+        let id = self
+            .start_member(
+                client,
+                deployment,
+                group,
+                spec,
+                Role::Init,
+                (image_ref, image),
+                resolved_mounts,
+                config_files,
+                None,
+            )
+            .await?;
+
+        let mut tasks = TasksClient::new(client.channel());
+        let wait = tasks
+            .wait(with_namespace!(
+                WaitRequest {
+                    container_id: id.clone(),
+                    exec_id: String::new(),
+                },
+                self.config.namespace
+            ))
+            .await;
+        // Its network namespace is gone with its process: free the address.
+        cni::del(&id, "").await;
+
+        let exit_status = wait
+            .map_err(|e| {
+                RuntimeError::InstanceCreationFailed(format!(
+                    "containerd failed to wait for init container '{}': {}",
+                    spec.name, e
+                ))
+            })?
+            .into_inner()
+            .exit_status;
+        if exit_status != 0 {
+            return Err(RuntimeError::InstanceCreationFailed(format!(
+                "init container '{}' exited with code {}",
+                spec.name, exit_status
+            )));
+        }
+        info!("containerd init container {} completed", id);
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn start_sidecar(
+        &self,
+        client: &containerd_client::Client,
+        deployment: &Deployment,
+        group: &str,
+        spec: &ContainerSpec,
+        image_ref: &str,
+        image: &ResolvedImage,
+        resolved_mounts: &[ResolvedMount],
+        config_files: &[(String, String)],
+        netns: &str,
+    ) -> Result<(), RuntimeError> {
+        self.start_member(
+            client,
+            deployment,
+            group,
+            spec,
+            Role::Sidecar,
+            (image_ref, image),
+            resolved_mounts,
+            config_files,
+            Some(netns),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Start the task of every sidecar of a running instance that exited
+    /// again, as the Docker runtime's `unless-stopped` policy does. The
+    /// container and its snapshot are reused, so is its log file, and the
+    /// task rejoins the network namespace of the instance, which still runs.
+    async fn restart_exited_sidecars(
+        &self,
+        deployment: &mut Deployment,
+        client: &containerd_client::Client,
+    ) {
+        if deployment.sidecars.is_empty() {
+            return;
+        }
+        let mut running = HashMap::new();
+        for member in instances::list_members(
+            client,
+            &self.config.namespace,
+            instance_group::MEMBER_OF_LABEL,
+            &deployment.id,
+        )
+        .await
+        {
+            let status = self.task_status(client, &member.id).await;
+            running.insert(
+                member.id,
+                matches!(status, Some(TaskStatus::Running | TaskStatus::Paused)),
+            );
+        }
+
+        let exited: Vec<(String, String)> = exited_sidecars(deployment, &running)
+            .into_iter()
+            .map(|(group, sidecar)| (group.to_string(), sidecar.name.clone()))
+            .collect();
+        for (group, name) in exited {
+            let Some(pid) = self.task_pid(client, &group).await else {
+                continue;
+            };
+            let id = instance_group::member_name(&group, &name);
+            match self
+                .restart_task(client, &id, &format!("/proc/{}/ns/net", pid))
+                .await
+            {
+                Ok(()) => deployment.emit_event(
+                    "warning",
+                    format!("Sidecar '{}' of {} exited and was restarted", name, group),
+                    "containerd",
+                    Some("sidecar_restart"),
+                ),
+                Err(e) => warn!("failed to restart sidecar {}: {}", id, e),
+            }
+        }
+    }
+
+    /// Replace the stopped task of `id` with a new one, on the mounts of its
+    /// existing snapshot.
+    async fn restart_task(
+        &self,
+        client: &containerd_client::Client,
+        id: &str,
+        netns: &str,
+    ) -> Result<(), RuntimeError> {
+        let mut tasks = TasksClient::new(client.channel());
+        let _ = tasks
+            .delete(with_namespace!(
+                DeleteTaskRequest {
+                    container_id: id.to_string(),
+                },
+                self.config.namespace
+            ))
+            .await;
+
+        let mut snapshots = SnapshotsClient::new(client.channel());
+        let req = with_namespace!(
+            MountsRequest {
+                snapshotter: DEFAULT_SNAPSHOTTER.to_string(),
+                key: id.to_string(),
+            },
+            self.config.namespace
+        );
+        let mounts = snapshots
+            .mounts(req)
+            .await
+            .map_err(|e| {
+                RuntimeError::InstanceCreationFailed(format!("Mounts failed for {}: {}", id, e))
+            })?
+            .into_inner()
+            .mounts;
+        self.create_and_start_task(client, id, mounts, Some(netns))
+            .await
+            .map(|_| ())
+    }
+
+    /// Tear down every init container and sidecar of the instance `group`.
+    async fn teardown_members(&self, client: &containerd_client::Client, group: &str) {
+        for member in instances::list_members(
+            client,
+            &self.config.namespace,
+            instance_group::GROUP_LABEL,
+            group,
+        )
+        .await
+        {
+            // An init container's address was freed when it exited, and a
+            // sidecar has none of its own: its netns is the main task's.
+            self.teardown_container(client, &member.id, false).await;
+        }
+    }
+
     async fn prepare_snapshot(
         &self,
         client: &containerd_client::Client,
@@ -551,19 +925,10 @@ impl ContainerdLifecycle {
         &self,
         client: &containerd_client::Client,
         instance_id: &str,
-        deployment_id: &str,
-        user_labels: &std::collections::HashMap<String, String>,
+        labels: std::collections::HashMap<String, String>,
         image_ref: &str,
         spec: prost_types::Any,
     ) -> Result<(), RuntimeError> {
-        // Carry the owning deployment id (for list/remove filtering) plus any
-        // user-supplied labels, matching the Docker runtime's label set.
-        let mut labels = std::collections::HashMap::new();
-        labels.insert(RING_DEPLOYMENT_LABEL.to_string(), deployment_id.to_string());
-        for (k, v) in user_labels {
-            labels.insert(k.clone(), v.clone());
-        }
-
         let container = Container {
             id: instance_id.to_string(),
             labels,
@@ -606,12 +971,16 @@ impl ContainerdLifecycle {
         }
     }
 
+    /// Create and start the task of `instance_id` and return its pid. A task
+    /// that joins an existing network namespace (`shared_netns`, a sidecar) is
+    /// not wired through CNI: the namespace already has its address.
     async fn create_and_start_task(
         &self,
         client: &containerd_client::Client,
         instance_id: &str,
         mounts: Vec<Mount>,
-    ) -> Result<(), RuntimeError> {
+        shared_netns: Option<&str>,
+    ) -> Result<u32, RuntimeError> {
         // Ensure the log file's parent dir exists so the shim can write stdio.
         let log_file = log_path(instance_id);
         if let Some(parent) = std::path::Path::new(&log_file).parent() {
@@ -643,10 +1012,12 @@ impl ContainerdLifecycle {
         // Wire up CNI on the task's netns *before* starting, while the process
         // is created-but-not-running. The runc shim sets up the netns at create
         // time, exposed at /proc/<pid>/ns/net.
-        cni::ensure_default_config();
         let netns = format!("/proc/{}/ns/net", pid);
-        if cni::add(instance_id, &netns).await.is_none() {
-            debug!("no CNI address assigned for {}", instance_id);
+        if shared_netns.is_none() {
+            cni::ensure_default_config();
+            if cni::add(instance_id, &netns).await.is_none() {
+                debug!("no CNI address assigned for {}", instance_id);
+            }
         }
 
         let start = StartRequest {
@@ -660,7 +1031,9 @@ impl ContainerdLifecycle {
             // The task was created (a shim + netns exist) and CNI may have
             // reserved an IP. Unwind both before surfacing the error, otherwise
             // we leak a created-but-stopped task and a permanent IPAM lease.
-            cni::del(instance_id, &netns).await;
+            if shared_netns.is_none() {
+                cni::del(instance_id, &netns).await;
+            }
             let _ = tasks
                 .delete(with_namespace!(
                     DeleteTaskRequest {
@@ -674,34 +1047,36 @@ impl ContainerdLifecycle {
                 instance_id, e
             )));
         }
-        Ok(())
+        Ok(pid)
     }
 
-    /// Full teardown of one instance: kill (TERM then KILL), delete task, CNI
-    /// DEL, delete container, remove snapshot. Best-effort throughout.
-    async fn teardown_instance(
-        &self,
-        client: &containerd_client::Client,
-        instance_id: &str,
-    ) -> bool {
-        let mut tasks = TasksClient::new(client.channel());
-
-        // Capture the pid for CNI teardown before we kill the task.
-        let netns = self
-            .task_pid(client, instance_id)
-            .await
-            .map(|pid| format!("/proc/{}/ns/net", pid));
+    /// Stop an instance's task without removing anything: its container,
+    /// snapshot and log stay for inspection. Graceful then forced, as its
+    /// `lifecycle:` block asks: run `pre_stop` if the task still runs, send
+    /// the stop signal, give the workload what is left of the grace period to
+    /// exit on its own (interrupted early if it does), then SIGKILL whatever
+    /// is left. Sending both signals back-to-back would make graceful shutdown
+    /// impossible — the process never sees the first one before SIGKILL.
+    async fn stop_task(&self, client: &containerd_client::Client, instance_id: &str) {
+        let lifecycle = termination::from_labels(&self.container_labels(client, instance_id).await);
+        let grace = lifecycle.grace_period();
+        let started = std::time::Instant::now();
+        if let Some(hook) = &lifecycle.pre_stop
+            && self.task_status(client, instance_id).await == Some(TaskStatus::Running)
+        {
+            match self.run_hook(client, instance_id, hook, grace).await {
+                Ok(()) => debug!("pre_stop hook of {} done", instance_id),
+                Err(e) => warn!("pre_stop hook of {} failed: {}", instance_id, e),
+            }
+        }
 
-        // Graceful then forced: send SIGTERM, give the workload a grace period to
-        // exit on its own (interrupted early if it does), then SIGKILL whatever
-        // is left. Sending both signals back-to-back would make graceful
-        // shutdown impossible — the process never sees SIGTERM before SIGKILL.
+        let mut tasks = TasksClient::new(client.channel());
         let _ = tasks
             .kill(with_namespace!(
                 KillRequest {
                     container_id: instance_id.to_string(),
                     exec_id: String::new(),
-                    signal: SIGTERM,
+                    signal: lifecycle.signal_number(),
                     all: true,
                 },
                 self.config.namespace
@@ -717,7 +1092,7 @@ impl ContainerdLifecycle {
             },
             self.config.namespace
         ));
-        let _ = tokio::time::timeout(STOP_GRACE_PERIOD, wait).await;
+        let _ = tokio::time::timeout(grace.saturating_sub(started.elapsed()), wait).await;
 
         let _ = tasks
             .kill(with_namespace!(
@@ -730,13 +1105,101 @@ impl ContainerdLifecycle {
                 self.config.namespace
             ))
             .await;
+    }
+
+    /// Run a lifecycle hook of the instance, bounded by `timeout`.
+    async fn run_hook(
+        &self,
+        client: &containerd_client::Client,
+        instance_id: &str,
+        hook: &LifecycleHook,
+        timeout: std::time::Duration,
+    ) -> Result<(), String> {
+        match hook {
+            LifecycleHook::Command(command) => termination::command_outcome(
+                super::health_check::run_exec(
+                    client,
+                    &self.config.namespace,
+                    instance_id,
+                    command.clone(),
+                    timeout,
+                )
+                .await,
+            ),
+            LifecycleHook::Http(http) => {
+                let address = super::health_check::instance_address(
+                    client,
+                    &self.config.namespace,
+                    instance_id,
+                )
+                .await;
+                termination::http_hook(address, http, timeout).await
+            }
+        }
+    }
+
+    /// Labels of the container object, empty if it is gone.
+    async fn container_labels(
+        &self,
+        client: &containerd_client::Client,
+        instance_id: &str,
+    ) -> std::collections::HashMap<String, String> {
+        let mut containers = ContainersClient::new(client.channel());
+        let req = with_namespace!(
+            GetContainerRequest {
+                id: instance_id.to_string(),
+            },
+            self.config.namespace
+        );
+        match containers.get(req).await {
+            Ok(resp) => resp
+                .into_inner()
+                .container
+                .map(|c| c.labels)
+                .unwrap_or_default(),
+            Err(_) => std::collections::HashMap::new(),
+        }
+    }
+
+    /// Full teardown of one instance. Its task is stopped first, so a
+    /// `pre_stop` hook can still reach the sidecars, then its init containers
+    /// and sidecars go, then the instance itself. Best-effort throughout.
+    async fn teardown_instance(
+        &self,
+        client: &containerd_client::Client,
+        instance_id: &str,
+    ) -> bool {
+        self.stop_task(client, instance_id).await;
+        self.teardown_members(client, instance_id).await;
+        self.teardown_container(client, instance_id, true).await
+    }
+
+    /// Teardown of one container: kill (TERM then KILL), delete task, CNI DEL
+    /// (when it has its own network), delete container, remove snapshot.
+    async fn teardown_container(
+        &self,
+        client: &containerd_client::Client,
+        instance_id: &str,
+        owns_network: bool,
+    ) -> bool {
+        let mut tasks = TasksClient::new(client.channel());
+
+        // Capture the pid for CNI teardown before we kill the task.
+        let netns = self
+            .task_pid(client, instance_id)
+            .await
+            .map(|pid| format!("/proc/{}/ns/net", pid));
+
+        self.stop_task(client, instance_id).await;
 
         // Tear down CNI. When the task was already stopped we have no live pid
         // (so no /proc/<pid>/ns/net), but host-local IPAM keys its lease off the
         // container id alone, so DEL with an empty netns still frees the address.
-        match &netns {
-            Some(netns) => cni::del(instance_id, netns).await,
-            None => cni::del(instance_id, "").await,
+        if owns_network {
+            match &netns {
+                Some(netns) => cni::del(instance_id, netns).await,
+                None => cni::del(instance_id, "").await,
+            }
         }
 
         let _ = tasks
@@ -805,6 +1268,26 @@ impl ContainerdLifecycle {
     }
 }
 
+/// The sidecars of the deployment's running instances whose container exists
+/// but whose task no longer runs, as `(instance id, sidecar)`. `running` maps
+/// the id of each init container and sidecar found to whether its task runs;
+/// init containers are never looked up, they are meant to exit.
+fn exited_sidecars<'a>(
+    deployment: &'a Deployment,
+    running: &HashMap<String, bool>,
+) -> Vec<(&'a str, &'a ContainerSpec)> {
+    let mut exited = Vec::new();
+    for group in &deployment.instances {
+        for sidecar in &deployment.sidecars {
+            let id = instance_group::member_name(group, &sidecar.name);
+            if running.get(&id) == Some(&false) {
+                exited.push((group.as_str(), sidecar));
+            }
+        }
+    }
+    exited
+}
+
 /// Materialize `Content` mounts (config/secret) to host files and return
 /// `(host_path, destination)` pairs for the OCI spec.
 async fn write_config_files(
@@ -842,7 +1325,7 @@ async fn write_config_files(
 fn handle_create_error(deployment: &mut Deployment, err: RuntimeError, increment_restart: bool) {
     // Decide retry-vs-give-up before mapping the message, like Docker: a terminal
     // error (image absent, config missing) can't fix itself on a retry, so jump
-    // straight to the restart bound instead of burning MAX_RESTART_COUNT
+    // straight to the restart bound instead of burning the whole budget in
     // reconcile cycles to reach the same outcome.
     //
     // `InstanceCreationFailed` is the one exception, and it is a containerd
@@ -870,7 +1353,7 @@ fn handle_create_error(deployment: &mut Deployment, err: RuntimeError, increment
 
     if increment_restart && !status_alone_stops_reconciliation {
         if terminal {
-            deployment.restart_count = MAX_RESTART_COUNT;
+            deployment.exhaust_restarts();
         } else {
             deployment.restart_count += 1;
         }
@@ -918,6 +1401,7 @@ fn handle_create_error(deployment: &mut Deployment, err: RuntimeError, increment
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::deployments::MAX_RESTART_COUNT;
     use std::collections::HashMap;
 
     fn worker() -> Deployment {
@@ -948,6 +1432,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
@@ -1039,4 +1541,38 @@ mod tests {
         assert_eq!(deployment.restart_count, 0);
         assert_eq!(deployment.status, DeploymentStatus::ImagePullBackOff);
     }
+
+    fn sidecar(name: &str) -> ContainerSpec {
+        ContainerSpec {
+            name: name.to_string(),
+            image: "busybox".to_string(),
+            command: vec![],
+            environment: HashMap::new(),
+        }
+    }
+
+    /// Only a sidecar found with a stopped task is started again: a running
+    /// one is left alone, and so is one that is not there to restart.
+    #[test]
+    fn exited_sidecars_are_the_stopped_ones_of_running_instances() {
+        let mut deployment = worker();
+        deployment.instances = vec!["test_app_a".to_string(), "test_app_b".to_string()];
+        deployment.sidecars = vec![sidecar("proxy"), sidecar("shipper")];
+        let running = HashMap::from([
+            ("test_app_a_proxy".to_string(), true),
+            ("test_app_a_shipper".to_string(), false),
+            ("test_app_b_proxy".to_string(), false),
+            ("test_app_gone_proxy".to_string(), false),
+        ]);
+
+        let exited: Vec<(&str, &str)> = exited_sidecars(&deployment, &running)
+            .into_iter()
+            .map(|(group, sidecar)| (group, sidecar.name.as_str()))
+            .collect();
+
+        assert_eq!(
+            exited,
+            vec![("test_app_a", "shipper"), ("test_app_b", "proxy")]
+        );
+    }
 }
@@ -5,7 +5,9 @@
 //! lifecycle points stdout/stderr at a per-instance host file
 //! (`/var/log/ring/containerd/<id>.log`); here we read and tail that file.
 
+use crate::hypervisor::console_logs;
 use futures::stream::{self, Stream};
+use std::path::Path;
 use std::pin::Pin;
 use tokio::io::{AsyncBufReadExt, BufReader};
 
@@ -16,11 +18,13 @@ fn log_file(instance_id: &str) -> String {
 /// Read the instance log file into lines, honouring an optional `tail` count.
 /// `since` is accepted for trait parity but not applied — the file lines carry
 /// no reliable timestamp containerd guarantees, so time-based filtering is left
-/// to the log content's own timestamps (see `extract_date`).
+/// to the log content's own timestamps (see `extract_date`). `until` goes
+/// through [`console_logs::written_until`], before `tail`.
 pub(crate) async fn read_logs(
     instance_id: &str,
     tail: Option<&str>,
     _since: Option<i32>,
+    until: Option<i32>,
 ) -> Vec<String> {
     let path = log_file(instance_id);
     let content = match tokio::fs::read_to_string(&path).await {
@@ -35,6 +39,9 @@ pub(crate) async fn read_logs(
         .map(|l| l.trim_end().to_string())
         .filter(|l| !l.trim().is_empty())
         .collect();
+    if let Some(until) = until {
+        lines = console_logs::written_until(Path::new(&path), lines, until).await;
+    }
 
     if let Some(tail) = tail
         && let Ok(n) = tail.parse::<usize>()
@@ -54,7 +61,7 @@ pub(crate) async fn stream_logs(
 ) -> Pin<Box<dyn Stream<Item = String> + Send>> {
     let path = log_file(&instance_id);
     // Seed with the current tail.
-    let initial = read_logs(&instance_id, tail, since).await;
+    let initial = read_logs(&instance_id, tail, since, None).await;
 
     let file = match tokio::fs::File::open(&path).await {
         Ok(f) => f,
@@ -32,6 +32,7 @@
 
 pub(crate) mod client;
 pub(crate) mod cni;
+mod exec;
 pub(crate) mod health_check;
 pub(crate) mod image;
 pub(crate) mod instances;
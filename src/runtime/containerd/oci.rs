@@ -34,12 +34,34 @@ pub(crate) fn build_spec(
     config_files: &[(String, String)],
     image_default_args: &[String],
 ) -> Any {
-    let spec = build_spec_value(
+    build_spec_in_netns(
+        deployment,
+        resolved_mounts,
+        config_files,
+        image_default_args,
+        None,
+    )
+}
+
+/// [`build_spec`] for a container that joins the network namespace at
+/// `netns` (e.g. `/proc/<pid>/ns/net` of the main task, for a sidecar)
+/// instead of getting its own. `None` behaves like [`build_spec`].
+pub(crate) fn build_spec_in_netns(
+    deployment: &Deployment,
+    resolved_mounts: &[ResolvedMount],
+    config_files: &[(String, String)],
+    image_default_args: &[String],
+    netns: Option<&str>,
+) -> Any {
+    let mut spec = build_spec_value(
         deployment,
         resolved_mounts,
         config_files,
         image_default_args,
     );
+    if let Some(path) = netns {
+        join_network_namespace(&mut spec, path);
+    }
     // `spec` is an in-memory serde_json::Value built from owned data, so
     // serialisation cannot fail in practice; expect() over unwrap_or_default()
     // so a truly impossible failure surfaces loudly instead of silently
@@ -51,6 +73,18 @@ pub(crate) fn build_spec(
     }
 }
 
+/// Point the spec's network namespace at an existing one: runc then joins it
+/// instead of creating a fresh namespace.
+fn join_network_namespace(spec: &mut Value, path: &str) {
+    if let Some(namespaces) = spec["linux"]["namespaces"].as_array_mut() {
+        for namespace in namespaces.iter_mut() {
+            if namespace["type"] == "network" {
+                namespace["path"] = json!(path);
+            }
+        }
+    }
+}
+
 /// Pure spec construction, split out so it can be unit-tested without gRPC.
 pub(crate) fn build_spec_value(
     deployment: &Deployment,
@@ -273,13 +307,22 @@ fn readonly_paths() -> Vec<&'static str> {
 /// Build an OCI process spec for an exec probe (used by health checks). Wrapped
 /// as an `Any` for `ExecProcessRequest.spec`.
 pub(crate) fn build_exec_process_spec(args: Vec<String>) -> Any {
+    build_interactive_process_spec(args, false)
+}
+
+/// Same as [`build_exec_process_spec`], for an interactive exec: with
+/// `terminal`, the process gets a terminal and a `TERM` to drive it.
+pub(crate) fn build_interactive_process_spec(args: Vec<String>, terminal: bool) -> Any {
+    let mut env =
+        vec!["PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin".to_string()];
+    if terminal {
+        env.push("TERM=xterm".to_string());
+    }
     let process = json!({
-        "terminal": false,
+        "terminal": terminal,
         "user": { "uid": 0, "gid": 0 },
         "args": args,
-        "env": [
-            "PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"
-        ],
+        "env": env,
         "cwd": "/",
         "capabilities": default_capabilities(),
         "noNewPrivileges": true,
@@ -328,6 +371,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
@@ -382,6 +443,27 @@ mod tests {
         assert!(namespaces.iter().any(|n| n["type"] == "network"));
     }
 
+    #[test]
+    fn spec_joins_an_existing_network_namespace() {
+        let d = make_deployment();
+        let mut spec = build_spec_value(&d, &[], &[], &[]);
+        join_network_namespace(&mut spec, "/proc/42/ns/net");
+        let namespaces = spec["linux"]["namespaces"].as_array().unwrap();
+        let network: Vec<_> = namespaces
+            .iter()
+            .filter(|ns| ns["type"] == "network")
+            .collect();
+        assert_eq!(network.len(), 1);
+        assert_eq!(network[0]["path"], "/proc/42/ns/net");
+        // Only the network is shared: the sidecar keeps its own pid namespace.
+        assert!(
+            namespaces
+                .iter()
+                .filter(|ns| ns["type"] != "network")
+                .all(|ns| ns.get("path").is_none())
+        );
+    }
+
     #[test]
     fn spec_binds_resolved_mounts() {
         let d = make_deployment();
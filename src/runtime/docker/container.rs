@@ -1,7 +1,10 @@
 use super::{DockerImage, ImagePullPolicy, ImageReference, parse_image_reference, tiny_id};
 use crate::hypervisor::error::RuntimeError;
+use crate::hypervisor::instance_group::{self, Role};
+use crate::hypervisor::termination;
 use crate::models::deployments::{
-    Deployment, EnvValue, NetworkMode, parse_cpu_string, parse_memory_string,
+    ContainerSpec, Deployment, EnvValue, LifecycleHook, NetworkMode, parse_cpu_string,
+    parse_memory_string,
 };
 use crate::models::health_check::HealthCheck;
 use crate::models::volume::ResolvedMount;
@@ -11,11 +14,12 @@ use bollard::{
     models::{
         ContainerCreateBody, EndpointSettings, HealthConfig, HostConfig, Mount, MountTypeEnum,
         MountVolumeOptions, MountVolumeOptionsDriverConfig, NetworkConnectRequest,
-        NetworkCreateRequest, PortBinding,
+        NetworkCreateRequest, PortBinding, RestartPolicy, RestartPolicyNameEnum,
     },
     query_parameters::{
-        CreateContainerOptionsBuilder, CreateImageOptionsBuilder, InspectNetworkOptionsBuilder,
-        RemoveContainerOptionsBuilder, StartContainerOptionsBuilder, StopContainerOptionsBuilder,
+        CreateContainerOptionsBuilder, CreateImageOptionsBuilder, InspectContainerOptions,
+        InspectNetworkOptionsBuilder, ListContainersOptionsBuilder, RemoveContainerOptionsBuilder,
+        StartContainerOptionsBuilder, StopContainerOptionsBuilder,
     },
 };
 use futures::StreamExt;
@@ -351,6 +355,7 @@ async fn ensure_named_volume(
 /// clear errored state instead of silently falling back to an anonymous pull.
 fn resolve_registry_auth(
     deployment: &Deployment,
+    image: &str,
     host_auth: &crate::runtime::registry_auth::HostAuthSettings,
 ) -> Result<Option<(String, String, String)>, RuntimeError> {
     let config = deployment.config.as_ref();
@@ -366,35 +371,24 @@ fn resolve_registry_auth(
         }
     });
 
-    crate::runtime::registry_auth::resolve_deployment_auth(
-        &deployment.image,
-        activated,
-        inline,
-        host_auth,
-    )
-    .map_err(|e| RuntimeError::ImagePullFailed(e.to_string()))
+    crate::runtime::registry_auth::resolve_deployment_auth(image, activated, inline, host_auth)
+        .map_err(|e| RuntimeError::ImagePullFailed(e.to_string()))
 }
 
-pub(crate) async fn create_container(
-    deployment: &mut Deployment,
+/// Make `image` available locally under the deployment's pull policy and
+/// credentials. Returns its repo digest when known.
+async fn ensure_image(
     docker: &Docker,
-    resolved_mounts: &[crate::models::volume::ResolvedMount],
+    deployment: &Deployment,
+    image: &str,
     host_auth: &crate::runtime::registry_auth::HostAuthSettings,
-) -> Result<(), RuntimeError> {
-    debug!("Create container for deployment id: {}", &deployment.id);
-
-    // Admission control before any expensive work (image pull, network create):
-    // if the host can't hold the requested memory, the container would only get
-    // OOM-killed at runtime (or, with no limit, take the host down with it).
-    // Fail here with an actionable message instead.
-    crate::hypervisor::resources::check_host_memory(deployment)?;
-
-    let (name, reference) = parse_image_reference(&deployment.image);
+) -> Result<Option<String>, RuntimeError> {
+    let (name, reference) = parse_image_reference(image);
 
     let image_config = DockerImage {
         name,
         reference,
-        auth: resolve_registry_auth(deployment, host_auth)?,
+        auth: resolve_registry_auth(deployment, image, host_auth)?,
     };
 
     let policy = deployment
@@ -412,23 +406,45 @@ pub(crate) async fn create_container(
             // the policy itself blocked the pull.
             let image_name = image_config.full_ref();
             match docker.inspect_image(&image_name).await {
-                Ok(inspect) => {
-                    deployment.image_digest =
-                        extract_digest(&inspect.repo_digests, &image_config.name);
-                }
-                Err(_) => {
-                    return Err(RuntimeError::ImageNotFound(format!(
-                        "image '{}' not in local cache and image_pull_policy=Never forbids pulling",
-                        image_name
-                    )));
-                }
+                Ok(inspect) => Ok(extract_digest(&inspect.repo_digests, &image_config.name)),
+                Err(_) => Err(RuntimeError::ImageNotFound(format!(
+                    "image '{}' not in local cache and image_pull_policy=Never forbids pulling",
+                    image_name
+                ))),
             }
         }
         ImagePullPolicy::Always | ImagePullPolicy::IfNotPresent => {
-            let digest = pull_image(docker.clone(), image_config, policy).await?;
-            deployment.image_digest = digest;
+            pull_image(docker.clone(), image_config, policy).await
         }
     }
+}
+
+pub(crate) async fn create_container(
+    deployment: &mut Deployment,
+    docker: &Docker,
+    resolved_mounts: &[crate::models::volume::ResolvedMount],
+    host_auth: &crate::runtime::registry_auth::HostAuthSettings,
+) -> Result<(), RuntimeError> {
+    debug!("Create container for deployment id: {}", &deployment.id);
+
+    // Admission control before any expensive work (image pull, network create):
+    // if the host can't hold the requested memory, the container would only get
+    // OOM-killed at runtime (or, with no limit, take the host down with it).
+    // Fail here with an actionable message instead.
+    crate::hypervisor::resources::check_host_memory(deployment)?;
+
+    let image = deployment.image.clone();
+    deployment.image_digest = ensure_image(docker, deployment, &image, host_auth).await?;
+    // Init containers and sidecars are pulled with the deployment's policy and
+    // credentials, up front: a missing image fails the instance before any of
+    // its containers runs.
+    for container in deployment
+        .init_containers
+        .iter()
+        .chain(deployment.sidecars.iter())
+    {
+        ensure_image(docker, deployment, &container.image, host_auth).await?;
+    }
 
     let use_host_network = matches!(
         deployment.network.as_ref().map(|n| n.mode),
@@ -448,9 +464,16 @@ pub(crate) async fn create_container(
 
     let mut labels = HashMap::new();
     labels.insert("ring_deployment".to_string(), deployment.id.clone());
+    if !deployment.init_containers.is_empty() || !deployment.sidecars.is_empty() {
+        labels.insert(
+            instance_group::GROUP_LABEL.to_string(),
+            container_name.clone(),
+        );
+    }
     for (key, value) in deployment.labels.iter() {
         labels.insert(key.clone(), value.clone());
     }
+    termination::insert_label(deployment, &mut labels);
 
     let envs: Vec<String> = deployment.environment
         .iter()
@@ -483,11 +506,36 @@ pub(crate) async fn create_container(
         mounts.push(create_mount_from_resolved(resolved, &deployment.id).await?);
     }
 
+    // Init containers share the instance's volumes and network, and must all
+    // exit 0 before the main container is even created.
+    let init_network_mode = if use_host_network {
+        "host".to_string()
+    } else {
+        network_name.clone()
+    };
+    for init in deployment.init_containers.clone() {
+        if let Err(e) = run_init_container(
+            docker,
+            deployment,
+            &container_name,
+            &init,
+            &mounts,
+            &init_network_mode,
+        )
+        .await
+        {
+            remove_members(docker, &container_name).await;
+            return Err(e);
+        }
+    }
+
     let user_config = build_user_config(&deployment.config);
     let privileged_config = get_privileged_config(&deployment.config);
 
+    // Under `idle`, Ring itself holds the published ports and proxies them to
+    // the container's address: binding them here too would collide.
     let port_bindings: HashMap<String, Option<Vec<PortBinding>>> = deployment
-        .ports
+        .runtime_bound_ports()
         .iter()
         .map(|p| {
             let key = format!("{}/{}", p.target, p.protocol.as_str());
@@ -510,7 +558,7 @@ pub(crate) async fn create_container(
     };
 
     let host_config = HostConfig {
-        mounts: Some(mounts),
+        mounts: Some(mounts.clone()),
         privileged: privileged_config,
         network_mode: if use_host_network {
             Some("host".to_string())
@@ -552,6 +600,17 @@ pub(crate) async fn create_container(
         user: user_config,
         healthcheck: build_health_config(&deployment.health_checks),
         exposed_ports,
+        // Also what a plain `docker stop` uses. Unset keeps the image's
+        // STOPSIGNAL and the daemon's 10s.
+        stop_signal: deployment
+            .lifecycle
+            .as_ref()
+            .filter(|l| l.stop_signal.is_some())
+            .map(|l| l.signal_number().to_string()),
+        stop_timeout: deployment
+            .lifecycle
+            .as_ref()
+            .map(|l| l.grace_period().as_secs() as i64),
         ..Default::default()
     };
 
@@ -614,6 +673,51 @@ pub(crate) async fn create_container(
                 )));
             }
 
+            // Sidecars join the main container's network namespace, so they
+            // can only start once it runs. Without all of them the instance is
+            // incomplete: tear the whole group down and let the retry start over.
+            let sidecar_network_mode = if use_host_network {
+                "host".to_string()
+            } else {
+                format!("container:{}", container.id)
+            };
+            for sidecar in deployment.sidecars.clone() {
+                if let Err(e) = start_sidecar(
+                    docker,
+                    deployment,
+                    &container_name,
+                    &sidecar,
+                    &mounts,
+                    &sidecar_network_mode,
+                )
+                .await
+                {
+                    remove_container(docker.clone(), container.id.clone()).await;
+                    deployment.instances.pop();
+                    return Err(e);
+                }
+            }
+
+            if let Some(hook) = deployment
+                .lifecycle
+                .as_ref()
+                .and_then(|l| l.post_start.clone())
+                && let Err(e) = run_hook(
+                    docker,
+                    &container.id,
+                    &hook,
+                    termination::POST_START_TIMEOUT,
+                )
+                .await
+            {
+                remove_container(docker.clone(), container.id.clone()).await;
+                deployment.instances.pop();
+                return Err(RuntimeError::InstanceCreationFailed(format!(
+                    "post_start hook failed: {}",
+                    e
+                )));
+            }
+
             info!(
                 "Docker container {} created and started successfully",
                 container_name
@@ -622,11 +726,240 @@ pub(crate) async fn create_container(
         }
         Err(e) => {
             error!("Docker failed to create container: {}", e);
+            remove_members(docker, &container_name).await;
             Err(RuntimeError::from(e))
         }
     }
 }
 
+/// Create and start one init container or sidecar of the group `group`.
+async fn start_member(
+    docker: &Docker,
+    deployment: &Deployment,
+    group: &str,
+    spec: &ContainerSpec,
+    role: Role,
+    mounts: &[Mount],
+    network_mode: &str,
+) -> Result<String, RuntimeError> {
+    let member = instance_group::member_deployment(deployment, spec);
+    let name = instance_group::member_name(group, &spec.name);
+
+    let envs: Vec<String> = member
+        .environment
+        .iter()
+        .filter_map(|(key, value)| match value {
+            EnvValue::Plain(v) => Some(format!("{}={}", key, v)),
+            EnvValue::SecretRef { .. } => {
+                error!(
+                    "Unresolved secretRef for key '{}' of container {}",
+                    key, name
+                );
+                None
+            }
+        })
+        .collect();
+
+    let host_config = HostConfig {
+        mounts: Some(mounts.to_vec()),
+        privileged: get_privileged_config(&member.config),
+        network_mode: Some(network_mode.to_string()),
+        // A crashed sidecar comes back on its own: it is not an instance, so
+        // the scheduler would never notice it is gone.
+        restart_policy: (role == Role::Sidecar).then_some(RestartPolicy {
+            name: Some(RestartPolicyNameEnum::UNLESS_STOPPED),
+            maximum_retry_count: None,
+        }),
+        ..Default::default()
+    };
+
+    let config = ContainerCreateBody {
+        image: Some(member.image.clone()),
+        cmd: (!member.command.is_empty()).then(|| member.command.clone()),
+        env: Some(envs),
+        labels: Some(instance_group::member_labels(deployment, group, spec, role)),
+        host_config: Some(host_config),
+        user: build_user_config(&member.config),
+        ..Default::default()
+    };
+
+    let options = CreateContainerOptionsBuilder::new().name(&name).build();
+    let container = docker
+        .create_container(Some(options), config)
+        .await
+        .map_err(|e| {
+            RuntimeError::InstanceCreationFailed(format!(
+                "Docker failed to create {} container '{}': {}",
+                role.as_str(),
+                spec.name,
+                e
+            ))
+        })?;
+
+    let start_options = StartContainerOptionsBuilder::new().build();
+    if let Err(e) = docker
+        .start_container(&container.id, Some(start_options))
+        .await
+    {
+        return Err(RuntimeError::InstanceCreationFailed(format!(
+            "Docker failed to start {} container '{}': {}",
+            role.as_str(),
+            spec.name,
+            e
+        )));
+    }
+
+    debug!("Docker {} container {} started", role.as_str(), name);
+    Ok(container.id)
+}
+
+/// Run one init container to completion. Anything but exit code 0 fails the
+/// instance; the exited container is kept so its logs stay readable until the
+/// group is removed.
+async fn run_init_container(
+    docker: &Docker,
+    deployment: &Deployment,
+    group: &str,
+    spec: &ContainerSpec,
+    mounts: &[Mount],
+    network_mode: &str,
+) -> Result<(), RuntimeError> {
+    let id = start_member(
+        docker,
+        deployment,
+        group,
+        spec,
+        Role::Init,
+        mounts,
+        network_mode,
+    )
+    .await?;
+
+    let exit_code = match docker.wait_container(&id, None).next().await {
+        Some(Ok(response)) => response.status_code,
+        Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => code,
+        Some(Err(e)) => {
+            return Err(RuntimeError::InstanceCreationFailed(format!(
+                "Docker failed to wait for init container '{}': {}",
+                spec.name, e
+            )));
+        }
+        None => {
+            return Err(RuntimeError::InstanceCreationFailed(format!(
+                "Docker returned no exit status for init container '{}'",
+                spec.name
+            )));
+        }
+    };
+
+    if exit_code != 0 {
+        return Err(RuntimeError::InstanceCreationFailed(format!(
+            "init container '{}' exited with code {}",
+            spec.name, exit_code
+        )));
+    }
+    info!("Docker init container {} completed", spec.name);
+    Ok(())
+}
+
+async fn start_sidecar(
+    docker: &Docker,
+    deployment: &Deployment,
+    group: &str,
+    spec: &ContainerSpec,
+    mounts: &[Mount],
+    network_mode: &str,
+) -> Result<(), RuntimeError> {
+    start_member(
+        docker,
+        deployment,
+        group,
+        spec,
+        Role::Sidecar,
+        mounts,
+        network_mode,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// An init container or sidecar, as found on the host.
+pub(crate) struct Member {
+    pub(crate) id: String,
+    /// Name of the instance (main container) it belongs to.
+    pub(crate) group: String,
+    /// Its name in the manifest.
+    pub(crate) container: String,
+}
+
+/// Init containers and sidecars whose `label` is `value`: a group with
+/// `GROUP_LABEL`, or a whole deployment with `MEMBER_OF_LABEL`. Listed without
+/// a server-side filter, like the instances (see `instances.rs`).
+pub(crate) async fn list_members(docker: &Docker, label: &str, value: &str) -> Vec<Member> {
+    let options = ListContainersOptionsBuilder::new().all(true).build();
+    let mut members = Vec::new();
+    match docker.list_containers(Some(options)).await {
+        Ok(containers) => {
+            for container in containers {
+                if let Some(labels) = &container.labels
+                    && labels.get(label).is_some_and(|v| v == value)
+                    && let Some(group) = labels.get(instance_group::GROUP_LABEL)
+                    && let Some(name) = labels.get(instance_group::CONTAINER_LABEL)
+                    && let Some(id) = &container.id
+                {
+                    members.push(Member {
+                        id: id.clone(),
+                        group: group.clone(),
+                        container: name.clone(),
+                    });
+                }
+            }
+        }
+        Err(e) => debug!("Docker list group members error: {}", e),
+    }
+    members
+}
+
+/// Remove every init container and sidecar of the group `group`.
+async fn remove_members(docker: &Docker, group: &str) {
+    for member in list_members(docker, instance_group::GROUP_LABEL, group).await {
+        let remove_options = RemoveContainerOptionsBuilder::new()
+            .v(true)
+            .force(true)
+            .build();
+        match docker
+            .remove_container(&member.id, Some(remove_options))
+            .await
+        {
+            Ok(_) => debug!("Container {} of {} removed", member.container, group),
+            Err(e) => debug!(
+                "Error removing container {} of {}: {:?}",
+                member.container, group, e
+            ),
+        }
+    }
+}
+
+/// Remove the init containers and sidecars that belong to the instance
+/// `container_id`, if it has any. Done once the instance is stopped (its
+/// `pre_stop` hook may still need its sidecars) and before it is removed, so a
+/// sidecar never outlives the network namespace it joined.
+async fn remove_group_of(docker: &Docker, container_id: &str) {
+    let Ok(inspect) = docker
+        .inspect_container(container_id, None::<InspectContainerOptions>)
+        .await
+    else {
+        return;
+    };
+    if let Some(group) = inspect
+        .config
+        .and_then(|config| config.labels)
+        .and_then(|labels| labels.get(instance_group::GROUP_LABEL).cloned())
+    {
+        remove_members(docker, &group).await;
+    }
+}
+
 async fn create_mount_from_resolved(
     resolved: &ResolvedMount,
     deployment_id: &str,
@@ -708,15 +1041,11 @@ async fn create_mount_from_resolved(
 }
 
 pub(crate) async fn remove_container(docker: Docker, container_id: String) {
-    let stop_options = StopContainerOptionsBuilder::new().build();
-
-    match docker
-        .stop_container(&container_id, Some(stop_options))
-        .await
-    {
+    match graceful_stop(&docker, &container_id).await {
         Ok(_) => debug!("Container {} stopped successfully", container_id),
         Err(e) => debug!("Error stopping container {}: {:?}", container_id, e),
     }
+    remove_group_of(&docker, &container_id).await;
 
     // `v(true)` removes the *anonymous* volumes attached to this container —
     // the ones Docker auto-creates from an image's `VOLUME` directive and which
@@ -734,11 +1063,18 @@ pub(crate) async fn remove_container(docker: Docker, container_id: String) {
     }
 }
 
+/// Stop a container and leave it in place, so its logs and exit code can
+/// still be read.
+pub(crate) async fn stop_container(docker: &Docker, container_id: &str) {
+    match graceful_stop(docker, container_id).await {
+        Ok(_) => info!("Container {} stopped", container_id),
+        Err(e) => debug!("Error stopping container {}: {:?}", container_id, e),
+    }
+}
+
 pub(crate) async fn remove_container_by_id(docker: &Docker, container_id: String) -> bool {
-    let stop_options = StopContainerOptionsBuilder::new().build();
-    let _ = docker
-        .stop_container(&container_id, Some(stop_options))
-        .await;
+    let _ = graceful_stop(docker, &container_id).await;
+    remove_group_of(docker, &container_id).await;
 
     // See `remove_container`: `v(true)` reaps anonymous volumes only; named
     // (Ring-managed / operator) volumes are untouched.
@@ -758,6 +1094,66 @@ pub(crate) async fn remove_container_by_id(docker: &Docker, container_id: String
     }
 }
 
+/// Stop a container the way its deployment's `lifecycle:` block asks: run
+/// `pre_stop` if it still runs, then send its stop signal (set on the
+/// container at creation) and kill it once what is left of the grace period
+/// is spent.
+async fn graceful_stop(docker: &Docker, container_id: &str) -> Result<(), bollard::errors::Error> {
+    let inspect = docker
+        .inspect_container(container_id, None::<InspectContainerOptions>)
+        .await?;
+    let running = inspect
+        .state
+        .as_ref()
+        .and_then(|state| state.running)
+        .unwrap_or(false);
+    let lifecycle = termination::from_labels(
+        &inspect
+            .config
+            .and_then(|config| config.labels)
+            .unwrap_or_default(),
+    );
+
+    let grace = lifecycle.grace_period();
+    let started = std::time::Instant::now();
+    if running && let Some(hook) = &lifecycle.pre_stop {
+        match run_hook(docker, container_id, hook, grace).await {
+            Ok(()) => debug!("pre_stop hook of container {} done", container_id),
+            Err(e) => warn!("pre_stop hook of container {} failed: {}", container_id, e),
+        }
+    }
+
+    let left = grace.saturating_sub(started.elapsed());
+    let stop_options = StopContainerOptionsBuilder::new()
+        .t(left.as_secs() as i32)
+        .build();
+    docker
+        .stop_container(container_id, Some(stop_options))
+        .await
+}
+
+/// Run a lifecycle hook of the container, bounded by `timeout`.
+async fn run_hook(
+    docker: &Docker,
+    container_id: &str,
+    hook: &LifecycleHook,
+    timeout: std::time::Duration,
+) -> Result<(), String> {
+    match hook {
+        LifecycleHook::Command(command) => {
+            let exec = super::health_check::run_exec(docker, container_id, command.clone());
+            match tokio::time::timeout(timeout, exec).await {
+                Ok(outcome) => termination::command_outcome(outcome),
+                Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+            }
+        }
+        LifecycleHook::Http(http) => {
+            let address = super::health_check::container_address(docker, container_id).await;
+            termination::http_hook(address, http, timeout).await
+        }
+    }
+}
+
 async fn create_network(docker: Docker, network_name: String) -> Result<(), RuntimeError> {
     debug!("Start Docker create network: {}", network_name);
 
@@ -1,16 +1,16 @@
 use crate::api::dto::stats::InstanceStatsOutput;
-use crate::hypervisor::lifecycle_trait::{Log, RuntimeLifecycle, classify_log, extract_date};
+use crate::hypervisor::exec::{ExecRequest, ExecSession};
+use crate::hypervisor::lifecycle_trait::{
+    Log, LogStream, RuntimeLifecycle, classify_log, extract_date,
+};
 use crate::models::deployments::Deployment;
 use crate::models::health_check::HealthCheckStatus;
 use crate::models::volume::ResolvedMount;
 use crate::runtime::registry_auth::HostAuthSettings;
 use crate::scheduler::intentional_shutdowns::IntentionalShutdowns;
 use async_trait::async_trait;
-use axum::response::sse::Event;
 use bollard::Docker;
-use futures::stream::{self, Stream, StreamExt};
-use std::convert::Infallible;
-use std::pin::Pin;
+use futures::stream::{self, StreamExt};
 
 fn filter_instances(
     instances: Vec<(String, String)>,
@@ -50,6 +50,42 @@ impl DockerLifecycle {
         }
     }
 
+    /// Every container whose logs belong to the deployment, as `(container
+    /// id, instance name, container name)`: the instances, then their init
+    /// containers and sidecars, tagged with their name in the manifest.
+    async fn log_sources(
+        &self,
+        deployment_id: &str,
+        instance_filter: Option<&str>,
+    ) -> Vec<(String, String, Option<String>)> {
+        let instances = super::instances::list_instances_with_names(
+            &self.docker,
+            deployment_id.to_string(),
+            "all",
+        )
+        .await;
+        let members = super::container::list_members(
+            &self.docker,
+            crate::hypervisor::instance_group::MEMBER_OF_LABEL,
+            deployment_id,
+        )
+        .await;
+
+        let mut sources: Vec<(String, String, Option<String>)> =
+            filter_instances(instances, instance_filter)
+                .into_iter()
+                .map(|(id, name)| (id, name, None))
+                .collect();
+        for member in members {
+            if instance_filter.is_none_or(|f| {
+                member.id.starts_with(f) || member.group.contains(f) || member.container == f
+            }) {
+                sources.push((member.id, member.group, Some(member.container)));
+            }
+        }
+        sources
+    }
+
     /// Podman registers under its own key but shares the Docker-compatible
     /// lifecycle; crash detection is identical, so this delegates to `new`.
     pub fn new_podman(
@@ -107,21 +143,22 @@ impl RuntimeLifecycle for DockerLifecycle {
         deployment_id: &str,
         tail: Option<&str>,
         since: Option<i32>,
+        until: Option<i32>,
         instance_filter: Option<&str>,
     ) -> Vec<Log> {
-        let instances = self
-            .list_instances_with_names(deployment_id.to_string(), "all")
-            .await;
-        let filtered = filter_instances(instances, instance_filter);
-
         let mut logs = Vec::new();
-        for (instance_id, instance_name) in filtered {
-            let instance_logs = super::logs::logs(&self.docker, instance_id, tail, since).await;
+        for (container_id, instance_name, container) in
+            self.log_sources(deployment_id, instance_filter).await
+        {
+            let instance_logs =
+                super::logs::logs(&self.docker, container_id, tail, since, until).await;
             for message in instance_logs {
                 logs.push(Log {
                     instance: instance_name.clone(),
+                    container: container.clone(),
                     level: classify_log(&message),
                     timestamp: extract_date(&message),
+                    fields: Default::default(),
                     message,
                 });
             }
@@ -135,32 +172,26 @@ impl RuntimeLifecycle for DockerLifecycle {
         tail: Option<&str>,
         since: Option<i32>,
         instance_filter: Option<&str>,
-    ) -> Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>> {
-        let instances = self
-            .list_instances_with_names(deployment_id.to_string(), "all")
-            .await;
-        let filtered = filter_instances(instances, instance_filter);
+    ) -> LogStream {
+        let sources = self.log_sources(deployment_id, instance_filter).await;
 
-        if filtered.is_empty() {
+        if sources.is_empty() {
             return Box::pin(stream::empty());
         }
 
-        let mut streams: Vec<Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>> =
-            Vec::new();
+        let mut streams: Vec<LogStream> = Vec::new();
 
-        for (instance_id, instance_name) in filtered {
+        for (container_id, instance_name, container) in sources {
             let raw_stream =
-                super::logs::logs_stream(self.docker.clone(), instance_id, tail, since).await;
-
-            let mapped = raw_stream.map(move |line| {
-                let log = Log {
-                    instance: instance_name.clone(),
-                    level: classify_log(&line),
-                    timestamp: extract_date(&line),
-                    message: line,
-                };
-                let json = serde_json::to_string(&log).unwrap_or_default();
-                Ok(Event::default().data(json))
+                super::logs::logs_stream(self.docker.clone(), container_id, tail, since).await;
+
+            let mapped = raw_stream.map(move |line| Log {
+                instance: instance_name.clone(),
+                container: container.clone(),
+                level: classify_log(&line),
+                timestamp: extract_date(&line),
+                fields: Default::default(),
+                message: line,
             });
 
             streams.push(Box::pin(mapped));
@@ -181,6 +212,10 @@ impl RuntimeLifecycle for DockerLifecycle {
         super::health_check::execute_command_check(&self.docker, instance_id, command).await
     }
 
+    async fn exec(&self, instance_id: &str, request: ExecRequest) -> Result<ExecSession, String> {
+        super::exec::open_session(&self.docker, instance_id, request).await
+    }
+
     async fn get_instance_stats(&self, deployment_id: &str) -> Vec<InstanceStatsOutput> {
         let instances = self
             .list_instances_with_names(deployment_id.to_string(), "all")
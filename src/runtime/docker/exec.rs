@@ -0,0 +1,144 @@
+//! Interactive exec for the Docker runtime (and Podman, which shares it): a
+//! `docker exec` attached over the daemon's hijacked connection.
+
+use crate::hypervisor::exec::{self, ExecOutput, ExecRequest, ExecSession, TerminalSize};
+use bollard::Docker;
+use bollard::container::LogOutput;
+use bollard::exec::{CreateExecOptions, ResizeExecOptions, StartExecOptions, StartExecResults};
+use futures::StreamExt;
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+use tokio::io::AsyncWriteExt;
+
+pub(crate) async fn open_session(
+    docker: &Docker,
+    container_id: &str,
+    request: ExecRequest,
+) -> Result<ExecSession, String> {
+    let options = CreateExecOptions {
+        cmd: Some(request.command),
+        attach_stdin: Some(true),
+        attach_stdout: Some(true),
+        attach_stderr: Some(true),
+        tty: Some(request.tty),
+        ..Default::default()
+    };
+    let created = docker
+        .create_exec(container_id, options)
+        .await
+        .map_err(|e| format!("Failed to create exec: {}", e))?;
+
+    let start = StartExecOptions {
+        detach: false,
+        tty: request.tty,
+        output_capacity: None,
+    };
+    let (mut output, mut input) = match docker.start_exec(&created.id, Some(start)).await {
+        Ok(StartExecResults::Attached { output, input }) => (output, input),
+        Ok(StartExecResults::Detached) => return Err("exec started detached".to_string()),
+        Err(e) => return Err(format!("Failed to start exec: {}", e)),
+    };
+    if let Some(size) = request.size.filter(|_| request.tty) {
+        resize(docker, &created.id, size).await;
+    }
+
+    let (session, mut io) = exec::channels();
+
+    tokio::spawn(async move {
+        while let Some(bytes) = io.stdin.recv().await {
+            if input.write_all(&bytes).await.is_err() || input.flush().await.is_err() {
+                break;
+            }
+        }
+        // Half-close, so the process reads EOF.
+        let _ = input.shutdown().await;
+    });
+
+    let resizer = docker.clone();
+    let exec_id = created.id.clone();
+    tokio::spawn(async move {
+        while let Some(size) = io.resize.recv().await {
+            resize(&resizer, &exec_id, size).await;
+        }
+    });
+
+    let docker = docker.clone();
+    tokio::spawn(async move {
+        loop {
+            let chunk = tokio::select! {
+                chunk = output.next() => chunk,
+                // Nobody is listening any more, even to a quiet process.
+                _ = io.output.closed() => {
+                    kill_orphan(&docker, &created.id).await;
+                    return;
+                }
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
+            let message = match chunk {
+                Ok(LogOutput::StdOut { message }) | Ok(LogOutput::Console { message }) => {
+                    ExecOutput::Stdout(message.to_vec())
+                }
+                Ok(LogOutput::StdErr { message }) => ExecOutput::Stderr(message.to_vec()),
+                Ok(LogOutput::StdIn { .. }) => continue,
+                Err(_) => break,
+            };
+            if io.output.send(message).await.is_err() {
+                kill_orphan(&docker, &created.id).await;
+                return;
+            }
+        }
+        let exit_code = docker
+            .inspect_exec(&created.id)
+            .await
+            .ok()
+            .and_then(|inspect| inspect.exit_code);
+        let _ = io.output.send(ExecOutput::Exit(exit_code)).await;
+    });
+
+    Ok(session)
+}
+
+/// Kill an exec'd process nobody is attached to any more. Closing its stdin is
+/// not enough: `sleep` or `tail -f` never read it. The daemon has no call to
+/// stop an exec, so the host PID `inspect_exec` reports is signalled, once it
+/// is found in the container's cgroup: a daemon on another host, or a PID
+/// already reused, is left alone.
+async fn kill_orphan(docker: &Docker, exec_id: &str) {
+    let inspect = match docker.inspect_exec(exec_id).await {
+        Ok(inspect) => inspect,
+        Err(e) => {
+            debug!("Failed to inspect exec {}: {}", exec_id, e);
+            return;
+        }
+    };
+    let (Some(true), Some(pid), Some(container_id)) =
+        (inspect.running, inspect.pid, inspect.container_id)
+    else {
+        return;
+    };
+    let in_container = tokio::fs::read_to_string(format!("/proc/{}/cgroup", pid))
+        .await
+        .is_ok_and(|cgroup| cgroup.contains(&container_id));
+    if !in_container {
+        warn!(
+            "Exec {} lost its client but pid {} is not visible from here; it keeps running",
+            exec_id, pid
+        );
+        return;
+    }
+    if let Err(e) = kill(Pid::from_raw(pid as i32), Signal::SIGKILL) {
+        warn!("Failed to kill exec {} (pid {}): {}", exec_id, pid, e);
+    }
+}
+
+async fn resize(docker: &Docker, exec_id: &str, size: TerminalSize) {
+    let options = ResizeExecOptions {
+        height: size.rows,
+        width: size.cols,
+    };
+    if let Err(e) = docker.resize_exec(exec_id, options).await {
+        debug!("Failed to resize exec {}: {}", exec_id, e);
+    }
+}
@@ -76,6 +76,16 @@ pub(crate) async fn execute_command_check(
         }
     };
 
+    run_exec(docker, container_id, cmd_parts).await
+}
+
+/// Run `cmd` inside the container via `docker exec` and report its exit code
+/// the way [`execute_command_check`] does. Also runs the lifecycle hooks.
+pub(crate) async fn run_exec(
+    docker: &Docker,
+    container_id: &str,
+    cmd_parts: Vec<String>,
+) -> (HealthCheckStatus, Option<String>) {
     let exec_options = CreateExecOptions {
         cmd: Some(cmd_parts),
         attach_stdout: Some(true),
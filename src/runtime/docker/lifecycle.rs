@@ -1,9 +1,10 @@
-use super::container::{create_container, remove_container};
+use super::container::{create_container, remove_container, stop_container};
 use super::instances::list_instances;
 use crate::hypervisor::classifier::Disposition;
 use crate::hypervisor::error::RuntimeError;
+use crate::hypervisor::job::{self, Attempt};
 use crate::hypervisor::types::InstanceStatus;
-use crate::models::deployments::{Deployment, DeploymentStatus, MAX_RESTART_COUNT};
+use crate::models::deployments::{Deployment, DeploymentStatus, RestartMode};
 use crate::models::volume::ResolvedMount;
 use crate::runtime::registry_auth::HostAuthSettings;
 use crate::scheduler::intentional_shutdowns::IntentionalShutdowns;
@@ -49,7 +50,7 @@ pub(crate) async fn apply(
 
 /// Count each unexpectedly-exited container toward `restart_count`, record the
 /// crash on the deployment's pending events, and flip to `CrashLoopBackOff` once
-/// the count reaches `MAX_RESTART_COUNT`. Returns `true` when reconciling should
+/// the count has used up the deployment's failure budget. Returns `true` when reconciling should
 /// stop this tick — either because the bound was hit, or because an exit code is
 /// non-retryable (the worker can never start its program) and we fail fast onto
 /// the terminal status instead of burning the whole restart budget.
@@ -65,8 +66,24 @@ fn apply_unexpected_exits(deployment: &mut Deployment, exited: &[(String, Option
         // A clean exit (code 0) is a *success*, not a crash: the worker finished
         // its work. Converge to Completed without touching restart_count, so it
         // is never recreated — recreating an exit-0 container every tick is the
-        // infinite pull/recreate loop this guards against.
+        // infinite pull/recreate loop this guards against. Unless the worker's
+        // restart policy is `always`: it is then restarted like a crash, within
+        // the same budget and backoff.
         if let Disposition::Terminal(status @ DeploymentStatus::Completed) = disposition {
+            if deployment.restart_mode() == RestartMode::Always {
+                deployment.restart_count += 1;
+                deployment.emit_event(
+                    "info",
+                    format!(
+                        "Container {} exited cleanly (code 0); restarting it (restart {})",
+                        &container_id[..container_id.len().min(12)],
+                        deployment.restart_count
+                    ),
+                    "docker",
+                    Some("container_exited"),
+                );
+                continue;
+            }
             deployment.emit_event(
                 "info",
                 format!(
@@ -94,7 +111,7 @@ fn apply_unexpected_exits(deployment: &mut Deployment, exited: &[(String, Option
 
         // Fast-fail on a non-retryable exit (127 command-not-found, 126
         // not-executable): the container can never start its program, so
-        // retrying it up to MAX_RESTART_COUNT only delays the inevitable. Land
+        // retrying it until the budget is spent only delays the inevitable. Land
         // on the terminal status now.
         if let Disposition::Terminal(status) = disposition {
             deployment.emit_event(
@@ -112,7 +129,7 @@ fn apply_unexpected_exits(deployment: &mut Deployment, exited: &[(String, Option
         }
     }
 
-    if deployment.restart_count >= MAX_RESTART_COUNT {
+    if deployment.restarts_exhausted() {
         deployment.status = DeploymentStatus::CrashLoopBackOff;
         return true;
     }
@@ -195,7 +212,7 @@ fn handle_create_error(deployment: &mut Deployment, err: RuntimeError, increment
 
     if increment_restart && marker_needed {
         if terminal {
-            deployment.restart_count = MAX_RESTART_COUNT;
+            deployment.exhaust_restarts();
         } else {
             deployment.restart_count += 1;
         }
@@ -356,54 +373,45 @@ async fn handle_job_deployment(
         return deployment;
     }
 
-    // Terminal states: a one-shot job is done either way. Stop reconciling.
-    // `Failed` is the job equivalent of `CrashLoopBackOff` for workers — set
-    // below when restart_count hits MAX_RESTART_COUNT.
-    if matches!(
-        deployment.status,
-        DeploymentStatus::Completed | DeploymentStatus::Failed
-    ) {
-        return deployment;
+    let all_instances = list_instances(&docker, deployment.id.to_string(), "all").await;
+    let mut attempts = Vec::with_capacity(all_instances.len());
+    for instance_id in all_instances {
+        let attempt = observe_attempt(&docker, &instance_id).await;
+        attempts.push((instance_id, attempt));
     }
 
-    // Cap retries the same way workers do, but flip to `Failed` (terminal,
-    // one-shot) instead of `CrashLoopBackOff` (long-running). A job that
-    // never managed to boot after MAX_RESTART_COUNT tries is functionally
-    // done — surface that to the operator.
-    if deployment.restart_count >= MAX_RESTART_COUNT {
-        deployment.status = DeploymentStatus::Failed;
-        return deployment;
+    let plan = job::step(&mut deployment, &attempts, chrono::Utc::now(), "docker");
+    for instance_id in &plan.stop {
+        intentional_shutdowns.mark(instance_id.clone()).await;
+        stop_container(&docker, instance_id).await;
+    }
+    // Already exited: no `die` event to suppress, just reap them so they are
+    // not counted again next tick.
+    for instance_id in plan.remove {
+        remove_container(docker.clone(), instance_id).await;
     }
 
-    let all_instances = list_instances(&docker, deployment.id.to_string(), "all").await;
-
-    if let Some(instance_id) = all_instances.first() {
-        match check_container_status(docker.clone(), instance_id.clone()).await {
-            InstanceStatus::Running => {
-                deployment.status = DeploymentStatus::Running;
-            }
-            InstanceStatus::Completed => {
-                deployment.status = DeploymentStatus::Completed;
-            }
-            InstanceStatus::Failed => {
-                deployment.status = DeploymentStatus::Failed;
-            }
-        }
-    } else {
-        // No instance: either we've never created one (Creating / Pending)
-        // or the previous attempt left a transient error state (e.g.
-        // create_container_error after Docker rejected `start`). Either
-        // way, try again — the retry path is what eventually grows
-        // restart_count past MAX_RESTART_COUNT and converges to Failed.
+    // Start what the job still needs. Creation errors count toward the same
+    // retry budget as failed attempts, so a job that can never boot
+    // converges to `Failed` instead of looping on `create_container_error`.
+    for _ in 0..plan.start {
         match create_container(&mut deployment, &docker, &resolved_mounts, &host_auth).await {
             Ok(_) => {
                 deployment.status = DeploymentStatus::Running;
             }
             Err(err) => {
-                // `true` so the failure counts toward MAX_RESTART_COUNT.
-                // Without this, the job loops on `create_container_error`
-                // forever and the operator never sees a terminal state.
+                let terminal =
+                    crate::hypervisor::classifier::classify_create_error(&err).is_terminal();
                 handle_create_error(&mut deployment, err, true);
+                // `handle_create_error` spends the default budget on a terminal
+                // error; a larger `backoff_limit` must not turn it back into
+                // a retry.
+                if terminal
+                    && !crate::hypervisor::classifier::scheduler_skips_by_status(&deployment.status)
+                {
+                    job::exhaust_retries(&mut deployment);
+                }
+                break;
             }
         }
     }
@@ -422,7 +430,7 @@ async fn handle_worker_deployment(
     if deployment.status == DeploymentStatus::Deleted {
         debug!("{} marked as deleted. Remove all instances", deployment.id);
         remove_all_instances(&mut deployment, &docker, "worker", &intentional_shutdowns).await;
-    } else if deployment.restart_count >= MAX_RESTART_COUNT {
+    } else if deployment.restarts_exhausted() {
         deployment.status = DeploymentStatus::CrashLoopBackOff;
         return deployment;
     } else if deployment.status == DeploymentStatus::CrashLoopBackOff {
@@ -637,9 +645,30 @@ async fn check_container_status(docker: Docker, container_id: String) -> Instanc
     }
 }
 
+/// What became of one attempt of a job. An attempt the daemon cannot inspect
+/// any more counts as failed, with no exit code.
+async fn observe_attempt(docker: &Docker, container_id: &str) -> Attempt {
+    match docker
+        .inspect_container(container_id, None::<InspectContainerOptions>)
+        .await
+    {
+        Ok(info) => match info.state {
+            Some(state) if state.running == Some(true) => Attempt::Running,
+            Some(state) if state.exit_code == Some(0) => Attempt::Succeeded,
+            Some(state) => Attempt::Failed(state.exit_code),
+            None => Attempt::Failed(None),
+        },
+        Err(e) => {
+            debug!("Failed to inspect container {}: {}", container_id, e);
+            Attempt::Failed(None)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::deployments::{MAX_RESTART_COUNT, RestartPolicy};
     use std::collections::HashMap;
 
     fn worker_running() -> Deployment {
@@ -670,6 +699,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
@@ -800,6 +847,42 @@ mod tests {
         );
     }
 
+    /// The restart policy moves both ends: `always` restarts a clean exit
+    /// within the budget, `never` stops on the first crash, and a policy
+    /// without `max_restarts` keeps restarting past the default bound.
+    #[test]
+    fn restart_policy_decides_which_exits_restart() {
+        let with_policy = |mode: RestartMode, max_restarts: Option<u32>| {
+            let mut deployment = worker_running();
+            deployment.restart_policy = Some(RestartPolicy {
+                mode,
+                max_restarts,
+                backoff: None,
+            });
+            deployment
+        };
+
+        let mut always = with_policy(RestartMode::Always, Some(1));
+        let clean = vec![("container-done".to_string(), Some(0))];
+        assert!(!apply_unexpected_exits(&mut always, &clean));
+        assert_eq!(always.status, DeploymentStatus::Running);
+        assert_eq!(always.restart_count, 1);
+        assert!(apply_unexpected_exits(&mut always, &clean));
+        assert_eq!(always.status, DeploymentStatus::CrashLoopBackOff);
+
+        let mut never = with_policy(RestartMode::Never, None);
+        let crashed = vec![("container-1".to_string(), Some(1))];
+        assert!(apply_unexpected_exits(&mut never, &crashed));
+        assert_eq!(never.status, DeploymentStatus::CrashLoopBackOff);
+
+        let mut unlimited = with_policy(RestartMode::OnFailure, None);
+        for tick in 0..(MAX_RESTART_COUNT * 4) {
+            let exited = vec![(format!("container-{tick}"), Some(1))];
+            assert!(!apply_unexpected_exits(&mut unlimited, &exited));
+        }
+        assert_eq!(unlimited.status, DeploymentStatus::Running);
+    }
+
     /// Liveness gate: a container still running right after start may be
     /// promoted to Running...
     #[test]
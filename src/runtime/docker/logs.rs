@@ -28,6 +28,7 @@ pub(crate) async fn logs(
     container_id: String,
     tail: Option<&str>,
     since: Option<i32>,
+    until: Option<i32>,
 ) -> Vec<String> {
     match docker
         .inspect_container(&container_id, None::<InspectContainerOptions>)
@@ -53,6 +54,10 @@ pub(crate) async fn logs(
         builder = builder.since(since_value);
     }
 
+    if let Some(until_value) = until {
+        builder = builder.until(until_value);
+    }
+
     let options = builder.build();
     let mut logs_stream = docker.logs(&container_id, Some(options));
     let mut logs = vec![];
@@ -1,5 +1,6 @@
 mod container;
 pub(crate) mod docker_lifecycle;
+mod exec;
 mod health_check;
 mod instances;
 mod lifecycle;
@@ -17,14 +17,16 @@ use crate::config::server::FirecrackerConfig;
 use crate::hypervisor::classifier::{apply_vm_start_failure, classify_vm_start_error};
 use crate::hypervisor::cloud_init::{GuestMount, GuestNet};
 use crate::hypervisor::error::RuntimeError;
+use crate::hypervisor::exec::{ExecRequest, ExecSession};
 use crate::hypervisor::host_net::{InstanceNet, cid_for_instance};
 use crate::hypervisor::lifecycle_trait::{Log, RuntimeLifecycle, classify_log, extract_date};
 use crate::hypervisor::port_forwarder::{self, PortForwarder};
 use crate::hypervisor::tap::TapDevice;
+use crate::hypervisor::termination;
 use crate::hypervisor::volume_image as vol;
 use crate::hypervisor::vsock_client::{self, VsockError};
-use crate::models::deployments::{Deployment, DeploymentStatus, MAX_RESTART_COUNT};
-use crate::models::health_check::{HealthCheck, HealthCheckStatus};
+use crate::models::deployments::{Deployment, DeploymentStatus};
+use crate::models::health_check::HealthCheckStatus;
 use crate::models::volume::ResolvedMount;
 use crate::runtime::docker::tiny_id;
 use crate::runtime::firecracker::client::{
@@ -118,6 +120,11 @@ pub struct FirecrackerLifecycle {
     /// Live socat port-forwarders, keyed by instance id. Dropping the entry
     /// kills the socat process.
     port_forwarders: Mutex<HashMap<String, Vec<PortForwarder>>>,
+    /// ACPI shutdown grace period per deployment id. Refreshed from
+    /// `lifecycle.stop_grace_period` on every apply, so it is back after a
+    /// ring-server restart as soon as the deployment is reconciled. Absent:
+    /// the guest only gets a brief moment after `SendCtrlAltDel`.
+    grace_periods: Mutex<HashMap<String, std::time::Duration>>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -137,6 +144,7 @@ impl FirecrackerLifecycle {
             pids: Mutex::new(HashMap::new()),
             taps: Mutex::new(HashMap::new()),
             port_forwarders: Mutex::new(HashMap::new()),
+            grace_periods: Mutex::new(HashMap::new()),
         }
     }
 
@@ -542,8 +550,8 @@ impl FirecrackerLifecycle {
         // than leaving a black-hole port. `forwarders` is owned locally; its
         // Drop kills any socat already spawned on early return.
         if let Some(n) = &net_alloc {
-            let mut forwarders = Vec::with_capacity(deployment.ports.len());
-            for p in &deployment.ports {
+            let mut forwarders = Vec::with_capacity(deployment.runtime_bound_ports().len());
+            for p in deployment.runtime_bound_ports() {
                 match port_forwarder::spawn_forwarder(
                     &n.guest_ip,
                     p.published,
@@ -708,30 +716,33 @@ impl FirecrackerLifecycle {
             .await
             .map_err(|e| e.to_string())?;
 
-        // Attach a vsock device only when the deployment declares a `command`
-        // health check — its sole consumer, mirroring the Cloud Hypervisor
-        // runtime. The guest reaches `ring-agent` over AF_VSOCK; the host
-        // reaches it through the multiplexing Unix socket at `vsock_path`.
-        // Same boot-time limitation as CH: adding a `command` check to a
-        // running deployment only takes effect after the next VM restart, and
-        // whether one ever happens depends on the check's `on_failure`
-        // (`restart` heals itself; `alert`/`stop` do not). `execute_command_probe`
-        // detects a VM booted without the device and says so.
-        if needs_vsock(deployment) {
-            client
-                .put_vsock(&Vsock {
-                    vsock_id: "vsock0".to_string(),
-                    guest_cid: cid_for_instance(instance_id),
-                    uds_path: self.vsock_path(instance_id),
-                })
-                .await
-                .map_err(|e| e.to_string())?;
-        }
+        // Every VM gets a vsock device: `ring-agent` behind it runs `command`
+        // health checks and `ring deployment exec` sessions, and Firecracker
+        // cannot attach one after boot. The guest reaches the agent over
+        // AF_VSOCK; the host reaches it through the multiplexing Unix socket
+        // at `vsock_path`.
+        client
+            .put_vsock(&Vsock {
+                vsock_id: "vsock0".to_string(),
+                guest_cid: cid_for_instance(instance_id),
+                uds_path: self.vsock_path(instance_id),
+            })
+            .await
+            .map_err(|e| e.to_string())?;
 
         client.start().await.map_err(|e| e.to_string())?;
         Ok(())
     }
 
+    /// Record the ACPI grace period of `deployment`'s instances, or forget it.
+    fn remember_grace_period(&self, deployment: &Deployment) {
+        let mut map = self.grace_periods.lock().unwrap();
+        match termination::acpi_grace_period(deployment) {
+            Some(grace) => map.insert(deployment.id.clone(), grace),
+            None => map.remove(&deployment.id),
+        };
+    }
+
     /// Tear down one instance: kill the socat forwarders, gracefully shut the
     /// guest, kill the process, delete the host tap, and unlink the socket,
     /// rootfs copy and cidata ISO. Returns true if the instance is gone after.
@@ -741,18 +752,11 @@ impl FirecrackerLifecycle {
         // Drop the port-forwarders first so nothing still routes to the guest.
         self.port_forwarders.lock().unwrap().remove(instance_id);
 
-        // Best-effort graceful shutdown if the socket is still live.
-        if Path::new(&socket_path).exists() {
-            let client = FirecrackerClient::new(&socket_path);
-            let _ = client.send_ctrl_alt_del().await;
-            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-        }
-
-        // Kill the firecracker process. The PID lives in `pids` for instances
-        // this process booted; after a ring-server restart the map is empty, so
-        // fall back to finding the process by its `--api-sock` argument in
-        // /proc. Firecracker has no remote "delete VM" — killing the process is
-        // the only way to stop it — so this fallback is what makes teardown
+        // The firecracker process. The PID lives in `pids` for instances this
+        // process booted; after a ring-server restart the map is empty, so fall
+        // back to finding the process by its `--api-sock` argument in /proc.
+        // Firecracker has no remote "delete VM" — killing the process is the
+        // only way to stop it — so this fallback is what makes teardown
         // survive a restart.
         let pid = self
             .pids
@@ -761,6 +765,32 @@ impl FirecrackerLifecycle {
             .remove(instance_id)
             .map(|info| info.pid)
             .or_else(|| find_pid_by_socket(&socket_path));
+
+        // Best-effort graceful shutdown if the socket is still live. A guest
+        // that powers off makes firecracker exit, so with a grace period we
+        // wait for the process to go, up to that long.
+        if Path::new(&socket_path).exists() {
+            let client = FirecrackerClient::new(&socket_path);
+            let _ = client.send_ctrl_alt_del().await;
+            let grace = self
+                .grace_periods
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(deployment_id, _)| instance_id.starts_with(&format!("{}-", deployment_id)))
+                .map(|(_, grace)| *grace);
+            match (grace, pid) {
+                (Some(grace), Some(pid)) => {
+                    let target = Pid::from_raw(pid as i32);
+                    let deadline = tokio::time::Instant::now() + grace;
+                    while kill(target, None).is_ok() && tokio::time::Instant::now() < deadline {
+                        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                    }
+                }
+                _ => tokio::time::sleep(tokio::time::Duration::from_millis(300)).await,
+            }
+        }
+
         if let Some(pid) = pid {
             self.kill_pid(pid).await;
         }
@@ -974,9 +1004,9 @@ impl FirecrackerLifecycle {
             let tap = TapDevice::adopt(&net.tap_name);
             crate::hypervisor::host_nat::ensure_outbound_nat();
 
-            let mut forwarders = Vec::with_capacity(deployment.ports.len());
+            let mut forwarders = Vec::with_capacity(deployment.runtime_bound_ports().len());
             let mut ok = true;
-            for p in &deployment.ports {
+            for p in deployment.runtime_bound_ports() {
                 // A forwarder orphaned by an unclean exit of the previous
                 // ring-server is reparented to init and still holds the port;
                 // kill it first so the re-spawn below doesn't hit "address
@@ -1079,7 +1109,7 @@ impl FirecrackerLifecycle {
                         // instead of silently sitting in Creating. A terminal
                         // verdict already owns the status and must not be
                         // overwritten here.
-                        if !terminal && deployment.restart_count < MAX_RESTART_COUNT {
+                        if !terminal && !deployment.restarts_exhausted() {
                             deployment.status = DeploymentStatus::CreateContainerError;
                         }
                         break;
@@ -1444,16 +1474,6 @@ fn cmdline_matches_socket(cmdline: &[u8], socket_path: &str) -> bool {
 /// only to clean up the per-port multiplexing socket Firecracker creates.
 const RING_AGENT_VSOCK_PORT: u32 = 2375;
 
-/// A deployment needs a vsock device iff it declares a `command` health check
-/// — the only consumer of the in-guest agent today. Mirrors the gate in the
-/// Cloud Hypervisor runtime so the two behave identically.
-fn needs_vsock(deployment: &Deployment) -> bool {
-    deployment
-        .health_checks
-        .iter()
-        .any(|hc| matches!(hc, HealthCheck::Command { .. }))
-}
-
 /// Render a CIDR prefix length as a dotted-quad netmask (e.g. 30 -> 255.255.255.252)
 /// for the kernel ipconfig `ip=` parameter, which wants the mask in that form.
 fn prefix_to_netmask(prefix_len: u8) -> String {
@@ -1526,6 +1546,7 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
         mut deployment: Deployment,
         resolved_mounts: Vec<ResolvedMount>,
     ) -> Deployment {
+        self.remember_grace_period(&deployment);
         if deployment.status == DeploymentStatus::Deleted {
             // Re-adopt first so an instance inherited from a previous
             // ring-server (its forwarders orphaned, not in our maps) is brought
@@ -1595,6 +1616,7 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
         deployment_id: &str,
         tail: Option<&str>,
         since: Option<i32>,
+        until: Option<i32>,
         instance_filter: Option<&str>,
     ) -> Vec<Log> {
         let mut logs = Vec::new();
@@ -1605,12 +1627,15 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
                 continue;
             }
             let path = PathBuf::from(self.console_log_path(&instance_id));
-            let lines = crate::hypervisor::console_logs::read_lines(&path, tail, since).await;
+            let lines =
+                crate::hypervisor::console_logs::read_lines(&path, tail, since, until).await;
             for message in lines {
                 logs.push(Log {
                     instance: instance_id.clone(),
+                    container: None,
                     level: classify_log(&message),
                     timestamp: extract_date(&message),
+                    fields: Default::default(),
                     message,
                 });
             }
@@ -1619,7 +1644,7 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
     }
 
     /// Follow the serial console of every matching instance, equivalent to
-    /// `tail -f`, emitting each new line as an SSE event. Mirrors
+    /// `tail -f`, emitting each new line as a `Log`. Mirrors
     /// `get_logs` instance selection (disk scan + optional filter).
     async fn stream_logs(
         &self,
@@ -1627,12 +1652,7 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
         tail: Option<&str>,
         since: Option<i32>,
         instance_filter: Option<&str>,
-    ) -> std::pin::Pin<
-        Box<
-            dyn futures::Stream<Item = Result<axum::response::sse::Event, std::convert::Infallible>>
-                + Send,
-        >,
-    > {
+    ) -> crate::hypervisor::lifecycle_trait::LogStream {
         use futures::stream::{self, StreamExt};
 
         let filtered: Vec<String> = self
@@ -1648,15 +1668,7 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
             return Box::pin(stream::empty());
         }
 
-        let mut streams: Vec<
-            std::pin::Pin<
-                Box<
-                    dyn futures::Stream<
-                            Item = Result<axum::response::sse::Event, std::convert::Infallible>,
-                        > + Send,
-                >,
-            >,
-        > = Vec::new();
+        let mut streams: Vec<crate::hypervisor::lifecycle_trait::LogStream> = Vec::new();
 
         for instance_id in filtered {
             let path = PathBuf::from(self.console_log_path(&instance_id));
@@ -1668,15 +1680,13 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
             )
             .await;
 
-            let mapped = raw.map(move |line| {
-                let log = Log {
-                    instance: owned_id.clone(),
-                    level: classify_log(&line),
-                    timestamp: extract_date(&line),
-                    message: line,
-                };
-                let json = serde_json::to_string(&log).unwrap_or_default();
-                Ok(axum::response::sse::Event::default().data(json))
+            let mapped = raw.map(move |line| Log {
+                instance: owned_id.clone(),
+                container: None,
+                level: classify_log(&line),
+                timestamp: extract_date(&line),
+                fields: Default::default(),
+                message: line,
             });
 
             streams.push(Box::pin(mapped));
@@ -1755,6 +1765,23 @@ impl RuntimeLifecycle for FirecrackerLifecycle {
         }
     }
 
+    /// Open an interactive session with `ring-agent` in the guest, over the
+    /// same vsock transport as `execute_command_probe`.
+    async fn exec(&self, instance_id: &str, request: ExecRequest) -> Result<ExecSession, String> {
+        let cid = cid_for_instance(instance_id);
+        let uds_path = self.vsock_path(instance_id);
+        match vsock_client::session_uds(cid, &uds_path, &request).await {
+            Ok(session) => Ok(session),
+            Err(VsockError::Connect { cid, source }) => Err(vsock_client::connect_failure_message(
+                "firecracker",
+                cid,
+                &source.to_string(),
+                Path::new(&uds_path).exists(),
+            )),
+            Err(e) => Err(format!("vsock session failed: {}", e)),
+        }
+    }
+
     /// Per-instance CPU / memory / network / disk / pid stats for every running
     /// instance of the deployment. Reads host-side `/proc/<pid>/*` and the
     /// per-VM tap counters via the shared `stats` helpers — same source and
@@ -2295,6 +2322,24 @@ mod tests {
             pending_events: vec![],
             parent_id: None,
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
@@ -1,12 +1,12 @@
 //! Horizontal autoscaling: pick a deployment's instance count from its observed
-//! CPU usage.
+//! load: CPU, memory, and gauges the workload exposes.
 //!
 //! The decision is deliberately conservative, because the cost of a wrong one is
 //! asymmetric: scaling up late costs latency for a few seconds, scaling up and
 //! down repeatedly (flapping) costs container churn, cold starts, and log noise
 //! for as long as nobody notices.
 //!
-//! Three mechanisms keep it calm:
+//! Four mechanisms keep it calm:
 //!
 //! * **One step at a time.** A decision moves the count by ±1, never straight to
 //!   a computed target. The runtimes already create one container per tick, so
@@ -17,6 +17,9 @@
 //!   under-provisioning hurts users, over-provisioning only costs resources, and
 //!   a slow scale-down is what stops a load that oscillates around the target
 //!   from driving the instance count up and down with it.
+//! * **Unanimity to scale down.** With several targets, any one of them above
+//!   its band adds an instance, but shedding one needs every target measured
+//!   and below its band: the busiest resource decides.
 //!
 //! State is in-memory and non-persistent, like [`super::healthy_window`] and
 //! [`super::backoff`]. At process restart the cooldowns start over: the first
@@ -24,21 +27,29 @@
 //! bounded by the policy and by one step.
 
 use crate::models::deployments::Autoscale;
+use crate::models::health_check::HealthCheck;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-/// How far from `target_cpu` still counts as "on target", in percentage points.
+/// How far from `target_cpu` or `target_memory` still counts as "on target",
+/// in percentage points, when the policy sets no `tolerance`.
 ///
 /// Without this, a deployment sitting exactly at its target scales up and down
 /// forever, because measured CPU is never exactly equal to anything.
 const DEAD_BAND_PERCENT: f64 = 10.0;
 
-/// Minimum time between a scale-up and the next decision.
+/// The same for a scraped metric, as a fraction of its target: a gauge has no
+/// natural scale points could be counted in.
+const DEFAULT_METRIC_TOLERANCE: f64 = 0.1;
+
+/// Minimum time between a scale-up and the next decision, unless the policy
+/// sets `cooldown_up`.
 const SCALE_UP_COOLDOWN: Duration = Duration::from_secs(60);
 
-/// Minimum time between a scale-down and the next decision. Longer than the
-/// scale-up cooldown on purpose: shedding capacity too eagerly is what turns a
-/// fluctuating load into a flapping instance count.
+/// Minimum time between a scale-down and the next decision, unless the policy
+/// sets `cooldown_down`. Longer than the scale-up cooldown on purpose: shedding
+/// capacity too eagerly is what turns a fluctuating load into a flapping
+/// instance count.
 const SCALE_DOWN_COOLDOWN: Duration = Duration::from_secs(300);
 
 /// What the autoscaler decided for one deployment on one tick.
@@ -50,11 +61,105 @@ pub(crate) enum Decision {
     Hold,
 }
 
+/// What was measured of one deployment, **per instance**. Each field is `None`
+/// when no usable measurement exists for it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Observation {
+    /// Average CPU percentage of an instance.
+    pub(crate) cpu_percent: Option<f64>,
+    /// Average memory usage of an instance, as a percentage of
+    /// `requests.memory`.
+    pub(crate) memory_percent: Option<f64>,
+    /// One value per entry of the policy's `metrics`, in the same order.
+    pub(crate) metrics: Vec<Option<f64>>,
+}
+
+/// One target of a policy, next to what was measured for it.
+struct Signal {
+    label: String,
+    value: Option<f64>,
+    target: f64,
+    /// Half-width of the dead band around `target`.
+    band: f64,
+}
+
+/// The policy's targets, each with its measurement and dead band.
+fn signals(policy: &Autoscale, observed: &Observation) -> Vec<Signal> {
+    let band = |target: f64, points: Option<f64>| match policy.tolerance {
+        Some(tolerance) => target * tolerance,
+        None => points.unwrap_or(target * DEFAULT_METRIC_TOLERANCE),
+    };
+    let mut signals = Vec::new();
+    if let Some(target) = policy.target_cpu {
+        signals.push(Signal {
+            label: "CPU".to_string(),
+            value: observed.cpu_percent,
+            target,
+            band: band(target, Some(DEAD_BAND_PERCENT)),
+        });
+    }
+    if let Some(target) = policy.target_memory {
+        signals.push(Signal {
+            label: "memory".to_string(),
+            value: observed.memory_percent,
+            target,
+            band: band(target, Some(DEAD_BAND_PERCENT)),
+        });
+    }
+    for (index, metric) in policy.metrics.iter().enumerate() {
+        signals.push(Signal {
+            label: metric.name.clone(),
+            value: observed.metrics.get(index).copied().flatten(),
+            target: metric.target,
+            band: band(metric.target, None),
+        });
+    }
+    // NaN, infinities and negative readings are broken measurements, not load.
+    for signal in &mut signals {
+        signal.value = signal.value.filter(|v| v.is_finite() && *v >= 0.0);
+    }
+    signals
+}
+
+/// What the autoscaler acted on, for logs and events, e.g. `CPU 85.0% (target
+/// 70%), queue_backlog 120.0 (target 50)`.
+pub(crate) fn describe(policy: &Autoscale, observed: &Observation) -> String {
+    signals(policy, observed)
+        .iter()
+        .map(|signal| {
+            let unit = if signal.label == "CPU" || signal.label == "memory" {
+                "%"
+            } else {
+                ""
+            };
+            match signal.value {
+                Some(value) => format!(
+                    "{} {:.1}{} (target {}{})",
+                    signal.label, value, unit, signal.target, unit
+                ),
+                None => format!(
+                    "{} unknown (target {}{})",
+                    signal.label, signal.target, unit
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `raw` as a duration, `default` when unset or unparsable.
+fn cooldown(raw: Option<&String>, default: Duration) -> Duration {
+    raw.and_then(|d| HealthCheck::parse_duration(d).ok())
+        .unwrap_or(default)
+}
+
 /// Per-deployment cooldown clock.
 #[derive(Default)]
 pub(crate) struct Autoscaler {
     /// `deployment_id -> when the last scaling action was taken`.
     last_action: HashMap<String, Instant>,
+    /// `deployment_id -> why its scale-up is held back`, until it scales.
+    held_back: HashMap<String, String>,
 }
 
 impl Autoscaler {
@@ -64,26 +169,25 @@ impl Autoscaler {
 
     /// Decide what to do with one deployment.
     ///
-    /// `cpu_percent` must be the **per-instance average**, not the deployment
-    /// total. `stats_cache` sums CPU across instances (correct for the
-    /// Prometheus gauge, wrong as a setpoint), so the caller divides by the
-    /// instance count — see [`average_cpu`]. Comparing a sum against a target
-    /// would be a runaway: three instances at 30% sum to 90%, which "exceeds" a
-    /// 70% target, so the autoscaler adds a fourth, pushing the sum higher
-    /// still, all the way to `max`, while every instance sits idle.
+    /// `observed` must hold **per-instance averages**, not deployment totals.
+    /// `stats_cache` sums CPU across instances (correct for the Prometheus
+    /// gauge, wrong as a setpoint), so the caller divides by the instance
+    /// count. Comparing a sum against a target would be a runaway: three
+    /// instances at 30% sum to 90%, which "exceeds" a 70% target, so the
+    /// autoscaler adds a fourth, pushing the sum higher still, all the way to
+    /// `max`, while every instance sits idle.
     ///
-    /// `cpu_percent` is `None` when no usable measurement exists (the runtime
-    /// was unreachable, the deployment has no running instance yet, or the
-    /// stats snapshot has not covered it). In that case the answer is always
-    /// [`Decision::Hold`]: acting on a missing measurement would mean scaling
-    /// blind, and "no data" most often means "nothing is running", where
-    /// scaling down is exactly wrong.
+    /// A target without a usable measurement (the runtime was unreachable, the
+    /// deployment has no running instance yet, the metric endpoint did not
+    /// answer) never adds an instance and blocks removing one: acting on a
+    /// missing measurement would mean scaling blind, and "no data" most often
+    /// means "nothing is running", where scaling down is exactly wrong.
     pub(crate) fn decide(
         &mut self,
         deployment_id: &str,
         policy: &Autoscale,
         current: u32,
-        cpu_percent: Option<f64>,
+        observed: &Observation,
         now: Instant,
     ) -> Decision {
         // A policy edited to a narrower range takes effect even while usage
@@ -95,12 +199,14 @@ impl Autoscaler {
             return Decision::ScaleTo(clamped);
         }
 
-        let Some(cpu) = cpu_percent.filter(|c| c.is_finite() && *c >= 0.0) else {
-            return Decision::Hold;
-        };
-
-        let above = cpu > policy.target_cpu + DEAD_BAND_PERCENT;
-        let below = cpu < policy.target_cpu - DEAD_BAND_PERCENT;
+        let signals = signals(policy, observed);
+        let above = signals
+            .iter()
+            .any(|s| s.value.is_some_and(|v| v > s.target + s.band));
+        let below = !signals.is_empty()
+            && signals
+                .iter()
+                .all(|s| s.value.is_some_and(|v| v < s.target - s.band));
 
         let target = if above {
             current.saturating_add(1)
@@ -118,9 +224,9 @@ impl Autoscaler {
         }
 
         let cooldown = if target > current {
-            SCALE_UP_COOLDOWN
+            cooldown(policy.cooldown_up.as_ref(), SCALE_UP_COOLDOWN)
         } else {
-            SCALE_DOWN_COOLDOWN
+            cooldown(policy.cooldown_down.as_ref(), SCALE_DOWN_COOLDOWN)
         };
 
         if let Some(last) = self.last_action.get(deployment_id)
@@ -133,10 +239,28 @@ impl Autoscaler {
         Decision::ScaleTo(target)
     }
 
-    /// Drop bookkeeping for deployments that no longer exist, so the map does
+    /// Record that a scale-up of `deployment_id` was refused for `reason`.
+    /// True when the refusal starts or its reason changed: a namespace at its
+    /// quota is refused on every tick, and only that is worth an event.
+    pub(crate) fn hold_back(&mut self, deployment_id: &str, reason: &str) -> bool {
+        if self.held_back.get(deployment_id).map(String::as_str) == Some(reason) {
+            return false;
+        }
+        self.held_back
+            .insert(deployment_id.to_string(), reason.to_string());
+        true
+    }
+
+    /// `deployment_id` scaled: a later refusal starts anew.
+    pub(crate) fn release(&mut self, deployment_id: &str) {
+        self.held_back.remove(deployment_id);
+    }
+
+    /// Drop bookkeeping for deployments that no longer exist, so the maps do
     /// not grow for the life of the process.
     pub(crate) fn retain_known(&mut self, live_ids: &[String]) {
         self.last_action.retain(|id, _| live_ids.contains(id));
+        self.held_back.retain(|id, _| live_ids.contains(id));
     }
 }
 
@@ -148,21 +272,29 @@ mod tests {
         Autoscale {
             min,
             max,
-            target_cpu,
+            target_cpu: Some(target_cpu),
+            ..Default::default()
+        }
+    }
+
+    fn cpu_at(cpu: f64) -> Observation {
+        Observation {
+            cpu_percent: Some(cpu),
+            ..Default::default()
         }
     }
 
     #[test]
     fn scales_up_when_cpu_is_above_the_dead_band() {
         let mut a = Autoscaler::new();
-        let d = a.decide("d", &policy(1, 5, 70.0), 2, Some(95.0), Instant::now());
+        let d = a.decide("d", &policy(1, 5, 70.0), 2, &cpu_at(95.0), Instant::now());
         assert_eq!(d, Decision::ScaleTo(3), "high CPU must add one instance");
     }
 
     #[test]
     fn scales_down_when_cpu_is_below_the_dead_band() {
         let mut a = Autoscaler::new();
-        let d = a.decide("d", &policy(1, 5, 70.0), 3, Some(10.0), Instant::now());
+        let d = a.decide("d", &policy(1, 5, 70.0), 3, &cpu_at(10.0), Instant::now());
         assert_eq!(d, Decision::ScaleTo(2), "idle CPU must remove one instance");
     }
 
@@ -170,7 +302,7 @@ mod tests {
     fn holds_inside_the_dead_band() {
         let mut a = Autoscaler::new();
         // 72% against a 70% target: normal jitter, not a signal.
-        let d = a.decide("d", &policy(1, 5, 70.0), 3, Some(72.0), Instant::now());
+        let d = a.decide("d", &policy(1, 5, 70.0), 3, &cpu_at(72.0), Instant::now());
         assert_eq!(d, Decision::Hold);
     }
 
@@ -179,7 +311,7 @@ mod tests {
         // Massively overloaded: still +1, not a jump to max. The next ticks
         // keep climbing while the load stays high.
         let mut a = Autoscaler::new();
-        let d = a.decide("d", &policy(1, 10, 50.0), 2, Some(100.0), Instant::now());
+        let d = a.decide("d", &policy(1, 10, 50.0), 2, &cpu_at(100.0), Instant::now());
         assert_eq!(d, Decision::ScaleTo(3));
     }
 
@@ -189,12 +321,12 @@ mod tests {
         let now = Instant::now();
         // At max under heavy load: hold, and do not burn the cooldown.
         assert_eq!(
-            a.decide("up", &policy(1, 3, 70.0), 3, Some(99.0), now),
+            a.decide("up", &policy(1, 3, 70.0), 3, &cpu_at(99.0), now),
             Decision::Hold
         );
         // At min while idle: hold.
         assert_eq!(
-            a.decide("down", &policy(2, 5, 70.0), 2, Some(1.0), now),
+            a.decide("down", &policy(2, 5, 70.0), 2, &cpu_at(1.0), now),
             Decision::Hold
         );
     }
@@ -206,11 +338,11 @@ mod tests {
         let mut a = Autoscaler::new();
         let now = Instant::now();
         assert_eq!(
-            a.decide("d", &policy(1, 5, 70.0), 3, None, now),
+            a.decide("d", &policy(1, 5, 70.0), 3, &Observation::default(), now),
             Decision::Hold
         );
         assert_eq!(
-            a.decide("d", &policy(1, 5, 70.0), 3, Some(f64::NAN), now),
+            a.decide("d", &policy(1, 5, 70.0), 3, &cpu_at(f64::NAN), now),
             Decision::Hold
         );
     }
@@ -220,19 +352,19 @@ mod tests {
         let mut a = Autoscaler::new();
         let t0 = Instant::now();
         assert_eq!(
-            a.decide("d", &policy(1, 9, 70.0), 2, Some(99.0), t0),
+            a.decide("d", &policy(1, 9, 70.0), 2, &cpu_at(99.0), t0),
             Decision::ScaleTo(3)
         );
         // One second later the load is still high, but the cooldown holds.
         let t1 = t0 + Duration::from_secs(1);
         assert_eq!(
-            a.decide("d", &policy(1, 9, 70.0), 3, Some(99.0), t1),
+            a.decide("d", &policy(1, 9, 70.0), 3, &cpu_at(99.0), t1),
             Decision::Hold
         );
         // Past the cooldown it may act again.
         let t2 = t0 + SCALE_UP_COOLDOWN + Duration::from_secs(1);
         assert_eq!(
-            a.decide("d", &policy(1, 9, 70.0), 3, Some(99.0), t2),
+            a.decide("d", &policy(1, 9, 70.0), 3, &cpu_at(99.0), t2),
             Decision::ScaleTo(4)
         );
     }
@@ -242,19 +374,19 @@ mod tests {
         let mut a = Autoscaler::new();
         let t0 = Instant::now();
         assert_eq!(
-            a.decide("d", &policy(1, 9, 70.0), 4, Some(5.0), t0),
+            a.decide("d", &policy(1, 9, 70.0), 4, &cpu_at(5.0), t0),
             Decision::ScaleTo(3)
         );
         // The scale-up cooldown has elapsed, but a scale-down needs longer.
         let t1 = t0 + SCALE_UP_COOLDOWN + Duration::from_secs(1);
         assert_eq!(
-            a.decide("d", &policy(1, 9, 70.0), 3, Some(5.0), t1),
+            a.decide("d", &policy(1, 9, 70.0), 3, &cpu_at(5.0), t1),
             Decision::Hold,
             "shedding capacity must not be as eager as adding it"
         );
         let t2 = t0 + SCALE_DOWN_COOLDOWN + Duration::from_secs(1);
         assert_eq!(
-            a.decide("d", &policy(1, 9, 70.0), 3, Some(5.0), t2),
+            a.decide("d", &policy(1, 9, 70.0), 3, &cpu_at(5.0), t2),
             Decision::ScaleTo(2)
         );
     }
@@ -264,7 +396,7 @@ mod tests {
         // max lowered from 10 to 4 while 8 instances run: converge immediately,
         // without waiting for the load to move.
         let mut a = Autoscaler::new();
-        let d = a.decide("d", &policy(1, 4, 70.0), 8, Some(72.0), Instant::now());
+        let d = a.decide("d", &policy(1, 4, 70.0), 8, &cpu_at(72.0), Instant::now());
         assert_eq!(d, Decision::ScaleTo(4));
     }
 
@@ -283,7 +415,7 @@ mod tests {
         for tick in 0..20 {
             let cpu = if tick % 2 == 0 { 95.0 } else { 5.0 };
             let now = t0 + Duration::from_secs(tick * 10);
-            if let Decision::ScaleTo(n) = a.decide("d", &p, current, Some(cpu), now) {
+            if let Decision::ScaleTo(n) = a.decide("d", &p, current, &cpu_at(cpu), now) {
                 if n > current {
                     ups += 1;
                 } else {
@@ -323,7 +455,7 @@ mod tests {
         for tick in 0..10 {
             let per_instance = 30.0;
             let now = t0 + Duration::from_secs(tick * 120);
-            if let Decision::ScaleTo(n) = a.decide("d", &p, current, Some(per_instance), now) {
+            if let Decision::ScaleTo(n) = a.decide("d", &p, current, &cpu_at(per_instance), now) {
                 current = n;
             }
         }
@@ -338,12 +470,103 @@ mod tests {
     fn retain_known_drops_deleted_deployments() {
         let mut a = Autoscaler::new();
         let now = Instant::now();
-        a.decide("gone", &policy(1, 5, 70.0), 2, Some(99.0), now);
-        a.decide("kept", &policy(1, 5, 70.0), 2, Some(99.0), now);
+        a.decide("gone", &policy(1, 5, 70.0), 2, &cpu_at(99.0), now);
+        a.decide("kept", &policy(1, 5, 70.0), 2, &cpu_at(99.0), now);
+        a.hold_back("gone", "namespace quota: cpu");
 
         a.retain_known(&["kept".to_string()]);
 
         assert!(!a.last_action.contains_key("gone"));
         assert!(a.last_action.contains_key("kept"));
+        assert!(!a.held_back.contains_key("gone"));
+    }
+
+    #[test]
+    fn a_refusal_is_news_when_it_starts_or_changes() {
+        let mut a = Autoscaler::new();
+        assert!(a.hold_back("api", "namespace quota: cpu"));
+        assert!(!a.hold_back("api", "namespace quota: cpu"));
+        assert!(a.hold_back("api", "namespace quota: memory"));
+
+        a.release("api");
+        assert!(a.hold_back("api", "namespace quota: memory"));
+    }
+
+    fn queue_policy() -> Autoscale {
+        serde_json::from_value(serde_json::json!({
+            "min": 1, "max": 10, "target_cpu": 70.0,
+            "metrics": [{"name": "queue_backlog", "port": 9100, "target": 50.0}],
+            "cooldown_up": "10s", "cooldown_down": "30s", "tolerance": 0.2
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn any_target_above_its_band_scales_up() {
+        // Idle CPU, but a backlog of 80 per instance against 50: the queue
+        // decides.
+        let mut a = Autoscaler::new();
+        let observed = Observation {
+            cpu_percent: Some(5.0),
+            memory_percent: None,
+            metrics: vec![Some(80.0)],
+        };
+        let d = a.decide("d", &queue_policy(), 3, &observed, Instant::now());
+        assert_eq!(d, Decision::ScaleTo(4));
+    }
+
+    #[test]
+    fn scaling_down_needs_every_target_measured_and_below() {
+        let mut a = Autoscaler::new();
+        let now = Instant::now();
+        // 45 is inside the ±20% band of 50: on target, hold.
+        let on_target = Observation {
+            cpu_percent: Some(5.0),
+            memory_percent: None,
+            metrics: vec![Some(45.0)],
+        };
+        assert_eq!(
+            a.decide("d", &queue_policy(), 3, &on_target, now),
+            Decision::Hold
+        );
+        // The metric endpoint did not answer: never shed capacity blind.
+        let unknown = Observation {
+            cpu_percent: Some(5.0),
+            memory_percent: None,
+            metrics: vec![None],
+        };
+        assert_eq!(
+            a.decide("d", &queue_policy(), 3, &unknown, now),
+            Decision::Hold
+        );
+        let idle = Observation {
+            cpu_percent: Some(5.0),
+            memory_percent: None,
+            metrics: vec![Some(2.0)],
+        };
+        assert_eq!(
+            a.decide("d", &queue_policy(), 3, &idle, now),
+            Decision::ScaleTo(2)
+        );
+    }
+
+    #[test]
+    fn a_policy_sets_its_own_cooldowns_and_memory_target() {
+        let mut a = Autoscaler::new();
+        let mut policy = queue_policy();
+        policy.target_cpu = None;
+        policy.metrics.clear();
+        policy.target_memory = Some(80.0);
+        let busy = Observation {
+            memory_percent: Some(99.0),
+            ..Default::default()
+        };
+        let t0 = Instant::now();
+        assert_eq!(a.decide("d", &policy, 2, &busy, t0), Decision::ScaleTo(3));
+        let t1 = t0 + Duration::from_secs(5);
+        assert_eq!(a.decide("d", &policy, 3, &busy, t1), Decision::Hold);
+        let t2 = t0 + Duration::from_secs(11);
+        assert_eq!(a.decide("d", &policy, 3, &busy, t2), Decision::ScaleTo(4));
+        assert_eq!(describe(&policy, &busy), "memory 99.0% (target 80%)");
     }
 }
@@ -3,7 +3,8 @@
 //! When a runtime fails to apply a deployment (image pull, VM boot, etc.) the
 //! scheduler should not retry on the next 1s tick — that burns CPU and clouds
 //! logs. Instead, each failure schedules the next attempt with exponential
-//! backoff (1, 2, 4, 8, 16, 32, capped at 60s). The state lives in the
+//! backoff, within the bounds of the deployment's `restart_policy.backoff`
+//! (1, 2, 4, 8, 16, 32, capped at 60s by default). The state lives in the
 //! scheduler so every runtime benefits without duplicating the logic.
 //!
 //! State is intentionally non-persistent: at process restart all deployments
@@ -13,8 +14,6 @@
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-const MAX_BACKOFF_SECS: u64 = 60;
-
 #[derive(Default)]
 pub(crate) struct RetryBackoff {
     next_attempt: HashMap<String, Instant>,
@@ -35,17 +34,22 @@ impl RetryBackoff {
     }
 
     /// Schedule the next retry. `attempt` is `restart_count` (already
-    /// incremented by the runtime). attempt=1 → 1s, 2 → 2s, 3 → 4s, …,
-    /// capped at MAX_BACKOFF_SECS.
-    pub(crate) fn arm(&mut self, deployment_id: &str, attempt: u32) {
-        let secs = 1u64
+    /// incremented by the runtime). attempt=1 → `initial`, 2 → twice that,
+    /// 3 → four times, …, capped at `max`.
+    pub(crate) fn arm(
+        &mut self,
+        deployment_id: &str,
+        attempt: u32,
+        initial: Duration,
+        max: Duration,
+    ) {
+        let delay = 1u32
             .checked_shl(attempt.saturating_sub(1))
-            .unwrap_or(MAX_BACKOFF_SECS)
-            .min(MAX_BACKOFF_SECS);
-        self.next_attempt.insert(
-            deployment_id.to_string(),
-            Instant::now() + Duration::from_secs(secs),
-        );
+            .and_then(|factor| initial.checked_mul(factor))
+            .unwrap_or(max)
+            .min(max);
+        self.next_attempt
+            .insert(deployment_id.to_string(), Instant::now() + delay);
     }
 
     /// Drop any pending backoff for a deployment (success, terminal status,
@@ -59,6 +63,9 @@ impl RetryBackoff {
 mod tests {
     use super::*;
 
+    const INITIAL: Duration = Duration::from_secs(1);
+    const MAX: Duration = Duration::from_secs(60);
+
     #[test]
     fn unknown_deployment_is_not_blocked() {
         let b = RetryBackoff::new();
@@ -68,14 +75,14 @@ mod tests {
     #[test]
     fn arm_blocks_until_window_elapses() {
         let mut b = RetryBackoff::new();
-        b.arm("d1", 1);
+        b.arm("d1", 1, INITIAL, MAX);
         assert!(b.is_blocked("d1"));
     }
 
     #[test]
     fn clear_unblocks() {
         let mut b = RetryBackoff::new();
-        b.arm("d1", 5);
+        b.arm("d1", 5, INITIAL, MAX);
         b.clear("d1");
         assert!(!b.is_blocked("d1"));
     }
@@ -86,10 +93,24 @@ mod tests {
         // check the arithmetic via the arm function's effect on stored time.
         let mut b = RetryBackoff::new();
         let before = Instant::now();
-        b.arm("d1", 10); // 2^9 = 512, capped to 60
+        b.arm("d1", 10, INITIAL, MAX); // 2^9 = 512, capped to 60
         let next = *b.next_attempt.get("d1").unwrap();
         let delta = next.duration_since(before);
-        assert!(delta <= Duration::from_secs(MAX_BACKOFF_SECS + 1));
-        assert!(delta >= Duration::from_secs(MAX_BACKOFF_SECS - 1));
+        assert!(delta <= MAX + Duration::from_secs(1));
+        assert!(delta >= MAX - Duration::from_secs(1));
+    }
+
+    #[test]
+    fn backoff_follows_the_policy_bounds() {
+        let mut b = RetryBackoff::new();
+        let before = Instant::now();
+        b.arm("d1", 3, Duration::from_secs(5), Duration::from_secs(600));
+        let delta = b.next_attempt["d1"].duration_since(before);
+        assert!(delta >= Duration::from_secs(20) && delta <= Duration::from_secs(21));
+
+        // Far past any shift: still the cap, not an overflow.
+        b.arm("d1", 200, Duration::from_secs(5), Duration::from_secs(600));
+        let delta = b.next_attempt["d1"].duration_since(before);
+        assert!(delta >= Duration::from_secs(599) && delta <= Duration::from_secs(601));
     }
 }
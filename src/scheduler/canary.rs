@@ -0,0 +1,180 @@
+//! Canary rollouts (`strategy: {type: canary}`).
+//!
+//! A canary is a rolling-update child held at a few instances next to its
+//! parent for an analysis window. Its progress is recorded as deployment events
+//! on the child rather than in scheduler memory, the same way a rolling restart
+//! paces itself: `canary_started` opens the window, `canary_promoted` lets the
+//! regular drain take over, `canary_aborted` tears the child down. A server
+//! restart therefore resumes the analysis where it was.
+//!
+//! The decisions are shared by the scheduler (automatic promotion or abort) and
+//! the API (`POST /deployments/{id}/promote|abort`), so both paths record and
+//! publish a phase the same way.
+
+use crate::events::{self, Event};
+use crate::models::deployment_event;
+use crate::models::deployments::{Deployment, DeploymentStatus};
+use crate::models::health_check_logs;
+use sqlx::SqlitePool;
+
+/// Event reason opening the analysis window.
+pub(crate) const STARTED: &str = "canary_started";
+/// Event reason of a promotion, automatic or manual.
+pub(crate) const PROMOTED: &str = "canary_promoted";
+/// Event reason of an abort, automatic or manual.
+pub(crate) const ABORTED: &str = "canary_aborted";
+
+/// True once the canary was promoted. A failed read answers `false`: the
+/// canary then stays at its few instances, which is the safe side.
+pub(crate) async fn is_promoted(pool: &SqlitePool, child: &Deployment) -> bool {
+    match deployment_event::find_latest_by_reason(pool, &child.id, PROMOTED).await {
+        Ok(event) => event.is_some(),
+        Err(e) => {
+            warn!(
+                "Failed to load the canary state of {}: {} — holding",
+                child.id, e
+            );
+            false
+        }
+    }
+}
+
+/// Why the canary must be aborted, if anything went wrong since `since` (the
+/// start of its analysis window, RFC 3339): a health check whose latest result
+/// is not a success, or a runtime error reported on the child (the events that
+/// also go out as `deployment.error`).
+pub(crate) async fn failure_since(
+    pool: &SqlitePool,
+    child: &Deployment,
+    since: &str,
+) -> Option<String> {
+    let Ok(since) = chrono::DateTime::parse_from_rfc3339(since) else {
+        return None;
+    };
+
+    match health_check_logs::find_latest_by_deployment(pool, child.id.clone()).await {
+        Ok(latest) => {
+            let failing = latest.iter().find(|record| {
+                record.status != "success"
+                    && chrono::DateTime::parse_from_rfc3339(&record.finished_at)
+                        .map(|at| at >= since)
+                        .unwrap_or(false)
+            });
+            if let Some(record) = failing {
+                return Some(format!(
+                    "{} health check {}",
+                    record.check_type, record.status
+                ));
+            }
+        }
+        Err(e) => warn!(
+            "Failed to load health check results of canary {}: {}",
+            child.id, e
+        ),
+    }
+
+    match deployment_event::find_events_since(pool, &child.id, &since.to_rfc3339()).await {
+        Ok(events) => {
+            let errors: Vec<&str> = events
+                .iter()
+                .filter_map(|event| event.reason.as_deref())
+                .filter(|reason| events::error_category(reason).is_some())
+                .collect();
+            if let Some(first) = errors.first() {
+                return Some(format!(
+                    "{} runtime error(s) reported, first: {}",
+                    errors.len(),
+                    first
+                ));
+            }
+        }
+        Err(e) => warn!("Failed to load the events of canary {}: {}", child.id, e),
+    }
+
+    None
+}
+
+/// Record the start of the analysis window.
+pub(crate) async fn start(pool: &SqlitePool, child: &Deployment, parent_id: &str, window: &str) {
+    info!(
+        "Canary {} is up next to {}, analysing for {}",
+        child.id, parent_id, window
+    );
+    let _ = deployment_event::log_event(
+        pool,
+        child.id.clone(),
+        "info",
+        format!(
+            "Canary started: the new version runs next to deployment {}; promoting after {} unless a health check fails or a runtime error is reported",
+            parent_id, window
+        ),
+        "scheduler",
+        Some(STARTED),
+    )
+    .await;
+    events::publish(
+        pool,
+        Event::deployment_rolling_update(child, parent_id, STARTED, None),
+    )
+    .await;
+}
+
+/// Promote the canary: from now on the child rolls out like a default rolling
+/// update, draining the parent as its new instances become ready.
+pub(crate) async fn promote(
+    pool: &SqlitePool,
+    child: &Deployment,
+    parent_id: &str,
+    detail: &str,
+    component: &str,
+) {
+    info!("Canary {} promoted: {}", child.id, detail);
+    let _ = deployment_event::log_event(
+        pool,
+        child.id.clone(),
+        "info",
+        format!(
+            "Canary promoted ({}): replacing the remaining instances of deployment {}",
+            detail, parent_id
+        ),
+        component,
+        Some(PROMOTED),
+    )
+    .await;
+    events::publish(
+        pool,
+        Event::deployment_rolling_update(child, parent_id, PROMOTED, None),
+    )
+    .await;
+}
+
+/// Abort the canary: the child is marked `Deleted`, so the scheduler tears its
+/// instances down, and the parent grows back to its full count. The caller
+/// persists the status.
+pub(crate) async fn abort(
+    pool: &SqlitePool,
+    child: &mut Deployment,
+    parent_id: &str,
+    detail: &str,
+    component: &str,
+) {
+    warn!("Canary {} aborted: {}", child.id, detail);
+    child.status = DeploymentStatus::Deleted;
+    let _ = deployment_event::log_event(
+        pool,
+        child.id.clone(),
+        "error",
+        format!(
+            "Canary aborted ({}): tearing the new version down, deployment {} keeps serving",
+            detail, parent_id
+        ),
+        component,
+        Some(ABORTED),
+    )
+    .await;
+    events::publish(
+        pool,
+        Event::deployment_rolling_update(child, parent_id, ABORTED, None),
+    )
+    .await;
+}
@@ -0,0 +1,466 @@
+//! Scheduled jobs (`kind: cronjob`).
+//!
+//! A cronjob never runs a container itself. On every tick the scheduler checks
+//! its `cron.schedule`; when a trigger is due it spawns a run, i.e. a regular
+//! `kind: job` deployment copied from the cronjob's spec with `cronjob_id`
+//! pointing back at it. From there on the run is reconciled, logged and torn
+//! down like any job, so runs get restarts, events and logs for free.
+//!
+//! The trigger acted on is persisted in `last_scheduled_at`: a server restart
+//! neither fires the same trigger twice nor replays every trigger it missed —
+//! all the missed ones collapse into a single run (see [`Cron::due`]).
+//!
+//! [`Cron::due`]: crate::models::deployments::Cron::due
+
+use crate::models::deployment_event;
+use crate::models::deployments::{
+    self, ConcurrencyPolicy, Deployment, DeploymentStatus, RECONCILED_STATUSES,
+};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Event reason on the cronjob when a trigger spawned a run.
+pub(crate) const RUN_STARTED: &str = "run_started";
+/// Event reason on the cronjob when `concurrency_policy: forbid` skipped a
+/// trigger.
+pub(crate) const RUN_SKIPPED: &str = "run_skipped";
+/// Event reason on a run torn down by `concurrency_policy: replace`.
+pub(crate) const RUN_REPLACED: &str = "run_replaced";
+
+/// True while a run still has work to do: the scheduler keeps reconciling it.
+/// Completed, failed and crash-looping runs are finished.
+pub(crate) fn is_active(run: &Deployment) -> bool {
+    run.status != DeploymentStatus::Deleted && RECONCILED_STATUSES.contains(&run.status)
+}
+
+/// One scheduler tick for a cronjob: mark it running, trim its run history and
+/// spawn a run if a trigger is due. The caller persists the cronjob's status.
+pub(crate) async fn reconcile(pool: &SqlitePool, cronjob: &mut Deployment, now: DateTime<Utc>) {
+    let Some(cron) = cronjob.cron.clone() else {
+        // Refused at the API; only a row edited by hand gets here.
+        warn!("Cronjob {} has no schedule, skipping", cronjob.id);
+        return;
+    };
+
+    // There is no instance to wait for: a cronjob is "running" as soon as the
+    // scheduler owns its schedule.
+    if matches!(
+        cronjob.status,
+        DeploymentStatus::Pending | DeploymentStatus::Creating
+    ) {
+        cronjob.status = DeploymentStatus::Running;
+    }
+
+    let runs = match deployments::find_runs(pool, &cronjob.id).await {
+        Ok(runs) => runs,
+        Err(e) => {
+            error!("Failed to load the runs of cronjob {}: {}", cronjob.id, e);
+            return;
+        }
+    };
+
+    prune_history(pool, &runs, cron.history_limit).await;
+
+    let Some(anchor) = cronjob.schedule_anchor() else {
+        warn!(
+            "Cronjob {} has no usable last trigger or creation time, skipping",
+            cronjob.id
+        );
+        return;
+    };
+    let Some(trigger) = cron.due(&anchor, &now) else {
+        return;
+    };
+
+    // Recorded first: whatever happens to the run below, this trigger is
+    // consumed and must not fire again on the next tick.
+    let scheduled_at = trigger.to_rfc3339();
+    match deployments::set_last_scheduled_at(pool, &cronjob.id, &scheduled_at).await {
+        Ok(true) => cronjob.last_scheduled_at = Some(scheduled_at),
+        Ok(false) => return,
+        Err(e) => {
+            error!(
+                "Failed to record the trigger of cronjob {}: {}",
+                cronjob.id, e
+            );
+            return;
+        }
+    }
+
+    let active: Vec<&Deployment> = runs.iter().filter(|run| is_active(run)).collect();
+    if !active.is_empty() {
+        match cron.concurrency_policy {
+            ConcurrencyPolicy::Allow => {}
+            ConcurrencyPolicy::Forbid => {
+                info!(
+                    "Cronjob {} trigger at {} skipped: run {} is still active",
+                    cronjob.id, trigger, active[0].id
+                );
+                let _ = deployment_event::log_event(
+                    pool,
+                    cronjob.id.clone(),
+                    "warning",
+                    format!(
+                        "Trigger at {} skipped: run {} is still {} (concurrency_policy: forbid)",
+                        trigger, active[0].name, active[0].status
+                    ),
+                    "scheduler",
+                    Some(RUN_SKIPPED),
+                )
+                .await;
+                return;
+            }
+            ConcurrencyPolicy::Replace => {
+                for run in active {
+                    replace_run(pool, cronjob, run.clone()).await;
+                }
+            }
+        }
+    }
+
+    spawn_run(pool, cronjob, trigger).await;
+}
+
+/// Mark every run of a deleted cronjob deleted too, so the scheduler tears
+/// their containers down and purges them.
+pub(crate) async fn delete_runs(pool: &SqlitePool, cronjob: &Deployment) {
+    let runs = match deployments::find_runs(pool, &cronjob.id).await {
+        Ok(runs) => runs,
+        Err(e) => {
+            error!("Failed to load the runs of cronjob {}: {}", cronjob.id, e);
+            return;
+        }
+    };
+    for mut run in runs {
+        run.status = DeploymentStatus::Deleted;
+        if let Err(e) = deployments::update(pool, &run).await {
+            error!(
+                "Failed to delete run {} of cronjob {}: {}",
+                run.id, cronjob.id, e
+            );
+        }
+    }
+}
+
+/// Keep the `history_limit` most recent finished runs, with their status,
+/// events and logs; older ones are deleted. Active runs never count against
+/// the limit.
+async fn prune_history(pool: &SqlitePool, runs: &[Deployment], history_limit: u32) {
+    // `runs` is most recent first.
+    let expired = runs
+        .iter()
+        .filter(|run| !is_active(run))
+        .skip(history_limit as usize);
+    for run in expired {
+        debug!("Deleting run {} beyond the history limit", run.id);
+        let mut run = run.clone();
+        run.status = DeploymentStatus::Deleted;
+        if let Err(e) = deployments::update(pool, &run).await {
+            error!("Failed to delete expired run {}: {}", run.id, e);
+        }
+    }
+}
+
+async fn replace_run(pool: &SqlitePool, cronjob: &Deployment, mut run: Deployment) {
+    info!("Cronjob {} replaces its active run {}", cronjob.id, run.id);
+    run.status = DeploymentStatus::Deleted;
+    if let Err(e) = deployments::update(pool, &run).await {
+        error!("Failed to replace run {}: {}", run.id, e);
+        return;
+    }
+    let _ = deployment_event::log_event(
+        pool,
+        run.id.clone(),
+        "warning",
+        format!(
+            "Replaced by a newer run of cronjob {} (concurrency_policy: replace)",
+            cronjob.name
+        ),
+        "scheduler",
+        Some(RUN_REPLACED),
+    )
+    .await;
+}
+
+/// Create the run for `trigger`: a one-replica `kind: job` copy of the cronjob,
+/// named after the cronjob and the trigger time.
+async fn spawn_run(pool: &SqlitePool, cronjob: &Deployment, trigger: DateTime<Utc>) {
+    let run = Deployment {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: Utc::now().to_string(),
+        updated_at: None,
+        status: DeploymentStatus::Creating,
+        restart_count: 0,
+        name: format!("{}-{}", cronjob.name, trigger.format("%Y%m%d%H%M")),
+        kind: "job".to_string(),
+        replicas: 1,
+        instances: vec![],
+        desired_replicas: None,
+        image_digest: None,
+        pending_events: vec![],
+        parent_id: None,
+        pending_restart: vec![],
+        paused: false,
+        strategy: None,
+        rollout_limit: None,
+        restart_surge: false,
+        cron: None,
+        cronjob_id: Some(cronjob.id.clone()),
+        last_scheduled_at: None,
+        exit_code: None,
+        ..cronjob.clone()
+    };
+
+    if let Err(e) = deployments::create(pool, &run).await {
+        error!(
+            "Failed to create the run of cronjob {} for {}: {}",
+            cronjob.id, trigger, e
+        );
+        let _ = deployment_event::log_event(
+            pool,
+            cronjob.id.clone(),
+            "error",
+            format!(
+                "Failed to start the run for the trigger at {}: {}",
+                trigger, e
+            ),
+            "scheduler",
+            Some("run_creation_failed"),
+        )
+        .await;
+        return;
+    }
+
+    info!(
+        "Cronjob {} started run {} for the trigger at {}",
+        cronjob.id, run.id, trigger
+    );
+    let _ = deployment_event::log_event(
+        pool,
+        cronjob.id.clone(),
+        "info",
+        format!("Run {} started for the trigger at {}", run.name, trigger),
+        "scheduler",
+        Some(RUN_STARTED),
+    )
+    .await;
+    let _ = deployment_event::log_event(
+        pool,
+        run.id.clone(),
+        "info",
+        format!(
+            "Started by cronjob {} for the trigger at {}",
+            cronjob.name, trigger
+        ),
+        "scheduler",
+        Some("deployment_created"),
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::deployments::Cron;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::collections::HashMap;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn cronjob(policy: ConcurrencyPolicy) -> Deployment {
+        Deployment {
+            id: "cron-1".to_string(),
+            created_at: "2026-10-18 02:30:00.000000000 UTC".to_string(),
+            updated_at: None,
+            status: DeploymentStatus::Creating,
+            restart_count: 0,
+            namespace: "reports".to_string(),
+            name: "nightly".to_string(),
+            image: "busybox".to_string(),
+            config: None,
+            runtime: "docker".to_string(),
+            kind: "cronjob".to_string(),
+            replicas: 1,
+            command: vec!["sh".to_string(), "-c".to_string(), "report".to_string()],
+            instances: vec![],
+            labels: HashMap::new(),
+            environment: HashMap::new(),
+            volumes: "[]".to_string(),
+            health_checks: vec![],
+            resources: None,
+            autoscale: None,
+            desired_replicas: None,
+            image_digest: None,
+            ports: vec![],
+            pending_events: vec![],
+            parent_id: None,
+            network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: Some(Cron {
+                schedule: "0 3 * * *".to_string(),
+                concurrency_policy: policy,
+                history_limit: 2,
+            }),
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
+        }
+    }
+
+    async fn set_status(pool: &SqlitePool, id: &str, status: &str) {
+        sqlx::query("UPDATE deployment SET status = ? WHERE id = ?")
+            .bind(status)
+            .bind(id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_due_trigger_spawns_one_job_run() {
+        let pool = test_pool().await;
+        let mut cron = cronjob(ConcurrencyPolicy::Forbid);
+        deployments::create(&pool, &cron).await.unwrap();
+
+        // Before 03:00 nothing is due.
+        reconcile(&pool, &mut cron, at("2026-10-18T02:59:00Z")).await;
+        assert_eq!(cron.status, DeploymentStatus::Running);
+        assert!(
+            deployments::find_runs(&pool, "cron-1")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        reconcile(&pool, &mut cron, at("2026-10-18T03:00:30Z")).await;
+        let runs = deployments::find_runs(&pool, "cron-1").await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].kind, "job");
+        assert_eq!(runs[0].name, "nightly-202610180300");
+        assert_eq!(runs[0].command, cron.command);
+        assert!(runs[0].cron.is_none());
+
+        // The trigger is consumed: the next tick does not fire it again.
+        let stored = deployments::find(&pool, "cron-1").await.unwrap().unwrap();
+        assert_eq!(
+            stored.last_scheduled_at.as_deref(),
+            Some("2026-10-18T03:00:00+00:00")
+        );
+        reconcile(&pool, &mut cron, at("2026-10-18T03:01:00Z")).await;
+        assert_eq!(
+            deployments::find_runs(&pool, "cron-1").await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn missed_triggers_collapse_into_one_run() {
+        let pool = test_pool().await;
+        let mut cron = cronjob(ConcurrencyPolicy::Allow);
+        deployments::create(&pool, &cron).await.unwrap();
+
+        // Three nights missed.
+        reconcile(&pool, &mut cron, at("2026-10-21T12:00:00Z")).await;
+        let runs = deployments::find_runs(&pool, "cron-1").await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].name, "nightly-202610210300");
+    }
+
+    #[tokio::test]
+    async fn forbid_skips_while_a_run_is_active() {
+        let pool = test_pool().await;
+        let mut cron = cronjob(ConcurrencyPolicy::Forbid);
+        deployments::create(&pool, &cron).await.unwrap();
+
+        reconcile(&pool, &mut cron, at("2026-10-18T03:00:00Z")).await;
+        let first = deployments::find_runs(&pool, "cron-1").await.unwrap();
+        set_status(&pool, &first[0].id, "running").await;
+
+        reconcile(&pool, &mut cron, at("2026-10-19T03:00:00Z")).await;
+        assert_eq!(
+            deployments::find_runs(&pool, "cron-1").await.unwrap().len(),
+            1
+        );
+        let skipped = deployment_event::find_latest_by_reason(&pool, "cron-1", RUN_SKIPPED)
+            .await
+            .unwrap();
+        assert!(skipped.is_some());
+
+        // Once the run has finished, the next trigger goes through.
+        set_status(&pool, &first[0].id, "completed").await;
+        reconcile(&pool, &mut cron, at("2026-10-20T03:00:00Z")).await;
+        assert_eq!(
+            deployments::find_runs(&pool, "cron-1").await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn replace_tears_the_active_run_down() {
+        let pool = test_pool().await;
+        let mut cron = cronjob(ConcurrencyPolicy::Replace);
+        deployments::create(&pool, &cron).await.unwrap();
+
+        reconcile(&pool, &mut cron, at("2026-10-18T03:00:00Z")).await;
+        let first = deployments::find_runs(&pool, "cron-1").await.unwrap();
+        set_status(&pool, &first[0].id, "running").await;
+
+        reconcile(&pool, &mut cron, at("2026-10-19T03:00:00Z")).await;
+        let runs = deployments::find_runs(&pool, "cron-1").await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].name, "nightly-202610190300");
+        let replaced = deployments::find(&pool, &first[0].id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(replaced.status, DeploymentStatus::Deleted);
+    }
+
+    #[tokio::test]
+    async fn finished_runs_beyond_the_history_limit_are_deleted() {
+        let pool = test_pool().await;
+        let mut cron = cronjob(ConcurrencyPolicy::Forbid);
+        deployments::create(&pool, &cron).await.unwrap();
+
+        for (day, status) in [(18, "completed"), (19, "failed"), (20, "completed")] {
+            reconcile(&pool, &mut cron, at(&format!("2026-10-{day}T03:00:00Z"))).await;
+            let latest = &deployments::find_runs(&pool, "cron-1").await.unwrap()[0];
+            set_status(&pool, &latest.id, status).await;
+        }
+
+        // The next tick trims the oldest finished run (limit 2).
+        reconcile(&pool, &mut cron, at("2026-10-20T04:00:00Z")).await;
+        let names: Vec<String> = deployments::find_runs(&pool, "cron-1")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|run| run.name)
+            .collect();
+        assert_eq!(names, vec!["nightly-202610200300", "nightly-202610190300"]);
+    }
+}
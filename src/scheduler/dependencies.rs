@@ -0,0 +1,250 @@
+//! Start ordering between deployments (`depends_on`).
+//!
+//! A deployment created with dependencies starts `pending`. On every tick the
+//! scheduler asks [`hold`] whether it may start: until each dependency of the
+//! same namespace reaches its condition the deployment stays `pending` with a
+//! `dependency_waiting` event saying what it waits for. Once they all do it
+//! moves to `creating` and is reconciled like any other deployment.
+//!
+//! Dependencies only gate the first start. A dependency that later goes down
+//! does not stop its dependents: they keep their own restart and health-check
+//! handling.
+
+use crate::models::deployment_event;
+use crate::models::deployments::{
+    self, Dependency, DependencyCondition, Deployment, DeploymentStatus,
+};
+use crate::models::health_check::ReadinessDecision;
+use crate::scheduler::scheduler::readiness_decision;
+use sqlx::SqlitePool;
+
+/// Event reason while a deployment is held back by its dependencies.
+pub(crate) const DEPENDENCY_WAITING: &str = "dependency_waiting";
+/// Event reason once every dependency is satisfied and the deployment starts.
+pub(crate) const DEPENDENCIES_MET: &str = "dependencies_met";
+
+/// True when a deployment in `status`, whose readiness is `ready`, satisfies
+/// `condition`. A job that already completed has been started too.
+fn satisfies(condition: DependencyCondition, status: &DeploymentStatus, ready: bool) -> bool {
+    match condition {
+        DependencyCondition::Started => {
+            matches!(
+                status,
+                DeploymentStatus::Running | DeploymentStatus::Completed
+            )
+        }
+        DependencyCondition::Ready => *status == DeploymentStatus::Running && ready,
+        DependencyCondition::Completed => *status == DeploymentStatus::Completed,
+    }
+}
+
+/// What keeps `dependency` from being satisfied, `None` once it is. Any active
+/// deployment of that name will do, so a dependency in the middle of a rolling
+/// update stays satisfied by the version still serving.
+async fn unmet(pool: &SqlitePool, namespace: &str, dependency: &Dependency) -> Option<String> {
+    let wanted = format!("{} to be {}", dependency.name, dependency.condition);
+    let candidates =
+        match deployments::find_active_by_namespace_name(pool, namespace, &dependency.name).await {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                warn!("Failed to look up dependency {}: {}", dependency.name, e);
+                return Some(wanted);
+            }
+        };
+    if candidates.is_empty() {
+        return Some(format!("{} (no such deployment)", wanted));
+    }
+
+    for candidate in &candidates {
+        let ready = dependency.condition != DependencyCondition::Ready
+            || matches!(
+                readiness_decision(pool, candidate).await,
+                ReadinessDecision::NotConfigured | ReadinessDecision::Ready
+            );
+        if satisfies(dependency.condition, &candidate.status, ready) {
+            return None;
+        }
+    }
+    Some(format!("{} (currently {})", wanted, candidates[0].status))
+}
+
+/// Whether `deployment` must stay `pending` this tick. Records a
+/// `dependency_waiting` event whenever what it waits for changes, and a
+/// `dependencies_met` event when it is released.
+pub(crate) async fn hold(pool: &SqlitePool, deployment: &Deployment) -> bool {
+    let mut waiting = Vec::new();
+    for dependency in &deployment.depends_on {
+        if let Some(reason) = unmet(pool, &deployment.namespace, dependency).await {
+            waiting.push(reason);
+        }
+    }
+
+    if waiting.is_empty() {
+        info!("Dependencies of deployment {} are met", deployment.id);
+        let _ = deployment_event::log_event(
+            pool,
+            deployment.id.clone(),
+            "info",
+            "Dependencies met, starting".to_string(),
+            "scheduler",
+            Some(DEPENDENCIES_MET),
+        )
+        .await;
+        return false;
+    }
+
+    let message = format!("Waiting for {}", waiting.join(", "));
+    let already_logged =
+        deployment_event::find_latest_by_reason(pool, &deployment.id, DEPENDENCY_WAITING)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|event| event.message == message);
+    if !already_logged {
+        debug!("Deployment {}: {}", deployment.id, message);
+        let _ = deployment_event::log_event(
+            pool,
+            deployment.id.clone(),
+            "info",
+            message,
+            "scheduler",
+            Some(DEPENDENCY_WAITING),
+        )
+        .await;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::collections::HashMap;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    fn deployment(name: &str, status: DeploymentStatus, depends_on: Vec<Dependency>) -> Deployment {
+        Deployment {
+            id: name.to_string(),
+            created_at: Utc::now().to_string(),
+            updated_at: None,
+            status,
+            restart_count: 0,
+            namespace: "stack".to_string(),
+            name: name.to_string(),
+            image: "busybox".to_string(),
+            config: None,
+            runtime: "docker".to_string(),
+            kind: "worker".to_string(),
+            replicas: 1,
+            command: vec![],
+            instances: vec![],
+            labels: HashMap::new(),
+            environment: HashMap::new(),
+            volumes: "[]".to_string(),
+            health_checks: vec![],
+            resources: None,
+            autoscale: None,
+            desired_replicas: None,
+            image_digest: None,
+            ports: vec![],
+            pending_events: vec![],
+            parent_id: None,
+            network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on,
+            pending_restart: vec![],
+            paused: false,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            job: None,
+            exit_code: None,
+        }
+    }
+
+    fn on(name: &str, condition: DependencyCondition) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            condition,
+        }
+    }
+
+    #[test]
+    fn conditions_match_the_dependency_status() {
+        use DependencyCondition::*;
+        use DeploymentStatus as S;
+
+        assert!(satisfies(Started, &S::Running, false));
+        assert!(satisfies(Started, &S::Completed, false));
+        assert!(!satisfies(Started, &S::Creating, false));
+
+        assert!(satisfies(Ready, &S::Running, true));
+        assert!(!satisfies(Ready, &S::Running, false));
+        assert!(!satisfies(Ready, &S::Creating, true));
+
+        assert!(satisfies(Completed, &S::Completed, true));
+        assert!(!satisfies(Completed, &S::Running, true));
+        assert!(!satisfies(Completed, &S::Failed, true));
+    }
+
+    #[tokio::test]
+    async fn a_dependent_is_held_until_its_dependencies_are_met() {
+        let pool = test_pool().await;
+        let api = deployment(
+            "api",
+            DeploymentStatus::Pending,
+            vec![
+                on("postgres", DependencyCondition::Ready),
+                on("migrate", DependencyCondition::Completed),
+            ],
+        );
+        deployments::create(&pool, &api).await.unwrap();
+
+        assert!(hold(&pool, &api).await);
+        let event = deployment_event::find_latest_by_reason(&pool, "api", DEPENDENCY_WAITING)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            event.message,
+            "Waiting for postgres to be ready (no such deployment), \
+             migrate to be completed (no such deployment)"
+        );
+
+        let postgres = deployment("postgres", DeploymentStatus::Running, vec![]);
+        deployments::create(&pool, &postgres).await.unwrap();
+        let mut migrate = deployment("migrate", DeploymentStatus::Running, vec![]);
+        migrate.kind = "job".to_string();
+        deployments::create(&pool, &migrate).await.unwrap();
+        assert!(hold(&pool, &api).await);
+
+        migrate.status = DeploymentStatus::Completed;
+        deployments::update(&pool, &migrate).await.unwrap();
+        assert!(!hold(&pool, &api).await);
+        assert!(
+            deployment_event::find_latest_by_reason(&pool, "api", DEPENDENCIES_MET)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
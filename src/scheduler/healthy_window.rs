@@ -8,7 +8,8 @@
 //! This tracker watches how long a worker has been continuously `Running` with a
 //! non-zero `restart_count`. Once it has stayed healthy for at least the
 //! anti-flap window (`min_healthy_time`, same window the rollout readiness gate
-//! uses), the count is reset to 0 — the crash budget refills.
+//! uses, stretched to the longest backoff of the worker's `restart_policy`),
+//! the count is reset to 0 — the crash budget refills.
 //!
 //! State is in-memory and non-persistent, like [`super::backoff::RetryBackoff`]:
 //! at process restart the clock starts over, which is safe (a still-crashing
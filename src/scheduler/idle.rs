@@ -0,0 +1,707 @@
+//! Scale-to-zero for the deployments with an `idle:` block.
+//!
+//! Ring binds their published ports itself — the runtimes leave them alone,
+//! see [`Deployment::runtime_bound_ports`] — and proxies every connection to
+//! the instance in userspace, like the VM runtimes' `socat` forwarders do.
+//! Sitting on the path of every connection is what both halves need:
+//!
+//! * **sleep**: a deployment that had no connection open for `idle.after` is
+//!   scaled to zero (`desired_replicas = 0`). Its ports stay bound.
+//! * **wake**: a connection to a sleeping deployment scales it back to its
+//!   `replicas`, and is held until an instance is ready — its readiness health
+//!   checks green since the wake, and the target port accepting connections —
+//!   then proxied through. One not ready within `idle.wake_timeout` drops it.
+//!
+//! Activity is keyed by `namespace/name`, so a redeploy does not reset the
+//! clock, and lives in memory only: after a `ring-server` restart every idle
+//! deployment gets a full `idle.after` before it is put to sleep. TCP only.
+
+use crate::api::server::RuntimeMap;
+use crate::models::deployment_event;
+use crate::models::deployments::{self, Deployment, DeploymentStatus};
+use crate::models::health_check_logs::{self, HealthCheckResultRecord};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+
+/// Pause between two attempts to reach the deployment a connection woke.
+const WAKE_POLL: Duration = Duration::from_millis(250);
+
+/// Upper bound of one connection attempt to an instance.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where a listener sends its connections. Replaced in place when the
+/// deployment is redeployed under a new id.
+#[derive(Debug, Clone, PartialEq)]
+struct Route {
+    deployment_id: String,
+    /// `namespace/name`, the key of the deployment's [`Activity`].
+    key: String,
+    published: u16,
+    target: u16,
+}
+
+#[derive(Debug)]
+struct Activity {
+    /// Connections being proxied or held right now.
+    open: usize,
+    /// When the last connection was opened or closed.
+    last_seen: Instant,
+    /// When a connection last woke the deployment.
+    woken_at: Option<DateTime<Utc>>,
+}
+
+/// Connections seen on the ports of every idle deployment.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Tracker(Arc<Mutex<HashMap<String, Activity>>>);
+
+impl Tracker {
+    fn opened(&self, key: &str, now: Instant) -> OpenConnection {
+        if let Ok(mut map) = self.0.lock() {
+            let activity = map.entry(key.to_string()).or_insert(Activity {
+                open: 0,
+                last_seen: now,
+                woken_at: None,
+            });
+            activity.open += 1;
+            activity.last_seen = now;
+        }
+        OpenConnection {
+            tracker: self.clone(),
+            key: key.to_string(),
+        }
+    }
+
+    fn closed(&self, key: &str, now: Instant) {
+        if let Ok(mut map) = self.0.lock()
+            && let Some(activity) = map.get_mut(key)
+        {
+            activity.open = activity.open.saturating_sub(1);
+            activity.last_seen = now;
+        }
+    }
+
+    /// How long `key` went without a connection, `None` while one is open. A
+    /// key seen for the first time starts its clock now.
+    fn quiet_for(&self, key: &str, now: Instant) -> Option<Duration> {
+        let mut map = self.0.lock().ok()?;
+        let activity = map.entry(key.to_string()).or_insert(Activity {
+            open: 0,
+            last_seen: now,
+            woken_at: None,
+        });
+        (activity.open == 0).then(|| now.saturating_duration_since(activity.last_seen))
+    }
+
+    fn woke(&self, key: &str, at: DateTime<Utc>) {
+        if let Ok(mut map) = self.0.lock()
+            && let Some(activity) = map.get_mut(key)
+        {
+            activity.woken_at = Some(at);
+        }
+    }
+
+    fn woken_at(&self, key: &str) -> Option<DateTime<Utc>> {
+        self.0.lock().ok()?.get(key).and_then(|a| a.woken_at)
+    }
+
+    /// Forget the deployments that are gone, so the map stays bounded.
+    fn retain(&self, keys: &HashSet<String>) {
+        if let Ok(mut map) = self.0.lock() {
+            map.retain(|key, _| keys.contains(key));
+        }
+    }
+}
+
+/// A connection counted in the [`Tracker`] until dropped.
+struct OpenConnection {
+    tracker: Tracker,
+    key: String,
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.tracker.closed(&self.key, Instant::now());
+    }
+}
+
+/// What a connection needs to reach its deployment.
+#[derive(Clone)]
+struct Shared {
+    pool: SqlitePool,
+    runtimes: RuntimeMap,
+    tracker: Tracker,
+}
+
+/// One bound port. Dropping it stops accepting; connections already accepted
+/// run to their end.
+struct Listener {
+    route: Arc<Mutex<Route>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The ports Ring holds for idle deployments, and the sleep decisions.
+pub(crate) struct IdleProxy {
+    shared: Shared,
+    listeners: HashMap<(String, u16), Listener>,
+    /// Ports that failed to bind, so the warning is not repeated every tick.
+    unbound: HashSet<(String, u16)>,
+}
+
+/// Run the idle proxy on the scheduler interval. Spawned once by the server.
+pub(crate) async fn run(pool: SqlitePool, runtimes: RuntimeMap, interval_secs: u64) {
+    let mut proxy = IdleProxy::new(pool, runtimes);
+    let tick = Duration::from_secs(interval_secs.max(1));
+    loop {
+        proxy.reconcile(Instant::now()).await;
+        sleep(tick).await;
+    }
+}
+
+impl IdleProxy {
+    pub(crate) fn new(pool: SqlitePool, runtimes: RuntimeMap) -> Self {
+        Self {
+            shared: Shared {
+                pool,
+                runtimes,
+                tracker: Tracker::default(),
+            },
+            listeners: HashMap::new(),
+            unbound: HashSet::new(),
+        }
+    }
+
+    /// Bind the ports of the idle deployments, release those of the others,
+    /// and put to sleep whatever went without a connection for long enough.
+    pub(crate) async fn reconcile(&mut self, now: Instant) {
+        let idle = match deployments::find_all(&self.shared.pool, HashMap::new()).await {
+            Ok(all) => idle_deployments(all),
+            Err(e) => {
+                warn!("idle: listing deployments failed: {}", e);
+                return;
+            }
+        };
+
+        self.bind(&idle).await;
+
+        for deployment in &idle {
+            let key = activity_key(deployment);
+            let Some(quiet) = self.shared.tracker.quiet_for(&key, now) else {
+                continue;
+            };
+            if sleeps(deployment, quiet) {
+                self.put_to_sleep(deployment).await;
+            }
+        }
+
+        let keys: HashSet<String> = idle.iter().map(activity_key).collect();
+        self.shared.tracker.retain(&keys);
+    }
+
+    async fn bind(&mut self, idle: &[Deployment]) {
+        let wanted = routes(idle);
+
+        self.listeners
+            .retain(|address, _| wanted.contains_key(address));
+        self.unbound.retain(|address| wanted.contains_key(address));
+
+        for (address, route) in wanted {
+            if let Some(listener) = self.listeners.get(&address) {
+                if let Ok(mut current) = listener.route.lock() {
+                    *current = route;
+                }
+                continue;
+            }
+            match TcpListener::bind((address.0.as_str(), address.1)).await {
+                Ok(socket) => {
+                    debug!(
+                        "idle: listening on {}:{} for {}",
+                        address.0, address.1, route.key
+                    );
+                    self.unbound.remove(&address);
+                    let route = Arc::new(Mutex::new(route));
+                    let task = tokio::spawn(accept(socket, route.clone(), self.shared.clone()));
+                    self.listeners.insert(address, Listener { route, task });
+                }
+                Err(e) => {
+                    if self.unbound.insert(address.clone()) {
+                        warn!(
+                            "idle: cannot listen on {}:{} for {}: {}",
+                            address.0, address.1, route.key, e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    async fn put_to_sleep(&self, deployment: &Deployment) {
+        let pool = &self.shared.pool;
+        match deployments::set_desired_replicas(pool, &deployment.id, 0).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                error!("idle: failed to scale {} to zero: {}", deployment.id, e);
+                return;
+            }
+        }
+        let after = deployment
+            .idle
+            .as_ref()
+            .map(|idle| idle.after.as_str())
+            .unwrap_or_default();
+        info!(
+            "Deployment {}/{} had no connection for {}; scaling to zero",
+            deployment.namespace, deployment.name, after
+        );
+        let _ = deployment_event::log_event(
+            pool,
+            deployment.id.clone(),
+            "info",
+            format!("No connection for {}; scaled to zero", after),
+            "idle",
+            Some("idle_sleep"),
+        )
+        .await;
+    }
+}
+
+/// The deployments whose ports Ring holds, oldest first so that a newer row
+/// published on the same port wins.
+fn idle_deployments(all: Vec<Deployment>) -> Vec<Deployment> {
+    let mut idle: Vec<Deployment> = all
+        .into_iter()
+        .filter(|d| d.idle.is_some() && d.status != DeploymentStatus::Deleted)
+        .collect();
+    idle.sort_by_key(|d| d.created_at_utc());
+    idle
+}
+
+fn activity_key(deployment: &Deployment) -> String {
+    format!("{}/{}", deployment.namespace, deployment.name)
+}
+
+/// The listener each published port of `idle` needs, by host address.
+fn routes(idle: &[Deployment]) -> HashMap<(String, u16), Route> {
+    let mut routes = HashMap::new();
+    for deployment in idle {
+        for port in &deployment.ports {
+            let host_ip = port
+                .host_ip
+                .clone()
+                .unwrap_or_else(|| crate::hypervisor::port_forwarder::DEFAULT_HOST_IP.to_string());
+            routes.insert(
+                (host_ip, port.published),
+                Route {
+                    deployment_id: deployment.id.clone(),
+                    key: activity_key(deployment),
+                    published: port.published,
+                    target: port.target,
+                },
+            );
+        }
+    }
+    routes
+}
+
+/// Whether `deployment`, quiet for `quiet`, is put to sleep now. Only a worker
+/// that is up and left to the scheduler is.
+fn sleeps(deployment: &Deployment, quiet: Duration) -> bool {
+    let Some(after) = deployment.idle.as_ref().and_then(|idle| idle.idle_after()) else {
+        return false;
+    };
+    deployment.status == DeploymentStatus::Running
+        && !deployment.paused
+        && !deployment.asleep()
+        && quiet >= after
+}
+
+async fn accept(socket: TcpListener, route: Arc<Mutex<Route>>, shared: Shared) {
+    loop {
+        match socket.accept().await {
+            Ok((inbound, _)) => {
+                let Ok(route) = route.lock().map(|r| r.clone()) else {
+                    return;
+                };
+                tokio::spawn(serve(inbound, route, shared.clone()));
+            }
+            Err(e) => {
+                // Typically out of file descriptors: back off rather than spin.
+                warn!("idle: accept failed: {}", e);
+                sleep(WAKE_POLL).await;
+            }
+        }
+    }
+}
+
+async fn serve(mut inbound: TcpStream, route: Route, shared: Shared) {
+    let _open = shared.tracker.opened(&route.key, Instant::now());
+    let Some(mut upstream) = upstream(&route, &shared).await else {
+        return;
+    };
+    if let Err(e) = tokio::io::copy_bidirectional(&mut inbound, &mut upstream).await {
+        debug!("idle: connection to {} ended: {}", route.key, e);
+    }
+}
+
+/// A connection to an instance of the route's deployment, waking it first if
+/// it sleeps. `None` when it is gone or not ready within its wake timeout.
+async fn upstream(route: &Route, shared: &Shared) -> Option<TcpStream> {
+    let deployment = match deployments::find(&shared.pool, &route.deployment_id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => return None,
+        Err(e) => {
+            warn!("idle: loading {} failed: {}", route.deployment_id, e);
+            return None;
+        }
+    };
+    let wake_timeout = deployment.idle.as_ref()?.wake_timeout();
+
+    if deployment.asleep() {
+        match deployments::wake(&shared.pool, &deployment.id).await {
+            Ok(true) => {
+                shared.tracker.woke(&route.key, Utc::now());
+                info!(
+                    "Deployment {} woken up by a connection on port {}",
+                    route.key, route.published
+                );
+                let _ = deployment_event::log_event(
+                    &shared.pool,
+                    deployment.id.clone(),
+                    "info",
+                    format!("Woken up by a connection on port {}", route.published),
+                    "idle",
+                    Some("idle_wake"),
+                )
+                .await;
+            }
+            // Another connection got there first.
+            Ok(false) => {}
+            Err(e) => {
+                error!("idle: failed to wake {}: {}", deployment.id, e);
+                return None;
+            }
+        }
+    }
+
+    let deadline = Instant::now() + wake_timeout;
+    loop {
+        if let Some(stream) = connect(route, shared).await {
+            return Some(stream);
+        }
+        if Instant::now() >= deadline {
+            warn!(
+                "idle: {} not ready {}s after the connection on port {}; dropping it",
+                route.key,
+                wake_timeout.as_secs(),
+                route.published
+            );
+            return None;
+        }
+        sleep(WAKE_POLL).await;
+    }
+}
+
+/// A connection to the first instance that is ready, if any.
+async fn connect(route: &Route, shared: &Shared) -> Option<TcpStream> {
+    let deployment = deployments::find(&shared.pool, &route.deployment_id)
+        .await
+        .ok()??;
+    if deployment.status == DeploymentStatus::Deleted {
+        return None;
+    }
+    let runtime = shared.runtimes.get(&deployment.runtime)?;
+    if !ready(
+        &shared.pool,
+        &deployment,
+        shared.tracker.woken_at(&route.key),
+    )
+    .await
+    {
+        return None;
+    }
+    for instance in runtime
+        .list_instances(deployment.id.clone(), "running")
+        .await
+    {
+        let Some(address) = runtime.instance_address(&instance).await else {
+            continue;
+        };
+        if let Ok(Ok(stream)) =
+            timeout(CONNECT_TIMEOUT, TcpStream::connect((address, route.target))).await
+        {
+            return Some(stream);
+        }
+    }
+    None
+}
+
+/// Whether the readiness health checks of `deployment` are green, on results
+/// taken after `woken_at`: those recorded before it fell asleep say nothing
+/// about the instance that just started.
+async fn ready(
+    pool: &SqlitePool,
+    deployment: &Deployment,
+    woken_at: Option<DateTime<Utc>>,
+) -> bool {
+    let readiness: HashSet<&str> = deployment
+        .health_checks
+        .iter()
+        .filter(|hc| hc.is_readiness())
+        .map(|hc| hc.check_type())
+        .collect();
+    if readiness.is_empty() {
+        return true;
+    }
+    match health_check_logs::find_latest_by_deployment(pool, deployment.id.clone()).await {
+        Ok(latest) => green_since(&readiness, &latest, woken_at),
+        Err(e) => {
+            warn!(
+                "idle: loading the readiness of {} failed: {}",
+                deployment.id, e
+            );
+            false
+        }
+    }
+}
+
+fn green_since(
+    readiness: &HashSet<&str>,
+    latest: &[HealthCheckResultRecord],
+    since: Option<DateTime<Utc>>,
+) -> bool {
+    readiness.iter().all(|check_type| {
+        latest.iter().any(|record| {
+            record.check_type == *check_type
+                && record.status == "success"
+                && since.is_none_or(|since| {
+                    DateTime::parse_from_rfc3339(&record.finished_at)
+                        .is_ok_and(|finished| finished >= since)
+                })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hypervisor::lifecycle_trait::RuntimeLifecycle;
+    use crate::hypervisor::mock::MockRuntime;
+    use crate::models::deployments::{DeploymentPort, Idle, PortProtocol};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn idle_worker(id: &str, published: u16, target: u16) -> Deployment {
+        Deployment {
+            id: id.to_string(),
+            created_at: Utc::now().to_string(),
+            updated_at: None,
+            status: DeploymentStatus::Running,
+            restart_count: 0,
+            namespace: "tools".to_string(),
+            name: "wiki".to_string(),
+            image: "wiki:1".to_string(),
+            config: None,
+            runtime: "docker".to_string(),
+            kind: "worker".to_string(),
+            replicas: 1,
+            command: vec![],
+            instances: vec![],
+            labels: HashMap::new(),
+            environment: HashMap::new(),
+            volumes: "[]".to_string(),
+            health_checks: vec![],
+            resources: None,
+            autoscale: None,
+            desired_replicas: None,
+            image_digest: None,
+            ports: vec![DeploymentPort {
+                published,
+                target,
+                host_ip: Some("127.0.0.1".to_string()),
+                protocol: PortProtocol::Tcp,
+            }],
+            pending_events: vec![],
+            parent_id: None,
+            network: None,
+            defaulted: vec![],
+            idle: Some(Idle {
+                after: "900s".to_string(),
+                wake_timeout: Some("5s".to_string()),
+            }),
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
+        }
+    }
+
+    fn runtimes(mock: MockRuntime) -> RuntimeMap {
+        let runtime: Arc<dyn RuntimeLifecycle> = Arc::new(mock);
+        Arc::new(HashMap::from([("docker".to_string(), runtime)]))
+    }
+
+    #[test]
+    fn an_open_connection_keeps_the_deployment_awake() {
+        let tracker = Tracker::default();
+        let start = Instant::now();
+        assert_eq!(tracker.quiet_for("tools/wiki", start), Some(Duration::ZERO));
+
+        let open = tracker.opened("tools/wiki", start);
+        let later = start + Duration::from_secs(3600);
+        assert_eq!(tracker.quiet_for("tools/wiki", later), None);
+
+        drop(open);
+        let quiet = tracker.quiet_for("tools/wiki", later).unwrap();
+        assert!(quiet < Duration::from_secs(3600));
+
+        let d = idle_worker("d1", 8080, 80);
+        assert!(!sleeps(&d, Duration::from_secs(899)));
+        assert!(sleeps(&d, Duration::from_secs(900)));
+        let mut paused = d.clone();
+        paused.paused = true;
+        assert!(!sleeps(&paused, Duration::from_secs(900)));
+        let mut asleep = d;
+        asleep.desired_replicas = Some(0);
+        assert!(!sleeps(&asleep, Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn readiness_counts_only_results_after_the_wake() {
+        let record = |check_type: &str, status: &str, finished_at: &str| HealthCheckResultRecord {
+            id: "r".to_string(),
+            deployment_id: "d1".to_string(),
+            check_type: check_type.to_string(),
+            status: status.to_string(),
+            message: None,
+            created_at: finished_at.to_string(),
+            started_at: finished_at.to_string(),
+            finished_at: finished_at.to_string(),
+        };
+        let readiness = HashSet::from(["http"]);
+        let woken = DateTime::parse_from_rfc3339("2026-10-18T10:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let stale = [record("http", "success", "2026-10-18T09:00:00+00:00")];
+        assert!(green_since(&readiness, &stale, None));
+        assert!(!green_since(&readiness, &stale, Some(woken)));
+
+        let fresh = [record("http", "success", "2026-10-18T10:00:05+00:00")];
+        assert!(green_since(&readiness, &fresh, Some(woken)));
+
+        let failing = [record("http", "failed", "2026-10-18T10:00:05+00:00")];
+        assert!(!green_since(&readiness, &failing, Some(woken)));
+        assert!(!green_since(&readiness, &[], None));
+    }
+
+    #[tokio::test]
+    async fn a_connection_wakes_a_sleeping_deployment_and_is_proxied() {
+        let pool = test_pool().await;
+
+        // The instance: echoes what it reads.
+        let instance = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = instance.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = instance.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 64];
+                    if let Ok(n) = stream.read(&mut buf).await {
+                        let _ = stream.write_all(&buf[..n]).await;
+                    }
+                });
+            }
+        });
+
+        let published = free_port();
+        let mut d = idle_worker("d1", published, target);
+        d.desired_replicas = Some(0);
+        deployments::create(&pool, &d).await.unwrap();
+
+        let mock = MockRuntime::healthy()
+            .with_instances(&["c1"])
+            .with_address("127.0.0.1".parse().unwrap());
+        let mut proxy = IdleProxy::new(pool.clone(), runtimes(mock));
+        proxy.reconcile(Instant::now()).await;
+
+        let mut client = TcpStream::connect(("127.0.0.1", published)).await.unwrap();
+        client.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let stored = deployments::find(&pool, "d1").await.unwrap().unwrap();
+        assert!(!stored.asleep());
+        let reasons: Vec<String> =
+            sqlx::query_scalar("SELECT reason FROM deployment_event WHERE deployment_id = 'd1'")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(reasons, vec!["idle_wake".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn a_quiet_deployment_is_scaled_to_zero_and_keeps_its_port() {
+        let pool = test_pool().await;
+        let published = free_port();
+        deployments::create(&pool, &idle_worker("d1", published, 80))
+            .await
+            .unwrap();
+
+        let mut proxy = IdleProxy::new(pool.clone(), runtimes(MockRuntime::healthy()));
+        let start = Instant::now();
+        proxy.reconcile(start).await;
+        let stored = deployments::find(&pool, "d1").await.unwrap().unwrap();
+        assert!(!stored.asleep(), "the clock starts on first sight");
+
+        proxy.reconcile(start + Duration::from_secs(900)).await;
+        let stored = deployments::find(&pool, "d1").await.unwrap().unwrap();
+        assert!(stored.asleep());
+        assert_eq!(stored.target_replicas(), 0);
+        assert!(
+            std::net::TcpListener::bind(("127.0.0.1", published)).is_err(),
+            "Ring still holds the port"
+        );
+    }
+}
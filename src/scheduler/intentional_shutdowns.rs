@@ -7,7 +7,7 @@
 //! reconcile pass sees it as an `exited` instance. Either can stem from a crash,
 //! an OOM kill, or a graceful `docker stop` we sent ourselves. The reconcile
 //! pass (`detect_and_count_crashes`) counts each unexpected exit toward
-//! `restart_count`; once it reaches `MAX_RESTART_COUNT` the deployment flips to
+//! `restart_count`; once it spends the failure budget the deployment flips to
 //! `CrashLoopBackOff`. Without this filter, every scale-down, delete, rolling
 //! update step or health-check eviction would be counted as a crash and could
 //! push a perfectly healthy deployment into `CrashLoopBackOff`.
@@ -0,0 +1,176 @@
+//! Cleanup of finished jobs (`job.ttl_after_finished`).
+//!
+//! Completed and failed jobs are no longer reconciled, so the sweep runs on
+//! its own at the start of every tick. An expired job is only marked
+//! `deleted`: the regular deleted path then removes its containers and row,
+//! exactly as if it had been deleted through the API.
+
+use crate::models::deployment_event;
+use crate::models::deployments::{self, Deployment, DeploymentStatus};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Event reason on a job deleted by its `ttl_after_finished`.
+pub(crate) const TTL_EXPIRED: &str = "ttl_expired";
+
+/// When the job reached its final status: its last write, which is that
+/// status change. `None` when it does not parse.
+fn finished_at(job: &Deployment) -> Option<DateTime<Utc>> {
+    // `updated_at` is SQLite's `datetime('now')`, e.g. "2026-05-30 20:07:20".
+    let updated_at = job.updated_at.as_deref()?;
+    chrono::NaiveDateTime::parse_from_str(updated_at, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+/// True once `job` has been finished for longer than its
+/// `ttl_after_finished`.
+fn expired(job: &Deployment, now: DateTime<Utc>) -> bool {
+    let Some(ttl) = job.job.as_ref().and_then(|spec| spec.ttl()) else {
+        return false;
+    };
+    let Some(finished_at) = finished_at(job) else {
+        return false;
+    };
+    (now - finished_at).to_std().is_ok_and(|since| since >= ttl)
+}
+
+/// Mark every finished job past its `ttl_after_finished` as deleted.
+pub(crate) async fn expire_finished(pool: &SqlitePool, now: DateTime<Utc>) {
+    let jobs = match deployments::find_expirable_jobs(pool).await {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            error!("Failed to load finished jobs: {}", e);
+            return;
+        }
+    };
+
+    for mut job in jobs.into_iter().filter(|job| expired(job, now)) {
+        info!(
+            "Job {} finished more than {} ago, deleting it",
+            job.id,
+            job.job
+                .as_ref()
+                .and_then(|spec| spec.ttl_after_finished.as_deref())
+                .unwrap_or_default()
+        );
+        let finished_as = job.status.clone();
+        job.status = DeploymentStatus::Deleted;
+        if let Err(e) = deployments::update(pool, &job).await {
+            error!("Failed to delete expired job {}: {}", job.id, e);
+            continue;
+        }
+        let _ = deployment_event::log_event(
+            pool,
+            job.id.clone(),
+            "info",
+            format!(
+                "Deleted: {} for longer than its ttl_after_finished",
+                finished_as
+            ),
+            "scheduler",
+            Some(TTL_EXPIRED),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::deployments::JobSpec;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::collections::HashMap;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    fn job(id: &str, status: DeploymentStatus, ttl: Option<&str>) -> Deployment {
+        Deployment {
+            id: id.to_string(),
+            created_at: Utc::now().to_string(),
+            updated_at: None,
+            status,
+            restart_count: 0,
+            namespace: "batch".to_string(),
+            name: id.to_string(),
+            image: "busybox".to_string(),
+            config: None,
+            runtime: "docker".to_string(),
+            kind: "job".to_string(),
+            replicas: 1,
+            command: vec![],
+            instances: vec![],
+            labels: HashMap::new(),
+            environment: HashMap::new(),
+            volumes: "[]".to_string(),
+            health_checks: vec![],
+            resources: None,
+            autoscale: None,
+            desired_replicas: None,
+            image_digest: None,
+            ports: vec![],
+            pending_events: vec![],
+            parent_id: None,
+            network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            pending_restart: vec![],
+            paused: false,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            job: Some(JobSpec {
+                ttl_after_finished: ttl.map(str::to_string),
+                ..JobSpec::default()
+            }),
+            exit_code: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn finished_jobs_are_deleted_once_their_ttl_is_over() {
+        let pool = test_pool().await;
+        for (id, status, ttl) in [
+            ("done", DeploymentStatus::Completed, Some("60s")),
+            ("broken", DeploymentStatus::Failed, Some("60s")),
+            ("busy", DeploymentStatus::Running, Some("60s")),
+            ("kept", DeploymentStatus::Completed, None),
+        ] {
+            let d = job(id, status, ttl);
+            deployments::create(&pool, &d).await.unwrap();
+            // Sets `updated_at`, as the status change that finished it did.
+            deployments::update(&pool, &d).await.unwrap();
+        }
+
+        let status = |id: &'static str| {
+            let pool = pool.clone();
+            async move { deployments::find(&pool, id).await.unwrap().unwrap().status }
+        };
+
+        expire_finished(&pool, Utc::now()).await;
+        assert_eq!(status("done").await, DeploymentStatus::Completed);
+
+        expire_finished(&pool, Utc::now() + chrono::Duration::minutes(2)).await;
+        assert_eq!(status("done").await, DeploymentStatus::Deleted);
+        assert_eq!(status("broken").await, DeploymentStatus::Deleted);
+        assert_eq!(status("busy").await, DeploymentStatus::Running);
+        assert_eq!(status("kept").await, DeploymentStatus::Completed);
+    }
+}
@@ -0,0 +1,442 @@
+//! Server-side archive of workload logs (`[server.logs.archive]`).
+//!
+//! A runtime only keeps the output of the instances it still has: once a
+//! crashed container is replaced, a rolling update removes the old ones or a
+//! scale-down stops one, its lines are gone, usually just when someone wants
+//! to read them. When the archive is enabled, a background collector follows
+//! every instance through the runtime's own `stream_logs` (see
+//! [`crate::scheduler::log_tailers`]) and appends each line to a segment file
+//! on the server, so `GET /deployments/{id}/logs` can still answer
+//! `previous=true` and `since=` afterwards.
+//!
+//! Layout: one directory per deployment *series* (`<root>/<namespace>/<name>`,
+//! so the history spans redeploys and rolling updates), holding a live
+//! `current.log` and its rotated backups `current.log.1` (newest) to `.N`.
+//! Rotation reuses [`console_logs::rotate_if_needed`]; each line is a JSON
+//! [`Entry`]. Segments last written longer than `max_age_hours` ago are
+//! deleted, and so is a series directory left empty.
+//!
+//! Best-effort by design: lines are batched and written after the fact, so a
+//! write failure only loses that batch (logged), and the collector starts each
+//! instance present at boot at its current end of output, leaving what the
+//! archive may already hold from before the restart undisturbed.
+
+use crate::api::server::RuntimeMap;
+use crate::config::server::LogArchiveConfig;
+use crate::hypervisor::console_logs;
+use crate::hypervisor::lifecycle_trait::Log;
+use crate::scheduler::log_tailers::{Start, Tailers};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+/// Name of the live segment in a series directory.
+const LIVE_SEGMENT: &str = "current.log";
+
+/// Most lines written per batch: what a busy instance produced since the
+/// previous write, so a burst costs one open and one rotation check.
+const BATCH_LINES: usize = 256;
+
+/// One archived line.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Entry {
+    /// Unix timestamp (seconds) at which the collector received the line;
+    /// what `since` is compared with.
+    at: i64,
+    /// The deployment that produced the line: a series outlives its ids.
+    deployment: String,
+    #[serde(flatten)]
+    log: Log,
+}
+
+/// What to read back from a series.
+#[derive(Debug, Default)]
+pub(crate) struct ArchiveQuery<'a> {
+    /// Unix timestamp: only lines received at or after it.
+    pub(crate) since: Option<i64>,
+    /// Unix timestamp: only lines received at or before it.
+    pub(crate) until: Option<i64>,
+    /// Same matching as the runtimes' `container` filter: instance names or
+    /// prefixes of them. Empty for every instance.
+    pub(crate) instances: &'a [String],
+    /// Instances whose lines are left out; the running ones for `previous`.
+    pub(crate) exclude: &'a [String],
+}
+
+pub(crate) struct LogArchive {
+    root: PathBuf,
+    max_segment_bytes: u64,
+    max_segments: u32,
+    max_age: Duration,
+}
+
+impl LogArchive {
+    /// `None` when the archive is disabled.
+    pub(crate) fn from_config(config: &LogArchiveConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            root: PathBuf::from(config.resolved_directory()),
+            max_segment_bytes: config.max_segment_bytes,
+            max_segments: config.max_segments,
+            max_age: Duration::from_secs(config.max_age_hours.saturating_mul(3600)),
+        })
+    }
+
+    fn live_segment(&self, namespace: &str, name: &str) -> PathBuf {
+        self.root
+            .join(path_component(namespace))
+            .join(path_component(name))
+            .join(LIVE_SEGMENT)
+    }
+
+    /// Lines of the `namespace`/`name` series matching `query`, oldest first.
+    pub(crate) async fn read(
+        &self,
+        namespace: &str,
+        name: &str,
+        query: &ArchiveQuery<'_>,
+    ) -> Vec<Log> {
+        let path = self.live_segment(namespace, name);
+        let mut logs = Vec::new();
+        for file in console_logs::rotated_files_in_read_order(&path) {
+            let Ok(content) = tokio::fs::read_to_string(&file).await else {
+                continue;
+            };
+            for line in content.lines() {
+                let Ok(entry) = serde_json::from_str::<Entry>(line) else {
+                    continue;
+                };
+                if query.since.is_some_and(|since| entry.at < since)
+                    || query.until.is_some_and(|until| entry.at > until)
+                    || (!query.instances.is_empty()
+                        && !query
+                            .instances
+                            .iter()
+                            .any(|wanted| entry.log.instance.starts_with(wanted.as_str())))
+                    || query.exclude.contains(&entry.log.instance)
+                {
+                    continue;
+                }
+                logs.push(entry.log);
+            }
+        }
+        logs
+    }
+
+    async fn append(&self, segment: &Segment, deployment_id: &str, at: i64, logs: Vec<Log>) {
+        let mut buf = Vec::new();
+        for log in logs {
+            let entry = Entry {
+                at,
+                deployment: deployment_id.to_string(),
+                log,
+            };
+            if let Ok(line) = serde_json::to_vec(&entry) {
+                buf.extend_from_slice(&line);
+                buf.push(b'\n');
+            }
+        }
+
+        let _guard = segment.lock.lock().await;
+        if let Some(dir) = segment.path.parent()
+            && let Err(e) = tokio::fs::create_dir_all(dir).await
+        {
+            warn!("log archive: failed to create {:?}: {}", dir, e);
+            return;
+        }
+        let written = async {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&segment.path)
+                .await?;
+            file.write_all(&buf).await
+        }
+        .await;
+        if let Err(e) = written {
+            warn!("log archive: failed to write {:?}: {}", segment.path, e);
+            return;
+        }
+        console_logs::rotate_if_needed(&segment.path, self.max_segment_bytes, self.max_segments)
+            .await;
+    }
+
+    /// Deletes the segments older than `max_age`, then the directories left
+    /// empty.
+    async fn prune(&self) {
+        let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) else {
+            return;
+        };
+        for namespace in subdirectories(&self.root).await {
+            for series in subdirectories(&namespace).await {
+                let Ok(mut files) = tokio::fs::read_dir(&series).await else {
+                    continue;
+                };
+                while let Ok(Some(file)) = files.next_entry().await {
+                    let expired = file
+                        .metadata()
+                        .await
+                        .ok()
+                        .and_then(|meta| meta.modified().ok())
+                        .and_then(|mtime| mtime.duration_since(SystemTime::UNIX_EPOCH).ok())
+                        .is_some_and(|mtime| now.saturating_sub(mtime) > self.max_age);
+                    if expired && let Err(e) = tokio::fs::remove_file(file.path()).await {
+                        warn!("log archive: failed to remove {:?}: {}", file.path(), e);
+                    }
+                }
+                // Fails, as intended, unless the series is now empty.
+                let _ = tokio::fs::remove_dir(&series).await;
+            }
+            let _ = tokio::fs::remove_dir(&namespace).await;
+        }
+    }
+}
+
+/// A namespace or deployment name as a single path component. Both are
+/// validated on the way in; this only guarantees nothing escapes the root.
+fn path_component(value: &str) -> String {
+    match value.replace(['/', '\\'], "_").as_str() {
+        "" | "." | ".." => "_".to_string(),
+        safe => safe.to_string(),
+    }
+}
+
+async fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return out;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry.file_type().await.is_ok_and(|t| t.is_dir()) {
+            out.push(entry.path());
+        }
+    }
+    out
+}
+
+/// The live segment of a series, shared by the tailers of its instances so
+/// their writes and the rotation never interleave.
+struct Segment {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+/// Run the collector forever on `interval_secs` (the scheduler interval):
+/// each tick starts a follower for every instance that has none and prunes
+/// expired segments.
+pub(crate) async fn run(
+    archive: Arc<LogArchive>,
+    pool: SqlitePool,
+    runtimes: RuntimeMap,
+    interval_secs: u64,
+) {
+    let tick = Duration::from_secs(interval_secs.max(1));
+    let mut tailers = Tailers::new("log archive");
+    let mut segments: HashMap<PathBuf, Arc<Segment>> = HashMap::new();
+    let mut startup = true;
+    loop {
+        tailers
+            .sweep(&pool, &runtimes, startup, |start| {
+                let path =
+                    archive.live_segment(&start.deployment.namespace, &start.deployment.name);
+                let segment = segments
+                    .entry(path.clone())
+                    .or_insert_with(|| {
+                        Arc::new(Segment {
+                            path,
+                            lock: Mutex::new(()),
+                        })
+                    })
+                    .clone();
+                tokio::spawn(follow(archive.clone(), segment, start))
+            })
+            .await;
+        // Series nobody writes to any more.
+        segments.retain(|_, segment| Arc::strong_count(segment) > 1);
+        archive.prune().await;
+        startup = false;
+        sleep(tick).await;
+    }
+}
+
+async fn follow(archive: Arc<LogArchive>, segment: Arc<Segment>, start: Start) {
+    let mut batches = start.stream().await.ready_chunks(BATCH_LINES);
+    while let Some(batch) = batches.next().await {
+        let at = chrono::Utc::now().timestamp();
+        archive
+            .append(&segment, &start.deployment.id, at, batch)
+            .await;
+        start.last_at.store(at, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_archive(label: &str, max_segment_bytes: u64) -> LogArchive {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        LogArchive {
+            root: std::env::temp_dir().join(format!(
+                "ring-log-archive-{}-{}-{}",
+                label,
+                std::process::id(),
+                nanos
+            )),
+            max_segment_bytes,
+            max_segments: 5,
+            max_age: Duration::from_secs(3600),
+        }
+    }
+
+    fn segment(archive: &LogArchive) -> Segment {
+        Segment {
+            path: archive.live_segment("default", "web"),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn line(instance: &str, message: &str) -> Log {
+        Log {
+            instance: instance.to_string(),
+            container: None,
+            message: message.to_string(),
+            level: "info".to_string(),
+            timestamp: None,
+            fields: Default::default(),
+        }
+    }
+
+    fn messages(logs: &[Log]) -> Vec<&str> {
+        logs.iter().map(|log| log.message.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn reads_back_what_was_appended_with_filters() {
+        let archive = scratch_archive("filters", 0);
+        let segment = segment(&archive);
+        archive
+            .append(
+                &segment,
+                "d1",
+                100,
+                vec![line("web-a", "one"), line("web-b", "two")],
+            )
+            .await;
+        archive
+            .append(&segment, "d2", 200, vec![line("web-c", "three")])
+            .await;
+
+        let all = archive
+            .read("default", "web", &ArchiveQuery::default())
+            .await;
+        assert_eq!(messages(&all), vec!["one", "two", "three"]);
+
+        let since = ArchiveQuery {
+            since: Some(150),
+            ..Default::default()
+        };
+        let recent = archive.read("default", "web", &since).await;
+        assert_eq!(messages(&recent), vec!["three"]);
+
+        let until = ArchiveQuery {
+            until: Some(150),
+            ..Default::default()
+        };
+        let older = archive.read("default", "web", &until).await;
+        assert_eq!(messages(&older), vec!["one", "two"]);
+
+        let running = vec!["web-c".to_string()];
+        let previous = ArchiveQuery {
+            exclude: &running,
+            ..Default::default()
+        };
+        let gone = archive.read("default", "web", &previous).await;
+        assert_eq!(messages(&gone), vec!["one", "two"]);
+
+        let some = vec!["web-a".to_string(), "web-c".to_string()];
+        let picked = ArchiveQuery {
+            instances: &some,
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(&archive.read("default", "web", &picked).await),
+            vec!["one", "three"]
+        );
+        assert!(
+            archive
+                .read("default", "api", &ArchiveQuery::default())
+                .await
+                .is_empty()
+        );
+        let _ = tokio::fs::remove_dir_all(&archive.root).await;
+    }
+
+    #[tokio::test]
+    async fn reads_across_rotated_segments_in_order() {
+        // Every append goes past the threshold, so each batch ends up in its
+        // own segment.
+        let archive = scratch_archive("rotation", 1);
+        let segment = segment(&archive);
+        for (at, message) in [(1, "first"), (2, "second"), (3, "third")] {
+            archive
+                .append(&segment, "d1", at, vec![line("web-a", message)])
+                .await;
+        }
+        assert!(backup_exists(&segment.path, 3));
+
+        let all = archive
+            .read("default", "web", &ArchiveQuery::default())
+            .await;
+        assert_eq!(messages(&all), vec!["first", "second", "third"]);
+        let _ = tokio::fs::remove_dir_all(&archive.root).await;
+    }
+
+    fn backup_exists(path: &Path, idx: u32) -> bool {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(format!(".{}", idx));
+        PathBuf::from(name).exists()
+    }
+
+    #[tokio::test]
+    async fn prune_drops_expired_segments_and_empty_series() {
+        let archive = scratch_archive("prune", 0);
+        let segment = segment(&archive);
+        archive
+            .append(&segment, "d1", 1, vec![line("web-a", "old")])
+            .await;
+
+        archive.prune().await;
+        assert!(segment.path.exists(), "a fresh segment is kept");
+
+        let week_ago = SystemTime::now() - Duration::from_secs(7 * 24 * 3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&segment.path)
+            .unwrap()
+            .set_modified(week_ago)
+            .unwrap();
+        archive.prune().await;
+        assert!(!segment.path.exists());
+        assert!(!archive.root.join("default").exists());
+        let _ = tokio::fs::remove_dir_all(&archive.root).await;
+    }
+
+    #[test]
+    fn names_cannot_escape_the_root() {
+        assert_eq!(path_component("default"), "default");
+        assert_eq!(path_component("../etc"), ".._etc");
+        assert_eq!(path_component(".."), "_");
+        assert_eq!(path_component(""), "_");
+    }
+}
@@ -0,0 +1,262 @@
+//! Forwarding of workload logs to external sinks (`[server.logs.sinks]` and
+//! `[[server.logs.routes]]`).
+//!
+//! `[server.telemetry.logs]` exports the server's own log; this ships what the
+//! workloads write, so no Promtail or Fluent Bit has to run next to Ring. A
+//! background collector follows every instance through the runtime's
+//! `stream_logs` (see [`crate::scheduler::log_tailers`]), reads each line with
+//! the deployment's `logging:` block (JSON fields, multi-line entries), and
+//! hands it, with its namespace, deployment and instance, to the sinks of
+//! every route it matches.
+//!
+//! Like the archive, the collector starts each instance present at boot at its
+//! current end of output: a server restart neither re-sends nor recovers what
+//! was written while it was down.
+
+use crate::api::server::RuntimeMap;
+use crate::config::server::WorkloadLogsConfig;
+use crate::hypervisor::log_format::{LogParser, normalize_level};
+use crate::scheduler::log_sinks::{Record, Sink};
+use crate::scheduler::log_tailers::{Start, Tailers};
+use futures::StreamExt;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// A `[[server.logs.routes]]` rule, its sinks resolved to indexes.
+struct Route {
+    namespaces: Vec<String>,
+    levels: Vec<String>,
+    sinks: Vec<usize>,
+}
+
+impl Route {
+    fn matches(&self, namespace: &str, level: &str) -> bool {
+        (self.namespaces.is_empty() || self.namespaces.iter().any(|n| n == namespace))
+            && (self.levels.is_empty() || self.levels.iter().any(|l| l == level))
+    }
+}
+
+pub(crate) struct LogForwarder {
+    sinks: Vec<Sink>,
+    routes: Vec<Route>,
+}
+
+impl LogForwarder {
+    /// Start the sinks the routes name. `None` when no route leads anywhere.
+    /// A sink that cannot start, or a route naming a sink that does not
+    /// exist, is reported and left out rather than stopping the server.
+    pub(crate) fn from_config(config: &WorkloadLogsConfig) -> Option<Self> {
+        let mut names: Vec<&str> = Vec::new();
+        let mut sinks = Vec::new();
+        let mut routes = Vec::new();
+        for (position, rule) in config.routes.iter().enumerate() {
+            let mut route = Route {
+                namespaces: rule.namespaces.clone(),
+                levels: rule.levels.iter().map(|l| normalize_level(l)).collect(),
+                sinks: Vec::new(),
+            };
+            for name in &rule.sinks {
+                if let Some(index) = names.iter().position(|started| started == name) {
+                    route.sinks.push(index);
+                    continue;
+                }
+                let Some(sink_config) = config.sinks.get(name) else {
+                    error!(
+                        "log forwarder: route {} names sink {}, which is not in [server.logs.sinks]",
+                        position + 1,
+                        name
+                    );
+                    continue;
+                };
+                match Sink::start(name, sink_config) {
+                    Ok(sink) => {
+                        route.sinks.push(sinks.len());
+                        names.push(name);
+                        sinks.push(sink);
+                    }
+                    Err(e) => error!("log forwarder: sink {} left out: {}", name, e),
+                }
+            }
+            if !route.sinks.is_empty() {
+                routes.push(route);
+            }
+        }
+        Self::new(sinks, routes)
+    }
+
+    fn new(sinks: Vec<Sink>, routes: Vec<Route>) -> Option<Self> {
+        (!routes.is_empty()).then_some(Self { sinks, routes })
+    }
+
+    /// Names of the sinks lines can reach, for the startup log.
+    pub(crate) fn sink_names(&self) -> Vec<&str> {
+        self.sinks.iter().map(Sink::name).collect()
+    }
+
+    /// Hand a line to the sinks of every route it matches, once per sink.
+    fn forward(&self, record: Record) {
+        let mut targets: Vec<usize> = self
+            .routes
+            .iter()
+            .filter(|route| route.matches(&record.namespace, &record.log.level))
+            .flat_map(|route| route.sinks.iter().copied())
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        targets.sort_unstable();
+        targets.dedup();
+        let record = Arc::new(record);
+        for index in targets {
+            self.sinks[index].send(record.clone());
+        }
+    }
+}
+
+/// Run the collector forever on `interval_secs` (the scheduler interval):
+/// each tick starts a follower for every instance that has none.
+pub(crate) async fn run(
+    forwarder: Arc<LogForwarder>,
+    pool: SqlitePool,
+    runtimes: RuntimeMap,
+    interval_secs: u64,
+) {
+    let tick = Duration::from_secs(interval_secs.max(1));
+    let mut tailers = Tailers::new("log forwarder");
+    let mut startup = true;
+    loop {
+        tailers
+            .sweep(&pool, &runtimes, startup, |start| {
+                tokio::spawn(follow(forwarder.clone(), start))
+            })
+            .await;
+        startup = false;
+        sleep(tick).await;
+    }
+}
+
+async fn follow(forwarder: Arc<LogForwarder>, start: Start) {
+    let parser = LogParser::for_deployment(&start.deployment);
+    let mut logs = parser.stream(start.stream().await);
+    while let Some(log) = logs.next().await {
+        let received_at = chrono::Utc::now();
+        start
+            .last_at
+            .store(received_at.timestamp(), Ordering::Relaxed);
+        forwarder.forward(Record {
+            namespace: start.deployment.namespace.clone(),
+            deployment: start.deployment.name.clone(),
+            deployment_id: start.deployment.id.clone(),
+            log,
+            received_at,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::server::{LogRouteConfig, LogSinkConfig};
+    use crate::hypervisor::lifecycle_trait::Log;
+    use tokio::sync::mpsc::Receiver;
+
+    fn record(namespace: &str, level: &str, message: &str) -> Record {
+        Record {
+            namespace: namespace.to_string(),
+            deployment: "api".to_string(),
+            deployment_id: "d1".to_string(),
+            log: Log {
+                instance: "api-1".to_string(),
+                container: None,
+                message: message.to_string(),
+                level: level.to_string(),
+                timestamp: None,
+                fields: Default::default(),
+            },
+            received_at: chrono::Utc::now(),
+        }
+    }
+
+    fn received(lines: &mut Receiver<Arc<Record>>) -> Vec<String> {
+        let mut messages = Vec::new();
+        while let Ok(record) = lines.try_recv() {
+            messages.push(record.log.message.clone());
+        }
+        messages
+    }
+
+    #[test]
+    fn lines_follow_every_route_they_match() {
+        let (everything, mut everything_lines) = Sink::channel("everything");
+        let (alerts, mut alert_lines) = Sink::channel("alerts");
+        let forwarder = LogForwarder::new(
+            vec![everything, alerts],
+            vec![
+                Route {
+                    namespaces: Vec::new(),
+                    levels: Vec::new(),
+                    sinks: vec![0],
+                },
+                Route {
+                    namespaces: vec!["payments".to_string()],
+                    levels: vec!["error".to_string()],
+                    sinks: vec![1, 0],
+                },
+            ],
+        )
+        .unwrap();
+
+        forwarder.forward(record("payments", "error", "declined"));
+        forwarder.forward(record("payments", "info", "paid"));
+        forwarder.forward(record("search", "error", "timeout"));
+
+        // Sent once to `everything`, though two routes lead there.
+        assert_eq!(
+            received(&mut everything_lines),
+            vec!["declined", "paid", "timeout"]
+        );
+        assert_eq!(received(&mut alert_lines), vec!["declined"]);
+    }
+
+    #[tokio::test]
+    async fn routes_to_unknown_sinks_are_left_out() {
+        let mut config = WorkloadLogsConfig::default();
+        config.routes.push(LogRouteConfig {
+            namespaces: Vec::new(),
+            levels: Vec::new(),
+            sinks: vec!["missing".to_string()],
+        });
+        assert!(LogForwarder::from_config(&config).is_none());
+
+        config.sinks.insert(
+            "broken".to_string(),
+            LogSinkConfig::Syslog {
+                address: "logs.internal:514".to_string(),
+                facility: "user".to_string(),
+            },
+        );
+        config.sinks.insert(
+            "audit".to_string(),
+            LogSinkConfig::File {
+                path: std::env::temp_dir()
+                    .join("ring-log-forwarder-unused.log")
+                    .display()
+                    .to_string(),
+                max_bytes: 0,
+                max_files: 0,
+            },
+        );
+        config.routes.push(LogRouteConfig {
+            namespaces: vec!["payments".to_string()],
+            levels: vec!["warn".to_string()],
+            sinks: vec!["broken".to_string(), "audit".to_string()],
+        });
+        let forwarder = LogForwarder::from_config(&config).unwrap();
+        assert_eq!(forwarder.sink_names(), vec!["audit"]);
+        assert_eq!(forwarder.routes.len(), 1);
+        assert_eq!(forwarder.routes[0].levels, vec!["warning"]);
+    }
+}
@@ -0,0 +1,677 @@
+//! Destinations of the log forwarder (`[server.logs.sinks]`).
+//!
+//! Each sink runs in its own task behind a bounded queue, so a slow or
+//! unreachable destination only holds up its own lines; once [`QUEUE_LINES`]
+//! are waiting, new ones are dropped (and counted) rather than buffered
+//! without bound. Delivery is best-effort: a line that cannot be written is
+//! logged and skipped, and a TCP syslog connection is re-opened on the next
+//! line.
+//!
+//! - **syslog** — RFC 5424, over UDP (one datagram per line), TCP (octet-
+//!   counted framing, RFC 6587) or a unix datagram socket such as `/dev/log`.
+//!   The namespace, deployment, instance and level travel as structured data.
+//! - **file** — one JSON object per line, rotated by size with
+//!   [`console_logs::rotate_if_needed`].
+//! - **otlp** — OTLP/gRPC logs, with the same exporter as
+//!   `[server.telemetry.logs]` but a provider of its own.
+
+use crate::config::server::LogSinkConfig;
+use crate::hypervisor::console_logs;
+use crate::hypervisor::lifecycle_trait::Log;
+use chrono::{DateTime, SecondsFormat, Utc};
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _, Severity};
+use opentelemetry_otlp::WithExportConfig as _;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::SystemTime;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket, UnixDatagram};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// Lines a sink holds while its destination is slow, before dropping new ones.
+const QUEUE_LINES: usize = 10_000;
+
+/// Lines a sink takes off its queue at once.
+const BATCH_LINES: usize = 256;
+
+/// SD-ID of the structured data element of a syslog line (RFC 5424 §7.2.2:
+/// `name@<private enterprise number>`; 32473 is the one reserved for
+/// documentation, RFC 5612).
+const SD_ID: &str = "ring@32473";
+
+/// A workload line, with where it comes from.
+pub(crate) struct Record {
+    pub(crate) namespace: String,
+    /// The deployment's name.
+    pub(crate) deployment: String,
+    pub(crate) deployment_id: String,
+    pub(crate) log: Log,
+    /// When the forwarder received it.
+    pub(crate) received_at: DateTime<Utc>,
+}
+
+impl Record {
+    /// The line's own time when it carries an RFC 3339 one, when it was
+    /// received otherwise.
+    fn time(&self) -> DateTime<Utc> {
+        self.log
+            .timestamp
+            .as_deref()
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or(self.received_at)
+    }
+}
+
+/// A started sink: [`Sink::send`] queues a line for its task.
+pub(crate) struct Sink {
+    name: String,
+    queue: mpsc::Sender<Arc<Record>>,
+    /// Lines dropped since the queue filled up.
+    dropped: AtomicU64,
+    overflowing: AtomicBool,
+}
+
+impl Sink {
+    /// Start the sink's task. Fails on a configuration the sink cannot work
+    /// with; an unreachable destination is only found out line by line.
+    pub(crate) fn start(name: &str, config: &LogSinkConfig) -> Result<Self, String> {
+        let (queue, lines) = mpsc::channel(QUEUE_LINES);
+        match config {
+            LogSinkConfig::Syslog { address, facility } => {
+                let target = SyslogTarget::parse(address)?;
+                let facility = facility_code(facility)
+                    .ok_or_else(|| format!("unknown syslog facility {}", facility))?;
+                tokio::spawn(run_syslog(name.to_string(), target, facility, lines));
+            }
+            LogSinkConfig::File {
+                path,
+                max_bytes,
+                max_files,
+            } => {
+                tokio::spawn(run_file(PathBuf::from(path), *max_bytes, *max_files, lines));
+            }
+            LogSinkConfig::Otlp {
+                endpoint,
+                service_name,
+            } => {
+                let exporter = opentelemetry_otlp::LogExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(endpoint)
+                    .build()
+                    .map_err(|e| format!("failed to build the OTLP exporter: {}", e))?;
+                let resource = opentelemetry_sdk::Resource::builder()
+                    .with_service_name(service_name.clone())
+                    .build();
+                let provider = opentelemetry_sdk::logs::SdkLoggerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(resource)
+                    .build();
+                tokio::spawn(run_otlp(provider, lines));
+            }
+        }
+        Ok(Self::from_queue(name, queue))
+    }
+
+    fn from_queue(name: &str, queue: mpsc::Sender<Arc<Record>>) -> Self {
+        Self {
+            name: name.to_string(),
+            queue,
+            dropped: AtomicU64::new(0),
+            overflowing: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Queue a line, or drop it when the sink is too far behind.
+    pub(crate) fn send(&self, record: Arc<Record>) {
+        match self.queue.try_send(record) {
+            Ok(()) => {
+                if self.overflowing.swap(false, Ordering::Relaxed) {
+                    warn!(
+                        "log sink {}: caught up, {} lines were dropped",
+                        self.name,
+                        self.dropped.swap(0, Ordering::Relaxed)
+                    );
+                }
+            }
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                if !self.overflowing.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "log sink {}: more than {} lines waiting, dropping new ones",
+                        self.name, QUEUE_LINES
+                    );
+                }
+            }
+            // The task is gone: it already said why.
+            Err(TrySendError::Closed(_)) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+impl Sink {
+    /// A sink whose lines land in the returned receiver.
+    pub(crate) fn channel(name: &str) -> (Self, mpsc::Receiver<Arc<Record>>) {
+        let (queue, lines) = mpsc::channel(QUEUE_LINES);
+        (Self::from_queue(name, queue), lines)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SyslogTarget {
+    Udp(String),
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl SyslogTarget {
+    fn parse(address: &str) -> Result<Self, String> {
+        let with_port = |host: &str| {
+            // A bracketed IPv6 address keeps its colons.
+            let has_port = match host.rfind(']') {
+                Some(close) => host[close..].contains(':'),
+                None => host.contains(':'),
+            };
+            if has_port {
+                host.to_string()
+            } else {
+                format!("{}:514", host)
+            }
+        };
+        if let Some(host) = address.strip_prefix("udp://") {
+            Ok(Self::Udp(with_port(host)))
+        } else if let Some(host) = address.strip_prefix("tcp://") {
+            Ok(Self::Tcp(with_port(host)))
+        } else if let Some(path) = address.strip_prefix("unix://") {
+            Ok(Self::Unix(PathBuf::from(path)))
+        } else {
+            Err(format!(
+                "syslog address {} must start with udp://, tcp:// or unix://",
+                address
+            ))
+        }
+    }
+}
+
+fn facility_code(name: &str) -> Option<u8> {
+    let code = match name {
+        "kern" => 0,
+        "user" => 1,
+        "mail" => 2,
+        "daemon" => 3,
+        "auth" => 4,
+        "syslog" => 5,
+        "lpr" => 6,
+        "news" => 7,
+        "uucp" => 8,
+        "cron" => 9,
+        "authpriv" => 10,
+        "ftp" => 11,
+        _ => {
+            let n: u8 = name.strip_prefix("local")?.parse().ok()?;
+            return (n <= 7).then_some(16 + n);
+        }
+    };
+    Some(code)
+}
+
+/// Syslog severity of a level as `classify_log` reports it.
+fn severity(level: &str) -> u8 {
+    match level {
+        "error" => 3,
+        "warning" => 4,
+        "debug" => 7,
+        _ => 6,
+    }
+}
+
+/// A header field: printable ASCII without spaces, at most `max` long, `-`
+/// when empty (RFC 5424 §6).
+fn header_field(value: &str, max: usize) -> String {
+    let field: String = value
+        .chars()
+        .map(|c| if c.is_ascii_graphic() { c } else { '_' })
+        .take(max)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// A structured data parameter value, with `"`, `\` and `]` escaped.
+fn param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The RFC 5424 line for a record: the deployment is the APP-NAME, the
+/// instance the PROCID, and everything goes in the structured data too.
+fn syslog_line(record: &Record, facility: u8, hostname: &str) -> String {
+    let log = &record.log;
+    let mut data = format!(
+        "[{} namespace=\"{}\" deployment=\"{}\" deployment_id=\"{}\" instance=\"{}\"",
+        SD_ID,
+        param_value(&record.namespace),
+        param_value(&record.deployment),
+        param_value(&record.deployment_id),
+        param_value(&log.instance),
+    );
+    if let Some(container) = &log.container {
+        data.push_str(&format!(" container=\"{}\"", param_value(container)));
+    }
+    data.push_str(&format!(" level=\"{}\"]", param_value(&log.level)));
+
+    format!(
+        "<{}>1 {} {} {} {} - {} {}",
+        u16::from(facility) * 8 + u16::from(severity(&log.level)),
+        record.time().to_rfc3339_opts(SecondsFormat::Micros, true),
+        header_field(hostname, 255),
+        header_field(&record.deployment, 48),
+        header_field(&log.instance, 128),
+        data,
+        log.message
+    )
+}
+
+/// An open syslog transport. TCP connects lazily, and again after a failure.
+enum SyslogConnection {
+    Udp(Option<UdpSocket>),
+    Tcp(Option<TcpStream>),
+    Unix(UnixDatagram),
+}
+
+impl SyslogConnection {
+    async fn send(&mut self, target: &SyslogTarget, line: &str) -> std::io::Result<()> {
+        match (self, target) {
+            (Self::Udp(socket), SyslogTarget::Udp(address)) => {
+                if socket.is_none() {
+                    let peer = tokio::net::lookup_host(address.as_str())
+                        .await?
+                        .next()
+                        .ok_or_else(|| std::io::Error::other("no address"))?;
+                    let local = if peer.is_ipv6() {
+                        "[::]:0"
+                    } else {
+                        "0.0.0.0:0"
+                    };
+                    let bound = UdpSocket::bind(local).await?;
+                    bound.connect(peer).await?;
+                    *socket = Some(bound);
+                }
+                if let Some(connected) = socket {
+                    connected.send(line.as_bytes()).await?;
+                }
+                Ok(())
+            }
+            (Self::Tcp(stream), SyslogTarget::Tcp(address)) => {
+                if stream.is_none() {
+                    *stream = Some(TcpStream::connect(address.as_str()).await?);
+                }
+                let Some(connected) = stream else {
+                    return Ok(());
+                };
+                let framed = format!("{} {}", line.len(), line);
+                if let Err(e) = connected.write_all(framed.as_bytes()).await {
+                    *stream = None;
+                    return Err(e);
+                }
+                Ok(())
+            }
+            (Self::Unix(socket), SyslogTarget::Unix(path)) => {
+                socket.send_to(line.as_bytes(), path).await.map(|_| ())
+            }
+            _ => Err(std::io::Error::other("transport does not match the target")),
+        }
+    }
+}
+
+async fn run_syslog(
+    name: String,
+    target: SyslogTarget,
+    facility: u8,
+    mut lines: mpsc::Receiver<Arc<Record>>,
+) {
+    let mut connection = match &target {
+        SyslogTarget::Udp(_) => SyslogConnection::Udp(None),
+        SyslogTarget::Tcp(_) => SyslogConnection::Tcp(None),
+        SyslogTarget::Unix(_) => match UnixDatagram::unbound() {
+            Ok(socket) => SyslogConnection::Unix(socket),
+            Err(e) => {
+                error!("log sink {}: failed to open a unix socket: {}", name, e);
+                return;
+            }
+        },
+    };
+    let hostname = sysinfo::System::host_name().unwrap_or_default();
+    // Say it once per outage, not once per line.
+    let mut failing = false;
+    let mut batch = Vec::with_capacity(BATCH_LINES);
+    while lines.recv_many(&mut batch, BATCH_LINES).await > 0 {
+        for record in batch.drain(..) {
+            let line = syslog_line(&record, facility, &hostname);
+            match connection.send(&target, &line).await {
+                Ok(()) if failing => {
+                    info!("log sink {}: {:?} reachable again", name, target);
+                    failing = false;
+                }
+                Ok(()) => {}
+                Err(e) if !failing => {
+                    warn!(
+                        "log sink {}: failed to send to {:?}: {} — dropping lines until it works again",
+                        name, target, e
+                    );
+                    failing = true;
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+/// One line of a file sink.
+#[derive(Serialize)]
+struct FileLine<'a> {
+    /// When the forwarder received the line, RFC 3339.
+    received_at: String,
+    namespace: &'a str,
+    deployment: &'a str,
+    deployment_id: &'a str,
+    #[serde(flatten)]
+    log: &'a Log,
+}
+
+async fn run_file(
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    mut lines: mpsc::Receiver<Arc<Record>>,
+) {
+    let mut batch = Vec::with_capacity(BATCH_LINES);
+    while lines.recv_many(&mut batch, BATCH_LINES).await > 0 {
+        let mut buf = Vec::new();
+        for record in batch.drain(..) {
+            let line = FileLine {
+                received_at: record
+                    .received_at
+                    .to_rfc3339_opts(SecondsFormat::Millis, true),
+                namespace: &record.namespace,
+                deployment: &record.deployment,
+                deployment_id: &record.deployment_id,
+                log: &record.log,
+            };
+            if let Ok(json) = serde_json::to_vec(&line) {
+                buf.extend_from_slice(&json);
+                buf.push(b'\n');
+            }
+        }
+
+        if let Some(dir) = path.parent()
+            && let Err(e) = tokio::fs::create_dir_all(dir).await
+        {
+            warn!("log sink: failed to create {:?}: {}", dir, e);
+            continue;
+        }
+        let written = async {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await?;
+            file.write_all(&buf).await
+        }
+        .await;
+        if let Err(e) = written {
+            warn!("log sink: failed to write {:?}: {}", path, e);
+            continue;
+        }
+        console_logs::rotate_if_needed(&path, max_bytes, max_files).await;
+    }
+}
+
+fn otlp_severity(level: &str) -> Option<(Severity, &'static str)> {
+    match level {
+        "error" => Some((Severity::Error, "error")),
+        "warning" => Some((Severity::Warn, "warning")),
+        "info" => Some((Severity::Info, "info")),
+        "debug" => Some((Severity::Debug, "debug")),
+        _ => None,
+    }
+}
+
+fn any_value(value: &serde_json::Value) -> AnyValue {
+    match value {
+        serde_json::Value::Bool(b) => AnyValue::Boolean(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => AnyValue::Int(i),
+            None => AnyValue::Double(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => AnyValue::from(s.clone()),
+        other => AnyValue::from(other.to_string()),
+    }
+}
+
+async fn run_otlp(
+    provider: opentelemetry_sdk::logs::SdkLoggerProvider,
+    mut lines: mpsc::Receiver<Arc<Record>>,
+) {
+    let logger = provider.logger("ring-workloads");
+    let mut batch = Vec::with_capacity(BATCH_LINES);
+    while lines.recv_many(&mut batch, BATCH_LINES).await > 0 {
+        for record in batch.drain(..) {
+            let log = &record.log;
+            let mut otel = logger.create_log_record();
+            otel.set_timestamp(SystemTime::from(record.time()));
+            otel.set_observed_timestamp(SystemTime::from(record.received_at));
+            if let Some((number, text)) = otlp_severity(&log.level) {
+                otel.set_severity_number(number);
+                otel.set_severity_text(text);
+            }
+            otel.set_body(AnyValue::from(log.message.clone()));
+            otel.add_attribute("ring.namespace", record.namespace.clone());
+            otel.add_attribute("ring.deployment", record.deployment.clone());
+            otel.add_attribute("ring.deployment.id", record.deployment_id.clone());
+            otel.add_attribute("ring.instance", log.instance.clone());
+            if let Some(container) = &log.container {
+                otel.add_attribute("ring.container", container.clone());
+            }
+            for (key, value) in &log.fields {
+                otel.add_attribute(key.clone(), any_value(value));
+            }
+            logger.emit(otel);
+        }
+    }
+    if let Err(e) = provider.shutdown() {
+        warn!("log sink: failed to shut the OTLP logger down: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: &str, message: &str, timestamp: Option<&str>) -> Record {
+        Record {
+            namespace: "payments".to_string(),
+            deployment: "api".to_string(),
+            deployment_id: "d1".to_string(),
+            log: Log {
+                instance: "payments_api_1a2b3c4d".to_string(),
+                container: None,
+                message: message.to_string(),
+                level: level.to_string(),
+                timestamp: timestamp.map(str::to_string),
+                fields: Default::default(),
+            },
+            received_at: DateTime::parse_from_rfc3339("2024-01-15T10:30:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn syslog_lines_follow_rfc_5424() {
+        let line = syslog_line(
+            &record("warning", "slow query", Some("2024-01-15T10:29:59.5Z")),
+            16,
+            "ring host",
+        );
+        assert_eq!(
+            line,
+            "<132>1 2024-01-15T10:29:59.500000Z ring_host api payments_api_1a2b3c4d - \
+             [ring@32473 namespace=\"payments\" deployment=\"api\" deployment_id=\"d1\" \
+             instance=\"payments_api_1a2b3c4d\" level=\"warning\"] slow query"
+        );
+
+        // Without a date of its own, the line is dated when it was received.
+        let mut undated = record("unknown", "done", Some("2024/01/15 10:29:59"));
+        undated.log.container = Some("migrate\"]".to_string());
+        let line = syslog_line(&undated, 1, "");
+        assert!(line.starts_with("<14>1 2024-01-15T10:30:00.000000Z - api "));
+        assert!(line.contains(" container=\"migrate\\\"\\]\" "));
+    }
+
+    #[test]
+    fn syslog_addresses_and_facilities() {
+        assert_eq!(
+            SyslogTarget::parse("udp://logs.internal").unwrap(),
+            SyslogTarget::Udp("logs.internal:514".to_string())
+        );
+        assert_eq!(
+            SyslogTarget::parse("tcp://[::1]:6514").unwrap(),
+            SyslogTarget::Tcp("[::1]:6514".to_string())
+        );
+        assert_eq!(
+            SyslogTarget::parse("tcp://[::1]").unwrap(),
+            SyslogTarget::Tcp("[::1]:514".to_string())
+        );
+        assert_eq!(
+            SyslogTarget::parse("unix:///dev/log").unwrap(),
+            SyslogTarget::Unix(PathBuf::from("/dev/log"))
+        );
+        assert!(SyslogTarget::parse("logs.internal:514").is_err());
+
+        assert_eq!(facility_code("user"), Some(1));
+        assert_eq!(facility_code("local7"), Some(23));
+        assert_eq!(facility_code("local8"), None);
+        assert_eq!(facility_code("kernel"), None);
+    }
+
+    #[tokio::test]
+    async fn syslog_over_tcp_is_octet_counted() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("tcp://{}", listener.local_addr().unwrap());
+        let sink = Sink::start(
+            "central",
+            &LogSinkConfig::Syslog {
+                address,
+                facility: "user".to_string(),
+            },
+        )
+        .unwrap();
+
+        sink.send(Arc::new(record("error", "boom\n  at main", None)));
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut received = Vec::new();
+        while !received.ends_with(b"at main") {
+            let mut buf = [0u8; 1024];
+            let n = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+            assert!(n > 0, "connection closed early");
+            received.extend_from_slice(&buf[..n]);
+        }
+        let received = String::from_utf8(received).unwrap();
+        let (length, line) = received.split_once(' ').unwrap();
+        assert_eq!(length.parse::<usize>().unwrap(), line.len());
+        assert!(line.starts_with("<11>1 "));
+    }
+
+    #[tokio::test]
+    async fn syslog_over_udp_sends_one_datagram_per_line() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("udp://{}", receiver.local_addr().unwrap());
+        let sink = Sink::start(
+            "central",
+            &LogSinkConfig::Syslog {
+                address,
+                facility: "local0".to_string(),
+            },
+        )
+        .unwrap();
+
+        sink.send(Arc::new(record("info", "one", None)));
+        sink.send(Arc::new(record("info", "two", None)));
+        let mut buf = [0u8; 2048];
+        for expected in ["one", "two"] {
+            let n = receiver.recv(&mut buf).await.unwrap();
+            let line = std::str::from_utf8(&buf[..n]).unwrap();
+            assert!(line.starts_with("<134>1 "), "{}", line);
+            assert!(line.ends_with(&format!("] {}", expected)), "{}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn the_file_sink_writes_json_lines_and_rotates() {
+        let dir = std::env::temp_dir().join(format!(
+            "ring-log-sink-{}-{}",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let path = dir.join("workloads.log");
+        let sink = Sink::start(
+            "audit",
+            &LogSinkConfig::File {
+                path: path.display().to_string(),
+                max_bytes: 1,
+                max_files: 2,
+            },
+        )
+        .unwrap();
+
+        let mut fielded = record("info", "slow query", None);
+        fielded.log.fields.insert("ms".to_string(), 812.into());
+        sink.send(Arc::new(fielded));
+        let rotated = dir.join("workloads.log.1");
+        for _ in 0..100 {
+            if rotated.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let content = std::fs::read_to_string(&rotated).unwrap();
+        let line: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(line["namespace"], "payments");
+        assert_eq!(line["deployment"], "api");
+        assert_eq!(line["instance"], "payments_api_1a2b3c4d");
+        assert_eq!(line["level"], "info");
+        assert_eq!(line["message"], "slow query");
+        assert_eq!(line["fields"]["ms"], 812);
+        assert_eq!(line["received_at"], "2024-01-15T10:30:00.000Z");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_full_queue_drops_new_lines() {
+        let (sink, _lines) = Sink::channel("slow");
+        for _ in 0..QUEUE_LINES + 3 {
+            sink.send(Arc::new(record("info", "line", None)));
+        }
+        assert_eq!(sink.dropped.load(Ordering::Relaxed), 3);
+        assert!(sink.overflowing.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,158 @@
+//! Followers of every instance's output, for the background consumers of
+//! workload logs: the log archive and the log forwarder.
+//!
+//! Each sweep lists the instances of every deployment and starts a follower
+//! for the ones that have none, through a callback that owns what happens to
+//! the lines. A follower whose stream the runtime ended (the instance stopped)
+//! is started again, where it left off, once the instance runs again; one
+//! whose instance is gone for good is aborted.
+
+use crate::api::server::RuntimeMap;
+use crate::hypervisor::lifecycle_trait::{LogStream, RuntimeLifecycle};
+use crate::models::deployments::{self, Deployment, DeploymentStatus};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tokio::task::JoinHandle;
+
+/// A follower to start, handed to the callback of [`Tailers::sweep`].
+pub(crate) struct Start {
+    pub(crate) deployment: Deployment,
+    pub(crate) runtime: Arc<dyn RuntimeLifecycle>,
+    pub(crate) instance: String,
+    tail: Option<&'static str>,
+    since: Option<i32>,
+    /// Unix timestamp of the last line the follower handled, to resume
+    /// without duplicates. The follower keeps it up to date.
+    pub(crate) last_at: Arc<AtomicI64>,
+}
+
+impl Start {
+    /// The instance's output from where this follower starts.
+    pub(crate) async fn stream(&self) -> LogStream {
+        self.runtime
+            .stream_logs(
+                &self.deployment.id,
+                self.tail,
+                self.since,
+                Some(&self.instance),
+            )
+            .await
+    }
+}
+
+struct Tailer {
+    handle: JoinHandle<()>,
+    last_at: Arc<AtomicI64>,
+}
+
+pub(crate) struct Tailers {
+    /// Who is following, for the log lines: `log archive`, `log forwarder`.
+    owner: &'static str,
+    /// Keyed by (deployment id, instance name).
+    tailers: HashMap<(String, String), Tailer>,
+}
+
+impl Tailers {
+    pub(crate) fn new(owner: &'static str) -> Self {
+        Self {
+            owner,
+            tailers: HashMap::new(),
+        }
+    }
+
+    /// Reconcile the followers with the instances the runtimes report. On the
+    /// first sweep (`startup`), instances start at their current end of
+    /// output: whatever they wrote before was handled by the previous run or
+    /// predates it.
+    pub(crate) async fn sweep(
+        &mut self,
+        pool: &SqlitePool,
+        runtimes: &RuntimeMap,
+        startup: bool,
+        mut spawn: impl FnMut(Start) -> JoinHandle<()>,
+    ) {
+        let all = match deployments::find_all(pool, HashMap::new()).await {
+            Ok(all) => all,
+            Err(e) => {
+                warn!("{}: listing deployments failed: {}", self.owner, e);
+                return;
+            }
+        };
+
+        let mut seen: HashSet<(String, String)> = HashSet::new();
+        for deployment in all {
+            // A deleted deployment is followed only until its last instance
+            // is gone, so its final lines are not lost.
+            if deployment.status == DeploymentStatus::Deleted
+                && !self.tailers.keys().any(|(id, _)| *id == deployment.id)
+            {
+                continue;
+            }
+            let Some(runtime) = runtimes.get(&deployment.runtime) else {
+                continue;
+            };
+
+            let instances = runtime
+                .list_instances_with_names(deployment.id.clone(), "all")
+                .await;
+            let mut running: Option<Vec<(String, String)>> = None;
+            for (_, name) in instances {
+                let key = (deployment.id.clone(), name.clone());
+                seen.insert(key.clone());
+
+                let start = match self.tailers.get(&key) {
+                    None if startup => Some((Some("0"), None)),
+                    None => Some((None, None)),
+                    Some(tailer) if !tailer.handle.is_finished() => None,
+                    // The runtime ended the stream: the instance stopped. Pick
+                    // up where it left off only once it runs again.
+                    Some(tailer) => {
+                        if running.is_none() {
+                            running = Some(
+                                runtime
+                                    .list_instances_with_names(deployment.id.clone(), "running")
+                                    .await,
+                            );
+                        }
+                        running
+                            .as_ref()
+                            .is_some_and(|r| r.iter().any(|(_, n)| *n == name))
+                            .then(|| {
+                                let since = tailer.last_at.load(Ordering::Relaxed) + 1;
+                                (None, Some(since as i32))
+                            })
+                    }
+                };
+                let Some((tail, since)) = start else {
+                    continue;
+                };
+
+                let last_at = self
+                    .tailers
+                    .remove(&key)
+                    .map(|tailer| tailer.last_at)
+                    .unwrap_or_default();
+                let handle = spawn(Start {
+                    deployment: deployment.clone(),
+                    runtime: runtime.clone(),
+                    instance: name,
+                    tail,
+                    since,
+                    last_at: last_at.clone(),
+                });
+                self.tailers.insert(key, Tailer { handle, last_at });
+            }
+        }
+
+        // Instances the runtime no longer reports: their output is gone too.
+        self.tailers.retain(|key, tailer| {
+            let keep = seen.contains(key);
+            if !keep {
+                tailer.handle.abort();
+            }
+            keep
+        });
+    }
+}
@@ -0,0 +1,222 @@
+//! Gauges an autoscaled workload exposes for `autoscale.metrics`, scraped from
+//! its instances over HTTP in the Prometheus text format.
+//!
+//! Runs inside the stats refresh (see [`super::stats_cache`]), so scraping is
+//! paced by the scheduler interval and never sits on the autoscaler's path. The
+//! result is one value **per instance** for each metric, which is what the
+//! autoscaler compares against `target`:
+//!
+//! * a `deployment`-scoped gauge (a queue's backlog, the same on every
+//!   instance) is read from the first instance that answers and split across
+//!   the running instances;
+//! * an `instance`-scoped gauge (requests in flight) is read from every
+//!   instance and averaged over those that answered.
+//!
+//! Fail-soft like the rest of the refresh: an instance that does not answer,
+//! or answers without the gauge, is left out, and a metric nobody reported is
+//! `None` — the autoscaler then holds rather than scaling blind.
+
+use crate::hypervisor::lifecycle_trait::RuntimeLifecycle;
+use crate::models::deployments::{MetricScope, MetricTarget};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Upper bound of one scrape. Several of them run in a refresh round, so a
+/// workload slow to render its metrics must not hold the round for long.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Per-instance value of each of `metrics` for a deployment, in order.
+pub(crate) async fn scrape(
+    runtime: &dyn RuntimeLifecycle,
+    deployment_id: &str,
+    metrics: &[MetricTarget],
+) -> Vec<Option<f64>> {
+    let instances = runtime
+        .list_instances(deployment_id.to_string(), "running")
+        .await;
+    if instances.is_empty() {
+        return vec![None; metrics.len()];
+    }
+    let mut addresses = Vec::with_capacity(instances.len());
+    for instance in &instances {
+        if let Some(address) = runtime.instance_address(instance).await {
+            addresses.push(address);
+        }
+    }
+
+    let client = match reqwest::Client::builder().timeout(SCRAPE_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("metric scraper: failed to build an HTTP client: {}", e);
+            return vec![None; metrics.len()];
+        }
+    };
+    // Several metrics commonly live on the same endpoint: fetch each one once.
+    let mut pages: HashMap<(IpAddr, u16, String), Option<String>> = HashMap::new();
+    let mut out = Vec::with_capacity(metrics.len());
+    for metric in metrics {
+        let mut values = Vec::new();
+        for address in &addresses {
+            let key = (*address, metric.port, metric.path.clone());
+            if !pages.contains_key(&key) {
+                let page = fetch(&client, *address, metric).await;
+                pages.insert(key.clone(), page);
+            }
+            let value = pages[&key]
+                .as_deref()
+                .and_then(|page| parse_gauge(page, &metric.name, &metric.labels));
+            if let Some(value) = value {
+                values.push(value);
+                if metric.scope == MetricScope::Deployment {
+                    break;
+                }
+            }
+        }
+        out.push(per_instance(metric.scope, &values, instances.len()));
+    }
+    out
+}
+
+/// Fold what the instances reported into the value per instance.
+fn per_instance(scope: MetricScope, values: &[f64], instances: usize) -> Option<f64> {
+    if values.is_empty() || instances == 0 {
+        return None;
+    }
+    match scope {
+        MetricScope::Deployment => Some(values[0] / instances as f64),
+        MetricScope::Instance => Some(values.iter().sum::<f64>() / values.len() as f64),
+    }
+}
+
+async fn fetch(client: &reqwest::Client, address: IpAddr, metric: &MetricTarget) -> Option<String> {
+    let url = format!("http://{}{}", socket(address, metric.port), metric.path);
+    match client.get(&url).send().await {
+        Ok(response) if response.status().is_success() => response.text().await.ok(),
+        Ok(response) => {
+            debug!("metric scraper: {} answered {}", url, response.status());
+            None
+        }
+        Err(e) => {
+            debug!("metric scraper: {} failed: {}", url, e);
+            None
+        }
+    }
+}
+
+fn socket(address: IpAddr, port: u16) -> String {
+    match address {
+        IpAddr::V4(v4) => format!("{}:{}", v4, port),
+        IpAddr::V6(v6) => format!("[{}]:{}", v6, port),
+    }
+}
+
+/// Sum of the samples of `name` in a Prometheus text page that carry every
+/// label of `labels`. `None` when no sample matches.
+pub(crate) fn parse_gauge(page: &str, name: &str, labels: &HashMap<String, String>) -> Option<f64> {
+    let mut total = None;
+    for line in page.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((sample_name, sample_labels, rest)) = split_sample(line) else {
+            continue;
+        };
+        if sample_name != name
+            || !labels
+                .iter()
+                .all(|(key, value)| sample_labels.get(key) == Some(value))
+        {
+            continue;
+        }
+        let Some(value) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|v| v.parse::<f64>().ok())
+        else {
+            continue;
+        };
+        total = Some(total.unwrap_or(0.0) + value);
+    }
+    total
+}
+
+/// `name{a="1",b="2"} 42 1700000000` into its name, labels, and what follows
+/// (value and optional timestamp).
+fn split_sample(line: &str) -> Option<(&str, HashMap<String, String>, &str)> {
+    let Some(open) = line.find('{') else {
+        let (name, rest) = line.split_once(char::is_whitespace)?;
+        return Some((name, HashMap::new(), rest));
+    };
+    let name = &line[..open];
+    let mut labels = HashMap::new();
+    let mut chars = line[open + 1..].char_indices();
+    let mut key = String::new();
+    loop {
+        let (index, c) = chars.next()?;
+        match c {
+            '}' => return Some((name, labels, &line[open + 1 + index + 1..])),
+            ',' | ' ' => {}
+            '=' => {
+                if chars.next()?.1 != '"' {
+                    return None;
+                }
+                let mut value = String::new();
+                loop {
+                    match chars.next()?.1 {
+                        '\\' => match chars.next()?.1 {
+                            'n' => value.push('\n'),
+                            escaped => value.push(escaped),
+                        },
+                        '"' => break,
+                        c => value.push(c),
+                    }
+                }
+                labels.insert(std::mem::take(&mut key), value);
+            }
+            c => key.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"
+# HELP queue_backlog Messages waiting.
+# TYPE queue_backlog gauge
+queue_backlog{queue="orders",shard="1"} 120
+queue_backlog{queue="orders",shard="2"} 30 1700000000
+queue_backlog{queue="refunds, late"} 7
+in_flight 4
+"#;
+
+    #[test]
+    fn sums_the_matching_series() {
+        let orders = HashMap::from([("queue".to_string(), "orders".to_string())]);
+        assert_eq!(parse_gauge(PAGE, "queue_backlog", &orders), Some(150.0));
+        assert_eq!(
+            parse_gauge(PAGE, "queue_backlog", &HashMap::new()),
+            Some(157.0)
+        );
+        let late = HashMap::from([("queue".to_string(), "refunds, late".to_string())]);
+        assert_eq!(parse_gauge(PAGE, "queue_backlog", &late), Some(7.0));
+        assert_eq!(parse_gauge(PAGE, "in_flight", &HashMap::new()), Some(4.0));
+        assert_eq!(parse_gauge(PAGE, "missing", &HashMap::new()), None);
+    }
+
+    #[test]
+    fn folds_values_by_scope() {
+        // A backlog of 150 read once, over 3 instances: 50 each.
+        assert_eq!(
+            per_instance(MetricScope::Deployment, &[150.0], 3),
+            Some(50.0)
+        );
+        assert_eq!(
+            per_instance(MetricScope::Instance, &[4.0, 8.0], 3),
+            Some(6.0)
+        );
+        assert_eq!(per_instance(MetricScope::Instance, &[], 3), None);
+    }
+}
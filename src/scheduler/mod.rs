@@ -1,9 +1,19 @@
 pub(crate) mod autoscaler;
 pub(crate) mod backoff;
+pub(crate) mod canary;
+pub(crate) mod cronjob;
+pub(crate) mod dependencies;
 pub(crate) mod docker_events;
 pub(crate) mod event_worker;
 pub(crate) mod health_checker;
 pub(crate) mod healthy_window;
+pub(crate) mod idle;
 pub(crate) mod intentional_shutdowns;
+pub(crate) mod job;
+pub(crate) mod log_archive;
+pub(crate) mod log_forwarder;
+pub(crate) mod log_sinks;
+pub(crate) mod log_tailers;
+pub(crate) mod metric_scraper;
 pub(crate) mod scheduler;
 pub(crate) mod stats_cache;
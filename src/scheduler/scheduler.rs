@@ -3,17 +3,21 @@ use crate::hypervisor::lifecycle_trait::RuntimeLifecycle;
 use crate::models::config;
 use crate::models::config::Config;
 use crate::models::deployment_event;
-use crate::models::deployments::{self, Deployment, DeploymentStatus, EnvValue};
+use crate::models::deployments::{self, Deployment, DeploymentStatus, EnvValue, Strategy};
 use crate::models::health_check::{HealthCheck, HealthCheckStatus};
 use crate::models::health_check_logs;
 use crate::models::secret as SecretModel;
 use crate::models::volume::ResolvedMount;
-use crate::scheduler::autoscaler::{Autoscaler, Decision};
+use crate::scheduler::autoscaler::{self, Autoscaler, Decision, Observation};
 use crate::scheduler::backoff::RetryBackoff;
+use crate::scheduler::canary;
+use crate::scheduler::cronjob;
+use crate::scheduler::dependencies;
 use crate::scheduler::docker_events::DockerEvent;
 use crate::scheduler::health_checker::HealthChecker;
 use crate::scheduler::healthy_window::HealthyWindow;
 use crate::scheduler::intentional_shutdowns::IntentionalShutdowns;
+use crate::scheduler::job;
 use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::env;
@@ -23,15 +27,32 @@ use tokio::time::{Duration, Instant, sleep};
 use tracing::Instrument as _;
 
 async fn resolve_environment(deployment: &mut Deployment, pool: &SqlitePool) -> Result<(), String> {
+    deployment.environment =
+        resolve_env_values(&deployment.environment, &deployment.namespace, pool).await?;
+    // Init containers and sidecars take `secretRef`s from the same namespace.
+    for container in deployment
+        .init_containers
+        .iter_mut()
+        .chain(deployment.sidecars.iter_mut())
+    {
+        container.environment =
+            resolve_env_values(&container.environment, &deployment.namespace, pool).await?;
+    }
+    Ok(())
+}
+
+async fn resolve_env_values(
+    environment: &HashMap<String, EnvValue>,
+    namespace: &str,
+    pool: &SqlitePool,
+) -> Result<HashMap<String, EnvValue>, String> {
     let mut resolved = HashMap::new();
 
-    for (key, env_value) in deployment.environment.iter() {
+    for (key, env_value) in environment.iter() {
         let value = match env_value {
             EnvValue::Plain(v) => EnvValue::Plain(v.clone()),
             EnvValue::SecretRef { secret_ref } => {
-                match SecretModel::find_by_namespace_name(pool, &deployment.namespace, secret_ref)
-                    .await
-                {
+                match SecretModel::find_by_namespace_name(pool, namespace, secret_ref).await {
                     Ok(Some(secret)) => match secret.get_decrypted_value() {
                         Ok(v) => EnvValue::Plain(v),
                         Err(e) => {
@@ -44,7 +65,7 @@ async fn resolve_environment(deployment: &mut Deployment, pool: &SqlitePool) ->
                     Ok(None) => {
                         return Err(format!(
                             "Secret '{}' not found in namespace '{}'",
-                            secret_ref, deployment.namespace
+                            secret_ref, namespace
                         ));
                     }
                     Err(e) => {
@@ -56,8 +77,7 @@ async fn resolve_environment(deployment: &mut Deployment, pool: &SqlitePool) ->
         resolved.insert(key.clone(), value);
     }
 
-    deployment.environment = resolved;
-    Ok(())
+    Ok(resolved)
 }
 
 /// Resolve `config.image_pull_secret` into inline registry credentials before
@@ -544,6 +564,18 @@ fn has_live_container(status: &DeploymentStatus, instances_empty: bool) -> bool
     }
 }
 
+/// The window a worker must stay up for its `restart_count` to be forgiven:
+/// its anti-flap window, stretched to the longest backoff of its restart
+/// policy. A shorter one would reset the count, and with it the backoff, before
+/// the long delays ever came into play.
+fn healthy_window_for(worker: &Deployment) -> Duration {
+    let window = min_healthy_time_for(worker);
+    match &worker.restart_policy {
+        Some(policy) => window.max(policy.backoff_bounds().1),
+        None => window,
+    }
+}
+
 /// Resolve the anti-flap window for a deployment: take the max of the
 /// per-HC `min_healthy_time` (parsed via `HealthCheck::parse_duration`)
 /// across readiness checks. Falls back to `DEFAULT_MIN_HEALTHY_TIME` when
@@ -653,7 +685,7 @@ fn rollout_deadline_exceeded(child: &Deployment) -> bool {
 /// is declared (callers fall back to legacy behaviour), and holds the rollout
 /// (`PendingNoResult` / `PendingMinHealthyTime` / `Failing`) on any DB error so
 /// a transient read failure never falsely reports "ready".
-async fn readiness_decision(
+pub(crate) async fn readiness_decision(
     pool: &SqlitePool,
     deployment: &Deployment,
 ) -> crate::models::health_check::ReadinessDecision {
@@ -861,12 +893,217 @@ async fn gate_running_on_readiness(
     }
 }
 
+/// Ceiling on a deployment's instance count for this tick while it takes part
+/// in a rollout, `None` otherwise. Set on the resolved deployment before
+/// `apply_runtime`, so the runtimes see it through `target_replicas()`.
+///
+/// - The outgoing deployment (`is_rollout_parent`) never grows back: it is
+///   capped at what is running now, so an instance drained by
+///   [`handle_rolling_update`] is not respawned by the parent's own reconcile.
+/// - The incoming one is held by its strategy: `recreate` starts nothing until
+///   every old instance is gone, `rolling` (and a promoted canary) keeps both
+///   versions together within `target + max_surge`, a canary under analysis
+///   stays at its `instances`.
+async fn rollout_limit(
+    pool: &SqlitePool,
+    deployment: &Deployment,
+    is_rollout_parent: bool,
+    runtime: &dyn RuntimeLifecycle,
+) -> Option<u32> {
+    if is_rollout_parent {
+        let running = runtime
+            .list_instances(deployment.id.clone(), "running")
+            .await;
+        return Some(running.len() as u32);
+    }
+
+    let parent_id = deployment.parent_id.clone()?;
+    let strategy = deployment.strategy.clone().unwrap_or_default();
+    if let Strategy::Canary { instances, .. } = strategy
+        && !canary::is_promoted(pool, deployment).await
+    {
+        return Some(instances);
+    }
+    match strategy.rolling_budget() {
+        None => {
+            let parent = runtime.list_instances(parent_id, "all").await;
+            (!parent.is_empty()).then_some(0)
+        }
+        Some((max_surge, _)) => {
+            let parent = runtime.list_instances(parent_id, "running").await;
+            let child = runtime
+                .list_instances(deployment.id.clone(), "running")
+                .await;
+            Some(rolling_child_limit(
+                deployment.settled_replicas(),
+                max_surge,
+                parent.len(),
+                child.len(),
+            ))
+        }
+    }
+}
+
+/// How many instances the incoming side of a rolling update may run: both
+/// versions together stay within `target + max_surge`, and what is already up
+/// is never scaled back.
+fn rolling_child_limit(
+    target: u32,
+    max_surge: u32,
+    parent_running: usize,
+    child_running: usize,
+) -> u32 {
+    (target + max_surge)
+        .saturating_sub(parent_running as u32)
+        .max(child_running as u32)
+}
+
+/// How many running instances of the outgoing side a rolling update may drain
+/// now: enough to bring the ready total down to `target - max_unavailable`,
+/// never more than the parent has.
+fn rolling_drain_count(
+    target: u32,
+    max_unavailable: u32,
+    child_ready: usize,
+    parent_running: usize,
+) -> usize {
+    let floor = target.saturating_sub(max_unavailable) as usize;
+    (child_ready + parent_running)
+        .saturating_sub(floor)
+        .min(parent_running)
+}
+
+/// Running instances of a rolling-update child that count as ready for the
+/// drain: all of them once the readiness gate is green (see
+/// [`is_ready_to_drain`]), none before.
+///
+/// Deadline guard: a child whose readiness probe never turns green would
+/// otherwise pin the parent forever — leaving two versions running
+/// indefinitely (e.g. a broken probe). Once the child has been alive past
+/// RING_ROLLOUT_DEADLINE (default 600s), its instances count as ready anyway:
+/// they are serving traffic regardless, so finishing the rollout is strictly
+/// better than a stuck pair. Operators still get a warning + event to fix the
+/// probe.
+async fn ready_child_instances(
+    pool: &SqlitePool,
+    child: &Deployment,
+    runtime: &dyn RuntimeLifecycle,
+) -> usize {
+    if child.status != DeploymentStatus::Running {
+        return 0;
+    }
+    let running = runtime.list_instances(child.id.clone(), "running").await;
+    if running.is_empty() || is_ready_to_drain(pool, child).await {
+        return running.len();
+    }
+    if !rollout_deadline_exceeded(child) {
+        return 0;
+    }
+
+    warn!(
+        "Rolling update: child {} still not ready after deadline — forcing parent drain to avoid a stuck duplicate (check its readiness probe)",
+        child.id
+    );
+    let _ = deployment_event::log_event(
+        pool,
+        child.id.clone(),
+        "warning",
+        "Rolling update: readiness never turned green before the deadline; draining the previous deployment anyway to avoid running two versions. Check the readiness health check.".to_string(),
+        "scheduler",
+        Some("rolling_update_deadline_forced"),
+    )
+    .await;
+    running.len()
+}
+
+/// Take a canary through its analysis window, one step per tick.
+///
+/// The window opens once the canary's instances are all running and its
+/// readiness gate is green. It closes with a promotion when `analysis_window`
+/// has elapsed without a failure (see [`canary::failure_since`]), with an abort
+/// on the first failure. A canary that never becomes ready is aborted at the
+/// rollout deadline: unlike a rolling update, forcing the drain would defeat
+/// the point of a canary.
+async fn analyse_canary(
+    pool: &SqlitePool,
+    child: &mut Deployment,
+    parent_id: &str,
+    instances: u32,
+    analysis_window: &str,
+    runtime: &dyn RuntimeLifecycle,
+) {
+    let started =
+        match deployment_event::find_latest_by_reason(pool, &child.id, canary::STARTED).await {
+            Ok(started) => started,
+            Err(e) => {
+                warn!(
+                    "Failed to load the canary state of {}: {} — holding",
+                    child.id, e
+                );
+                return;
+            }
+        };
+
+    let Some(started) = started else {
+        let wanted = instances.min(child.settled_replicas()) as usize;
+        let running = runtime.list_instances(child.id.clone(), "running").await;
+        if child.status == DeploymentStatus::Running
+            && running.len() >= wanted
+            && is_ready_to_drain(pool, child).await
+        {
+            canary::start(pool, child, parent_id, analysis_window).await;
+        } else if rollout_deadline_exceeded(child) {
+            canary::abort(
+                pool,
+                child,
+                parent_id,
+                "never became ready before the rollout deadline",
+                "scheduler",
+            )
+            .await;
+        }
+        return;
+    };
+
+    if let Some(failure) = canary::failure_since(pool, child, &started.timestamp).await {
+        canary::abort(pool, child, parent_id, &failure, "scheduler").await;
+        return;
+    }
+
+    // Validated at the API; a stored value that no longer parses falls back to
+    // the default rather than holding the canary forever.
+    let window = HealthCheck::parse_duration(analysis_window).unwrap_or(Duration::from_secs(300));
+    let elapsed = chrono::DateTime::parse_from_rfc3339(&started.timestamp)
+        .map(|at| chrono::Utc::now() - at.with_timezone(&chrono::Utc))
+        .unwrap_or_default();
+    if elapsed.num_milliseconds() >= window.as_millis() as i64 {
+        canary::promote(
+            pool,
+            child,
+            parent_id,
+            &format!("green for {}", analysis_window),
+            "scheduler",
+        )
+        .await;
+    }
+}
+
 /// Handle rolling update coordination for deployments that have a `parent_id`.
 ///
 /// Called after `apply_runtime` + `run_health_checks` for each child deployment.
-/// - If the child is `Running` (healthy): remove one instance from the parent.
-///   When the parent reaches 0 instances, mark it as `Deleted` and clear `parent_id`.
-/// - If the child is `Failed`: stop the rollout — parent containers keep running.
+/// How many parent instances go this cycle depends on the child's strategy:
+/// - `rolling`: as many as keep the ready total (child ready + parent running)
+///   at or above `target - max_unavailable`. Dead parent instances serve
+///   nothing and are always drained. With the default strategy this is one
+///   parent instance per ready child instance.
+/// - `recreate`: every parent instance at once, without waiting for the child,
+///   which `rollout_limit` keeps at zero until the parent is gone.
+/// - `canary`: nothing until the canary is promoted (see [`analyse_canary`]),
+///   then as `rolling` with the default budget.
+///
+/// When the parent reaches 0 instances, it is marked `Deleted` and the child's
+/// `parent_id` is cleared. If the child is `Failed`, the rollout stops and the
+/// parent keeps its remaining instances.
 async fn handle_rolling_update(
     pool: &SqlitePool,
     child: &mut Deployment,
@@ -904,43 +1141,26 @@ async fn handle_rolling_update(
         return;
     }
 
-    // Only proceed when the child has at least one running instance.
-    if child.status != DeploymentStatus::Running || child.instances.is_empty() {
+    // A canary drains nothing until it is promoted.
+    let strategy = child.strategy.clone().unwrap_or_default();
+    if let Strategy::Canary {
+        instances,
+        analysis_window,
+    } = &strategy
+        && !canary::is_promoted(pool, child).await
+    {
+        analyse_canary(
+            pool,
+            child,
+            &parent_id,
+            *instances,
+            analysis_window,
+            runtime,
+        )
+        .await;
         return;
     }
 
-    // Readiness gate. If the child declares any HC with `readiness: true`,
-    // we hold off on draining the parent until those checks have been
-    // green for at least `min_healthy_time`. Deployments without any
-    // readiness HC keep the legacy behaviour (drain on `Running`) so this
-    // change is fully opt-in for existing manifests.
-    //
-    // Deadline guard: a child whose readiness probe never turns green would
-    // otherwise pin the parent forever — leaving two instances running
-    // indefinitely (e.g. a broken probe). Once the child has been alive past
-    // RING_ROLLOUT_DEADLINE (default 600s), force the drain: the child is
-    // serving traffic regardless, so one instance is strictly better than a
-    // stuck pair. Operators still get a warning + event to fix the probe.
-    if !is_ready_to_drain(pool, child).await {
-        if rollout_deadline_exceeded(child) {
-            warn!(
-                "Rolling update: child {} still not ready after deadline — forcing parent drain to avoid a stuck duplicate (check its readiness probe)",
-                child.id
-            );
-            let _ = deployment_event::log_event(
-                pool,
-                child.id.clone(),
-                "warning",
-                "Rolling update: readiness never turned green before the deadline; draining the previous deployment anyway to avoid running two instances. Check the readiness health check.".to_string(),
-                "scheduler",
-                Some("rolling_update_deadline_forced"),
-            )
-            .await;
-        } else {
-            return;
-        }
-    }
-
     // Load the parent deployment.
     let mut parent = match deployments::find(pool, &parent_id).await {
         Ok(Some(d)) => d,
@@ -959,6 +1179,16 @@ async fn handle_rolling_update(
         }
     };
 
+    // Pausing either side holds the rollout: the old deployment keeps every
+    // instance it still has until it is resumed.
+    if parent.paused {
+        debug!(
+            "Rolling update of {} on hold: parent {} is paused",
+            child.id, parent.id
+        );
+        return;
+    }
+
     // Refresh the parent's instance list. Deliberately `all`, not `active`: the
     // drain below is what releases each instance's host resources (tap, rootfs
     // copy, console logs, temp volumes), and a dead-but-not-reaped instance
@@ -968,25 +1198,43 @@ async fn handle_rolling_update(
     // crashed VM left behind.
     parent.instances = runtime.list_instances(parent.id.clone(), "all").await;
 
-    // Drain one instance per cycle if the parent still has some. If the
-    // remove fails, bail — the next cycle will retry.
-    if !parent.instances.is_empty() {
-        let instance_id = parent.instances[0].clone();
-        if runtime.remove_instance(instance_id.clone()).await {
-            parent.instances.remove(0);
-            info!(
-                "Rolling update: removed instance {} from parent {} ({} remaining)",
-                instance_id,
-                parent.id,
-                parent.instances.len()
+    let to_drain: Vec<String> = match strategy.rolling_budget() {
+        None => parent.instances.clone(),
+        Some((_, max_unavailable)) => {
+            let running = runtime.list_instances(parent.id.clone(), "running").await;
+            let ready = ready_child_instances(pool, child, runtime).await;
+            let count = rolling_drain_count(
+                child.settled_replicas(),
+                max_unavailable,
+                ready,
+                running.len(),
             );
-        } else {
+            parent
+                .instances
+                .iter()
+                .filter(|id| !running.contains(id))
+                .chain(running.iter().take(count))
+                .cloned()
+                .collect()
+        }
+    };
+
+    // If a remove fails, bail — the next cycle will retry.
+    for instance_id in to_drain {
+        if !runtime.remove_instance(instance_id.clone()).await {
             warn!(
                 "Rolling update: failed to remove instance {} from parent {}, will retry next cycle",
                 instance_id, parent.id
             );
             return;
         }
+        parent.instances.retain(|id| *id != instance_id);
+        info!(
+            "Rolling update: removed instance {} from parent {} ({} remaining)",
+            instance_id,
+            parent.id,
+            parent.instances.len()
+        );
 
         if let Err(e) = deployment_event::log_event(
             pool,
@@ -1052,13 +1300,181 @@ async fn handle_rolling_update(
     }
 }
 
+/// Whether a rolling restart is under way and free to progress: instances are
+/// pending, and the deployment is `Running` outside of any rollout (draining
+/// its parent, or being drained), which would otherwise remove instances
+/// together with it.
+fn restart_in_progress(deployment: &Deployment) -> bool {
+    !deployment.pending_restart.is_empty()
+        && deployment.status == DeploymentStatus::Running
+        && deployment.parent_id.is_none()
+        && deployment.rollout_limit.is_none()
+}
+
+/// Whether a rolling restart in progress brings each replacement up before
+/// removing the instance it replaces ([`Deployment::can_surge`]). A deployment
+/// publishing a host port, on the host network, or whose strategy allows no
+/// overlap is restarted the other way round.
+fn restart_surges(deployment: &Deployment) -> bool {
+    restart_in_progress(deployment) && deployment.can_surge()
+}
+
+/// Progress a rolling restart (`POST /deployments/{id}/restart`).
+///
+/// `pending_restart` holds the instances that were running when the restart
+/// was requested. One of them is removed per cycle, once the deployment
+/// passes the same readiness gate as a rolling-update drain
+/// ([`is_ready_to_drain`]) and the request or the previous step is
+/// `min_healthy_time` old. When it [surges](restart_surges), the runtime runs
+/// one instance over the count (`restart_surge`) and the old instance waits
+/// for that replacement, so capacity never drops below the count. Otherwise it
+/// waits for the deployment to be back at full count, and the runtime starts
+/// the replacement on its next tick: capacity drops by one meanwhile.
+///
+/// Called after [`handle_rolling_update`], which may have just cleared
+/// `parent_id`.
+async fn handle_rolling_restart(
+    pool: &SqlitePool,
+    deployment: &mut Deployment,
+    runtime: &dyn RuntimeLifecycle,
+) {
+    if !restart_in_progress(deployment) {
+        return;
+    }
+
+    // Instances that died or were replaced on their own since the request
+    // already count as restarted.
+    let live = runtime
+        .list_instances(deployment.id.clone(), "running")
+        .await;
+    let before = deployment.pending_restart.len();
+    deployment.pending_restart.retain(|id| live.contains(id));
+
+    if !deployment.pending_restart.is_empty() {
+        // The replacement is not up yet.
+        let wanted = deployment.settled_replicas() + u32::from(deployment.can_surge());
+        if (live.len() as u32) < wanted
+            || !is_ready_to_drain(pool, deployment).await
+            || !restart_step_settled(pool, deployment).await
+        {
+            if deployment.pending_restart.len() != before {
+                persist_pending_restart(pool, deployment).await;
+            }
+            return;
+        }
+
+        let instance_id = deployment.pending_restart[0].clone();
+        if !runtime.remove_instance(instance_id.clone()).await {
+            warn!(
+                "Rolling restart: failed to remove instance {} of {}, will retry next cycle",
+                instance_id, deployment.id
+            );
+            return;
+        }
+        deployment.pending_restart.remove(0);
+        persist_pending_restart(pool, deployment).await;
+
+        info!(
+            "Rolling restart: replaced instance {} of {} ({} remaining)",
+            instance_id,
+            deployment.id,
+            deployment.pending_restart.len()
+        );
+        let _ = deployment_event::log_event(
+            pool,
+            deployment.id.clone(),
+            "info",
+            format!(
+                "Rolling restart: replaced instance {} ({} remaining)",
+                instance_id,
+                deployment.pending_restart.len()
+            ),
+            "scheduler",
+            Some("restart"),
+        )
+        .await;
+        events::publish(
+            pool,
+            Event::deployment_restart(deployment, "step", Some(&instance_id)),
+        )
+        .await;
+
+        if !deployment.pending_restart.is_empty() {
+            return;
+        }
+    } else {
+        persist_pending_restart(pool, deployment).await;
+    }
+
+    info!("Rolling restart of {} complete", deployment.id);
+    let _ = deployment_event::log_event(
+        pool,
+        deployment.id.clone(),
+        "info",
+        "Rolling restart complete: every instance was replaced".to_string(),
+        "scheduler",
+        Some("restart_complete"),
+    )
+    .await;
+    events::publish(
+        pool,
+        Event::deployment_restart(deployment, "complete", None),
+    )
+    .await;
+}
+
+/// True once the restart was requested, and the last instance it replaced
+/// removed, at least `min_healthy_time` ago: the replacement is started on the
+/// tick that follows either. Readiness is tracked per check type, not per
+/// instance, so without this pause the still-green older instances would let
+/// the restart race ahead of a replacement that has not been probed yet.
+async fn restart_step_settled(pool: &SqlitePool, deployment: &Deployment) -> bool {
+    let mut last = None;
+    for reason in ["restart_requested", "restart"] {
+        match deployment_event::find_latest_by_reason(pool, &deployment.id, reason).await {
+            Ok(Some(event)) => {
+                if let Ok(at) = chrono::DateTime::parse_from_rfc3339(&event.timestamp) {
+                    last = last.max(Some(at.with_timezone(&chrono::Utc)));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!(
+                    "Failed to load the last restart step of {}: {} — holding",
+                    deployment.id, e
+                );
+                return false;
+            }
+        }
+    }
+
+    match last {
+        Some(at) => {
+            let elapsed = chrono::Utc::now() - at;
+            elapsed.num_milliseconds() >= min_healthy_time_for(deployment).as_millis() as i64
+        }
+        None => true,
+    }
+}
+
+async fn persist_pending_restart(pool: &SqlitePool, deployment: &Deployment) {
+    if let Err(e) =
+        deployments::set_pending_restart(pool, &deployment.id, &deployment.pending_restart).await
+    {
+        error!(
+            "Failed to persist rolling restart progress of {}: {}",
+            deployment.id, e
+        );
+    }
+}
+
 /// Drain all Docker events currently in the channel and apply their effects to
 /// the database. Non-blocking: returns as soon as the channel is empty so the
 /// scheduler can proceed to its reconciliation pass.
 ///
 /// On every event that signals an instance has died (die / oom / kill), bump
 /// `restart_count` for the deployment and log a deployment_event so the user
-/// can see the crash trace. Once `restart_count` reaches `MAX_RESTART_COUNT`,
+/// can see the crash trace. Once `restart_count` spends the failure budget,
 /// the existing logic in `lifecycle::handle_worker_deployment` flips the
 /// status to `CrashLoopBackOff` and stops respawning — that's what bounds the
 /// loop and prevents disk saturation.
@@ -1223,9 +1639,8 @@ async fn run_autoscaling(
     stats: &crate::scheduler::stats_cache::StatsCache,
     autoscaler: &mut Autoscaler,
 ) {
-    // Snapshot the per-deployment CPU averages once, keyed the same way the
-    // cache is (namespace + name is unique per deployment).
-    let measurements: HashMap<String, Option<f64>> = {
+    // Snapshot the per-deployment measurements once, keyed by id.
+    let measurements: HashMap<String, crate::scheduler::stats_cache::DeploymentRuntimeStats> = {
         let Ok(snapshot) = stats.read() else {
             warn!("Stats snapshot lock poisoned; skipping autoscaling this tick");
             return;
@@ -1249,7 +1664,7 @@ async fn run_autoscaling(
         snapshot
             .deployments
             .iter()
-            .map(|d| (d.id.clone(), d.cpu_usage_percent_per_instance))
+            .map(|d| (d.id.clone(), d.clone()))
             .collect()
     };
 
@@ -1278,7 +1693,9 @@ async fn run_autoscaling(
         // rolling updates entirely: the incoming child is still converging
         // (`parent_id` set), and the outgoing parent is draining (its id shows
         // up as someone's parent). Resizing either reacts to a transient state.
+        // A paused deployment is left at whatever count it was paused at.
         if deployment.status != DeploymentStatus::Running
+            || deployment.paused
             || deployment.parent_id.is_some()
             || draining.contains(&deployment.id)
         {
@@ -1288,13 +1705,41 @@ async fn run_autoscaling(
         // Keyed by id, never by name: a rollout puts two running deployments
         // under the same namespace/name, so a name lookup could hand this
         // deployment the other one's CPU.
-        let cpu = measurements.get(&deployment.id).copied().flatten();
+        let observed = measurements
+            .get(&deployment.id)
+            .map(|stats| observe(deployment, stats))
+            .unwrap_or_default();
 
         let current = deployment.target_replicas();
 
-        match autoscaler.decide(&deployment.id, policy, current, cpu, now) {
+        match autoscaler.decide(&deployment.id, policy, current, &observed, now) {
             Decision::Hold => {}
             Decision::ScaleTo(target) => {
+                if target > current
+                    && let Some(reason) = quota_refusal(pool, deployment, target).await
+                {
+                    warn!(
+                        "Autoscaling {}/{} to {} instances held back: {}",
+                        deployment.namespace, deployment.name, target, reason
+                    );
+                    // Refused again on every tick while the namespace is at
+                    // its quota: only its start, or a new reason, is an event.
+                    if autoscaler.hold_back(&deployment.id, &reason) {
+                        let _ = crate::models::deployment_event::log_event(
+                            pool,
+                            deployment.id.clone(),
+                            "warning",
+                            format!(
+                                "Autoscaling from {} to {} instances held back: {}",
+                                current, target, reason
+                            ),
+                            "autoscaler",
+                            Some("quota_exceeded"),
+                        )
+                        .await;
+                    }
+                    continue;
+                }
                 match deployments::set_desired_replicas(pool, &deployment.id, target).await {
                     Ok(true) => {}
                     Ok(false) => {
@@ -1321,15 +1766,12 @@ async fn run_autoscaling(
                 // reconciliation loop below consumes this list, so without
                 // this the new count would only take effect one tick later.
                 deployment.desired_replicas = Some(target);
+                autoscaler.release(&deployment.id);
 
+                let signals = autoscaler::describe(policy, &observed);
                 info!(
-                    "Autoscaled {}/{}: {} -> {} instances (cpu {:.1}% vs target {:.1}%)",
-                    deployment.namespace,
-                    deployment.name,
-                    current,
-                    target,
-                    cpu.unwrap_or(f64::NAN),
-                    policy.target_cpu
+                    "Autoscaled {}/{}: {} -> {} instances ({})",
+                    deployment.namespace, deployment.name, current, target, signals
                 );
 
                 let _ = crate::models::deployment_event::log_event(
@@ -1337,8 +1779,8 @@ async fn run_autoscaling(
                     deployment.id.clone(),
                     "info",
                     format!(
-                        "Autoscaled from {} to {} instances (average CPU {:.1}% against a {:.1}% target)",
-                        current, target, cpu.unwrap_or(f64::NAN), policy.target_cpu
+                        "Autoscaled from {} to {} instances (per instance: {})",
+                        current, target, signals
                     ),
                     "autoscaler",
                     Some(if target > current {
@@ -1358,6 +1800,61 @@ async fn run_autoscaling(
     autoscaler.retain_known(&live);
 }
 
+/// Why the quota of `deployment`'s namespace has no room for it at
+/// `replicas`, `None` when it has. A namespace whose usage cannot be read
+/// holds too: scaling blind could starve the other namespaces.
+async fn quota_refusal(
+    pool: &SqlitePool,
+    deployment: &Deployment,
+    replicas: u32,
+) -> Option<String> {
+    let quota = match crate::models::namespace::find_by_name(pool, &deployment.namespace).await {
+        Ok(namespace) => namespace?.quota?,
+        Err(e) => return Some(format!("failed to load the namespace quota: {}", e)),
+    };
+    let usage =
+        match crate::models::namespace::usage(pool, &deployment.namespace, Some(&deployment.name))
+            .await
+        {
+            Ok(usage) => usage,
+            Err(e) => return Some(format!("failed to compute the namespace usage: {}", e)),
+        };
+    let footprint = crate::models::namespace::Footprint::of(deployment).with_replicas(replicas);
+    // The deployment is already counted among the namespace's deployments.
+    let breaches: Vec<String> = quota
+        .check(&usage, &footprint)
+        .into_iter()
+        .filter(|breach| breach.field != "deployments")
+        .map(|breach| format!("namespace quota: {}", breach.message))
+        .collect();
+    (!breaches.is_empty()).then(|| breaches.join("; "))
+}
+
+/// What the autoscaler compares against `deployment`'s policy, per instance.
+/// Memory is a percentage of `requests.memory`, `None` without a request.
+fn observe(
+    deployment: &Deployment,
+    stats: &crate::scheduler::stats_cache::DeploymentRuntimeStats,
+) -> Observation {
+    let requested_memory = deployment
+        .resources
+        .as_ref()
+        .and_then(|r| r.requests.as_ref())
+        .and_then(|r| r.memory.as_deref())
+        .and_then(|m| crate::models::deployments::parse_memory_string(m).ok())
+        .filter(|bytes| *bytes > 0);
+    let memory_percent = requested_memory
+        .filter(|_| stats.instance_count > 0)
+        .map(|bytes| {
+            stats.memory_usage_bytes as f64 / stats.instance_count as f64 / bytes as f64 * 100.0
+        });
+    Observation {
+        cpu_percent: stats.cpu_usage_percent_per_instance,
+        memory_percent,
+        metrics: stats.metrics.clone(),
+    }
+}
+
 pub(crate) async fn schedule(
     pool: SqlitePool,
     config: crate::config::config::Config,
@@ -1419,11 +1916,16 @@ pub(crate) async fn schedule(
         )
         .await;
 
+        // Finished jobs are not reconciled below; the ones past their
+        // `ttl_after_finished` are marked deleted here so this very tick
+        // removes them.
+        job::expire_finished(&pool, chrono::Utc::now()).await;
+
         // The scheduler picks up every status that can still progress on the
         // next tick. Pending/Creating need their first apply, Running needs
         // reconciliation, Deleted needs cleanup, and the transient error
-        // states need to keep retrying until `restart_count` reaches
-        // `MAX_RESTART_COUNT` — at which point the runtime flips the
+        // states need to keep retrying until `restart_count` spends the
+        // failure budget — at which point the runtime flips the
         // deployment to `CrashLoopBackOff` and stops being included here.
         //
         // Statuses left out on purpose: Completed (terminal job), Failed,
@@ -1485,6 +1987,16 @@ pub(crate) async fn schedule(
         // it in place attaches the span without holding an `entered()` guard
         // across `.await` (which would make the loop future non-`Send`). An
         // idle tick uses `Span::none()`, so no span is recorded at all.
+        // Outgoing deployments of an ongoing rollout, keyed by their id. A
+        // failed child no longer holds its parent back, so it is left out.
+        let rollout_parents: std::collections::HashSet<String> = list_deployments
+            .iter()
+            .filter(|d| {
+                d.status != DeploymentStatus::Failed && d.status != DeploymentStatus::Deleted
+            })
+            .filter_map(|d| d.parent_id.clone())
+            .collect();
+
         async {
         for deployment in list_deployments.into_iter() {
             let runtime = match runtimes.get(&deployment.runtime) {
@@ -1526,6 +2038,9 @@ pub(crate) async fn schedule(
                 };
 
                 let old_status = deployment.status.clone();
+                if deployment.kind == "cronjob" {
+                    cronjob::delete_runs(&pool, &deployment).await;
+                }
                 persist_pending_events(&pool, &mut result).await;
                 handle_status_transitions(&pool, &mut result, &mut deleted).await;
 
@@ -1537,6 +2052,56 @@ pub(crate) async fn schedule(
                 continue;
             }
 
+            // A paused deployment is not reconciled at all: no apply (so no
+            // respawn and no scaling), no health-check action, no rollout or
+            // restart step. Its instances stay exactly as they are until it is
+            // resumed. Deletes are handled above, so a paused deployment can
+            // still be torn down.
+            if deployment.paused {
+                debug!(
+                    "Deployment {} is paused, skipping reconciliation",
+                    deployment.id
+                );
+                continue;
+            }
+
+            // A deployment created with `depends_on` waits in `pending` until
+            // its dependencies are met, then starts like a fresh one.
+            let deployment = if deployment.status == DeploymentStatus::Pending
+                && !deployment.depends_on.is_empty()
+            {
+                if dependencies::hold(&pool, &deployment).await {
+                    continue;
+                }
+                let old_status = deployment.status.clone();
+                let mut deployment = deployment;
+                deployment.status = DeploymentStatus::Creating;
+                if let Err(e) = deployments::update(&pool, &deployment).await {
+                    error!("Failed to update deployment {}: {}", deployment.id, e);
+                    continue;
+                }
+                publish_status_change(&pool, &old_status, &deployment).await;
+                deployment
+            } else {
+                deployment
+            };
+
+            // A cronjob has no container of its own: it only spawns its runs,
+            // which are reconciled as regular jobs.
+            if deployment.kind == "cronjob" {
+                let old_status = deployment.status.clone();
+                let mut deployment = deployment;
+                cronjob::reconcile(&pool, &mut deployment, chrono::Utc::now()).await;
+                if deployment.status != old_status {
+                    if let Err(e) = deployments::update(&pool, &deployment).await {
+                        error!("Failed to update cronjob {}: {}", deployment.id, e);
+                    } else {
+                        publish_status_change(&pool, &old_status, &deployment).await;
+                    }
+                }
+                continue;
+            }
+
             // Honour the retry backoff. (Deletes are handled above and never
             // reach this point, so they're never blocked by backoff.)
             if backoff.is_blocked(&deployment.id) {
@@ -1656,7 +2221,17 @@ pub(crate) async fn schedule(
                 }
             }
 
+            resolved.rollout_limit = rollout_limit(
+                &pool,
+                &deployment,
+                rollout_parents.contains(&deployment.id),
+                runtime.as_ref(),
+            )
+            .await;
+            resolved.restart_surge = resolved.rollout_limit.is_none() && restart_surges(&deployment);
+
             let restart_count_before = deployment.restart_count;
+            let exit_code_before = deployment.exit_code;
             let mut result = match apply_runtime(
                 &pool,
                 &deployment,
@@ -1691,6 +2266,17 @@ pub(crate) async fn schedule(
                 }
             }
 
+            // A job's exit code only changes when one of its attempts exits;
+            // written on its own, the full-row `update` does not carry it.
+            if result.exit_code != exit_code_before
+                && let Err(e) = deployments::set_exit_code(&pool, &result.id, result.exit_code).await
+            {
+                error!(
+                    "Failed to record the exit code of deployment {}: {}",
+                    result.id, e
+                );
+            }
+
             // Translate the runtime's outcome into a backoff decision.
             // A bumped restart_count means the runtime hit a transient
             // failure — arm the next retry. Otherwise (success, terminal
@@ -1700,7 +2286,8 @@ pub(crate) async fn schedule(
                 && result.status != DeploymentStatus::CrashLoopBackOff
                 && result.status != DeploymentStatus::Failed
             {
-                backoff.arm(&result.id, result.restart_count);
+                let (initial, max) = result.restart_backoff();
+                backoff.arm(&result.id, result.restart_count, initial, max);
             } else {
                 backoff.clear(&result.id);
             }
@@ -1736,6 +2323,7 @@ pub(crate) async fn schedule(
             .await;
             gate_running_on_readiness(&pool, &old_status, &mut result).await;
             handle_rolling_update(&pool, &mut result, &mut deleted, runtime.as_ref()).await;
+            handle_rolling_restart(&pool, &mut result, runtime.as_ref()).await;
 
             // Log the creating -> running transition only now that the status
             // for this cycle is settled (the gate above may have reverted it).
@@ -1754,7 +2342,7 @@ pub(crate) async fn schedule(
                     &result.id,
                     has_live_container(&result.status, result.instances.is_empty()),
                     result.restart_count,
-                    min_healthy_time_for(&result),
+                    healthy_window_for(&result),
                 )
             {
                 info!(
@@ -1858,6 +2446,24 @@ mod tests {
             pending_events: vec![],
             parent_id: Some("parent-id".to_string()),
             network: None,
+            defaulted: vec![],
+            idle: None,
+            logging: None,
+            restart_policy: None,
+            lifecycle: None,
+            init_containers: vec![],
+            sidecars: vec![],
+            depends_on: vec![],
+            job: None,
+            exit_code: None,
+            cron: None,
+            cronjob_id: None,
+            last_scheduled_at: None,
+            strategy: None,
+            rollout_limit: None,
+            restart_surge: false,
+            paused: false,
+            pending_restart: vec![],
         }
     }
 
@@ -1886,6 +2492,7 @@ mod tests {
             namespace: "test".to_string(),
             name: "DB_PASSWORD".to_string(),
             value: encrypt_value(raw),
+            labels: HashMap::new(),
         };
         create_secret(&pool, &secret).await.unwrap();
 
@@ -2405,4 +3012,459 @@ mod tests {
             assert!(!has_live_container(&status, true));
         }
     }
+
+    #[tokio::test]
+    async fn rolling_update_held_while_parent_is_paused() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        let mut parent = simple_running("parent-id", vec![]);
+        parent.paused = true;
+        deployments::create(&pool, &parent).await.unwrap();
+        let runtime = MockRuntime::healthy().with_instances(&["old-1"]);
+        let mut child = child_with_health_checks("child-id", vec![]);
+        let mut deleted = Vec::new();
+
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert!(runtime.removed().is_empty());
+        assert_eq!(child.parent_id.as_deref(), Some("parent-id"));
+
+        // Resuming the parent lets the drain go ahead.
+        deployments::set_paused(&pool, "parent-id", false)
+            .await
+            .unwrap();
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["old-1"]);
+        assert!(child.parent_id.is_none());
+    }
+
+    // ---- rollout strategies ----
+
+    #[test]
+    fn rolling_child_limit_stays_within_the_surge() {
+        // 3 old running, surge 1: one new instance at a time.
+        assert_eq!(rolling_child_limit(3, 1, 3, 0), 1);
+        // Surge 0: nothing new until an old instance is gone.
+        assert_eq!(rolling_child_limit(3, 0, 3, 0), 0);
+        assert_eq!(rolling_child_limit(3, 0, 2, 0), 1);
+        // Never scales back what is already up.
+        assert_eq!(rolling_child_limit(3, 1, 3, 2), 2);
+    }
+
+    #[test]
+    fn rolling_drain_count_respects_max_unavailable() {
+        // Default strategy: one old instance per ready new one.
+        assert_eq!(rolling_drain_count(3, 0, 0, 3), 0);
+        assert_eq!(rolling_drain_count(3, 0, 1, 3), 1);
+        // max_unavailable lets the old side drop before anything new is ready.
+        assert_eq!(rolling_drain_count(3, 1, 0, 3), 1);
+        assert_eq!(rolling_drain_count(4, 2, 2, 4), 4);
+        // Never more than the parent has.
+        assert_eq!(rolling_drain_count(1, 1, 5, 1), 1);
+    }
+
+    #[tokio::test]
+    async fn rollout_limit_caps_both_sides_of_a_rolling_update() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy()
+            .with_deployment_instances("parent-id", &["old-1", "old-2", "old-3"])
+            .with_deployment_instances("child-id", &[]);
+        let parent = simple_running("parent-id", vec![]);
+        assert_eq!(rollout_limit(&pool, &parent, true, &runtime).await, Some(3));
+        assert_eq!(rollout_limit(&pool, &parent, false, &runtime).await, None);
+
+        let mut child = child_with_health_checks("child-id", vec![]);
+        child.replicas = 3;
+        child.strategy = Some(Strategy::Rolling {
+            max_surge: 2,
+            max_unavailable: 0,
+        });
+        assert_eq!(rollout_limit(&pool, &child, false, &runtime).await, Some(2));
+
+        child.strategy = Some(Strategy::Recreate);
+        assert_eq!(rollout_limit(&pool, &child, false, &runtime).await, Some(0));
+    }
+
+    #[tokio::test]
+    async fn recreate_drains_the_whole_parent_without_waiting_for_the_child() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        deployments::create(&pool, &simple_running("parent-id", vec![]))
+            .await
+            .unwrap();
+        let runtime = MockRuntime::healthy()
+            .with_deployment_instances("parent-id", &["old-1", "old-2"])
+            .with_deployment_instances("child-id", &[]);
+        let mut child = child_with_health_checks("child-id", vec![]);
+        child.status = DeploymentStatus::Pending;
+        child.instances = vec![];
+        child.strategy = Some(Strategy::Recreate);
+        let mut deleted = Vec::new();
+
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+
+        assert_eq!(runtime.removed(), vec!["old-1", "old-2"]);
+        assert_eq!(deleted, vec!["parent-id".to_string()]);
+        assert!(child.parent_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn rolling_with_max_unavailable_drains_before_the_child_is_up() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        deployments::create(&pool, &simple_running("parent-id", vec![]))
+            .await
+            .unwrap();
+        let runtime = MockRuntime::healthy()
+            .with_deployment_instances("parent-id", &["old-1", "old-2"])
+            .with_deployment_instances("child-id", &[]);
+        let mut child = child_with_health_checks("child-id", vec![]);
+        child.replicas = 2;
+        child.status = DeploymentStatus::Pending;
+        child.instances = vec![];
+        child.strategy = Some(Strategy::Rolling {
+            max_surge: 0,
+            max_unavailable: 1,
+        });
+        let mut deleted = Vec::new();
+
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["old-1"]);
+        assert!(deleted.is_empty());
+
+        // Nothing more goes until the new instance is up and ready.
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["old-1"]);
+    }
+
+    fn canary_child() -> Deployment {
+        let mut child = child_with_health_checks("child-id", vec![]);
+        child.replicas = 3;
+        child.strategy = Some(Strategy::Canary {
+            instances: 1,
+            analysis_window: "60s".to_string(),
+        });
+        child
+    }
+
+    async fn insert_event_ago(pool: &SqlitePool, deployment_id: &str, reason: &str, secs_ago: i64) {
+        let mut event = deployment_event::DeploymentEvent::new(
+            deployment_id.to_string(),
+            "info",
+            reason.to_string(),
+            "test",
+            Some(reason),
+        );
+        event.timestamp = (chrono::Utc::now() - chrono::Duration::seconds(secs_ago)).to_rfc3339();
+        deployment_event::create_event(pool, &event).await.unwrap();
+    }
+
+    async fn event_reasons(pool: &SqlitePool, deployment_id: &str) -> Vec<String> {
+        sqlx::query_scalar(
+            "SELECT reason FROM deployment_event WHERE deployment_id = ? AND reason IS NOT NULL ORDER BY timestamp",
+        )
+        .bind(deployment_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn canary_holds_at_its_instances_until_promoted() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy()
+            .with_deployment_instances("parent-id", &["old-1", "old-2", "old-3"])
+            .with_deployment_instances("child-id", &["new-1"]);
+        let child = canary_child();
+        assert_eq!(rollout_limit(&pool, &child, false, &runtime).await, Some(1));
+
+        insert_event_ago(&pool, "child-id", canary::PROMOTED, 0).await;
+        // Promoted: the default rolling budget takes over.
+        assert_eq!(rollout_limit(&pool, &child, false, &runtime).await, Some(1));
+    }
+
+    #[tokio::test]
+    async fn canary_opens_its_window_then_promotes_without_draining_early() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        deployments::create(&pool, &simple_running("parent-id", vec![]))
+            .await
+            .unwrap();
+        let runtime = MockRuntime::healthy()
+            .with_deployment_instances("parent-id", &["old-1", "old-2", "old-3"])
+            .with_deployment_instances("child-id", &["new-1"]);
+        let mut child = canary_child();
+        let mut deleted = Vec::new();
+
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(
+            event_reasons(&pool, "child-id").await,
+            vec![canary::STARTED]
+        );
+        assert!(runtime.removed().is_empty());
+
+        // Still inside the 60s window: nothing happens.
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(event_reasons(&pool, "child-id").await.len(), 1);
+
+        // Window opened long enough ago: promoted on this tick, drained from
+        // the next one on.
+        sqlx::query("DELETE FROM deployment_event")
+            .execute(&pool)
+            .await
+            .unwrap();
+        insert_event_ago(&pool, "child-id", canary::STARTED, 120).await;
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert!(runtime.removed().is_empty());
+        assert!(
+            event_reasons(&pool, "child-id")
+                .await
+                .contains(&canary::PROMOTED.to_string())
+        );
+
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["old-1"]);
+    }
+
+    #[tokio::test]
+    async fn canary_aborts_on_a_runtime_error_during_analysis() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        deployments::create(&pool, &simple_running("parent-id", vec![]))
+            .await
+            .unwrap();
+        let runtime = MockRuntime::healthy()
+            .with_deployment_instances("parent-id", &["old-1"])
+            .with_deployment_instances("child-id", &["new-1"]);
+        let mut child = canary_child();
+        let mut deleted = Vec::new();
+
+        insert_event_ago(&pool, "child-id", canary::STARTED, 10).await;
+        insert_event_ago(&pool, "child-id", "instance_creation_failed", 5).await;
+
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(child.status, DeploymentStatus::Deleted);
+        assert!(runtime.removed().is_empty());
+        assert!(
+            event_reasons(&pool, "child-id")
+                .await
+                .contains(&canary::ABORTED.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn canary_aborts_on_a_failing_health_check_during_analysis() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        deployments::create(&pool, &simple_running("parent-id", vec![]))
+            .await
+            .unwrap();
+        let runtime = MockRuntime::healthy()
+            .with_deployment_instances("parent-id", &["old-1"])
+            .with_deployment_instances("child-id", &["new-1"]);
+        let mut child = canary_child();
+        let mut deleted = Vec::new();
+
+        insert_event_ago(&pool, "child-id", canary::STARTED, 10).await;
+        insert_hc_result(&pool, "child-id", "http", "failed", 2).await;
+
+        handle_rolling_update(&pool, &mut child, &mut deleted, &runtime).await;
+        assert_eq!(child.status, DeploymentStatus::Deleted);
+    }
+
+    // ---- handle_rolling_restart ----
+
+    /// A running deployment with a restart pending on `instances`, persisted so
+    /// the progress written by the scheduler can be read back.
+    async fn restarting(pool: &SqlitePool, id: &str, instances: &[&str]) -> Deployment {
+        let mut d = simple_running(id, vec![]);
+        d.replicas = instances.len() as u32;
+        d.pending_restart = instances.iter().map(|i| i.to_string()).collect();
+        deployments::create(pool, &d).await.unwrap();
+        d
+    }
+
+    /// Push the last restart step out of the anti-flap window.
+    async fn age_restart_steps(pool: &SqlitePool, deployment_id: &str) {
+        let ts = (chrono::Utc::now() - chrono::Duration::seconds(60)).to_rfc3339();
+        sqlx::query("UPDATE deployment_event SET timestamp = ? WHERE deployment_id = ?")
+            .bind(ts)
+            .bind(deployment_id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_replaces_one_instance_at_a_time() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy().with_instances(&["a", "b"]);
+        let mut d = restarting(&pool, "restart-1", &["a", "b"]).await;
+
+        // The first replacement is not up yet: nothing is removed.
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert!(runtime.removed().is_empty());
+
+        runtime.add_instance("c");
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["a"]);
+        assert_eq!(d.pending_restart, vec!["b"]);
+        let stored = deployments::find(&pool, "restart-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.pending_restart, vec!["b"]);
+
+        // Back at full count only: the next replacement is not up, hold.
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["a"]);
+
+        // Up, but it has not settled yet.
+        runtime.add_instance("d");
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["a"]);
+
+        age_restart_steps(&pool, "restart-1").await;
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["a", "b"]);
+        assert!(d.pending_restart.is_empty());
+        let stored = deployments::find(&pool, "restart-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.pending_restart.is_empty());
+        assert!(
+            deployment_event::find_latest_by_reason(&pool, "restart-1", "restart_complete")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_of_a_single_replica_starts_the_replacement_first() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy().with_instances(&["a"]);
+        let mut d = restarting(&pool, "restart-5", &["a"]).await;
+        deployment_event::log_event(
+            &pool,
+            "restart-5".to_string(),
+            "info",
+            "Rolling restart requested".to_string(),
+            "api",
+            Some("restart_requested"),
+        )
+        .await
+        .unwrap();
+
+        // The runtime is asked for the replacement next to `a`.
+        assert!(restart_surges(&d));
+        d.restart_surge = true;
+        assert_eq!(d.target_replicas(), 2);
+
+        // `a` is the only instance: it stays until the replacement is up...
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert!(runtime.removed().is_empty());
+
+        // ...and has had `min_healthy_time` since the request.
+        runtime.add_instance("b");
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert!(runtime.removed().is_empty());
+
+        age_restart_steps(&pool, "restart-5").await;
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["a"]);
+        assert!(d.pending_restart.is_empty());
+        assert!(!restart_surges(&d));
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_on_a_host_port_removes_before_replacing() {
+        use crate::hypervisor::mock::MockRuntime;
+        use crate::models::deployments::{DeploymentPort, PortProtocol};
+
+        // A second instance would collide with `a` on port 8080: `a` goes
+        // first, and the runtime brings up its replacement on the next tick.
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy().with_instances(&["a"]);
+        let mut d = simple_running("restart-6", vec![]);
+        d.ports = vec![DeploymentPort {
+            published: 8080,
+            target: 80,
+            host_ip: None,
+            protocol: PortProtocol::default(),
+        }];
+        d.pending_restart = vec!["a".to_string()];
+        deployments::create(&pool, &d).await.unwrap();
+
+        assert!(restart_in_progress(&d));
+        assert!(!restart_surges(&d));
+
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert_eq!(runtime.removed(), vec!["a"]);
+        assert!(d.pending_restart.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_waits_for_readiness() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy().with_instances(&["a", "b"]);
+        let mut d = restarting(&pool, "restart-2", &["a", "b"]).await;
+        d.health_checks = vec![readiness_command("ready")];
+        insert_hc_result(&pool, "restart-2", "command", "failed", 1).await;
+
+        // The replacement is up, but not ready.
+        runtime.add_instance("c");
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert!(runtime.removed().is_empty());
+        assert_eq!(d.pending_restart, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_held_during_a_rolling_update() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy().with_instances(&["a"]);
+        let mut d = restarting(&pool, "restart-3", &["a"]).await;
+        d.parent_id = Some("parent-id".to_string());
+
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert!(runtime.removed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rolling_restart_forgets_instances_that_are_already_gone() {
+        use crate::hypervisor::mock::MockRuntime;
+
+        // `a` crashed and was replaced by `c` on its own: it counts as done,
+        // and with nothing else pending the restart completes.
+        let pool = new_test_pool().await;
+        let runtime = MockRuntime::healthy().with_instances(&["c"]);
+        let mut d = restarting(&pool, "restart-4", &["a"]).await;
+
+        handle_rolling_restart(&pool, &mut d, &runtime).await;
+        assert!(runtime.removed().is_empty());
+        assert!(d.pending_restart.is_empty());
+        let stored = deployments::find(&pool, "restart-4")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.pending_restart.is_empty());
+    }
 }
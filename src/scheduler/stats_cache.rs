@@ -19,6 +19,7 @@
 use crate::api::dto::stats::InstanceStatsOutput;
 use crate::api::server::RuntimeMap;
 use crate::models::deployments;
+use crate::scheduler::metric_scraper;
 use sqlx::SqlitePool;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
@@ -70,6 +71,10 @@ pub(crate) struct DeploymentRuntimeStats {
     pub disk_write_bytes: u64,
     pub pids: u64,
     pub restarts: u64,
+    /// Per-instance value of each of the deployment's `autoscale.metrics`, in
+    /// order (see [`super::metric_scraper`]). Empty without such metrics.
+    /// Not exported to Prometheus: the workload already serves them.
+    pub metrics: Vec<Option<f64>>,
 }
 
 /// In-memory snapshot read by `/metrics`. Replaced wholesale on each refresh so
@@ -158,13 +163,22 @@ pub(crate) async fn refresh(
             continue;
         }
 
-        out.push(aggregate(
+        let mut aggregated = aggregate(
             &deployment.id,
             &deployment.name,
             &deployment.namespace,
             &deployment.runtime,
             &stats,
-        ));
+        );
+        if let Some(policy) = deployment
+            .autoscale
+            .as_ref()
+            .filter(|p| !p.metrics.is_empty())
+        {
+            aggregated.metrics =
+                metric_scraper::scrape(runtime.as_ref(), &deployment.id, &policy.metrics).await;
+        }
+        out.push(aggregated);
     }
 
     match cache.write() {
@@ -208,6 +222,7 @@ fn aggregate(
         disk_write_bytes: instances.iter().map(|i| i.disk_io.write_bytes).sum(),
         pids: instances.iter().map(|i| i.pids.current).sum(),
         restarts: instances.iter().map(|i| i.restart_count).sum(),
+        metrics: Vec::new(),
     }
 }
 
@@ -67,6 +67,7 @@ const PAGE_ORDER: Record<string, string[]> = {
     'configure-health-checks',
     'perform-rolling-update',
     'run-a-job',
+    'migrate-from-docker-compose',
     // Networking & traffic
     'isolate-namespaces-network',
     'expose-http-traffic',